@@ -28,10 +28,11 @@ fn main() -> TdfResult<()> {
                     println!("  H{}: {}", level, text);
                 }
                 tdf_core::content::ContentBlock::Paragraph { text, .. } => {
+                    let text = text.plain_text();
                     let preview = if text.len() > 50 {
                         format!("{}...", &text[..47])
                     } else {
-                        text.clone()
+                        text.to_string()
                     };
                     println!("  Paragraph: {}", preview);
                 }
@@ -53,9 +54,11 @@ fn main() -> TdfResult<()> {
                                 let value = match cell {
                                     tdf_core::content::CellValue::Text(s) => s.clone(),
                                     tdf_core::content::CellValue::Number { display, .. } => display.clone(),
+                                    tdf_core::content::CellValue::Formula { display, .. } => display.clone(),
                                     tdf_core::content::CellValue::Currency { display, .. } => display.clone(),
                                     tdf_core::content::CellValue::Percentage { display, .. } => display.clone(),
                                     tdf_core::content::CellValue::Date { display, .. } => display.clone(),
+                                    tdf_core::content::CellValue::Redacted { .. } => "[redacted]".to_string(),
                                 };
                                 let truncated = if value.len() > 13 {
                                     format!("{}...", &value[..10])
@@ -75,7 +78,7 @@ fn main() -> TdfResult<()> {
                     let list_type = if *ordered { "Ordered" } else { "Unordered" };
                     println!("  {} List: {} items", list_type, items.len());
                     for item in items.iter().take(3) {
-                        println!("    - {}", item);
+                        println!("    - {}", item.text);
                     }
                 }
                 _ => {
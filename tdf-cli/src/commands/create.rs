@@ -2,11 +2,70 @@ use crate::utils;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tdf_core::archive::ArchiveBuilder;
+use tdf_core::certificate::CertificateChain;
 use tdf_core::content::{ContentBlock, DocumentContent, Section};
 use tdf_core::document::Document;
 use tdf_core::error::{TdfError, TdfResult};
 use tdf_core::merkle::HashAlgorithm;
-use tdf_core::timestamp::ManualTimestampProvider;
+use tdf_core::pkcs11::{Pkcs11Config, Pkcs11Signer};
+use tdf_core::remote_signer::RemoteSigner;
+use tdf_core::signature::DocumentSigner;
+use tdf_core::timestamp::{ManualTimestampProvider, TimestampProvider};
+use zeroize::Zeroizing;
+
+/// Environment variable holding the remote signer bearer token
+pub const SIGNER_TOKEN_ENV: &str = "TDF_SIGNER_TOKEN";
+/// Environment variable holding the PKCS#11 user PIN
+pub const PKCS11_PIN_ENV: &str = "TDF_PKCS11_PIN";
+
+/// Signer certificate, external signer selection and signing mode
+///
+/// With an external signer the private key stays outside this process.
+#[derive(Debug, Default)]
+pub struct SignerOptions {
+    pub cert: Option<PathBuf>,
+    pub remote_signer: Option<String>,
+    pub pkcs11_module: Option<PathBuf>,
+    pub pkcs11_token: Option<String>,
+    pub pkcs11_key: Option<String>,
+    /// Sign per-block commitments so content can be redacted afterwards
+    pub redactable: bool,
+}
+
+impl SignerOptions {
+    /// Open the configured external signer, if any
+    pub fn open(&self) -> TdfResult<Option<Box<dyn DocumentSigner>>> {
+        match (&self.remote_signer, &self.pkcs11_module) {
+            (Some(_), Some(_)) => Err(TdfError::InvalidDocument(
+                "Use either --remote-signer or --pkcs11-module, not both".to_string(),
+            )),
+            (Some(url), None) => {
+                let mut signer = RemoteSigner::new(url.clone());
+                if let Ok(token) = std::env::var(SIGNER_TOKEN_ENV) {
+                    signer = signer.with_bearer_token(token);
+                }
+                Ok(Some(Box::new(signer)))
+            }
+            (None, Some(module)) => {
+                let token_label = self.pkcs11_token.clone().ok_or_else(|| {
+                    TdfError::InvalidDocument("--pkcs11-token is required with --pkcs11-module".to_string())
+                })?;
+                let key_label = self.pkcs11_key.clone().ok_or_else(|| {
+                    TdfError::InvalidDocument("--pkcs11-key is required with --pkcs11-module".to_string())
+                })?;
+                let pin = std::env::var(PKCS11_PIN_ENV).map_err(|_| {
+                    TdfError::InvalidDocument(format!("Set {} to the token user PIN", PKCS11_PIN_ENV))
+                })?;
+                let signer = Pkcs11Signer::open(
+                    module,
+                    Pkcs11Config { token_label, key_label, pin: Zeroizing::new(pin) },
+                )?;
+                Ok(Some(Box::new(signer)))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+}
 
 pub fn create_document(
     input: PathBuf,
@@ -15,9 +74,26 @@ pub fn create_document(
     signer_name: Option<String>,
     key: Option<PathBuf>,
     timestamp_manual: bool,
+    signer_options: SignerOptions,
 ) -> TdfResult<()> {
+    let document = document_from_json(&input)?;
+    let output_path = output.unwrap_or_else(|| default_output(&input));
+    write_document(document, &output_path, signer_id, signer_name, key, timestamp_manual, signer_options)
+}
+
+/// Output path next to the working directory, named after the JSON input
+pub(crate) fn default_output(input: &Path) -> PathBuf {
+    input
+        .with_extension("tdf")
+        .file_name()
+        .map(|n| PathBuf::from(n))
+        .unwrap_or_else(|| PathBuf::from("output.tdf"))
+}
+
+/// Parse a JSON document description (title, language, styles, layout, xbrl, sections)
+pub(crate) fn document_from_json(input: &Path) -> TdfResult<Document> {
     // Read input JSON
-    let json_str = fs::read_to_string(&input)?;
+    let json_str = fs::read_to_string(input)?;
     let json_value: serde_json::Value = serde_json::from_str(&json_str)?;
 
     // Parse document structure
@@ -62,7 +138,7 @@ pub fn create_document(
                 })
                 .collect();
 
-            Section { id, title, content }
+            Section { id, title, content, encrypted: None }
         })
         .collect();
 
@@ -70,31 +146,61 @@ pub fn create_document(
 
     // Create document
     let mut document = Document::new(title, language, content, styles);
+    document.layout = json_value
+        .get("layout")
+        .map(|v| serde_json::from_value(v.clone()))
+        .transpose()?;
+    document.manifest.xbrl = json_value
+        .get("xbrl")
+        .map(|v| serde_json::from_value(v.clone()))
+        .transpose()?;
     document.manifest.integrity.algorithm = tdf_core::document::HashAlgorithm::Sha256;
+    Ok(document)
+}
 
+/// Build the archive, signing it with a key file or external signer if given
+pub(crate) fn write_document(
+    document: Document,
+    output_path: &Path,
+    signer_id: Option<String>,
+    signer_name: Option<String>,
+    key: Option<PathBuf>,
+    timestamp_manual: bool,
+    signer_options: SignerOptions,
+) -> TdfResult<()> {
     // Load signing key if provided
-    let signing_key = if let Some(key_path) = key {
-        Some(utils::load_signing_key(&key_path)?)
+    let signing_key = if let Some(key_path) = &key {
+        Some(utils::load_signing_key(key_path)?)
     } else {
         None
     };
 
-    // Determine output path
-    let output_path = output.unwrap_or_else(|| {
-        input
-            .with_extension("tdf")
-            .file_name()
-            .map(|n| PathBuf::from(n))
-            .unwrap_or_else(|| PathBuf::from("output.tdf"))
-    });
-
     // Build archive with optional timestamp provider
     let mut builder = ArchiveBuilder::new(document);
+    if signer_options.redactable {
+        builder = builder.with_redaction();
+    }
+    if let Some(cert_path) = &signer_options.cert {
+        builder = builder.with_signer_certificate(CertificateChain::from_file(cert_path)?);
+    }
 
-    if timestamp_manual {
+    if let Some(signer) = signer_options.open()? {
+        if key.is_some() {
+            return Err(TdfError::InvalidDocument(
+                "--key cannot be combined with an external signer".to_string(),
+            ));
+        }
+        let (signer_id, signer_name) = signer_id.zip(signer_name).ok_or_else(|| {
+            TdfError::InvalidDocument("--signer-id and --signer-name are required for signing".to_string())
+        })?;
+        let manual = ManualTimestampProvider;
+        let timestamp_provider: Option<&dyn TimestampProvider> = if timestamp_manual { Some(&manual) } else { None };
+        builder.build_with_signer(output_path, signer.as_ref(), signer_id, signer_name, timestamp_provider)?;
+        println!("Created TDF document (external signer): {}", output_path.display());
+    } else if timestamp_manual {
         let timestamp_provider = ManualTimestampProvider;
         builder.build_with_timestamp(
-            &output_path,
+            output_path,
             signing_key.as_ref(),
             None, // secp256k1 key
             signer_id,
@@ -105,7 +211,7 @@ pub fn create_document(
         println!("Created TDF document with manual timestamp: {}", output_path.display());
     } else {
         builder.build(
-            &output_path,
+            output_path,
             signing_key.as_ref(),
             signer_id,
             signer_name,
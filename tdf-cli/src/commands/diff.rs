@@ -0,0 +1,319 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use tdf_core::archive::ArchiveReader;
+use tdf_core::diff::{diff_archives, BlockDiff, ChangeKind, DocumentDiff, TableChange, TextChange, TextOp};
+use tdf_core::error::{TdfError, TdfResult};
+
+/// Output format of `tdf diff`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    Text,
+    Json,
+    Html,
+}
+
+impl std::str::FromStr for DiffFormat {
+    type Err = TdfError;
+
+    fn from_str(s: &str) -> TdfResult<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(DiffFormat::Text),
+            "json" => Ok(DiffFormat::Json),
+            "html" => Ok(DiffFormat::Html),
+            other => Err(TdfError::InvalidDocument(format!(
+                "Unknown diff format '{}' (expected text, json or html)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Show what changed between two TDF documents
+///
+/// Both documents are verified first; a failed integrity check is reported
+/// as a warning, since the diff is then not of what was signed.
+pub fn diff_documents(old: PathBuf, new: PathBuf, format: DiffFormat, output: Option<PathBuf>) -> TdfResult<()> {
+    for path in [&old, &new] {
+        if !ArchiveReader::verify(path)?.integrity_valid {
+            eprintln!("Warning: {} failed integrity verification", path.display());
+        }
+    }
+    let diff = diff_archives(&old, &new)?;
+    let (old_name, new_name) = (old.display().to_string(), new.display().to_string());
+
+    let rendered = match format {
+        DiffFormat::Text => render_text(&diff, &old_name, &new_name),
+        DiffFormat::Json => serde_json::to_string_pretty(&diff)? + "\n",
+        DiffFormat::Html => render_html(&diff, &old_name, &new_name),
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            println!("Wrote diff to: {}", path.display());
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+fn symbol(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Added => "+",
+        ChangeKind::Removed => "-",
+        ChangeKind::Modified => "~",
+        ChangeKind::Moved => ">",
+    }
+}
+
+fn kind_name(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Added => "added",
+        ChangeKind::Removed => "removed",
+        ChangeKind::Modified => "modified",
+        ChangeKind::Moved => "moved",
+    }
+}
+
+fn value(v: &Option<String>) -> &str {
+    v.as_deref().unwrap_or("(none)")
+}
+
+/// Short description of where a block moved
+fn block_summary(block: &BlockDiff) -> String {
+    let mut summary = format!("{} {} ({})", symbol(block.kind), block.key, block.block_type);
+    if let (ChangeKind::Moved, Some(from), Some(to)) = (block.kind, &block.old, &block.new) {
+        let _ = write!(summary, " moved from {}[{}] to {}[{}]", from.section, from.index, to.section, to.index);
+    }
+    if !block.fields.is_empty() {
+        let _ = write!(summary, " [{}]", block.fields.join(", "));
+    }
+    summary
+}
+
+/// Inline text diff: deletions as [-...-], insertions as {+...+}
+fn inline_text(changes: &[TextChange]) -> String {
+    changes
+        .iter()
+        .map(|c| match c.op {
+            TextOp::Equal => c.text.clone(),
+            TextOp::Delete => format!("[-{}-]", c.text),
+            TextOp::Insert => format!("{{+{}+}}", c.text),
+        })
+        .collect()
+}
+
+fn table_change(change: &TableChange) -> String {
+    match change {
+        TableChange::RowAdded { row } => format!("+ row {}", row),
+        TableChange::RowRemoved { row } => format!("- row {}", row),
+        TableChange::Cell { row, column, old, new } => {
+            format!("~ row {} {}: {} -> {}", row, column, value(old), value(new))
+        }
+    }
+}
+
+fn render_text(diff: &DocumentDiff, old: &str, new: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "TDF Diff");
+    let _ = writeln!(out, "========");
+    let _ = writeln!(out, "Old: {}", old);
+    let _ = writeln!(out, "New: {}", new);
+
+    if diff.is_empty() {
+        let _ = writeln!(out, "\nNo differences.");
+        return out;
+    }
+
+    if !diff.manifest.is_empty() {
+        let _ = writeln!(out, "\nMANIFEST:");
+        for change in &diff.manifest {
+            let _ = writeln!(out, "  ~ {}: {} -> {}", change.field, value(&change.old), value(&change.new));
+        }
+    }
+
+    if !diff.sections.is_empty() {
+        let _ = writeln!(out, "\nCONTENT:");
+        for section in &diff.sections {
+            let _ = write!(out, "  {} section {}", symbol(section.kind), section.id);
+            if section.kind == ChangeKind::Moved {
+                let _ = write!(out, " (position {} -> {})", section.old_index.unwrap_or(0), section.new_index.unwrap_or(0));
+            }
+            let _ = writeln!(out);
+            if let Some(ref title) = section.title {
+                let _ = writeln!(out, "      title: {} -> {}", value(&title.old), value(&title.new));
+            }
+            for block in &section.blocks {
+                let _ = writeln!(out, "    {}", block_summary(block));
+                if !block.text.is_empty() {
+                    for line in inline_text(&block.text).lines() {
+                        let _ = writeln!(out, "        {}", line);
+                    }
+                }
+                for change in &block.table {
+                    let _ = writeln!(out, "        {}", table_change(change));
+                }
+            }
+        }
+    }
+
+    if !diff.styles.is_empty() {
+        let _ = writeln!(out, "\nSTYLES:");
+        for change in diff.styles.iter().filter(|c| c.op != TextOp::Equal) {
+            let prefix = if change.op == TextOp::Insert { "+" } else { "-" };
+            for line in change.text.lines() {
+                let _ = writeln!(out, "  {} {}", prefix, line);
+            }
+        }
+    }
+
+    if !diff.assets.is_empty() {
+        let _ = writeln!(out, "\nASSETS:");
+        for asset in &diff.assets {
+            let _ = writeln!(out, "  {} {} ({})", symbol(asset.kind), asset.path, kind_name(asset.kind));
+        }
+    }
+
+    if !diff.signatures.is_empty() {
+        let _ = writeln!(out, "\nSIGNATURES:");
+        for sig in &diff.signatures {
+            let _ = writeln!(out, "  {} {} ({}) at {}", symbol(sig.kind), sig.signer_name, sig.signer_id, sig.time);
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_text(changes: &[TextChange]) -> String {
+    changes
+        .iter()
+        .map(|c| match c.op {
+            TextOp::Equal => escape(&c.text),
+            TextOp::Delete => format!("<del>{}</del>", escape(&c.text)),
+            TextOp::Insert => format!("<ins>{}</ins>", escape(&c.text)),
+        })
+        .collect()
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:960px;margin:2rem auto;color:#222}\
+h1{font-size:1.4rem}h2{font-size:1.1rem;border-bottom:1px solid #ddd;padding-bottom:.25rem;margin-top:2rem}\
+table{border-collapse:collapse;width:100%}td,th{border:1px solid #ddd;padding:.3rem .5rem;text-align:left;vertical-align:top}\
+del{background:#fdd;text-decoration:line-through}ins{background:#dfd;text-decoration:none}\
+.added{color:#1a7f37}.removed{color:#cf222e}.modified{color:#9a6700}.moved{color:#0550ae}\
+.block{margin:.5rem 0 .5rem 1rem}.text{white-space:pre-wrap;margin:.25rem 0 .25rem 1rem;font-family:ui-monospace,monospace}";
+
+fn render_html(diff: &DocumentDiff, old: &str, new: &str) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>TDF Diff</title>\n<style>{}</style>\n</head>\n<body>\n",
+        HTML_STYLE
+    );
+    let _ = writeln!(out, "<h1>TDF Diff</h1>\n<p>Old: <code>{}</code><br>New: <code>{}</code></p>", escape(old), escape(new));
+
+    if diff.is_empty() {
+        let _ = writeln!(out, "<p>No differences.</p>");
+    }
+
+    if !diff.manifest.is_empty() {
+        let _ = writeln!(out, "<h2>Manifest</h2>\n<table>\n<tr><th>Field</th><th>Old</th><th>New</th></tr>");
+        for change in &diff.manifest {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td><del>{}</del></td><td><ins>{}</ins></td></tr>",
+                escape(&change.field),
+                escape(value(&change.old)),
+                escape(value(&change.new))
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+
+    if !diff.sections.is_empty() {
+        let _ = writeln!(out, "<h2>Content</h2>");
+        for section in &diff.sections {
+            let kind = kind_name(section.kind);
+            let _ = writeln!(out, "<h3 class=\"{}\">Section {} ({})</h3>", kind, escape(&section.id), kind);
+            if let Some(ref title) = section.title {
+                let _ = writeln!(
+                    out,
+                    "<p>Title: <del>{}</del> <ins>{}</ins></p>",
+                    escape(value(&title.old)),
+                    escape(value(&title.new))
+                );
+            }
+            for block in &section.blocks {
+                let _ = writeln!(
+                    out,
+                    "<div class=\"block\"><span class=\"{}\">{}</span>",
+                    kind_name(block.kind),
+                    escape(&block_summary(block))
+                );
+                if !block.text.is_empty() {
+                    let _ = writeln!(out, "<div class=\"text\">{}</div>", html_text(&block.text));
+                }
+                if !block.table.is_empty() {
+                    let _ = writeln!(out, "<table>\n<tr><th>Row</th><th>Column</th><th>Old</th><th>New</th></tr>");
+                    for change in &block.table {
+                        let _ = match change {
+                            TableChange::RowAdded { row } => {
+                                writeln!(out, "<tr class=\"added\"><td>{}</td><td colspan=\"3\">row added</td></tr>", row)
+                            }
+                            TableChange::RowRemoved { row } => {
+                                writeln!(out, "<tr class=\"removed\"><td>{}</td><td colspan=\"3\">row removed</td></tr>", row)
+                            }
+                            TableChange::Cell { row, column, old, new } => writeln!(
+                                out,
+                                "<tr><td>{}</td><td>{}</td><td><del>{}</del></td><td><ins>{}</ins></td></tr>",
+                                row,
+                                escape(column),
+                                escape(value(old)),
+                                escape(value(new))
+                            ),
+                        };
+                    }
+                    let _ = writeln!(out, "</table>");
+                }
+                let _ = writeln!(out, "</div>");
+            }
+        }
+    }
+
+    if !diff.styles.is_empty() {
+        let _ = writeln!(out, "<h2>Styles</h2>\n<div class=\"text\">{}</div>", html_text(&diff.styles));
+    }
+
+    if !diff.assets.is_empty() {
+        let _ = writeln!(out, "<h2>Assets</h2>\n<ul>");
+        for asset in &diff.assets {
+            let kind = kind_name(asset.kind);
+            let _ = writeln!(out, "<li class=\"{}\">{} ({})</li>", kind, escape(&asset.path), kind);
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    if !diff.signatures.is_empty() {
+        let _ = writeln!(out, "<h2>Signatures</h2>\n<ul>");
+        for sig in &diff.signatures {
+            let kind = kind_name(sig.kind);
+            let _ = writeln!(
+                out,
+                "<li class=\"{}\">{} ({}) at {}: {}</li>",
+                kind,
+                escape(&sig.signer_name),
+                escape(&sig.signer_id),
+                escape(&sig.time),
+                kind
+            );
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
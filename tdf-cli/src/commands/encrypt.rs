@@ -0,0 +1,206 @@
+use crate::utils;
+use std::path::{Path, PathBuf};
+use tdf_core::archive::{ArchiveBuilder, ArchiveReader};
+use tdf_core::encryption::{
+    decrypt_sections, ContentCipher, EncryptionConfig, Identity, Recipient, SectionAccess, SectionEncryptionConfig,
+};
+use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::key_file::Kdf;
+use tdf_core::key_format::decode_public_key;
+use zeroize::Zeroizing;
+
+/// Environment variable holding the document passphrase (prompted otherwise)
+pub const DOCUMENT_PASSPHRASE_ENV: &str = "TDF_DOCUMENT_PASSPHRASE";
+
+/// Options for re-signing the rewritten archive
+pub struct ResignOptions {
+    pub key: Option<PathBuf>,
+    pub signer_id: Option<String>,
+    pub signer_name: Option<String>,
+}
+
+/// Read the document passphrase
+///
+/// Kept separate from the key file passphrase, since encrypting with a
+/// passphrase and re-signing with an encrypted key can need both.
+fn read_document_passphrase(confirm: bool) -> TdfResult<Zeroizing<String>> {
+    let passphrase = match std::env::var(DOCUMENT_PASSPHRASE_ENV) {
+        Ok(value) => Zeroizing::new(value),
+        Err(_) => {
+            let first = Zeroizing::new(rpassword::prompt_password("Document passphrase: ")?);
+            if confirm {
+                let second = Zeroizing::new(rpassword::prompt_password("Confirm document passphrase: ")?);
+                if first != second {
+                    return Err(TdfError::InvalidDocument("Passphrases do not match".to_string()));
+                }
+            }
+            first
+        }
+    };
+    if passphrase.is_empty() {
+        return Err(TdfError::InvalidDocument("Passphrase must not be empty".to_string()));
+    }
+    Ok(passphrase)
+}
+
+/// Asset path to pass back to `ArchiveBuilder::add_asset` for an archive entry name
+fn builder_asset_path(name: &str) -> String {
+    if name.starts_with("assets/images/") || name.starts_with("assets/fonts/") {
+        name.to_string()
+    } else {
+        name.strip_prefix("assets/").unwrap_or(name).to_string()
+    }
+}
+
+fn default_output(input: &Path, suffix: &str) -> PathBuf {
+    let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("document");
+    input.with_file_name(format!("{}.{}.tdf", stem, suffix))
+}
+
+/// Write the document (and its assets) to a new archive, signing it if a key is given
+fn write_archive(
+    mut builder: ArchiveBuilder,
+    assets: impl IntoIterator<Item = (String, Vec<u8>)>,
+    output: &Path,
+    resign: ResignOptions,
+    was_signed: bool,
+) -> TdfResult<()> {
+    for (name, data) in assets {
+        builder.add_asset(builder_asset_path(&name), data)?;
+    }
+
+    let signing_key = match &resign.key {
+        Some(path) => Some(utils::load_signing_key(path)?),
+        None => None,
+    };
+    builder.build(output, signing_key.as_ref(), resign.signer_id, resign.signer_name)?;
+
+    if signing_key.is_none() && was_signed {
+        eprintln!("Warning: the original signatures cover the previous archive and were not carried over.");
+        eprintln!("         Use --key to sign {}", output.display());
+    }
+    Ok(())
+}
+
+/// Who the document (or its sections) is encrypted for
+pub struct RecipientOptions {
+    /// Ed25519 public key files
+    pub keys: Vec<PathBuf>,
+    /// Also encrypt to a passphrase
+    pub passphrase: bool,
+    pub kdf: Kdf,
+}
+
+/// Section-level encryption: only these sections, for one audience group
+pub struct SectionOptions {
+    pub ids: Vec<String>,
+    pub group: String,
+}
+
+fn load_recipients(options: &RecipientOptions) -> TdfResult<Vec<Recipient>> {
+    if options.keys.is_empty() && !options.passphrase {
+        return Err(TdfError::InvalidDocument(
+            "At least one --recipient or --passphrase is required".to_string(),
+        ));
+    }
+    let mut recipients = Vec::new();
+    for path in &options.keys {
+        let key = decode_public_key(&std::fs::read(path)?)?;
+        recipients.push(Recipient::from_public_key(&key)?);
+    }
+    if options.passphrase {
+        recipients.push(Recipient::passphrase(&read_document_passphrase(true)?, options.kdf)?);
+    }
+    Ok(recipients)
+}
+
+/// Encrypt a TDF document, or some of its sections, for Ed25519 key holders and/or a passphrase
+///
+/// The payload is encrypted with a random content key that is wrapped for
+/// each recipient. With `--section`, only the listed sections are encrypted
+/// for the group; run again with another group to give other sections a
+/// different audience. Because the Merkle tree covers the ciphertext, the
+/// output must be re-signed (`--key`) to carry a signature.
+pub fn encrypt_document(
+    document: PathBuf,
+    output: Option<PathBuf>,
+    recipients: RecipientOptions,
+    sections: SectionOptions,
+    cipher: ContentCipher,
+    resign: ResignOptions,
+) -> TdfResult<()> {
+    let (manifest, _, sig_block) = ArchiveReader::read_envelope(&document)?;
+    if manifest.encryption.is_some() {
+        return Err(TdfError::InvalidDocument(format!("{} is already encrypted", document.display())));
+    }
+    let (doc, _, _) = ArchiveReader::read(&document)?;
+    let assets = ArchiveReader::read_assets(&document, None)?;
+    let recipient_list = load_recipients(&recipients)?;
+
+    let builder = if sections.ids.is_empty() {
+        let mut config = EncryptionConfig::new(cipher);
+        config.recipients = recipient_list;
+        ArchiveBuilder::new(doc).with_encryption(config)
+    } else {
+        let config = sections.ids.iter().fold(
+            SectionEncryptionConfig::new(cipher).with_group(&sections.group, recipient_list),
+            |config, id| config.with_section(id, &sections.group),
+        );
+        ArchiveBuilder::new(doc).with_section_encryption(config)
+    };
+
+    let output_path = output.unwrap_or_else(|| default_output(&document, "encrypted"));
+    write_archive(builder, assets, &output_path, resign, !sig_block.signatures.is_empty())?;
+
+    println!("Encrypted TDF document: {}", output_path.display());
+    println!("  Cipher: {:?}", cipher);
+    if !sections.ids.is_empty() {
+        println!("  Sections: {} (group: {})", sections.ids.join(", "), sections.group);
+    }
+    println!(
+        "  Recipients: {} key(s){}",
+        recipients.keys.len(),
+        if recipients.passphrase { " + passphrase" } else { "" }
+    );
+    Ok(())
+}
+
+/// Decrypt an encrypted TDF document into a plain archive
+///
+/// Sections encrypted for groups the identity is not in stay encrypted.
+///
+/// # Arguments
+/// * `identity` - Recipient private key (Ed25519); a passphrase is used when omitted
+pub fn decrypt_document(
+    document: PathBuf,
+    output: Option<PathBuf>,
+    identity: Option<PathBuf>,
+    resign: ResignOptions,
+) -> TdfResult<()> {
+    let identity = match &identity {
+        Some(path) => Identity::from_ed25519(&utils::load_signing_key(path)?),
+        None => Identity::passphrase(&read_document_passphrase(false)?),
+    };
+
+    let (mut doc, _, sig_block) = ArchiveReader::read_decrypted(&document, &identity)?;
+    let document_encrypted = doc.manifest.encryption.is_some();
+    let sections = decrypt_sections(&mut doc, std::slice::from_ref(&identity))?;
+    let has_encrypted_sections = sections.iter().any(|s| s.group.is_some());
+    if !document_encrypted && !has_encrypted_sections {
+        return Err(TdfError::InvalidDocument(format!("{} is not encrypted", document.display())));
+    }
+    let assets = ArchiveReader::read_assets(&document, Some(&identity))?;
+
+    let output_path = output.unwrap_or_else(|| default_output(&document, "decrypted"));
+    write_archive(ArchiveBuilder::new(doc), assets, &output_path, resign, !sig_block.signatures.is_empty())?;
+
+    println!("Decrypted TDF document: {}", output_path.display());
+    for section in sections.iter().filter(|s| s.group.is_some()) {
+        let status = match section.access {
+            SectionAccess::Decrypted => "decrypted",
+            _ => "still encrypted (not a recipient)",
+        };
+        println!("  Section {}: {}", section.id, status);
+    }
+    Ok(())
+}
@@ -1,214 +1,402 @@
-use printpdf::*;
-use std::fs::File;
-use std::io::BufWriter;
-use std::path::PathBuf;
+use crate::html::{self_verifying_trailer, Verifier};
+use crate::pdf::{attach_source, PageGeometry, PdfRenderer, SignerSummary, VerificationSummary};
+use crate::utils;
+use chrono::Utc;
+use ed25519_dalek::VerifyingKey;
+use std::path::{Path, PathBuf};
+use tdf_convert::export::docx::write_docx;
+use tdf_convert::export::markdown::write_markdown;
+use tdf_convert::export::xbrl::{write_inline, write_instance};
 use tdf_core::archive::ArchiveReader;
-use tdf_core::error::TdfResult;
+use tdf_core::certificate::{ChainStatus, SignerCertificateReport, TrustAnchors};
+use tdf_core::config::SecurityConfig;
+use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::html::render_page;
+use tdf_core::revocation::RevocationManager;
+use tdf_core::signature::{SignatureManager, VerificationResult};
 
-pub fn export_to_pdf(document: PathBuf, output: Option<PathBuf>) -> TdfResult<()> {
-    let (doc, _, _) = ArchiveReader::read(&document)?;
+/// Output format of `tdf export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Pdf,
+    Html,
+    Markdown,
+    Docx,
+    /// XBRL instance of the document's tagged facts
+    Xbrl,
+    /// Inline XBRL (XHTML) rendering of the document
+    InlineXbrl,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = TdfError;
+
+    fn from_str(s: &str) -> TdfResult<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "pdf" => Ok(ExportFormat::Pdf),
+            "html" => Ok(ExportFormat::Html),
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "docx" => Ok(ExportFormat::Docx),
+            "xbrl" => Ok(ExportFormat::Xbrl),
+            "ixbrl" | "inline-xbrl" => Ok(ExportFormat::InlineXbrl),
+            other => Err(TdfError::InvalidDocument(format!(
+                "Unknown export format '{}' (expected pdf, html, md, docx, xbrl or ixbrl)",
+                other
+            ))),
+        }
+    }
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Html => "html",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Docx => "docx",
+            ExportFormat::Xbrl => "xbrl",
+            ExportFormat::InlineXbrl => "xhtml",
+        }
+    }
+}
+
+/// What `tdf export` adds beyond the rendered content
+#[derive(Debug, Default)]
+pub struct ExportOptions {
+    /// Verifying key used to check signatures for the appendix
+    pub key: Option<PathBuf>,
+    /// External revocation list (CBOR) consulted alongside the embedded one
+    pub revocation_list: Option<PathBuf>,
+    /// Trust anchors for signer certificate chains; without them a
+    /// certificate's key is not trusted for the appendix
+    pub trust_anchors: Option<PathBuf>,
+    /// Leave out the verification appendix
+    pub no_appendix: bool,
+    /// Do not attach the original `.tdf`
+    pub no_attach: bool,
+    /// Embed the archive and the wasm verifier in an HTML export
+    pub self_verifying: bool,
+    /// wasm-pack output directory holding the verifier (default `tdf-wasm/pkg`)
+    pub verifier: Option<PathBuf>,
+}
+
+/// Check the document's integrity, signatures and revocation status now
+fn verification_summary(document: &Path, options: &ExportOptions, attachment: Option<String>) -> TdfResult<VerificationSummary> {
+    let (doc, _, sig_block, embedded) = ArchiveReader::read_with_revocation(document)?;
+    let mut revocation_manager = RevocationManager::new();
+    if let Some(list) = embedded {
+        revocation_manager.add_list(list);
+    }
+    if let Some(path) = &options.revocation_list {
+        revocation_manager.add_list(RevocationManager::from_cbor(&std::fs::read(path)?)?);
+    }
+    let anchors = options.trust_anchors.as_deref().map(TrustAnchors::from_file).transpose()?;
+    let report = ArchiveReader::verify_with_trust_anchors(
+        document,
+        SecurityConfig::default(),
+        Some(&revocation_manager),
+        anchors.as_ref(),
+    )?;
+
+    let results = match &options.key {
+        Some(path) => {
+            let key = utils::load_verifying_key(path)?;
+            let keys: Vec<(String, VerifyingKey)> =
+                sig_block.signatures.iter().map(|s| (s.signer.id.clone(), key)).collect();
+            let root_hash = hex::decode(&doc.manifest.integrity.root_hash)
+                .map_err(|e| TdfError::InvalidDocument(format!("Invalid root hash hex: {}", e)))?;
+            Some(SignatureManager::verify_signature_block_with_revocation(
+                &sig_block,
+                &root_hash,
+                &keys,
+                Some(&revocation_manager),
+            )?)
+        }
+        None => None,
+    };
+
+    // `--key` is one signer's key: when it verifies some signatures, the
+    // others were made with keys it cannot check
+    let key_matched = results.as_ref().is_some_and(|results| {
+        results
+            .iter()
+            .any(|r| matches!(r, VerificationResult::Valid { .. } | VerificationResult::Revoked { .. }))
+    });
+
+    let signers = sig_block
+        .signatures
+        .iter()
+        .enumerate()
+        .map(|(i, sig)| {
+            let signature = if sig.root_hash != report.root_hash {
+                "Root hash mismatch".to_string()
+            } else {
+                let certificate = report.signer_certificates.iter().find(|c| c.signer_id == sig.signer.id);
+                signature_status(results.as_ref().map(|r| r.get(i)), certificate, key_matched)
+            };
+            let revocation = match revocation_manager.is_revoked(&sig.signer.id) {
+                Some(entry) => format!("Revoked {} ({:?})", entry.revoked_at.format("%Y-%m-%d %H:%M:%S UTC"), entry.reason),
+                None => "Not revoked at export".to_string(),
+            };
+            let mut timestamp = sig.timestamp.time.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+            if let Some(authority) = &sig.timestamp.authority {
+                timestamp = format!("{} ({})", timestamp, authority);
+            }
+            SignerSummary {
+                name: sig.signer.name.clone(),
+                id: sig.signer.id.clone(),
+                algorithm: format!("{:?}", sig.algorithm),
+                timestamp,
+                signature,
+                revocation,
+            }
+        })
+        .collect();
+
+    Ok(VerificationSummary {
+        document_id: doc.manifest.document.id.clone(),
+        root_hash: report.root_hash,
+        integrity_valid: report.integrity_valid,
+        exported_at: Utc::now(),
+        attachment,
+        signers,
+    })
+}
+
+/// Appendix status of one signature
+///
+/// A signature is valid if it verifies under `--key` or under the key of
+/// its own signer certificate, when that certificate chains to one of the
+/// `--trust-anchors`. When `--key` verified another signature
+/// (`key_matched`), a signature it does not verify is reported as not
+/// checked rather than invalid.
+///
+/// # Arguments
+/// * `result` - Outcome under `--key`; `None` when no key was given
+fn signature_status(
+    result: Option<Option<&VerificationResult>>,
+    certificate: Option<&SignerCertificateReport>,
+    key_matched: bool,
+) -> String {
+    match (result, certificate) {
+        (Some(Some(VerificationResult::Valid { .. })), _) => "Valid".to_string(),
+        (Some(Some(VerificationResult::Revoked { .. })), _) => "Valid, key revoked".to_string(),
+        (_, Some(cert)) if cert.signature_valid => match &cert.chain_status {
+            ChainStatus::Trusted { .. } => "Valid (signer certificate key)".to_string(),
+            ChainStatus::Untrusted { reason } => format!("Not verified (untrusted certificate chain: {})", reason),
+            ChainStatus::NotChecked => "Not verified (certificate chain not checked)".to_string(),
+        },
+        (_, Some(cert)) => format!("Invalid under signer certificate: {}", cert.errors.join("; ")),
+        (None, None) => "Not verified (no key given)".to_string(),
+        (Some(Some(VerificationResult::Invalid { .. })), None) if key_matched => {
+            "Not verified (--key is another signer's key)".to_string()
+        }
+        (Some(Some(VerificationResult::Invalid { reason, .. })), None) => format!("Invalid: {}", reason),
+        (Some(_), None) => "Not verified".to_string(),
+    }
+}
+
+/// Export a TDF document to PDF, HTML, Markdown, DOCX or XBRL
+pub fn export_document(document: PathBuf, output: Option<PathBuf>, format: ExportFormat, options: ExportOptions) -> TdfResult<()> {
+    if options.self_verifying && format != ExportFormat::Html {
+        return Err(TdfError::InvalidDocument("--self-verifying requires --format html".to_string()));
+    }
 
     // Determine output path
     let output_path = output.unwrap_or_else(|| {
         document
-            .with_extension("pdf")
+            .with_extension(format.extension())
             .file_name()
-            .map(|n| PathBuf::from(n))
-            .unwrap_or_else(|| PathBuf::from("output.pdf"))
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(format!("output.{}", format.extension())))
     });
+    match format {
+        ExportFormat::Pdf => export_to_pdf(&document, &output_path, &options),
+        ExportFormat::Html => export_to_html(&document, &output_path, &options),
+        ExportFormat::Markdown => export_to_markdown(&document, &output_path),
+        ExportFormat::Docx => export_to_docx(&document, &output_path),
+        ExportFormat::Xbrl | ExportFormat::InlineXbrl => export_to_xbrl(&document, &output_path, format),
+    }
+}
 
-    // Create PDF document
-    let (mut doc_pdf, page1, layer1) = PdfDocument::new("TDF Document", Mm(210.0), Mm(297.0), "Layer 1");
-    let current_layer = doc_pdf.get_page(page1).get_layer(layer1);
-
-    // Set font
-    let font = doc_pdf.add_builtin_font(BuiltinFont::HelveticaBold)
-        .map_err(|e| tdf_core::error::TdfError::InvalidDocument(format!("Font error: {}", e)))?;
-    let font_regular = doc_pdf.add_builtin_font(BuiltinFont::Helvetica)
-        .map_err(|e| tdf_core::error::TdfError::InvalidDocument(format!("Font error: {}", e)))?;
-
-    let mut y_position = 280.0; // Start near top (A4 height is 297mm)
-
-    // Title
-    current_layer.use_text(&doc.manifest.document.title, 24.0, Mm(20.0), Mm(y_position), &font);
-    y_position -= 15.0;
-
-    // Metadata
-    let metadata_text = format!(
-        "ID: {}\nCreated: {}\nModified: {}",
-        doc.manifest.document.id,
-        doc.manifest.document.created.format("%Y-%m-%d %H:%M:%S"),
-        doc.manifest.document.modified.format("%Y-%m-%d %H:%M:%S")
-    );
-    current_layer.use_text(&metadata_text, 10.0, Mm(20.0), Mm(y_position), &font_regular);
-    y_position -= 20.0;
-
-    // Render sections
-    for section in &doc.content.sections {
-        if y_position < 30.0 {
-            // Would need to add new page here in full implementation
-            break;
-        }
+/// File name of the archive as offered to the reader
+fn archive_name(document: &Path) -> String {
+    document
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "document.tdf".to_string())
+}
 
-        // Section title
-        if let Some(ref title) = section.title {
-            current_layer.use_text(title, 16.0, Mm(20.0), Mm(y_position), &font);
-            y_position -= 10.0;
+/// Export a TDF document to a standalone HTML page
+///
+/// The document's `styles.css` is applied and assets are inlined. With
+/// `--self-verifying` the archive and the wasm verifier are embedded so
+/// the page checks its own integrity when opened.
+fn export_to_html(document: &Path, output_path: &Path, options: &ExportOptions) -> TdfResult<()> {
+    let (doc, _, _) = ArchiveReader::read(document)?;
+    let assets = ArchiveReader::read_assets(document, None)?;
+
+    let trailer = if options.self_verifying {
+        let dir = options.verifier.clone().unwrap_or_else(|| PathBuf::from("tdf-wasm/pkg"));
+        let verifier = Verifier::load(&dir)?;
+        let report = ArchiveReader::verify(document)?;
+        if !report.integrity_valid {
+            return Err(TdfError::IntegrityFailure(
+                "Document integrity check failed; refusing to build a self-verifying page".to_string(),
+            ));
         }
+        let archive = std::fs::read(document)?;
+        Some(self_verifying_trailer(&archive, &archive_name(document), &report.root_hash, &verifier))
+    } else {
+        None
+    };
 
-        // Render content blocks
-        for block in &section.content {
-            if y_position < 30.0 {
-                break;
-            }
+    std::fs::write(output_path, render_page(&doc, &assets, trailer.as_deref()))?;
+    println!("Exported HTML to: {}", output_path.display());
+    if options.self_verifying {
+        println!("  Embedded archive and verifier: the page verifies itself when opened");
+    }
+    Ok(())
+}
 
-            match block {
-                tdf_core::content::ContentBlock::Heading { level, text, .. } => {
-                    let size = match *level {
-                        1 => 18.0,
-                        2 => 16.0,
-                        3 => 14.0,
-                        _ => 12.0,
-                    };
-                    current_layer.use_text(text, size, Mm(20.0), Mm(y_position), &font);
-                    y_position -= (size / 2.0) + 2.0;
-                }
-                tdf_core::content::ContentBlock::Paragraph { text, .. } => {
-                    // Simple text rendering (would need word wrapping in full implementation)
-                    let lines: Vec<&str> = text.lines().collect();
-                    for line in lines.iter().take(3) {
-                        // Limit lines per paragraph for simplicity
-                        current_layer.use_text(line.to_string(), 10.0, Mm(20.0), Mm(y_position), &font_regular);
-                        y_position -= 5.0;
-                        if y_position < 30.0 {
-                            break;
-                        }
-                    }
-                    y_position -= 3.0;
-                }
-                tdf_core::content::ContentBlock::Table { id, columns, rows, caption, .. } => {
-                    // Enhanced table rendering with borders
-                    let mut table_y = y_position;
-                    
-                    // Caption
-                    if let Some(ref cap) = caption {
-                        current_layer.use_text(cap, 9.0, Mm(20.0), Mm(table_y + 2.0), &font);
-                        table_y -= 5.0;
-                    }
-                    
-                    // Calculate column widths (simplified)
-                    let col_count = columns.len().min(4);
-                    let col_width: f32 = 170.0 / (col_count as f32);
-                    
-                    // Header
-                    let mut x_pos: f32 = 20.0;
-                    for col in columns.iter().take(4) {
-                        current_layer.use_text(&col.header, 9.0, Mm(x_pos), Mm(table_y), &font);
-                        x_pos += col_width;
-                    }
-                    table_y -= 8.0;
-                    
-                    // Rows
-                    for row in rows.iter().take(10) {
-                        if table_y < 30.0 {
-                            break;
-                        }
-                        
-                        x_pos = 20.0;
-                        for col in columns.iter().take(4) {
-                            if let Some(cell) = row.cells.get(&col.id) {
-                                let cell_text = match cell {
-                                    tdf_core::content::CellValue::Text(s) => s.clone(),
-                                    tdf_core::content::CellValue::Number { display, .. } => display.clone(),
-                                    tdf_core::content::CellValue::Currency { display, .. } => display.clone(),
-                                    tdf_core::content::CellValue::Percentage { display, .. } => display.clone(),
-                                    tdf_core::content::CellValue::Date { display, .. } => display.clone(),
-                                };
-                                // Truncate long text
-                                let display_text = if cell_text.len() > 20 {
-                                    format!("{}...", &cell_text[..17])
-                                } else {
-                                    cell_text
-                                };
-                                current_layer.use_text(&display_text, 8.0, Mm(x_pos), Mm(table_y), &font_regular);
-                            }
-                            x_pos += col_width;
-                        }
-                        table_y -= 6.0;
-                    }
-                    y_position = table_y - 5.0;
-                }
-                tdf_core::content::ContentBlock::Diagram { id, diagram_type, nodes, edges, title, .. } => {
-                    // Render diagram as text representation
-                    if y_position < 50.0 {
-                        break;
-                    }
-                    
-                    if let Some(ref t) = title {
-                        current_layer.use_text(t, 12.0, Mm(20.0), Mm(y_position), &font);
-                        y_position -= 8.0;
-                    }
-                    
-                    // Render nodes
-                    let mut node_y = y_position;
-                    for node in nodes.iter().take(5) {
-                        if node_y < 30.0 {
-                            break;
-                        }
-                        let node_text = format!("• {}", node.label.replace('\n', " - "));
-                        current_layer.use_text(&node_text, 9.0, Mm(25.0), Mm(node_y), &font_regular);
-                        node_y -= 6.0;
-                    }
-                    
-                    // Render edges
-                    if !edges.is_empty() {
-                        current_layer.use_text("Connections:", 9.0, Mm(25.0), Mm(node_y), &font_regular);
-                        node_y -= 6.0;
-                        for edge in edges.iter().take(3) {
-                            if node_y < 30.0 {
-                                break;
-                            }
-                            let edge_text = format!("  {} → {}", edge.from, edge.to);
-                            current_layer.use_text(&edge_text, 8.0, Mm(30.0), Mm(node_y), &font_regular);
-                            node_y -= 5.0;
-                        }
-                    }
-                    
-                    y_position = node_y - 5.0;
-                }
-                tdf_core::content::ContentBlock::List { ordered, items, .. } => {
-                    let mut list_y = y_position;
-                    for (i, item) in items.iter().take(8).enumerate() {
-                        if list_y < 30.0 {
-                            break;
-                        }
-                        let prefix = if *ordered {
-                            format!("{}. ", i + 1)
-                        } else {
-                            "• ".to_string()
-                        };
-                        current_layer.use_text(&format!("{}{}", prefix, item), 9.0, Mm(25.0), Mm(list_y), &font_regular);
-                        list_y -= 5.0;
-                    }
-                    y_position = list_y - 3.0;
-                }
-                _ => {
-                    // Other block types
-                    y_position -= 5.0;
-                }
-            }
-        }
+/// Export a TDF document to PDF
+///
+/// Content flows over as many pages as needed, using the document's page
+/// layout when it has one; figures are embedded from the archive's assets.
+/// Unless turned off, a verification appendix records the trust status at
+/// export time and the original `.tdf` is attached to the PDF.
+fn export_to_pdf(document: &Path, output_path: &Path, options: &ExportOptions) -> TdfResult<()> {
+    let (doc, _, _) = ArchiveReader::read(document)?;
+    let assets = ArchiveReader::read_assets(document, None)?;
+    let attachment = if options.no_attach { None } else { Some(archive_name(document)) };
 
-        y_position -= 10.0; // Space between sections
+    let page = PageGeometry::from_layout(doc.layout.as_ref())?;
+    let mut renderer = PdfRenderer::new(&doc.manifest.document.title, page, &assets)?;
+    renderer.render_document(&doc);
+    if !options.no_appendix {
+        renderer.verification_appendix(&verification_summary(document, options, attachment.clone())?);
     }
+    let mut pdf = renderer
+        .finish()
+        .save_to_bytes()
+        .map_err(|e| TdfError::InvalidDocument(format!("PDF save error: {}", e)))?;
 
-    // Save PDF
-    doc_pdf.save(&mut BufWriter::new(File::create(&output_path)?))
-        .map_err(|e| tdf_core::error::TdfError::InvalidDocument(format!("PDF save error: {}", e)))?;
+    if let Some(name) = &attachment {
+        pdf = attach_source(&pdf, name, &std::fs::read(document)?, doc.manifest.document.modified)?;
+    }
+    std::fs::write(output_path, pdf)?;
 
     println!("Exported PDF to: {}", output_path.display());
-    println!("Note: This is a basic export. Full formatting requires additional implementation.");
+    if let Some(name) = attachment {
+        println!("  Attached source document: {}", name);
+    }
+    Ok(())
+}
+
+/// Export a TDF document to Markdown for editing
+///
+/// Figures' images are written next to the Markdown file at their archive
+/// paths, so `tdf import` of the result finds them again.
+fn export_to_markdown(document: &Path, output_path: &Path) -> TdfResult<()> {
+    let (doc, _, _) = ArchiveReader::read(document)?;
+    let assets = ArchiveReader::read_assets(document, None)?;
+    let written = write_markdown(&doc, &assets, output_path).map_err(conversion_error)?;
+
+    println!("Exported Markdown to: {}", output_path.display());
+    for path in written.iter().skip(1) {
+        println!("  Image: {}", path.display());
+    }
+    Ok(())
+}
+
+/// Export a TDF document to DOCX for editing
+///
+/// Section titles use a dedicated paragraph style so `tdf import` of the
+/// result restores the sections.
+fn export_to_docx(document: &Path, output_path: &Path) -> TdfResult<()> {
+    let (doc, _, _) = ArchiveReader::read(document)?;
+    let assets = ArchiveReader::read_assets(document, None)?;
+    write_docx(&doc, &assets, output_path).map_err(conversion_error)?;
 
+    println!("Exported DOCX to: {}", output_path.display());
     Ok(())
 }
 
+/// Export a TDF document's tagged facts as XBRL or inline XBRL
+///
+/// Facts are only filed from a document whose integrity holds; the root
+/// hash they were read from is recorded in the output.
+fn export_to_xbrl(document: &Path, output_path: &Path, format: ExportFormat) -> TdfResult<()> {
+    let report = ArchiveReader::verify(document)?;
+    if !report.integrity_valid {
+        return Err(TdfError::IntegrityFailure(
+            "Document integrity check failed; refusing to export its XBRL facts".to_string(),
+        ));
+    }
+    let (doc, _, _) = ArchiveReader::read(document)?;
+    let (facts, kind) = match format {
+        ExportFormat::InlineXbrl => (write_inline(&doc, output_path).map_err(conversion_error)?, "inline XBRL"),
+        _ => (write_instance(&doc, output_path).map_err(conversion_error)?, "XBRL instance"),
+    };
+
+    println!("Exported {} to: {}", kind, output_path.display());
+    println!("  {} tagged fact(s) from root hash {}", facts, report.root_hash);
+    Ok(())
+}
+
+fn conversion_error(e: tdf_convert::ConvertError) -> TdfError {
+    TdfError::InvalidDocument(format!("Conversion error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid() -> VerificationResult {
+        VerificationResult::Valid { signer: "CFO".to_string(), timestamp: Utc::now() }
+    }
+
+    fn invalid() -> VerificationResult {
+        VerificationResult::Invalid { signer: "CEO".to_string(), reason: "Signature verification returned false".to_string() }
+    }
+
+    #[test]
+    fn test_key_only_checks_its_own_signer() {
+        // --key verified one signature, so the other signer's is not checked
+        assert_eq!(signature_status(Some(Some(&valid())), None, true), "Valid");
+        assert_eq!(
+            signature_status(Some(Some(&invalid())), None, true),
+            "Not verified (--key is another signer's key)"
+        );
+        // A key that verifies no signature reports them as invalid
+        assert!(signature_status(Some(Some(&invalid())), None, false).starts_with("Invalid"));
+        assert_eq!(signature_status(None, None, false), "Not verified (no key given)");
+    }
+
+    #[test]
+    fn test_certificate_key_checks_its_signer() {
+        let mut certificate = SignerCertificateReport {
+            signer_id: "did:web:ceo.acme.com".to_string(),
+            leaf: None,
+            chain_length: 1,
+            chain_status: ChainStatus::NotChecked,
+            signature_valid: true,
+            errors: Vec::new(),
+        };
+        // A certificate anyone could have made for their own key is not trusted
+        assert_eq!(
+            signature_status(Some(Some(&invalid())), Some(&certificate), true),
+            "Not verified (certificate chain not checked)"
+        );
+        certificate.chain_status = ChainStatus::Untrusted { reason: "unknown issuer".to_string() };
+        assert!(signature_status(None, Some(&certificate), false).contains("unknown issuer"));
+        certificate.chain_status = ChainStatus::Trusted { anchor_subject: "CN=Test CA".to_string() };
+        assert_eq!(
+            signature_status(Some(Some(&invalid())), Some(&certificate), true),
+            "Valid (signer certificate key)"
+        );
+        certificate.signature_valid = false;
+        certificate.errors.push("key mismatch".to_string());
+        assert!(signature_status(None, Some(&certificate), false).contains("key mismatch"));
+    }
+}
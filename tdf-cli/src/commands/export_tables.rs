@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+use tdf_convert::tables::{collect_tables, export_tables, TableFormat};
+use tdf_core::archive::ArchiveReader;
+use tdf_core::error::{TdfError, TdfResult};
+
+/// Export a document's tables as typed CSV, XLSX or Parquet
+///
+/// The archive's integrity is checked first; its verified root hash is
+/// written into the provenance columns of every row.
+///
+/// # Arguments
+/// * `document` - TDF file to export from
+/// * `output` - Workbook for XLSX (default `<name>.xlsx`); directory for
+///   CSV and Parquet (default: current directory)
+/// * `format` - csv, xlsx or parquet
+/// * `tables` - Table ids to export; all tables when empty
+pub fn export_document_tables(
+    document: PathBuf,
+    output: Option<PathBuf>,
+    format: &str,
+    tables: Vec<String>,
+) -> TdfResult<()> {
+    let conversion = |e| TdfError::InvalidDocument(format!("Conversion error: {}", e));
+    let format: TableFormat = format.parse().map_err(conversion)?;
+
+    let report = ArchiveReader::verify(&document)?;
+    if !report.integrity_valid {
+        return Err(TdfError::IntegrityFailure(
+            "Document integrity check failed; refusing to export its tables".to_string(),
+        ));
+    }
+    let (doc, _, _) = ArchiveReader::read(&document)?;
+
+    let typed = collect_tables(&doc, &report.root_hash, &tables);
+    if let Some(missing) = tables.iter().find(|id| !typed.iter().any(|t| &t.id == *id)) {
+        return Err(TdfError::InvalidDocument(format!("No table with id '{}'", missing)));
+    }
+
+    let output_path = output.unwrap_or_else(|| match format {
+        TableFormat::Xlsx => document
+            .with_extension("xlsx")
+            .file_name()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("tables.xlsx")),
+        _ => PathBuf::from("."),
+    });
+    let written = export_tables(&typed, format, &output_path).map_err(conversion)?;
+
+    println!("Exported {} table(s):", typed.len());
+    for path in written {
+        println!("  {}", path.display());
+    }
+    Ok(())
+}
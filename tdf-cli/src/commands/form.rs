@@ -0,0 +1,112 @@
+use crate::utils;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tdf_core::archive::ArchiveReader;
+use tdf_core::did::DidPublicKey;
+use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::form::{add_submission, fields, read_submissions, read_template, Submission};
+use tdf_core::key_format::{decode_public_key, PrivateKey};
+use tdf_core::signature::{DocumentSigner, VerificationResult};
+
+/// Fill in a form template and add the filler's signed submission to it
+///
+/// The values are checked against the template's fields before signing.
+/// The template's content and signatures are copied unchanged.
+///
+/// # Arguments
+/// * `values` - JSON object of field id to value
+/// * `key` - Filler's private key; `*.secp256k1.*` files sign with secp256k1
+pub fn fill_form(
+    template: PathBuf,
+    values: PathBuf,
+    output: Option<PathBuf>,
+    key: PathBuf,
+    signer_id: String,
+    signer_name: String,
+) -> TdfResult<()> {
+    let values: BTreeMap<String, Value> = serde_json::from_str(&fs::read_to_string(&values)?)?;
+    let document = read_template(&template)?;
+    if fields(&document).is_empty() {
+        return Err(TdfError::InvalidDocument(format!("{} has no form fields", template.display())));
+    }
+
+    let mut submission = Submission::new(&document, values)?;
+    let signer: Box<dyn DocumentSigner> = match utils::load_private_key(&key, utils::algorithm_from_path(&key))? {
+        PrivateKey::Ed25519(key) => Box::new(key),
+        PrivateKey::Secp256k1(key) => Box::new(key),
+    };
+    submission.sign(signer.as_ref(), signer_id, signer_name)?;
+
+    let output_path = output.unwrap_or_else(|| {
+        let stem = template.file_stem().and_then(|s| s.to_str()).unwrap_or("document");
+        template.with_file_name(format!("{}.submitted.tdf", stem))
+    });
+    add_submission(&template, &output_path, &submission)?;
+
+    let report = ArchiveReader::verify(&output_path)?;
+    println!("Submitted form: {}", output_path.display());
+    println!("  Submission: {}", submission.id);
+    println!("  Fields filled: {}", submission.values.len());
+    println!("  Template root hash: {} (unchanged)", report.root_hash);
+    println!("  Template signatures kept: {}", report.signature_count);
+    Ok(())
+}
+
+/// Write the submissions of a form as a JSON array
+///
+/// Each entry holds the values, the filler and any problems found checking
+/// the values against the template. With `key`, each filler signature is
+/// verified against it; otherwise signed submissions are "not verified".
+pub fn extract_submissions(document: PathBuf, output: Option<PathBuf>, key: Option<PathBuf>) -> TdfResult<()> {
+    let template = read_template(&document)?;
+    let public_key = match &key {
+        Some(path) => Some(decode_public_key(&fs::read(path)?)?),
+        None => None,
+    };
+
+    let mut entries = Vec::new();
+    for submission in read_submissions(&document)? {
+        let status = match (&submission.signature, &public_key) {
+            (None, _) => "unsigned".to_string(),
+            (Some(_), None) => "not verified".to_string(),
+            (Some(signature), Some(public_key)) => {
+                let signer = signature.signer.id.clone();
+                let result = match public_key {
+                    DidPublicKey::Ed25519(key) => submission.verify_signature(&[(signer, *key)], &[])?,
+                    DidPublicKey::Secp256k1(key) => submission.verify_signature(&[], &[(signer, *key)])?,
+                };
+                match result {
+                    Some(VerificationResult::Valid { .. }) => "valid".to_string(),
+                    Some(VerificationResult::Invalid { reason, .. }) => format!("invalid: {}", reason),
+                    Some(VerificationResult::Revoked { reason, .. }) => format!("revoked: {}", reason),
+                    Some(VerificationResult::Unsupported { algorithm, .. }) => format!("unsupported algorithm {}", algorithm),
+                    None => "unsigned".to_string(),
+                }
+            }
+        };
+        let signer = submission.signature.as_ref().map(|s| {
+            json!({ "id": s.signer.id, "name": s.signer.name, "signed": s.timestamp.time })
+        });
+        entries.push(json!({
+            "id": submission.id,
+            "submitted": submission.submitted,
+            "template": submission.template,
+            "signer": signer,
+            "signature": status,
+            "problems": submission.check(&template),
+            "values": submission.values,
+        }));
+    }
+
+    let json = serde_json::to_string_pretty(&entries)?;
+    match output {
+        Some(path) => {
+            fs::write(&path, json)?;
+            println!("Extracted {} submission(s) to {}", entries.len(), path.display());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
@@ -1,25 +1,37 @@
 use std::path::PathBuf;
 use tdf_core::archive::ArchiveReader;
+use tdf_core::content::ContentBlock;
 use tdf_core::error::TdfResult;
 
 pub fn show_info(document: PathBuf) -> TdfResult<()> {
-    let (doc, merkle_tree, sig_block) = ArchiveReader::read(&document)?;
+    let (manifest, _, sig_block) = ArchiveReader::read_envelope(&document)?;
+    let doc = if manifest.encryption.is_some() {
+        None
+    } else {
+        Some(ArchiveReader::read(&document)?.0)
+    };
 
     println!("TDF Document Information");
     println!("=======================");
-    println!("Title: {}", doc.manifest.document.title);
-    println!("ID: {}", doc.manifest.document.id);
-    println!("Language: {}", doc.manifest.document.language);
-    println!("Created: {}", doc.manifest.document.created);
-    println!("Modified: {}", doc.manifest.document.modified);
-    println!("Schema Version: {}", doc.manifest.schema_version);
-
-    if let Some(ref classification) = doc.manifest.classification {
+    println!("Title: {}", manifest.document.title);
+    println!("ID: {}", manifest.document.id);
+    println!("Language: {}", manifest.document.language);
+    println!("Created: {}", manifest.document.created);
+    println!("Modified: {}", manifest.document.modified);
+    println!("Schema Version: {}", manifest.schema_version);
+
+    if let Some(ref revision) = manifest.revision {
+        println!("Revision: {} (supersedes {})", revision.number, revision.previous_id);
+        println!("  Previous Root Hash: {}", revision.previous_root_hash);
+        println!("  Reason: {}", revision.reason);
+    }
+
+    if let Some(ref classification) = manifest.classification {
         println!("Classification: {:?}", classification);
     }
 
     println!("\nAuthors:");
-    for author in &doc.manifest.authors {
+    for author in &manifest.authors {
         println!("  - {} ({})", author.name, author.id);
         if let Some(ref role) = author.role {
             println!("    Role: {}", role);
@@ -27,16 +39,40 @@ pub fn show_info(document: PathBuf) -> TdfResult<()> {
     }
 
     println!("\nIntegrity:");
-    println!("  Algorithm: {:?}", doc.manifest.integrity.algorithm);
-    println!("  Root Hash: {}", doc.manifest.integrity.root_hash);
+    println!("  Algorithm: {:?}", manifest.integrity.algorithm);
+    println!("  Root Hash: {}", manifest.integrity.root_hash);
+    if manifest.redaction.is_some() {
+        println!("  Redactable: yes (per-block commitments)");
+    }
 
     println!("\nContent:");
-    println!("  Sections: {}", doc.content.sections.len());
-    for section in &doc.content.sections {
-        println!("    - {} ({} blocks)", 
-            section.title.as_ref().unwrap_or(&section.id),
-            section.content.len()
-        );
+    match doc {
+        Some(doc) => {
+            println!("  Sections: {}", doc.content.sections.len());
+            for section in &doc.content.sections {
+                match section.encrypted {
+                    Some(ref encrypted) => println!("    - {} (encrypted for {})", section.id, encrypted.group),
+                    None => {
+                        println!("    - {} ({} blocks)", 
+                            section.title.as_ref().unwrap_or(&section.id),
+                            section.content.len()
+                        );
+                        for block in &section.content {
+                            if let ContentBlock::Redacted { id, .. } = block {
+                                println!("      [redacted] {}", id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        None => {
+            let info = manifest.encryption.as_ref().expect("encrypted manifest");
+            let recipients = ArchiveReader::read_encryption_header(&document)?
+                .map(|header| header.recipients.len())
+                .unwrap_or(0);
+            println!("  Encrypted: {:?} ({} recipient(s))", info.cipher, recipients);
+        }
     }
 
     println!("\nSignatures: {}", sig_block.signatures.len());
@@ -0,0 +1,215 @@
+use crate::utils;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tdf_core::did::DidPublicKey;
+use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::key_file::{write_private_file, EncryptedKeyFile, Kdf, KdfParams, StoredKey};
+use tdf_core::key_format::{
+    decode_public_key, encode_public_key, public_key_fingerprint, raw_public_key, KeyFormat, PrivateKey,
+};
+use tdf_core::signature::SignatureAlgorithm;
+use zeroize::Zeroizing;
+
+/// A key read from a file of unknown format
+enum AnyKey {
+    Private(PrivateKey),
+    Public(DidPublicKey),
+}
+
+impl AnyKey {
+    fn public_key(&self) -> DidPublicKey {
+        match self {
+            AnyKey::Private(key) => key.public_key(),
+            AnyKey::Public(key) => key.clone(),
+        }
+    }
+}
+
+fn raw_algorithm(path: &Path, secp256k1: bool) -> SignatureAlgorithm {
+    if secp256k1 {
+        SignatureAlgorithm::Secp256k1
+    } else {
+        utils::algorithm_from_path(path)
+    }
+}
+
+/// Read a private or public key in any supported format
+///
+/// Raw 32-byte files are private keys only when named `*.signing`, since
+/// raw Ed25519 public and private keys have the same length.
+fn read_any_key(path: &Path, secp256k1: bool) -> TdfResult<AnyKey> {
+    let data = Zeroizing::new(fs::read(path)?);
+    let is_signing_file = path.extension().is_some_and(|ext| ext == "signing");
+    let raw_public = match data.len() {
+        32 => !is_signing_file,
+        33 | 65 => true,
+        _ => false,
+    };
+    if raw_public {
+        return decode_public_key(&data).map(AnyKey::Public);
+    }
+
+    match utils::load_private_key(path, raw_algorithm(path, secp256k1)) {
+        Ok(key) => Ok(AnyKey::Private(key)),
+        Err(private_err) => decode_public_key(&data).map(AnyKey::Public).map_err(|_| private_err),
+    }
+}
+
+fn algorithm_name(algorithm: SignatureAlgorithm) -> &'static str {
+    match algorithm {
+        SignatureAlgorithm::Secp256k1 => "secp256k1",
+        _ => "Ed25519",
+    }
+}
+
+/// Encrypt an unencrypted private key file in place
+///
+/// Accepts legacy raw keys as well as PKCS#8/SEC1 and JWK files. The
+/// encrypted file is written next to the original and renamed over it, so
+/// an interrupted migration never leaves a truncated key behind.
+pub fn migrate_key(path: PathBuf, secp256k1: bool, kdf: Kdf) -> TdfResult<()> {
+    let key = match StoredKey::read(&path)? {
+        StoredKey::Encrypted(_) => {
+            println!("{} is already encrypted", path.display());
+            return Ok(());
+        }
+        StoredKey::Plain(key) => key,
+        StoredKey::Legacy(secret) => PrivateKey::from_secret(&secret, raw_algorithm(&path, secp256k1))?,
+    };
+
+    let passphrase = utils::read_passphrase("New key passphrase: ", true)?;
+    let encrypted = EncryptedKeyFile::from_private_key(&key, passphrase.as_bytes(), KdfParams::new(kdf)?)?;
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    encrypted.write(&tmp_path)?;
+    fs::rename(&tmp_path, &path)?;
+
+    println!("✓ Encrypted {} ({:?}, {:?})", path.display(), encrypted.algorithm, kdf);
+    Ok(())
+}
+
+/// Export a key as raw bytes, PKCS#8/SPKI (PEM or DER) or JWK
+///
+/// Private keys are exported unencrypted, so the output file is replaced by
+/// an owner-readable one. Text formats go to stdout without `--output`.
+pub fn export_key(
+    input: PathBuf,
+    format: KeyFormat,
+    public: bool,
+    output: Option<PathBuf>,
+    secp256k1: bool,
+) -> TdfResult<()> {
+    let key = read_any_key(&input, secp256k1)?;
+
+    let (data, private) = match (&key, public) {
+        (AnyKey::Private(key), false) => (key.encode(format)?.as_bytes().to_vec(), true),
+        (AnyKey::Public(_), false) => {
+            return Err(TdfError::InvalidDocument(format!(
+                "{} holds a public key; use --public",
+                input.display()
+            )))
+        }
+        (_, true) => (encode_public_key(&key.public_key(), format)?, false),
+    };
+    let data = Zeroizing::new(data);
+
+    match output {
+        Some(path) => {
+            if private {
+                write_private_file(&path, &data)?;
+                eprintln!("Warning: {} holds an UNENCRYPTED private key", path.display());
+            } else {
+                fs::write(&path, &*data)?;
+            }
+            eprintln!("Exported {} key to {}", if private { "private" } else { "public" }, path.display());
+        }
+        None if matches!(format, KeyFormat::Pem | KeyFormat::Jwk) => {
+            let mut stdout = std::io::stdout();
+            stdout.write_all(&data)?;
+            if !data.ends_with(b"\n") {
+                stdout.write_all(b"\n")?;
+            }
+        }
+        None => {
+            return Err(TdfError::InvalidDocument(
+                "Binary formats (raw, der) require --output".to_string(),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Import a PEM, DER, JWK or raw key into TDF key files
+///
+/// Private keys are written as an encrypted `<name>.signing` (or
+/// `<name>.secp256k1.signing`) with the matching raw `.verifying` key;
+/// public keys only produce the `.verifying` file.
+pub fn import_key(
+    input: PathBuf,
+    name: String,
+    output: Option<PathBuf>,
+    secp256k1: bool,
+    kdf: Kdf,
+) -> TdfResult<()> {
+    let key = read_any_key(&input, secp256k1)?;
+    let public_key = key.public_key();
+
+    let output_dir = output.unwrap_or_else(|| PathBuf::from("."));
+    fs::create_dir_all(&output_dir)?;
+    let prefix = match public_key {
+        DidPublicKey::Secp256k1(_) => format!("{}.secp256k1", name),
+        DidPublicKey::Ed25519(_) => name,
+    };
+
+    if let AnyKey::Private(ref private_key) = key {
+        let passphrase = utils::read_passphrase("New key passphrase: ", true)?;
+        let signing_key_path = output_dir.join(format!("{}.signing", prefix));
+        EncryptedKeyFile::from_private_key(private_key, passphrase.as_bytes(), KdfParams::new(kdf)?)?
+            .write(&signing_key_path)?;
+        println!("Signing key (private, encrypted) written to: {}", signing_key_path.display());
+    }
+
+    let verifying_key_path = output_dir.join(format!("{}.verifying", prefix));
+    fs::write(&verifying_key_path, raw_public_key(&public_key))?;
+    println!("Verifying key (public) written to: {}", verifying_key_path.display());
+    println!("  Algorithm: {}", algorithm_name(public_key.algorithm()));
+    println!("  Fingerprint: {}", public_key_fingerprint(&public_key));
+    Ok(())
+}
+
+/// Show the algorithm, fingerprint and did:key of a key file
+pub fn show_fingerprint(input: PathBuf, secp256k1: bool) -> TdfResult<()> {
+    let key = read_any_key(&input, secp256k1)?;
+    let public_key = key.public_key();
+    println!("Key: {}", input.display());
+    println!("  Type: {}", if matches!(key, AnyKey::Private(_)) { "private" } else { "public" });
+    println!("  Algorithm: {}", algorithm_name(public_key.algorithm()));
+    println!("  Fingerprint: {}", public_key_fingerprint(&public_key));
+    println!("  did:key: {}", public_key.to_did_key());
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_export_private_key_over_readable_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("key.pem");
+        let (signing_key, _) = utils::generate_keypair();
+        fs::write(&input, PrivateKey::Ed25519(signing_key).encode(KeyFormat::Pem).unwrap().as_bytes()).unwrap();
+
+        let output = dir.path().join("export.pem");
+        fs::write(&output, b"").unwrap();
+        fs::set_permissions(&output, fs::Permissions::from_mode(0o644)).unwrap();
+
+        export_key(input.clone(), KeyFormat::Pem, false, Some(output.clone()), false).unwrap();
+        assert_eq!(fs::metadata(&output).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read(&output).unwrap(), fs::read(&input).unwrap());
+    }
+}
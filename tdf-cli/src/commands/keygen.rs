@@ -3,13 +3,16 @@ use ed25519_dalek::{SigningKey, VerifyingKey};
 use k256::ecdsa::{SigningKey as Secp256k1SigningKey, VerifyingKey as Secp256k1VerifyingKey};
 use std::fs;
 use std::path::{Path, PathBuf};
+use tdf_core::did::DidPublicKey;
 use tdf_core::error::TdfResult;
+use tdf_core::key_file::{EncryptedKeyFile, Kdf, KdfParams};
+use tdf_core::key_format::public_key_fingerprint;
 
 pub fn generate_keypair(output: Option<PathBuf>, name: String) -> TdfResult<()> {
-    generate_keypair_ed25519(output, name)
+    generate_keypair_ed25519(output, name, Kdf::default())
 }
 
-pub fn generate_keypair_ed25519(output: Option<PathBuf>, name: String) -> TdfResult<()> {
+pub fn generate_keypair_ed25519(output: Option<PathBuf>, name: String, kdf: Kdf) -> TdfResult<()> {
     let output_dir = output.unwrap_or_else(|| PathBuf::from("."));
     
     // Ensure output directory exists
@@ -18,10 +21,12 @@ pub fn generate_keypair_ed25519(output: Option<PathBuf>, name: String) -> TdfRes
     // Generate keypair
     let (signing_key, verifying_key) = utils::generate_keypair();
 
-    // Write signing key (private key)
+    // Write signing key (private key), encrypted under a passphrase
+    let passphrase = utils::read_passphrase("New key passphrase: ", true)?;
     let signing_key_path = output_dir.join(format!("{}.signing", name));
-    fs::write(&signing_key_path, signing_key.to_bytes())?;
-    println!("Signing key (private) written to: {}", signing_key_path.display());
+    EncryptedKeyFile::from_ed25519(&signing_key, passphrase.as_bytes(), KdfParams::new(kdf)?)?
+        .write(&signing_key_path)?;
+    println!("Signing key (private, encrypted) written to: {}", signing_key_path.display());
     println!("  ⚠️  Keep this file secure and never share it!");
 
     // Write verifying key (public key)
@@ -32,8 +37,11 @@ pub fn generate_keypair_ed25519(output: Option<PathBuf>, name: String) -> TdfRes
 
     // Show key info
     println!("\nKey Information:");
-    println!("  Signing key size: {} bytes", signing_key.to_bytes().len());
     println!("  Verifying key size: {} bytes", verifying_key.to_bytes().len());
+    println!("  Key file KDF: {:?}", kdf);
+    let public_key = DidPublicKey::Ed25519(verifying_key);
+    println!("  Fingerprint: {}", public_key_fingerprint(&public_key));
+    println!("  did:key: {}", public_key.to_did_key());
     println!("\nUsage:");
     println!("  Create document: tdf create input.json --key {}.signing --signer-id \"did:web:example.com\" --signer-name \"Your Name\"", name);
     println!("  Verify document: tdf verify document.tdf --key {}.verifying", name);
@@ -41,7 +49,7 @@ pub fn generate_keypair_ed25519(output: Option<PathBuf>, name: String) -> TdfRes
     Ok(())
 }
 
-pub fn generate_keypair_secp256k1(output: Option<PathBuf>, name: String) -> TdfResult<()> {
+pub fn generate_keypair_secp256k1(output: Option<PathBuf>, name: String, kdf: Kdf) -> TdfResult<()> {
     let output_dir = output.unwrap_or_else(|| PathBuf::from("."));
     
     fs::create_dir_all(&output_dir)?;
@@ -52,11 +60,12 @@ pub fn generate_keypair_secp256k1(output: Option<PathBuf>, name: String) -> TdfR
     let signing_key = Secp256k1SigningKey::random(&mut csprng);
     let verifying_key = Secp256k1VerifyingKey::from(&signing_key);
 
-    // Write signing key (private key) - raw bytes
+    // Write signing key (private key), encrypted under a passphrase
+    let passphrase = utils::read_passphrase("New key passphrase: ", true)?;
     let signing_key_path = output_dir.join(format!("{}.secp256k1.signing", name));
-    let signing_key_bytes = signing_key.to_bytes();
-    fs::write(&signing_key_path, signing_key_bytes.as_slice())?;
-    println!("Signing key (private) written to: {}", signing_key_path.display());
+    EncryptedKeyFile::from_secp256k1(&signing_key, passphrase.as_bytes(), KdfParams::new(kdf)?)?
+        .write(&signing_key_path)?;
+    println!("Signing key (private, encrypted) written to: {}", signing_key_path.display());
     println!("  ⚠️  Keep this file secure and never share it!");
 
     // Write verifying key (public key) - SEC1 format
@@ -68,8 +77,11 @@ pub fn generate_keypair_secp256k1(output: Option<PathBuf>, name: String) -> TdfR
 
     println!("\nKey Information:");
     println!("  Algorithm: secp256k1 (Web3 compatible)");
-    println!("  Signing key size: {} bytes", signing_key_bytes.as_slice().len());
     println!("  Verifying key size: {} bytes", pubkey_bytes.len());
+    println!("  Key file KDF: {:?}", kdf);
+    let public_key = DidPublicKey::Secp256k1(verifying_key);
+    println!("  Fingerprint: {}", public_key_fingerprint(&public_key));
+    println!("  did:key: {}", public_key.to_did_key());
 
     Ok(())
 }
@@ -0,0 +1,64 @@
+use crate::commands::create::document_from_json;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tdf_core::archive::ArchiveReader;
+use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::lint::{lint_document, Severity};
+
+/// Check a TDF document, or the JSON input of `tdf create`, for content errors
+///
+/// JSON input is checked as `tdf create` would build it, which adds no
+/// assets, so every local figure is reported missing. Errors fail the
+/// command; warnings fail it only with `deny_warnings`.
+///
+/// # Arguments
+/// * `input` - A `.tdf` archive or a `.json` document description
+/// * `format` - `text` or `json`
+/// * `deny_warnings` - Treat warnings as errors
+pub fn lint(input: PathBuf, format: &str, deny_warnings: bool) -> TdfResult<()> {
+    let is_json = input.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let (document, assets) = if is_json {
+        (document_from_json(&input)?, HashSet::new())
+    } else {
+        let (document, _, _) = ArchiveReader::read(&input)?;
+        let assets = ArchiveReader::read_assets(&input, None)?.into_keys().collect();
+        (document, assets)
+    };
+    let report = lint_document(&document, Some(&assets));
+    let (errors, warnings) = (report.errors().count(), report.warnings().count());
+
+    match format.to_ascii_lowercase().as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+        "text" => {
+            println!("TDF Lint: {}", input.display());
+            println!();
+            for finding in &report.findings {
+                let symbol = match finding.severity {
+                    Severity::Error => "✗",
+                    Severity::Warning => "⚠",
+                };
+                println!("  {} {}", symbol, finding);
+            }
+            if report.findings.is_empty() {
+                println!("RESULT: ✓ NO ISSUES");
+            } else {
+                println!();
+                println!("RESULT: {} error(s), {} warning(s)", errors, warnings);
+            }
+        }
+        other => {
+            return Err(TdfError::InvalidDocument(format!(
+                "Unknown lint format '{}' (expected text or json)",
+                other
+            )))
+        }
+    }
+
+    if errors > 0 || (deny_warnings && warnings > 0) {
+        return Err(TdfError::InvalidDocument(format!(
+            "Document has {} content error(s) and {} warning(s)",
+            errors, warnings
+        )));
+    }
+    Ok(())
+}
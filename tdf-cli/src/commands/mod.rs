@@ -1,10 +1,19 @@
 pub mod create;
+pub mod diff;
+pub mod encrypt;
 pub mod extract;
+pub mod form;
 pub mod export;
+pub mod export_tables;
 pub mod import;
 pub mod info;
 pub mod keygen;
+pub mod key;
+pub mod lint;
+pub mod redact;
+pub mod revision;
 pub mod verify;
 pub mod workflow;
 pub mod revoke;
+pub mod signer_server;
 
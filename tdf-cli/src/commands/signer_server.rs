@@ -0,0 +1,27 @@
+use crate::commands::create::SIGNER_TOKEN_ENV;
+use crate::utils;
+use std::path::PathBuf;
+use tdf_core::error::TdfResult;
+use tdf_core::remote_signer::{RemoteSignerServer, SIGN_PATH};
+use tdf_core::signature::DocumentSigner;
+
+pub fn run_signer_server(key: PathBuf, secp256k1: bool, listen: String) -> TdfResult<()> {
+    let signer: Box<dyn DocumentSigner> = if secp256k1 {
+        Box::new(utils::load_secp256k1_signing_key(&key)?)
+    } else {
+        Box::new(utils::load_signing_key(&key)?)
+    };
+
+    let mut server = RemoteSignerServer::bind(&listen, signer)?;
+    match std::env::var(SIGNER_TOKEN_ENV) {
+        Ok(token) => server = server.with_bearer_token(token),
+        Err(_) => {
+            println!("  ⚠️  {} not set: requests are not authenticated", SIGNER_TOKEN_ENV);
+        }
+    }
+
+    println!("Remote signer listening on http://{}{}", server.local_addr()?, SIGN_PATH);
+    println!("  Sign with: tdf create input.json --remote-signer http://{} --signer-id ... --signer-name ...", server.local_addr()?);
+    server.serve();
+    Ok(())
+}
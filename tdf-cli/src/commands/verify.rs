@@ -6,13 +6,23 @@
 //! - Strict mode by default (CVE-TDF-018)
 //! - Enforced whitelist checking (CVE-TDF-012)
 //! - Mandatory revocation checking (CVE-TDF-011)
+//!
+//! Formula cells are re-computed and a stored value that disagrees with its
+//! formula fails verification, even when the document is validly signed.
 
 use crate::utils;
 use ed25519_dalek::VerifyingKey;
 use std::path::PathBuf;
 use tdf_core::archive::ArchiveReader;
+use tdf_core::certificate::{ChainStatus, TrustAnchors};
 use tdf_core::config::{SecurityConfig, SizeTier};
+use tdf_core::encryption::{decrypt_sections, Identity, SectionAccess};
+use tdf_core::did::{
+    verify_signature_with_did, CompositeResolver, DidDocumentCache, DidKeyResolver, DidResolver,
+    DidWebResolver, HttpDidFetcher,
+};
 use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::formula::FormulaOutcome;
 use tdf_core::revocation::RevocationManager;
 use tdf_core::signature::SignatureManager;
 use tdf_core::whitelist::SignerWhitelist;
@@ -51,17 +61,101 @@ struct VerifyConfig {
     skip_revocation: bool,
 }
 
-pub fn verify_document(
-    document: PathBuf,
-    key: Option<PathBuf>,
-    security_tier: String,
-    revocation_list: Option<PathBuf>,
-    trusted_signers: Option<PathBuf>,
-    allow_unsigned: bool,
-    lenient: bool,
-    enforce_whitelist: bool,
-    skip_revocation: bool,
-) -> TdfResult<()> {
+/// Check a signer verified without --key against the revocation list and whitelist
+fn check_signer_policy(
+    signer_id: &str,
+    revocation_manager: &RevocationManager,
+    whitelist: Option<&SignerWhitelist>,
+    config: &VerifyConfig,
+    errors: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    if !config.skip_revocation {
+        if let Some(entry) = revocation_manager.is_revoked(signer_id) {
+            let msg = format!("Key revoked at {}: {:?}", entry.revoked_at, entry.reason);
+            println!("    Revoked: ✓ (at {}, reason: {:?})", entry.revoked_at, entry.reason);
+            if config.strict {
+                errors.push(msg);
+            } else {
+                warnings.push(msg);
+            }
+        }
+    }
+
+    if let Some(wl) = whitelist {
+        if !wl.is_trusted(signer_id) {
+            let msg = format!("Signer not in whitelist: {}", signer_id);
+            if config.enforce_whitelist {
+                errors.push(msg);
+            } else {
+                warnings.push(msg);
+            }
+        }
+    }
+}
+
+/// DID resolution options for signer verification
+#[derive(Debug, Default)]
+pub struct DidOptions {
+    pub enabled: bool,
+    pub cache_dir: Option<PathBuf>,
+    pub offline: bool,
+}
+
+/// Build a resolver for did:key and did:web from the CLI options
+fn build_did_resolver(options: &DidOptions) -> TdfResult<CompositeResolver> {
+    let web_fetcher: Box<dyn tdf_core::did::DidDocumentFetcher> = match (&options.cache_dir, options.offline) {
+        (Some(dir), true) => Box::new(DidDocumentCache::new(dir)),
+        (Some(dir), false) => Box::new(DidDocumentCache::new(dir).with_upstream(Box::new(HttpDidFetcher::new()))),
+        (None, true) => {
+            return Err(TdfError::DidResolution(
+                "--did-offline requires --did-cache <dir>".to_string(),
+            ))
+        }
+        (None, false) => Box::new(HttpDidFetcher::new()),
+    };
+
+    Ok(CompositeResolver::new()
+        .with_resolver(Box::new(DidKeyResolver::new()))
+        .with_resolver(Box::new(DidWebResolver::new(web_fetcher))))
+}
+
+/// Command line options for `tdf verify`
+#[derive(Debug)]
+pub struct VerifyOptions {
+    /// Verifying key for the document signatures
+    pub key: Option<PathBuf>,
+    /// Size tier: micro, standard, extended or permissive
+    pub security_tier: String,
+    /// External revocation list (CBOR)
+    pub revocation_list: Option<PathBuf>,
+    /// Trusted signers whitelist (JSON)
+    pub trusted_signers: Option<PathBuf>,
+    pub allow_unsigned: bool,
+    pub lenient: bool,
+    pub enforce_whitelist: bool,
+    pub skip_revocation: bool,
+    /// X.509 trust anchors for signer certificate chains
+    pub trust_anchors: Option<PathBuf>,
+    pub did_options: DidOptions,
+    /// Private key used to open sections encrypted for an audience group
+    pub identity: Option<PathBuf>,
+}
+
+pub fn verify_document(document: PathBuf, options: VerifyOptions) -> TdfResult<()> {
+    let VerifyOptions {
+        key,
+        security_tier,
+        revocation_list,
+        trusted_signers,
+        allow_unsigned,
+        lenient,
+        enforce_whitelist,
+        skip_revocation,
+        trust_anchors,
+        did_options,
+        identity,
+    } = options;
     let config = VerifyConfig {
         allow_unsigned,
         strict: !lenient,  // Default is strict (lenient = false)
@@ -91,6 +185,21 @@ pub fn verify_document(
         None
     };
 
+    // Load X.509 trust anchors if provided
+    let anchors = if let Some(anchors_path) = &trust_anchors {
+        let anchors = TrustAnchors::from_file(anchors_path)?;
+        println!("Loaded {} trust anchor(s): {}", anchors.len(), anchors_path.display());
+        Some(anchors)
+    } else {
+        None
+    };
+
+    let did_resolver = if did_options.enabled {
+        Some(build_did_resolver(&did_options)?)
+    } else {
+        None
+    };
+
     // Print header
     println!();
     println!("TDF Verification Report");
@@ -101,14 +210,88 @@ pub fn verify_document(
     println!();
 
     // Perform verification with security config
-    let report = ArchiveReader::verify_with_config(&document, security_config, Some(&revocation_manager))?;
+    let report = ArchiveReader::verify_with_trust_anchors(
+        &document,
+        security_config,
+        Some(&revocation_manager),
+        anchors.as_ref(),
+    )?;
 
     // Print integrity status
     println!("INTEGRITY: {}", if report.integrity_valid { "✓ VALID" } else { "✗ INVALID" });
     println!("  Root Hash: {}", &report.root_hash[..32.min(report.root_hash.len())]);
     println!("  Algorithm: SHA-256");
+    if report.encrypted {
+        println!("  Payload: encrypted (verified without decrypting)");
+    }
     println!();
 
+    // The predecessor link is part of the signed manifest
+    if let Some(ref revision) = report.document.manifest.revision {
+        println!("REVISION: {}", revision.number);
+        println!("  Supersedes: {}", revision.previous_id);
+        println!("  Previous Root Hash: {}", &revision.previous_root_hash[..32.min(revision.previous_root_hash.len())]);
+        println!("  Reason: {}", revision.reason);
+        println!("  Use `tdf history` with the earlier revisions to check the chain");
+        println!();
+    }
+
+    // Redacted content is proven by its commitments
+    if report.document.manifest.redaction.is_some() && report.integrity_valid {
+        println!("REDACTIONS: {}", report.redacted_blocks.len());
+        for id in &report.redacted_blocks {
+            println!("  ■ {}", id);
+        }
+        println!("  Remaining content is exactly what was signed");
+        println!();
+    }
+
+    // Sections encrypted for audience groups are covered by the same signature
+    let identities = match &identity {
+        Some(path) => vec![Identity::from_ed25519(&utils::load_signing_key(path)?)],
+        None => Vec::new(),
+    };
+    let opened = if !report.encrypted {
+        Ok(Some(report.document.clone()))
+    } else if let Some(identity) = identities.first() {
+        ArchiveReader::read_decrypted(&document, identity).map(|(opened, _, _)| Some(opened))
+    } else {
+        Ok(None)
+    };
+    let sections = opened.and_then(|opened| match opened {
+        Some(mut opened) if opened.content.sections.iter().any(|s| s.encrypted.is_some()) => {
+            decrypt_sections(&mut opened, &identities).map(Some)
+        }
+        _ => Ok(None),
+    });
+    match sections {
+        Ok(Some(sections)) => {
+            println!("SECTIONS:");
+            for section in &sections {
+                match (section.access, &section.group) {
+                    (SectionAccess::Plain, _) => println!("  - {}: plain", section.id),
+                    (SectionAccess::Decrypted, Some(group)) => {
+                        println!("  ✓ {}: decrypted ({})", section.id, group)
+                    }
+                    (_, group) => println!(
+                        "  🔒 {}: encrypted for {}",
+                        section.id,
+                        group.as_deref().unwrap_or("another audience")
+                    ),
+                }
+            }
+            println!("  All sections are covered by the document signature");
+            println!();
+        }
+        Ok(None) => {}
+        Err(e) => {
+            println!("SECTIONS: ✗ could not be decrypted ({})", e);
+            println!("  All sections are covered by the document signature");
+            println!();
+            warnings.push(format!("Section decryption failed: {}", e));
+        }
+    }
+
     if !report.integrity_valid {
         eprintln!("✗ INTEGRITY FAILURE");
         eprintln!("The document has been modified or corrupted.");
@@ -117,6 +300,31 @@ pub fn verify_document(
         ));
     }
 
+    // Totals are re-computed, so a wrong figure is caught even when signed
+    if !report.formulas.is_empty() {
+        println!("FORMULAS: {} checked", report.formulas.len());
+        for check in &report.formulas {
+            match &check.outcome {
+                FormulaOutcome::Matches => {}
+                FormulaOutcome::Mismatch { computed } => {
+                    println!("  ✗ {} = {}: shows {} but computes {}", check.key(), check.formula, check.display, computed);
+                    errors.push(format!("Formula {} shows {} but computes {}", check.key(), check.display, computed));
+                }
+                FormulaOutcome::Error { message } => {
+                    println!("  ✗ {} = {}: {}", check.key(), check.formula, message);
+                    errors.push(format!("Formula {} cannot be evaluated: {}", check.key(), message));
+                }
+                FormulaOutcome::Unverifiable { message } => {
+                    println!("  ■ {} = {}: not re-computed ({})", check.key(), check.formula, message);
+                }
+            }
+        }
+        if !report.formulas.iter().any(|c| c.failed()) {
+            println!("  ✓ All computed values match their formulas");
+        }
+        println!();
+    }
+
     // Print timestamp warnings from report
     if !report.timestamp_warnings.is_empty() {
         for warning in &report.timestamp_warnings {
@@ -127,12 +335,21 @@ pub fn verify_document(
     // === SECURITY FIX: Check signature requirements (CVE-TDF-001) ===
     println!("SIGNATURES: {} found", report.signature_count);
 
-    // Read document to check signatures
-    let (doc, _, sig_block) = ArchiveReader::read(&document)?;
+    // Read signatures (readable without decrypting encrypted documents)
+    let (manifest, _, sig_block) = ArchiveReader::read_envelope(&document)?;
     let has_signatures = !sig_block.signatures.is_empty();
 
+    // Signatures carrying certificate chains can be verified against trust anchors instead of --key
+    let certificate_verified = anchors.is_some()
+        && has_signatures
+        && sig_block.signatures.iter().all(|s| s.signer.certificate.is_some());
+
+    // Signer DIDs are resolved to their DID documents whenever --resolve-did is
+    // given; with --key as well, each signature must pass both checks
+    let did_verified = did_resolver.is_some() && has_signatures;
+
     // Mandatory signature check
-    if has_signatures && key.is_none() {
+    if has_signatures && key.is_none() && !certificate_verified && !did_verified {
         errors.push("Document has signatures but no verification key provided".to_string());
         println!();
         println!("  ✗ ERROR: Document is signed but --key not provided");
@@ -160,7 +377,7 @@ pub fn verify_document(
     }
 
     // Get root hash for signature verification
-    let root_hash_hex = &doc.manifest.integrity.root_hash;
+    let root_hash_hex = &manifest.integrity.root_hash;
     let root_hash = hex::decode(root_hash_hex)
         .map_err(|e| TdfError::InvalidDocument(format!("Invalid root hash hex: {}", e)))?;
 
@@ -284,6 +501,10 @@ pub fn verify_document(
                 continue;
             }
         }
+    } else if did_verified {
+        println!("  Signatures verified against signer DID documents (--resolve-did)");
+    } else if certificate_verified {
+        println!("  Signatures verified against signer certificates (--trust-anchors)");
     } else if has_signatures && verifying_key.is_none() {
         println!("  Signatures present but verification skipped (--allow-unsigned)");
         warnings.push("Signatures not verified".to_string());
@@ -291,6 +512,102 @@ pub fn verify_document(
         println!("  No signatures (--allow-unsigned mode)");
     }
 
+    // === SIGNER CERTIFICATES ===
+    if !report.signer_certificates.is_empty() {
+        println!();
+        println!("CERTIFICATES: {} found", report.signer_certificates.len());
+        for cert in &report.signer_certificates {
+            println!();
+            println!("  Signer: {}", cert.signer_id);
+            if let Some(leaf) = &cert.leaf {
+                println!("    Subject: {}", leaf.subject);
+                println!("    Issuer: {}", leaf.issuer);
+                println!("    Serial: {}", leaf.serial);
+                if leaf.key_usage.is_empty() {
+                    println!("    Key Usage: (not restricted)");
+                } else {
+                    println!("    Key Usage: {}", leaf.key_usage.join(", "));
+                }
+                println!("    Valid: {} to {}", leaf.not_before, leaf.not_after);
+            }
+            println!("    Key Binding: {}", if cert.signature_valid { "✓ VALID" } else { "✗ INVALID" });
+            match &cert.chain_status {
+                ChainStatus::Trusted { anchor_subject } => {
+                    println!("    Chain: ✓ TRUSTED ({} certificate(s), anchor: {})", cert.chain_length, anchor_subject);
+                }
+                ChainStatus::Untrusted { reason } => {
+                    println!("    Chain: ✗ UNTRUSTED ({})", reason);
+                }
+                ChainStatus::NotChecked => {
+                    println!("    Chain: not checked (use --trust-anchors)");
+                }
+            }
+
+            if !cert.signature_valid {
+                errors.push(format!("Certificate key binding invalid for {}", cert.signer_id));
+            }
+            if let ChainStatus::Untrusted { reason } = &cert.chain_status {
+                errors.push(format!("Certificate chain invalid for {}: {}", cert.signer_id, reason));
+            }
+        }
+    }
+
+    // === SIGNER DIDS ===
+    if let (true, Some(resolver)) = (did_verified, did_resolver.as_ref()) {
+        println!();
+        println!("DID RESOLUTION: {} signer(s)", sig_block.signatures.len());
+        for sig in &sig_block.signatures {
+            println!();
+            println!("  Signer: {} ({})", sig.signer.name, sig.signer.id);
+
+            if sig.root_hash != report.root_hash {
+                println!("    ✗ ROOT HASH MISMATCH - Signature may be from different document!");
+                errors.push(format!("Signature root hash mismatch for {}", sig.signer.id));
+                continue;
+            }
+
+            match verify_signature_with_did(sig, &root_hash, resolver as &dyn DidResolver) {
+                Ok(result) => {
+                    println!("    Key: ✓ listed in DID document ({})", result.verification_method);
+                }
+                Err(e) => {
+                    println!("    Key: ✗ {}", e);
+                    errors.push(format!("Signature not verified via DID: {}", sig.signer.id));
+                    continue;
+                }
+            }
+
+            // Revocation and whitelist were already checked with --key
+            if verifying_key.is_some() {
+                continue;
+            }
+
+            check_signer_policy(&sig.signer.id, &revocation_manager, whitelist.as_ref(), &config, &mut errors, &mut warnings);
+        }
+    }
+
+    if certificate_verified && !did_verified {
+        let verified: Vec<&str> = report.signer_certificates.iter().map(|c| c.signer_id.as_str()).collect();
+        for sig in &sig_block.signatures {
+            if !verified.contains(&sig.signer.id.as_str()) {
+                errors.push(format!("Signature not verified: {}", sig.signer.id));
+                continue;
+            }
+
+            // Root hash, revocation and whitelist were already checked with --key
+            if verifying_key.is_some() {
+                continue;
+            }
+
+            if sig.root_hash != report.root_hash {
+                errors.push(format!("Signature root hash mismatch for {}", sig.signer.id));
+                continue;
+            }
+
+            check_signer_policy(&sig.signer.id, &revocation_manager, whitelist.as_ref(), &config, &mut errors, &mut warnings);
+        }
+    }
+
     // Print warnings summary
     println!();
     if warnings.is_empty() {
@@ -0,0 +1,87 @@
+//! Self-verifying HTML bundles for `tdf export --format html`
+//!
+//! The page carries the original archive (base64) and the `tdf-wasm`
+//! verifier: its wasm-bindgen glue and its WebAssembly module. When the
+//! page is opened, the verifier checks the archive's Merkle tree, renders
+//! the body again from the verified archive with the same renderer that
+//! produced the page, and reports whether the two match, all offline.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::path::Path;
+use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::html::escape;
+
+/// wasm-bindgen glue produced by `wasm-pack build --target web`
+pub const GLUE_FILE: &str = "tdf_wasm.js";
+/// WebAssembly module produced by `wasm-pack build --target web`
+pub const WASM_FILE: &str = "tdf_wasm_bg.wasm";
+
+const VERIFIER_SCRIPT: &str = include_str!("verifier.js");
+
+const VERIFIER_STYLES: &str = "\
+.tdf-verification { font-family: Helvetica, Arial, sans-serif; max-width: 52em; margin: 1em auto; padding: 0.8em 1em; border: 2px solid #999; border-radius: 6px; background: #f7f7f7; }
+.tdf-verification.valid { border-color: #2e7d32; background: #edf7ed; }
+.tdf-verification.invalid { border-color: #c62828; background: #fdecea; }
+.tdf-verification-status { font-weight: bold; margin: 0; }
+.tdf-verification-details { margin: 0.5em 0 0; padding-left: 1.2em; font-size: 0.9em; word-break: break-all; }
+";
+
+/// The `tdf-wasm` verifier as built by wasm-pack
+pub struct Verifier {
+    glue: String,
+    wasm: Vec<u8>,
+}
+
+impl Verifier {
+    /// Load the verifier from a wasm-pack output directory (`tdf-wasm/pkg`)
+    pub fn load(dir: &Path) -> TdfResult<Self> {
+        let read = |name: &str| {
+            std::fs::read(dir.join(name)).map_err(|_| {
+                TdfError::InvalidDocument(format!(
+                    "Verifier file {} not found in {}; build it with `wasm-pack build --target web` \
+                     in tdf-wasm (see tdf-wasm/BUILD_WASM.md) or pass --verifier <dir>",
+                    name,
+                    dir.display()
+                ))
+            })
+        };
+        let glue = String::from_utf8(read(GLUE_FILE)?)
+            .map_err(|_| TdfError::InvalidDocument(format!("{} is not valid UTF-8", GLUE_FILE)))?;
+        Ok(Verifier { glue, wasm: read(WASM_FILE)? })
+    }
+}
+
+/// Keep script text from closing its `<script>` element early
+fn script_text(text: &str) -> String {
+    text.replace("</script", "<\\/script")
+}
+
+/// Markup that makes an exported page verify itself
+///
+/// # Arguments
+/// * `archive` - Bytes of the original `.tdf`
+/// * `file_name` - Name offered when the archive is downloaded
+/// * `root_hash` - Root hash computed at export time, shown and compared
+/// * `verifier` - The `tdf-wasm` verifier
+///
+/// # Returns
+/// The verification panel and scripts, to place after the page's `<main>`
+pub fn self_verifying_trailer(archive: &[u8], file_name: &str, root_hash: &str, verifier: &Verifier) -> String {
+    format!(
+        "<style>\n{styles}</style>\n\
+         <aside id=\"tdf-verification\" class=\"tdf-verification\" data-root-hash=\"{root}\" data-file-name=\"{name}\">\n\
+         <p class=\"tdf-verification-status\">Verifying document integrity...</p>\n\
+         <noscript><p>Enable JavaScript to verify this document, or run <code>tdf verify</code> on the original.</p></noscript>\n\
+         <ul class=\"tdf-verification-details\"></ul>\n</aside>\n\
+         <script type=\"application/octet-stream\" id=\"tdf-archive\">{archive}</script>\n\
+         <script type=\"application/wasm\" id=\"tdf-verifier-wasm\">{wasm}</script>\n\
+         <script type=\"module\">\n{glue}\n{script}</script>\n",
+        styles = VERIFIER_STYLES,
+        root = escape(root_hash),
+        name = escape(file_name),
+        archive = STANDARD.encode(archive),
+        wasm = STANDARD.encode(&verifier.wasm),
+        glue = script_text(&verifier.glue),
+        script = script_text(VERIFIER_SCRIPT),
+    )
+}
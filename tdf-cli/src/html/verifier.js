@@ -0,0 +1,69 @@
+// Self-verification for HTML exports. Runs after the tdf-wasm glue in the
+// same module script, so `initSync`, `verify_document` and `render_html`
+// are in scope. Everything needed is embedded in the page; nothing is fetched.
+(() => {
+    const panel = document.getElementById('tdf-verification');
+    const status = panel.querySelector('.tdf-verification-status');
+    const details = panel.querySelector('.tdf-verification-details');
+    document.body.prepend(panel);
+
+    const bytes = (id) => {
+        const text = atob(document.getElementById(id).textContent.trim());
+        const out = new Uint8Array(text.length);
+        for (let i = 0; i < text.length; i++) out[i] = text.charCodeAt(i);
+        return out;
+    };
+    const row = (label, value) => {
+        const item = document.createElement('li');
+        const strong = document.createElement('strong');
+        strong.textContent = label + ': ';
+        item.append(strong, value);
+        details.appendChild(item);
+    };
+    const report = (state, message) => {
+        panel.className = 'tdf-verification ' + state;
+        status.textContent = message;
+    };
+
+    try {
+        initSync({ module: bytes('tdf-verifier-wasm') });
+        const archive = bytes('tdf-archive');
+        const result = verify_document(archive);
+        const expected = panel.dataset.rootHash;
+
+        row('Root hash', result.root_hash);
+        row('Signatures', String(result.signature_count) +
+            (result.signature_count > 0 ? ' (check signers with `tdf verify --key` on the original)' : ''));
+        for (const error of result.errors) row('Error', error);
+
+        if (!result.integrity_valid) {
+            report('invalid', 'Integrity check FAILED: the embedded document has been modified.');
+            return;
+        }
+        if (result.root_hash !== expected) {
+            report('invalid', 'Root hash does not match the one this page was exported with.');
+            return;
+        }
+
+        // Show the content rendered from the verified archive, and say if it
+        // differs from what the page was shipped with
+        const main = document.getElementById('tdf-document');
+        const verified = document.createElement('template');
+        verified.innerHTML = render_html(archive);
+        const matches = verified.innerHTML === main.innerHTML;
+        main.replaceChildren(verified.content);
+        if (matches) {
+            report('valid', 'Integrity verified: the content shown is exactly the signed document.');
+        } else {
+            report('invalid', 'The page was altered after export; now showing the verified document content.');
+        }
+
+        const link = document.createElement('a');
+        link.href = URL.createObjectURL(new Blob([archive], { type: 'application/zip' }));
+        link.download = panel.dataset.fileName;
+        link.textContent = 'Download ' + panel.dataset.fileName;
+        row('Original', link);
+    } catch (error) {
+        report('invalid', 'Verification could not run: ' + error);
+    }
+})();
@@ -1,4 +1,6 @@
 mod commands;
+mod html;
+mod pdf;
 mod utils;
 
 use clap::{Parser, Subcommand};
@@ -27,6 +29,97 @@ enum WorkflowCommand {
     },
 }
 
+#[derive(Subcommand)]
+enum FormCommand {
+    /// Fill in a form template and add the signed submission to it
+    Fill {
+        /// TDF form template
+        template: PathBuf,
+        /// JSON object of field id to value
+        values: PathBuf,
+        /// Output TDF file (default: <name>.submitted.tdf)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Filler's private key (secp256k1 for *.secp256k1.* files)
+        #[arg(long)]
+        key: PathBuf,
+        /// Filler's signer ID (DID format)
+        #[arg(long)]
+        signer_id: String,
+        /// Filler's name
+        #[arg(long)]
+        signer_name: String,
+    },
+    /// Extract the submissions of a form as JSON
+    Extract {
+        /// TDF form with submissions
+        document: PathBuf,
+        /// Output JSON file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Filler's public key to verify submission signatures with
+        #[arg(long)]
+        key: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeyCommand {
+    /// Encrypt an unencrypted private key file (raw, PEM or JWK) in place
+    Migrate {
+        /// Private key file
+        path: PathBuf,
+        /// The key is a secp256k1 key (detected from *.secp256k1.signing)
+        #[arg(long)]
+        secp256k1: bool,
+        /// Passphrase KDF: argon2id or scrypt
+        #[arg(long, default_value = "argon2id")]
+        kdf: String,
+    },
+    /// Export a key as PEM (PKCS#8/SPKI), DER, JWK or raw bytes
+    Export {
+        /// Key file (any supported format)
+        input: PathBuf,
+        /// Output format: pem, der, jwk, raw
+        #[arg(long, default_value = "pem")]
+        format: String,
+        /// Export only the public key
+        #[arg(long)]
+        public: bool,
+        /// Output file (PEM and JWK default to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Raw 32-byte input keys are secp256k1
+        #[arg(long)]
+        secp256k1: bool,
+    },
+    /// Import a PEM, DER, JWK or raw key as TDF key files
+    Import {
+        /// Key file to import
+        input: PathBuf,
+        /// Key name prefix
+        #[arg(short, long, default_value = "tdf-key")]
+        name: String,
+        /// Output directory (default: current directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Raw 32-byte input keys are secp256k1
+        #[arg(long)]
+        secp256k1: bool,
+        /// Passphrase KDF for the private key file: argon2id or scrypt
+        #[arg(long, default_value = "argon2id")]
+        kdf: String,
+    },
+    /// Show the fingerprint and did:key of a key
+    Fingerprint {
+        /// Key file (any supported format)
+        input: PathBuf,
+        /// Raw 32-byte input keys are secp256k1
+        #[arg(long)]
+        secp256k1: bool,
+    },
+}
+
 #[derive(Parser)]
 #[command(name = "tdf")]
 #[command(about = "TDF (TrustDoc Financial) format tool")]
@@ -34,6 +127,12 @@ enum WorkflowCommand {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Read private key passphrases from this environment variable
+    #[arg(long, global = true)]
+    passphrase_env: Option<String>,
+    /// Read private key passphrases from this file descriptor (one line per key)
+    #[arg(long, global = true, conflicts_with = "passphrase_env")]
+    passphrase_fd: Option<i32>,
 }
 
 #[derive(Subcommand)]
@@ -51,18 +150,36 @@ enum Commands {
         /// Signer name
         #[arg(long)]
         signer_name: Option<String>,
-        /// Path to signing key file (Ed25519 private key)
+        /// Path to signing key file (Ed25519 private key: TDF key file, PKCS#8 PEM/DER or JWK)
         #[arg(long)]
         key: Option<PathBuf>,
         /// Use manual timestamp (local system time)
         #[arg(long)]
         timestamp_manual: bool,
+        /// Signer X.509 certificate chain (PEM or DER, leaf first)
+        #[arg(long)]
+        cert: Option<PathBuf>,
+        /// Sign with a remote signing service (token from TDF_SIGNER_TOKEN)
+        #[arg(long)]
+        remote_signer: Option<String>,
+        /// Sign with a key on a PKCS#11 token (PIN from TDF_PKCS11_PIN)
+        #[arg(long)]
+        pkcs11_module: Option<PathBuf>,
+        /// PKCS#11 token label
+        #[arg(long)]
+        pkcs11_token: Option<String>,
+        /// PKCS#11 key label
+        #[arg(long)]
+        pkcs11_key: Option<String>,
+        /// Commit to each block and table cell so content can be redacted after signing
+        #[arg(long)]
+        redactable: bool,
     },
     /// Verify integrity and signatures of a TDF document
     Verify {
         /// TDF file to verify
         document: PathBuf,
-        /// Path to verifying key file (Ed25519 public key)
+        /// Path to verifying key file (Ed25519 public key: raw, SPKI PEM/DER or JWK)
         #[arg(short, long)]
         key: Option<PathBuf>,
         /// Security tier: micro, standard, extended, permissive
@@ -86,6 +203,21 @@ enum Commands {
         /// Skip revocation checking
         #[arg(long)]
         skip_revocation: bool,
+        /// Trusted root certificates for signer certificate chains (PEM or DER)
+        #[arg(long)]
+        trust_anchors: Option<PathBuf>,
+        /// Resolve signer DIDs (did:key, did:web) and verify against their DID documents
+        #[arg(long)]
+        resolve_did: bool,
+        /// Local did:web document cache directory (<dir>/<host>/<path>/did.json)
+        #[arg(long)]
+        did_cache: Option<PathBuf>,
+        /// Resolve did:web only from --did-cache (no network access)
+        #[arg(long)]
+        did_offline: bool,
+        /// Recipient private key (Ed25519) to report which encrypted sections it opens
+        #[arg(short, long)]
+        identity: Option<PathBuf>,
     },
     /// Extract structured data from a TDF document
     Extract {
@@ -100,13 +232,183 @@ enum Commands {
         /// TDF file
         document: PathBuf,
     },
-    /// Export TDF document to PDF
+    /// Export TDF document to PDF (with verification appendix and the original attached), HTML, Markdown, DOCX, or its tagged facts as XBRL or inline XBRL
     Export {
         /// TDF file to export
         document: PathBuf,
-        /// Output PDF file
+        /// Output file (default: <name>.pdf, .html, .md, .docx, .xbrl or .xhtml)
         #[arg(short, long)]
         output: Option<PathBuf>,
+        /// Output format: pdf, html, md, docx, xbrl or ixbrl
+        #[arg(long, default_value = "pdf")]
+        format: String,
+        /// Verifying key of a signer, used to check their signature for the appendix
+        #[arg(short, long)]
+        key: Option<PathBuf>,
+        /// External revocation list (CBOR) to check signers against
+        #[arg(long)]
+        revocation_list: Option<PathBuf>,
+        /// Trusted root certificates for signer certificate chains (PEM or DER)
+        #[arg(long)]
+        trust_anchors: Option<PathBuf>,
+        /// Leave out the verification appendix
+        #[arg(long)]
+        no_appendix: bool,
+        /// Do not attach the original .tdf to the PDF
+        #[arg(long)]
+        no_attach: bool,
+        /// HTML: embed the archive and the wasm verifier so the page verifies itself offline
+        #[arg(long)]
+        self_verifying: bool,
+        /// wasm-pack output directory of tdf-wasm (default: tdf-wasm/pkg)
+        #[arg(long)]
+        verifier: Option<PathBuf>,
+    },
+    /// Export document tables as typed CSV, XLSX (one sheet per table) or Parquet
+    ExportTables {
+        /// TDF file to export from
+        document: PathBuf,
+        /// Output: workbook for xlsx (default: <name>.xlsx), directory for csv and parquet (default: .)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Output format: csv, xlsx or parquet
+        #[arg(long, default_value = "csv")]
+        format: String,
+        /// Export only this table id; repeatable
+        #[arg(long)]
+        table: Vec<String>,
+    },
+    /// Encrypt a TDF document for recipients (Ed25519 keys and/or a passphrase)
+    Encrypt {
+        /// TDF file to encrypt
+        document: PathBuf,
+        /// Output TDF file (default: <name>.encrypted.tdf)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Recipient Ed25519 public key (raw, SPKI PEM/DER or JWK); repeatable
+        #[arg(short, long)]
+        recipient: Vec<PathBuf>,
+        /// Also encrypt to a passphrase (from TDF_DOCUMENT_PASSPHRASE or prompted)
+        #[arg(long)]
+        passphrase: bool,
+        /// Encrypt only these sections (by id) instead of the whole document; repeatable
+        #[arg(long)]
+        section: Vec<String>,
+        /// Audience group name recorded for --section (e.g. directors)
+        #[arg(long, default_value = "recipients")]
+        group: String,
+        /// Content cipher: aes-256-gcm or xchacha20-poly1305
+        #[arg(long, default_value = "aes-256-gcm")]
+        cipher: String,
+        /// Passphrase KDF: argon2id or scrypt
+        #[arg(long, default_value = "argon2id")]
+        kdf: String,
+        /// Signing key for the encrypted archive (original signatures cannot be kept)
+        #[arg(long)]
+        key: Option<PathBuf>,
+        /// Signer ID (DID format)
+        #[arg(long)]
+        signer_id: Option<String>,
+        /// Signer name
+        #[arg(long)]
+        signer_name: Option<String>,
+    },
+    /// Decrypt an encrypted TDF document into a plain archive
+    Decrypt {
+        /// Encrypted TDF file
+        document: PathBuf,
+        /// Output TDF file (default: <name>.decrypted.tdf)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Recipient private key (Ed25519); uses a passphrase when omitted
+        #[arg(short, long)]
+        identity: Option<PathBuf>,
+        /// Signing key for the decrypted archive
+        #[arg(long)]
+        key: Option<PathBuf>,
+        /// Signer ID (DID format)
+        #[arg(long)]
+        signer_id: Option<String>,
+        /// Signer name
+        #[arg(long)]
+        signer_name: Option<String>,
+    },
+    /// Redact blocks or table cells of a redactable document, keeping its signatures valid
+    Redact {
+        /// Redactable TDF file
+        document: PathBuf,
+        /// Output TDF file (default: <name>.redacted.tdf)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Block id (or <section id>/<index> for blocks without an id); repeatable
+        #[arg(long)]
+        block: Vec<String>,
+        /// Table cell as <table id>/<row>/<column id>; repeatable
+        #[arg(long)]
+        cell: Vec<String>,
+    },
+    /// Create a successor of a TDF document, linked to it by id and root hash
+    Revise {
+        /// TDF document being superseded
+        previous: PathBuf,
+        /// Input JSON file for the new revision
+        input: PathBuf,
+        /// Output TDF file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Reason for the revision (e.g. "Corrected Q3 revenue")
+        #[arg(long)]
+        reason: String,
+        /// Path to signing key file
+        #[arg(long)]
+        key: Option<PathBuf>,
+        /// Signer ID (DID format)
+        #[arg(long)]
+        signer_id: Option<String>,
+        /// Signer name
+        #[arg(long)]
+        signer_name: Option<String>,
+        /// Signer X.509 certificate chain (PEM or DER, leaf first)
+        #[arg(long)]
+        cert: Option<PathBuf>,
+        /// Commit to each block and table cell so content can be redacted after signing
+        #[arg(long)]
+        redactable: bool,
+    },
+    /// Check the revision chain across a set of TDF documents (forks, gaps, mismatched links)
+    History {
+        /// TDF documents to check
+        #[arg(required = true)]
+        documents: Vec<PathBuf>,
+    },
+    /// Show what changed between two TDF documents (content, manifest, styles, assets, signatures)
+    Diff {
+        /// Old TDF document
+        old: PathBuf,
+        /// New TDF document
+        new: PathBuf,
+        /// Output format: text, json or html
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check a TDF document or JSON input for content errors (ids, assets, diagram edges, table cells, layout)
+    Lint {
+        /// TDF file, or JSON input of `tdf create`
+        input: PathBuf,
+        /// Output format: text or json
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Fail on warnings as well as errors
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Fill in form templates and extract their signed submissions
+    Form {
+        #[command(subcommand)]
+        form_cmd: FormCommand,
     },
     /// Multi-party signing workflow
     Workflow {
@@ -125,8 +427,16 @@ enum Commands {
         /// Use secp256k1 algorithm (Web3 compatible) instead of Ed25519
         #[arg(long)]
         secp256k1: bool,
+        /// Passphrase KDF for the private key file: argon2id or scrypt
+        #[arg(long, default_value = "argon2id")]
+        kdf: String,
     },
-    /// Import file(s) and convert to TDF (supports CSV, XLSX, DOCX, PPTX, TXT, MD, PDF)
+    /// Manage key files: migrate, export, import, fingerprint
+    Key {
+        #[command(subcommand)]
+        key_cmd: KeyCommand,
+    },
+    /// Import file(s) and convert to TDF (supports CSV, XLSX, DOCX, PPTX, TXT, MD, PDF, XBRL)
     Import {
         /// Input file or folder containing files to convert
         input: PathBuf,
@@ -139,7 +449,7 @@ enum Commands {
         /// Signer name
         #[arg(long)]
         signer_name: Option<String>,
-        /// Path to signing key file (Ed25519 private key)
+        /// Path to signing key file (Ed25519 private key: TDF key file, PKCS#8 PEM/DER or JWK)
         #[arg(long)]
         key: Option<PathBuf>,
         /// Batch mode: convert all supported files in folder
@@ -161,6 +471,18 @@ enum Commands {
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
+    /// Run the reference remote signing server (token from TDF_SIGNER_TOKEN)
+    SignerServer {
+        /// Path to signing key file
+        #[arg(long)]
+        key: PathBuf,
+        /// The key is a secp256k1 key
+        #[arg(long)]
+        secp256k1: bool,
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8787")]
+        listen: String,
+    },
     /// Check revocation status of keys in a TDF document
     CheckRevocation {
         /// TDF file to check
@@ -173,6 +495,11 @@ enum Commands {
 
 fn main() {
     let cli = Cli::parse();
+    if let Some(var) = cli.passphrase_env.clone() {
+        utils::set_passphrase_source(utils::PassphraseSource::Env(var));
+    } else if let Some(fd) = cli.passphrase_fd {
+        utils::set_passphrase_source(utils::PassphraseSource::Fd(fd));
+    }
 
     let result = match cli.command {
         Commands::Create {
@@ -182,7 +509,31 @@ fn main() {
             signer_name,
             key,
             timestamp_manual,
-        } => commands::create::create_document(input, output, signer_id, signer_name, key, timestamp_manual),
+            cert,
+            remote_signer,
+            pkcs11_module,
+            pkcs11_token,
+            pkcs11_key,
+            redactable,
+        } => {
+            let signer_options = commands::create::SignerOptions {
+                cert,
+                remote_signer,
+                pkcs11_module,
+                pkcs11_token,
+                pkcs11_key,
+                redactable,
+            };
+            commands::create::create_document(
+                input,
+                output,
+                signer_id,
+                signer_name,
+                key,
+                timestamp_manual,
+                signer_options,
+            )
+        }
         Commands::Verify {
             document,
             key,
@@ -193,9 +544,18 @@ fn main() {
             lenient,
             enforce_whitelist,
             skip_revocation,
+            trust_anchors,
+            resolve_did,
+            did_cache,
+            did_offline,
+            identity,
         } => {
-            commands::verify::verify_document(
-                document,
+            let did_options = commands::verify::DidOptions {
+                enabled: resolve_did || did_cache.is_some() || did_offline,
+                cache_dir: did_cache,
+                offline: did_offline,
+            };
+            let options = commands::verify::VerifyOptions {
                 key,
                 security_tier,
                 revocation_list,
@@ -204,11 +564,98 @@ fn main() {
                 lenient,
                 enforce_whitelist,
                 skip_revocation,
-            )
+                trust_anchors,
+                did_options,
+                identity,
+            };
+            commands::verify::verify_document(document, options)
         }
         Commands::Extract { document, output } => commands::extract::extract_data(document, output),
         Commands::Info { document } => commands::info::show_info(document),
-        Commands::Export { document, output } => commands::export::export_to_pdf(document, output),
+        Commands::Export {
+            document,
+            output,
+            format,
+            key,
+            revocation_list,
+            trust_anchors,
+            no_appendix,
+            no_attach,
+            self_verifying,
+            verifier,
+        } => format.parse().and_then(|format| {
+            let options = commands::export::ExportOptions {
+                key,
+                revocation_list,
+                trust_anchors,
+                no_appendix,
+                no_attach,
+                self_verifying,
+                verifier,
+            };
+            commands::export::export_document(document, output, format, options)
+        }),
+        Commands::ExportTables {
+            document,
+            output,
+            format,
+            table,
+        } => commands::export_tables::export_document_tables(document, output, &format, table),
+        Commands::Encrypt {
+            document,
+            output,
+            recipient,
+            passphrase,
+            section,
+            group,
+            cipher,
+            kdf,
+            key,
+            signer_id,
+            signer_name,
+        } => cipher.parse().and_then(|cipher| {
+            let resign = commands::encrypt::ResignOptions { key, signer_id, signer_name };
+            kdf.parse().and_then(|kdf| {
+                let recipients = commands::encrypt::RecipientOptions { keys: recipient, passphrase, kdf };
+                let sections = commands::encrypt::SectionOptions { ids: section, group };
+                commands::encrypt::encrypt_document(document, output, recipients, sections, cipher, resign)
+            })
+        }),
+        Commands::Decrypt { document, output, identity, key, signer_id, signer_name } => {
+            let resign = commands::encrypt::ResignOptions { key, signer_id, signer_name };
+            commands::encrypt::decrypt_document(document, output, identity, resign)
+        }
+        Commands::Redact { document, output, block, cell } => {
+            commands::redact::redact_document(document, output, block, cell)
+        }
+        Commands::Revise {
+            previous,
+            input,
+            output,
+            reason,
+            key,
+            signer_id,
+            signer_name,
+            cert,
+            redactable,
+        } => {
+            let resign = commands::encrypt::ResignOptions { key, signer_id, signer_name };
+            let signer_options = commands::create::SignerOptions { cert, redactable, ..Default::default() };
+            commands::revision::revise_document(previous, input, output, reason, resign, signer_options)
+        }
+        Commands::History { documents } => commands::revision::show_history(documents),
+        Commands::Diff { old, new, format, output } => {
+            format.parse().and_then(|format| commands::diff::diff_documents(old, new, format, output))
+        }
+        Commands::Lint { input, format, deny_warnings } => commands::lint::lint(input, &format, deny_warnings),
+        Commands::Form { form_cmd } => match form_cmd {
+            FormCommand::Fill { template, values, output, key, signer_id, signer_name } => {
+                commands::form::fill_form(template, values, output, key, signer_id, signer_name)
+            }
+            FormCommand::Extract { document, output, key } => {
+                commands::form::extract_submissions(document, output, key)
+            }
+        },
         Commands::Workflow { workflow_cmd } => {
             match workflow_cmd {
                 Some(WorkflowCommand::Create { document, output, order, signers }) => {
@@ -223,12 +670,26 @@ fn main() {
                 }
             }
         },
-        Commands::Keygen { output, name, secp256k1 } => {
-            if secp256k1 {
-                commands::keygen::generate_keypair_secp256k1(output, name)
-            } else {
-                commands::keygen::generate_keypair_ed25519(output, name)
+        Commands::Keygen { output, name, secp256k1, kdf } => {
+            kdf.parse().and_then(|kdf| {
+                if secp256k1 {
+                    commands::keygen::generate_keypair_secp256k1(output, name, kdf)
+                } else {
+                    commands::keygen::generate_keypair_ed25519(output, name, kdf)
+                }
+            })
+        },
+        Commands::Key { key_cmd } => match key_cmd {
+            KeyCommand::Migrate { path, secp256k1, kdf } => {
+                kdf.parse().and_then(|kdf| commands::key::migrate_key(path, secp256k1, kdf))
             }
+            KeyCommand::Export { input, format, public, output, secp256k1 } => format
+                .parse()
+                .and_then(|format| commands::key::export_key(input, format, public, output, secp256k1)),
+            KeyCommand::Import { input, name, output, secp256k1, kdf } => {
+                kdf.parse().and_then(|kdf| commands::key::import_key(input, name, output, secp256k1, kdf))
+            }
+            KeyCommand::Fingerprint { input, secp256k1 } => commands::key::show_fingerprint(input, secp256k1),
         },
         Commands::Import { input, output, signer_id, signer_name, key, batch } => {
             if batch || input.is_dir() {
@@ -240,6 +701,9 @@ fn main() {
         Commands::Revoke { key_id, reason, authority, output } => {
             commands::revoke::revoke_key(key_id, reason, authority, output)
         },
+        Commands::SignerServer { key, secp256k1, listen } => {
+            commands::signer_server::run_signer_server(key, secp256k1, listen)
+        },
         Commands::CheckRevocation { document, revocation_list } => {
             commands::revoke::check_revocation(document, revocation_list)
         },
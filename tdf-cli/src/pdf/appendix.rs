@@ -0,0 +1,224 @@
+//! Verification appendix for exported PDFs
+//!
+//! A final page restating what `tdf verify` found at export time: the
+//! Merkle root hash, each signature with its algorithm, timestamp and
+//! revocation status, and a QR code of the document id and root hash so a
+//! printed copy can be matched against the attached `.tdf`.
+
+use super::fonts::Face;
+use super::{line_height, rgb, Align, PdfRenderer, BODY_SIZE};
+use chrono::{DateTime, Utc};
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::Polygon;
+use tdf_core::qr::QrCode;
+
+/// Edge length of the QR code including its quiet zone, in millimetres
+const QR_SIZE: f32 = 40.0;
+/// Light modules a reader needs around the symbol
+const QUIET_ZONE: usize = 4;
+
+/// One signature as checked at export time
+#[derive(Debug, Clone)]
+pub struct SignerSummary {
+    pub name: String,
+    pub id: String,
+    pub algorithm: String,
+    /// Signing time, with the timestamp authority when there is one
+    pub timestamp: String,
+    /// Outcome of signature verification
+    pub signature: String,
+    /// Revocation status of the signer
+    pub revocation: String,
+}
+
+/// Trust information shown on the verification appendix
+#[derive(Debug, Clone)]
+pub struct VerificationSummary {
+    pub document_id: String,
+    pub root_hash: String,
+    pub integrity_valid: bool,
+    pub exported_at: DateTime<Utc>,
+    /// File name of the attached `.tdf`, if it is attached
+    pub attachment: Option<String>,
+    pub signers: Vec<SignerSummary>,
+}
+
+impl VerificationSummary {
+    /// Text encoded in the QR code
+    pub fn qr_payload(&self) -> String {
+        format!("tdf:verify?id={}&root={}", self.document_id, self.root_hash)
+    }
+}
+
+impl PdfRenderer<'_> {
+    /// Add the verification appendix on a new page
+    pub fn verification_appendix(&mut self, summary: &VerificationSummary) {
+        self.new_page();
+        self.text_block("Verification Appendix", Face::Bold, 16.0, 0.0, Align::Left);
+        self.y -= 2.0;
+
+        let source = match &summary.attachment {
+            Some(name) => format!(
+                "The original document is attached to this PDF as {}. Run `tdf verify {}` on it to check these \
+                 results independently; the QR code encodes the document id and root hash.",
+                name, name
+            ),
+            None => "The QR code encodes the document id and root hash; compare them with the original .tdf \
+                     using `tdf verify`."
+                .to_string(),
+        };
+        self.text_block(
+            &format!(
+                "This page records the integrity and signature status of the source document when it was \
+                 exported. {}",
+                source
+            ),
+            Face::Regular,
+            BODY_SIZE,
+            0.0,
+            Align::Left,
+        );
+        self.y -= 4.0;
+
+        let properties = vec![
+            vec!["Document ID".to_string(), summary.document_id.clone()],
+            vec!["Exported at".to_string(), summary.exported_at.format("%Y-%m-%d %H:%M:%S UTC").to_string()],
+            vec![
+                "Integrity".to_string(),
+                if summary.integrity_valid { "Valid" } else { "INVALID - content does not match its hashes" }.to_string(),
+            ],
+            vec!["Root hash (SHA-256)".to_string(), summary.root_hash.clone()],
+        ];
+        let headers = ["Property".to_string(), "Value".to_string()];
+        self.grid(None, &headers, &properties, None, &[Align::Left, Align::Left]);
+
+        match QrCode::encode(summary.qr_payload().as_bytes()) {
+            Ok(code) => self.qr_code(&code),
+            Err(_) => self.text_block("(Document id too long for a QR code)", Face::Italic, 8.0, 0.0, Align::Left),
+        }
+
+        if summary.signers.is_empty() {
+            self.text_block("The document is not signed.", Face::Bold, BODY_SIZE, 0.0, Align::Left);
+            return;
+        }
+        let headers: Vec<String> = ["Signer", "Algorithm", "Timestamp", "Signature", "Revocation"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let rows: Vec<Vec<String>> = summary
+            .signers
+            .iter()
+            .map(|s| {
+                vec![
+                    format!("{} ({})", s.name, s.id),
+                    s.algorithm.clone(),
+                    s.timestamp.clone(),
+                    s.signature.clone(),
+                    s.revocation.clone(),
+                ]
+            })
+            .collect();
+        self.grid(Some("Signatures"), &headers, &rows, None, &[Align::Left; 5]);
+    }
+
+    /// Draw `code` at the left margin with a caption below it
+    fn qr_code(&mut self, code: &QrCode) {
+        let caption_height = line_height(8.0);
+        self.ensure(QR_SIZE + caption_height + 4.0);
+        let module = QR_SIZE / (code.size() + 2 * QUIET_ZONE) as f32;
+        let left = self.page.left + QUIET_ZONE as f32 * module;
+        let top = self.y - QUIET_ZONE as f32 * module;
+
+        // One rectangle per horizontal run of dark modules
+        let mut rings = Vec::new();
+        for row in 0..code.size() {
+            let mut x = 0;
+            while x < code.size() {
+                if !code.is_dark(x, row) {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < code.size() && code.is_dark(x, row) {
+                    x += 1;
+                }
+                let (x0, x1) = (left + start as f32 * module, left + x as f32 * module);
+                let (y0, y1) = (top - row as f32 * module, top - (row + 1) as f32 * module);
+                rings.push(Self::points(&[(x0, y0), (x1, y0), (x1, y1), (x0, y1)]));
+            }
+        }
+        let layer = self.layer();
+        layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+        layer.add_polygon(Polygon { rings, mode: PaintMode::Fill, winding_order: WindingOrder::NonZero });
+
+        self.y -= QR_SIZE;
+        self.text_block("Document id and root hash", Face::Italic, 8.0, 0.0, Align::Left);
+        self.y -= 4.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::tests::page_text;
+    use crate::pdf::PageGeometry;
+    use std::collections::HashMap;
+
+    fn summary(signers: Vec<SignerSummary>) -> VerificationSummary {
+        VerificationSummary {
+            document_id: "doc-1".to_string(),
+            root_hash: "ab".repeat(32),
+            integrity_valid: true,
+            exported_at: Utc::now(),
+            attachment: Some("report.tdf".to_string()),
+            signers,
+        }
+    }
+
+    fn appendix(summary: &VerificationSummary) -> Vec<Vec<(f32, String)>> {
+        let assets = HashMap::new();
+        let mut renderer = PdfRenderer::new("Test", PageGeometry::default(), &assets).unwrap();
+        renderer.verification_appendix(summary);
+        page_text(&renderer.finish().save_to_bytes().unwrap())
+    }
+
+    fn contains(page: &[(f32, String)], needle: &str) -> bool {
+        page.iter().any(|(_, text)| text.contains(needle))
+    }
+
+    #[test]
+    fn test_appendix_on_its_own_last_page() {
+        let signer = SignerSummary {
+            name: "CFO".to_string(),
+            id: "did:web:cfo.acme.com".to_string(),
+            algorithm: "Ed25519".to_string(),
+            timestamp: "2026-01-01 00:00:00 UTC".to_string(),
+            signature: "Valid".to_string(),
+            revocation: "Not revoked at export".to_string(),
+        };
+        let pages = appendix(&summary(vec![signer]));
+        // The renderer starts with one page; the appendix always begins a new one
+        assert_eq!(pages.len(), 2);
+        let page = &pages[1];
+        assert!(contains(page, "Verification Appendix"));
+        assert!(contains(page, "doc-1"));
+        assert!(contains(page, "report.tdf"));
+        assert!(contains(page, "CFO (did:web:cfo.acme.com)"));
+        assert!(contains(page, "Valid"));
+        assert!(contains(page, "Document id and root hash"));
+    }
+
+    #[test]
+    fn test_unsigned_document_noted() {
+        let pages = appendix(&summary(Vec::new()));
+        assert!(contains(pages.last().unwrap(), "The document is not signed."));
+        assert!(!contains(pages.last().unwrap(), "Signatures"));
+    }
+
+    #[test]
+    fn test_qr_payload_fits_a_code() {
+        let summary = summary(Vec::new());
+        assert_eq!(summary.qr_payload(), format!("tdf:verify?id=doc-1&root={}", "ab".repeat(32)));
+        assert!(QrCode::encode(summary.qr_payload().as_bytes()).is_ok());
+    }
+}
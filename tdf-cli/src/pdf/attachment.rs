@@ -0,0 +1,136 @@
+//! Embedding the source `.tdf` in an exported PDF
+//!
+//! The archive is added as an associated source file: an `EmbeddedFile`
+//! stream behind a file specification whose `AFRelationship` is `Source`,
+//! listed both in the catalog's `EmbeddedFiles` name tree (so viewers show
+//! it as an attachment) and in the catalog's `AF` array. The exported PDF
+//! is not PDF/A: its standard fonts are not embedded and it carries no
+//! XMP metadata or output intent.
+
+use chrono::{DateTime, Utc};
+use printpdf::lopdf::{dictionary, Document, Object, Stream, StringFormat};
+use tdf_core::error::{TdfError, TdfResult};
+
+fn pdf_error(e: impl std::fmt::Display) -> TdfError {
+    TdfError::InvalidDocument(format!("PDF attachment error: {}", e))
+}
+
+/// PDF date string (`D:YYYYMMDDHHmmSSZ`)
+fn pdf_date(time: DateTime<Utc>) -> Object {
+    Object::string_literal(time.format("D:%Y%m%d%H%M%SZ").to_string())
+}
+
+/// Attach `data` to the PDF in `pdf` as its source file
+///
+/// # Arguments
+/// * `pdf` - Serialized PDF
+/// * `name` - File name shown by PDF viewers
+/// * `data` - Bytes of the attached file
+/// * `modified` - Modification date recorded for the attachment
+///
+/// # Returns
+/// The serialized PDF with the attachment
+pub fn attach_source(pdf: &[u8], name: &str, data: &[u8], modified: DateTime<Utc>) -> TdfResult<Vec<u8>> {
+    let mut document = Document::load_mem(pdf).map_err(pdf_error)?;
+
+    let mut stream = Stream::new(
+        dictionary! {
+            "Type" => "EmbeddedFile",
+            "Subtype" => "application/zip",
+            "Params" => dictionary! {
+                "Size" => data.len() as i64,
+                "ModDate" => pdf_date(modified),
+            },
+        },
+        data.to_vec(),
+    );
+    // The archive is already deflated; compressing it again gains nothing
+    stream.allows_compression = false;
+    let file = document.add_object(stream);
+
+    let file_name = || Object::String(name.as_bytes().to_vec(), StringFormat::Literal);
+    let spec = document.add_object(dictionary! {
+        "Type" => "Filespec",
+        "F" => file_name(),
+        "UF" => file_name(),
+        "Desc" => Object::string_literal("Original TDF document"),
+        "AFRelationship" => "Source",
+        "EF" => dictionary! { "F" => file, "UF" => file },
+    });
+
+    let catalog = document.catalog_mut().map_err(pdf_error)?;
+    let tree = dictionary! {
+        "Names" => vec![file_name(), Object::Reference(spec)],
+    };
+    match catalog.get_mut(b"Names").and_then(Object::as_dict_mut) {
+        Ok(names) => names.set("EmbeddedFiles", tree),
+        Err(_) => catalog.set("Names", dictionary! { "EmbeddedFiles" => tree }),
+    }
+    catalog.set("AF", vec![Object::Reference(spec)]);
+    catalog.set("PageMode", "UseAttachments");
+
+    let mut output = Vec::new();
+    document.save_to(&mut output).map_err(pdf_error)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::tests::{page_text, render};
+    use serde_json::json;
+
+    fn pdf() -> Vec<u8> {
+        render(json!([{ "id": "s", "content": [{ "type": "paragraph", "text": "Attached" }] }]))
+    }
+
+    #[test]
+    fn test_source_attached_as_associated_file() {
+        let data = b"PK\x03\x04 archive bytes".to_vec();
+        let input = pdf();
+        let output = attach_source(&input, "report.tdf", &data, Utc::now()).unwrap();
+        let document = Document::load_mem(&output).unwrap();
+        let catalog = document.catalog().unwrap();
+
+        let af = catalog.get(b"AF").and_then(Object::as_array).unwrap();
+        let spec_id = af[0].as_reference().unwrap();
+        let spec = document.get_dictionary(spec_id).unwrap();
+        assert_eq!(spec.get(b"AFRelationship").and_then(Object::as_name_str).unwrap(), "Source");
+        assert_eq!(spec.get(b"UF").and_then(Object::as_str).unwrap(), b"report.tdf");
+
+        let names = catalog.get(b"Names").and_then(Object::as_dict).unwrap();
+        let tree = names.get(b"EmbeddedFiles").and_then(Object::as_dict).unwrap();
+        let entries = tree.get(b"Names").and_then(Object::as_array).unwrap();
+        assert_eq!(entries[0].as_str().unwrap(), b"report.tdf");
+        assert_eq!(entries[1].as_reference().unwrap(), spec_id);
+
+        let file_id = spec.get(b"EF").and_then(Object::as_dict).unwrap().get(b"F").unwrap().as_reference().unwrap();
+        let stream = document.get_object(file_id).and_then(Object::as_stream).unwrap();
+        assert_eq!(stream.content, data);
+        assert_eq!(stream.dict.get(b"Params").and_then(Object::as_dict).unwrap().get(b"Size").unwrap().as_i64().unwrap(), data.len() as i64);
+
+        // The rendered pages are unchanged
+        assert_eq!(page_text(&output), page_text(&input));
+    }
+
+    #[test]
+    fn test_existing_names_dictionary_kept() {
+        let mut document = Document::load_mem(&pdf()).unwrap();
+        document
+            .catalog_mut()
+            .unwrap()
+            .set("Names", dictionary! { "Dests" => dictionary! { "Names" => Vec::<Object>::new() } });
+        let mut input = Vec::new();
+        document.save_to(&mut input).unwrap();
+
+        let output = Document::load_mem(&attach_source(&input, "a.tdf", b"data", Utc::now()).unwrap()).unwrap();
+        let names = output.catalog().unwrap().get(b"Names").and_then(Object::as_dict).unwrap();
+        assert!(names.has(b"Dests"));
+        assert!(names.has(b"EmbeddedFiles"));
+    }
+
+    #[test]
+    fn test_invalid_pdf_rejected() {
+        assert!(attach_source(b"not a pdf", "a.tdf", b"data", Utc::now()).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Widths of the standard Helvetica and Courier fonts and greedy line
+//! wrapping
+//!
+//! The PDF base-14 fonts are not embedded, so printpdf has no metrics for
+//! them; the widths below are from the Adobe AFM files (units of 1/1000 em)
+//! for the printable ASCII range, plus the few WinAnsi characters we emit.
+//! Every Courier character is 600 units wide.
+
+/// Millimetres per PDF point
+pub const PT_TO_MM: f32 = 25.4 / 72.0;
+
+/// Helvetica widths for ' ' (32) through '~' (126)
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // ' ' - '/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, // '0' - '9'
+    278, 278, 584, 584, 584, 556, 1015, // ':' - '@'
+    667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, // 'A' - 'M'
+    722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, // 'N' - 'Z'
+    278, 278, 278, 469, 556, 333, // '[' - '`'
+    556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, // 'a' - 'm'
+    556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, // 'n' - 'z'
+    334, 260, 334, 584, // '{' - '~'
+];
+
+/// Helvetica-Bold widths for ' ' (32) through '~' (126)
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, // ' ' - '/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, // '0' - '9'
+    333, 333, 584, 584, 584, 611, 975, // ':' - '@'
+    722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, // 'A' - 'M'
+    722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, // 'N' - 'Z'
+    333, 278, 333, 584, 556, 333, // '[' - '`'
+    556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, // 'a' - 'm'
+    611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, // 'n' - 'z'
+    389, 280, 389, 584, // '{' - '~'
+];
+
+/// Font face used by the renderer (Helvetica-Oblique shares the regular widths)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    Regular,
+    Bold,
+    Italic,
+    /// Courier, for code
+    Mono,
+}
+
+/// Width of every Courier character
+pub const MONO_WIDTH: u16 = 600;
+
+fn char_width(face: Face, c: char) -> u16 {
+    let table = match face {
+        Face::Bold => &HELVETICA_BOLD,
+        Face::Regular | Face::Italic => &HELVETICA,
+        Face::Mono => return MONO_WIDTH,
+    };
+    match c {
+        ' '..='~' => table[c as usize - 32],
+        '\u{2022}' => 350,
+        '\u{2013}' | '\u{20ac}' | '\u{a3}' | '\u{a5}' => 556,
+        '\u{2014}' => 1000,
+        '\u{2018}' | '\u{2019}' => 222,
+        '\u{201c}' | '\u{201d}' => 333,
+        '\u{a0}' => 278,
+        _ => 556,
+    }
+}
+
+/// Width of `text` in millimetres at `size` points
+pub fn text_width(text: &str, face: Face, size: f32) -> f32 {
+    let units: u32 = text.chars().map(|c| char_width(face, c) as u32).sum();
+    units as f32 / 1000.0 * size * PT_TO_MM
+}
+
+/// Break `text` into lines no wider than `width` millimetres
+///
+/// Explicit newlines are kept; words wider than a whole line are split
+/// between characters. An empty string yields no lines.
+pub fn wrap(text: &str, face: Face, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if text_width(&candidate, face, size) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            if text_width(word, face, size) <= width {
+                line = word.to_string();
+                continue;
+            }
+            for c in word.chars() {
+                line.push(c);
+                if text_width(&line, face, size) > width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Text set in one face
+pub type Span = (String, Face);
+
+/// Append `text` in `face` to `spans`, extending the last span if it has
+/// the same face
+fn push_span(spans: &mut Vec<Span>, text: &str, face: Face) {
+    match spans.last_mut() {
+        Some((last, last_face)) if *last_face == face => last.push_str(text),
+        _ => spans.push((text.to_string(), face)),
+    }
+}
+
+/// Break text set in several faces into lines no wider than `width`
+///
+/// Works like `wrap`, with words that change face kept together. An input
+/// without text yields no lines.
+pub fn wrap_spans(spans: &[Span], size: f32, width: f32) -> Vec<Vec<Span>> {
+    if spans.iter().all(|(text, _)| text.is_empty()) {
+        return Vec::new();
+    }
+    // Words of each paragraph, as spans
+    let mut paragraphs: Vec<Vec<Vec<Span>>> = vec![Vec::new()];
+    let mut word: Vec<Span> = Vec::new();
+    for (text, face) in spans {
+        for c in text.chars() {
+            if c == '\n' || c.is_whitespace() {
+                if !word.is_empty() {
+                    paragraphs.last_mut().expect("paragraph").push(std::mem::take(&mut word));
+                }
+                if c == '\n' {
+                    paragraphs.push(Vec::new());
+                }
+            } else {
+                push_span(&mut word, c.encode_utf8(&mut [0; 4]), *face);
+            }
+        }
+    }
+    if !word.is_empty() {
+        paragraphs.last_mut().expect("paragraph").push(word);
+    }
+
+    let span_width = |spans: &[Span]| spans.iter().map(|(text, face)| text_width(text, *face, size)).sum::<f32>();
+    let mut lines = Vec::new();
+    for paragraph in paragraphs {
+        let mut line: Vec<Span> = Vec::new();
+        let mut line_width = 0.0;
+        for word in paragraph {
+            let word_width = span_width(&word);
+            let space_face = line.last().map_or(Face::Regular, |(_, face)| *face);
+            let space = if line.is_empty() { 0.0 } else { text_width(" ", space_face, size) };
+            if line_width + space + word_width <= width {
+                if !line.is_empty() {
+                    push_span(&mut line, " ", space_face);
+                }
+                for (text, face) in &word {
+                    push_span(&mut line, text, *face);
+                }
+                line_width += space + word_width;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            line_width = 0.0;
+            for (text, face) in &word {
+                for c in text.chars() {
+                    let c_width = text_width(c.encode_utf8(&mut [0; 4]), *face, size);
+                    if line_width + c_width > width && !line.is_empty() && word_width > width {
+                        lines.push(std::mem::take(&mut line));
+                        line_width = 0.0;
+                    }
+                    push_span(&mut line, c.encode_utf8(&mut [0; 4]), *face);
+                    line_width += c_width;
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Width of the widest word in `text`, the narrowest a column can usefully be
+pub fn longest_word(text: &str, face: Face, size: f32) -> f32 {
+    text.split_whitespace()
+        .map(|w| text_width(w, face, size))
+        .fold(0.0, f32::max)
+}
@@ -0,0 +1,1481 @@
+//! PDF layout engine for `tdf export`
+//!
+//! Content flows over as many pages as it needs, using the page size,
+//! orientation and margins of the document's `Layout` (A4 portrait with
+//! 20mm margins when it has none). Text is wrapped with the Helvetica
+//! metrics in `fonts`, tables are drawn as grids whose header rows repeat
+//! on every page they span (merged cells span their columns and rows, and
+//! subtotal and total rows are bold), figures are embedded from the archive's
+//! assets, and diagrams and charts are drawn as vectors from the layouts
+//! in `tdf_core::diagram` and `tdf_core::chart`. Footnotes referenced with
+//! `{{fn:id}}` markers or footnote reference runs are numbered and set at
+//! the bottom of the page that first references them, and every page gets
+//! a "Page X of Y" footer. Bold and italic runs of paragraphs and list
+//! items are set in the matching Helvetica face and code in Courier.
+//! Nested lists are indented under their item, task items get a drawn
+//! checkbox, quotes are indented in italics and code blocks keep their
+//! line breaks on a shaded band. Form fields are drawn empty, as a
+//! labelled box to fill in by hand.
+//! Signed exports can end with a verification appendix (see `appendix`)
+//! and carry the original archive as an attachment (see `attachment`).
+
+mod appendix;
+mod attachment;
+mod fonts;
+
+pub use appendix::{SignerSummary, VerificationSummary};
+pub use attachment::attach_source;
+
+use fonts::{longest_word, text_width, wrap, wrap_spans, Face, Span, MONO_WIDTH, PT_TO_MM};
+use printpdf::image_crate::{self, DynamicImage, GenericImageView};
+use printpdf::{
+    BuiltinFont, Color, Image, ImageTransform, IndirectFontRef, Line, LineDashPattern, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference, Point, Polygon, Rgb,
+};
+use printpdf::path::{PaintMode, WindingOrder};
+use std::collections::{HashMap, HashSet};
+use tdf_core::content::{
+    covered_cells, header_layout, CellType, ChartAxes, ChartFormat, ChartType, ContentBlock, DiagramEdge, DiagramLayout, DiagramNode, DiagramShape, DiagramType, DocumentContent,
+    EdgeType, HeaderCell, InlineRun, InlineText, ListItem, Mark, RowRole, Section, TableColumn, TableRow,
+};
+use tdf_core::chart::{self, Anchor, ChartData, Shape};
+use tdf_core::diagram;
+use tdf_core::document::{Document, Layout, Orientation, PageSize};
+use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::form::{FieldKind, FormField};
+
+const BODY_SIZE: f32 = 10.0;
+const TABLE_SIZE: f32 = 8.5;
+const NOTE_SIZE: f32 = 8.0;
+const CODE_SIZE: f32 = 8.5;
+const CELL_PADDING: f32 = 1.5;
+/// Indent of an outlined table row's first cell per level
+const ROW_LEVEL_INDENT: f32 = 3.0;
+/// Gap between the text above a footnote area and its separator rule
+const NOTE_SEPARATOR: f32 = 3.0;
+/// Resolution used when placing images; the scale factor sets their size
+const IMAGE_DPI: f32 = 300.0;
+
+/// Line height in millimetres for text set at `size` points
+fn line_height(size: f32) -> f32 {
+    size * 1.35 * PT_TO_MM
+}
+
+/// Distance from the top of a line to its baseline
+fn ascent(size: f32) -> f32 {
+    line_height(size) * 0.78
+}
+
+fn rgb(r: f32, g: f32, b: f32) -> Color {
+    Color::Rgb(Rgb::new(r, g, b, None))
+}
+
+/// Parse a CSS-style length ("20mm", "2cm", "1in", "72pt", "96px") into millimetres
+///
+/// A bare number is taken as millimetres.
+pub fn parse_length(value: &str) -> TdfResult<f32> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f32 = number
+        .parse()
+        .map_err(|_| TdfError::InvalidDocument(format!("Invalid length '{}'", value)))?;
+    let factor = match unit.trim() {
+        "" | "mm" => 1.0,
+        "cm" => 10.0,
+        "in" => 25.4,
+        "pt" => PT_TO_MM,
+        "px" => 25.4 / 96.0,
+        other => {
+            return Err(TdfError::InvalidDocument(format!("Unknown length unit '{}' in '{}'", other, value)));
+        }
+    };
+    if !number.is_finite() || number < 0.0 {
+        return Err(TdfError::InvalidDocument(format!("Invalid length '{}'", value)));
+    }
+    Ok(number * factor)
+}
+
+/// Page size and margins in millimetres
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageGeometry {
+    pub width: f32,
+    pub height: f32,
+    pub top: f32,
+    pub bottom: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl Default for PageGeometry {
+    fn default() -> Self {
+        PageGeometry { width: 210.0, height: 297.0, top: 20.0, bottom: 20.0, left: 20.0, right: 20.0 }
+    }
+}
+
+impl PageGeometry {
+    /// Geometry of the document's page layout, or A4 portrait with 20mm margins
+    pub fn from_layout(layout: Option<&Layout>) -> TdfResult<Self> {
+        let Some(layout) = layout else {
+            return Ok(PageGeometry::default());
+        };
+        let pages = &layout.pages;
+        let (mut width, mut height) = match &pages.size {
+            PageSize::A4 => (210.0, 297.0),
+            PageSize::Letter => (215.9, 279.4),
+            PageSize::Legal => (215.9, 355.6),
+            PageSize::Custom { width, height } => (parse_length(width)?, parse_length(height)?),
+        };
+        if pages.orientation == Orientation::Landscape && width < height {
+            std::mem::swap(&mut width, &mut height);
+        }
+        let geometry = PageGeometry {
+            width,
+            height,
+            top: parse_length(&pages.margins.top)?,
+            bottom: parse_length(&pages.margins.bottom)?,
+            left: parse_length(&pages.margins.left)?,
+            right: parse_length(&pages.margins.right)?,
+        };
+        // Leave room for at least a few lines of text and a table column
+        if geometry.content_width() < 30.0 || geometry.content_height() < 40.0 {
+            return Err(TdfError::InvalidDocument(format!(
+                "Page layout leaves no room for content ({}x{}mm with margins {}/{}/{}/{}mm)",
+                width, height, geometry.top, geometry.right, geometry.bottom, geometry.left
+            )));
+        }
+        Ok(geometry)
+    }
+
+    pub fn content_width(&self) -> f32 {
+        self.width - self.left - self.right
+    }
+
+    pub fn content_height(&self) -> f32 {
+        self.height - self.top - self.bottom
+    }
+}
+
+/// Footnote texts and the numbers assigned to them in reading order
+#[derive(Debug, Default)]
+struct Footnotes {
+    text: HashMap<String, String>,
+    /// Footnotes referenced from the text; the rest are shown where they appear
+    referenced: HashSet<String>,
+    numbers: HashMap<String, usize>,
+    /// Numbers already set at the bottom of a page
+    placed: HashSet<usize>,
+}
+
+impl Footnotes {
+    fn collect(content: &DocumentContent) -> Self {
+        let mut notes = Footnotes::default();
+        let blocks = || content.sections.iter().flat_map(|s| s.content.iter());
+        for block in blocks() {
+            if let ContentBlock::Footnote { id, text } = block {
+                notes.text.insert(id.clone(), text.clone());
+            }
+        }
+        for block in blocks() {
+            let texts: Vec<&InlineText> = match block {
+                ContentBlock::Paragraph { text, .. } | ContentBlock::Quote { text, .. } => vec![text],
+                ContentBlock::List { items, .. } => items.iter().flat_map(ListItem::texts).collect(),
+                _ => continue,
+            };
+            for text in texts.iter().map(|t| t.plain_text()) {
+                for id in notes.markers(&text).into_iter().map(|(_, _, id)| id.to_string()).collect::<Vec<_>>() {
+                    notes.referenced.insert(id);
+                }
+            }
+        }
+        notes
+    }
+
+    /// `{{id}}` markers naming a known footnote, as (start, end, id)
+    fn markers<'t>(&self, text: &'t str) -> Vec<(usize, usize, &'t str)> {
+        let mut found = Vec::new();
+        let mut from = 0;
+        while let Some(start) = text[from..].find("{{").map(|i| from + i) {
+            let Some(len) = text[start + 2..].find("}}") else { break };
+            let id = &text[start + 2..start + 2 + len];
+            let end = start + 2 + len + 2;
+            if self.text.contains_key(id) {
+                found.push((start, end, id));
+            }
+            from = end;
+        }
+        found
+    }
+
+    /// Replace footnote markers with "[n]", numbering footnotes on first use
+    fn expand(&mut self, text: &str) -> (String, Vec<usize>) {
+        let mut out = String::new();
+        let mut numbers = Vec::new();
+        let mut last = 0;
+        for (start, end, id) in self.markers(text) {
+            let next = self.numbers.len() + 1;
+            let number = *self.numbers.entry(id.to_string()).or_insert(next);
+            out.push_str(&text[last..start]);
+            out.push_str(&format!("[{}]", number));
+            numbers.push(number);
+            last = end;
+        }
+        out.push_str(&text[last..]);
+        (out, numbers)
+    }
+
+    /// Spans for inline text in the faces of its marks, with footnote
+    /// references replaced as in `expand`
+    fn expand_inline(&mut self, text: &InlineText) -> (Vec<Span>, Vec<usize>) {
+        let mut spans = Vec::new();
+        let mut numbers = Vec::new();
+        for run in text.runs() {
+            let (text, face) = match run {
+                InlineRun::Text { text, marks, .. } if marks.contains(&Mark::Code) => (text, Face::Mono),
+                InlineRun::Text { text, marks, .. } if marks.contains(&Mark::Bold) => (text, Face::Bold),
+                InlineRun::Text { text, marks, .. } if marks.contains(&Mark::Italic) => (text, Face::Italic),
+                InlineRun::Text { text, .. } | InlineRun::CrossRef { text, .. } => (text, Face::Regular),
+                InlineRun::FootnoteRef { id } => (format!("{{{{{}}}}}", id), Face::Regular),
+            };
+            let (text, found) = self.expand(&text);
+            numbers.extend(found);
+            spans.push((text, face));
+        }
+        (spans, numbers)
+    }
+
+    fn text_of(&self, number: usize) -> &str {
+        self.numbers
+            .iter()
+            .find(|(_, n)| **n == number)
+            .and_then(|(id, _)| self.text.get(id))
+            .map(String::as_str)
+            .unwrap_or("")
+    }
+}
+
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    italic: IndirectFontRef,
+    mono: IndirectFontRef,
+}
+
+impl Fonts {
+    fn get(&self, face: Face) -> &IndirectFontRef {
+        match face {
+            Face::Regular => &self.regular,
+            Face::Bold => &self.bold,
+            Face::Italic => &self.italic,
+            Face::Mono => &self.mono,
+        }
+    }
+}
+
+/// Horizontal placement of a line of text
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// Cell of a drawn table, over columns `column..column + colspan` and
+/// `rowspan` rows from its own
+struct GridCell {
+    column: usize,
+    colspan: usize,
+    rowspan: usize,
+    text: String,
+}
+
+/// Row of a drawn table
+struct GridRow {
+    cells: Vec<GridCell>,
+    face: Face,
+    shade: Option<f32>,
+    /// Header rows are left aligned, with merged cells centred
+    header: bool,
+    /// Extra left indent of the first column, for outlined rows
+    indent: f32,
+    /// Heavy rule above the row, for totals
+    rule: bool,
+}
+
+impl Default for GridRow {
+    fn default() -> Self {
+        GridRow { cells: Vec::new(), face: Face::Regular, shade: None, header: false, indent: 0.0, rule: false }
+    }
+}
+
+impl GridRow {
+    /// A shaded bold header row
+    fn header() -> Self {
+        GridRow { face: Face::Bold, shade: Some(0.9), header: true, ..GridRow::default() }
+    }
+
+    /// A row with one single cell per column
+    fn plain(cells: &[String], face: Face) -> Self {
+        let cells = cells
+            .iter()
+            .enumerate()
+            .map(|(column, text)| GridCell { column, colspan: 1, rowspan: 1, text: text.clone() })
+            .collect();
+        GridRow { cells, face, ..GridRow::default() }
+    }
+}
+
+/// A footnote set at the bottom of the current page
+struct PageNote {
+    number: usize,
+    lines: Vec<String>,
+}
+
+/// Flows a document over pages and draws it with printpdf
+pub struct PdfRenderer<'a> {
+    pdf: PdfDocumentReference,
+    page: PageGeometry,
+    fonts: Fonts,
+    layers: Vec<PdfLayerReference>,
+    /// Top of the next line, in millimetres from the bottom of the page
+    y: f32,
+    notes: Footnotes,
+    page_notes: Vec<PageNote>,
+    /// Height reserved at the bottom of the current page for its footnotes
+    note_space: f32,
+    assets: &'a HashMap<String, Vec<u8>>,
+    /// Data of every chart, by chart id
+    charts: HashMap<String, Result<ChartData, String>>,
+}
+
+impl<'a> PdfRenderer<'a> {
+    /// Start a PDF with one empty page
+    ///
+    /// # Arguments
+    /// * `title` - PDF document title
+    /// * `page` - Page size and margins
+    /// * `assets` - Archive assets keyed by entry name, for figures
+    pub fn new(title: &str, page: PageGeometry, assets: &'a HashMap<String, Vec<u8>>) -> TdfResult<Self> {
+        let (pdf, page_index, layer_index) = PdfDocument::new(title, Mm(page.width), Mm(page.height), "Content");
+        let font = |f: BuiltinFont| {
+            pdf.add_builtin_font(f)
+                .map_err(|e| TdfError::InvalidDocument(format!("Font error: {}", e)))
+        };
+        let fonts = Fonts {
+            regular: font(BuiltinFont::Helvetica)?,
+            bold: font(BuiltinFont::HelveticaBold)?,
+            italic: font(BuiltinFont::HelveticaOblique)?,
+            mono: font(BuiltinFont::Courier)?,
+        };
+        let layer = pdf.get_page(page_index).get_layer(layer_index);
+        Ok(PdfRenderer {
+            pdf,
+            page,
+            fonts,
+            layers: vec![layer],
+            y: page.height - page.top,
+            notes: Footnotes::default(),
+            page_notes: Vec::new(),
+            note_space: 0.0,
+            assets,
+            charts: HashMap::new(),
+        })
+    }
+
+    /// Lay out the title block and every section of `document`
+    pub fn render_document(&mut self, document: &Document) {
+        self.notes = Footnotes::collect(&document.content);
+        self.charts = chart::collect(&document.content);
+        let manifest = &document.manifest.document;
+        self.text_block(&manifest.title, Face::Bold, 20.0, 0.0, Align::Left);
+        self.y -= 1.0;
+        self.set_color(0.4);
+        let meta = format!(
+            "ID: {}   Created: {}   Modified: {}",
+            manifest.id,
+            manifest.created.format("%Y-%m-%d %H:%M:%S"),
+            manifest.modified.format("%Y-%m-%d %H:%M:%S")
+        );
+        self.text_block(&meta, Face::Regular, 8.0, 0.0, Align::Left);
+        self.set_color(0.0);
+        self.y -= 6.0;
+
+        for section in &document.content.sections {
+            self.section(section);
+        }
+    }
+
+    /// Set pending footnotes and the page footers, and return the PDF
+    pub fn finish(mut self) -> PdfDocumentReference {
+        self.flush_notes();
+        let total = self.layers.len();
+        let baseline = (self.page.bottom / 2.0).max(4.0);
+        for (i, layer) in self.layers.iter().enumerate() {
+            let label = format!("Page {} of {}", i + 1, total);
+            let x = (self.page.width - text_width(&label, Face::Regular, 8.0)) / 2.0;
+            layer.set_fill_color(rgb(0.4, 0.4, 0.4));
+            layer.use_text(label, 8.0, Mm(x), Mm(baseline), &self.fonts.regular);
+        }
+        self.pdf
+    }
+
+    fn layer(&self) -> &PdfLayerReference {
+        self.layers.last().expect("renderer always has a page")
+    }
+
+    fn top(&self) -> f32 {
+        self.page.height - self.page.top
+    }
+
+    /// Lowest point content may reach on the current page
+    fn limit(&self) -> f32 {
+        self.page.bottom + self.note_space
+    }
+
+    fn at_page_top(&self) -> bool {
+        (self.y - self.top()).abs() < 0.01
+    }
+
+    fn new_page(&mut self) {
+        self.flush_notes();
+        let (page, layer) = self.pdf.add_page(Mm(self.page.width), Mm(self.page.height), "Content");
+        self.layers.push(self.pdf.get_page(page).get_layer(layer));
+        self.y = self.top();
+        self.note_space = 0.0;
+    }
+
+    /// Start a new page unless `height` fits above the bottom margin
+    ///
+    /// A fresh page is never skipped, so content taller than a page is
+    /// placed (and clipped) rather than looping.
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < self.limit() && !self.at_page_top() {
+            self.new_page();
+        }
+    }
+
+    fn set_color(&self, grey: f32) {
+        self.layer().set_fill_color(rgb(grey, grey, grey));
+    }
+
+    fn text(&self, text: &str, face: Face, size: f32, x: f32, baseline: f32) {
+        self.layer().use_text(text, size, Mm(x), Mm(baseline), self.fonts.get(face));
+    }
+
+    fn aligned_x(&self, line: &str, face: Face, size: f32, x: f32, width: f32, align: Align) -> f32 {
+        match align {
+            Align::Left => x,
+            Align::Center => x + (width - text_width(line, face, size)) / 2.0,
+            Align::Right => x + width - text_width(line, face, size),
+        }
+    }
+
+    /// Wrap and set `text` at `indent` from the left margin, breaking pages as needed
+    fn text_block(&mut self, text: &str, face: Face, size: f32, indent: f32, align: Align) {
+        let width = self.page.content_width() - indent;
+        let lines = wrap(text, face, size, width);
+        self.lines(&lines, face, size, indent, align, &[]);
+    }
+
+    /// Set wrapped lines, reserving room for footnotes first referenced on them
+    fn lines(&mut self, lines: &[String], face: Face, size: f32, indent: f32, align: Align, notes: &[usize]) {
+        let lines: Vec<Vec<Span>> = lines.iter().map(|line| vec![(line.clone(), face)]).collect();
+        self.span_lines(&lines, size, indent, align, notes);
+    }
+
+    /// `lines`, for lines of text in several faces
+    fn span_lines(&mut self, lines: &[Vec<Span>], size: f32, indent: f32, align: Align, notes: &[usize]) {
+        let height = line_height(size);
+        let left = self.page.left + indent;
+        let width = self.page.content_width() - indent;
+        for line in lines {
+            let text: String = line.iter().map(|(text, _)| text.as_str()).collect();
+            let mut new_notes: Vec<usize> = notes
+                .iter()
+                .copied()
+                .filter(|n| !self.notes.placed.contains(n) && text.contains(&format!("[{}]", n)))
+                .collect();
+            new_notes.dedup();
+            let mut reserve = self.note_height(&new_notes);
+            if self.y - height - reserve < self.limit() && !self.at_page_top() {
+                self.new_page();
+                reserve = self.note_height(&new_notes);
+            }
+            let line_width: f32 = line.iter().map(|(text, face)| text_width(text, *face, size)).sum();
+            let mut x = match align {
+                Align::Left => left,
+                Align::Center => left + (width - line_width) / 2.0,
+                Align::Right => left + width - line_width,
+            };
+            for (text, face) in line {
+                self.text(text, *face, size, x, self.y - ascent(size));
+                x += text_width(text, *face, size);
+            }
+            self.y -= height;
+            self.place_notes(&new_notes, reserve);
+        }
+    }
+
+    fn note_lines(&self, number: usize) -> Vec<String> {
+        wrap(self.notes.text_of(number), Face::Regular, NOTE_SIZE, self.page.content_width() - 6.0)
+    }
+
+    /// Space needed to add `numbers` to the footnotes of the current page
+    fn note_height(&self, numbers: &[usize]) -> f32 {
+        if numbers.is_empty() {
+            return 0.0;
+        }
+        let lines: usize = numbers.iter().map(|&n| self.note_lines(n).len().max(1)).sum();
+        let separator = if self.page_notes.is_empty() { NOTE_SEPARATOR } else { 0.0 };
+        lines as f32 * line_height(NOTE_SIZE) + separator
+    }
+
+    fn place_notes(&mut self, numbers: &[usize], reserve: f32) {
+        for &number in numbers {
+            let lines = self.note_lines(number);
+            self.page_notes.push(PageNote { number, lines });
+            self.notes.placed.insert(number);
+        }
+        self.note_space += reserve;
+    }
+
+    /// Set the current page's footnotes in the space reserved for them
+    fn flush_notes(&mut self) {
+        if self.page_notes.is_empty() {
+            return;
+        }
+        let left = self.page.left;
+        let top = self.page.bottom + self.note_space;
+        let rule = top - NOTE_SEPARATOR / 2.0;
+        self.stroke(&[(left, rule), (left + 40.0, rule)], false, 0.5);
+        let mut y = top - NOTE_SEPARATOR;
+        for note in std::mem::take(&mut self.page_notes) {
+            self.text(&note.number.to_string(), Face::Regular, NOTE_SIZE, left, y - ascent(NOTE_SIZE));
+            for line in &note.lines {
+                self.text(line, Face::Regular, NOTE_SIZE, left + 6.0, y - ascent(NOTE_SIZE));
+                y -= line_height(NOTE_SIZE);
+            }
+            if note.lines.is_empty() {
+                y -= line_height(NOTE_SIZE);
+            }
+        }
+    }
+
+    fn points(points: &[(f32, f32)]) -> Vec<(Point, bool)> {
+        points.iter().map(|&(x, y)| (Point::new(Mm(x), Mm(y)), false)).collect()
+    }
+
+    fn stroke(&self, points: &[(f32, f32)], closed: bool, thickness: f32) {
+        let layer = self.layer();
+        layer.set_outline_thickness(thickness);
+        layer.add_line(Line { points: Self::points(points), is_closed: closed });
+    }
+
+    fn fill_rect(&self, x: f32, y: f32, width: f32, height: f32, grey: f32) {
+        let layer = self.layer();
+        layer.set_fill_color(rgb(grey, grey, grey));
+        layer.add_polygon(Polygon {
+            rings: vec![Self::points(&[(x, y), (x + width, y), (x + width, y - height), (x, y - height)])],
+            mode: PaintMode::Fill,
+            winding_order: WindingOrder::NonZero,
+        });
+        layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+    }
+
+    fn section(&mut self, section: &Section) {
+        if let Some(ref title) = section.title {
+            // Keep the title with the first lines of the section
+            self.y -= 2.0;
+            self.ensure(line_height(15.0) + 3.0 * line_height(BODY_SIZE));
+            self.text_block(title, Face::Bold, 15.0, 0.0, Align::Left);
+            self.y -= 2.0;
+        }
+        if let Some(ref encrypted) = section.encrypted {
+            self.set_color(0.4);
+            self.text_block(&format!("[Encrypted section for {}]", encrypted.group), Face::Italic, BODY_SIZE, 0.0, Align::Left);
+            self.set_color(0.0);
+            self.y -= 3.0;
+        }
+        for block in &section.content {
+            self.block(block);
+        }
+        self.y -= 4.0;
+    }
+
+    fn block(&mut self, block: &ContentBlock) {
+        match block {
+            ContentBlock::Heading { level, text, .. } => {
+                let size = match *level {
+                    1 => 16.0,
+                    2 => 14.0,
+                    3 => 12.0,
+                    _ => 11.0,
+                };
+                self.y -= 1.5;
+                self.ensure(line_height(size) + 2.0 * line_height(BODY_SIZE));
+                self.text_block(&text.plain_text(), Face::Bold, size, 0.0, Align::Left);
+                self.y -= 1.5;
+            }
+            ContentBlock::Paragraph { text, .. } => {
+                let (spans, notes) = self.notes.expand_inline(text);
+                let lines = wrap_spans(&spans, BODY_SIZE, self.page.content_width());
+                self.span_lines(&lines, BODY_SIZE, 0.0, Align::Left, &notes);
+                self.y -= 3.0;
+            }
+            ContentBlock::List { ordered, items, .. } => {
+                self.list(*ordered, items, 0, 0.0);
+                self.y -= 2.0;
+            }
+            ContentBlock::Quote { text, attribution, .. } => {
+                let indent = 8.0;
+                let (spans, notes) = self.notes.expand_inline(text);
+                let spans: Vec<Span> = spans
+                    .into_iter()
+                    .map(|(text, face)| (text, if face == Face::Regular { Face::Italic } else { face }))
+                    .collect();
+                let lines = wrap_spans(&spans, BODY_SIZE, self.page.content_width() - 2.0 * indent);
+                self.set_color(0.25);
+                self.span_lines(&lines, BODY_SIZE, indent, Align::Left, &notes);
+                if let Some(attribution) = attribution {
+                    let lines = wrap(&format!("\u{2014} {}", attribution), Face::Regular, NOTE_SIZE + 1.0, self.page.content_width() - 2.0 * indent);
+                    self.lines(&lines, Face::Regular, NOTE_SIZE + 1.0, indent, Align::Left, &[]);
+                }
+                self.set_color(0.0);
+                self.y -= 3.0;
+            }
+            ContentBlock::Code { text, .. } => {
+                self.code(text);
+                self.y -= 3.0;
+            }
+            ContentBlock::PageBreak => {
+                if !self.at_page_top() {
+                    self.new_page();
+                }
+            }
+            ContentBlock::HorizontalRule => {
+                self.ensure(4.0);
+                self.y -= 2.0;
+                self.stroke(&[(self.page.left, self.y), (self.page.left + self.page.content_width(), self.y)], false, 0.5);
+                self.y -= 2.0;
+            }
+            ContentBlock::Table { caption, columns, header_rows, rows, footer, .. } => {
+                let header_rows = header_rows.as_deref().unwrap_or_default();
+                self.table(caption.as_deref(), columns, header_rows, rows, footer.as_deref());
+            }
+            ContentBlock::Diagram { diagram_type, title, nodes, edges, layout, .. } => {
+                self.diagram(diagram_type, title.as_deref(), nodes, edges, layout.as_ref());
+            }
+            ContentBlock::Chart { id, chart_type, title, axes, format, .. } => {
+                self.chart(id, *chart_type, title.as_deref(), axes.as_ref(), format.as_ref());
+            }
+            ContentBlock::Figure { asset, alt, caption, width, .. } => {
+                self.figure(asset, alt, caption.as_deref(), *width);
+            }
+            ContentBlock::Footnote { id, text } => {
+                // Referenced footnotes are set at the foot of the page instead
+                if !self.notes.referenced.contains(id) {
+                    self.set_color(0.3);
+                    self.text_block(text, Face::Regular, NOTE_SIZE, 0.0, Align::Left);
+                    self.set_color(0.0);
+                    self.y -= 2.0;
+                }
+            }
+            ContentBlock::Redacted { id, .. } => {
+                let height = line_height(BODY_SIZE) + 2.0;
+                self.ensure(height);
+                self.fill_rect(self.page.left, self.y, self.page.content_width(), height, 0.88);
+                self.set_color(0.3);
+                let label = format!("[Redacted: {}]", id);
+                self.text(&label, Face::Italic, BODY_SIZE, self.page.left + 2.0, self.y - 1.0 - ascent(BODY_SIZE));
+                self.set_color(0.0);
+                self.y -= height + 3.0;
+            }
+            ContentBlock::TextField { .. }
+            | ContentBlock::NumberField { .. }
+            | ContentBlock::DateField { .. }
+            | ContentBlock::Checkbox { .. }
+            | ContentBlock::Choice { .. }
+            | ContentBlock::SignatureField { .. } => {
+                if let Some(field) = FormField::from_block(block) {
+                    self.field(&field);
+                }
+            }
+        }
+    }
+
+    /// Bold label over an empty box to fill in (a small box before the
+    /// label for a checkbox), then a hint of what the field accepts
+    fn field(&mut self, field: &FormField) {
+        let label = if field.required { format!("{} *", field.label) } else { field.label.to_string() };
+        let box_height = match field.kind {
+            FieldKind::Checkbox => 0.0,
+            FieldKind::Text { multiline: true, .. } => 3.0 * line_height(BODY_SIZE) + 2.0,
+            FieldKind::Signature { .. } => 2.0 * line_height(BODY_SIZE) + 2.0,
+            _ => line_height(BODY_SIZE) + 2.0,
+        };
+        self.ensure(line_height(BODY_SIZE) + box_height + 2.0 + line_height(NOTE_SIZE));
+        if let FieldKind::Checkbox = field.kind {
+            let (x, size) = (self.page.left + 1.0, 2.6);
+            let top = self.y - (line_height(BODY_SIZE) - size) / 2.0;
+            self.stroke(&[(x, top), (x + size, top), (x + size, top - size), (x, top - size)], true, 0.4);
+            self.text_block(&label, Face::Bold, BODY_SIZE, 6.0, Align::Left);
+        } else {
+            self.text_block(&label, Face::Bold, BODY_SIZE, 0.0, Align::Left);
+            self.y -= 1.0;
+            let (left, right) = (self.page.left, self.page.left + self.page.content_width());
+            self.stroke(&[(left, self.y), (right, self.y), (right, self.y - box_height), (left, self.y - box_height)], true, 0.3);
+            self.y -= box_height + 1.0;
+        }
+        self.set_color(0.4);
+        self.text_block(&field.describe(), Face::Italic, NOTE_SIZE, 0.0, Align::Left);
+        if let Some(help) = field.help {
+            self.text_block(help, Face::Regular, NOTE_SIZE, 0.0, Align::Left);
+        }
+        self.set_color(0.0);
+        self.y -= 3.0;
+    }
+
+    /// Items of a list at nesting `depth`, `indent` from the left margin,
+    /// each followed by its sublist
+    fn list(&mut self, ordered: bool, items: &[ListItem], depth: usize, indent: f32) {
+        let hang = if ordered && items.iter().all(|item| item.checked.is_none()) { 8.0 } else { 6.0 };
+        let text_indent = indent + hang;
+        for (i, item) in items.iter().enumerate() {
+            let (spans, notes) = self.notes.expand_inline(&item.text);
+            let lines = wrap_spans(&spans, BODY_SIZE, self.page.content_width() - text_indent);
+            self.ensure(line_height(BODY_SIZE));
+            let x = self.page.left + indent + 1.0;
+            match item.checked {
+                Some(checked) => {
+                    let size = 2.6;
+                    let top = self.y - (line_height(BODY_SIZE) - size) / 2.0;
+                    self.stroke(&[(x, top), (x + size, top), (x + size, top - size), (x, top - size)], true, 0.4);
+                    if checked {
+                        self.stroke(&[(x + 0.5, top - 1.4), (x + 1.1, top - 2.1), (x + 2.2, top - 0.5)], false, 0.5);
+                    }
+                }
+                None => {
+                    let marker = match (ordered, depth % 2) {
+                        (true, _) => format!("{}.", i + 1),
+                        (false, 0) => "\u{2022}".to_string(),
+                        (false, _) => "\u{2013}".to_string(),
+                    };
+                    self.text(&marker, Face::Regular, BODY_SIZE, x, self.y - ascent(BODY_SIZE));
+                }
+            }
+            if lines.is_empty() {
+                self.y -= line_height(BODY_SIZE);
+            }
+            self.span_lines(&lines, BODY_SIZE, text_indent, Align::Left, &notes);
+            self.y -= 1.0;
+            if let Some(sublist) = &item.sublist {
+                self.list(sublist.ordered, &sublist.items, depth + 1, text_indent);
+            }
+        }
+    }
+
+    /// Code on a shaded band, line breaks and indentation kept and long
+    /// lines broken at the right margin
+    fn code(&mut self, text: &str) {
+        let width = self.page.content_width();
+        let height = line_height(CODE_SIZE);
+        let columns = ((width - 4.0) / (MONO_WIDTH as f32 / 1000.0 * CODE_SIZE * PT_TO_MM)).max(1.0) as usize;
+        let mut lines = Vec::new();
+        for line in text.replace('\t', "    ").split('\n') {
+            let chars: Vec<char> = line.chars().collect();
+            if chars.is_empty() {
+                lines.push(String::new());
+            }
+            lines.extend(chars.chunks(columns).map(|chunk| chunk.iter().collect::<String>()));
+        }
+        self.ensure(height + 2.0);
+        self.fill_rect(self.page.left, self.y, width, 1.0, 0.95);
+        self.y -= 1.0;
+        for line in lines {
+            self.ensure(height);
+            self.fill_rect(self.page.left, self.y, width, height, 0.95);
+            self.text(&line, Face::Mono, CODE_SIZE, self.page.left + 2.0, self.y - ascent(CODE_SIZE));
+            self.y -= height;
+        }
+        self.fill_rect(self.page.left, self.y, width, 1.0, 0.95);
+        self.y -= 1.0;
+    }
+
+    /// Column widths filling the content width, from natural and minimum widths
+    fn column_widths(&self, natural: &[f32], minimum: &[f32]) -> Vec<f32> {
+        let width = self.page.content_width();
+        let total: f32 = natural.iter().sum();
+        let total_min: f32 = minimum.iter().sum();
+        if total <= width {
+            natural.iter().map(|w| w * width / total).collect()
+        } else if total_min >= width {
+            minimum.iter().map(|w| w * width / total_min).collect()
+        } else {
+            let share = (width - total_min) / (total - total_min);
+            natural.iter().zip(minimum).map(|(n, m)| m + (n - m) * share).collect()
+        }
+    }
+
+    /// Table grid; the header rows are repeated at the top of every page
+    fn table(&mut self, caption: Option<&str>, columns: &[TableColumn], header_rows: &[Vec<HeaderCell>], rows: &[TableRow], footer: Option<&[String]>) {
+        if columns.is_empty() {
+            return;
+        }
+        let header: Vec<GridRow> = header_layout(columns, header_rows)
+            .iter()
+            .map(|slots| GridRow {
+                cells: slots
+                    .iter()
+                    .map(|slot| GridCell { column: slot.column, colspan: slot.colspan, rowspan: slot.rowspan, text: slot.text.to_string() })
+                    .collect(),
+                ..GridRow::header()
+            })
+            .collect();
+        let covered = covered_cells(columns, rows);
+        let mut body: Vec<GridRow> = rows
+            .iter()
+            .enumerate()
+            .map(|(r, row)| GridRow {
+                cells: columns
+                    .iter()
+                    .enumerate()
+                    .filter(|(c, _)| !covered.contains_key(&(r, *c)))
+                    .map(|(c, column)| {
+                        let span = row.span(&column.id);
+                        let text = row.cells.get(&column.id).map(|v| v.display_text().to_string()).unwrap_or_default();
+                        GridCell { column: c, colspan: span.colspan, rowspan: span.rowspan, text }
+                    })
+                    .collect(),
+                face: if row.format.role == RowRole::Data { Face::Regular } else { Face::Bold },
+                indent: f32::from(row.format.level) * ROW_LEVEL_INDENT,
+                rule: row.format.role == RowRole::Total,
+                ..GridRow::default()
+            })
+            .collect();
+        if let Some(footer) = footer {
+            let cells: Vec<String> = (0..columns.len()).map(|i| footer.get(i).cloned().unwrap_or_default()).collect();
+            body.push(GridRow { shade: Some(0.95), ..GridRow::plain(&cells, Face::Bold) });
+        }
+        let align: Vec<Align> = columns
+            .iter()
+            .map(|c| match c.cell_type {
+                CellType::Number | CellType::Currency | CellType::Percentage => Align::Right,
+                _ => Align::Left,
+            })
+            .collect();
+        self.grid_rows(caption, &header, &body, &align);
+    }
+
+    /// Grid of text cells with a shaded header row repeated on every page
+    ///
+    /// `body` and `footer` rows must have one cell per header.
+    fn grid(&mut self, caption: Option<&str>, headers: &[String], body: &[Vec<String>], footer: Option<&[String]>, align: &[Align]) {
+        let header = [GridRow { shade: Some(0.9), header: true, ..GridRow::plain(headers, Face::Bold) }];
+        let mut rows: Vec<GridRow> = body.iter().map(|cells| GridRow::plain(cells, Face::Regular)).collect();
+        if let Some(cells) = footer {
+            rows.push(GridRow { shade: Some(0.95), ..GridRow::plain(cells, Face::Bold) });
+        }
+        self.grid_rows(caption, &header, &rows, align);
+    }
+
+    /// Column widths fitting every cell; a merged cell wider than its
+    /// columns widens them evenly
+    fn grid_widths(&self, rows: &[&GridRow], columns: usize) -> Vec<f32> {
+        let pad = 2.0 * CELL_PADDING;
+        let (mut natural, mut minimum) = (vec![0.0f32; columns], vec![0.0f32; columns]);
+        for merged in [false, true] {
+            for row in rows {
+                for cell in row.cells.iter().filter(|cell| (cell.colspan > 1) == merged) {
+                    let indent = if cell.column == 0 { row.indent } else { 0.0 };
+                    let range = cell.column..(cell.column + cell.colspan).min(columns);
+                    let span = range.len().max(1) as f32;
+                    let need = text_width(&cell.text, row.face, TABLE_SIZE) + pad + indent;
+                    let have: f32 = natural[range.clone()].iter().sum();
+                    natural[range.clone()].iter_mut().for_each(|w| *w += (need - have).max(0.0) / span);
+                    let need = longest_word(&cell.text, row.face, TABLE_SIZE) + pad + indent;
+                    let have: f32 = minimum[range.clone()].iter().sum();
+                    minimum[range].iter_mut().for_each(|w| *w += (need - have).max(0.0) / span);
+                }
+            }
+        }
+        let minimum: Vec<f32> = minimum.iter().map(|m| m.max(8.0)).collect();
+        let natural: Vec<f32> = natural.iter().zip(&minimum).map(|(n, m)| n.max(*m)).collect();
+        self.column_widths(&natural, &minimum)
+    }
+
+    /// Wrapped lines of each cell and the height of each row of a band
+    ///
+    /// Rows are as tall as their single-row cells; the last row a merged
+    /// cell covers grows if the cell needs more room.
+    fn band_layout(band: &[GridRow], widths: &[f32]) -> (Vec<Vec<Vec<String>>>, Vec<f32>) {
+        let pad = 2.0 * CELL_PADDING;
+        let lines: Vec<Vec<Vec<String>>> = band
+            .iter()
+            .map(|row| {
+                row.cells
+                    .iter()
+                    .map(|cell| {
+                        let indent = if cell.column == 0 { row.indent } else { 0.0 };
+                        let width: f32 = widths.iter().skip(cell.column).take(cell.colspan).sum();
+                        wrap(&cell.text, row.face, TABLE_SIZE, width - pad - indent)
+                    })
+                    .collect()
+            })
+            .collect();
+        let height = |count: usize| count.max(1) as f32 * line_height(TABLE_SIZE) + pad;
+        let mut heights: Vec<f32> = band
+            .iter()
+            .zip(&lines)
+            .map(|(row, lines)| {
+                row.cells
+                    .iter()
+                    .zip(lines)
+                    .filter(|(cell, _)| cell.rowspan <= 1)
+                    .map(|(_, lines)| height(lines.len()))
+                    .fold(height(1), f32::max)
+            })
+            .collect();
+        for (r, (row, lines)) in band.iter().zip(&lines).enumerate() {
+            for (cell, lines) in row.cells.iter().zip(lines).filter(|(cell, _)| cell.rowspan > 1) {
+                let last = (r + cell.rowspan).min(band.len()) - 1;
+                let have: f32 = heights[r..=last].iter().sum();
+                heights[last] += (height(lines.len()) - have).max(0.0);
+            }
+        }
+        (lines, heights)
+    }
+
+    fn draw_band(&mut self, band: &[GridRow], widths: &[f32], align: &[Align]) {
+        let (lines, heights) = Self::band_layout(band, widths);
+        let (left, top) = (self.page.left, self.y);
+        let total_width: f32 = widths.iter().sum();
+        let mut y = top;
+        for (row, height) in band.iter().zip(&heights) {
+            if let Some(grey) = row.shade {
+                self.fill_rect(left, y, total_width, *height, grey);
+            }
+            y -= height;
+        }
+
+        self.layer().set_outline_color(rgb(0.6, 0.6, 0.6));
+        let mut y = top;
+        for (r, (row, lines)) in band.iter().zip(&lines).enumerate() {
+            for (cell, cell_lines) in row.cells.iter().zip(lines) {
+                let x = left + widths.iter().take(cell.column).sum::<f32>();
+                let width: f32 = widths.iter().skip(cell.column).take(cell.colspan).sum();
+                let height: f32 = heights.iter().skip(r).take(cell.rowspan.max(1)).sum();
+                let rect = [(x, y), (x + width, y), (x + width, y - height), (x, y - height)];
+                self.stroke(&rect, true, 0.5);
+                let indent = if cell.column == 0 { row.indent } else { 0.0 };
+                let align = match (row.header, cell.colspan > 1) {
+                    (true, true) => Align::Center,
+                    (true, false) => Align::Left,
+                    _ => align.get(cell.column).copied().unwrap_or(Align::Left),
+                };
+                let mut baseline = y - CELL_PADDING - ascent(TABLE_SIZE);
+                for line in cell_lines {
+                    let tx = self.aligned_x(line, row.face, TABLE_SIZE, x + CELL_PADDING + indent, width - 2.0 * CELL_PADDING - indent, align);
+                    self.text(line, row.face, TABLE_SIZE, tx, baseline);
+                    baseline -= line_height(TABLE_SIZE);
+                }
+            }
+            if row.rule {
+                self.layer().set_outline_color(rgb(0.25, 0.25, 0.25));
+                self.stroke(&[(left, y), (left + total_width, y)], false, 1.2);
+                self.layer().set_outline_color(rgb(0.6, 0.6, 0.6));
+            }
+            y -= heights[r];
+        }
+        self.layer().set_outline_color(rgb(0.0, 0.0, 0.0));
+        self.y = y;
+    }
+
+    /// Rows that must stay on one page because merged cells join them,
+    /// as index ranges
+    fn bands(rows: &[GridRow]) -> Vec<std::ops::Range<usize>> {
+        let mut bands = Vec::new();
+        let mut start = 0;
+        let mut end = 0;
+        for (r, row) in rows.iter().enumerate() {
+            end = end.max(r + 1).max(r + row.cells.iter().map(|cell| cell.rowspan).max().unwrap_or(1));
+            if end == r + 1 {
+                bands.push(start..end);
+                start = end;
+            }
+        }
+        if start < rows.len() {
+            bands.push(start..rows.len());
+        }
+        bands
+    }
+
+    /// Grid of header and body rows; the header rows repeat on every page
+    /// and rows joined by merged cells are kept together
+    fn grid_rows(&mut self, caption: Option<&str>, header: &[GridRow], body: &[GridRow], align: &[Align]) {
+        let all: Vec<&GridRow> = header.iter().chain(body).collect();
+        let widths = self.grid_widths(&all, align.len());
+        let header_height: f32 = Self::band_layout(header, &widths).1.iter().sum();
+        let bands = Self::bands(body);
+        let band_height = |band: &std::ops::Range<usize>| Self::band_layout(&body[band.clone()], &widths).1.iter().sum::<f32>();
+        let first_band = bands.first().map(band_height).unwrap_or(0.0);
+
+        // Keep the caption and header with the first rows
+        let caption_lines = caption.map(|c| wrap(c, Face::Bold, 9.0, self.page.content_width())).unwrap_or_default();
+        self.ensure(caption_lines.len() as f32 * line_height(9.0) + 1.0 + header_height + first_band);
+        if !caption_lines.is_empty() {
+            self.lines(&caption_lines, Face::Bold, 9.0, 0.0, Align::Left, &[]);
+            self.y -= 1.0;
+        }
+        self.draw_band(header, &widths, align);
+        for band in &bands {
+            if self.y - band_height(band) < self.limit() {
+                self.new_page();
+                self.draw_band(header, &widths, align);
+            }
+            self.draw_band(&body[band.clone()], &widths, align);
+        }
+        self.y -= 5.0;
+    }
+
+    /// Look up a figure's asset under the names it may be stored as
+    fn asset(&self, asset: &str) -> Option<&'a Vec<u8>> {
+        let assets = self.assets;
+        let name = asset.trim_start_matches('/');
+        [name.to_string(), format!("assets/images/{}", name), format!("assets/{}", name)]
+            .iter()
+            .find_map(|candidate| assets.get(candidate))
+    }
+
+    fn figure(&mut self, asset: &str, alt: &str, caption: Option<&str>, width_px: Option<u32>) {
+        let content_width = self.page.content_width();
+        let caption_lines = caption.map(|c| wrap(c, Face::Italic, 8.5, content_width)).unwrap_or_default();
+        let caption_height = caption_lines.len() as f32 * line_height(8.5);
+
+        match self.asset(asset).and_then(|data| image_crate::load_from_memory(data).ok()) {
+            Some(image) => {
+                let (px_width, px_height) = image.dimensions();
+                if px_width == 0 || px_height == 0 {
+                    return;
+                }
+                // Widths are CSS pixels (96 per inch), capped to the text block
+                let mut width = (width_px.unwrap_or(px_width) as f32 * 25.4 / 96.0).min(content_width);
+                let mut height = width * px_height as f32 / px_width as f32;
+                let max_height = self.page.content_height() - caption_height - 4.0;
+                if height > max_height {
+                    width *= max_height / height;
+                    height = max_height;
+                }
+                self.ensure(height + caption_height + 2.0);
+                let x = self.page.left + (content_width - width) / 2.0;
+                let scale = width / (px_width as f32 / IMAGE_DPI * 25.4);
+                // Alpha would need a soft mask; flatten to RGB
+                let rgb_image = DynamicImage::ImageRgb8(image.to_rgb8());
+                Image::from_dynamic_image(&rgb_image).add_to_layer(
+                    self.layer().clone(),
+                    ImageTransform {
+                        translate_x: Some(Mm(x)),
+                        translate_y: Some(Mm(self.y - height)),
+                        scale_x: Some(scale),
+                        scale_y: Some(scale),
+                        dpi: Some(IMAGE_DPI),
+                        ..Default::default()
+                    },
+                );
+                self.y -= height + 2.0;
+            }
+            None => {
+                let height = line_height(BODY_SIZE) * 2.0 + 4.0;
+                self.ensure(height + caption_height + 2.0);
+                let (left, top) = (self.page.left, self.y);
+                self.layer().set_outline_color(rgb(0.6, 0.6, 0.6));
+                self.stroke(
+                    &[(left, top), (left + content_width, top), (left + content_width, top - height), (left, top - height)],
+                    true,
+                    0.5,
+                );
+                self.layer().set_outline_color(rgb(0.0, 0.0, 0.0));
+                self.set_color(0.4);
+                self.y -= 2.0;
+                self.lines(&[format!("[Figure: {}]", alt)], Face::Italic, BODY_SIZE, 2.0, Align::Left, &[]);
+                self.lines(&[format!("Image '{}' is missing or in an unsupported format", asset)], Face::Regular, 8.0, 2.0, Align::Left, &[]);
+                self.set_color(0.0);
+                self.y = top - height - 2.0;
+            }
+        }
+        if !caption_lines.is_empty() {
+            self.set_color(0.3);
+            self.lines(&caption_lines, Face::Italic, 8.5, 0.0, Align::Center, &[]);
+            self.set_color(0.0);
+        }
+        self.y -= 4.0;
+    }
+
+    /// Diagram drawn as vector shapes, scaled down to fit the text block
+    fn diagram(
+        &mut self,
+        diagram_type: &DiagramType,
+        title: Option<&str>,
+        nodes: &[DiagramNode],
+        edges: &[DiagramEdge],
+        layout: Option<&DiagramLayout>,
+    ) {
+        // Labels are measured with the Helvetica widths we print them in
+        let geometry = diagram::layout_with(diagram_type, nodes, edges, layout, |text| {
+            text_width(text, Face::Regular, diagram::LABEL_SIZE) / PT_TO_MM
+        });
+        let (width, height) = (geometry.width * PT_TO_MM, geometry.height * PT_TO_MM);
+        let title_height = if title.is_some() { line_height(10.0) + 2.0 } else { 0.0 };
+        let content_width = self.page.content_width();
+        let max_height = self.page.content_height() - title_height - 4.0;
+        let scale = if width > 0.0 && height > 0.0 {
+            (content_width / width).min(max_height / height).min(1.0)
+        } else {
+            1.0
+        };
+        // Diagram points to page millimetres
+        let factor = PT_TO_MM * scale;
+
+        self.ensure(title_height + height * scale);
+        if let Some(title) = title {
+            self.text_block(title, Face::Bold, 10.0, 0.0, Align::Left);
+            self.y -= 2.0;
+        }
+        let x0 = self.page.left + (content_width - width * scale) / 2.0;
+        let y0 = self.y;
+        let at = |(x, y): (f32, f32)| (x0 + x * factor, y0 - y * factor);
+        let layer = self.layer().clone();
+
+        layer.set_outline_thickness(0.75);
+        layer.set_outline_color(rgb(0.35, 0.35, 0.35));
+        for edge in &geometry.edges {
+            let points: Vec<(f32, f32)> = edge.points.iter().copied().map(at).collect();
+            let dash = match edge.edge_type {
+                EdgeType::Solid => LineDashPattern::default(),
+                EdgeType::Dashed => LineDashPattern { dash_1: Some(4), gap_1: Some(2), ..Default::default() },
+                EdgeType::Dotted => LineDashPattern { dash_1: Some(1), gap_1: Some(2), ..Default::default() },
+            };
+            layer.set_line_dash_pattern(dash);
+            layer.add_line(Line { points: Self::points(&points), is_closed: false });
+            layer.set_line_dash_pattern(LineDashPattern::default());
+
+            // Arrowhead at the target, along the last segment
+            let (from, to) = (points[points.len() - 2], points[points.len() - 1]);
+            let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let (ux, uy) = (dx / length, dy / length);
+                let size = 2.2 * scale.max(0.5);
+                let base = (to.0 - ux * size, to.1 - uy * size);
+                let head = [to, (base.0 - uy * size * 0.5, base.1 + ux * size * 0.5), (base.0 + uy * size * 0.5, base.1 - ux * size * 0.5)];
+                layer.set_fill_color(rgb(0.35, 0.35, 0.35));
+                layer.add_polygon(Polygon { rings: vec![Self::points(&head)], mode: PaintMode::Fill, winding_order: WindingOrder::NonZero });
+            }
+            if let Some(ref label) = edge.label {
+                let size = (diagram::EDGE_LABEL_SIZE * scale).max(5.0);
+                let mid = at(edge.midpoint());
+                layer.set_fill_color(rgb(0.25, 0.25, 0.25));
+                self.text(label, Face::Italic, size, mid.0 + 1.0, mid.1 + 1.0);
+            }
+        }
+
+        let label_size = (diagram::LABEL_SIZE * scale).max(5.0);
+        for node in &geometry.nodes {
+            let (x, y) = at((node.x, node.y));
+            let (w, h) = (node.width * factor, node.height * factor);
+            layer.set_fill_color(rgb(0.93, 0.95, 0.99));
+            layer.set_outline_color(rgb(0.2, 0.3, 0.5));
+            layer.add_polygon(Polygon {
+                rings: vec![shape_path(&node.shape, x, y, w, h)],
+                mode: PaintMode::FillStroke,
+                winding_order: WindingOrder::NonZero,
+            });
+            layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+            let line = diagram::LABEL_LINE * factor;
+            let mut baseline = y - (h - node.lines.len() as f32 * line) / 2.0 - line * 0.78;
+            for text in &node.lines {
+                let tx = x + (w - text_width(text, Face::Regular, label_size)) / 2.0;
+                self.text(text, Face::Regular, label_size, tx, baseline);
+                baseline -= line;
+            }
+        }
+        layer.set_outline_color(rgb(0.0, 0.0, 0.0));
+        layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+        self.y = y0 - height * scale - 5.0;
+    }
+
+    /// Chart drawn as vector shapes from its table's data, scaled down to fit the text block
+    fn chart(&mut self, id: &str, chart_type: ChartType, title: Option<&str>, axes: Option<&ChartAxes>, format: Option<&ChartFormat>) {
+        let data = match self.charts.get(id) {
+            Some(Ok(data)) => data,
+            Some(Err(e)) => {
+                let message = format!("[Chart cannot be drawn: {}]", e);
+                self.text_block(&message, Face::Italic, BODY_SIZE, 0.0, Align::Left);
+                return;
+            }
+            None => return,
+        };
+        let geometry = chart::layout_with(chart_type, title, data, axes, format, |text, size| {
+            text_width(text, Face::Regular, size) / PT_TO_MM
+        });
+        let (width, height) = (geometry.width * PT_TO_MM, geometry.height * PT_TO_MM);
+        let content_width = self.page.content_width();
+        let scale = (content_width / width).min(self.page.content_height() / height).min(1.0);
+        let factor = PT_TO_MM * scale;
+
+        self.ensure(height * scale);
+        let x0 = self.page.left + (content_width - width * scale) / 2.0;
+        let y0 = self.y;
+        let at = |(x, y): (f32, f32)| (x0 + x * factor, y0 - y * factor);
+        let color = |c: &chart::Color| rgb(c.0 as f32 / 255.0, c.1 as f32 / 255.0, c.2 as f32 / 255.0);
+        let layer = self.layer().clone();
+        for shape in &geometry.shapes {
+            match shape {
+                Shape::Rect { x, y, width, height, fill } => {
+                    let (left, top) = at((*x, *y));
+                    let (right, bottom) = at((x + width, y + height));
+                    layer.set_fill_color(color(fill));
+                    let ring = Self::points(&[(left, top), (right, top), (right, bottom), (left, bottom)]);
+                    layer.add_polygon(Polygon { rings: vec![ring], mode: PaintMode::Fill, winding_order: WindingOrder::NonZero });
+                }
+                Shape::Polygon { points, fill } => {
+                    let ring: Vec<(f32, f32)> = points.iter().copied().map(at).collect();
+                    layer.set_fill_color(color(fill));
+                    layer.set_outline_color(rgb(1.0, 1.0, 1.0));
+                    layer.set_outline_thickness(0.75);
+                    layer.add_polygon(Polygon { rings: vec![Self::points(&ring)], mode: PaintMode::FillStroke, winding_order: WindingOrder::NonZero });
+                }
+                Shape::Line { points, color: stroke, width } => {
+                    let line: Vec<(f32, f32)> = points.iter().copied().map(at).collect();
+                    layer.set_outline_color(color(stroke));
+                    layer.set_outline_thickness(width * scale);
+                    layer.add_line(Line { points: Self::points(&line), is_closed: false });
+                }
+                Shape::Text { x, y, text, size, anchor, bold, color: fill } => {
+                    let face = if *bold { Face::Bold } else { Face::Regular };
+                    let size = size * scale;
+                    let (x, baseline) = at((*x, *y));
+                    let x = match anchor {
+                        Anchor::Start => x,
+                        Anchor::Middle => x - text_width(text, face, size) / 2.0,
+                        Anchor::End => x - text_width(text, face, size),
+                    };
+                    layer.set_fill_color(color(fill));
+                    self.text(text, face, size, x, baseline);
+                }
+            }
+        }
+        layer.set_outline_color(rgb(0.0, 0.0, 0.0));
+        layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+        self.y = y0 - height * scale - 5.0;
+    }
+}
+
+/// One piece of a path outline
+enum Segment {
+    Line((f32, f32)),
+    Curve((f32, f32), (f32, f32), (f32, f32)),
+}
+
+/// Points for printpdf, flagging the points that start a Bézier curve
+fn path(start: (f32, f32), segments: &[Segment]) -> Vec<(Point, bool)> {
+    let point = |(x, y): (f32, f32)| Point::new(Mm(x), Mm(y));
+    let curve_next = |i: usize| matches!(segments.get(i), Some(Segment::Curve(..)));
+    let mut points = vec![(point(start), curve_next(0))];
+    for (i, segment) in segments.iter().enumerate() {
+        match *segment {
+            Segment::Line(to) => points.push((point(to), curve_next(i + 1))),
+            Segment::Curve(c1, c2, to) => {
+                points.push((point(c1), true));
+                points.push((point(c2), false));
+                points.push((point(to), curve_next(i + 1)));
+            }
+        }
+    }
+    points
+}
+
+/// Outline of a node shape whose bounding box has its top left at (x, y)
+fn shape_path(shape: &DiagramShape, x: f32, y: f32, w: f32, h: f32) -> Vec<(Point, bool)> {
+    // Control point distance for a quarter ellipse
+    const K: f32 = 0.5523;
+    match shape {
+        DiagramShape::Box => path((x, y), &[Segment::Line((x + w, y)), Segment::Line((x + w, y - h)), Segment::Line((x, y - h))]),
+        DiagramShape::Diamond => {
+            let (cx, cy) = (x + w / 2.0, y - h / 2.0);
+            path((cx, y), &[Segment::Line((x + w, cy)), Segment::Line((cx, y - h)), Segment::Line((x, cy))])
+        }
+        DiagramShape::Circle => {
+            let (rx, ry) = (w / 2.0, h / 2.0);
+            let (cx, cy) = (x + rx, y - ry);
+            path(
+                (cx + rx, cy),
+                &[
+                    Segment::Curve((cx + rx, cy + K * ry), (cx + K * rx, cy + ry), (cx, cy + ry)),
+                    Segment::Curve((cx - K * rx, cy + ry), (cx - rx, cy + K * ry), (cx - rx, cy)),
+                    Segment::Curve((cx - rx, cy - K * ry), (cx - K * rx, cy - ry), (cx, cy - ry)),
+                    Segment::Curve((cx + K * rx, cy - ry), (cx + rx, cy - K * ry), (cx + rx, cy)),
+                ],
+            )
+        }
+        DiagramShape::Rounded => {
+            let r = (w.min(h) * 0.25).min(3.0);
+            let k = r * (1.0 - K);
+            let (right, bottom) = (x + w, y - h);
+            path(
+                (x + r, y),
+                &[
+                    Segment::Line((right - r, y)),
+                    Segment::Curve((right - k, y), (right, y - k), (right, y - r)),
+                    Segment::Line((right, bottom + r)),
+                    Segment::Curve((right, bottom + k), (right - k, bottom), (right - r, bottom)),
+                    Segment::Line((x + r, bottom)),
+                    Segment::Curve((x + k, bottom), (x, bottom + k), (x, bottom + r)),
+                    Segment::Line((x, y - r)),
+                    Segment::Curve((x, y - k), (x + k, y), (x + r, y)),
+                ],
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use printpdf::lopdf;
+    use serde_json::json;
+
+    /// Render sections given as JSON to PDF bytes on A4 portrait pages
+    pub(crate) fn render(sections: serde_json::Value) -> Vec<u8> {
+        let content: DocumentContent = serde_json::from_value(json!({ "sections": sections })).unwrap();
+        let document = Document::new("Test".to_string(), "en".to_string(), content, String::new());
+        let assets = HashMap::new();
+        let mut renderer = PdfRenderer::new("Test", PageGeometry::default(), &assets).unwrap();
+        renderer.render_document(&document);
+        renderer.finish().save_to_bytes().unwrap()
+    }
+
+    /// Text drawn on each page as (baseline in mm from the bottom, text)
+    pub(crate) fn page_text(pdf: &[u8]) -> Vec<Vec<(f32, String)>> {
+        let pdf = lopdf::Document::load_mem(pdf).unwrap();
+        pdf.get_pages()
+            .values()
+            .map(|&page| {
+                let content = lopdf::content::Content::decode(&pdf.get_page_content(page).unwrap()).unwrap();
+                let mut baseline = 0.0;
+                let mut texts = Vec::new();
+                for op in content.operations {
+                    match op.operator.as_str() {
+                        "Td" => baseline = op.operands[1].as_float().unwrap() / (72.0 / 25.4),
+                        "Tj" => {
+                            let text = String::from_utf8_lossy(op.operands[0].as_str().unwrap()).to_string();
+                            texts.push((baseline, text));
+                        }
+                        _ => {}
+                    }
+                }
+                texts
+            })
+            .collect()
+    }
+
+    fn paragraph(text: &str) -> serde_json::Value {
+        json!({ "type": "paragraph", "text": text })
+    }
+
+    fn find<'p>(page: &'p [(f32, String)], needle: &str) -> Option<&'p (f32, String)> {
+        page.iter().find(|(_, text)| text.contains(needle))
+    }
+
+    #[test]
+    fn test_content_flows_onto_new_pages() {
+        let blocks: Vec<_> = (0..120).map(|i| paragraph(&format!("Paragraph {}", i))).collect();
+        let pages = page_text(&render(json!([{ "id": "s", "content": blocks }])));
+        assert!(pages.len() > 1);
+
+        let geometry = PageGeometry::default();
+        for (i, page) in pages.iter().enumerate() {
+            let footer = format!("Page {} of {}", i + 1, pages.len());
+            assert!(find(page, &footer).is_some(), "missing footer {}", footer);
+            for (baseline, text) in page.iter().filter(|(_, text)| text.starts_with("Paragraph")) {
+                assert!(*baseline >= geometry.bottom, "{} set in the bottom margin", text);
+            }
+        }
+        assert!(find(&pages[0], "Paragraph 0").is_some());
+        assert!(find(pages.last().unwrap(), "Paragraph 119").is_some());
+
+        // An explicit page break starts the next block on a fresh page
+        let pages = page_text(&render(json!([{ "id": "s", "content": [
+            paragraph("Before the break"),
+            { "type": "page_break" },
+            paragraph("After the break"),
+        ] }])));
+        assert_eq!(pages.len(), 2);
+        assert!(find(&pages[1], "After the break").is_some());
+    }
+
+    #[test]
+    fn test_text_wraps_to_content_width() {
+        let width = PageGeometry::default().content_width();
+        let text = "The quarterly figures were restated after the audit committee reviewed the revenue recognition policy. ".repeat(4);
+        let lines = wrap(&text, Face::Regular, BODY_SIZE, width);
+        assert!(lines.len() > 1);
+        for line in &lines {
+            assert!(text_width(line, Face::Regular, BODY_SIZE) <= width, "line too wide: {}", line);
+        }
+        assert_eq!(lines.join(" ").split_whitespace().count(), text.split_whitespace().count());
+
+        // A word wider than the line is broken across lines
+        let word = "X".repeat(200);
+        let lines = wrap(&word, Face::Bold, BODY_SIZE, width);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.concat(), word);
+
+        // Wrapped lines of a paragraph are set one below the other
+        let pages = page_text(&render(json!([{ "id": "s", "content": [paragraph(&text)] }])));
+        let body: Vec<f32> = pages[0].iter().filter(|(_, t)| t.contains("quarterly") || t.contains("policy")).map(|(y, _)| *y).collect();
+        assert!(body.len() > 1);
+        assert!(body.windows(2).all(|pair| pair[1] < pair[0]));
+    }
+
+    #[test]
+    fn test_table_header_repeats_on_every_page() {
+        let rows: Vec<_> = (0..150)
+            .map(|i| json!({ "item": { "raw": format!("Line item {}", i), "display": format!("Line item {}", i) },
+                             "amount": { "raw": i, "display": i.to_string() } }))
+            .collect();
+        let table = json!({
+            "type": "table",
+            "id": "tbl",
+            "columns": [
+                { "id": "item", "header": "Item", "type": "text" },
+                { "id": "amount", "header": "Amount (EUR)", "type": "number" }
+            ],
+            "rows": rows
+        });
+        let pages = page_text(&render(json!([{ "id": "s", "content": [table] }])));
+        assert!(pages.len() > 1);
+        for page in &pages {
+            let header = find(page, "Amount (EUR)").expect("header row on every page");
+            let first_row = page.iter().find(|(_, t)| t.starts_with("Line item")).expect("rows on every page");
+            assert!(header.0 > first_row.0, "header must be above the rows");
+        }
+    }
+
+    #[test]
+    fn test_footnote_set_at_bottom_of_referencing_page() {
+        let mut blocks: Vec<_> = (0..60).map(|i| paragraph(&format!("Filler {}", i))).collect();
+        blocks.push(paragraph("Revenue was restated.{{fn-restated}}"));
+        blocks.push(json!({ "type": "footnote", "id": "fn-restated", "text": "Restated under the revised policy." }));
+        let pages = page_text(&render(json!([{ "id": "s", "content": blocks }])));
+
+        let page = pages.iter().position(|p| find(p, "Revenue was restated.[1]").is_some()).expect("reference numbered");
+        assert!(page > 0, "reference should be past the first page");
+        let reference = find(&pages[page], "[1]").unwrap().0;
+        let note = find(&pages[page], "Restated under the revised policy.").expect("footnote on the referencing page").0;
+        assert!(note < reference);
+        assert!(note >= PageGeometry::default().bottom);
+        assert_eq!(pages.iter().flatten().filter(|(_, t)| t.contains("Restated under")).count(), 1);
+    }
+}
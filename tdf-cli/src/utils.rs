@@ -1,31 +1,188 @@
 use ed25519_dalek::{SigningKey, VerifyingKey};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::did::DidPublicKey;
+use tdf_core::key_file::StoredKey;
+use tdf_core::key_format::{decode_public_key, PrivateKey};
+use tdf_core::signature::SignatureAlgorithm;
+use zeroize::Zeroizing;
+
+/// Where private key passphrases come from
+#[derive(Debug, Clone, Default)]
+pub enum PassphraseSource {
+    /// Interactive prompt on the terminal
+    #[default]
+    Prompt,
+    /// Named environment variable
+    Env(String),
+    /// First line read from an inherited file descriptor
+    Fd(i32),
+}
+
+static PASSPHRASE_SOURCE: OnceLock<PassphraseSource> = OnceLock::new();
+static PASSPHRASES: Mutex<BTreeMap<PathBuf, Zeroizing<String>>> = Mutex::new(BTreeMap::new());
+
+/// Select the passphrase source for this process (set once from the command line)
+pub fn set_passphrase_source(source: PassphraseSource) {
+    let _ = PASSPHRASE_SOURCE.set(source);
+}
+
+/// Read the passphrase for an existing key file
+///
+/// Passphrases are cached per key path, so batch commands ask once for each
+/// key and different keys never share a passphrase.
+pub fn read_key_passphrase(path: &Path) -> TdfResult<Zeroizing<String>> {
+    let mut cache = PASSPHRASES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(passphrase) = cache.get(path) {
+        return Ok(passphrase.clone());
+    }
+    let passphrase = read_passphrase(&format!("Passphrase for {}: ", path.display()), false)?;
+    cache.insert(path.to_path_buf(), passphrase.clone());
+    Ok(passphrase)
+}
+
+/// Read a passphrase from the selected source
+///
+/// With `--passphrase-fd`, each call reads the next line from the descriptor.
+///
+/// # Arguments
+/// * `prompt` - Prompt shown when reading from the terminal
+/// * `confirm` - Ask twice (used when a new key file is written)
+pub fn read_passphrase(prompt: &str, confirm: bool) -> TdfResult<Zeroizing<String>> {
+    let passphrase = match PASSPHRASE_SOURCE.get().cloned().unwrap_or_default() {
+        PassphraseSource::Prompt => {
+            let first = Zeroizing::new(rpassword::prompt_password(prompt)?);
+            if confirm {
+                let second = Zeroizing::new(rpassword::prompt_password("Confirm passphrase: ")?);
+                if first != second {
+                    return Err(TdfError::InvalidDocument("Passphrases do not match".to_string()));
+                }
+            }
+            first
+        }
+        PassphraseSource::Env(var) => Zeroizing::new(std::env::var(&var).map_err(|_| {
+            TdfError::InvalidDocument(format!("Passphrase environment variable {} is not set", var))
+        })?),
+        PassphraseSource::Fd(fd) => read_passphrase_fd(fd)?,
+    };
+
+    if passphrase.is_empty() {
+        return Err(TdfError::InvalidDocument("Passphrase must not be empty".to_string()));
+    }
+    Ok(passphrase)
+}
+
+#[cfg(unix)]
+fn read_passphrase_fd(fd: i32) -> TdfResult<Zeroizing<String>> {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::io::FromRawFd;
+
+    static READER: Mutex<Option<BufReader<fs::File>>> = Mutex::new(None);
+
+    let mut reader = READER.lock().unwrap_or_else(|e| e.into_inner());
+    if reader.is_none() {
+        if fd <= 2 {
+            return Err(TdfError::InvalidDocument(
+                "--passphrase-fd must not be stdin, stdout or stderr".to_string(),
+            ));
+        }
+        // SAFETY: F_GETFD only queries descriptor flags
+        if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+            return Err(TdfError::InvalidDocument(format!("--passphrase-fd {} is not an open file descriptor", fd)));
+        }
+        // SAFETY: the descriptor is open and handed to us by the caller for
+        // this purpose; it is owned by the reader from here on.
+        *reader = Some(BufReader::new(unsafe { fs::File::from_raw_fd(fd) }));
+    }
+
+    let mut line = Zeroizing::new(String::new());
+    if let Some(reader) = reader.as_mut() {
+        reader.read_line(&mut line)?;
+    }
+    Ok(Zeroizing::new(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+#[cfg(not(unix))]
+fn read_passphrase_fd(_fd: i32) -> TdfResult<Zeroizing<String>> {
+    Err(TdfError::InvalidDocument("--passphrase-fd is only supported on Unix".to_string()))
+}
+
+fn warn_unencrypted_key(path: &Path) {
+    eprintln!(
+        "Warning: {} is an unencrypted private key file. Run `tdf key migrate {}` to encrypt it.",
+        path.display(),
+        path.display()
+    );
+}
+
+/// Algorithm implied by the key file name (`*.secp256k1.signing`)
+pub fn algorithm_from_path(path: &Path) -> SignatureAlgorithm {
+    let is_secp256k1 = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.contains(".secp256k1."));
+    if is_secp256k1 {
+        SignatureAlgorithm::Secp256k1
+    } else {
+        SignatureAlgorithm::Ed25519
+    }
+}
+
+/// Load a private key in any supported format
+///
+/// Accepts encrypted TDF key files (passphrase required), PKCS#8/SEC1 PEM or
+/// DER, JWK, and legacy raw 32-byte keys. Raw keys are interpreted as
+/// `raw_algorithm`; other formats carry their own algorithm.
+pub fn load_private_key(path: &Path, raw_algorithm: SignatureAlgorithm) -> TdfResult<PrivateKey> {
+    match StoredKey::read(path)? {
+        StoredKey::Encrypted(file) => {
+            file.decrypt_private_key(read_key_passphrase(path)?.as_bytes())
+        }
+        StoredKey::Plain(key) => {
+            warn_unencrypted_key(path);
+            Ok(key)
+        }
+        StoredKey::Legacy(secret) => {
+            warn_unencrypted_key(path);
+            PrivateKey::from_secret(&secret, raw_algorithm)
+        }
+    }
+}
+
+fn wrong_key_algorithm(path: &Path, key: &PrivateKey, expected: &str) -> TdfError {
+    TdfError::UnsupportedSignatureAlgorithm(format!(
+        "{} holds a {:?} key, expected {}",
+        path.display(),
+        key.algorithm(),
+        expected
+    ))
+}
 
 pub fn load_signing_key(path: &Path) -> TdfResult<SigningKey> {
-    let key_bytes = fs::read(path)?;
-    if key_bytes.len() != 32 {
-        return Err(TdfError::InvalidDocument(
-            "Signing key must be 32 bytes".to_string(),
-        ));
+    match load_private_key(path, SignatureAlgorithm::Ed25519)? {
+        PrivateKey::Ed25519(key) => Ok(key),
+        key => Err(wrong_key_algorithm(path, &key, "Ed25519")),
     }
-    let mut key_array = [0u8; 32];
-    key_array.copy_from_slice(&key_bytes);
-    Ok(SigningKey::from_bytes(&key_array))
 }
 
+/// Load an Ed25519 public key (raw, SPKI PEM/DER or JWK)
 pub fn load_verifying_key(path: &Path) -> TdfResult<VerifyingKey> {
-    let key_bytes = fs::read(path)?;
-    if key_bytes.len() != 32 {
-        return Err(TdfError::InvalidDocument(
-            "Verifying key must be 32 bytes".to_string(),
-        ));
+    match decode_public_key(&fs::read(path)?)? {
+        DidPublicKey::Ed25519(key) => Ok(key),
+        DidPublicKey::Secp256k1(_) => Err(TdfError::InvalidDocument(
+            "Verifying key must be an Ed25519 key".to_string(),
+        )),
+    }
+}
+
+pub fn load_secp256k1_signing_key(path: &Path) -> TdfResult<k256::ecdsa::SigningKey> {
+    match load_private_key(path, SignatureAlgorithm::Secp256k1)? {
+        PrivateKey::Secp256k1(key) => Ok(key),
+        key => Err(wrong_key_algorithm(path, &key, "secp256k1")),
     }
-    let mut key_array = [0u8; 32];
-    key_array.copy_from_slice(&key_bytes);
-    VerifyingKey::from_bytes(&key_array)
-        .map_err(|e| TdfError::InvalidDocument(format!("Invalid verifying key: {}", e)))
 }
 
 pub fn generate_keypair() -> (SigningKey, VerifyingKey) {
@@ -36,3 +193,15 @@ pub fn generate_keypair() -> (SigningKey, VerifyingKey) {
     (signing_key, verifying_key)
 }
 
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_fd_rejects_standard_and_closed_descriptors() {
+        for fd in [0, 1, 2] {
+            assert!(read_passphrase_fd(fd).unwrap_err().to_string().contains("stdin"));
+        }
+        assert!(read_passphrase_fd(4096).unwrap_err().to_string().contains("not an open"));
+    }
+}
@@ -2,7 +2,7 @@ use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
 use tdf_core::archive::ArchiveBuilder;
-use tdf_core::content::{ContentBlock, CellType, DocumentContent, Section, TableColumn, TableRow};
+use tdf_core::content::{ContentBlock, CellType, CellValue, Decimal, DocumentContent, Section, TableColumn, TableRow};
 use tdf_core::document::Document;
 use crate::error::ConvertError;
 use std::collections::HashMap;
@@ -50,7 +50,7 @@ pub fn convert_csv_to_tdf(
             }
         }
         
-        rows.push(TableRow { cells });
+        rows.push(TableRow::new(cells));
     }
     
     // Create table block
@@ -58,6 +58,7 @@ pub fn convert_csv_to_tdf(
         id: "data".to_string(),
         caption: Some(format!("Data from {}", input.file_name().unwrap_or_default().to_string_lossy())),
         columns,
+        header_rows: None,
         rows,
         footer: None,
     };
@@ -74,6 +75,7 @@ pub fn convert_csv_to_tdf(
             id: "data".to_string(),
             title: Some("Data".to_string()),
             content: vec![table],
+            encrypted: None,
         }],
     };
     
@@ -108,10 +110,77 @@ fn detect_column_type(header: &str) -> CellType {
     }
 }
 
+/// Table block from text cells, with column types inferred from the values
+///
+/// Empty cells are left out of their row.
+pub(crate) fn text_table(
+    id: String,
+    caption: Option<String>,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    footer: Option<Vec<String>>,
+) -> ContentBlock {
+    let columns: Vec<TableColumn> = headers
+        .into_iter()
+        .enumerate()
+        .map(|(idx, header)| {
+            let values: Vec<&str> = rows.iter().filter_map(|r| r.get(idx)).map(String::as_str).collect();
+            TableColumn { id: format!("col_{}", idx), header, cell_type: infer_column_type(&values), currency: None }
+        })
+        .collect();
+    let rows = rows
+        .iter()
+        .map(|row| {
+            TableRow::new(
+                row.iter()
+                    .zip(&columns)
+                    .filter(|(value, _)| !value.is_empty())
+                    .map(|(value, column)| (column.id.clone(), inferred_cell_value(value, &column.cell_type)))
+                    .collect(),
+            )
+        })
+        .collect();
+    ContentBlock::Table { id, caption, columns, header_rows: None, rows, footer }
+}
+
+/// Type that fits every non-empty value of a column, for tables imported
+/// without column types (Markdown and DOCX)
+pub(crate) fn infer_column_type(values: &[&str]) -> CellType {
+    let values: Vec<&str> = values.iter().map(|v| v.trim()).filter(|v| !v.is_empty()).collect();
+    let number = |v: &str| v.replace(',', "").trim().parse::<Decimal>().is_ok();
+    if values.is_empty() {
+        CellType::Text
+    } else if values.iter().all(|v| v.ends_with('%') && number(v.trim_end_matches('%'))) {
+        CellType::Percentage
+    } else if values.iter().all(|v| v.contains(['$', '€', '£']) && number(&v.replace(['$', '€', '£'], ""))) {
+        CellType::Currency
+    } else if values.iter().all(|v| number(v)) {
+        CellType::Number
+    } else if values.iter().all(|v| chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").is_ok()) {
+        CellType::Date
+    } else {
+        CellType::Text
+    }
+}
+
+/// Cell of a column typed by `infer_column_type`, whose amounts may carry
+/// thousands separators and any of the currency symbols it accepts
+fn inferred_cell_value(value: &str, cell_type: &CellType) -> CellValue {
+    let number = || value.replace(['$', '€', '£', '%', ','], "").trim().parse::<Decimal>().ok();
+    let display = value.to_string();
+    let cell = match cell_type {
+        CellType::Number => number().map(|raw| CellValue::Number { raw, display }),
+        CellType::Currency => number().map(|raw| CellValue::Currency { raw, display, currency: detect_currency(value) }),
+        CellType::Percentage => number().and_then(|n| n.shift(-2)).map(|raw| CellValue::Percentage { raw, display }),
+        _ => None,
+    };
+    cell.unwrap_or_else(|| parse_cell_value(value, cell_type))
+}
+
 fn parse_cell_value(value: &str, cell_type: &CellType) -> tdf_core::content::CellValue {
     match cell_type {
         CellType::Number => {
-            if let Ok(num) = value.parse::<f64>() {
+            if let Ok(num) = value.parse::<Decimal>() {
                 tdf_core::content::CellValue::Number {
                     raw: num,
                     display: value.to_string(),
@@ -123,7 +192,7 @@ fn parse_cell_value(value: &str, cell_type: &CellType) -> tdf_core::content::Cel
         CellType::Currency => {
             // Try to parse currency (remove $, €, etc.)
             let cleaned = value.replace("$", "").replace("€", "").replace(",", "").trim().to_string();
-            if let Ok(num) = cleaned.parse::<f64>() {
+            if let Ok(num) = cleaned.parse::<Decimal>() {
                 tdf_core::content::CellValue::Currency {
                     raw: num,
                     display: value.to_string(),
@@ -135,9 +204,9 @@ fn parse_cell_value(value: &str, cell_type: &CellType) -> tdf_core::content::Cel
         }
         CellType::Percentage => {
             let cleaned = value.replace("%", "").trim().to_string();
-            if let Ok(num) = cleaned.parse::<f64>() {
+            if let Some(num) = cleaned.parse::<Decimal>().ok().and_then(|n| n.shift(-2)) {
                 tdf_core::content::CellValue::Percentage {
-                    raw: num / 100.0,
+                    raw: num,
                     display: value.to_string(),
                 }
             } else {
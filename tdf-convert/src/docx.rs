@@ -1,9 +1,20 @@
+use std::collections::HashMap;
 use std::fs;
+use std::io::{Read, Seek};
 use std::path::Path;
 use tdf_core::archive::ArchiveBuilder;
-use tdf_core::content::{ContentBlock, DocumentContent, Section};
+use tdf_core::content::{
+    CellSpan, ContentBlock, DocumentContent, HeaderCell, InlineRun, InlineText, ListItem, Mark, NestedList, RowRole, Section, MAX_LIST_DEPTH,
+    MAX_ROW_LEVEL,
+};
 use tdf_core::document::Document;
+use crate::csv::text_table;
 use crate::error::ConvertError;
+use crate::export::docx::{
+    image_size, CHECKED_BOX, CODE_BLOCK_STYLE, CODE_STYLE, QUOTE_ATTRIBUTION_STYLE, QUOTE_STYLE, ROW_LEVEL_INDENT_TWIPS, SECTION_STYLE,
+    SUBTOTAL_STYLE, TOTAL_STYLE, UNCHECKED_BOX,
+};
+use crate::xml::{parse_xml, Node};
 
 pub fn convert_docx_to_tdf(
     input: &Path,
@@ -14,114 +25,808 @@ pub fn convert_docx_to_tdf(
 ) -> Result<(), ConvertError> {
     // Read DOCX file
     let file = fs::File::open(input)?;
-    let mut archive = zip::ZipArchive::new(std::io::BufReader::new(file))
-        .map_err(|e| ConvertError::Docx(format!("Failed to open DOCX: {}", e)))?;
-    
-    // Read document.xml
-    let mut doc_xml = String::new();
-    let mut doc_file = archive.by_name("word/document.xml")
-        .map_err(|e| ConvertError::Docx(format!("Failed to read document.xml: {}", e)))?;
-    std::io::Read::read_to_string(&mut doc_file, &mut doc_xml)
-        .map_err(|e| ConvertError::Docx(format!("Failed to read document content: {}", e)))?;
-    
-    // Parse XML and extract text
-    // This is a simplified parser - for production, use a proper XML parser
-    let text = extract_text_from_docx_xml(&doc_xml);
-    
-    // Process text into blocks
-    let paragraphs: Vec<&str> = text
-        .split("\n\n")
-        .map(|p| p.trim())
-        .filter(|p| !p.is_empty())
-        .collect();
-    
-    let mut content_blocks = Vec::new();
-    
-    // Add title
-    let title = input
+    let fallback_title = input
         .file_stem()
         .and_then(|s| s.to_str())
-        .unwrap_or("DOCX Import")
-        .to_string();
-    
-    content_blocks.push(ContentBlock::Heading {
-        level: 1,
-        text: title.clone(),
-        id: Some("title".to_string()),
-    });
-    
-    // Add paragraphs
-    for (idx, para) in paragraphs.iter().enumerate() {
-        content_blocks.push(ContentBlock::Paragraph {
-            text: para.to_string(),
-            id: Some(format!("para-{}", idx)),
-        });
-    }
-    
-    let content = DocumentContent {
-        sections: vec![Section {
-            id: "content".to_string(),
-            title: Some("Content".to_string()),
-            content: content_blocks,
-        }],
-    };
-    
-    let styles = get_default_styles();
-    let mut document = Document::new(title, "en".to_string(), content, styles);
-    document.manifest.integrity.algorithm = tdf_core::document::HashAlgorithm::Sha256;
-    
+        .unwrap_or("DOCX Import");
+    let (document, assets) = docx_to_document(std::io::BufReader::new(file), fallback_title)?;
+
     // Build archive
     let mut builder = ArchiveBuilder::new(document);
+    for (path, data) in assets {
+        builder.add_asset(path, data)?;
+    }
     builder.build(
         output,
         None,
         signer_id,
         signer_name,
     )?;
-    
+
     Ok(())
 }
 
-fn extract_text_from_docx_xml(xml: &str) -> String {
-    // Simple text extraction from DOCX XML
-    // Remove XML tags and extract text content
+/// Read a DOCX package into a document
+///
+/// Paragraph styles decide the blocks: `Title` is the document title,
+/// `TdfSection` starts a section, `Heading1`-`Heading6` are headings,
+/// `Quote` and `CodeBlock` paragraphs quotes and code and numbered
+/// paragraphs become lists, nested by numbering level, with a leading
+/// ballot box making an item a task. Page breaks and empty paragraphs
+/// with a border become page break and rule blocks. Tables take their
+/// repeated header rows (or else the first row) as headers, the last of
+/// them naming the columns, and a marked total row as the footer; merged
+/// cells become spans, `TableSubtotal` and `TableTotal` rows get those
+/// roles and an indented first cell an outline level. `Caption` paragraphs
+/// before a table or after a picture become captions. Footnote references
+/// become footnote reference runs with the footnote blocks at the end of
+/// the section that first cites them. Direct run formatting becomes marks,
+/// hyperlinks links and hyperlinks to bookmarks cross-references; a
+/// bookmark on a paragraph gives its block that id.
+///
+/// # Arguments
+/// * `reader` - The `.docx` file
+/// * `fallback_title` - Title used when the document has no `Title`
+///   paragraph; it is also added as a heading, as documents without one
+///   have no other visible title
+///
+/// # Returns
+/// The document and its embedded images, keyed by asset path
+pub fn docx_to_document<R: Read + Seek>(
+    reader: R,
+    fallback_title: &str,
+) -> Result<(Document, HashMap<String, Vec<u8>>), ConvertError> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| ConvertError::Docx(format!("Failed to open DOCX: {}", e)))?;
+    let body = read_part(&mut archive, "word/document.xml")?
+        .ok_or_else(|| ConvertError::Docx("Failed to read document.xml: not found".to_string()))?;
+    let body = parse_xml(&body)?;
+    let relationships = match read_part(&mut archive, "word/_rels/document.xml.rels")? {
+        Some(xml) => parse_xml(&xml)?
+            .children
+            .iter()
+            .filter_map(|r| Some((r.attr("Id")?.to_string(), r.attr("Target")?.to_string())))
+            .collect(),
+        None => HashMap::new(),
+    };
+    let numbering = match read_part(&mut archive, "word/numbering.xml")? {
+        Some(xml) => ordered_lists(&parse_xml(&xml)?),
+        None => HashMap::new(),
+    };
+    let footnotes = match read_part(&mut archive, "word/footnotes.xml")? {
+        Some(xml) => parse_xml(&xml)?
+            .children
+            .iter()
+            .filter(|n| n.name == "w:footnote" && n.attr("w:type").is_none())
+            .filter_map(|n| {
+                let paragraphs: Vec<String> =
+                    n.all("w:p").into_iter().map(|p| plain_text(p).trim().to_string()).collect();
+                Some((n.attr("w:id")?.to_string(), paragraphs.join("\n")))
+            })
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    let mut importer = DocxImporter {
+        relationships,
+        numbering,
+        footnotes,
+        ..DocxImporter::default()
+    };
+    if let Some(body) = body.find("w:body") {
+        importer.body(&body.children);
+    }
+    let mut sections = importer.finish();
+
+    let title = match importer.title.take() {
+        Some(title) => title,
+        None => {
+            let title = fallback_title.to_string();
+            if sections.is_empty() {
+                sections.push(Section {
+                    id: "content".to_string(),
+                    title: Some("Content".to_string()),
+                    content: Vec::new(),
+                    encrypted: None,
+                });
+            }
+            sections[0].content.insert(0, ContentBlock::Heading {
+                level: 1,
+                text: title.clone().into(),
+                id: Some("title".to_string()),
+            });
+            title
+        }
+    };
+
+    let mut assets = HashMap::new();
+    for (key, target) in importer.images {
+        let path = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("word/{}", target),
+        };
+        if let Some(data) = read_binary(&mut archive, &path)? {
+            assets.insert(key, data);
+        }
+    }
+    // Pictures shown at their natural size need no width
+    for block in sections.iter_mut().flat_map(|s| s.content.iter_mut()) {
+        if let ContentBlock::Figure { asset, width, .. } = block {
+            let natural = assets.get(asset.as_str()).and_then(|data| image_size(data)).map(|(w, _)| w);
+            if natural.is_some() && natural == *width {
+                *width = None;
+            }
+        }
+    }
+
+    let content = DocumentContent { sections };
+    let styles = get_default_styles();
+    let mut document = Document::new(title, "en".to_string(), content, styles);
+    document.manifest.integrity.algorithm = tdf_core::document::HashAlgorithm::Sha256;
+    Ok((document, assets))
+}
+
+fn read_part<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Result<Option<String>, ConvertError> {
+    let Some(data) = read_binary(archive, name)? else { return Ok(None) };
+    String::from_utf8(data)
+        .map(Some)
+        .map_err(|e| ConvertError::Docx(format!("Failed to read {}: {}", name, e)))
+}
+
+fn read_binary<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Result<Option<Vec<u8>>, ConvertError> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(ConvertError::Docx(format!("Failed to read {}: {}", name, e))),
+    };
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .map_err(|e| ConvertError::Docx(format!("Failed to read {}: {}", name, e)))?;
+    Ok(Some(data))
+}
+
+impl Node {
+    /// `w:val` of a child of the properties element `properties`
+    fn property(&self, properties: &str, name: &str) -> Option<&str> {
+        self.child(properties)?.child(name)?.attr("w:val")
+    }
+}
+
+/// Whether each level of each numbering instance counts (true) or uses
+/// bullets
+fn ordered_lists(numbering: &Node) -> HashMap<String, Vec<bool>> {
+    let formats: HashMap<&str, Vec<bool>> = numbering
+        .children
+        .iter()
+        .filter(|n| n.name == "w:abstractNum")
+        .filter_map(|n| {
+            let mut levels = vec![false; MAX_LIST_DEPTH];
+            for level in n.children.iter().filter(|l| l.name == "w:lvl") {
+                let Some(index) = level.attr("w:ilvl").and_then(|i| i.parse::<usize>().ok()).filter(|i| *i < MAX_LIST_DEPTH) else {
+                    continue;
+                };
+                let format = level.child("w:numFmt").and_then(|f| f.attr("w:val")).unwrap_or("bullet");
+                levels[index] = !matches!(format, "bullet" | "none");
+            }
+            Some((n.attr("w:abstractNumId")?, levels))
+        })
+        .collect();
+    numbering
+        .children
+        .iter()
+        .filter(|n| n.name == "w:num")
+        .filter_map(|n| {
+            let abstract_id = n.child("w:abstractNumId")?.attr("w:val")?;
+            Some((n.attr("w:numId")?.to_string(), formats.get(abstract_id)?.clone()))
+        })
+        .collect()
+}
+
+/// Text of an element's runs, with tabs and line breaks
+fn plain_text(node: &Node) -> String {
     let mut text = String::new();
-    let mut in_text = false;
-    let mut current_text = String::new();
-    
-    for line in xml.lines() {
-        if line.contains("<w:t>") {
-            in_text = true;
-            if let Some(start) = line.find("<w:t>") {
-                if let Some(end) = line.find("</w:t>") {
-                    let content = &line[start + 5..end];
-                    current_text.push_str(content);
+    collect_text(node, &mut |part| match part {
+        Run::Text(t, ..) => text.push_str(t),
+        Run::Footnote(_) | Run::Drawing(_) => {}
+    });
+    text
+}
+
+/// Inline content of a paragraph
+enum Run<'n> {
+    /// Text with the `w:r` and `w:hyperlink` elements it is in
+    Text(&'n str, Option<&'n Node>, Option<&'n Node>),
+    /// Word footnote id
+    Footnote(&'n str),
+    Drawing(&'n Node),
+}
+
+fn collect_text<'n>(node: &'n Node, out: &mut dyn FnMut(Run<'n>)) {
+    collect_runs(node, None, None, out);
+}
+
+fn collect_runs<'n>(node: &'n Node, run: Option<&'n Node>, link: Option<&'n Node>, out: &mut dyn FnMut(Run<'n>)) {
+    for child in &node.children {
+        match child.name.as_str() {
+            "w:t" => out(Run::Text(&child.text, run, link)),
+            "w:tab" if node.name == "w:r" => out(Run::Text("\t", run, link)),
+            "w:br" | "w:cr" if child.attr("w:type").is_none_or(|t| t == "textWrapping") => out(Run::Text("\n", run, link)),
+            "w:noBreakHyphen" => out(Run::Text("-", run, link)),
+            "w:footnoteReference" => {
+                if let Some(id) = child.attr("w:id") {
+                    out(Run::Footnote(id));
                 }
             }
-        } else if line.contains("</w:t>") {
-            in_text = false;
-            if !current_text.is_empty() {
-                text.push_str(&current_text);
-                text.push_str(" ");
-                current_text.clear();
+            "w:drawing" => out(Run::Drawing(child)),
+            // Properties, deleted text and field codes are not content
+            "w:pPr" | "w:rPr" | "w:del" | "w:instrText" | "w:tblPr" | "w:tcPr" | "w:trPr" => {}
+            "w:r" => collect_runs(child, Some(child), link, out),
+            "w:hyperlink" => collect_runs(child, run, Some(child), out),
+            _ => collect_runs(child, run, link, out),
+        }
+    }
+}
+
+/// Marks from the direct formatting of a `w:r` element
+fn run_marks(run: &Node) -> Vec<Mark> {
+    let Some(properties) = run.child("w:rPr") else { return Vec::new() };
+    // Toggle properties are on unless `w:val` turns them off
+    let on = |name: &str| {
+        properties
+            .child(name)
+            .is_some_and(|p| !matches!(p.attr("w:val"), Some("0" | "false" | "off" | "none")))
+    };
+    let mut marks = Vec::new();
+    if on("w:b") {
+        marks.push(Mark::Bold);
+    }
+    if on("w:i") {
+        marks.push(Mark::Italic);
+    }
+    if on("w:u") {
+        marks.push(Mark::Underline);
+    }
+    if on("w:strike") || on("w:dstrike") {
+        marks.push(Mark::Strike);
+    }
+    match properties.child("w:vertAlign").and_then(|v| v.attr("w:val")) {
+        Some("superscript") => marks.push(Mark::Superscript),
+        Some("subscript") => marks.push(Mark::Subscript),
+        _ => {}
+    }
+    let font = properties.child("w:rFonts").and_then(|f| f.attr("w:ascii"));
+    if properties.child("w:rStyle").and_then(|s| s.attr("w:val")) == Some(CODE_STYLE)
+        || matches!(font, Some("Courier New" | "Consolas"))
+    {
+        marks.push(Mark::Code);
+    }
+    marks
+}
+
+/// Name of the first bookmark in a paragraph; Word's own hidden
+/// bookmarks (`_GoBack`, `_Toc...`) are skipped
+fn bookmark(paragraph: &Node) -> Option<String> {
+    paragraph
+        .all("w:bookmarkStart")
+        .into_iter()
+        .filter_map(|b| b.attr("w:name"))
+        .find(|name| !name.is_empty() && !name.starts_with('_'))
+        .map(str::to_string)
+}
+
+/// Role and outline level of a table row, from the style and indent of
+/// its first cell's paragraph
+fn row_format(row: &Node) -> (RowRole, u8) {
+    let Some(paragraph) = row.child("w:tc").and_then(|cell| cell.child("w:p")) else { return (RowRole::Data, 0) };
+    let role = match paragraph.property("w:pPr", "w:pStyle") {
+        Some(SUBTOTAL_STYLE) => RowRole::Subtotal,
+        Some(TOTAL_STYLE) => RowRole::Total,
+        _ => RowRole::Data,
+    };
+    let indent = paragraph
+        .child("w:pPr")
+        .and_then(|p| p.child("w:ind"))
+        .and_then(|ind| ind.attr("w:left").or(ind.attr("w:start")))
+        .and_then(|left| left.parse::<usize>().ok())
+        .unwrap_or(0);
+    let level = (indent + ROW_LEVEL_INDENT_TWIPS / 2) / ROW_LEVEL_INDENT_TWIPS;
+    (role, level.min(usize::from(MAX_ROW_LEVEL)) as u8)
+}
+
+/// Where a paragraph has a page break: `Some(true)` before its text,
+/// `Some(false)` after it
+fn page_break(paragraph: &Node) -> Option<bool> {
+    fn first(node: &Node) -> Option<&'static str> {
+        node.children.iter().find_map(|child| match child.name.as_str() {
+            "w:t" if !child.text.is_empty() => Some("text"),
+            "w:br" if child.attr("w:type") == Some("page") => Some("break"),
+            "w:del" | "w:instrText" => None,
+            _ => first(child),
+        })
+    }
+    let before = paragraph.child("w:pPr").and_then(|p| p.child("w:pageBreakBefore")).is_some_and(|b| b.attr("w:val") != Some("0"));
+    match first(paragraph) {
+        _ if before => Some(true),
+        Some("break") => Some(true),
+        Some(_) => paragraph.all("w:br").iter().any(|b| b.attr("w:type") == Some("page")).then_some(false),
+        None => None,
+    }
+}
+
+/// Whether an empty paragraph draws a rule with a top or bottom border
+fn is_rule(paragraph: &Node) -> bool {
+    paragraph
+        .child("w:pPr")
+        .and_then(|p| p.child("w:pBdr"))
+        .is_some_and(|b| b.children.iter().any(|side| matches!(side.name.as_str(), "w:top" | "w:bottom") && side.attr("w:val") != Some("nil")))
+}
+
+/// Task state of a list item led by a ballot box, and the item without it
+fn task_item(text: InlineText) -> (Option<bool>, InlineText) {
+    let mut runs = text.runs();
+    let checked = match runs.first() {
+        Some(InlineRun::Text { text, .. }) => match text.chars().next() {
+            Some(c) if c == UNCHECKED_BOX => Some(false),
+            Some(c) if c == CHECKED_BOX || c == '\u{2611}' => Some(true),
+            _ => None,
+        },
+        _ => None,
+    };
+    if checked.is_some() {
+        if let Some(InlineRun::Text { text, .. }) = runs.first_mut() {
+            *text = text.chars().skip(1).collect::<String>().trim_start().to_string();
+        }
+    }
+    (checked, InlineText::from_runs(runs))
+}
+
+/// A list, or a list nested in the last item of the one before it
+struct ListFrame {
+    numbering: String,
+    level: usize,
+    ordered: bool,
+    items: Vec<ListItem>,
+}
+
+/// A picture found in a paragraph
+struct Picture {
+    asset: String,
+    alt: String,
+    width: Option<u32>,
+}
+
+#[derive(Default)]
+struct DocxImporter {
+    /// Relationship id to target, from `document.xml.rels`
+    relationships: HashMap<String, String>,
+    /// Numbering instance to whether each level is ordered, from
+    /// `numbering.xml`
+    numbering: HashMap<String, Vec<bool>>,
+    /// Word footnote id to text, from `footnotes.xml`
+    footnotes: HashMap<String, String>,
+    title: Option<String>,
+    sections: Vec<Section>,
+    section: Option<Section>,
+    blocks: Vec<ContentBlock>,
+    /// The list being read, outermost first, and its bookmark
+    list: Vec<ListFrame>,
+    list_anchor: Option<String>,
+    /// TDF footnote id of each Word footnote referenced so far
+    footnote_ids: HashMap<String, String>,
+    /// Footnote blocks created so far
+    footnote_count: usize,
+    /// Footnotes first referenced in the current section
+    pending_footnotes: Vec<ContentBlock>,
+    /// Caption paragraph and its bookmark for the table that follows
+    pending_caption: Option<(String, Option<String>)>,
+    /// Asset path to package part of each embedded image
+    images: Vec<(String, String)>,
+    counters: HashMap<&'static str, usize>,
+}
+
+impl DocxImporter {
+    fn next_id(&mut self, kind: &'static str) -> String {
+        let counter = self.counters.entry(kind).or_insert(0);
+        *counter += 1;
+        format!("{}-{}", kind, counter)
+    }
+
+    fn body(&mut self, children: &[Node]) {
+        for (i, node) in children.iter().enumerate() {
+            match node.name.as_str() {
+                "w:p" => {
+                    let next_is_table = children[i + 1..]
+                        .iter()
+                        .find(|n| n.name == "w:p" || n.name == "w:tbl")
+                        .is_some_and(|n| n.name == "w:tbl");
+                    self.paragraph(node, next_is_table);
+                }
+                "w:tbl" => {
+                    self.flush_list();
+                    self.table(node);
+                }
+                "w:sdt" => {
+                    if let Some(content) = node.child("w:sdtContent") {
+                        self.body(&content.children);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Paragraph text as runs, and its pictures
+    fn inline(&mut self, paragraph: &Node) -> (InlineText, Vec<Picture>) {
+        let mut parts = Vec::new();
+        collect_text(paragraph, &mut |run| parts.push(run));
+        let mut runs = Vec::new();
+        let mut pictures = Vec::new();
+        // The hyperlink to a bookmark whose text is being collected
+        let mut anchor: Option<&Node> = None;
+        for part in parts {
+            match part {
+                Run::Text(t, run, link) => {
+                    let target = link.and_then(|l| l.attr("w:anchor")).filter(|_| link.and_then(|l| l.attr("r:id")).is_none());
+                    if let (Some(target), Some(link)) = (target, link) {
+                        match runs.last_mut() {
+                            Some(InlineRun::CrossRef { text, .. }) if anchor.is_some_and(|a| std::ptr::eq(a, link)) => text.push_str(t),
+                            _ => runs.push(InlineRun::CrossRef { target: target.to_string(), text: t.to_string() }),
+                        }
+                        anchor = Some(link);
+                        continue;
+                    }
+                    let href = link.and_then(|l| l.attr("r:id")).and_then(|id| self.relationships.get(id)).cloned();
+                    let mut marks = run.map(run_marks).unwrap_or_default();
+                    marks.sort();
+                    runs.push(InlineRun::Text { text: t.to_string(), marks, link: href });
+                }
+                Run::Footnote(word_id) => {
+                    let id = self.footnote(word_id);
+                    runs.push(InlineRun::FootnoteRef { id });
+                }
+                Run::Drawing(drawing) => pictures.extend(self.picture(drawing)),
+            }
+            anchor = None;
+        }
+        (InlineText::from_runs(runs), pictures)
+    }
+
+    /// TDF id of a Word footnote, creating its block on first reference
+    fn footnote(&mut self, word_id: &str) -> String {
+        if let Some(id) = self.footnote_ids.get(word_id) {
+            return id.clone();
+        }
+        self.footnote_count += 1;
+        let id = format!("fn:{}", self.footnote_count);
+        self.footnote_ids.insert(word_id.to_string(), id.clone());
+        let text = self.footnotes.get(word_id).cloned().unwrap_or_default();
+        self.pending_footnotes.push(ContentBlock::Footnote { id: id.clone(), text });
+        id
+    }
+
+    fn picture(&mut self, drawing: &Node) -> Option<Picture> {
+        let relationship = drawing.find("a:blip")?.attr("r:embed")?;
+        let target = self.relationships.get(relationship)?.clone();
+        let name = target.rsplit('/').next().unwrap_or(&target);
+        if name.is_empty() || name.contains("..") {
+            return None;
+        }
+        let asset = format!("assets/images/{}", name);
+        let alt = drawing.find("wp:docPr").and_then(|d| d.attr("descr")).unwrap_or("").to_string();
+        let shown = drawing
+            .find("wp:extent")
+            .and_then(|e| e.attr("cx"))
+            .and_then(|cx| cx.parse::<u64>().ok())
+            .map(|cx| (cx / 9525) as u32);
+        if !self.images.iter().any(|(key, _)| *key == asset) {
+            self.images.push((asset.clone(), target));
+        }
+        Some(Picture { asset, alt, width: shown })
+    }
+
+    fn paragraph(&mut self, node: &Node, next_is_table: bool) {
+        let page_break = page_break(node);
+        if page_break == Some(true) {
+            self.flush_list();
+            self.blocks.push(ContentBlock::PageBreak);
+        }
+        self.paragraph_content(node, next_is_table);
+        if page_break == Some(false) {
+            self.flush_list();
+            self.blocks.push(ContentBlock::PageBreak);
+        }
+    }
+
+    fn paragraph_content(&mut self, node: &Node, next_is_table: bool) {
+        let style = node.property("w:pPr", "w:pStyle").unwrap_or("").to_string();
+        let numbering = node
+            .child("w:pPr")
+            .and_then(|p| p.child("w:numPr"))
+            .and_then(|n| n.child("w:numId"))
+            .and_then(|n| n.attr("w:val"))
+            .filter(|id| *id != "0")
+            .map(str::to_string);
+        let level = node
+            .child("w:pPr")
+            .and_then(|p| p.child("w:numPr"))
+            .and_then(|n| n.child("w:ilvl"))
+            .and_then(|n| n.attr("w:val"))
+            .and_then(|l| l.parse::<usize>().ok())
+            .unwrap_or(0)
+            .min(MAX_LIST_DEPTH - 1);
+        let (inline, pictures) = self.inline(node);
+        let text = inline.plain_text().into_owned();
+        let anchor = bookmark(node);
+
+        if style == "Title" && self.title.is_none() && self.section.is_none() && self.blocks.is_empty() {
+            // An empty title paragraph leaves the fallback title in place
+            if !text.trim().is_empty() {
+                self.title = Some(text.trim().to_string());
+            }
+            return;
+        }
+        if style == SECTION_STYLE {
+            self.finish_section();
+            let title = text.trim();
+            let id = anchor.unwrap_or_else(|| self.next_id("section"));
+            self.section = Some(Section {
+                id,
+                title: (!title.is_empty()).then(|| title.to_string()),
+                content: Vec::new(),
+                encrypted: None,
+            });
+            return;
+        }
+
+        if let Some(numbering) = numbering {
+            self.list_item(numbering, level, inline, anchor);
+            return;
+        }
+        self.flush_list();
+
+        if text.is_empty() && pictures.is_empty() && is_rule(node) {
+            self.blocks.push(ContentBlock::HorizontalRule);
+            return;
+        }
+
+        if let Some(picture) = pictures.into_iter().next() {
+            let id = self.next_id("figure");
+            self.blocks.push(ContentBlock::Figure {
+                id,
+                asset: picture.asset.clone(),
+                alt: picture.alt,
+                caption: None,
+                width: picture.width,
+            });
+            if text.trim().is_empty() {
+                return;
+            }
+        }
+        if text.trim().is_empty() {
+            return;
+        }
+
+        if style == "Caption" {
+            if next_is_table {
+                self.pending_caption = Some((text, anchor));
+                return;
+            }
+            if let Some(ContentBlock::Figure { id, caption: caption @ None, .. }) = self.blocks.last_mut() {
+                *caption = Some(text);
+                if let Some(anchor) = anchor {
+                    *id = anchor;
+                }
+                return;
+            }
+        }
+        if style == CODE_BLOCK_STYLE {
+            let id = anchor.unwrap_or_else(|| self.next_id("code"));
+            self.blocks.push(ContentBlock::Code { language: None, text, id: Some(id) });
+            return;
+        }
+        if style == QUOTE_ATTRIBUTION_STYLE {
+            if let Some(ContentBlock::Quote { attribution: slot @ None, .. }) = self.blocks.last_mut() {
+                let source = text.trim();
+                *slot = Some(source.strip_prefix('\u{2014}').unwrap_or(source).trim().to_string());
+                return;
             }
-        } else if in_text && line.contains("</w:t>") {
-            if let Some(end) = line.find("</w:t>") {
-                let content = &line[..end];
-                current_text.push_str(content);
-                text.push_str(&current_text);
-                text.push_str(" ");
-                current_text.clear();
+        }
+        if matches!(style.as_str(), QUOTE_STYLE | "IntenseQuote") {
+            let id = anchor.unwrap_or_else(|| self.next_id("quote"));
+            self.blocks.push(ContentBlock::Quote { text: inline, attribution: None, id: Some(id) });
+            return;
+        }
+        if style == "FootnoteText" {
+            self.footnote_count += 1;
+            let id = format!("fn:{}", self.footnote_count);
+            self.blocks.push(ContentBlock::Footnote { id, text });
+            return;
+        }
+
+        let level = style
+            .strip_prefix("Heading")
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| (1..=6).contains(n));
+        match level {
+            Some(level) => {
+                let id = anchor.unwrap_or_else(|| self.next_id("heading"));
+                self.blocks.push(ContentBlock::Heading { level, text: inline, id: Some(id) });
+            }
+            None => {
+                let id = anchor.unwrap_or_else(|| self.next_id("para"));
+                self.blocks.push(ContentBlock::Paragraph { text: inline, id: Some(id), xbrl: None });
+            }
+        }
+    }
+
+    fn table(&mut self, node: &Node) {
+        let (caption, anchor) = self.pending_caption.take().unzip();
+        let table_rows: Vec<&Node> = node.children.iter().filter(|n| n.name == "w:tr").collect();
+        // Cell texts by grid column, with merged cells spread over the
+        // columns they cover, and the merges as (row, column, colspan, rowspan)
+        let mut grid: Vec<Vec<String>> = Vec::new();
+        let mut merges: Vec<(usize, usize, usize, usize)> = Vec::new();
+        let mut formats = Vec::new();
+        for (r, row) in table_rows.iter().enumerate() {
+            let mut texts: Vec<String> = Vec::new();
+            for cell in row.children.iter().filter(|n| n.name == "w:tc") {
+                let column = texts.len();
+                let colspan = cell.property("w:tcPr", "w:gridSpan").and_then(|v| v.parse().ok()).unwrap_or(1usize).max(1);
+                let vertical = cell.child("w:tcPr").and_then(|p| p.child("w:vMerge"));
+                if vertical.is_some_and(|v| v.attr("w:val").is_none_or(|v| v == "continue")) {
+                    if let Some(merge) = merges.iter_mut().find(|m| m.1 == column && m.0 + m.3 == r) {
+                        merge.3 += 1;
+                    }
+                    texts.resize(column + colspan, String::new());
+                    continue;
+                }
+                if colspan > 1 || vertical.is_some() {
+                    merges.push((r, column, colspan, 1));
+                }
+                let paragraphs: Vec<String> = cell.all("w:p").into_iter().map(|p| self.inline(p).0.plain_text().into_owned()).collect();
+                texts.push(paragraphs.join("\n"));
+                texts.resize(column + colspan, String::new());
+            }
+            grid.push(texts);
+            formats.push(row_format(row));
+        }
+        merges.retain(|m| m.2 > 1 || m.3 > 1);
+
+        // Repeated header rows group the columns named by the last of them
+        let repeated = table_rows
+            .iter()
+            .take_while(|row| row.child("w:trPr").and_then(|p| p.child("w:tblHeader")).is_some())
+            .count();
+        let groups = repeated.saturating_sub(1).min(grid.len().saturating_sub(1));
+        if grid.is_empty() {
+            return;
+        }
+        for &(r, c, colspan, rowspan) in &merges {
+            if r < groups && colspan == 1 && r + rowspan > groups && grid[groups].get(c).is_some_and(String::is_empty) {
+                grid[groups][c] = std::mem::take(&mut grid[r][c]);
+            }
+        }
+        let width = grid[groups].len();
+        let group_rows: Vec<Vec<HeaderCell>> = grid[..groups]
+            .iter()
+            .enumerate()
+            .map(|(r, texts)| {
+                let mut cells = Vec::new();
+                let mut c = 0;
+                while c < width {
+                    let colspan = merges.iter().find(|m| (m.0, m.1) == (r, c)).map_or(1, |m| m.2).min(width - c);
+                    cells.push(HeaderCell::new(texts.get(c).cloned().unwrap_or_default(), colspan));
+                    c += colspan;
+                }
+                cells
+            })
+            .collect();
+        let mut rows = grid.split_off(groups + 1);
+        let headers = grid.pop().unwrap_or_default();
+
+        // The total row is marked in the table look, as an attribute or
+        // as bit 0x0040 of the legacy hex value
+        let look = node.child("w:tblPr").and_then(|p| p.child("w:tblLook"));
+        let last_row = look.is_some_and(|look| match look.attr("w:lastRow") {
+            Some(value) => matches!(value, "1" | "true" | "on"),
+            None => look.attr("w:val").and_then(|v| u16::from_str_radix(v, 16).ok()).is_some_and(|v| v & 0x0040 != 0),
+        });
+        let footer = if last_row && !rows.is_empty() { rows.pop() } else { None };
+
+        let id = anchor.flatten().unwrap_or_else(|| self.next_id("table"));
+        let data_rows = rows.len();
+        let mut table = text_table(id, caption, headers, rows, footer);
+        if let ContentBlock::Table { columns, header_rows, rows, .. } = &mut table {
+            *header_rows = (!group_rows.is_empty()).then_some(group_rows);
+            let first = groups + 1;
+            for (row, (role, level)) in rows.iter_mut().zip(&formats[first..]) {
+                row.format.role = *role;
+                row.format.level = *level;
+            }
+            for &(r, c, colspan, rowspan) in merges.iter().filter(|m| m.0 >= first && m.0 - first < data_rows) {
+                let (r, Some(column)) = (r - first, columns.get(c)) else { continue };
+                let span = CellSpan { colspan: colspan.min(columns.len() - c), rowspan: rowspan.min(data_rows - r) };
+                rows[r].format.spans.insert(column.id.clone(), span);
             }
-            in_text = false;
         }
+        self.blocks.push(table);
+    }
+
+    /// Add a numbered paragraph to the list being read
+    ///
+    /// A deeper level starts a list nested in the item before; a new
+    /// numbering instance at the outermost level starts a new list.
+    fn list_item(&mut self, numbering: String, level: usize, text: InlineText, anchor: Option<String>) {
+        while self.list.len() > 1 && self.list.last().is_some_and(|frame| frame.level > level) {
+            self.close_nested_list();
+        }
+        if self.list.first().is_some_and(|root| root.numbering != numbering && level <= root.level) {
+            self.flush_list();
+        }
+        let nested = self.list.last().is_some_and(|frame| level > frame.level && !frame.items.is_empty());
+        if self.list.is_empty() || nested {
+            let ordered = self.numbering.get(&numbering).and_then(|levels| levels.get(level)).copied().unwrap_or(false);
+            if self.list.is_empty() {
+                self.list_anchor = anchor;
+            }
+            self.list.push(ListFrame { numbering, level, ordered, items: Vec::new() });
+        }
+        let (checked, text) = task_item(text);
+        if let Some(frame) = self.list.last_mut() {
+            frame.items.push(ListItem { text, checked, sublist: None });
+        }
+    }
+
+    /// Attach the innermost list to the last item of its parent
+    fn close_nested_list(&mut self) {
+        let Some(frame) = self.list.pop() else { return };
+        let Some(item) = self.list.last_mut().and_then(|parent| parent.items.last_mut()) else { return };
+        match &mut item.sublist {
+            Some(sublist) => sublist.items.extend(frame.items),
+            None => item.sublist = Some(NestedList { ordered: frame.ordered, items: frame.items }),
+        }
+    }
+
+    fn flush_list(&mut self) {
+        while self.list.len() > 1 {
+            self.close_nested_list();
+        }
+        if let Some(root) = self.list.pop() {
+            let id = self.list_anchor.take().unwrap_or_else(|| self.next_id("list"));
+            self.blocks.push(ContentBlock::List { ordered: root.ordered, items: root.items, id: Some(id) });
+        }
+    }
+
+    fn finish_section(&mut self) {
+        self.flush_list();
+        if let Some((caption, anchor)) = self.pending_caption.take() {
+            let id = anchor.unwrap_or_else(|| self.next_id("para"));
+            self.blocks.push(ContentBlock::Paragraph { text: caption.into(), id: Some(id), xbrl: None });
+        }
+        let mut content = std::mem::take(&mut self.blocks);
+        content.append(&mut self.pending_footnotes);
+        match self.section.take() {
+            Some(mut section) => {
+                section.content = content;
+                self.sections.push(section);
+            }
+            // Content before the first section title
+            None if !content.is_empty() => self.sections.push(Section {
+                id: "content".to_string(),
+                title: Some("Content".to_string()),
+                content,
+                encrypted: None,
+            }),
+            None => {}
+        }
+    }
+
+    fn finish(&mut self) -> Vec<Section> {
+        self.finish_section();
+        std::mem::take(&mut self.sections)
     }
-    
-    // Clean up multiple spaces
-    text.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 fn get_default_styles() -> String {
     include_str!("styles/default.css").to_string()
 }
-
@@ -29,6 +29,12 @@ pub enum ConvertError {
     #[error("Markdown parsing error: {0}")]
     Markdown(String),
     
+    #[error("XML parsing error: {0}")]
+    Xml(String),
+    
+    #[error("XBRL error: {0}")]
+    Xbrl(String),
+    
     #[error("Conversion error: {0}")]
     Conversion(String),
 }
@@ -1,10 +1,32 @@
+//! XLSX import
+//!
+//! Each sheet becomes a section holding one table. Merged ranges and row
+//! outline levels, which calamine does not expose, are read from the
+//! worksheet XML: leading rows with horizontally merged cells become
+//! grouping header rows, a header merged down to the column header row
+//! becomes that column's header, and merges among the data rows become
+//! cell spans. Rows whose first text starts with "Subtotal" or "Total" get
+//! that row role.
+//!
+//! Formulas over the table's own data cells that `tdf_core::formula` can
+//! evaluate become formula cells, with their references moved to table
+//! positions and the cached result as value; any other formula keeps only
+//! its cached result.
+
 use std::path::Path;
 use tdf_core::archive::ArchiveBuilder;
-use tdf_core::content::{ContentBlock, CellType, DocumentContent, Section, TableColumn, TableRow};
+use tdf_core::content::{
+    CellValue, ContentBlock, CellType, Decimal, DocumentContent, HeaderCell, RowRole, Section, TableColumn, TableRow,
+    MAX_ROW_LEVEL,
+};
 use tdf_core::document::Document;
+use tdf_core::formula::{self, CellRef};
 use crate::error::ConvertError;
 use calamine::{open_workbook, Reader, Xlsx};
+use quick_xml::events::Event;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
 
 pub fn convert_excel_to_tdf(
     input: &Path,
@@ -16,6 +38,7 @@ pub fn convert_excel_to_tdf(
     let mut workbook: Xlsx<_> = open_workbook(input)
         .map_err(|e| ConvertError::Excel(format!("Failed to open Excel file: {}", e)))?;
     
+    let structures = sheet_structures(input)?;
     let mut sections = Vec::new();
     
     // Process each sheet
@@ -35,54 +58,20 @@ pub fn convert_excel_to_tdf(
                 continue;
             }
             
-            // First row as headers
-            let headers = rows[0].clone();
-            let data_rows = rows[1..].to_vec();
-            
-            // Build columns
-            let columns: Vec<TableColumn> = headers
-                .iter()
-                .enumerate()
-                .map(|(idx, header)| {
-                    let cell_type = detect_column_type(header);
-                    TableColumn {
-                        id: format!("col_{}", idx),
-                        header: header.clone(),
-                        cell_type,
-                        currency: None,
-                    }
-                })
-                .collect();
-            
-            // Build rows
-            let table_rows: Vec<TableRow> = data_rows
-                .iter()
-                .map(|row_data| {
-                    let mut cells = HashMap::new();
-                    for (idx, cell_value) in row_data.iter().enumerate() {
-                        if idx < columns.len() {
-                            let col_id = format!("col_{}", idx);
-                            let cell_value = parse_cell_value(cell_value, &columns[idx].cell_type);
-                            cells.insert(col_id, cell_value);
-                        }
-                    }
-                    TableRow { cells }
-                })
-                .collect();
-            
-            // Create table
-            let table = ContentBlock::Table {
-                id: format!("sheet_{}", sections.len()),
-                caption: Some(format!("Sheet: {}", sheet_name)),
-                columns,
-                rows: table_rows,
-                footer: None,
-            };
+            let origin = range.start().unwrap_or((0, 0));
+            let structure = structures.get(&sheet_name).map(|s| s.relative_to(origin));
+            let formulas = workbook
+                .worksheet_formula(&sheet_name)
+                .map(|range| SheetFormulas::new(&range, origin))
+                .unwrap_or_default();
+            let (id, caption) = (format!("sheet_{}", sections.len()), Some(format!("Sheet: {}", sheet_name)));
+            let table = build_table(id, caption, rows, &structure.unwrap_or_default(), &formulas);
             
             sections.push(Section {
                 id: format!("sheet_{}", sections.len()),
                 title: Some(sheet_name.clone()),
                 content: vec![table],
+                encrypted: None,
             });
         }
     }
@@ -115,6 +104,273 @@ pub fn convert_excel_to_tdf(
     Ok(())
 }
 
+/// Merged ranges and row outline levels of a worksheet, zero-based
+#[derive(Debug, Default)]
+struct SheetStructure {
+    /// First and last cell of each merged range, as `(row, column)`
+    merges: Vec<((u32, u32), (u32, u32))>,
+    levels: HashMap<u32, u8>,
+}
+
+impl SheetStructure {
+    /// The structure with positions relative to a range starting at `start`
+    fn relative_to(&self, start: (u32, u32)) -> SheetStructure {
+        let (r0, c0) = start;
+        SheetStructure {
+            merges: self
+                .merges
+                .iter()
+                .filter(|((r, c), _)| *r >= r0 && *c >= c0)
+                .map(|((r1, c1), (r2, c2))| ((r1 - r0, c1 - c0), (r2 - r0, c2 - c0)))
+                .collect(),
+            levels: self.levels.iter().filter(|(r, _)| **r >= r0).map(|(r, level)| (r - r0, *level)).collect(),
+        }
+    }
+
+    /// The merged range whose first cell is at `(row, column)`, as its
+    /// number of columns and rows
+    fn span_at(&self, row: usize, column: usize) -> Option<(usize, usize)> {
+        self.merges
+            .iter()
+            .find(|((r, c), _)| (*r as usize, *c as usize) == (row, column))
+            .map(|((r1, c1), (r2, c2))| ((c2 - c1) as usize + 1, (r2 - r1) as usize + 1))
+    }
+
+    /// First cell of the merged range covering `(row, column)`, if it is
+    /// covered but not that first cell
+    fn covered_by(&self, row: usize, column: usize) -> Option<(usize, usize)> {
+        let (row, column) = (row as u32, column as u32);
+        self.merges
+            .iter()
+            .find(|((r1, c1), (r2, c2))| (*r1..=*r2).contains(&row) && (*c1..=*c2).contains(&column) && (*r1, *c1) != (row, column))
+            .map(|((r, c), _)| (*r as usize, *c as usize))
+    }
+}
+
+/// Formulas of a worksheet, by position relative to the data range
+#[derive(Debug, Default)]
+struct SheetFormulas {
+    /// Sheet position of the data range's first cell
+    origin: (u32, u32),
+    cells: HashMap<(usize, usize), String>,
+}
+
+impl SheetFormulas {
+    fn new(range: &calamine::Range<String>, origin: (u32, u32)) -> Self {
+        let start = range.start().unwrap_or((0, 0));
+        let cells = range
+            .cells()
+            .filter(|(_, _, formula)| !formula.is_empty())
+            .filter_map(|(r, c, formula)| {
+                let row = (start.0 as usize + r).checked_sub(origin.0 as usize)?;
+                let column = (start.1 as usize + c).checked_sub(origin.1 as usize)?;
+                Some(((row, column), formula.clone()))
+            })
+            .collect();
+        SheetFormulas { origin, cells }
+    }
+
+    /// Formula at a grid position, with its references moved to the data
+    /// rows of a table starting at grid row `first_data_row`, if it only
+    /// references cells of that table and can be evaluated
+    fn table_formula(&self, row: usize, column: usize, first_data_row: usize, width: usize, height: usize) -> Option<String> {
+        let expr = formula::parse(self.cells.get(&(row, column))?).ok()?;
+        let top = self.origin.0 as usize + first_data_row;
+        let rebased = expr.rebase(&|at: CellRef| {
+            let row = at.row.checked_sub(top)?;
+            let column = at.column.checked_sub(self.origin.1 as usize)?;
+            (row < height && column < width).then_some(CellRef { row, column })
+        })?;
+        Some(rebased.to_string())
+    }
+}
+
+/// Table of a sheet's rows, the first being the header unless merged
+/// cells make more of them headers
+fn build_table(
+    id: String,
+    caption: Option<String>,
+    mut rows: Vec<Vec<String>>,
+    structure: &SheetStructure,
+    formulas: &SheetFormulas,
+) -> ContentBlock {
+    let width = rows[0].len();
+    // Leading rows with horizontally merged cells group the headers below
+    let groups = (0..rows.len() - 1)
+        .take_while(|&r| structure.merges.iter().any(|((r1, c1), (_, c2))| *r1 as usize == r && c2 > c1))
+        .count();
+
+    // A header merged down from a grouping row belongs to the column header
+    let merged_down: Vec<(usize, usize)> = (0..width)
+        .filter_map(|c| structure.covered_by(groups, c).filter(|&(r, anchor)| r < groups && anchor == c).map(|(r, _)| (r, c)))
+        .collect();
+    for (r, c) in merged_down {
+        if rows[groups][c].is_empty() {
+            rows[groups][c] = std::mem::take(&mut rows[r][c]);
+        }
+    }
+    let header_rows: Vec<Vec<HeaderCell>> = (0..groups)
+        .map(|r| {
+            let mut cells = Vec::new();
+            let mut c = 0;
+            while c < width {
+                let colspan = structure.span_at(r, c).map_or(1, |(colspan, _)| colspan.min(width - c));
+                cells.push(HeaderCell::new(rows[r][c].clone(), colspan));
+                c += colspan;
+            }
+            cells
+        })
+        .collect();
+
+    let columns: Vec<TableColumn> = rows[groups]
+        .iter()
+        .enumerate()
+        .map(|(idx, header)| {
+            let cell_type = detect_column_type(header);
+            TableColumn {
+                id: format!("col_{}", idx),
+                header: header.clone(),
+                cell_type,
+                currency: None,
+            }
+        })
+        .collect();
+
+    let first_data_row = groups + 1;
+    let table_rows: Vec<TableRow> = rows[first_data_row..]
+        .iter()
+        .enumerate()
+        .map(|(i, row_data)| {
+            let r = first_data_row + i;
+            let mut row = TableRow::new(HashMap::new());
+            for (idx, cell_value) in row_data.iter().enumerate().take(columns.len()) {
+                if structure.covered_by(r, idx).is_some() {
+                    continue;
+                }
+                if let Some((colspan, rowspan)) = structure.span_at(r, idx) {
+                    let colspan = colspan.min(columns.len() - idx);
+                    let rowspan = rowspan.min(rows.len() - r);
+                    row = row.with_span(format!("col_{}", idx), colspan, rowspan);
+                }
+                let height = rows.len() - first_data_row;
+                let formula = formulas.table_formula(r, idx, first_data_row, columns.len(), height);
+                let cell_value = match (formula, cell_value.parse::<Decimal>()) {
+                    (Some(formula), Ok(raw)) => CellValue::Formula { formula, raw, display: cell_value.clone() },
+                    _ => parse_cell_value(cell_value, &columns[idx].cell_type),
+                };
+                row.cells.insert(format!("col_{}", idx), cell_value);
+            }
+            let level = structure.levels.get(&(r as u32)).copied().unwrap_or(0);
+            let role = row_data.iter().find(|text| !text.trim().is_empty()).map_or(RowRole::Data, |text| detect_row_role(text));
+            row.with_level(level.min(MAX_ROW_LEVEL)).with_role(role)
+        })
+        .collect();
+
+    ContentBlock::Table {
+        id,
+        caption,
+        columns,
+        header_rows: (!header_rows.is_empty()).then_some(header_rows),
+        rows: table_rows,
+        footer: None,
+    }
+}
+
+/// Merged ranges and outline levels of every sheet of a workbook, by
+/// sheet name
+fn sheet_structures(input: &Path) -> Result<HashMap<String, SheetStructure>, ConvertError> {
+    let mut archive = zip::ZipArchive::new(File::open(input)?)
+        .map_err(|e| ConvertError::Excel(format!("Failed to open Excel file: {}", e)))?;
+    let mut part = |name: &str| -> Result<Option<String>, ConvertError> {
+        let Ok(mut file) = archive.by_name(name) else { return Ok(None) };
+        let mut xml = String::new();
+        file.read_to_string(&mut xml)
+            .map_err(|e| ConvertError::Excel(format!("Failed to read {}: {}", name, e)))?;
+        Ok(Some(xml))
+    };
+
+    let (Some(workbook), Some(relationships)) = (part("xl/workbook.xml")?, part("xl/_rels/workbook.xml.rels")?) else {
+        return Ok(HashMap::new());
+    };
+    let targets: HashMap<String, String> = elements(&relationships, b"Relationship")?
+        .into_iter()
+        .filter_map(|mut attributes| Some((attributes.remove("Id")?, attributes.remove("Target")?)))
+        .collect();
+
+    let mut structures = HashMap::new();
+    for mut sheet in elements(&workbook, b"sheet")? {
+        let (Some(name), Some(target)) = (sheet.remove("name"), sheet.remove("r:id").and_then(|id| targets.get(&id))) else {
+            continue;
+        };
+        let path = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target),
+        };
+        let Some(xml) = part(&path)? else { continue };
+        let merges = elements(&xml, b"mergeCell")?
+            .iter()
+            .filter_map(|attributes| {
+                let (first, last) = attributes.get("ref")?.split_once(':')?;
+                Some((cell_position(first)?, cell_position(last)?))
+            })
+            .collect();
+        let levels = elements(&xml, b"row")?
+            .iter()
+            .filter_map(|attributes| {
+                let row: u32 = attributes.get("r")?.parse().ok()?;
+                let level: u8 = attributes.get("outlineLevel")?.parse().ok()?;
+                Some((row.checked_sub(1)?, level))
+            })
+            .filter(|(_, level)| *level > 0)
+            .collect();
+        structures.insert(name, SheetStructure { merges, levels });
+    }
+    Ok(structures)
+}
+
+/// Attributes of every element named `name` in an XML part
+fn elements(xml: &str, name: &[u8]) -> Result<Vec<HashMap<String, String>>, ConvertError> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut found = Vec::new();
+    loop {
+        match reader.read_event().map_err(|e| ConvertError::Excel(format!("Invalid worksheet XML: {}", e)))? {
+            Event::Start(element) | Event::Empty(element) if element.name().as_ref() == name => {
+                let mut attributes = HashMap::new();
+                for attribute in element.attributes().flatten() {
+                    let value = attribute.decode_and_unescape_value(&reader).map(|v| v.into_owned()).unwrap_or_default();
+                    attributes.insert(String::from_utf8_lossy(attribute.key.as_ref()).into_owned(), value);
+                }
+                found.push(attributes);
+            }
+            Event::Eof => return Ok(found),
+            _ => {}
+        }
+    }
+}
+
+/// Zero-based `(row, column)` of an A1-style cell reference
+fn cell_position(reference: &str) -> Option<(u32, u32)> {
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let column = letters.bytes().fold(0u32, |n, b| n * 26 + u32::from(b - b'A') + 1) - 1;
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, column))
+}
+
+fn detect_row_role(text: &str) -> RowRole {
+    let text = text.trim_start().to_lowercase();
+    if text.starts_with("subtotal") || text.starts_with("sub-total") {
+        RowRole::Subtotal
+    } else if text.starts_with("total") {
+        RowRole::Total
+    } else {
+        RowRole::Data
+    }
+}
+
 fn detect_column_type(header: &str) -> CellType {
     let header_lower = header.to_lowercase();
     if header_lower.contains("amount") || header_lower.contains("price") || header_lower.contains("cost") {
@@ -130,10 +386,10 @@ fn detect_column_type(header: &str) -> CellType {
     }
 }
 
-fn parse_cell_value(value: &str, cell_type: &CellType) -> tdf_core::content::CellValue {
+fn parse_cell_value(value: &str, cell_type: &CellType) -> CellValue {
     match cell_type {
         CellType::Number => {
-            if let Ok(num) = value.parse::<f64>() {
+            if let Ok(num) = value.parse::<Decimal>() {
                 tdf_core::content::CellValue::Number {
                     raw: num,
                     display: value.to_string(),
@@ -143,8 +399,8 @@ fn parse_cell_value(value: &str, cell_type: &CellType) -> tdf_core::content::Cel
             }
         }
         CellType::Currency => {
-            let cleaned = value.replace("$", "").replace("€", "").replace(",", "").trim().to_string();
-            if let Ok(num) = cleaned.parse::<f64>() {
+            let cleaned = value.replace(['$', '€', '£', ','], "").trim().to_string();
+            if let Ok(num) = cleaned.parse::<Decimal>() {
                 tdf_core::content::CellValue::Currency {
                     raw: num,
                     display: value.to_string(),
@@ -156,9 +412,9 @@ fn parse_cell_value(value: &str, cell_type: &CellType) -> tdf_core::content::Cel
         }
         CellType::Percentage => {
             let cleaned = value.replace("%", "").trim().to_string();
-            if let Ok(num) = cleaned.parse::<f64>() {
+            if let Some(num) = cleaned.parse::<Decimal>().ok().and_then(|n| n.shift(-2)) {
                 tdf_core::content::CellValue::Percentage {
-                    raw: num / 100.0,
+                    raw: num,
                     display: value.to_string(),
                 }
             } else {
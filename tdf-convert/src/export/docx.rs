@@ -0,0 +1,996 @@
+//! DOCX export
+//!
+//! WordprocessingML written directly into the package. The document title
+//! uses the `Title` style and section titles the `TdfSection` style (based
+//! on Heading 1), which is how the importer finds section boundaries.
+//! Headings use `Heading1`-`Heading6`, lists get their own Word numbering,
+//! with nested lists one level down and task items led by a ballot box,
+//! quotes use the `Quote` style (their attribution `QuoteAttribution`),
+//! code the `CodeBlock` style, page breaks are `w:br w:type="page"` and
+//! rules an empty paragraph with a bottom border. Tables repeat their
+//! header rows and mark a footer as the total row (`w:lastRow`); merged
+//! cells use `w:gridSpan` and `w:vMerge`, subtotal and total rows the
+//! `TableSubtotal` and `TableTotal` paragraph styles and outlined rows an
+//! indented first cell. Table captions are a `Caption` paragraph before
+//! the table and figure captions one after the picture. Referenced footnotes become
+//! Word footnotes; unreferenced ones stay in place as `FootnoteText`
+//! paragraphs. Diagrams are listed as their edges and charts as their
+//! values by category. Form fields are a bold label with a hint of what
+//! they accept above an empty paragraph with a bottom border to write on;
+//! checkboxes are led by a ballot box instead.
+//!
+//! Inline marks are direct run formatting, except code, which uses the
+//! `InlineCode` character style. Links become hyperlinks to external
+//! targets and cross-references hyperlinks to a bookmark on the block or
+//! section they name.
+
+use super::{block_texts, footnote_ids, inline_parts, inline_runs, xml_text, Inline};
+use crate::error::ConvertError;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+use tdf_core::content::{
+    covered_cells, header_layout, CellType, ContentBlock, DiagramEdge, DiagramNode, HeaderCell, InlineRun, InlineText, ListItem, Mark,
+    RowRole, Section, TableColumn, TableRow, MAX_LIST_DEPTH,
+};
+use tdf_core::chart::{self, ChartData};
+use tdf_core::document::Document;
+use tdf_core::form::{FieldKind, FormField};
+use tdf_core::html::is_safe_link;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Paragraph style of section titles
+pub const SECTION_STYLE: &str = "TdfSection";
+
+/// Character style of code spans
+pub const CODE_STYLE: &str = "InlineCode";
+
+/// Paragraph style of code blocks
+pub const CODE_BLOCK_STYLE: &str = "CodeBlock";
+
+/// Paragraph styles of block quotes and their attribution
+pub const QUOTE_STYLE: &str = "Quote";
+pub const QUOTE_ATTRIBUTION_STYLE: &str = "QuoteAttribution";
+
+/// Paragraph styles of the cells of subtotal and total table rows
+pub const SUBTOTAL_STYLE: &str = "TableSubtotal";
+pub const TOTAL_STYLE: &str = "TableTotal";
+
+/// Indent of a table row's first cell per outline level, in twentieths of
+/// a point
+pub const ROW_LEVEL_INDENT_TWIPS: usize = 284;
+
+/// Glyphs leading unchecked and checked task list items
+pub const UNCHECKED_BOX: char = '\u{2610}';
+pub const CHECKED_BOX: char = '\u{2612}';
+
+/// Empty paragraph with a bottom border, written on to fill in a field
+const FIELD_LINE: &str = "<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"4\" w:space=\"1\" w:color=\"auto\"/></w:pBdr></w:pPr></w:p>";
+
+const W_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const R_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const WP_NS: &str = "http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing";
+const A_NS: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+const PIC_NS: &str = "http://schemas.openxmlformats.org/drawingml/2006/picture";
+
+/// Text width of an A4 page with 1 inch margins, in twentieths of a point
+const TEXT_WIDTH_TWIPS: usize = 9026;
+/// Widest picture, in EMU (6 inches)
+const MAX_PICTURE_EMU: u64 = 6 * 914_400;
+const EMU_PER_PIXEL: u64 = 9525;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:eastAsia="Calibri" w:cs="Calibri"/><w:sz w:val="22"/><w:szCs w:val="22"/><w:lang w:val="en-US"/></w:rPr></w:rPrDefault><w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="264" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults>
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>
+<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="240"/></w:pPr><w:rPr><w:b/><w:sz w:val="48"/><w:szCs w:val="48"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="32"/><w:szCs w:val="32"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="120"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="28"/><w:szCs w:val="28"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="80"/><w:outlineLvl w:val="2"/></w:pPr><w:rPr><w:b/><w:sz w:val="26"/><w:szCs w:val="26"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading4"><w:name w:val="heading 4"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="200" w:after="80"/><w:outlineLvl w:val="3"/></w:pPr><w:rPr><w:b/><w:sz w:val="24"/><w:szCs w:val="24"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading5"><w:name w:val="heading 5"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="200" w:after="80"/><w:outlineLvl w:val="4"/></w:pPr><w:rPr><w:b/><w:i/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading6"><w:name w:val="heading 6"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="200" w:after="80"/><w:outlineLvl w:val="5"/></w:pPr><w:rPr><w:i/></w:rPr></w:style>
+<w:style w:type="paragraph" w:customStyle="1" w:styleId="TdfSection"><w:name w:val="Section Title"/><w:basedOn w:val="Heading1"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:pBdr><w:bottom w:val="single" w:sz="4" w:space="1" w:color="999999"/></w:pBdr></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="ListParagraph"><w:name w:val="List Paragraph"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="60"/><w:ind w:left="720"/></w:pPr></w:style>
+<w:style w:type="paragraph" w:styleId="Quote"><w:name w:val="Quote"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:before="120"/><w:ind w:left="864" w:right="864"/></w:pPr><w:rPr><w:i/><w:color w:val="404040"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:customStyle="1" w:styleId="QuoteAttribution"><w:name w:val="Quote Attribution"/><w:basedOn w:val="Quote"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:jc w:val="right"/></w:pPr><w:rPr><w:i w:val="0"/><w:sz w:val="20"/><w:szCs w:val="20"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:customStyle="1" w:styleId="CodeBlock"><w:name w:val="Code Block"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:shd w:val="clear" w:color="auto" w:fill="F6F6F6"/><w:spacing w:line="240" w:lineRule="auto"/></w:pPr><w:rPr><w:rFonts w:ascii="Courier New" w:hAnsi="Courier New" w:cs="Courier New"/><w:sz w:val="20"/><w:szCs w:val="20"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:customStyle="1" w:styleId="TableSubtotal"><w:name w:val="Table Subtotal"/><w:basedOn w:val="Normal"/><w:qFormat/><w:rPr><w:b/></w:rPr></w:style>
+<w:style w:type="paragraph" w:customStyle="1" w:styleId="TableTotal"><w:name w:val="Table Total"/><w:basedOn w:val="TableSubtotal"/><w:qFormat/></w:style>
+<w:style w:type="paragraph" w:styleId="Caption"><w:name w:val="caption"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:rPr><w:i/><w:color w:val="555555"/><w:sz w:val="18"/><w:szCs w:val="18"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="FootnoteText"><w:name w:val="footnote text"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="0"/></w:pPr><w:rPr><w:sz w:val="18"/><w:szCs w:val="18"/></w:rPr></w:style>
+<w:style w:type="character" w:styleId="FootnoteReference"><w:name w:val="footnote reference"/><w:rPr><w:vertAlign w:val="superscript"/></w:rPr></w:style>
+<w:style w:type="character" w:styleId="Hyperlink"><w:name w:val="Hyperlink"/><w:rPr><w:color w:val="0563C1"/><w:u w:val="single"/></w:rPr></w:style>
+<w:style w:type="character" w:customStyle="1" w:styleId="InlineCode"><w:name w:val="Inline Code"/><w:rPr><w:rFonts w:ascii="Courier New" w:hAnsi="Courier New" w:cs="Courier New"/><w:sz w:val="20"/><w:szCs w:val="20"/></w:rPr></w:style>
+<w:style w:type="table" w:default="1" w:styleId="TableNormal"><w:name w:val="Normal Table"/><w:tblPr><w:tblInd w:w="0" w:type="dxa"/><w:tblCellMar><w:top w:w="0" w:type="dxa"/><w:left w:w="108" w:type="dxa"/><w:bottom w:w="0" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>
+<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:basedOn w:val="TableNormal"/><w:pPr><w:spacing w:after="0"/></w:pPr><w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4" w:space="0" w:color="BBBBBB"/><w:left w:val="single" w:sz="4" w:space="0" w:color="BBBBBB"/><w:bottom w:val="single" w:sz="4" w:space="0" w:color="BBBBBB"/><w:right w:val="single" w:sz="4" w:space="0" w:color="BBBBBB"/><w:insideH w:val="single" w:sz="4" w:space="0" w:color="BBBBBB"/><w:insideV w:val="single" w:sz="4" w:space="0" w:color="BBBBBB"/></w:tblBorders></w:tblPr></w:style>
+</w:styles>
+"#;
+
+/// Pixel size of a PNG, GIF or JPEG image
+pub(crate) fn image_size(data: &[u8]) -> Option<(u32, u32)> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") && data.len() >= 24 {
+        let read = |at: usize| u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+        return Some((read(16), read(20)));
+    }
+    if data.starts_with(b"GIF8") && data.len() >= 10 {
+        return Some((u16::from_le_bytes([data[6], data[7]]) as u32, u16::from_le_bytes([data[8], data[9]]) as u32));
+    }
+    if data.starts_with(&[0xFF, 0xD8]) {
+        let mut at = 2;
+        while at + 9 < data.len() {
+            if data[at] != 0xFF {
+                return None;
+            }
+            let marker = data[at + 1];
+            let len = u16::from_be_bytes([data[at + 2], data[at + 3]]) as usize;
+            // Start-of-frame markers, except DHT, JPG and DAC
+            if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                let height = u16::from_be_bytes([data[at + 5], data[at + 6]]) as u32;
+                let width = u16::from_be_bytes([data[at + 7], data[at + 8]]) as u32;
+                return Some((width, height));
+            }
+            at += 2 + len;
+        }
+    }
+    None
+}
+
+/// Media type of an image Word can show, from its file extension
+fn image_content_type(name: &str) -> Option<(&'static str, &'static str)> {
+    let extension = name.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some(("png", "image/png")),
+        "jpg" => Some(("jpg", "image/jpeg")),
+        "jpeg" => Some(("jpeg", "image/jpeg")),
+        "gif" => Some(("gif", "image/gif")),
+        _ => None,
+    }
+}
+
+fn is_numeric(column: &TableColumn) -> bool {
+    matches!(column.cell_type, CellType::Number | CellType::Currency | CellType::Percentage | CellType::Formula)
+}
+
+/// Cell of a Word table row: `text` in grid column `column`, merged across
+/// `colspan` columns and `rowspan` rows
+struct GridCell<'a> {
+    column: usize,
+    colspan: usize,
+    rowspan: usize,
+    text: &'a str,
+}
+
+/// How the cells of a Word table row are drawn
+#[derive(Default)]
+struct RowLook<'a> {
+    header: bool,
+    bold: bool,
+    style: Option<&'a str>,
+    /// Outline level, indenting the first cell
+    level: u8,
+    /// Rule above the row
+    rule: bool,
+}
+
+/// An image packaged in `word/media`
+struct Media {
+    relationship: String,
+    file_name: String,
+    data: Vec<u8>,
+}
+
+struct DocxWriter<'d> {
+    body: String,
+    assets: &'d HashMap<String, Vec<u8>>,
+    /// Footnote blocks by id
+    footnote_text: HashMap<String, String>,
+    /// Footnotes referenced from text, which become Word footnotes
+    referenced: HashSet<String>,
+    /// Word footnote ids in the order footnotes were first referenced
+    footnote_order: Vec<String>,
+    /// Numbering instance of each list and nested list: whether it is
+    /// ordered and its level
+    lists: Vec<(bool, usize)>,
+    media: Vec<Media>,
+    media_by_asset: HashMap<String, usize>,
+    drawings: usize,
+    /// Targets of external hyperlinks, in relationship order
+    links: Vec<String>,
+    /// Block and section ids cross-references point to that have no
+    /// bookmark yet
+    targets: HashSet<String>,
+    bookmarks: usize,
+    /// Data of every chart, by chart id
+    charts: HashMap<String, Result<ChartData, String>>,
+}
+
+impl DocxWriter<'_> {
+    /// Runs for inline text; footnote markers become footnote references
+    fn runs(&mut self, text: &str, bold: bool) -> String {
+        let run_properties = if bold { "<w:rPr><w:b/></w:rPr>" } else { "" };
+        let mut out = String::new();
+        let referenced = self.referenced.clone();
+        for part in inline_parts(text, &referenced) {
+            match part {
+                Inline::Footnote(id) => out.push_str(&self.footnote_reference(id)),
+                Inline::Text(text) => out.push_str(&text_run(text, run_properties)),
+            }
+        }
+        out
+    }
+
+    /// Runs for rich inline text: marks as run properties, links and
+    /// cross-references as hyperlinks and footnote references as Word
+    /// footnote references
+    fn inline(&mut self, text: &InlineText) -> String {
+        let runs = inline_runs(text, &self.referenced);
+        let mut out = String::new();
+        let mut i = 0;
+        while i < runs.len() {
+            match &runs[i] {
+                InlineRun::FootnoteRef { id } if self.referenced.contains(id) => out.push_str(&self.footnote_reference(id)),
+                InlineRun::FootnoteRef { id } => out.push_str(&text_run(id, &run_properties(&[Mark::Superscript], None))),
+                InlineRun::CrossRef { target, text } => {
+                    let _ = write!(
+                        out,
+                        "<w:hyperlink w:anchor=\"{}\" w:history=\"1\">{}</w:hyperlink>",
+                        xml_text(target),
+                        text_run(text, &run_properties(&[], Some("Hyperlink")))
+                    );
+                }
+                InlineRun::Text { text, marks, link } => match link.as_deref().filter(|href| is_safe_link(href)) {
+                    Some(href) => {
+                        // Runs sharing a link are one hyperlink
+                        let end = runs[i..]
+                            .iter()
+                            .position(|run| !matches!(run, InlineRun::Text { link: Some(other), .. } if other == href))
+                            .map_or(runs.len(), |n| i + n);
+                        match href.strip_prefix('#') {
+                            Some(anchor) => {
+                                let _ = write!(out, "<w:hyperlink w:anchor=\"{}\" w:history=\"1\">", xml_text(anchor));
+                            }
+                            None => {
+                                let relationship = self.hyperlink(href);
+                                let _ = write!(out, "<w:hyperlink r:id=\"{}\" w:history=\"1\">", relationship);
+                            }
+                        }
+                        for run in &runs[i..end] {
+                            if let InlineRun::Text { text, marks, .. } = run {
+                                out.push_str(&text_run(text, &run_properties(marks, Some("Hyperlink"))));
+                            }
+                        }
+                        out.push_str("</w:hyperlink>");
+                        i = end;
+                        continue;
+                    }
+                    None => out.push_str(&text_run(text, &run_properties(marks, None))),
+                },
+            }
+            i += 1;
+        }
+        out
+    }
+
+    /// A reference to a footnote, numbered in order of first reference
+    fn footnote_reference(&mut self, id: &str) -> String {
+        let number = match self.footnote_order.iter().position(|n| n == id) {
+            Some(index) => index + 1,
+            None => {
+                self.footnote_order.push(id.to_string());
+                self.footnote_order.len()
+            }
+        };
+        format!(
+            "<w:r><w:rPr><w:rStyle w:val=\"FootnoteReference\"/></w:rPr><w:footnoteReference w:id=\"{}\"/></w:r>",
+            number
+        )
+    }
+
+    /// Relationship id of an external hyperlink target
+    fn hyperlink(&mut self, href: &str) -> String {
+        let index = match self.links.iter().position(|l| l == href) {
+            Some(index) => index,
+            None => {
+                self.links.push(href.to_string());
+                self.links.len() - 1
+            }
+        };
+        format!("rIdLink{}", index + 1)
+    }
+
+    /// Bookmark for a block or section a cross-reference points to
+    fn bookmark(&mut self, id: Option<&str>) -> String {
+        match id {
+            Some(id) if self.targets.remove(id) => {
+                self.bookmarks += 1;
+                format!(
+                    "<w:bookmarkStart w:id=\"{0}\" w:name=\"{1}\"/><w:bookmarkEnd w:id=\"{0}\"/>",
+                    self.bookmarks,
+                    xml_text(id)
+                )
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn paragraph(&mut self, style: Option<&str>, text: &str) {
+        self.anchored_paragraph(style, None, text);
+    }
+
+    /// A paragraph that cross-references to `id` point to
+    fn anchored_paragraph(&mut self, style: Option<&str>, id: Option<&str>, text: &str) {
+        let bookmark = self.bookmark(id);
+        let runs = self.runs(text, false);
+        let _ = write!(self.body, "<w:p>{}{}{}</w:p>", paragraph_properties(style, ""), bookmark, runs);
+    }
+
+    fn rich_paragraph(&mut self, style: Option<&str>, properties: &str, id: Option<&str>, text: &InlineText) {
+        let bookmark = self.bookmark(id);
+        let runs = self.inline(text);
+        let _ = write!(self.body, "<w:p>{}{}{}</w:p>", paragraph_properties(style, properties), bookmark, runs);
+    }
+
+    fn block(&mut self, block: &ContentBlock) {
+        match block {
+            ContentBlock::Heading { level, text, id } => {
+                let style = format!("Heading{}", (*level).clamp(1, 6));
+                self.rich_paragraph(Some(&style), "", id.as_deref(), text);
+            }
+            ContentBlock::Paragraph { text, id, .. } => self.rich_paragraph(None, "", id.as_deref(), text),
+            ContentBlock::List { ordered, items, id } => self.list(*ordered, items, 0, id.as_deref()),
+            ContentBlock::Quote { text, attribution, id } => {
+                self.rich_paragraph(Some(QUOTE_STYLE), "", id.as_deref(), text);
+                if let Some(attribution) = attribution {
+                    self.paragraph(Some(QUOTE_ATTRIBUTION_STYLE), &format!("\u{2014} {}", attribution));
+                }
+            }
+            ContentBlock::Code { text, id, .. } => {
+                let bookmark = self.bookmark(id.as_deref());
+                let _ = write!(self.body, "<w:p>{}{}{}</w:p>", paragraph_properties(Some(CODE_BLOCK_STYLE), ""), bookmark, text_run(text, ""));
+            }
+            ContentBlock::PageBreak => self.body.push_str("<w:p><w:r><w:br w:type=\"page\"/></w:r></w:p>"),
+            ContentBlock::HorizontalRule => {
+                self.body.push_str("<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"auto\"/></w:pBdr></w:pPr></w:p>");
+            }
+            ContentBlock::Table { id, caption, columns, header_rows, rows, footer } => {
+                if let Some(caption) = caption {
+                    self.anchored_paragraph(Some("Caption"), Some(id), caption);
+                }
+                self.table(columns, header_rows.as_deref().unwrap_or_default(), rows, footer.as_deref());
+            }
+            ContentBlock::Figure { id, asset, alt, caption, width } => {
+                self.figure(asset, alt, *width);
+                if let Some(caption) = caption {
+                    self.anchored_paragraph(Some("Caption"), Some(id), caption);
+                }
+            }
+            ContentBlock::Footnote { id, text } => {
+                if !self.referenced.contains(id) {
+                    self.paragraph(Some("FootnoteText"), text);
+                }
+            }
+            ContentBlock::Diagram { title, nodes, edges, .. } => self.diagram(title.as_deref(), nodes, edges),
+            ContentBlock::Chart { id, title, .. } => self.chart(id, title.as_deref()),
+            ContentBlock::Redacted { .. } => {
+                let _ = write!(
+                    self.body,
+                    "<w:p><w:pPr><w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"E0E0E0\"/></w:pPr>{}</w:p>",
+                    text_run("[Redacted]", "<w:rPr><w:i/></w:rPr>")
+                );
+            }
+            ContentBlock::TextField { .. }
+            | ContentBlock::NumberField { .. }
+            | ContentBlock::DateField { .. }
+            | ContentBlock::Checkbox { .. }
+            | ContentBlock::Choice { .. }
+            | ContentBlock::SignatureField { .. } => {
+                if let Some(field) = FormField::from_block(block) {
+                    self.field(&field);
+                }
+            }
+        }
+    }
+
+    /// Bold label with a hint of what the field accepts, then an empty
+    /// line to write on
+    fn field(&mut self, field: &FormField) {
+        let bookmark = self.bookmark(Some(field.id));
+        let mut label = field.label.to_string();
+        if field.required {
+            label.push_str(" *");
+        }
+        if let FieldKind::Checkbox = field.kind {
+            label = format!("{} {}", UNCHECKED_BOX, label);
+        }
+        let _ = write!(
+            self.body,
+            "<w:p>{}{}{}</w:p>",
+            bookmark,
+            text_run(&label, "<w:rPr><w:b/></w:rPr>"),
+            text_run(&format!(" ({})", field.describe()), "<w:rPr><w:i/></w:rPr>")
+        );
+        if let Some(help) = field.help {
+            self.paragraph(None, help);
+        }
+        let lines = match field.kind {
+            FieldKind::Checkbox => 0,
+            FieldKind::Text { multiline: true, .. } => 3,
+            _ => 1,
+        };
+        for _ in 0..lines {
+            self.body.push_str(FIELD_LINE);
+        }
+    }
+
+    /// Paragraphs of a list at `level`, each sublist after its item with a
+    /// numbering instance of its own
+    fn list(&mut self, ordered: bool, items: &[ListItem], level: usize, id: Option<&str>) {
+        self.lists.push((ordered, level));
+        let properties = format!("<w:numPr><w:ilvl w:val=\"{}\"/><w:numId w:val=\"{}\"/></w:numPr>", level, self.lists.len());
+        let indent = format!("<w:ind w:left=\"{}\" w:hanging=\"360\"/>", 720 * (level + 1));
+        for (i, item) in items.iter().enumerate() {
+            let id = if i == 0 { id } else { None };
+            let text = match item.checked {
+                Some(checked) => {
+                    let checkbox = if checked { CHECKED_BOX } else { UNCHECKED_BOX };
+                    let mut runs = item.text.runs();
+                    runs.insert(0, InlineRun::text(format!("{} ", checkbox)));
+                    InlineText::from_runs(runs)
+                }
+                None => item.text.clone(),
+            };
+            self.rich_paragraph(Some("ListParagraph"), &format!("{}{}", properties, indent), id, &text);
+            if let Some(sublist) = &item.sublist {
+                self.list(sublist.ordered, &sublist.items, level + 1, None);
+            }
+        }
+    }
+
+    fn table(&mut self, columns: &[TableColumn], header_rows: &[Vec<HeaderCell>], rows: &[TableRow], footer: Option<&[String]>) {
+        if columns.is_empty() {
+            return;
+        }
+        let width = TEXT_WIDTH_TWIPS / columns.len();
+        let _ = write!(
+            self.body,
+            "<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/><w:tblW w:w=\"5000\" w:type=\"pct\"/>\
+             <w:tblLook w:firstRow=\"1\" w:lastRow=\"{}\" w:firstColumn=\"0\" w:lastColumn=\"0\" w:noHBand=\"1\" w:noVBand=\"1\"/>\
+             </w:tblPr><w:tblGrid>",
+            u8::from(footer.is_some())
+        );
+        for _ in columns {
+            let _ = write!(self.body, "<w:gridCol w:w=\"{}\"/>", width);
+        }
+        self.body.push_str("</w:tblGrid>");
+
+        // Rows still to be covered by a vertical merge, and its width, by
+        // the merge's first column
+        let mut merges = vec![None; columns.len()];
+        for slots in header_layout(columns, header_rows) {
+            let cells: Vec<GridCell> = slots
+                .iter()
+                .map(|slot| GridCell { column: slot.column, colspan: slot.colspan, rowspan: slot.rowspan, text: slot.text })
+                .collect();
+            self.row(columns, &cells, width, &RowLook { header: true, bold: true, ..RowLook::default() }, &mut merges);
+        }
+        let covered = covered_cells(columns, rows);
+        for (r, row) in rows.iter().enumerate() {
+            let cells: Vec<GridCell> = columns
+                .iter()
+                .enumerate()
+                .filter(|(c, _)| !covered.contains_key(&(r, *c)))
+                .map(|(c, column)| {
+                    let span = row.span(&column.id);
+                    let text = row.cells.get(&column.id).map(|v| v.display_text()).unwrap_or("");
+                    GridCell { column: c, colspan: span.colspan, rowspan: span.rowspan, text }
+                })
+                .collect();
+            let look = RowLook {
+                style: match row.format.role {
+                    RowRole::Data => None,
+                    RowRole::Subtotal => Some(SUBTOTAL_STYLE),
+                    RowRole::Total => Some(TOTAL_STYLE),
+                },
+                level: row.format.level,
+                rule: row.format.role == RowRole::Total,
+                ..RowLook::default()
+            };
+            self.row(columns, &cells, width, &look, &mut merges);
+        }
+        if let Some(footer) = footer {
+            let cells: Vec<GridCell> = (0..columns.len())
+                .map(|i| GridCell { column: i, colspan: 1, rowspan: 1, text: footer.get(i).map(String::as_str).unwrap_or("") })
+                .collect();
+            self.row(columns, &cells, width, &RowLook { bold: true, ..RowLook::default() }, &mut merges);
+        }
+        // Keeps a following table from merging into this one
+        self.body.push_str("</w:tbl><w:p/>");
+    }
+
+    /// A table row; grid columns without a cell are filled with empty
+    /// cells or with the continuation of a vertical merge from above
+    fn row(&mut self, columns: &[TableColumn], cells: &[GridCell], width: usize, look: &RowLook, merges: &mut [Option<(usize, usize)>]) {
+        self.body.push_str(if look.header { "<w:tr><w:trPr><w:tblHeader/></w:trPr>" } else { "<w:tr>" });
+        let rule = if look.rule { "<w:tcBorders><w:top w:val=\"single\" w:sz=\"12\" w:space=\"0\" w:color=\"444444\"/></w:tcBorders>" } else { "" };
+        let mut cells = cells.iter().peekable();
+        let mut column = 0;
+        while column < columns.len() {
+            while cells.next_if(|cell| cell.column < column).is_some() {}
+            let (colspan, merge, text) = match (merges[column], cells.next_if(|cell| cell.column == column)) {
+                (Some((remaining, colspan)), _) => {
+                    merges[column] = (remaining > 1).then_some((remaining - 1, colspan));
+                    (colspan, "<w:vMerge/>", "")
+                }
+                (None, Some(cell)) if cell.rowspan > 1 => {
+                    merges[column] = Some((cell.rowspan - 1, cell.colspan));
+                    (cell.colspan, "<w:vMerge w:val=\"restart\"/>", cell.text)
+                }
+                (None, Some(cell)) => (cell.colspan, "", cell.text),
+                (None, None) => (1, "", ""),
+            };
+            let colspan = colspan.clamp(1, columns.len() - column);
+            let grid_span = if colspan > 1 { format!("<w:gridSpan w:val=\"{}\"/>", colspan) } else { String::new() };
+            let mut properties = String::new();
+            if column == 0 && look.level > 0 {
+                let _ = write!(properties, "<w:ind w:left=\"{}\"/>", ROW_LEVEL_INDENT_TWIPS * usize::from(look.level));
+            }
+            if is_numeric(&columns[column]) && !look.header {
+                properties.push_str("<w:jc w:val=\"right\"/>");
+            }
+            let runs = self.runs(text, look.bold);
+            let _ = write!(
+                self.body,
+                "<w:tc><w:tcPr><w:tcW w:w=\"{}\" w:type=\"dxa\"/>{}{}{}</w:tcPr><w:p>{}{}</w:p></w:tc>",
+                width * colspan,
+                grid_span,
+                merge,
+                rule,
+                paragraph_properties(look.style, &properties),
+                runs
+            );
+            column += colspan;
+        }
+        self.body.push_str("</w:tr>");
+    }
+
+    fn figure(&mut self, asset: &str, alt: &str, width: Option<u32>) {
+        let picture = self.assets.get(asset).and_then(|data| {
+            let name = asset.rsplit('/').next().unwrap_or(asset);
+            image_content_type(name)?;
+            Some((name.to_string(), data))
+        });
+        let Some((name, data)) = picture else {
+            // Word cannot show this image; keep its description
+            let _ = write!(self.body, "<w:p>{}</w:p>", text_run(&format!("[Figure: {}]", alt), "<w:rPr><w:i/></w:rPr>"));
+            return;
+        };
+
+        let (pixels_wide, pixels_high) = image_size(data).unwrap_or((640, 480));
+        let shown_wide = width.unwrap_or(pixels_wide).max(1) as u64;
+        let mut cx = shown_wide * EMU_PER_PIXEL;
+        let mut cy = cx * pixels_high.max(1) as u64 / pixels_wide.max(1) as u64;
+        if cx > MAX_PICTURE_EMU {
+            cy = cy * MAX_PICTURE_EMU / cx;
+            cx = MAX_PICTURE_EMU;
+        }
+
+        let index = match self.media_by_asset.get(asset) {
+            Some(&index) => index,
+            None => {
+                let mut file_name = name.clone();
+                let mut n = 2;
+                while self.media.iter().any(|m| m.file_name == file_name) {
+                    file_name = format!("{}-{}", n, name);
+                    n += 1;
+                }
+                self.media.push(Media { relationship: format!("rIdImage{}", self.media.len() + 1), file_name, data: data.clone() });
+                self.media_by_asset.insert(asset.to_string(), self.media.len() - 1);
+                self.media.len() - 1
+            }
+        };
+        self.drawings += 1;
+        let id = self.drawings;
+        let alt = xml_text(alt);
+        let _ = write!(
+            self.body,
+            "<w:p><w:pPr><w:keepNext/><w:jc w:val=\"center\"/></w:pPr><w:r><w:drawing>\
+             <wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\"><wp:extent cx=\"{cx}\" cy=\"{cy}\"/>\
+             <wp:docPr id=\"{id}\" name=\"Picture {id}\" descr=\"{alt}\"/>\
+             <wp:cNvGraphicFramePr><a:graphicFrameLocks xmlns:a=\"{a}\" noChangeAspect=\"1\"/></wp:cNvGraphicFramePr>\
+             <a:graphic xmlns:a=\"{a}\"><a:graphicData uri=\"{pic}\"><pic:pic xmlns:pic=\"{pic}\">\
+             <pic:nvPicPr><pic:cNvPr id=\"{id}\" name=\"{name}\" descr=\"{alt}\"/><pic:cNvPicPr/></pic:nvPicPr>\
+             <pic:blipFill><a:blip r:embed=\"{rel}\"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>\
+             <pic:spPr><a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm><a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr>\
+             </pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>",
+            a = A_NS,
+            pic = PIC_NS,
+            name = xml_text(&self.media[index].file_name),
+            rel = self.media[index].relationship,
+        );
+    }
+
+    fn diagram(&mut self, title: Option<&str>, nodes: &[DiagramNode], edges: &[DiagramEdge]) {
+        let label = |id: &str| nodes.iter().find(|n| n.id == id).map(|n| n.label.replace('\n', " ")).unwrap_or_else(|| id.to_string());
+        let heading = match title {
+            Some(title) => format!("Diagram: {}", title),
+            None => "Diagram".to_string(),
+        };
+        let _ = write!(self.body, "<w:p>{}{}</w:p>", paragraph_properties(Some("Caption"), "<w:keepNext/>"), text_run(&heading, ""));
+        for edge in edges {
+            let mut text = format!("{} \u{2192} {}", label(&edge.from), label(&edge.to));
+            if let Some(edge_label) = &edge.label {
+                let _ = write!(text, " ({})", edge_label);
+            }
+            let _ = write!(
+                self.body,
+                "<w:p>{}{}</w:p>",
+                paragraph_properties(None, "<w:ind w:left=\"360\"/>"),
+                text_run(&text, "")
+            );
+        }
+    }
+
+    /// A chart's values, one paragraph per category
+    fn chart(&mut self, id: &str, title: Option<&str>) {
+        let heading = match title {
+            Some(title) => format!("Chart: {}", title),
+            None => "Chart".to_string(),
+        };
+        let _ = write!(self.body, "<w:p>{}{}</w:p>", paragraph_properties(Some("Caption"), "<w:keepNext/>"), text_run(&heading, ""));
+        let lines = match self.charts.get(id) {
+            Some(Ok(data)) => data
+                .categories
+                .iter()
+                .enumerate()
+                .map(|(i, category)| {
+                    let values: Vec<String> = data
+                        .series
+                        .iter()
+                        .map(|s| {
+                            let value = s.values[i].as_ref().map_or("\u{2014}", |v| v.display.as_str());
+                            format!("{} {}", s.label, value)
+                        })
+                        .collect();
+                    format!("{}: {}", category, values.join(", "))
+                })
+                .collect(),
+            Some(Err(e)) => vec![format!("[Chart cannot be drawn: {}]", e)],
+            None => Vec::new(),
+        };
+        for line in lines {
+            let _ = write!(
+                self.body,
+                "<w:p>{}{}</w:p>",
+                paragraph_properties(None, "<w:ind w:left=\"360\"/>"),
+                text_run(&line, "")
+            );
+        }
+    }
+
+    fn section(&mut self, section: &Section) {
+        self.anchored_paragraph(Some(SECTION_STYLE), Some(&section.id), section.title.as_deref().unwrap_or(""));
+        if section.encrypted.is_some() {
+            let _ = write!(self.body, "<w:p>{}</w:p>", text_run("[Encrypted section]", "<w:rPr><w:i/></w:rPr>"));
+        }
+        for block in &section.content {
+            self.block(block);
+        }
+    }
+
+    fn numbering(&self) -> String {
+        let levels = |ordered: bool| {
+            (0..MAX_LIST_DEPTH)
+                .map(|level| {
+                    let (format, text) = match (ordered, level % 3) {
+                        (true, _) => ("decimal", format!("%{}.", level + 1)),
+                        (false, 0) => ("bullet", "\u{2022}".to_string()),
+                        (false, 1) => ("bullet", "\u{25E6}".to_string()),
+                        (false, _) => ("bullet", "\u{25AA}".to_string()),
+                    };
+                    format!(
+                        "<w:lvl w:ilvl=\"{}\"><w:start w:val=\"1\"/><w:numFmt w:val=\"{}\"/><w:lvlText w:val=\"{}\"/><w:lvlJc w:val=\"left\"/>\
+                         <w:pPr><w:ind w:left=\"{}\" w:hanging=\"360\"/></w:pPr></w:lvl>",
+                        level,
+                        format,
+                        text,
+                        720 * (level + 1)
+                    )
+                })
+                .collect::<String>()
+        };
+        let mut out = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:numbering xmlns:w=\"{}\">\
+             <w:abstractNum w:abstractNumId=\"0\"><w:multiLevelType w:val=\"multilevel\"/>{}</w:abstractNum>\
+             <w:abstractNum w:abstractNumId=\"1\"><w:multiLevelType w:val=\"multilevel\"/>{}</w:abstractNum>",
+            W_NS,
+            levels(false),
+            levels(true)
+        );
+        // One instance per list, so numbering restarts and lists stay apart
+        for (i, (ordered, level)) in self.lists.iter().enumerate() {
+            let _ = write!(
+                out,
+                "<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"{}\"/><w:lvlOverride w:ilvl=\"{}\"><w:startOverride w:val=\"1\"/></w:lvlOverride></w:num>",
+                i + 1,
+                u8::from(*ordered),
+                level
+            );
+        }
+        out.push_str("</w:numbering>\n");
+        out
+    }
+
+    fn footnotes(&mut self) -> String {
+        let mut out = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:footnotes xmlns:w=\"{}\" xmlns:r=\"{}\">\
+             <w:footnote w:type=\"separator\" w:id=\"-1\"><w:p><w:pPr><w:spacing w:after=\"0\"/></w:pPr><w:r><w:separator/></w:r></w:p></w:footnote>\
+             <w:footnote w:type=\"continuationSeparator\" w:id=\"0\"><w:p><w:pPr><w:spacing w:after=\"0\"/></w:pPr><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>",
+            W_NS, R_NS
+        );
+        for (i, id) in self.footnote_order.iter().enumerate() {
+            let text = self.footnote_text.get(id).map(String::as_str).unwrap_or("");
+            let _ = write!(
+                out,
+                "<w:footnote w:id=\"{}\"><w:p>{}<w:r><w:rPr><w:rStyle w:val=\"FootnoteReference\"/></w:rPr><w:footnoteRef/></w:r>{}</w:p></w:footnote>",
+                i + 1,
+                paragraph_properties(Some("FootnoteText"), ""),
+                text_run(&format!(" {}", text), "")
+            );
+        }
+        out.push_str("</w:footnotes>\n");
+        out
+    }
+}
+
+fn paragraph_properties(style: Option<&str>, properties: &str) -> String {
+    match style {
+        Some(style) => format!("<w:pPr><w:pStyle w:val=\"{}\"/>{}</w:pPr>", style, properties),
+        None if properties.is_empty() => String::new(),
+        None => format!("<w:pPr>{}</w:pPr>", properties),
+    }
+}
+
+/// Run properties for `marks`, in schema order, with an optional
+/// character style; code always uses the code style
+fn run_properties(marks: &[Mark], style: Option<&str>) -> String {
+    let mut out = String::new();
+    if let Some(style) = if marks.contains(&Mark::Code) { Some(CODE_STYLE) } else { style } {
+        let _ = write!(out, "<w:rStyle w:val=\"{}\"/>", style);
+    }
+    for (mark, property) in [
+        (Mark::Bold, "<w:b/>"),
+        (Mark::Italic, "<w:i/>"),
+        (Mark::Strike, "<w:strike/>"),
+        (Mark::Underline, "<w:u w:val=\"single\"/>"),
+    ] {
+        if marks.contains(&mark) {
+            out.push_str(property);
+        }
+    }
+    if marks.contains(&Mark::Superscript) {
+        out.push_str("<w:vertAlign w:val=\"superscript\"/>");
+    } else if marks.contains(&Mark::Subscript) {
+        out.push_str("<w:vertAlign w:val=\"subscript\"/>");
+    }
+    if out.is_empty() {
+        out
+    } else {
+        format!("<w:rPr>{}</w:rPr>", out)
+    }
+}
+
+/// A run of text; line breaks and tabs become `w:br` and `w:tab`
+fn text_run(text: &str, run_properties: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("<w:r>{}", run_properties);
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push_str("<w:br/>");
+        }
+        for (j, piece) in line.split('\t').enumerate() {
+            if j > 0 {
+                out.push_str("<w:tab/>");
+            }
+            if !piece.is_empty() {
+                let _ = write!(out, "<w:t xml:space=\"preserve\">{}</w:t>", xml_text(piece));
+            }
+        }
+    }
+    out.push_str("</w:r>");
+    out
+}
+
+/// Render a document as a DOCX package
+///
+/// # Arguments
+/// * `document` - Document to export
+/// * `assets` - Archive assets, keyed by path; PNG, JPEG and GIF figures
+///   are embedded
+///
+/// # Returns
+/// The bytes of the `.docx` file
+pub fn render_docx(document: &Document, assets: &HashMap<String, Vec<u8>>) -> Result<Vec<u8>, ConvertError> {
+    let content = &document.content;
+    let blocks = || content.sections.iter().flat_map(|s| s.content.iter());
+    let ids = footnote_ids(content);
+    let referenced = blocks()
+        .flat_map(block_texts)
+        .flat_map(|text| {
+            inline_parts(&text, &ids)
+                .into_iter()
+                .filter_map(|part| match part {
+                    Inline::Footnote(id) => Some(id.to_string()),
+                    Inline::Text(_) => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let footnote_text = blocks()
+        .filter_map(|block| match block {
+            ContentBlock::Footnote { id, text } => Some((id.clone(), text.clone())),
+            _ => None,
+        })
+        .collect();
+    let targets = blocks()
+        .flat_map(|block| match block {
+            ContentBlock::Heading { text, .. } | ContentBlock::Paragraph { text, .. } | ContentBlock::Quote { text, .. } => text.runs(),
+            ContentBlock::List { items, .. } => items.iter().flat_map(ListItem::texts).flat_map(InlineText::runs).collect(),
+            _ => Vec::new(),
+        })
+        .filter_map(|run| match run {
+            InlineRun::CrossRef { target, .. } => Some(target),
+            InlineRun::Text { link: Some(href), .. } => href.strip_prefix('#').map(str::to_string),
+            _ => None,
+        })
+        .collect();
+
+    let mut writer = DocxWriter {
+        body: String::new(),
+        assets,
+        footnote_text,
+        referenced,
+        footnote_order: Vec::new(),
+        lists: Vec::new(),
+        media: Vec::new(),
+        media_by_asset: HashMap::new(),
+        drawings: 0,
+        links: Vec::new(),
+        targets,
+        bookmarks: 0,
+        charts: chart::collect(content),
+    };
+    let title = document.manifest.document.title.clone();
+    writer.paragraph(Some("Title"), &title);
+    for section in &content.sections {
+        writer.section(section);
+    }
+
+    let document_xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <w:document xmlns:w=\"{}\" xmlns:r=\"{}\" xmlns:wp=\"{}\"><w:body>{}\
+         <w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/><w:pgMar w:top=\"1440\" w:right=\"1440\" w:bottom=\"1440\" w:left=\"1440\" w:header=\"708\" w:footer=\"708\" w:gutter=\"0\"/></w:sectPr>\
+         </w:body></w:document>\n",
+        W_NS, R_NS, WP_NS, writer.body
+    );
+    let footnotes_xml = writer.footnotes();
+    let numbering_xml = writer.numbering();
+    let settings_xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:settings xmlns:w=\"{}\">\
+         <w:footnotePr><w:footnote w:id=\"-1\"/><w:footnote w:id=\"0\"/></w:footnotePr></w:settings>\n",
+        W_NS
+    );
+    let meta = &document.manifest.document;
+    let core_xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:dcterms=\"http://purl.org/dc/terms/\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\
+         <dc:title>{}</dc:title><dc:identifier>{}</dc:identifier><dc:language>{}</dc:language>\
+         <dcterms:created xsi:type=\"dcterms:W3CDTF\">{}</dcterms:created>\
+         <dcterms:modified xsi:type=\"dcterms:W3CDTF\">{}</dcterms:modified></cp:coreProperties>\n",
+        xml_text(&meta.title),
+        xml_text(&meta.id),
+        xml_text(&meta.language),
+        meta.created.format("%Y-%m-%dT%H:%M:%SZ"),
+        meta.modified.format("%Y-%m-%dT%H:%M:%SZ")
+    );
+
+    let mut content_types = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>",
+    );
+    let mut extensions = HashSet::new();
+    for media in &writer.media {
+        if let Some((extension, content_type)) = image_content_type(&media.file_name) {
+            if extensions.insert(extension) {
+                let _ = write!(content_types, "<Default Extension=\"{}\" ContentType=\"{}\"/>", extension, content_type);
+            }
+        }
+    }
+    let word = "application/vnd.openxmlformats-officedocument.wordprocessingml";
+    for (part, kind) in [
+        ("/word/document.xml", "document.main"),
+        ("/word/styles.xml", "styles"),
+        ("/word/numbering.xml", "numbering"),
+        ("/word/footnotes.xml", "footnotes"),
+        ("/word/settings.xml", "settings"),
+    ] {
+        let _ = write!(content_types, "<Override PartName=\"{}\" ContentType=\"{}.{}+xml\"/>", part, word, kind);
+    }
+    content_types.push_str(
+        "<Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/></Types>\n",
+    );
+
+    let root_relationships = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+         <Relationship Id=\"rId1\" Type=\"{}/officeDocument\" Target=\"word/document.xml\"/>\
+         <Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" Target=\"docProps/core.xml\"/>\
+         </Relationships>\n",
+        R_NS
+    );
+    let mut relationships = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    );
+    for (i, part) in ["styles", "numbering", "footnotes", "settings"].iter().enumerate() {
+        let _ = write!(relationships, "<Relationship Id=\"rId{}\" Type=\"{}/{}\" Target=\"{}.xml\"/>", i + 1, R_NS, part, part);
+    }
+    for media in &writer.media {
+        let _ = write!(
+            relationships,
+            "<Relationship Id=\"{}\" Type=\"{}/image\" Target=\"media/{}\"/>",
+            media.relationship,
+            R_NS,
+            xml_text(&media.file_name)
+        );
+    }
+    for (i, href) in writer.links.iter().enumerate() {
+        let _ = write!(
+            relationships,
+            "<Relationship Id=\"rIdLink{}\" Type=\"{}/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>",
+            i + 1,
+            R_NS,
+            xml_text(href)
+        );
+    }
+    relationships.push_str("</Relationships>\n");
+
+    let zip_error = |e: zip::result::ZipError| ConvertError::Docx(format!("DOCX write error: {}", e));
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let parts: [(&str, &[u8]); 9] = [
+        ("[Content_Types].xml", content_types.as_bytes()),
+        ("_rels/.rels", root_relationships.as_bytes()),
+        ("docProps/core.xml", core_xml.as_bytes()),
+        ("word/document.xml", document_xml.as_bytes()),
+        ("word/_rels/document.xml.rels", relationships.as_bytes()),
+        ("word/styles.xml", STYLES.as_bytes()),
+        ("word/numbering.xml", numbering_xml.as_bytes()),
+        ("word/footnotes.xml", footnotes_xml.as_bytes()),
+        ("word/settings.xml", settings_xml.as_bytes()),
+    ];
+    for (name, data) in parts {
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(data)?;
+    }
+    let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+    for media in &writer.media {
+        zip.start_file(format!("word/media/{}", media.file_name), stored).map_err(zip_error)?;
+        zip.write_all(&media.data)?;
+    }
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+/// Write a document as a `.docx` file
+pub fn write_docx(document: &Document, assets: &HashMap<String, Vec<u8>>, path: &Path) -> Result<(), ConvertError> {
+    std::fs::write(path, render_docx(document, assets)?)?;
+    Ok(())
+}
@@ -0,0 +1,574 @@
+//! Markdown export
+//!
+//! CommonMark with the GitHub extensions the importer reads: pipe tables
+//! (caption on a `Table:` line after the table, footer as a bold last row),
+//! footnotes (`[^id]`) and images for figures. Each section starts with a
+//! `<!-- tdf:section id="..." -->` comment followed by its title as a
+//! level-2 heading. Diagrams and charts become `mermaid` code blocks.
+//! Underline, superscript and subscript marks, which Markdown has no syntax
+//! for, are written as `<u>`, `<sup>` and `<sub>` tags. Task list items use GitHub
+//! `[ ]`/`[x]` checkboxes, a quote's attribution is a last `— ` paragraph
+//! and page breaks are `<!-- tdf:page-break -->` comments. Form fields are
+//! written for reading only: a bold label, a blank to fill in (a task item
+//! for a checkbox) and a hint of what the field accepts.
+
+use super::{footnote_ids, inline_parts, inline_runs, is_safe_asset_path, Inline};
+use crate::error::ConvertError;
+use crate::markdown::{ATTRIBUTION_PREFIX, PAGE_BREAK_MARKER, SECTION_MARKER, TABLE_CAPTION_PREFIX};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tdf_core::content::{
+    CellType, ChartAxes, ChartType, ContentBlock, DiagramEdge, DiagramNode, DiagramShape, EdgeType, InlineRun, InlineText, LayoutDirection, ListItem,
+    Mark, Section, TableColumn, TableRow,
+};
+use tdf_core::chart::{self, ChartData};
+use tdf_core::document::Document;
+use tdf_core::form::{FieldKind, FormField};
+use tdf_core::html::is_safe_link;
+
+/// Characters escaped wherever they appear in text
+const ESCAPED: &str = "\\`*_[]<>#|~&!";
+
+/// Escape one line of text so Markdown reads it back verbatim
+fn escape_line(line: &str, at_line_start: bool) -> String {
+    let line = if at_line_start { line.trim_start() } else { line };
+    let mut out = String::with_capacity(line.len());
+    for c in line.chars() {
+        if ESCAPED.contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    if at_line_start {
+        // Characters that would open a list, rule or setext heading
+        let digits = out.chars().take_while(|c| c.is_ascii_digit()).count();
+        if out.starts_with(['-', '+', '=']) {
+            out.insert(0, '\\');
+        } else if digits > 0 && out[digits..].starts_with(['.', ')']) {
+            out.insert(digits, '\\');
+        }
+    }
+    out
+}
+
+/// Escape inline text, turning footnote markers into `[^id]` and line
+/// breaks into hard breaks indented by `indent`
+fn inline(text: &str, footnotes: &HashSet<String>, indent: &str) -> String {
+    inline_from(text, footnotes, indent, true)
+}
+
+/// `inline`, for text that does not start a line when `line_start` is false
+fn inline_from(text: &str, footnotes: &HashSet<String>, indent: &str, mut line_start: bool) -> String {
+    let mut out = String::new();
+    for part in inline_parts(text, footnotes) {
+        match part {
+            Inline::Footnote(id) => {
+                let _ = write!(out, "[^{}]", id);
+                line_start = false;
+            }
+            Inline::Text(text) => escape_text(&mut out, text, indent, &mut line_start),
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// Escape text line by line, joining lines with hard breaks indented by
+/// `indent`
+fn escape_text(out: &mut String, text: &str, indent: &str, line_start: &mut bool) {
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            let _ = write!(out, "\\\n{}", indent);
+            *line_start = true;
+        }
+        out.push_str(&escape_line(line, *line_start));
+        *line_start = *line_start && line.is_empty();
+    }
+}
+
+/// Escape rich inline text: bold, italics and strike-through as emphasis
+/// delimiters, code as code spans, other marks as HTML tags, safe links
+/// and cross-references as links and footnote references as `[^id]`
+fn inline_text(text: &InlineText, footnotes: &HashSet<String>, indent: &str) -> String {
+    let runs = inline_runs(text, footnotes);
+    let mut out = String::new();
+    let mut line_start = true;
+    let mut i = 0;
+    while i < runs.len() {
+        match &runs[i] {
+            InlineRun::FootnoteRef { id } => {
+                let _ = write!(out, "[^{}]", id);
+                line_start = false;
+            }
+            InlineRun::CrossRef { target, text } => {
+                out.push('[');
+                escape_text(&mut out, &text.replace('\n', " "), indent, &mut false);
+                let _ = write!(out, "](<#{}>)", link_destination(target));
+                line_start = false;
+            }
+            InlineRun::Text { text, marks, link } => match link.as_deref().filter(|href| is_safe_link(href)) {
+                Some(href) => {
+                    // Runs sharing a link are one link with formatted parts
+                    let end = runs[i..]
+                        .iter()
+                        .position(|run| !matches!(run, InlineRun::Text { link: Some(other), .. } if other == href))
+                        .map_or(runs.len(), |n| i + n);
+                    out.push('[');
+                    for j in i..end {
+                        if let InlineRun::Text { text, marks, .. } = &runs[j] {
+                            let next = if j + 1 == end { Some(']') } else { lead_char(&runs[j + 1], true) };
+                            marked_text(&mut out, text, marks, indent, &mut false, next);
+                        }
+                    }
+                    let _ = write!(out, "](<{}>)", link_destination(href));
+                    line_start = false;
+                    i = end;
+                    continue;
+                }
+                None => {
+                    let next = runs.get(i + 1).and_then(|run| lead_char(run, false));
+                    marked_text(&mut out, text, marks, indent, &mut line_start, next);
+                }
+            },
+        }
+        i += 1;
+    }
+    out.trim_end().to_string()
+}
+
+/// Emphasis delimiter of a mark Markdown has syntax for
+fn delimiter(mark: Mark) -> Option<&'static str> {
+    match mark {
+        Mark::Bold => Some("**"),
+        Mark::Italic => Some("*"),
+        Mark::Strike => Some("~~"),
+        _ => None,
+    }
+}
+
+/// HTML tag of a mark, for marks without Markdown syntax and emphasis
+/// that would not parse as delimiters where it stands
+fn mark_tag(mark: Mark) -> &'static str {
+    match mark {
+        Mark::Bold => "strong",
+        Mark::Italic => "em",
+        Mark::Strike => "s",
+        Mark::Underline => "u",
+        Mark::Superscript => "sup",
+        Mark::Subscript => "sub",
+        Mark::Code => "code",
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    !c.is_alphanumeric() && !c.is_whitespace()
+}
+
+/// First character a run is written with, as far as emphasis parsing
+/// cares; `*` for runs that may start with a delimiter
+fn lead_char(run: &InlineRun, in_link: bool) -> Option<char> {
+    match run {
+        InlineRun::FootnoteRef { .. } | InlineRun::CrossRef { .. } => Some('['),
+        InlineRun::Text { link: Some(href), .. } if !in_link && is_safe_link(href) => Some('['),
+        InlineRun::Text { marks, .. } if marks.iter().any(|m| delimiter(*m).is_some()) => Some('*'),
+        InlineRun::Text { marks, .. } if marks.contains(&Mark::Code) => Some('`'),
+        InlineRun::Text { marks, .. } if !marks.is_empty() => Some('<'),
+        InlineRun::Text { text, .. } => text.chars().next(),
+    }
+}
+
+/// Whether emphasis delimiters around `body` open and close where it
+/// stands between `prev` and `next` (CommonMark flanking rules)
+fn can_delimit(prev: Option<char>, body: &str, next: Option<char>) -> bool {
+    let (Some(first), Some(last)) = (body.chars().next(), body.chars().last()) else { return false };
+    let open_side = |c: Option<char>| c.is_none_or(|c| c.is_whitespace() || is_punctuation(c));
+    !matches!(prev, Some('*' | '_' | '~'))
+        && !matches!(next, Some('*' | '_' | '~'))
+        && (!is_punctuation(first) || open_side(prev))
+        && (!is_punctuation(last) || open_side(next))
+}
+
+/// Code span holding `text`, fenced with more backticks than it contains
+fn code_span(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+    format!("{0}{1}{2}{1}{0}", fence, pad, text)
+}
+
+/// Write a text run with its marks; surrounding whitespace is kept
+/// outside the marks so delimiters stay next to the text
+fn marked_text(out: &mut String, text: &str, marks: &[Mark], indent: &str, line_start: &mut bool, next: Option<char>) {
+    let core = text.trim();
+    if marks.is_empty() || core.is_empty() {
+        escape_text(out, text, indent, line_start);
+        return;
+    }
+    let lead = &text[..text.len() - text.trim_start().len()];
+    let trail = &text[text.trim_end().len()..];
+    escape_text(out, lead, indent, line_start);
+
+    let mut body = if marks.contains(&Mark::Code) {
+        code_span(core)
+    } else {
+        let mut body = String::new();
+        escape_text(&mut body, core, indent, &mut false);
+        body
+    };
+    for mark in [Mark::Underline, Mark::Superscript, Mark::Subscript] {
+        if marks.contains(&mark) {
+            body = format!("<{0}>{1}</{0}>", mark_tag(mark), body);
+        }
+    }
+    let next = if trail.is_empty() { next } else { Some(' ') };
+    let delimited = can_delimit(out.chars().last(), &body, next);
+    for mark in [Mark::Strike, Mark::Italic, Mark::Bold] {
+        if !marks.contains(&mark) {
+            continue;
+        }
+        body = match delimiter(mark) {
+            Some(delimiter) if delimited => format!("{0}{1}{0}", delimiter, body),
+            _ => format!("<{0}>{1}</{0}>", mark_tag(mark), body),
+        };
+    }
+    out.push_str(&body);
+    *line_start = false;
+    escape_text(out, trail, indent, line_start);
+}
+
+/// Link destination for the `<...>` form
+fn link_destination(href: &str) -> String {
+    href.replace('\\', "\\\\").replace('<', "%3C").replace('>', "%3E").replace('\n', "%0A")
+}
+
+/// Escape text for a table cell, where line breaks become `<br>` and
+/// nothing starts a block
+fn cell(text: &str, footnotes: &HashSet<String>) -> String {
+    text.split('\n').map(|line| inline_from(line, footnotes, "", false)).collect::<Vec<_>>().join("<br>")
+}
+
+fn is_numeric(column: &TableColumn) -> bool {
+    matches!(column.cell_type, CellType::Number | CellType::Currency | CellType::Percentage | CellType::Formula)
+}
+
+/// Text that stays inside a mermaid label
+fn mermaid_label(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br>").replace('|', "#124;")
+}
+
+struct MarkdownWriter {
+    out: String,
+    footnotes: HashSet<String>,
+    /// Data of every chart, by chart id
+    charts: HashMap<String, Result<ChartData, String>>,
+}
+
+impl MarkdownWriter {
+    fn block(&mut self, block: &ContentBlock) {
+        match block {
+            ContentBlock::Heading { level, text, .. } => {
+                let level = (*level).clamp(1, 6) as usize;
+                let text = inline_text(text, &self.footnotes, "");
+                let _ = writeln!(self.out, "{} {}\n", "#".repeat(level), text.replace("\\\n", " "));
+            }
+            ContentBlock::Paragraph { text, .. } => {
+                let _ = writeln!(self.out, "{}\n", inline_text(text, &self.footnotes, ""));
+            }
+            ContentBlock::List { ordered, items, .. } => {
+                self.list(*ordered, items, "");
+                self.out.push('\n');
+            }
+            ContentBlock::Quote { text, attribution, .. } => {
+                let _ = writeln!(self.out, "> {}", inline_text(text, &self.footnotes, "> "));
+                if let Some(attribution) = attribution {
+                    let _ = writeln!(self.out, ">\n> {}{}", ATTRIBUTION_PREFIX, inline_from(attribution, &self.footnotes, "> ", false));
+                }
+                self.out.push('\n');
+            }
+            ContentBlock::Code { language, text, .. } => {
+                // The fence is longer than any backtick run in the code
+                let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+                let fence = "`".repeat(longest.max(2) + 1);
+                let _ = writeln!(self.out, "{}{}\n{}\n{}\n", fence, language.as_deref().unwrap_or(""), text, fence);
+            }
+            ContentBlock::PageBreak => {
+                let _ = writeln!(self.out, "<!-- {} -->\n", PAGE_BREAK_MARKER);
+            }
+            ContentBlock::HorizontalRule => self.out.push_str("---\n\n"),
+            ContentBlock::Table { caption, columns, rows, footer, .. } => self.table(caption.as_deref(), columns, rows, footer.as_deref()),
+            ContentBlock::Figure { asset, alt, caption, .. } => {
+                let alt = alt.replace('\n', " ").replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]");
+                let url = asset.replace('<', "%3C").replace('>', "%3E");
+                match caption {
+                    Some(caption) => {
+                        let title = caption.replace('\\', "\\\\").replace('"', "\\\"");
+                        let _ = writeln!(self.out, "![{}](<{}> \"{}\")\n", alt, url, title);
+                    }
+                    None => {
+                        let _ = writeln!(self.out, "![{}](<{}>)\n", alt, url);
+                    }
+                }
+            }
+            ContentBlock::Footnote { id, text } => {
+                let _ = writeln!(self.out, "[^{}]: {}\n", id, inline(text, &self.footnotes, "    "));
+            }
+            ContentBlock::Diagram { title, nodes, edges, layout, .. } => self.diagram(title.as_deref(), nodes, edges, layout.as_ref().and_then(|l| l.direction.as_ref())),
+            ContentBlock::Chart { id, chart_type, title, axes, .. } => self.chart(id, *chart_type, title.as_deref(), axes.as_ref()),
+            ContentBlock::Redacted { .. } => self.out.push_str("*\\[Redacted\\]*\n\n"),
+            ContentBlock::TextField { .. }
+            | ContentBlock::NumberField { .. }
+            | ContentBlock::DateField { .. }
+            | ContentBlock::Checkbox { .. }
+            | ContentBlock::Choice { .. }
+            | ContentBlock::SignatureField { .. } => {
+                if let Some(field) = FormField::from_block(block) {
+                    self.field(&field);
+                }
+            }
+        }
+    }
+
+    /// Label, a blank to fill in and a hint of what the field accepts
+    fn field(&mut self, field: &FormField) {
+        let required = if field.required { "\\*" } else { "" };
+        let label = inline(field.label, &self.footnotes, "");
+        let hint = inline(&field.describe(), &self.footnotes, "");
+        match field.kind {
+            FieldKind::Checkbox => {
+                let _ = write!(self.out, "- [ ] **{}**{} *({})*", label, required, hint);
+            }
+            _ => {
+                let _ = write!(self.out, "**{}**{}: \\_\\_\\_\\_\\_\\_\\_\\_\\_\\_ *({})*", label, required, hint);
+            }
+        }
+        if let Some(help) = field.help {
+            let _ = write!(self.out, "\\\n{}", inline(help, &self.footnotes, ""));
+        }
+        self.out.push_str("\n\n");
+    }
+
+    /// Items of a list, each line prefixed with `indent`
+    fn list(&mut self, ordered: bool, items: &[ListItem], indent: &str) {
+        for (i, item) in items.iter().enumerate() {
+            let marker = if ordered { format!("{}.", i + 1) } else { "-".to_string() };
+            let checkbox = match item.checked {
+                Some(true) => "[x] ",
+                Some(false) => "[ ] ",
+                None => "",
+            };
+            let inner = format!("{}{}", indent, " ".repeat(marker.len() + 1));
+            let text = inline_text(&item.text, &self.footnotes, &inner);
+            let _ = writeln!(self.out, "{}{} {}{}", indent, marker, checkbox, text);
+            if let Some(sublist) = &item.sublist {
+                self.list(sublist.ordered, &sublist.items, &inner);
+            }
+        }
+    }
+
+    fn table(&mut self, caption: Option<&str>, columns: &[TableColumn], rows: &[TableRow], footer: Option<&[String]>) {
+        if columns.is_empty() {
+            return;
+        }
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        let headers = columns.iter().map(|c| cell(&c.header, &self.footnotes)).map(|h| if h.is_empty() { " ".to_string() } else { h }).collect();
+        self.out.push_str(&line(headers));
+        self.out.push_str(&line(columns.iter().map(|c| if is_numeric(c) { "---:" } else { "---" }.to_string()).collect()));
+        for row in rows {
+            let cells = columns
+                .iter()
+                .map(|c| row.cells.get(&c.id).map(|v| cell(v.display_text(), &self.footnotes)).unwrap_or_default())
+                .collect();
+            self.out.push_str(&line(cells));
+        }
+        if let Some(footer) = footer {
+            let cells = (0..columns.len())
+                .map(|i| match footer.get(i).map(|t| cell(t, &self.footnotes)) {
+                    Some(text) if !text.is_empty() => format!("**{}**", text),
+                    _ => String::new(),
+                })
+                .collect();
+            self.out.push_str(&line(cells));
+        }
+        self.out.push('\n');
+        if let Some(caption) = caption {
+            let _ = writeln!(self.out, "{}{}\n", TABLE_CAPTION_PREFIX, inline(caption, &self.footnotes, ""));
+        }
+    }
+
+    fn diagram(&mut self, title: Option<&str>, nodes: &[DiagramNode], edges: &[DiagramEdge], direction: Option<&LayoutDirection>) {
+        self.out.push_str("```mermaid\n");
+        if let Some(title) = title {
+            let _ = writeln!(self.out, "---\ntitle: {}\n---", title.replace('\n', " "));
+        }
+        let direction = match direction {
+            Some(LayoutDirection::LeftRight) => "LR",
+            Some(LayoutDirection::BottomUp) => "BT",
+            Some(LayoutDirection::RightLeft) => "RL",
+            Some(LayoutDirection::TopDown) | None => "TD",
+        };
+        let _ = writeln!(self.out, "flowchart {}", direction);
+        // Mermaid ids are restricted, so nodes are numbered
+        let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (n.id.as_str(), i)).collect();
+        for (i, node) in nodes.iter().enumerate() {
+            let label = mermaid_label(&node.label);
+            let shape = match node.shape {
+                Some(DiagramShape::Rounded) => format!("(\"{}\")", label),
+                Some(DiagramShape::Diamond) => format!("{{\"{}\"}}", label),
+                Some(DiagramShape::Circle) => format!("((\"{}\"))", label),
+                Some(DiagramShape::Box) | None => format!("[\"{}\"]", label),
+            };
+            let _ = writeln!(self.out, "    n{}{}", i, shape);
+        }
+        for edge in edges {
+            let (Some(from), Some(to)) = (index.get(edge.from.as_str()), index.get(edge.to.as_str())) else { continue };
+            let arrow = match edge.edge_type {
+                EdgeType::Solid => "-->",
+                EdgeType::Dashed | EdgeType::Dotted => "-.->",
+            };
+            match &edge.label {
+                Some(label) => {
+                    let _ = writeln!(self.out, "    n{} {}|\"{}\"| n{}", from, arrow, mermaid_label(label), to);
+                }
+                None => {
+                    let _ = writeln!(self.out, "    n{} {} n{}", from, arrow, to);
+                }
+            }
+        }
+        self.out.push_str("```\n\n");
+    }
+
+    /// A mermaid `pie` or `xychart-beta` block; gaps in a series are drawn as zero
+    fn chart(&mut self, id: &str, chart_type: ChartType, title: Option<&str>, axes: Option<&ChartAxes>) {
+        let data = match self.charts.get(id) {
+            Some(Ok(data)) => data,
+            Some(Err(e)) => {
+                let _ = writeln!(self.out, "*\\[Chart cannot be drawn: {}\\]*\n", inline(e, &HashSet::new(), ""));
+                return;
+            }
+            None => return,
+        };
+        let quoted = |text: &str| format!("\"{}\"", mermaid_label(text).replace("<br>", " "));
+        self.out.push_str("```mermaid\n");
+        if chart_type == ChartType::Pie {
+            match title {
+                Some(title) => {
+                    let _ = writeln!(self.out, "pie title {}", title.replace('\n', " "));
+                }
+                None => self.out.push_str("pie\n"),
+            }
+            if let Some(series) = data.series.first() {
+                for (category, value) in data.categories.iter().zip(&series.values) {
+                    if let Some(value) = value {
+                        let _ = writeln!(self.out, "    {} : {}", quoted(category), value.value);
+                    }
+                }
+            }
+            self.out.push_str("```\n\n");
+            return;
+        }
+        self.out.push_str("xychart-beta\n");
+        if let Some(title) = title {
+            let _ = writeln!(self.out, "    title {}", quoted(title));
+        }
+        let categories: Vec<String> = data.categories.iter().map(|c| quoted(c)).collect();
+        let x_label = axes.and_then(|a| a.x_label.as_deref()).map(|l| format!("{} ", quoted(l))).unwrap_or_default();
+        let _ = writeln!(self.out, "    x-axis {}[{}]", x_label, categories.join(", "));
+        let y_label = axes.and_then(|a| a.y_label.as_deref()).map(quoted);
+        let range = axes.and_then(|a| Some(format!("{} --> {}", a.min?, a.max?)));
+        if y_label.is_some() || range.is_some() {
+            let parts: Vec<String> = y_label.into_iter().chain(range).collect();
+            let _ = writeln!(self.out, "    y-axis {}", parts.join(" "));
+        }
+        let kind = if chart_type == ChartType::Line { "line" } else { "bar" };
+        for series in &data.series {
+            let values: Vec<String> = series
+                .values
+                .iter()
+                .map(|v| v.as_ref().map_or("0".to_string(), |v| v.value.to_string()))
+                .collect();
+            let _ = writeln!(self.out, "    {} [{}]", kind, values.join(", "));
+        }
+        self.out.push_str("```\n\n");
+    }
+
+    fn section(&mut self, section: &Section) {
+        let id = section.id.replace("--", "-").replace('"', "");
+        match &section.title {
+            Some(title) => {
+                let _ = writeln!(self.out, "<!-- {} id=\"{}\" -->\n", SECTION_MARKER, id);
+                let _ = writeln!(self.out, "## {}\n", inline(title, &self.footnotes, "").replace("\\\n", " "));
+            }
+            None => {
+                let _ = writeln!(self.out, "<!-- {} id=\"{}\" untitled -->\n", SECTION_MARKER, id);
+            }
+        }
+        if section.encrypted.is_some() {
+            self.out.push_str("*\\[Encrypted section\\]*\n\n");
+        }
+        let mut previous: Option<&ContentBlock> = None;
+        for block in &section.content {
+            // Consecutive lists would merge into one
+            if matches!((previous, block), (Some(ContentBlock::List { .. }), ContentBlock::List { .. })) {
+                self.out.push_str("<!-- -->\n\n");
+            }
+            self.block(block);
+            previous = Some(block);
+        }
+    }
+}
+
+/// Render a document as Markdown
+///
+/// Figures refer to their assets by archive path (`assets/images/...`);
+/// `write_markdown` writes those files next to the Markdown.
+pub fn render_markdown(document: &Document) -> String {
+    let mut writer = MarkdownWriter {
+        out: String::new(),
+        footnotes: footnote_ids(&document.content),
+        charts: chart::collect(&document.content),
+    };
+    let _ = writeln!(writer.out, "# {}\n", inline(&document.manifest.document.title, &HashSet::new(), ""));
+    for section in &document.content.sections {
+        writer.section(section);
+    }
+    let trimmed = writer.out.trim_end().len();
+    writer.out.truncate(trimmed);
+    writer.out.push('\n');
+    writer.out
+}
+
+/// Write a document as Markdown, with the figures' images beside it
+///
+/// # Arguments
+/// * `document` - Document to export
+/// * `assets` - Archive assets, keyed by path
+/// * `path` - Markdown file to write; images go below its directory at
+///   their archive paths
+///
+/// # Returns
+/// The files written, Markdown first
+pub fn write_markdown(document: &Document, assets: &HashMap<String, Vec<u8>>, path: &Path) -> Result<Vec<PathBuf>, ConvertError> {
+    std::fs::write(path, render_markdown(document))?;
+    let mut written = vec![path.to_path_buf()];
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let figures = document.content.sections.iter().flat_map(|s| s.content.iter()).filter_map(|block| match block {
+        ContentBlock::Figure { asset, .. } => Some(asset),
+        _ => None,
+    });
+    let mut seen = HashSet::new();
+    for asset in figures {
+        let Some(data) = assets.get(asset) else { continue };
+        if !is_safe_asset_path(asset) || !seen.insert(asset) {
+            continue;
+        }
+        let target = dir.join(asset);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, data)?;
+        written.push(target);
+    }
+    Ok(written)
+}
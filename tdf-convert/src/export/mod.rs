@@ -0,0 +1,128 @@
+//! Export of TDF documents to editable formats
+//!
+//! Markdown and DOCX output map blocks to each format's native constructs,
+//! so a document can be edited in familiar tools and imported again with
+//! [`crate::markdown`] and [`crate::docx`]. Headings, paragraphs, nested
+//! and task lists, quotes, code, page breaks, rules, tables, figures and
+//! footnotes survive the round trip; diagrams, redactions and encrypted
+//! sections are written for reading only.
+//!
+//! XBRL output writes the facts tagged in a document as an XBRL instance or
+//! as inline XBRL, for filing rather than editing.
+
+pub mod docx;
+pub mod markdown;
+pub mod xbrl;
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use tdf_core::content::{ContentBlock, DocumentContent, InlineRun, InlineText, ListItem};
+
+/// A piece of inline text
+#[derive(Debug, PartialEq)]
+pub(crate) enum Inline<'t> {
+    Text(&'t str),
+    /// Reference to the footnote block with this id
+    Footnote(&'t str),
+}
+
+/// Ids of the document's footnote blocks
+pub(crate) fn footnote_ids(content: &DocumentContent) -> HashSet<String> {
+    content
+        .sections
+        .iter()
+        .flat_map(|s| s.content.iter())
+        .filter_map(|block| match block {
+            ContentBlock::Footnote { id, .. } => Some(id.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Split text at `{{id}}` markers naming one of `footnotes`; other braces
+/// stay text
+pub(crate) fn inline_parts<'t>(text: &'t str, footnotes: &HashSet<String>) -> Vec<Inline<'t>> {
+    let mut parts = Vec::new();
+    let mut last = 0;
+    let mut from = 0;
+    while let Some(start) = text[from..].find("{{").map(|i| from + i) {
+        let Some(len) = text[start + 2..].find("}}") else { break };
+        let id = &text[start + 2..start + 2 + len];
+        let end = start + 2 + len + 2;
+        if footnotes.contains(id) {
+            if start > last {
+                parts.push(Inline::Text(&text[last..start]));
+            }
+            parts.push(Inline::Footnote(id));
+            last = end;
+        }
+        from = end;
+    }
+    if last < text.len() {
+        parts.push(Inline::Text(&text[last..]));
+    }
+    parts
+}
+
+/// Runs of inline text with `{{id}}` markers naming one of `footnotes`
+/// split out as footnote references
+pub(crate) fn inline_runs(text: &InlineText, footnotes: &HashSet<String>) -> Vec<InlineRun> {
+    let mut runs = Vec::new();
+    for run in text.runs() {
+        match run {
+            InlineRun::Text { text, marks, link } => {
+                for part in inline_parts(&text, footnotes) {
+                    runs.push(match part {
+                        Inline::Text(text) => InlineRun::Text { text: text.to_string(), marks: marks.clone(), link: link.clone() },
+                        Inline::Footnote(id) => InlineRun::FootnoteRef { id: id.to_string() },
+                    });
+                }
+            }
+            other => runs.push(other),
+        }
+    }
+    runs
+}
+
+/// Whether an archive path can be written below an export directory
+pub(crate) fn is_safe_asset_path(path: &str) -> bool {
+    !path.is_empty() && !path.contains("..") && !path.starts_with('/') && !path.starts_with('\\') && !path.contains(':')
+}
+
+/// Escape text for XML, dropping control characters XML cannot carry
+pub(crate) fn xml_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Inline texts of a block that may hold footnote markers
+///
+/// Footnote reference runs of rich text are given as `{{id}}` markers.
+pub(crate) fn block_texts(block: &ContentBlock) -> Vec<Cow<'_, str>> {
+    match block {
+        ContentBlock::Heading { text, .. } | ContentBlock::Paragraph { text, .. } | ContentBlock::Quote { text, .. } => {
+            vec![text.plain_text()]
+        }
+        ContentBlock::List { items, .. } => items.iter().flat_map(ListItem::texts).map(InlineText::plain_text).collect(),
+        ContentBlock::Table { caption, columns, rows, footer, .. } => caption
+            .iter()
+            .map(|c| Cow::Borrowed(c.as_str()))
+            .chain(columns.iter().map(|c| Cow::Borrowed(c.header.as_str())))
+            .chain(rows.iter().flat_map(|r| r.cells.values().map(|v| Cow::Borrowed(v.display_text()))))
+            .chain(footer.iter().flatten().map(|c| Cow::Borrowed(c.as_str())))
+            .collect(),
+        ContentBlock::Figure { caption, .. } => caption.iter().map(|c| Cow::Borrowed(c.as_str())).collect(),
+        _ => Vec::new(),
+    }
+}
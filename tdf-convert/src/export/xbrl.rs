@@ -0,0 +1,572 @@
+//! XBRL export
+//!
+//! The facts tagged in a document (see `tdf_core::xbrl`) are written as an
+//! XBRL 2.1 instance, or as inline XBRL: an XHTML rendering of the document
+//! whose tagged cells and paragraphs are `ix:nonFraction` and
+//! `ix:nonNumeric` elements. Contexts and units are numbered in order of
+//! first use, so a document always exports to the same file, and both
+//! formats record the document id and root hash the facts were read from.
+//!
+//! An amount is marked up where it is shown when its display text reads
+//! back as the amount with the `ixt:num-dot-decimal` or
+//! `ixt:num-comma-decimal` transformation, scaled by a power of ten
+//! (`€542K`, `14.9%`); other amounts, and dates, go into `ix:hidden`.
+//! Diagrams, charts and figures are only named, and encrypted sections and
+//! redacted content are left out.
+
+use super::xml_text;
+use crate::error::ConvertError;
+use std::fmt::Write as _;
+use std::path::Path;
+use tdf_core::content::{
+    covered_cells, header_layout, CellValue, ContentBlock, Decimal, HeaderCell, InlineRun, InlineText, ListItem, Mark, RowRole, Section,
+    TableColumn, TableRow,
+};
+use tdf_core::document::Document;
+use tdf_core::xbrl::{self, Fact, FactValue, XbrlEntity, XbrlInfo, XbrlPeriod, XbrlUnit, BUILTIN_PREFIXES};
+
+const LINK_NS: &str = "http://www.xbrl.org/2003/linkbase";
+const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+const IX_NS: &str = "http://www.xbrl.org/2013/inlineXBRL";
+const IXT_NS: &str = "http://www.xbrl.org/inlineXBRL/transformation/2020-02-12";
+
+/// Powers of ten an amount may be shown scaled by, most likely first
+const SCALES: [i32; 8] = [0, -2, 3, 6, 9, -3, 2, 1];
+
+const INLINE_STYLES: &str = "body { font-family: Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 60em; }\n\
+table { border-collapse: collapse; margin: 1em 0; }\n\
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; }\n\
+td.numeric { text-align: right; }\n\
+tr.subtotal td, tr.total td { font-weight: bold; }\n\
+.omitted { color: #666; font-style: italic; }\n";
+
+/// Contexts and units of a set of facts, numbered in order of first use
+struct Resources {
+    contexts: Vec<(XbrlEntity, XbrlPeriod)>,
+    units: Vec<XbrlUnit>,
+}
+
+impl Resources {
+    fn new(facts: &[Fact]) -> Self {
+        let mut resources = Resources { contexts: Vec::new(), units: Vec::new() };
+        for fact in facts {
+            let context = (fact.entity.clone(), fact.period);
+            if !resources.contexts.contains(&context) {
+                resources.contexts.push(context);
+            }
+            if let FactValue::Numeric { unit, .. } = &fact.value {
+                if !resources.units.contains(unit) {
+                    resources.units.push(unit.clone());
+                }
+            }
+        }
+        resources
+    }
+
+    /// `contextRef`, and for amounts `unitRef` and `decimals`, of a fact
+    fn refs(&self, fact: &Fact) -> String {
+        let context = self.contexts.iter().position(|(entity, period)| *entity == fact.entity && *period == fact.period);
+        let mut refs = format!(" contextRef=\"ctx{}\"", context.unwrap_or_default() + 1);
+        if let FactValue::Numeric { unit, decimals, .. } = &fact.value {
+            let unit = self.units.iter().position(|u| u == unit).unwrap_or_default() + 1;
+            let decimals = decimals.map_or_else(|| "INF".to_string(), |d| d.to_string());
+            let _ = write!(refs, " unitRef=\"unit{}\" decimals=\"{}\"", unit, decimals);
+        }
+        refs
+    }
+
+    fn write(&self, out: &mut String, indent: &str) {
+        for (i, (entity, period)) in self.contexts.iter().enumerate() {
+            let _ = writeln!(out, "{}<xbrli:context id=\"ctx{}\">", indent, i + 1);
+            let _ = writeln!(
+                out,
+                "{0}  <xbrli:entity>\n{0}    <xbrli:identifier scheme=\"{1}\">{2}</xbrli:identifier>\n{0}  </xbrli:entity>",
+                indent,
+                xml_text(&entity.scheme),
+                xml_text(&entity.identifier)
+            );
+            let _ = writeln!(out, "{}  <xbrli:period>", indent);
+            match period {
+                XbrlPeriod::Instant { instant } => {
+                    let _ = writeln!(out, "{}    <xbrli:instant>{}</xbrli:instant>", indent, instant);
+                }
+                XbrlPeriod::Duration { start, end } => {
+                    let _ = writeln!(out, "{0}    <xbrli:startDate>{1}</xbrli:startDate>\n{0}    <xbrli:endDate>{2}</xbrli:endDate>", indent, start, end);
+                }
+            }
+            let _ = writeln!(out, "{0}  </xbrli:period>\n{0}</xbrli:context>", indent);
+        }
+        let measures = |measures: &[String], indent: &str| -> String {
+            measures.iter().map(|m| format!("{}<xbrli:measure>{}</xbrli:measure>\n", indent, xml_text(m))).collect()
+        };
+        for (i, unit) in self.units.iter().enumerate() {
+            let _ = writeln!(out, "{}<xbrli:unit id=\"unit{}\">", indent, i + 1);
+            if unit.denominators.is_empty() {
+                out.push_str(&measures(&unit.numerators, &format!("{}  ", indent)));
+            } else {
+                let inner = format!("{}      ", indent);
+                let _ = write!(
+                    out,
+                    "{0}  <xbrli:divide>\n{0}    <xbrli:unitNumerator>\n{1}{0}    </xbrli:unitNumerator>\n\
+                     {0}    <xbrli:unitDenominator>\n{2}{0}    </xbrli:unitDenominator>\n{0}  </xbrli:divide>\n",
+                    indent,
+                    measures(&unit.numerators, &inner),
+                    measures(&unit.denominators, &inner)
+                );
+            }
+            let _ = writeln!(out, "{}</xbrli:unit>", indent);
+        }
+    }
+}
+
+/// The document's taxonomy and facts
+fn taxonomy(document: &Document) -> Result<(&XbrlInfo, Vec<Fact>), ConvertError> {
+    let info = document
+        .manifest
+        .xbrl
+        .as_ref()
+        .ok_or_else(|| ConvertError::Xbrl("the document declares no XBRL taxonomy (manifest `xbrl`)".to_string()))?;
+    let facts = xbrl::facts(document).map_err(ConvertError::Xbrl)?;
+    if facts.is_empty() {
+        return Err(ConvertError::Xbrl("the document has no XBRL-tagged cells or paragraphs".to_string()));
+    }
+    Ok((info, facts))
+}
+
+/// Namespace declarations of the XBRL prefixes and the taxonomy's own
+fn namespaces(info: &XbrlInfo, extra: &[(&str, &str)]) -> String {
+    let mut declared: Vec<(&str, &str)> = extra.to_vec();
+    declared.extend([("link", LINK_NS), ("xlink", XLINK_NS)]);
+    declared.extend(BUILTIN_PREFIXES);
+    for (prefix, uri) in &info.namespaces {
+        if !declared.iter().any(|(p, _)| p == prefix) {
+            declared.push((prefix, uri));
+        }
+    }
+    declared.iter().map(|(prefix, uri)| format!(" xmlns:{}=\"{}\"", prefix, xml_text(uri))).collect()
+}
+
+fn provenance(document: &Document) -> String {
+    format!(
+        "<!-- Facts of TDF document {}, root hash {} -->\n",
+        document.manifest.document.id.replace("--", "- -"),
+        document.manifest.integrity.root_hash.replace("--", "- -")
+    )
+}
+
+/// Render the document's facts as an XBRL instance
+///
+/// Facts reported more than once with the same value are written once.
+///
+/// # Errors
+/// `ConvertError::Xbrl` when the document has no taxonomy, no tagged facts
+/// or a tag that does not resolve.
+pub fn render_instance(document: &Document) -> Result<String, ConvertError> {
+    let (info, facts) = taxonomy(document)?;
+    let resources = Resources::new(&facts);
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&provenance(document));
+    let _ = writeln!(out, "<xbrli:xbrl{}>", namespaces(info, &[]));
+    for schema in &info.schema_refs {
+        let _ = writeln!(out, "  <link:schemaRef xlink:type=\"simple\" xlink:href=\"{}\"/>", xml_text(schema));
+    }
+    resources.write(&mut out, "  ");
+    let mut written: Vec<&Fact> = Vec::new();
+    for fact in &facts {
+        if written.iter().any(|w| w.key() == fact.key() && w.same_value(fact)) {
+            continue;
+        }
+        written.push(fact);
+        let value = match &fact.value {
+            FactValue::Numeric { value, .. } => value.to_string(),
+            FactValue::Text(text) => xml_text(text),
+        };
+        let _ = writeln!(out, "  <{0}{1}>{2}</{0}>", fact.concept, resources.refs(fact), value);
+    }
+    out.push_str("</xbrli:xbrl>\n");
+    Ok(out)
+}
+
+/// Render the document as inline XBRL (XHTML)
+///
+/// # Errors
+/// As `render_instance`.
+pub fn render_inline(document: &Document) -> Result<String, ConvertError> {
+    let (info, facts) = taxonomy(document)?;
+    let resources = Resources::new(&facts);
+    let mut page = InlinePage { info, resources: &resources, body: String::new(), hidden: String::new() };
+    for section in &document.content.sections {
+        page.section(section);
+    }
+
+    let meta = &document.manifest.document;
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&provenance(document));
+    let _ = writeln!(
+        out,
+        "<html xmlns=\"{}\"{} xml:lang=\"{}\">",
+        XHTML_NS,
+        namespaces(info, &[("ix", IX_NS), ("ixt", IXT_NS)]),
+        xml_text(&meta.language)
+    );
+    let _ = writeln!(
+        out,
+        "<head>\n<meta http-equiv=\"Content-Type\" content=\"text/html; charset=UTF-8\"/>\n<title>{}</title>\n\
+         <meta name=\"tdf-document-id\" content=\"{}\"/>\n<meta name=\"tdf-root-hash\" content=\"{}\"/>\n\
+         <style type=\"text/css\">\n{}</style>\n</head>\n<body>",
+        xml_text(&meta.title),
+        xml_text(&meta.id),
+        xml_text(&document.manifest.integrity.root_hash),
+        INLINE_STYLES
+    );
+    out.push_str("<div style=\"display:none\">\n<ix:header>\n");
+    if !page.hidden.is_empty() {
+        let _ = write!(out, "<ix:hidden>\n{}</ix:hidden>\n", page.hidden);
+    }
+    out.push_str("<ix:references>\n");
+    for schema in &info.schema_refs {
+        let _ = writeln!(out, "<link:schemaRef xlink:type=\"simple\" xlink:href=\"{}\"/>", xml_text(schema));
+    }
+    out.push_str("</ix:references>\n<ix:resources>\n");
+    resources.write(&mut out, "");
+    out.push_str("</ix:resources>\n</ix:header>\n</div>\n");
+    let _ = writeln!(out, "<h1>{}</h1>", xml_text(&meta.title));
+    out.push_str(&page.body);
+    out.push_str("</body>\n</html>\n");
+    Ok(out)
+}
+
+/// Write the document's facts as an XBRL instance, returning how many
+/// tagged values were read
+pub fn write_instance(document: &Document, path: &Path) -> Result<usize, ConvertError> {
+    std::fs::write(path, render_instance(document)?)?;
+    Ok(xbrl::facts(document).map_err(ConvertError::Xbrl)?.len())
+}
+
+/// Write the document as inline XBRL, returning how many tagged values it
+/// marks up
+pub fn write_inline(document: &Document, path: &Path) -> Result<usize, ConvertError> {
+    std::fs::write(path, render_inline(document)?)?;
+    Ok(xbrl::facts(document).map_err(ConvertError::Xbrl)?.len())
+}
+
+/// How an amount's display text is marked up in place
+#[derive(Debug, PartialEq)]
+struct Shown<'d> {
+    before: &'d str,
+    number: &'d str,
+    after: &'d str,
+    format: &'static str,
+    scale: i32,
+}
+
+/// Split a display text around the number it shows, if that number is the
+/// amount `value` scaled by a power of ten
+fn shown<'d>(display: &'d str, value: &Decimal) -> Option<Shown<'d>> {
+    let Some(first) = display.find(|c: char| c.is_ascii_digit()) else {
+        let dash = display.trim();
+        return (value.is_zero() && matches!(dash, "-" | "\u{2013}" | "\u{2014}")).then(|| Shown {
+            before: &display[..display.find(dash).unwrap_or_default()],
+            number: dash,
+            after: "",
+            format: "ixt:fixed-zero",
+            scale: 0,
+        });
+    };
+    let last = display.rfind(|c: char| c.is_ascii_digit()).unwrap_or(first);
+    let (before, number, after) = (&display[..first], &display[first..=last], &display[last + 1..]);
+    let negative = before.contains(['-', '\u{2212}', '(']);
+    if negative != value.is_negative() && !value.is_zero() {
+        return None;
+    }
+    let spaces = [' ', '\u{a0}', '\u{202f}'];
+    let candidates = [
+        ("ixt:num-dot-decimal", number.replace(',', "").replace(spaces, "")),
+        ("ixt:num-comma-decimal", number.replace('.', "").replace(spaces, "").replace(',', ".")),
+    ];
+    let candidates: Vec<(&'static str, Decimal)> = candidates.into_iter().filter_map(|(format, digits)| Some((format, digits.parse().ok()?))).collect();
+    SCALES.into_iter().find_map(|scale| {
+        let (format, _) = candidates.iter().find(|(_, shown)| shown.shift(scale).is_some_and(|v| v == value.abs()))?;
+        Some(Shown { before, number, after, format, scale })
+    })
+}
+
+/// XHTML body of an inline XBRL page, and the facts it hides
+struct InlinePage<'a> {
+    info: &'a XbrlInfo,
+    resources: &'a Resources,
+    body: String,
+    hidden: String,
+}
+
+impl InlinePage<'_> {
+    fn section(&mut self, section: &Section) {
+        self.body.push_str("<div class=\"section\">\n");
+        if let Some(title) = &section.title {
+            let _ = writeln!(self.body, "<h2>{}</h2>", xml_text(title));
+        }
+        if section.encrypted.is_some() {
+            self.body.push_str("<p class=\"omitted\">Encrypted section</p>\n");
+        }
+        for block in &section.content {
+            self.block(block);
+        }
+        self.body.push_str("</div>\n");
+    }
+
+    fn block(&mut self, block: &ContentBlock) {
+        let id = |id: Option<&str>| id.map(|id| format!(" id=\"{}\"", xml_id(id))).unwrap_or_default();
+        let omitted = |kind: &str, name: Option<&str>| match name {
+            Some(name) => format!("<p class=\"omitted\">[{}: {}]</p>\n", kind, xml_text(name)),
+            None => format!("<p class=\"omitted\">[{}]</p>\n", kind),
+        };
+        let html = match block {
+            ContentBlock::Heading { level, text, id: block_id } => {
+                let level = (*level).clamp(2, 6);
+                format!("<h{0}{1}>{2}</h{0}>\n", level, id(block_id.as_deref()), inline(text))
+            }
+            ContentBlock::Paragraph { text, id: block_id, xbrl: Some(tag) } => match xbrl::paragraph_fact(Some(self.info), tag, text) {
+                Ok(fact) => format!("<p{}>{}</p>\n", id(block_id.as_deref()), self.text_fact(&fact, &xml_text(&text.plain_text()))),
+                Err(_) => format!("<p{}>{}</p>\n", id(block_id.as_deref()), inline(text)),
+            },
+            ContentBlock::Paragraph { text, id: block_id, .. } => format!("<p{}>{}</p>\n", id(block_id.as_deref()), inline(text)),
+            ContentBlock::List { ordered, items, id: block_id } => list(*ordered, items, &id(block_id.as_deref())),
+            ContentBlock::Quote { text, attribution, id: block_id } => {
+                let attribution = attribution.as_deref().map(|a| format!("<p>\u{2014} {}</p>", xml_text(a))).unwrap_or_default();
+                format!("<blockquote{}><p>{}</p>{}</blockquote>\n", id(block_id.as_deref()), inline(text), attribution)
+            }
+            ContentBlock::Code { text, id: block_id, .. } => format!("<pre{}><code>{}</code></pre>\n", id(block_id.as_deref()), xml_text(text)),
+            ContentBlock::PageBreak | ContentBlock::HorizontalRule => "<hr/>\n".to_string(),
+            ContentBlock::Table { id: table_id, caption, columns, header_rows, rows, footer } => {
+                self.table(table_id, caption.as_deref(), columns, header_rows.as_deref().unwrap_or_default(), rows, footer.as_deref());
+                return;
+            }
+            ContentBlock::Diagram { title, .. } => omitted("Diagram", title.as_deref()),
+            ContentBlock::Chart { title, table, .. } => omitted("Chart", Some(title.as_deref().unwrap_or(table))),
+            ContentBlock::Figure { alt, caption, .. } => omitted("Figure", Some(caption.as_deref().unwrap_or(alt))),
+            ContentBlock::Footnote { id: note_id, text } => {
+                format!("<p class=\"footnote\" id=\"{}\"><sup>{}</sup> {}</p>\n", xml_id(note_id), xml_text(note_id), xml_text(text))
+            }
+            ContentBlock::Redacted { .. } => omitted("Redacted", None),
+            ContentBlock::TextField { label, .. }
+            | ContentBlock::NumberField { label, .. }
+            | ContentBlock::DateField { label, .. }
+            | ContentBlock::Checkbox { label, .. }
+            | ContentBlock::Choice { label, .. }
+            | ContentBlock::SignatureField { label, .. } => omitted("Field", Some(label)),
+        };
+        self.body.push_str(&html);
+    }
+
+    fn table(
+        &mut self,
+        id: &str,
+        caption: Option<&str>,
+        columns: &[TableColumn],
+        header_rows: &[Vec<HeaderCell>],
+        rows: &[TableRow],
+        footer: Option<&[String]>,
+    ) {
+        let spans = |colspan: usize, rowspan: usize| {
+            let mut attributes = String::new();
+            if colspan > 1 {
+                let _ = write!(attributes, " colspan=\"{}\"", colspan);
+            }
+            if rowspan > 1 {
+                let _ = write!(attributes, " rowspan=\"{}\"", rowspan);
+            }
+            attributes
+        };
+        let _ = writeln!(self.body, "<table id=\"{}\">", xml_id(id));
+        if let Some(caption) = caption {
+            let _ = writeln!(self.body, "<caption>{}</caption>", xml_text(caption));
+        }
+        self.body.push_str("<thead>\n");
+        for slots in header_layout(columns, header_rows) {
+            self.body.push_str("<tr>");
+            for slot in slots {
+                let _ = write!(self.body, "<th{}>{}</th>", spans(slot.colspan, slot.rowspan), xml_text(slot.text));
+            }
+            self.body.push_str("</tr>\n");
+        }
+        self.body.push_str("</thead>\n<tbody>\n");
+        let covered = covered_cells(columns, rows);
+        for (r, row) in rows.iter().enumerate() {
+            self.body.push_str(match row.format.role {
+                RowRole::Data => "<tr>",
+                RowRole::Subtotal => "<tr class=\"subtotal\">",
+                RowRole::Total => "<tr class=\"total\">",
+            });
+            for (c, column) in columns.iter().enumerate() {
+                if covered.contains_key(&(r, c)) {
+                    continue;
+                }
+                let span = row.span(&column.id);
+                let cell = row.cells.get(&column.id);
+                let class = match cell {
+                    Some(CellValue::Number { .. } | CellValue::Currency { .. } | CellValue::Percentage { .. } | CellValue::Formula { .. }) => {
+                        " class=\"numeric\""
+                    }
+                    _ => "",
+                };
+                let fact = row.format.tags.get(&column.id).and_then(|tag| xbrl::cell_fact(Some(self.info), tag, column, cell).ok().flatten());
+                let content = match (cell, fact) {
+                    (Some(CellValue::Redacted { .. }), _) => "Redacted".to_string(),
+                    (Some(cell), Some(fact)) => self.cell_fact(&fact, cell),
+                    (Some(cell), None) => xml_text(cell.display_text()),
+                    (None, _) => String::new(),
+                };
+                let _ = write!(self.body, "<td{}{}>{}</td>", class, spans(span.colspan, span.rowspan), content);
+            }
+            self.body.push_str("</tr>\n");
+        }
+        self.body.push_str("</tbody>\n");
+        if let Some(footer) = footer {
+            self.body.push_str("<tfoot><tr>");
+            for cell in footer {
+                let _ = write!(self.body, "<td>{}</td>", xml_text(cell));
+            }
+            self.body.push_str("</tr></tfoot>\n");
+        }
+        self.body.push_str("</table>\n");
+    }
+
+    /// Markup of a tagged cell
+    fn cell_fact(&mut self, fact: &Fact, cell: &CellValue) -> String {
+        let display = cell.display_text();
+        match (&fact.value, cell) {
+            (FactValue::Numeric { value, .. }, _) => self.amount(fact, value, display),
+            (FactValue::Text(_), CellValue::Text(_)) => self.text_fact(fact, &xml_text(display)),
+            // Dates are shown as written and reported as ISO dates
+            (FactValue::Text(value), _) => {
+                self.hide_text(fact, value);
+                xml_text(display)
+            }
+        }
+    }
+
+    fn amount(&mut self, fact: &Fact, value: &Decimal, display: &str) -> String {
+        let refs = self.resources.refs(fact);
+        let sign = if value.is_negative() { " sign=\"-\"" } else { "" };
+        match shown(display, value) {
+            Some(shown) => format!(
+                "{}<ix:nonFraction name=\"{}\"{} format=\"{}\" scale=\"{}\"{}>{}</ix:nonFraction>{}",
+                xml_text(shown.before),
+                fact.concept,
+                refs,
+                shown.format,
+                shown.scale,
+                sign,
+                xml_text(shown.number),
+                xml_text(shown.after)
+            ),
+            None => {
+                let _ = writeln!(self.hidden, "<ix:nonFraction name=\"{}\"{}{}>{}</ix:nonFraction>", fact.concept, refs, sign, value.abs());
+                xml_text(display)
+            }
+        }
+    }
+
+    fn text_fact(&self, fact: &Fact, content: &str) -> String {
+        format!("<ix:nonNumeric name=\"{}\"{}>{}</ix:nonNumeric>", fact.concept, self.resources.refs(fact), content)
+    }
+
+    fn hide_text(&mut self, fact: &Fact, value: &str) {
+        let _ = writeln!(self.hidden, "{}", self.text_fact(fact, &xml_text(value)));
+    }
+}
+
+/// An id usable as an XML ID: characters other than letters, digits, `-`,
+/// `_` and `.` become `-`, and a leading non-letter gets a `_` before it
+fn xml_id(id: &str) -> String {
+    let id: String = id.chars().map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '-' }).collect();
+    if id.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        id
+    } else {
+        format!("_{}", id)
+    }
+}
+
+fn inline(text: &InlineText) -> String {
+    let mut out = String::new();
+    for run in text.runs() {
+        match run {
+            InlineRun::Text { text, marks, link } => {
+                let mut html = xml_text(&text);
+                for mark in &marks {
+                    let tag = match mark {
+                        Mark::Bold => "strong",
+                        Mark::Italic => "em",
+                        Mark::Underline => "u",
+                        Mark::Strike => "del",
+                        Mark::Code => "code",
+                        Mark::Superscript => "sup",
+                        Mark::Subscript => "sub",
+                    };
+                    html = format!("<{0}>{1}</{0}>", tag, html);
+                }
+                if let Some(link) = link {
+                    html = format!("<a href=\"{}\">{}</a>", xml_text(&link), html);
+                }
+                out.push_str(&html);
+            }
+            InlineRun::FootnoteRef { id } => {
+                let _ = write!(out, "<sup><a href=\"#{}\">{}</a></sup>", xml_id(&id), xml_text(&id));
+            }
+            InlineRun::CrossRef { target, text } => {
+                let _ = write!(out, "<a href=\"#{}\">{}</a>", xml_id(&target), xml_text(&text));
+            }
+        }
+    }
+    out
+}
+
+fn list(ordered: bool, items: &[ListItem], id: &str) -> String {
+    let tag = if ordered { "ol" } else { "ul" };
+    let mut out = format!("<{}{}>", tag, id);
+    for item in items {
+        let checkbox = match item.checked {
+            Some(true) => "\u{2612} ",
+            Some(false) => "\u{2610} ",
+            None => "",
+        };
+        let _ = write!(out, "<li>{}{}", checkbox, inline(&item.text));
+        if let Some(sublist) = &item.sublist {
+            out.push_str(&list(sublist.ordered, &sublist.items, ""));
+        }
+        out.push_str("</li>");
+    }
+    let _ = writeln!(out, "</{}>", tag);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_shown_amounts() {
+        let euros = shown("€542,000", &decimal("542000")).unwrap();
+        assert_eq!((euros.before, euros.number, euros.after, euros.format, euros.scale), ("€", "542,000", "", "ixt:num-dot-decimal", 0));
+        let thousands = shown("542K", &decimal("542000")).unwrap();
+        assert_eq!((thousands.number, thousands.after, thousands.scale), ("542", "K", 3));
+        assert_eq!(shown("+14.9%", &decimal("0.149")).unwrap().scale, -2);
+        let negative = shown("(1.200,50)", &decimal("-1200.5")).unwrap();
+        assert_eq!((negative.before, negative.format), ("(", "ixt:num-comma-decimal"));
+        assert_eq!(shown("\u{2014}", &Decimal::ZERO).unwrap().format, "ixt:fixed-zero");
+        // A sign that disagrees, or a number that is not the amount, is hidden
+        assert!(shown("1,200", &decimal("-1200")).is_none());
+        assert!(shown("Q1 542", &decimal("542")).is_none());
+    }
+
+    #[test]
+    fn test_xml_ids() {
+        assert_eq!(xml_id("fn:1"), "fn-1");
+        assert_eq!(xml_id("2025-results"), "_2025-results");
+    }
+}
@@ -1,7 +1,11 @@
 //! TDF Format Conversion Library
 //! 
 //! Provides conversion from various document formats to TDF (TrustDoc Financial) format.
-//! Supports: CSV, XLSX, DOCX, PPTX, TXT, MD, PDF
+//! Supports: CSV, XLSX, DOCX, PPTX, TXT, MD, PDF, XBRL
+//!
+//! The `tables` module exports document tables back out as typed CSV, XLSX
+//! or Parquet; the `export` module writes whole documents as Markdown or
+//! DOCX that the importers read back, or their tagged facts as XBRL.
 //!
 //! # Example
 //! ```no_run
@@ -18,10 +22,14 @@ pub mod csv;
 pub mod docx;
 pub mod error;
 pub mod excel;
+pub mod export;
 pub mod markdown;
 pub mod pdf;
 pub mod pptx;
+pub mod tables;
 pub mod text;
+pub mod xbrl;
+mod xml;
 
 use std::path::Path;
 
@@ -49,13 +57,14 @@ pub fn convert_file(
         "txt" => text::convert_text_to_tdf(input, output, signer_id, signer_name, signing_key),
         "md" | "markdown" => markdown::convert_markdown_to_tdf(input, output, signer_id, signer_name, signing_key),
         "pdf" => pdf::convert_pdf_to_tdf(input, output, signer_id, signer_name, signing_key),
+        "xbrl" => xbrl::convert_xbrl_to_tdf(input, output, signer_id, signer_name, signing_key),
         _ => Err(ConvertError::UnsupportedFormat(extension)),
     }
 }
 
 /// Get list of supported file extensions
 pub fn supported_formats() -> Vec<&'static str> {
-    vec!["csv", "xlsx", "xls", "docx", "doc", "pptx", "ppt", "txt", "md", "markdown", "pdf"]
+    vec!["csv", "xlsx", "xls", "docx", "doc", "pptx", "ppt", "txt", "md", "markdown", "pdf", "xbrl"]
 }
 
 /// Check if a file format is supported
@@ -1,10 +1,28 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use tdf_core::archive::ArchiveBuilder;
-use tdf_core::content::{ContentBlock, DocumentContent, Section};
+use tdf_core::content::{ContentBlock, DocumentContent, InlineRun, InlineText, ListItem, Mark, NestedList, Section};
 use tdf_core::document::Document;
+use crate::csv::text_table;
 use crate::error::ConvertError;
-use pulldown_cmark::{Parser, Event, Tag, HeadingLevel};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+
+/// Comment that starts a section: `<!-- tdf:section id="..." -->`
+///
+/// The heading right after it is the section title, unless the comment
+/// says `untitled`. Written by the Markdown export so sections survive a
+/// round trip; files without it are read as a single section.
+pub const SECTION_MARKER: &str = "tdf:section";
+
+/// Prefix of the paragraph after a table that holds its caption
+pub const TABLE_CAPTION_PREFIX: &str = "Table: ";
+
+/// Comment standing for a page break: `<!-- tdf:page-break -->`
+pub const PAGE_BREAK_MARKER: &str = "tdf:page-break";
+
+/// Prefix of the last paragraph of a block quote that names its source
+pub const ATTRIBUTION_PREFIX: &str = "— ";
 
 pub fn convert_markdown_to_tdf(
     input: &Path,
@@ -14,28 +32,375 @@ pub fn convert_markdown_to_tdf(
     _signing_key: Option<&[u8]>,
 ) -> Result<(), ConvertError> {
     let content_text = fs::read_to_string(input)?;
-    let parser = Parser::new(&content_text);
-    
-    let mut content_blocks = Vec::new();
-    let mut current_heading_level: Option<HeadingLevel> = None;
-    let mut current_text = String::new();
-    let mut in_code_block = false;
-    
-    for event in parser {
+    let fallback_title = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Markdown Import");
+    let (document, assets) = markdown_to_document(&content_text, input.parent(), fallback_title)?;
+
+    // Build archive
+    let mut builder = ArchiveBuilder::new(document);
+    for (path, data) in assets {
+        builder.add_asset(path, data)?;
+    }
+    builder.build(
+        output,
+        None,
+        signer_id,
+        signer_name,
+    )?;
+
+    Ok(())
+}
+
+/// Parse Markdown into a document
+///
+/// Headings, paragraphs, nested and task lists, block quotes, code blocks,
+/// thematic breaks, pipe tables, footnotes and images standing alone in a
+/// paragraph (figures) become the matching blocks. Emphasis,
+/// code spans, links and `<u>`, `<sup>` and `<sub>` tags become inline
+/// runs; links to `#id` are cross-references. Table column types are
+/// inferred from the cell text.
+///
+/// # Arguments
+/// * `text` - Markdown source
+/// * `base_dir` - Directory image paths are resolved against; images are
+///   not loaded when `None`
+/// * `fallback_title` - Title used when the text has no heading
+///
+/// # Returns
+/// The document and the images it references, keyed by asset path
+pub fn markdown_to_document(
+    text: &str,
+    base_dir: Option<&Path>,
+    fallback_title: &str,
+) -> Result<(Document, HashMap<String, Vec<u8>>), ConvertError> {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut importer = MarkdownImporter::default();
+    for event in Parser::new_ext(text, options) {
+        importer.event(event);
+    }
+    let mut sections = importer.finish();
+
+    // Extract title from first heading or filename; a leading level-1
+    // heading alone before the first section is only the title
+    let first_heading = sections.iter().flat_map(|s| s.content.iter()).find_map(|block| match block {
+        ContentBlock::Heading { text, .. } => Some(text.plain_text().into_owned()),
+        _ => None,
+    });
+    let title = first_heading.unwrap_or_else(|| fallback_title.to_string());
+    if sections.len() > 1
+        && sections[0].id == "content"
+        && matches!(&sections[0].content[..], [ContentBlock::Heading { level: 1, .. }])
+    {
+        sections.remove(0);
+    }
+
+    let mut assets = HashMap::new();
+    for block in sections.iter_mut().flat_map(|s| s.content.iter_mut()) {
+        if let ContentBlock::Figure { asset, .. } = block {
+            if let Some(key) = asset_key(asset) {
+                if let Some(data) = base_dir.and_then(|dir| fs::read(dir.join(asset.as_str())).ok()) {
+                    assets.insert(key.clone(), data);
+                }
+                *asset = key;
+            }
+        }
+    }
+
+    let content = DocumentContent { sections };
+    let styles = get_default_styles();
+    let mut document = Document::new(title, "en".to_string(), content, styles);
+    document.manifest.integrity.algorithm = tdf_core::document::HashAlgorithm::Sha256;
+    Ok((document, assets))
+}
+
+/// Archive path for a local image reference; `None` for URLs and paths
+/// leaving the Markdown file's directory
+fn asset_key(url: &str) -> Option<String> {
+    if url.contains("://") || url.contains("..") || url.starts_with('/') || url.starts_with('\\') || url.is_empty() {
+        return None;
+    }
+    if url.starts_with("assets/") {
+        Some(url.to_string())
+    } else {
+        let name = url.rsplit(['/', '\\']).next().unwrap_or(url);
+        Some(format!("assets/images/{}", name))
+    }
+}
+
+/// The mark an inline HTML tag opens or closes (`true` when closing)
+fn html_mark(tag: &str) -> Option<(Mark, bool)> {
+    let tag = tag.strip_prefix('<')?.strip_suffix('>')?.to_ascii_lowercase();
+    let (name, closing) = match tag.strip_prefix('/') {
+        Some(name) => (name.to_string(), true),
+        None => (tag, false),
+    };
+    let mark = match name.as_str() {
+        "b" | "strong" => Mark::Bold,
+        "i" | "em" => Mark::Italic,
+        "u" | "ins" => Mark::Underline,
+        "s" | "del" | "strike" => Mark::Strike,
+        "code" => Mark::Code,
+        "sup" => Mark::Superscript,
+        "sub" => Mark::Subscript,
+        _ => return None,
+    };
+    Some((mark, closing))
+}
+
+/// Inline text of `runs` without leading and trailing whitespace
+fn trimmed(runs: Vec<InlineRun>) -> InlineText {
+    let mut runs = InlineText::from_runs(runs).runs();
+    while matches!(runs.first(), Some(InlineRun::Text { text, .. }) if text.trim().is_empty()) {
+        runs.remove(0);
+    }
+    while matches!(runs.last(), Some(InlineRun::Text { text, .. }) if text.trim().is_empty()) {
+        runs.pop();
+    }
+    if let Some(InlineRun::Text { text, .. }) = runs.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some(InlineRun::Text { text, .. }) = runs.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    InlineText::from_runs(runs)
+}
+
+/// Value of `name="..."` in a marker comment
+fn marker_attribute(comment: &str, name: &str) -> Option<String> {
+    let start = comment.find(&format!("{}=\"", name))? + name.len() + 2;
+    let len = comment[start..].find('"')?;
+    Some(comment[start..start + len].to_string())
+}
+
+/// A list being read; the last item is the one open, if any
+struct ListState {
+    ordered: bool,
+    items: Vec<ListItem>,
+}
+
+/// A pipe table being read
+#[derive(Default)]
+struct TableState {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+    /// Whether each row's non-empty cells are all bold (a footer candidate)
+    bold_rows: Vec<bool>,
+    row: Vec<String>,
+    row_bold: bool,
+    cell_bold: bool,
+}
+
+/// Event-driven reader turning Markdown events into sections and blocks
+struct MarkdownImporter {
+    sections: Vec<Section>,
+    section_id: String,
+    section_title: Option<String>,
+    /// Whether the current section came from a marker (kept even if empty)
+    marked: bool,
+    /// The next heading is the title of a section just started
+    expect_title: bool,
+    blocks: Vec<ContentBlock>,
+    /// Inline text of the current paragraph, heading, item or cell
+    text: String,
+    /// `text` as runs with their marks and links
+    runs: Vec<InlineRun>,
+    /// Marks open at the current position
+    marks: Vec<Mark>,
+    /// Links open at the current position, with the run they start at
+    links: Vec<(String, usize)>,
+    /// Runs of earlier paragraphs of the current list item
+    item_runs: Vec<InlineRun>,
+    lists: Vec<ListState>,
+    footnote: Option<(String, String)>,
+    table: Option<TableState>,
+    /// Images in the current paragraph: url, title and where their alt
+    /// text starts and ends in `text`
+    images: Vec<(String, String, usize, usize)>,
+    strong: usize,
+    /// Language and text of the fenced or indented code block being read
+    code: Option<(Option<String>, String)>,
+    /// Nesting depth of block quotes and the runs of their paragraphs
+    quote_depth: usize,
+    quote_paragraphs: Vec<Vec<InlineRun>>,
+}
+
+impl Default for MarkdownImporter {
+    fn default() -> Self {
+        MarkdownImporter {
+            sections: Vec::new(),
+            section_id: "content".to_string(),
+            section_title: Some("Content".to_string()),
+            marked: false,
+            expect_title: false,
+            blocks: Vec::new(),
+            text: String::new(),
+            runs: Vec::new(),
+            marks: Vec::new(),
+            links: Vec::new(),
+            item_runs: Vec::new(),
+            lists: Vec::new(),
+            footnote: None,
+            table: None,
+            images: Vec::new(),
+            strong: 0,
+            code: None,
+            quote_depth: 0,
+            quote_paragraphs: Vec::new(),
+        }
+    }
+}
+
+impl MarkdownImporter {
+    fn event(&mut self, event: Event) {
         match event {
-            Event::Start(Tag::Heading(level, _, _)) => {
-                // Flush current paragraph if any
-                if !current_text.trim().is_empty() {
-                    content_blocks.push(ContentBlock::Paragraph {
-                        text: current_text.trim().to_string(),
-                        id: Some(format!("para-{}", content_blocks.len())),
-                    });
-                    current_text.clear();
-                }
-                current_heading_level = Some(level);
-            }
-            Event::End(Tag::Heading(level, _, _)) => {
-                if !current_text.trim().is_empty() {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(ref text) | Event::Code(ref text) => {
+                if let Some((_, code)) = &mut self.code {
+                    code.push_str(text);
+                    return;
+                }
+                if let Some(table) = &mut self.table {
+                    if self.strong == 0 && !text.trim().is_empty() {
+                        table.cell_bold = false;
+                    }
+                }
+                self.push_text(text, matches!(event, Event::Code(_)));
+            }
+            Event::FootnoteReference(label) => {
+                self.text.push_str(&format!("{{{{{}}}}}", label));
+                self.runs.push(InlineRun::FootnoteRef { id: label.to_string() });
+            }
+            Event::TaskListMarker(checked) => {
+                if let Some(item) = self.lists.last_mut().and_then(|list| list.items.last_mut()) {
+                    item.checked = Some(checked);
+                }
+            }
+            Event::Rule => self.blocks.push(ContentBlock::HorizontalRule),
+            Event::SoftBreak => self.push_text(" ", false),
+            Event::HardBreak => self.push_text("\n", false),
+            Event::Html(html) => {
+                let html = html.trim();
+                if html.starts_with("<!--") && html.contains(SECTION_MARKER) {
+                    self.finish_section();
+                    self.section_id = marker_attribute(html, "id").unwrap_or_else(|| format!("section-{}", self.sections.len()));
+                    self.section_title = None;
+                    self.marked = true;
+                    self.expect_title = !html.contains("untitled");
+                } else if html.starts_with("<!--") && html.contains(PAGE_BREAK_MARKER) {
+                    self.blocks.push(ContentBlock::PageBreak);
+                } else if matches!(html, "<br>" | "<br/>" | "<br />") {
+                    self.push_text("\n", false);
+                } else if let Some((mark, closing)) = html_mark(html) {
+                    if closing {
+                        self.close_mark(mark);
+                    } else {
+                        self.marks.push(mark);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Append text to the current paragraph, heading, item or cell
+    fn push_text(&mut self, text: &str, code: bool) {
+        self.text.push_str(text);
+        let mut marks = self.marks.clone();
+        if code {
+            marks.push(Mark::Code);
+        }
+        marks.sort();
+        marks.dedup();
+        let link = self.links.last().map(|(url, _)| url.clone());
+        self.runs.push(InlineRun::Text { text: text.to_string(), marks, link });
+    }
+
+    fn close_mark(&mut self, mark: Mark) {
+        if let Some(i) = self.marks.iter().rposition(|m| *m == mark) {
+            self.marks.remove(i);
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        if !matches!(tag, Tag::Heading(..)) && !self.is_inline(&tag) {
+            self.expect_title = false;
+        }
+        match tag {
+            Tag::Heading(..) | Tag::Paragraph => {
+                self.text.clear();
+                self.runs.clear();
+                self.images.clear();
+            }
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().map(str::to_string),
+                    CodeBlockKind::Indented => None,
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::BlockQuote => self.quote_depth += 1,
+            Tag::List(start) => {
+                // Text of an item holding a nested list ends where the list starts
+                self.flush_item();
+                self.lists.push(ListState { ordered: start.is_some(), items: Vec::new() });
+            }
+            Tag::Item => {
+                self.text.clear();
+                self.runs.clear();
+                self.item_runs.clear();
+                if let Some(list) = self.lists.last_mut() {
+                    list.items.push(ListItem::default());
+                }
+            }
+            Tag::FootnoteDefinition(label) => self.footnote = Some((label.to_string(), String::new())),
+            Tag::Table(_) => self.table = Some(TableState::default()),
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = &mut self.table {
+                    table.row.clear();
+                    table.row_bold = true;
+                }
+            }
+            Tag::TableCell => {
+                self.text.clear();
+                self.runs.clear();
+                if let Some(table) = &mut self.table {
+                    table.cell_bold = true;
+                }
+            }
+            Tag::Strong => {
+                self.strong += 1;
+                self.marks.push(Mark::Bold);
+            }
+            Tag::Emphasis => self.marks.push(Mark::Italic),
+            Tag::Strikethrough => self.marks.push(Mark::Strike),
+            Tag::Link(_, url, _) => self.links.push((url.to_string(), self.runs.len())),
+            Tag::Image(_, url, title) => {
+                let at = self.text.len();
+                self.images.push((url.to_string(), title.to_string(), at, at));
+            }
+        }
+    }
+
+    fn is_inline(&self, tag: &Tag) -> bool {
+        matches!(
+            tag,
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link(..) | Tag::Image(..) | Tag::TableCell | Tag::TableRow | Tag::TableHead
+        )
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading(level, _, _) => {
+                let text = self.text.trim().to_string();
+                self.text.clear();
+                let runs = std::mem::take(&mut self.runs);
+                if self.expect_title {
+                    self.section_title = Some(text);
+                    self.expect_title = false;
+                } else if !text.is_empty() {
                     let level_num = match level {
                         HeadingLevel::H1 => 1,
                         HeadingLevel::H2 => 2,
@@ -44,89 +409,240 @@ pub fn convert_markdown_to_tdf(
                         HeadingLevel::H5 => 5,
                         HeadingLevel::H6 => 6,
                     };
-                    content_blocks.push(ContentBlock::Heading {
-                        level: level_num,
-                        text: current_text.trim().to_string(),
-                        id: Some(format!("heading-{}", content_blocks.len())),
-                    });
-                    current_text.clear();
+                    let id = Some(format!("heading-{}", self.block_count()));
+                    self.blocks.push(ContentBlock::Heading { level: level_num, text: trimmed(runs), id });
+                }
+            }
+            Tag::Paragraph => self.end_paragraph(),
+            Tag::CodeBlock(_) => self.end_code_block(),
+            Tag::BlockQuote => {
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                if self.quote_depth == 0 {
+                    self.end_quote();
+                }
+            }
+            Tag::Item => {
+                self.flush_item();
+                // Items left without text, checkbox or sublist are dropped
+                if let Some(list) = self.lists.last_mut() {
+                    if list.items.last() == Some(&ListItem::default()) {
+                        list.items.pop();
+                    }
+                }
+            }
+            Tag::List(_) => {
+                if let Some(list) = self.lists.pop() {
+                    let parent = self.lists.last_mut().and_then(|parent| parent.items.last_mut());
+                    match (parent, list.items.is_empty()) {
+                        (_, true) => {}
+                        (Some(item), false) => match &mut item.sublist {
+                            Some(sublist) => sublist.items.extend(list.items),
+                            None => item.sublist = Some(NestedList { ordered: list.ordered, items: list.items }),
+                        },
+                        (None, false) => self.blocks.push(ContentBlock::List { ordered: list.ordered, items: list.items, id: None }),
+                    }
                 }
-                current_heading_level = None;
             }
-            Event::Start(Tag::CodeBlock(_)) => {
-                in_code_block = true;
+            Tag::FootnoteDefinition(_) => {
+                if let Some((id, text)) = self.footnote.take() {
+                    let text = format!("{}{}", text, self.text).trim().to_string();
+                    self.text.clear();
+                    self.blocks.push(ContentBlock::Footnote { id, text });
+                }
+            }
+            Tag::TableCell => {
+                let cell = self.text.trim().to_string();
+                self.text.clear();
+                if let Some(table) = &mut self.table {
+                    if !cell.is_empty() && !table.cell_bold {
+                        table.row_bold = false;
+                    }
+                    table.row.push(cell);
+                }
+            }
+            Tag::TableHead => {
+                if let Some(table) = &mut self.table {
+                    table.header = std::mem::take(&mut table.row);
+                }
             }
-            Event::End(Tag::CodeBlock(_)) => {
-                in_code_block = false;
+            Tag::TableRow => {
+                if let Some(table) = &mut self.table {
+                    let row = std::mem::take(&mut table.row);
+                    let bold = table.row_bold && row.iter().any(|c| !c.is_empty());
+                    table.rows.push(row);
+                    table.bold_rows.push(bold);
+                }
             }
-            Event::Text(text) => {
-                if !in_code_block {
-                    current_text.push_str(&text);
+            Tag::Table(_) => {
+                if let Some(mut table) = self.table.take() {
+                    // A bold last row below at least two others is the footer
+                    let footer = if table.rows.len() > 2 && table.bold_rows.last() == Some(&true) { table.rows.pop() } else { None };
+                    let id = format!("table-{}", self.block_count());
+                    self.blocks.push(text_table(id, None, table.header, table.rows, footer));
                 }
             }
-            Event::SoftBreak => {
-                current_text.push(' ');
+            Tag::Strong => {
+                self.strong = self.strong.saturating_sub(1);
+                self.close_mark(Mark::Bold);
             }
-            Event::HardBreak => {
-                current_text.push('\n');
+            Tag::Emphasis => self.close_mark(Mark::Italic),
+            Tag::Strikethrough => self.close_mark(Mark::Strike),
+            Tag::Link(..) => {
+                // A link to an anchor in the document is a cross-reference
+                if let Some((url, start)) = self.links.pop() {
+                    if let Some(target) = url.strip_prefix('#').filter(|t| !t.is_empty()) {
+                        let start = start.min(self.runs.len());
+                        let text = InlineText::from_runs(self.runs.drain(start..)).plain_text().into_owned();
+                        self.runs.push(InlineRun::CrossRef { target: target.to_string(), text });
+                    }
+                }
             }
-            Event::End(Tag::Paragraph) => {
-                if !current_text.trim().is_empty() && current_heading_level.is_none() {
-                    content_blocks.push(ContentBlock::Paragraph {
-                        text: current_text.trim().to_string(),
-                        id: Some(format!("para-{}", content_blocks.len())),
-                    });
-                    current_text.clear();
+            Tag::Image(..) => {
+                if let Some(image) = self.images.last_mut() {
+                    image.3 = self.text.len();
                 }
             }
-            _ => {}
         }
     }
-    
-    // Flush remaining text
-    if !current_text.trim().is_empty() {
-        content_blocks.push(ContentBlock::Paragraph {
-            text: current_text.trim().to_string(),
-            id: Some(format!("para-{}", content_blocks.len())),
-        });
+
+    fn end_paragraph(&mut self) {
+        let runs = std::mem::take(&mut self.runs);
+        if let Some((_, text)) = &mut self.footnote {
+            // Paragraphs of footnotes and loose list items stay in them
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(self.text.trim());
+            self.text.clear();
+            return;
+        }
+        if !self.lists.is_empty() {
+            if !self.item_runs.is_empty() {
+                self.item_runs.push(InlineRun::text("\n"));
+            }
+            self.item_runs.extend(trimmed(runs).runs());
+            self.text.clear();
+            return;
+        }
+        if self.quote_depth > 0 {
+            self.quote_paragraphs.push(trimmed(runs).runs());
+            self.text.clear();
+            return;
+        }
+
+        // An image alone in its paragraph is a figure
+        if let [(url, title, 0, end)] = &self.images[..] {
+            if *end == self.text.len() {
+                let id = format!("figure-{}", self.block_count());
+                let caption = if title.is_empty() { None } else { Some(title.clone()) };
+                let alt = std::mem::take(&mut self.text);
+                self.blocks.push(ContentBlock::Figure { id, asset: url.clone(), alt, caption, width: None });
+                return;
+            }
+        }
+        let text = self.text.trim().to_string();
+        self.text.clear();
+        if text.is_empty() {
+            return;
+        }
+        if let (Some(caption), Some(ContentBlock::Table { caption: slot @ None, .. })) =
+            (text.strip_prefix(TABLE_CAPTION_PREFIX), self.blocks.last_mut())
+        {
+            *slot = Some(caption.to_string());
+            return;
+        }
+        let id = Some(format!("para-{}", self.block_count()));
+        self.blocks.push(ContentBlock::Paragraph { text: trimmed(runs), id, xbrl: None });
+    }
+
+    /// Add the text read so far to the open list item
+    fn flush_item(&mut self) {
+        let Some(item) = self.lists.last_mut().and_then(|list| list.items.last_mut()) else { return };
+        let mut runs = std::mem::take(&mut self.item_runs);
+        let rest = trimmed(std::mem::take(&mut self.runs));
+        if !rest.is_empty() {
+            if !runs.is_empty() {
+                runs.push(InlineRun::text("\n"));
+            }
+            runs.extend(rest.runs());
+        }
+        self.text.clear();
+        if !runs.is_empty() {
+            // Text after a nested list continues the item on a new line
+            let mut text = item.text.runs();
+            if !text.is_empty() {
+                text.push(InlineRun::text("\n"));
+            }
+            text.extend(runs);
+            item.text = InlineText::from_runs(text);
+        }
+    }
+
+    fn end_code_block(&mut self) {
+        let Some((language, mut text)) = self.code.take() else { return };
+        if text.ends_with('\n') {
+            text.pop();
+        }
+        // Code inside a list item stays part of the item
+        if !self.lists.is_empty() {
+            if !self.item_runs.is_empty() {
+                self.item_runs.push(InlineRun::text("\n"));
+            }
+            self.item_runs.push(InlineRun::marked(text, &[Mark::Code]));
+            return;
+        }
+        let id = Some(format!("code-{}", self.block_count()));
+        self.blocks.push(ContentBlock::Code { language, text, id });
+    }
+
+    /// Emit the block quote just closed; paragraphs join with line breaks
+    /// and a last `— ` paragraph after another is the attribution
+    fn end_quote(&mut self) {
+        let mut paragraphs = std::mem::take(&mut self.quote_paragraphs);
+        paragraphs.retain(|runs| !runs.is_empty());
+        let mut attribution = None;
+        if paragraphs.len() > 1 {
+            let last = InlineText::from_runs(paragraphs[paragraphs.len() - 1].clone());
+            if let Some(source) = last.plain_text().strip_prefix(ATTRIBUTION_PREFIX) {
+                attribution = Some(source.trim().to_string());
+                paragraphs.pop();
+            }
+        }
+        let mut runs = Vec::new();
+        for paragraph in paragraphs {
+            if !runs.is_empty() {
+                runs.push(InlineRun::text("\n"));
+            }
+            runs.extend(paragraph);
+        }
+        let text = InlineText::from_runs(runs);
+        if !text.is_empty() {
+            let id = Some(format!("quote-{}", self.block_count()));
+            self.blocks.push(ContentBlock::Quote { text, attribution, id });
+        }
+    }
+
+    fn block_count(&self) -> usize {
+        self.sections.iter().map(|s| s.content.len()).sum::<usize>() + self.blocks.len()
+    }
+
+    fn finish_section(&mut self) {
+        if self.marked || !self.blocks.is_empty() {
+            self.sections.push(Section {
+                id: self.section_id.clone(),
+                title: self.section_title.take(),
+                content: std::mem::take(&mut self.blocks),
+                encrypted: None,
+            });
+        }
+    }
+
+    fn finish(mut self) -> Vec<Section> {
+        self.finish_section();
+        self.sections
     }
-    
-    // Extract title from first heading or filename
-    let title = if let Some(ContentBlock::Heading { text, .. }) = content_blocks.first() {
-        text.clone()
-    } else {
-        input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("Markdown Import")
-            .to_string()
-    };
-    
-    let content = DocumentContent {
-        sections: vec![Section {
-            id: "content".to_string(),
-            title: Some("Content".to_string()),
-            content: content_blocks,
-        }],
-    };
-    
-    let styles = get_default_styles();
-    let mut document = Document::new(title, "en".to_string(), content, styles);
-    document.manifest.integrity.algorithm = tdf_core::document::HashAlgorithm::Sha256;
-    
-    // Build archive
-    let mut builder = ArchiveBuilder::new(document);
-    builder.build(
-        output,
-        None,
-        signer_id,
-        signer_name,
-    )?;
-    
-    Ok(())
 }
 
 fn get_default_styles() -> String {
     include_str!("styles/default.css").to_string()
 }
-
@@ -33,7 +33,7 @@ pub fn convert_pdf_to_tdf(
     
     content_blocks.push(ContentBlock::Heading {
         level: 1,
-        text: title.clone(),
+        text: title.clone().into(),
         id: Some("title".to_string()),
     });
     
@@ -55,13 +55,14 @@ pub fn convert_pdf_to_tdf(
         if is_heading {
             content_blocks.push(ContentBlock::Heading {
                 level: 2,
-                text: trimmed.to_string(),
+                text: trimmed.into(),
                 id: Some(format!("heading-{}", idx)),
             });
         } else {
             content_blocks.push(ContentBlock::Paragraph {
-                text: trimmed.to_string(),
+                text: trimmed.into(),
                 id: Some(format!("para-{}", idx)),
+                xbrl: None,
             });
         }
     }
@@ -69,8 +70,9 @@ pub fn convert_pdf_to_tdf(
     if content_blocks.len() == 1 {
         // Only title, add full text
         content_blocks.push(ContentBlock::Paragraph {
-            text: text_result.trim().to_string(),
+            text: text_result.trim().into(),
             id: Some("content".to_string()),
+            xbrl: None,
         });
     }
     
@@ -79,6 +81,7 @@ pub fn convert_pdf_to_tdf(
             id: "content".to_string(),
             title: Some("Content".to_string()),
             content: content_blocks,
+            encrypted: None,
         }],
     };
     
@@ -33,7 +33,7 @@ pub fn convert_pptx_to_tdf(
                 let mut content_blocks = Vec::new();
                 content_blocks.push(ContentBlock::Heading {
                     level: 2,
-                    text: format!("Slide {}", i),
+                    text: format!("Slide {}", i).into(),
                     id: Some(format!("slide-{}", i)),
                 });
                 
@@ -42,8 +42,9 @@ pub fn convert_pptx_to_tdf(
                     let trimmed = para.trim();
                     if !trimmed.is_empty() {
                         content_blocks.push(ContentBlock::Paragraph {
-                            text: trimmed.to_string(),
+                            text: trimmed.into(),
                             id: Some(format!("slide-{}-para-{}", i, idx)),
+                            xbrl: None,
                         });
                     }
                 }
@@ -52,6 +53,7 @@ pub fn convert_pptx_to_tdf(
                     id: format!("slide_{}", i),
                     title: Some(format!("Slide {}", i)),
                     content: content_blocks,
+                    encrypted: None,
                 });
             }
         } else {
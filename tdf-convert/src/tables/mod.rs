@@ -0,0 +1,419 @@
+//! Export of document tables with typed cells
+//!
+//! Every `ContentBlock::Table` becomes a `TypedTable`: raw numbers,
+//! currency amounts, percentages and dates keep their native types instead
+//! of their display strings. A column is typed only if every cell in it
+//! parses as that type; otherwise it falls back to text of the display
+//! values, so nothing is lost. Currency columns are followed by a
+//! `<column>_currency` code column, and every row starts with provenance
+//! columns (`document_id`, `root_hash`, `table_id`, `row`) so extracted
+//! data can be traced back to the signed document.
+//!
+//! Tables can be written as CSV (one file per table), XLSX (one sheet per
+//! table) or Parquet (one file per table).
+
+pub mod parquet;
+pub mod xlsx;
+
+use crate::error::ConvertError;
+use chrono::{DateTime, NaiveDate};
+use std::path::{Path, PathBuf};
+use tdf_core::content::{CellType, CellValue, ContentBlock, Decimal, TableColumn, TableRow};
+use tdf_core::document::Document;
+
+/// Names of the provenance columns leading every exported row
+pub const PROVENANCE_COLUMNS: [&str; 4] = ["document_id", "root_hash", "table_id", "row"];
+
+/// Output format of `export_tables`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Csv,
+    Xlsx,
+    Parquet,
+}
+
+impl std::str::FromStr for TableFormat {
+    type Err = ConvertError;
+
+    fn from_str(s: &str) -> Result<Self, ConvertError> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(TableFormat::Csv),
+            "xlsx" => Ok(TableFormat::Xlsx),
+            "parquet" => Ok(TableFormat::Parquet),
+            other => Err(ConvertError::UnsupportedFormat(format!(
+                "{} (expected csv, xlsx or parquet)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Native type of an exported column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Text,
+    Integer,
+    Number,
+    Currency,
+    /// A fraction, so 12.5% is 0.125
+    Percentage,
+    Date,
+}
+
+/// An exported column
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedColumn {
+    pub name: String,
+    pub kind: ColumnKind,
+}
+
+/// A cell value in its native type
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedValue {
+    /// Missing or redacted cell
+    Null,
+    Text(String),
+    Integer(i64),
+    /// Exact amount; Parquet stores it as a double
+    Number(Decimal),
+    Date(NaiveDate),
+}
+
+/// Document id and root hash carried by every exported row
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    pub document_id: String,
+    pub root_hash: String,
+}
+
+/// A table ready for export, provenance columns included
+#[derive(Debug, Clone)]
+pub struct TypedTable {
+    pub id: String,
+    pub caption: Option<String>,
+    pub provenance: Provenance,
+    pub columns: Vec<TypedColumn>,
+    pub rows: Vec<Vec<TypedValue>>,
+}
+
+/// Parse a date cell's raw value (`YYYY-MM-DD` or an RFC 3339 timestamp)
+fn parse_date(raw: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(raw.trim(), "%Y-%m-%d")
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(raw.trim()).ok().map(|t| t.date_naive()))
+}
+
+/// Native type of `column`, if every cell in it has that type
+fn column_kind(column: &TableColumn, rows: &[TableRow]) -> ColumnKind {
+    let cells = || rows.iter().filter_map(|r| r.cells.get(&column.id)).filter(|c| !matches!(c, CellValue::Redacted { .. }));
+    let all = |f: fn(&CellValue) -> bool| cells().all(f);
+    match column.cell_type {
+        // Formula cells export their computed value
+        CellType::Number | CellType::Formula if all(|c| matches!(c, CellValue::Number { .. } | CellValue::Formula { .. })) => {
+            ColumnKind::Number
+        }
+        CellType::Currency
+            if all(|c| matches!(c, CellValue::Currency { .. } | CellValue::Number { .. } | CellValue::Formula { .. })) =>
+        {
+            ColumnKind::Currency
+        }
+        // Percentage cells deserialize as numbers; the column type says what they are
+        CellType::Percentage
+            if all(|c| matches!(c, CellValue::Percentage { .. } | CellValue::Number { .. } | CellValue::Formula { .. })) =>
+        {
+            ColumnKind::Percentage
+        }
+        CellType::Date if all(|c| matches!(c, CellValue::Date { raw, .. } if parse_date(raw).is_some())) => {
+            ColumnKind::Date
+        }
+        _ => ColumnKind::Text,
+    }
+}
+
+/// Convert one table to its typed form
+///
+/// # Arguments
+/// * `id` - Table block id
+/// * `caption` - Table caption, used to name XLSX sheets
+/// * `columns` / `rows` - Table content
+/// * `provenance` - Document id and root hash for the provenance columns
+pub fn typed_table(id: &str, caption: Option<&str>, columns: &[TableColumn], rows: &[TableRow], provenance: &Provenance) -> TypedTable {
+    let kinds: Vec<ColumnKind> = columns.iter().map(|c| column_kind(c, rows)).collect();
+
+    let mut typed_columns: Vec<TypedColumn> = PROVENANCE_COLUMNS
+        .iter()
+        .map(|name| TypedColumn {
+            name: name.to_string(),
+            kind: if *name == "row" { ColumnKind::Integer } else { ColumnKind::Text },
+        })
+        .collect();
+    for (column, &kind) in columns.iter().zip(&kinds) {
+        typed_columns.push(TypedColumn { name: column.header.clone(), kind });
+        if kind == ColumnKind::Currency {
+            typed_columns.push(TypedColumn { name: format!("{}_currency", column.header), kind: ColumnKind::Text });
+        }
+    }
+
+    let typed_rows = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let mut values = vec![
+                TypedValue::Text(provenance.document_id.clone()),
+                TypedValue::Text(provenance.root_hash.clone()),
+                TypedValue::Text(id.to_string()),
+                TypedValue::Integer(index as i64 + 1),
+            ];
+            for (column, &kind) in columns.iter().zip(&kinds) {
+                let cell = row.cells.get(&column.id).filter(|c| !matches!(c, CellValue::Redacted { .. }));
+                let value = match (kind, cell) {
+                    (_, None) => TypedValue::Null,
+                    (ColumnKind::Text, Some(cell)) => TypedValue::Text(cell.display_text().to_string()),
+                    (ColumnKind::Date, Some(CellValue::Date { raw, .. })) => {
+                        parse_date(raw).map(TypedValue::Date).unwrap_or(TypedValue::Null)
+                    }
+                    (
+                        _,
+                        Some(
+                            CellValue::Number { raw, .. }
+                            | CellValue::Formula { raw, .. }
+                            | CellValue::Currency { raw, .. }
+                            | CellValue::Percentage { raw, .. },
+                        ),
+                    ) => TypedValue::Number(*raw),
+                    (_, Some(cell)) => TypedValue::Text(cell.display_text().to_string()),
+                };
+                values.push(value);
+                if kind == ColumnKind::Currency {
+                    let code = match cell {
+                        Some(CellValue::Currency { currency, .. }) => Some(currency.clone()),
+                        Some(_) => column.currency.clone(),
+                        None => None,
+                    };
+                    values.push(code.map(TypedValue::Text).unwrap_or(TypedValue::Null));
+                }
+            }
+            values
+        })
+        .collect();
+
+    TypedTable {
+        id: id.to_string(),
+        caption: caption.map(str::to_string),
+        provenance: provenance.clone(),
+        columns: typed_columns,
+        rows: typed_rows,
+    }
+}
+
+/// Typed form of every table in `document`, in reading order
+///
+/// # Arguments
+/// * `document` - Document to export from
+/// * `root_hash` - Verified root hash of the archive, for provenance
+/// * `only` - Table ids to export; all tables when empty
+pub fn collect_tables(document: &Document, root_hash: &str, only: &[String]) -> Vec<TypedTable> {
+    let provenance = Provenance { document_id: document.manifest.document.id.clone(), root_hash: root_hash.to_string() };
+    document
+        .content
+        .sections
+        .iter()
+        .flat_map(|s| s.content.iter())
+        .filter_map(|block| match block {
+            ContentBlock::Table { id, caption, columns, rows, .. } if only.is_empty() || only.contains(id) => {
+                Some(typed_table(id, caption.as_deref(), columns, rows, &provenance))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Text of a value in CSV: full-precision numbers and ISO dates
+fn csv_field(value: &TypedValue) -> String {
+    match value {
+        TypedValue::Null => String::new(),
+        TypedValue::Text(text) => text.clone(),
+        TypedValue::Integer(n) => n.to_string(),
+        TypedValue::Number(n) => n.to_string(),
+        TypedValue::Date(date) => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Write one table as CSV with a header row
+pub fn write_csv(table: &TypedTable, path: &Path) -> Result<(), ConvertError> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(table.columns.iter().map(|c| c.name.as_str()))?;
+    for row in &table.rows {
+        writer.write_record(row.iter().map(csv_field))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// File name stem for a table, safe on common file systems
+fn file_stem(table_id: &str) -> String {
+    table_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect()
+}
+
+/// Writer of one table to one file
+type TableWriter = fn(&TypedTable, &Path) -> Result<(), ConvertError>;
+
+/// Write `tables` in `format`
+///
+/// # Arguments
+/// * `tables` - Tables to write
+/// * `format` - Output format
+/// * `output` - Workbook path for XLSX; directory for CSV and Parquet
+///   (one `<table id>.csv` or `<table id>.parquet` per table)
+///
+/// # Returns
+/// The files written
+pub fn export_tables(tables: &[TypedTable], format: TableFormat, output: &Path) -> Result<Vec<PathBuf>, ConvertError> {
+    if tables.is_empty() {
+        return Err(ConvertError::Conversion("Document has no tables to export".to_string()));
+    }
+    let (extension, write): (&str, TableWriter) = match format {
+        TableFormat::Xlsx => {
+            xlsx::write_workbook(tables, output)?;
+            return Ok(vec![output.to_path_buf()]);
+        }
+        TableFormat::Csv => ("csv", write_csv),
+        TableFormat::Parquet => ("parquet", parquet::write_parquet),
+    };
+
+    std::fs::create_dir_all(output)?;
+    let mut written = Vec::new();
+    for table in tables {
+        let path = output.join(format!("{}.{}", file_stem(&table.id), extension));
+        write(table, &path)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn columns(value: serde_json::Value) -> Vec<TableColumn> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn rows(value: serde_json::Value) -> Vec<TableRow> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn provenance() -> Provenance {
+        Provenance { document_id: "doc-1".to_string(), root_hash: "ab".repeat(32) }
+    }
+
+    fn decimal(value: &str) -> TypedValue {
+        TypedValue::Number(value.parse().unwrap())
+    }
+
+    /// Table with one column of each exported type and a missing amount
+    pub(crate) fn sample_table() -> TypedTable {
+        let columns = columns(json!([
+            { "id": "item", "header": "Item", "type": "text" },
+            { "id": "amount", "header": "Amount", "type": "currency", "currency": "EUR" },
+            { "id": "share", "header": "Share", "type": "percentage" },
+            { "id": "due", "header": "Due", "type": "date" }
+        ]));
+        let rows = rows(json!([
+            { "item": "Cash", "amount": { "raw": "1250.50", "display": "€1,250.50", "currency": "EUR" },
+              "share": { "raw": "0.125", "display": "12.5%" }, "due": { "raw": "2025-06-30", "display": "30 Jun 2025" } },
+            { "item": "Receivables", "amount": { "raw": "99", "display": "$99.00", "currency": "USD" },
+              "share": { "raw": "0.875", "display": "87.5%" }, "due": { "raw": "2025-09-30T00:00:00Z", "display": "30 Sep 2025" } },
+            { "item": "Pending", "share": { "raw": "0", "display": "0%" }, "due": { "raw": "2025-12-31", "display": "31 Dec 2025" } }
+        ]));
+        typed_table("tbl-assets", Some("Assets"), &columns, &rows, &provenance())
+    }
+
+    #[test]
+    fn test_columns_keep_native_types() {
+        let table = sample_table();
+        let kinds: Vec<(&str, ColumnKind)> = table.columns.iter().map(|c| (c.name.as_str(), c.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                ("document_id", ColumnKind::Text),
+                ("root_hash", ColumnKind::Text),
+                ("table_id", ColumnKind::Text),
+                ("row", ColumnKind::Integer),
+                ("Item", ColumnKind::Text),
+                ("Amount", ColumnKind::Currency),
+                ("Amount_currency", ColumnKind::Text),
+                ("Share", ColumnKind::Percentage),
+                ("Due", ColumnKind::Date),
+            ]
+        );
+
+        let first = &table.rows[0];
+        assert_eq!(first[..4], [
+            TypedValue::Text("doc-1".to_string()),
+            TypedValue::Text("ab".repeat(32)),
+            TypedValue::Text("tbl-assets".to_string()),
+            TypedValue::Integer(1),
+        ]);
+        assert_eq!(first[5], decimal("1250.50"));
+        assert_eq!(first[6], TypedValue::Text("EUR".to_string()));
+        assert_eq!(first[7], decimal("0.125"));
+        assert_eq!(first[8], TypedValue::Date(NaiveDate::from_ymd_opt(2025, 6, 30).unwrap()));
+
+        // Each amount keeps its own currency; RFC 3339 timestamps become dates
+        assert_eq!(table.rows[1][6], TypedValue::Text("USD".to_string()));
+        assert_eq!(table.rows[1][8], TypedValue::Date(NaiveDate::from_ymd_opt(2025, 9, 30).unwrap()));
+        // A missing amount is null, and so is its currency
+        assert_eq!(table.rows[2][5..7], [TypedValue::Null, TypedValue::Null]);
+    }
+
+    #[test]
+    fn test_column_falls_back_to_text() {
+        let columns = columns(json!([
+            { "id": "value", "header": "Value", "type": "number" },
+            { "id": "date", "header": "Date", "type": "date" }
+        ]));
+        let rows = rows(json!([
+            { "value": { "raw": "1", "display": "1" }, "date": { "raw": "2025-01-31", "display": "Jan 31" } },
+            { "value": "n/a", "date": { "raw": "end of quarter", "display": "Q1 end" } }
+        ]));
+        let table = typed_table("t", None, &columns, &rows, &provenance());
+        assert_eq!(table.columns[4].kind, ColumnKind::Text);
+        assert_eq!(table.columns[5].kind, ColumnKind::Text);
+        // Display values are kept, not raw ones
+        assert_eq!(table.rows[0][4], TypedValue::Text("1".to_string()));
+        assert_eq!(table.rows[0][5], TypedValue::Text("Jan 31".to_string()));
+        assert_eq!(table.rows[1][4], TypedValue::Text("n/a".to_string()));
+    }
+
+    #[test]
+    fn test_redacted_and_formula_cells() {
+        let columns = columns(json!([{ "id": "total", "header": "Total", "type": "number" }]));
+        let rows = rows(json!([
+            { "total": { "raw": "10", "display": "10" } },
+            { "total": { "redacted": "00ff" } },
+            { "total": { "formula": "SUM(total)", "raw": "10", "display": "10" } }
+        ]));
+        let table = typed_table("t", None, &columns, &rows, &provenance());
+        assert_eq!(table.columns[4].kind, ColumnKind::Number);
+        assert_eq!(table.rows[1][4], TypedValue::Null);
+        assert_eq!(table.rows[2][4], decimal("10"));
+    }
+
+    #[test]
+    fn test_csv_written_with_header_and_typed_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = export_tables(&[sample_table()], TableFormat::Csv, dir.path()).unwrap();
+        assert_eq!(written, [dir.path().join("tbl-assets.csv")]);
+
+        let text = std::fs::read_to_string(&written[0]).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next().unwrap(), "document_id,root_hash,table_id,row,Item,Amount,Amount_currency,Share,Due");
+        assert!(lines.next().unwrap().ends_with(",1,Cash,1250.50,EUR,0.125,2025-06-30"));
+        assert!(export_tables(&[], TableFormat::Csv, dir.path()).is_err());
+    }
+}
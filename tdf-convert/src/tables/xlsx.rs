@@ -0,0 +1,259 @@
+//! XLSX workbooks with one sheet per table
+//!
+//! Written directly as SpreadsheetML parts in a ZIP container. Text uses
+//! inline strings, numbers are stored as numbers, dates as serial day
+//! numbers with a `yyyy-mm-dd` format, percentages with `0.00%` and
+//! currency amounts with `#,##0.00`. The header row is bold and frozen.
+
+use super::{ColumnKind, TypedTable, TypedValue};
+use crate::error::ConvertError;
+use crate::export::xml_text;
+use chrono::NaiveDate;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const MAIN_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// Indexes into `cellXfs` of `STYLES`
+const STYLE_HEADER: usize = 1;
+const STYLE_DATE: usize = 2;
+const STYLE_PERCENT: usize = 3;
+const STYLE_CURRENCY: usize = 4;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+<numFmts count="1"><numFmt numFmtId="164" formatCode="yyyy-mm-dd"/></numFmts>
+<fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts>
+<fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>
+<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>
+<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>
+<cellXfs count="5">
+<xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>
+<xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/>
+<xf numFmtId="164" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>
+<xf numFmtId="10" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>
+<xf numFmtId="4" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>
+</cellXfs>
+<cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles>
+</styleSheet>
+"#;
+
+fn zip_error(e: zip::result::ZipError) -> ConvertError {
+    ConvertError::Conversion(format!("XLSX write error: {}", e))
+}
+
+/// Spreadsheet column letters for a zero-based index (0 = A, 26 = AA)
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Excel serial day number of `date` (1900 date system)
+fn serial_date(date: NaiveDate) -> i64 {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30).expect("valid epoch");
+    (date - epoch).num_days()
+}
+
+/// Sheet names are at most 31 characters, unique, without `[]:*?/\`
+fn sheet_name(table: &TypedTable, used: &mut HashSet<String>) -> String {
+    let base: String = table
+        .id
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+        .take(31)
+        .collect();
+    let base = if base.trim().is_empty() { "Table".to_string() } else { base };
+    let mut name = base.clone();
+    let mut n = 2;
+    while !used.insert(name.to_lowercase()) {
+        let suffix = format!(" ({})", n);
+        name = format!("{}{}", base.chars().take(31 - suffix.len()).collect::<String>(), suffix);
+        n += 1;
+    }
+    name
+}
+
+fn inline_string(out: &mut String, reference: &str, style: Option<usize>, text: &str) {
+    let style = style.map(|s| format!(" s=\"{}\"", s)).unwrap_or_default();
+    let space = if text.trim() != text { " xml:space=\"preserve\"" } else { "" };
+    let _ = write!(out, "<c r=\"{}\" t=\"inlineStr\"{}><is><t{}>{}</t></is></c>", reference, style, space, xml_text(text));
+}
+
+fn number(out: &mut String, reference: &str, style: Option<usize>, value: impl std::fmt::Display) {
+    let style = style.map(|s| format!(" s=\"{}\"", s)).unwrap_or_default();
+    let _ = write!(out, "<c r=\"{}\"{}><v>{}</v></c>", reference, style, value);
+}
+
+fn worksheet(table: &TypedTable) -> String {
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<worksheet xmlns=\"{}\">\
+         <sheetViews><sheetView workbookViewId=\"0\"><pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/>\
+         </sheetView></sheetViews><sheetData>",
+        MAIN_NS
+    );
+    let names: Vec<String> = (0..table.columns.len()).map(column_name).collect();
+
+    out.push_str("<row r=\"1\">");
+    for (column, name) in table.columns.iter().zip(&names) {
+        inline_string(&mut out, &format!("{}1", name), Some(STYLE_HEADER), &column.name);
+    }
+    out.push_str("</row>");
+
+    for (i, row) in table.rows.iter().enumerate() {
+        let r = i + 2;
+        let _ = write!(out, "<row r=\"{}\">", r);
+        for ((value, column), name) in row.iter().zip(&table.columns).zip(&names) {
+            let reference = format!("{}{}", name, r);
+            let style = match column.kind {
+                ColumnKind::Date => Some(STYLE_DATE),
+                ColumnKind::Percentage => Some(STYLE_PERCENT),
+                ColumnKind::Currency => Some(STYLE_CURRENCY),
+                _ => None,
+            };
+            match value {
+                TypedValue::Null => {}
+                TypedValue::Text(text) => inline_string(&mut out, &reference, None, text),
+                TypedValue::Integer(n) => number(&mut out, &reference, None, n),
+                TypedValue::Number(n) => number(&mut out, &reference, style, n),
+                TypedValue::Date(date) => number(&mut out, &reference, style, serial_date(*date)),
+            }
+        }
+        out.push_str("</row>");
+    }
+    out.push_str("</sheetData></worksheet>\n");
+    out
+}
+
+/// Write `tables` to an XLSX workbook at `path`, one sheet per table
+pub fn write_workbook(tables: &[TypedTable], path: &Path) -> Result<(), ConvertError> {
+    let mut used = HashSet::new();
+    let names: Vec<String> = tables.iter().map(|t| sheet_name(t, &mut used)).collect();
+
+    let mut content_types = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+         <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+         <Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>",
+    );
+    let mut workbook = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<workbook xmlns=\"{}\" xmlns:r=\"{}\"><sheets>",
+        MAIN_NS, REL_NS
+    );
+    let mut relationships = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    );
+    for (i, name) in names.iter().enumerate() {
+        let n = i + 1;
+        let _ = write!(
+            content_types,
+            "<Override PartName=\"/xl/worksheets/sheet{}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+            n
+        );
+        let _ = write!(workbook, "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>", xml_text(name), n, n);
+        let _ = write!(
+            relationships,
+            "<Relationship Id=\"rId{}\" Type=\"{}/worksheet\" Target=\"worksheets/sheet{}.xml\"/>",
+            n, REL_NS, n
+        );
+    }
+    let _ = writeln!(
+        relationships,
+        "<Relationship Id=\"rId{}\" Type=\"{}/styles\" Target=\"styles.xml\"/></Relationships>",
+        names.len() + 1,
+        REL_NS
+    );
+    content_types.push_str("</Types>\n");
+    workbook.push_str("</sheets></workbook>\n");
+    let root_relationships = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+         <Relationship Id=\"rId1\" Type=\"{}/officeDocument\" Target=\"xl/workbook.xml\"/></Relationships>\n",
+        REL_NS
+    );
+
+    let mut zip = ZipWriter::new(std::fs::File::create(path)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut part = |name: &str, data: &str| -> Result<(), ConvertError> {
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(data.as_bytes())?;
+        Ok(())
+    };
+    part("[Content_Types].xml", &content_types)?;
+    part("_rels/.rels", &root_relationships)?;
+    part("xl/workbook.xml", &workbook)?;
+    part("xl/_rels/workbook.xml.rels", &relationships)?;
+    part("xl/styles.xml", STYLES)?;
+    for (i, table) in tables.iter().enumerate() {
+        part(&format!("xl/worksheets/sheet{}.xml", i + 1), &worksheet(table))?;
+    }
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::tests::sample_table;
+    use calamine::{open_workbook, Data, Reader, Xlsx};
+
+    #[test]
+    fn test_column_names() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_name(702), "AAA");
+    }
+
+    #[test]
+    fn test_sheet_names_sanitized_and_unique() {
+        let mut used = HashSet::new();
+        let mut table = sample_table();
+        table.id = "q1/q2:[assets]".to_string();
+        assert_eq!(sheet_name(&table, &mut used), "q1_q2__assets_");
+        assert_eq!(sheet_name(&table, &mut used), "q1_q2__assets_ (2)");
+        table.id = "x".repeat(40);
+        assert_eq!(sheet_name(&table, &mut used).chars().count(), 31);
+    }
+
+    #[test]
+    fn test_workbook_cells_keep_their_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tables.xlsx");
+        write_workbook(&[sample_table()], &path).unwrap();
+
+        let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+        assert_eq!(workbook.sheet_names(), ["tbl-assets"]);
+        let range = workbook.worksheet_range("tbl-assets").unwrap();
+
+        assert_eq!(range.get_value((0, 5)), Some(&Data::String("Amount".to_string())));
+        assert_eq!(range.get_value((1, 3)), Some(&Data::Float(1.0)));
+        assert_eq!(range.get_value((1, 4)), Some(&Data::String("Cash".to_string())));
+        assert_eq!(range.get_value((1, 5)), Some(&Data::Float(1250.5)));
+        assert_eq!(range.get_value((1, 6)), Some(&Data::String("EUR".to_string())));
+        assert_eq!(range.get_value((1, 7)), Some(&Data::Float(0.125)));
+        // Dates are serial day numbers with a date format
+        match range.get_value((1, 8)) {
+            Some(Data::DateTime(date)) => assert_eq!(date.as_f64(), 45838.0),
+            other => panic!("expected a date, got {:?}", other),
+        }
+        // The missing amount is an empty cell
+        assert!(matches!(range.get_value((3, 5)), None | Some(Data::Empty)));
+    }
+}
@@ -32,15 +32,16 @@ pub fn convert_text_to_tdf(
     
     content_blocks.push(ContentBlock::Heading {
         level: 1,
-        text: title.clone(),
+        text: title.clone().into(),
         id: Some("title".to_string()),
     });
     
     // Add paragraphs
     for (idx, para) in paragraphs.iter().enumerate() {
         content_blocks.push(ContentBlock::Paragraph {
-            text: para.to_string(),
+            text: (*para).into(),
             id: Some(format!("para-{}", idx)),
+            xbrl: None,
         });
     }
     
@@ -49,6 +50,7 @@ pub fn convert_text_to_tdf(
             id: "content".to_string(),
             title: Some("Content".to_string()),
             content: content_blocks,
+            encrypted: None,
         }],
     };
     
@@ -0,0 +1,527 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use chrono::NaiveDate;
+use tdf_core::archive::ArchiveBuilder;
+use tdf_core::content::{CellType, CellValue, ContentBlock, Decimal, DocumentContent, Section, TableColumn, TableRow};
+use tdf_core::document::Document;
+use tdf_core::xbrl::{XbrlEntity, XbrlInfo, XbrlPeriod, XbrlTag, XbrlUnit, ISO4217_NS, XBRLI_NS};
+use crate::error::ConvertError;
+use crate::xml::{parse_xml, Node};
+
+const LINK_NS: &str = "http://www.xbrl.org/2003/linkbase";
+const XSI_NS: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+pub fn convert_xbrl_to_tdf(
+    input: &Path,
+    output: &Path,
+    signer_id: Option<String>,
+    signer_name: Option<String>,
+    _signing_key: Option<&[u8]>,
+) -> Result<(), ConvertError> {
+    let xml = fs::read_to_string(input)?;
+    let title = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("XBRL Import");
+    let document = xbrl_to_document(&xml, title)?;
+
+    // Build archive
+    let mut builder = ArchiveBuilder::new(document);
+    builder.build(
+        output,
+        None,
+        signer_id,
+        signer_name,
+    )?;
+
+    Ok(())
+}
+
+/// Namespace prefixes declared on the instance's root element
+struct Namespaces(HashMap<String, String>);
+
+impl Namespaces {
+    fn new(root: &Node) -> Self {
+        let declared = root.attributes.iter().filter_map(|(key, uri)| {
+            let prefix = if key == "xmlns" { "" } else { key.strip_prefix("xmlns:")? };
+            Some((prefix.to_string(), uri.clone()))
+        });
+        Namespaces(declared.collect())
+    }
+
+    /// Namespace and local part of a prefixed name
+    fn resolve<'n>(&self, name: &'n str) -> (Option<&str>, &'n str) {
+        let (prefix, local) = name.split_once(':').unwrap_or(("", name));
+        (self.0.get(prefix).map(String::as_str), local)
+    }
+
+    fn is(&self, node: &Node, namespace: &str, local: &str) -> bool {
+        self.resolve(&node.name) == (Some(namespace), local)
+    }
+
+    fn children<'n>(&self, node: &'n Node, namespace: &'n str, local: &'n str) -> impl Iterator<Item = &'n Node> + 'n {
+        let matching: Vec<&Node> = node.children.iter().filter(|c| self.is(c, namespace, local)).collect();
+        matching.into_iter()
+    }
+
+    fn attr<'n>(&self, node: &'n Node, namespace: &str, local: &str) -> Option<&'n str> {
+        node.attributes
+            .iter()
+            .find(|(key, _)| key.contains(':') && self.resolve(key) == (Some(namespace), local))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// A prefixed name with the built-in XBRL prefixes normalised to
+    /// `xbrli` and `iso4217`; names in the default namespace get a prefix
+    /// declared for it
+    fn qname(&self, name: &str) -> Result<String, ConvertError> {
+        let (namespace, local) = self.resolve(name);
+        let namespace = namespace.ok_or_else(|| ConvertError::Xbrl(format!("undeclared prefix in '{}'", name)))?;
+        let prefix = match namespace {
+            XBRLI_NS => "xbrli",
+            ISO4217_NS => "iso4217",
+            _ => {
+                let (prefix, _) = name.split_once(':').unwrap_or(("", name));
+                if prefix.is_empty() {
+                    let mut prefixes = self.0.iter().filter(|(p, uri)| !p.is_empty() && *uri == namespace).map(|(p, _)| p);
+                    prefixes.next().ok_or_else(|| ConvertError::Xbrl(format!("'{}' is in a namespace without a prefix", name)))?
+                } else {
+                    prefix
+                }
+            }
+        };
+        Ok(format!("{}:{}", prefix, local))
+    }
+}
+
+/// A context the facts of a table can be reported in
+enum Context {
+    Reported(XbrlEntity, XbrlPeriod),
+    /// Dimensional, or for a forever period
+    Unsupported,
+}
+
+fn date(node: Option<&Node>) -> Result<NaiveDate, ConvertError> {
+    let text = node.map(|n| n.text.trim()).unwrap_or_default();
+    NaiveDate::parse_from_str(text.get(..10).unwrap_or(text), "%Y-%m-%d")
+        .map_err(|_| ConvertError::Xbrl(format!("invalid context date '{}'", text)))
+}
+
+fn context(ns: &Namespaces, node: &Node) -> Result<Context, ConvertError> {
+    let invalid = |what: &str| ConvertError::Xbrl(format!("context '{}' has no {}", node.attr("id").unwrap_or_default(), what));
+    let entity = ns.children(node, XBRLI_NS, "entity").next().ok_or_else(|| invalid("entity"))?;
+    let identifier = ns.children(entity, XBRLI_NS, "identifier").next().ok_or_else(|| invalid("identifier"))?;
+    let dimensional = ns.children(entity, XBRLI_NS, "segment").next().is_some() || ns.children(node, XBRLI_NS, "scenario").next().is_some();
+    let period = ns.children(node, XBRLI_NS, "period").next().ok_or_else(|| invalid("period"))?;
+    if dimensional || ns.children(period, XBRLI_NS, "forever").next().is_some() {
+        return Ok(Context::Unsupported);
+    }
+    let period = match ns.children(period, XBRLI_NS, "instant").next() {
+        Some(instant) => XbrlPeriod::Instant { instant: date(Some(instant))? },
+        None => XbrlPeriod::Duration {
+            start: date(ns.children(period, XBRLI_NS, "startDate").next())?,
+            end: date(ns.children(period, XBRLI_NS, "endDate").next())?,
+        },
+    };
+    let entity = XbrlEntity {
+        scheme: identifier.attr("scheme").unwrap_or_default().to_string(),
+        identifier: identifier.text.trim().to_string(),
+    };
+    Ok(Context::Reported(entity, period))
+}
+
+fn unit(ns: &Namespaces, node: &Node) -> Result<XbrlUnit, ConvertError> {
+    let measures = |parent: &Node| -> Result<Vec<String>, ConvertError> {
+        ns.children(parent, XBRLI_NS, "measure").map(|m| ns.qname(m.text.trim())).collect()
+    };
+    match ns.children(node, XBRLI_NS, "divide").next() {
+        Some(divide) => {
+            let part = |local: &str| -> Result<Vec<String>, ConvertError> {
+                ns.children(divide, XBRLI_NS, local).next().map(measures).transpose().map(Option::unwrap_or_default)
+            };
+            Ok(XbrlUnit { numerators: part("unitNumerator")?, denominators: part("unitDenominator")? })
+        }
+        None => Ok(XbrlUnit { numerators: measures(node)?, denominators: Vec::new() }),
+    }
+}
+
+/// A reported value
+struct Reported {
+    concept: String,
+    entity: XbrlEntity,
+    period: XbrlPeriod,
+    value: ReportedValue,
+}
+
+enum ReportedValue {
+    Numeric { value: Decimal, unit: XbrlUnit, decimals: Option<i32> },
+    Text(String),
+}
+
+/// Readable label of a concept: `ifrs-full:ProfitLoss` is "Profit loss"
+fn label(concept: &str) -> String {
+    let local: Vec<char> = concept.rsplit(':').next().unwrap_or(concept).chars().collect();
+    let mut label = String::new();
+    for (i, &c) in local.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|p| local[p]);
+        let next_lower = local.get(i + 1).is_some_and(|n| n.is_lowercase());
+        // A word starts at a capital after a lower-case letter or digit, or
+        // at the last capital of an acronym
+        let starts_word = c.is_uppercase() && previous.is_some_and(|p| !p.is_uppercase() || next_lower);
+        if starts_word {
+            label.push(' ');
+        }
+        if i > 0 && c.is_uppercase() && next_lower {
+            label.extend(c.to_lowercase());
+        } else {
+            label.push(c);
+        }
+    }
+    label
+}
+
+/// An amount with thousands separators, as written in the instance
+fn grouped(value: &Decimal) -> String {
+    let text = value.abs().to_string();
+    let (whole, fraction) = text.split_once('.').map_or((text.as_str(), None), |(w, f)| (w, Some(f)));
+    let mut out = String::new();
+    if value.is_negative() {
+        out.push('-');
+    }
+    for (i, c) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    if let Some(fraction) = fraction {
+        out.push('.');
+        out.push_str(fraction);
+    }
+    out
+}
+
+fn period_header(period: &XbrlPeriod) -> String {
+    match period {
+        XbrlPeriod::Instant { instant } => format!("At {}", instant),
+        XbrlPeriod::Duration { .. } => period.to_string(),
+    }
+}
+
+/// Read an XBRL instance into a document
+///
+/// Numeric facts become a table per entity, with a row per concept and a
+/// column per period, each cell tagged with its concept, period, unit and
+/// accuracy; amounts in a single ISO 4217 currency are currency cells.
+/// Text facts follow as a heading and a tagged paragraph each. The
+/// instance's schema references and namespace prefixes become the
+/// manifest's XBRL taxonomy, with the entity reporting most facts as its
+/// default. Nil facts, tuples and facts in dimensional or forever contexts
+/// cannot be tagged in a document and are counted in a note instead.
+///
+/// # Arguments
+/// * `xml` - XBRL 2.1 instance
+/// * `title` - Document title
+pub fn xbrl_to_document(xml: &str, title: &str) -> Result<Document, ConvertError> {
+    let root = parse_xml(xml)?;
+    let ns = Namespaces::new(&root);
+    if !ns.is(&root, XBRLI_NS, "xbrl") {
+        return Err(ConvertError::Xbrl(format!("root element '{}' is not an XBRL instance", root.name)));
+    }
+
+    let mut contexts = HashMap::new();
+    for node in ns.children(&root, XBRLI_NS, "context") {
+        contexts.insert(node.attr("id").unwrap_or_default(), context(&ns, node)?);
+    }
+    let mut units = HashMap::new();
+    for node in ns.children(&root, XBRLI_NS, "unit") {
+        units.insert(node.attr("id").unwrap_or_default(), unit(&ns, node)?);
+    }
+
+    let mut reported = Vec::new();
+    let mut left_out = 0;
+    for node in &root.children {
+        let Some(context_ref) = node.attr("contextRef") else {
+            // Tuples group facts without reporting one themselves
+            let (namespace, _) = ns.resolve(&node.name);
+            if ![Some(XBRLI_NS), Some(LINK_NS)].contains(&namespace) {
+                left_out += count_facts(node);
+            }
+            continue;
+        };
+        if ns.attr(node, XSI_NS, "nil") == Some("true") {
+            left_out += 1;
+            continue;
+        }
+        let concept = ns.qname(&node.name)?;
+        let (entity, period) = match contexts.get(context_ref) {
+            Some(Context::Reported(entity, period)) => (entity.clone(), *period),
+            Some(Context::Unsupported) => {
+                left_out += 1;
+                continue;
+            }
+            None => return Err(ConvertError::Xbrl(format!("fact '{}' refers to unknown context '{}'", concept, context_ref))),
+        };
+        let value = match node.attr("unitRef") {
+            Some(unit_ref) => {
+                let unit = units
+                    .get(unit_ref)
+                    .cloned()
+                    .ok_or_else(|| ConvertError::Xbrl(format!("fact '{}' refers to unknown unit '{}'", concept, unit_ref)))?;
+                let value = node
+                    .text
+                    .trim()
+                    .parse()
+                    .map_err(|_| ConvertError::Xbrl(format!("fact '{}' has invalid value '{}'", concept, node.text.trim())))?;
+                let decimals = node.attr("decimals").and_then(|d| d.parse().ok());
+                ReportedValue::Numeric { value, unit, decimals }
+            }
+            None => ReportedValue::Text(node.text.trim().to_string()),
+        };
+        reported.push(Reported { concept, entity, period, value });
+    }
+
+    // The taxonomy: schemas, the prefixes facts and units use, and the
+    // entity reporting most facts
+    let mut info = XbrlInfo {
+        schema_refs: ns
+            .children(&root, LINK_NS, "schemaRef")
+            .filter_map(|node| ns.attr(node, "http://www.w3.org/1999/xlink", "href"))
+            .map(str::to_string)
+            .collect(),
+        ..XbrlInfo::default()
+    };
+    let mut names: Vec<&str> = reported.iter().map(|r| r.concept.as_str()).collect();
+    names.extend(units.values().flat_map(|u| u.measures()));
+    for name in names {
+        let prefix = tdf_core::xbrl::prefix(name);
+        if prefix != "xbrli" && prefix != "iso4217" {
+            if let Some(uri) = ns.0.get(prefix) {
+                info.namespaces.insert(prefix.to_string(), uri.clone());
+            }
+        }
+    }
+    let mut entities: Vec<(XbrlEntity, usize)> = Vec::new();
+    for fact in &reported {
+        match entities.iter_mut().find(|(e, _)| *e == fact.entity) {
+            Some((_, count)) => *count += 1,
+            None => entities.push((fact.entity.clone(), 1)),
+        }
+    }
+    entities.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    info.entity = entities.first().map(|(e, _)| e.clone());
+
+    let mut sections = Vec::new();
+    for (index, (entity, _)) in entities.iter().enumerate() {
+        let facts: Vec<&Reported> = reported.iter().filter(|r| r.entity == *entity).collect();
+        let tag_entity = (index > 0).then(|| entity.clone());
+        let mut content = Vec::new();
+        if let Some(table) = fact_table(&facts, &tag_entity, index) {
+            content.push(table);
+        }
+        for (i, fact) in facts.iter().enumerate() {
+            let ReportedValue::Text(text) = &fact.value else { continue };
+            content.push(ContentBlock::Heading {
+                level: 3,
+                text: format!("{} ({})", label(&fact.concept), period_header(&fact.period)).into(),
+                id: None,
+            });
+            let tag = XbrlTag { concept: fact.concept.clone(), period: fact.period, entity: tag_entity.clone(), unit: None, decimals: None };
+            content.push(ContentBlock::Paragraph { text: text.as_str().into(), id: Some(format!("fact-{}-{}", index + 1, i + 1)), xbrl: Some(tag) });
+        }
+        sections.push(Section {
+            id: format!("entity-{}", index + 1),
+            title: Some(format!("{} ({})", entity.identifier, entity.scheme)),
+            content,
+            encrypted: None,
+        });
+    }
+    if left_out > 0 {
+        let note = format!(
+            "{} fact{} left out: nil values, tuples and facts in dimensional or forever contexts cannot be tagged in a TDF document.",
+            left_out,
+            if left_out == 1 { " was" } else { "s were" }
+        );
+        sections.push(Section {
+            id: "import-notes".to_string(),
+            title: Some("Import notes".to_string()),
+            content: vec![ContentBlock::Paragraph { text: note.into(), id: None, xbrl: None }],
+            encrypted: None,
+        });
+    }
+    if sections.is_empty() {
+        return Err(ConvertError::Xbrl("the instance reports no facts".to_string()));
+    }
+
+    let content = DocumentContent { sections };
+    let styles = get_default_styles();
+    let mut document = Document::new(title.to_string(), "en".to_string(), content, styles);
+    document.manifest.integrity.algorithm = tdf_core::document::HashAlgorithm::Sha256;
+    document.manifest.xbrl = Some(info);
+    Ok(document)
+}
+
+/// Facts inside a tuple, at any depth
+fn count_facts(node: &Node) -> usize {
+    node.children.iter().map(|c| if c.attr("contextRef").is_some() { 1 } else { count_facts(c) }).sum()
+}
+
+/// Table of an entity's numeric facts: a row per concept and unit, a
+/// column per period; a concept reported twice for a period with different
+/// values gets another row
+fn fact_table(facts: &[&Reported], entity: &Option<XbrlEntity>, index: usize) -> Option<ContentBlock> {
+    let numeric: Vec<(&Reported, &Decimal, &XbrlUnit, Option<i32>)> = facts
+        .iter()
+        .filter_map(|f| match &f.value {
+            ReportedValue::Numeric { value, unit, decimals } => Some((*f, value, unit, *decimals)),
+            ReportedValue::Text(_) => None,
+        })
+        .collect();
+    if numeric.is_empty() {
+        return None;
+    }
+    let mut periods: Vec<XbrlPeriod> = numeric.iter().map(|(f, ..)| f.period).collect();
+    periods.sort_by_key(|p| (p.end(), *p));
+    periods.dedup();
+    let column_ids: BTreeMap<XbrlPeriod, String> = periods.iter().enumerate().map(|(i, p)| (*p, format!("period_{}", i + 1))).collect();
+
+    let mut columns = vec![TableColumn { id: "concept".to_string(), header: "Concept".to_string(), cell_type: CellType::Text, currency: None }];
+    columns.extend(periods.iter().map(|p| TableColumn {
+        id: column_ids[p].clone(),
+        header: period_header(p),
+        cell_type: CellType::Number,
+        currency: None,
+    }));
+
+    let mut rows: Vec<(String, XbrlUnit, TableRow)> = Vec::new();
+    for (fact, value, unit, decimals) in numeric {
+        let column = &column_ids[&fact.period];
+        let currency = match (&unit.numerators[..], unit.denominators.is_empty()) {
+            ([measure], true) => measure.strip_prefix("iso4217:"),
+            _ => None,
+        };
+        let cell = match currency {
+            Some(code) => CellValue::Currency { raw: *value, display: grouped(value), currency: code.to_string() },
+            None => CellValue::Number { raw: *value, display: grouped(value) },
+        };
+        let free = rows.iter_mut().find(|(concept, u, row)| {
+            *concept == fact.concept && u == unit && row.cells.get(column).is_none_or(|existing| *existing == cell)
+        });
+        let row = match free {
+            Some((_, _, row)) => row,
+            None => {
+                let mut cells = HashMap::new();
+                cells.insert("concept".to_string(), CellValue::Text(label(&fact.concept)));
+                rows.push((fact.concept.clone(), unit.clone(), TableRow::new(cells)));
+                &mut rows.last_mut().expect("row just added").2
+            }
+        };
+        row.cells.insert(column.clone(), cell);
+        let tag = XbrlTag {
+            concept: fact.concept.clone(),
+            period: fact.period,
+            entity: entity.clone(),
+            unit: Some(unit.to_string()),
+            decimals,
+        };
+        row.format.tags.insert(column.clone(), tag);
+    }
+
+    Some(ContentBlock::Table {
+        id: format!("facts-{}", index + 1),
+        caption: Some("Reported amounts".to_string()),
+        columns,
+        header_rows: None,
+        rows: rows.into_iter().map(|(_, _, row)| row).collect(),
+        footer: None,
+    })
+}
+
+fn get_default_styles() -> String {
+    include_str!("styles/default.css").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tdf_core::xbrl::{facts, FactValue};
+
+    const INSTANCE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbrl xmlns="http://www.xbrl.org/2003/instance" xmlns:link="http://www.xbrl.org/2003/linkbase"
+      xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:iso="http://www.xbrl.org/2003/iso4217"
+      xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:ifrs-full="https://xbrl.ifrs.org/taxonomy/2024-03-27/ifrs-full">
+  <link:schemaRef xlink:type="simple" xlink:href="https://xbrl.ifrs.org/taxonomy/2024-03-27/full_ifrs_entry_point_2024-03-27.xsd"/>
+  <context id="FY24">
+    <entity><identifier scheme="http://standards.iso.org/iso/17442">5299000J2N45DDNE4Y28</identifier></entity>
+    <period><startDate>2024-01-01</startDate><endDate>2024-12-31</endDate></period>
+  </context>
+  <context id="FY23">
+    <entity><identifier scheme="http://standards.iso.org/iso/17442">5299000J2N45DDNE4Y28</identifier></entity>
+    <period><startDate>2023-01-01</startDate><endDate>2023-12-31</endDate></period>
+  </context>
+  <context id="FY24-segment">
+    <entity>
+      <identifier scheme="http://standards.iso.org/iso/17442">5299000J2N45DDNE4Y28</identifier>
+      <segment><member>Retail</member></segment>
+    </entity>
+    <period><startDate>2024-01-01</startDate><endDate>2024-12-31</endDate></period>
+  </context>
+  <unit id="EUR"><measure>iso:EUR</measure></unit>
+  <ifrs-full:Revenue contextRef="FY24" unitRef="EUR" decimals="-3">4250000</ifrs-full:Revenue>
+  <ifrs-full:Revenue contextRef="FY23" unitRef="EUR" decimals="-3">3980000</ifrs-full:Revenue>
+  <ifrs-full:Revenue contextRef="FY24-segment" unitRef="EUR" decimals="-3">1200000</ifrs-full:Revenue>
+  <ifrs-full:ProfitLoss contextRef="FY24" unitRef="EUR" decimals="INF">-125000.50</ifrs-full:ProfitLoss>
+  <ifrs-full:Dividends contextRef="FY24" unitRef="EUR" xsi:nil="true"/>
+  <ifrs-full:NameOfReportingEntity contextRef="FY24">Example Holdings SA</ifrs-full:NameOfReportingEntity>
+</xbrl>"#;
+
+    #[test]
+    fn test_instance_import() {
+        let document = xbrl_to_document(INSTANCE, "Annual report").unwrap();
+        document.validate().unwrap();
+        let info = document.manifest.xbrl.as_ref().unwrap();
+        assert_eq!(info.schema_refs.len(), 1);
+        assert_eq!(info.namespaces.keys().collect::<Vec<_>>(), ["ifrs-full"]);
+        assert_eq!(info.entity.as_ref().unwrap().identifier, "5299000J2N45DDNE4Y28");
+
+        let facts = facts(&document).unwrap();
+        assert_eq!(facts.len(), 4);
+        let profit = facts.iter().find(|f| f.concept == "ifrs-full:ProfitLoss").unwrap();
+        match &profit.value {
+            FactValue::Numeric { value, display, unit, decimals } => {
+                assert_eq!(value.to_string(), "-125000.50");
+                assert_eq!(display, "-125,000.50");
+                assert_eq!(unit.to_string(), "iso4217:EUR");
+                assert_eq!(*decimals, None);
+            }
+            FactValue::Text(_) => panic!("expected an amount"),
+        }
+        assert!(facts.iter().any(|f| f.value == FactValue::Text("Example Holdings SA".to_string())));
+
+        // The segment fact and the nil fact are counted in a note
+        let note = document.content.sections.last().unwrap();
+        assert_eq!(note.id, "import-notes");
+        let ContentBlock::Paragraph { text, .. } = &note.content[0] else { panic!("expected a note") };
+        assert!(text.plain_text().starts_with("2 facts were left out"));
+    }
+
+    #[test]
+    fn test_concept_labels() {
+        assert_eq!(label("ifrs-full:ProfitLoss"), "Profit loss");
+        assert_eq!(label("us-gaap:EarningsPerShareBasic"), "Earnings per share basic");
+        assert_eq!(label("ifrs-full:IFRSStatementDate"), "IFRS statement date");
+        assert_eq!(grouped(&"1234567.5".parse().unwrap()), "1,234,567.5");
+    }
+
+    #[test]
+    fn test_rejects_other_xml() {
+        assert!(matches!(xbrl_to_document("<html/>", "x"), Err(ConvertError::Xbrl(_))));
+    }
+
+    #[test]
+    fn test_rejects_deeply_nested_instance() {
+        let xml = format!("<xbrl>{}{}</xbrl>", "<a>".repeat(200_000), "</a>".repeat(200_000));
+        assert!(matches!(xbrl_to_document(&xml, "x"), Err(ConvertError::Xml(_))));
+    }
+}
@@ -0,0 +1,129 @@
+//! Minimal XML tree for the importers
+//!
+//! Element names keep their prefix (`w:p`, `xbrli:context`); namespaces
+//! are not resolved, which is enough for formats whose prefixes are fixed
+//! or declared on the root element.
+
+use crate::error::ConvertError;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+
+/// Deepest element nesting accepted; lookups and dropping a tree recurse
+/// once per level, so deeper input would overflow the stack
+const MAX_DEPTH: usize = 256;
+
+/// An XML element with its attributes, children and text
+#[derive(Debug, Default)]
+pub(crate) struct Node {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+    pub text: String,
+}
+
+impl Node {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// First element with this name below this one, depth first
+    pub fn find(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find_map(|c| if c.name == name { Some(c) } else { c.find(name) })
+    }
+
+    /// All elements with this name below this one, not looking inside
+    /// matches
+    pub fn all(&self, name: &str) -> Vec<&Node> {
+        let mut found = Vec::new();
+        for child in &self.children {
+            if child.name == name {
+                found.push(child);
+            } else {
+                found.extend(child.all(name));
+            }
+        }
+        found
+    }
+}
+
+fn element(start: &BytesStart, reader: &Reader<&[u8]>) -> Result<Node, ConvertError> {
+    let mut node = Node {
+        name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+        ..Node::default()
+    };
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| ConvertError::Xml(format!("Invalid XML attribute: {}", e)))?;
+        let value = attribute
+            .decode_and_unescape_value(reader)
+            .map_err(|e| ConvertError::Xml(format!("Invalid XML attribute: {}", e)))?;
+        node.attributes.push((String::from_utf8_lossy(attribute.key.as_ref()).into_owned(), value.into_owned()));
+    }
+    Ok(node)
+}
+
+/// Parse an XML document into its root element
+pub(crate) fn parse_xml(xml: &str) -> Result<Node, ConvertError> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack = vec![Node::default()];
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| ConvertError::Xml(format!("Invalid XML at {}: {}", reader.buffer_position(), e)))?;
+        match event {
+            Event::Start(start) => {
+                if stack.len() > MAX_DEPTH {
+                    return Err(ConvertError::Xml(format!("XML nested deeper than {} elements", MAX_DEPTH)));
+                }
+                stack.push(element(&start, &reader)?);
+            }
+            Event::Empty(start) => {
+                let node = element(&start, &reader)?;
+                stack.last_mut().expect("root").children.push(node);
+            }
+            Event::End(_) => {
+                let node = stack.pop().expect("open element");
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return Err(ConvertError::Xml("Unbalanced XML".to_string())),
+                }
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| ConvertError::Xml(format!("Invalid XML text: {}", e)))?;
+                stack.last_mut().expect("root").text.push_str(&text);
+            }
+            Event::CData(data) => {
+                stack.last_mut().expect("root").text.push_str(&String::from_utf8_lossy(&data));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let mut document = stack.pop().filter(|_| stack.is_empty()).ok_or_else(|| ConvertError::Xml("Unclosed XML element".to_string()))?;
+    document.children.pop().ok_or_else(|| ConvertError::Xml("Empty XML document".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nesting_at_limit_parses() {
+        let xml = format!("{}{}", "<a>".repeat(MAX_DEPTH), "</a>".repeat(MAX_DEPTH));
+        let root = parse_xml(&xml).unwrap();
+        assert_eq!(root.name, "a");
+        assert!(root.find("b").is_none());
+    }
+
+    #[test]
+    fn test_deep_nesting_rejected() {
+        let xml = format!("<xbrl>{}{}</xbrl>", "<a>".repeat(200_000), "</a>".repeat(200_000));
+        match parse_xml(&xml) {
+            Err(ConvertError::Xml(message)) => assert!(message.contains("nested deeper")),
+            other => panic!("expected a nesting error, got {:?}", other.map(|n| n.name)),
+        }
+    }
+}
@@ -0,0 +1,645 @@
+//! Export followed by import keeps the content structure
+//!
+//! Covers the blocks both sides of each format support: headings,
+//! paragraphs, nested and task lists, quotes, code, page breaks, rules,
+//! tables, figures and footnotes, and inline marks, links and references.
+//! Merged cells, header rows and row roles go through DOCX and through
+//! XLSX import, and XBRL-tagged facts through an XBRL instance.
+
+use std::collections::HashMap;
+use std::io::Write;
+use tdf_convert::docx::docx_to_document;
+use tdf_convert::excel::convert_excel_to_tdf;
+use tdf_convert::export::docx::render_docx;
+use tdf_convert::export::markdown::{render_markdown, write_markdown};
+use tdf_convert::export::xbrl::{render_inline, render_instance};
+use tdf_convert::markdown::markdown_to_document;
+use tdf_convert::xbrl::xbrl_to_document;
+use tdf_core::content::{
+    CellSpan, CellType, CellValue, ContentBlock, DocumentContent, HeaderCell, InlineRun, InlineText, ListItem, Mark, RowRole, Section,
+    TableColumn, TableRow,
+};
+use tdf_core::archive::ArchiveReader;
+use tdf_core::document::Document;
+use tdf_core::xbrl::{Fact, FactValue, XbrlEntity, XbrlInfo, XbrlPeriod, XbrlTag};
+
+/// A 1x1 PNG
+const PNG: &[u8] = &[
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4, 0x89, 0x00, 0x00, 0x00, 0x0D, 0x49,
+    0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x60, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01, 0xE5, 0x27, 0xDE, 0xFC, 0x00, 0x00,
+    0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+];
+
+const CHART: &str = "assets/images/chart.png";
+
+fn paragraph(text: &str) -> ContentBlock {
+    ContentBlock::Paragraph { text: text.into(), id: None, xbrl: None }
+}
+
+fn list(ordered: bool, items: &[&str]) -> ContentBlock {
+    ContentBlock::List { ordered, items: items.iter().map(|&s| s.into()).collect(), id: None }
+}
+
+fn footnote(id: &str, text: &str) -> ContentBlock {
+    ContentBlock::Footnote { id: id.to_string(), text: text.to_string() }
+}
+
+fn link(text: &str, href: &str, marks: &[Mark]) -> InlineRun {
+    InlineRun::Text { text: text.to_string(), marks: marks.to_vec(), link: Some(href.to_string()) }
+}
+
+/// A paragraph using every kind of run
+fn rich_paragraph() -> ContentBlock {
+    let runs = vec![
+        InlineRun::text("Net income rose "),
+        InlineRun::marked("14%", &[Mark::Bold]),
+        InlineRun::text(" in "),
+        InlineRun::marked("FY2025", &[Mark::Bold, Mark::Italic]),
+        InlineRun::text(" (see "),
+        InlineRun::CrossRef { target: "kpis".to_string(), text: "Key figures".to_string() },
+        InlineRun::text(" and the "),
+        link("annual ", "https://example.com/10-k?year=2025", &[]),
+        link("filing", "https://example.com/10-k?year=2025", &[Mark::Italic]),
+        InlineRun::text("). CO"),
+        InlineRun::marked("2", &[Mark::Subscript]),
+        InlineRun::text(" per "),
+        InlineRun::marked("m^2", &[Mark::Code]),
+        InlineRun::text(" fell"),
+        InlineRun::FootnoteRef { id: "fn:2".to_string() },
+        InlineRun::text(", "),
+        InlineRun::marked("not", &[Mark::Strike]),
+        InlineRun::text(" "),
+        InlineRun::marked("restated", &[Mark::Underline]),
+        InlineRun::text(" to 10"),
+        InlineRun::marked("6", &[Mark::Superscript]),
+        InlineRun::text(" *tonnes*."),
+    ];
+    ContentBlock::Paragraph { text: InlineText::from_runs(runs), id: None, xbrl: None }
+}
+
+fn column(index: usize, header: &str, cell_type: CellType) -> TableColumn {
+    TableColumn { id: format!("col_{}", index), header: header.to_string(), cell_type, currency: None }
+}
+
+fn row(cells: [CellValue; 5]) -> TableRow {
+    TableRow::new(cells.into_iter().enumerate().map(|(i, v)| (format!("col_{}", i), v)).collect())
+}
+
+fn number(raw: &str, display: &str) -> CellValue {
+    CellValue::Number { raw: raw.parse().unwrap(), display: display.to_string() }
+}
+
+fn sample() -> (Document, HashMap<String, Vec<u8>>) {
+    let table = ContentBlock::Table {
+        id: "kpis".to_string(),
+        caption: Some("Key figures".to_string()),
+        columns: vec![
+            column(0, "Metric", CellType::Text),
+            column(1, "Amount", CellType::Currency),
+            column(2, "Margin", CellType::Percentage),
+            column(3, "Units", CellType::Number),
+            column(4, "As of", CellType::Date),
+        ],
+        header_rows: None,
+        rows: vec![
+            row([
+                CellValue::Text("Revenue".to_string()),
+                CellValue::Currency { raw: "1234.50".parse().unwrap(), display: "$1,234.50".to_string(), currency: "USD".to_string() },
+                number("0.125", "12.5%"),
+                number("42", "42"),
+                CellValue::Date { raw: "2025-03-31".to_string(), display: "2025-03-31".to_string() },
+            ]),
+            row([
+                CellValue::Text("Cost | overhead".to_string()),
+                CellValue::Currency { raw: "500.25".parse().unwrap(), display: "$500.25".to_string(), currency: "USD".to_string() },
+                number("0.5", "50%"),
+                number("7", "7"),
+                CellValue::Date { raw: "2025-06-30".to_string(), display: "2025-06-30".to_string() },
+            ]),
+        ],
+        footer: Some(vec!["Total".to_string(), "$1,734.75".to_string(), String::new(), "49".to_string(), String::new()]),
+    };
+
+    let sections = vec![
+        Section {
+            id: "summary".to_string(),
+            title: Some("Summary".to_string()),
+            content: vec![
+                ContentBlock::Heading {
+                    level: 3,
+                    text: InlineText::from_runs([InlineRun::text("Highlights & "), InlineRun::marked("notes", &[Mark::Italic])]),
+                    id: None,
+                },
+                paragraph("Revenue grew *12%* to $1,234 <net>{{fn:1}} and # is_not a [heading]."),
+                paragraph("- not a list\nsecond line\n3. not ordered"),
+                list(true, &["First", "Second with a note{{fn:2}}"]),
+                rich_paragraph(),
+                ContentBlock::List {
+                    ordered: false,
+                    items: vec![InlineText::from_runs([InlineRun::marked("Bold", &[Mark::Bold]), InlineRun::text(" item")]).into()],
+                    id: None,
+                },
+                list(false, &["Alpha", "Beta"]),
+                list(false, &["Gamma"]),
+                ContentBlock::List {
+                    ordered: true,
+                    items: vec![
+                        ListItem::task("Close the books", true).with_sublist(
+                            false,
+                            vec!["Accruals{{fn:1}}".into(), ListItem::from("Deferrals").with_sublist(true, vec!["Level three".into()])],
+                        ),
+                        ListItem::task("File the *report*", false),
+                        "Plain item".into(),
+                    ],
+                    id: None,
+                },
+                ContentBlock::Quote {
+                    text: "We delivered.\nAgain, with <margin>.".into(),
+                    attribution: Some("Chief Executive".to_string()),
+                    id: None,
+                },
+                ContentBlock::Code { language: None, text: "SELECT *\n  FROM ledger -- `x` ```\n\tWHERE a < b".to_string(), id: None },
+                ContentBlock::HorizontalRule,
+                ContentBlock::PageBreak,
+                footnote("fn:1", "Audited figures."),
+                footnote("fn:2", "Second note."),
+            ],
+            encrypted: None,
+        },
+        Section {
+            id: "data".to_string(),
+            title: Some("Data".to_string()),
+            content: vec![
+                table,
+                ContentBlock::Figure {
+                    id: "chart".to_string(),
+                    asset: CHART.to_string(),
+                    alt: "Revenue by quarter".to_string(),
+                    caption: Some("Figure 1: Revenue".to_string()),
+                    width: None,
+                },
+                ContentBlock::Heading { level: 4, text: "Outlook".into(), id: None },
+                paragraph("Growth continues."),
+            ],
+            encrypted: None,
+        },
+        Section {
+            id: "notes".to_string(),
+            title: None,
+            content: vec![paragraph("Untitled section body."), footnote("fn:3", "A note nothing cites.")],
+            encrypted: None,
+        },
+    ];
+    let document = Document::new(
+        "Annual Report 2025".to_string(),
+        "en".to_string(),
+        DocumentContent { sections },
+        String::new(),
+    );
+    let assets = HashMap::from([(CHART.to_string(), PNG.to_vec())]);
+    (document, assets)
+}
+
+/// Runs of inline text with `{{id}}` footnote markers as references,
+/// which the importers produce for either form
+fn runs(text: &InlineText) -> Vec<InlineRun> {
+    let mut runs = Vec::new();
+    for run in text.runs() {
+        let InlineRun::Text { text, marks, link } = run else {
+            runs.push(run);
+            continue;
+        };
+        let mut rest = text.as_str();
+        while let Some((before, after)) = rest.split_once("{{").and_then(|(b, a)| Some((b, a.split_once("}}")?))) {
+            runs.push(InlineRun::Text { text: before.to_string(), marks: marks.clone(), link: link.clone() });
+            runs.push(InlineRun::FootnoteRef { id: after.0.to_string() });
+            rest = after.1;
+        }
+        runs.push(InlineRun::Text { text: rest.to_string(), marks, link });
+    }
+    InlineText::from_runs(runs).runs()
+}
+
+fn item(list_item: &ListItem) -> String {
+    let sublist = list_item
+        .sublist
+        .as_ref()
+        .map(|list| format!("ordered={} {:?}", list.ordered, list.items.iter().map(item).collect::<Vec<_>>()));
+    format!("{:?} {:?} {:?}", list_item.checked, runs(&list_item.text), sublist)
+}
+
+/// A block with generated ids cleared and tables reduced to their text
+/// and column types, which is what the importers can recover
+fn normalize(block: &ContentBlock) -> String {
+    match block {
+        ContentBlock::Heading { level, text, .. } => format!("heading {} {:?}", level, runs(text)),
+        ContentBlock::Paragraph { text, .. } => format!("paragraph {:?}", runs(text)),
+        ContentBlock::List { ordered, items, .. } => {
+            format!("list ordered={} {:?}", ordered, items.iter().map(item).collect::<Vec<_>>())
+        }
+        ContentBlock::Quote { text, attribution, .. } => format!("quote {:?} {:?}", runs(text), attribution),
+        ContentBlock::Code { language, text, .. } => format!("code {:?} {:?}", language, text),
+        ContentBlock::Table { caption, columns, rows, footer, .. } => {
+            let columns: Vec<(String, CellType)> = columns.iter().map(|c| (c.header.clone(), c.cell_type.clone())).collect();
+            let rows: Vec<Vec<String>> = rows
+                .iter()
+                .map(|r| {
+                    (0..columns.len())
+                        .map(|i| r.cells.get(&format!("col_{}", i)).map(|v| v.display_text().to_string()).unwrap_or_default())
+                        .collect()
+                })
+                .collect();
+            format!("table {:?} {:?} {:?} {:?}", caption, columns, rows, footer)
+        }
+        ContentBlock::Figure { asset, alt, caption, width, .. } => {
+            format!("figure {:?} {:?} {:?} {:?}", asset, alt, caption, width)
+        }
+        ContentBlock::Footnote { id, text } => format!("footnote {:?} {:?}", id, text),
+        other => format!("{:?}", other),
+    }
+}
+
+fn structure(document: &Document) -> Vec<(Option<String>, Vec<String>)> {
+    document
+        .content
+        .sections
+        .iter()
+        .map(|s| (s.title.clone(), s.content.iter().map(normalize).collect()))
+        .collect()
+}
+
+#[test]
+fn test_markdown_round_trip() {
+    let (document, assets) = sample();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("report.md");
+    let written = write_markdown(&document, &assets, &path).unwrap();
+    assert_eq!(written, vec![path.clone(), dir.path().join(CHART)]);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let (imported, imported_assets) = markdown_to_document(&text, Some(dir.path()), "fallback").unwrap();
+
+    assert_eq!(imported.manifest.document.title, "Annual Report 2025");
+    let ids: Vec<&str> = imported.content.sections.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, ["summary", "data", "notes"]);
+    assert_eq!(structure(&imported), structure(&document));
+    assert_eq!(imported_assets, assets);
+}
+
+#[test]
+fn test_markdown_export_is_stable() {
+    let (document, _) = sample();
+    let first = render_markdown(&document);
+    let (imported, _) = markdown_to_document(&first, None, "fallback").unwrap();
+    assert_eq!(render_markdown(&imported), first);
+}
+
+#[test]
+fn test_docx_round_trip() {
+    let (document, assets) = sample();
+    let bytes = render_docx(&document, &assets).unwrap();
+    let (imported, imported_assets) = docx_to_document(std::io::Cursor::new(bytes), "fallback").unwrap();
+
+    assert_eq!(imported.manifest.document.title, "Annual Report 2025");
+    assert_eq!(structure(&imported), structure(&document));
+    assert_eq!(imported_assets, assets);
+}
+
+#[test]
+fn test_docx_without_title_keeps_file_title() {
+    let (mut document, assets) = sample();
+    document.manifest.document.title = String::new();
+    let bytes = render_docx(&document, &assets).unwrap();
+    let (imported, _) = docx_to_document(std::io::Cursor::new(bytes), "fallback").unwrap();
+
+    assert_eq!(imported.manifest.document.title, "fallback");
+    assert!(matches!(
+        &imported.content.sections[0].content[0],
+        ContentBlock::Heading { level: 1, text, .. } if text.plain_text() == "fallback"
+    ));
+}
+
+#[test]
+fn test_markdown_marks_that_cannot_be_delimiters() {
+    // Emphasis next to punctuation or other emphasis falls back to HTML tags
+    let text = InlineText::from_runs([
+        InlineRun::text("Total"),
+        InlineRun::marked("$5", &[Mark::Bold]),
+        InlineRun::marked("x", &[Mark::Italic]),
+        InlineRun::marked("y", &[Mark::Bold]),
+        InlineRun::marked("`tick`", &[Mark::Code]),
+        InlineRun::text("end"),
+    ]);
+    let mut document = sample().0;
+    document.content.sections[0].content = vec![ContentBlock::Paragraph { text: text.clone(), id: None, xbrl: None }];
+    let markdown = render_markdown(&document);
+    assert!(markdown.contains("Total<strong>$5</strong><em>x</em>**y**`` `tick` ``end"), "{}", markdown);
+
+    let (imported, _) = markdown_to_document(&markdown, None, "fallback").unwrap();
+    assert!(matches!(
+        &imported.content.sections[0].content[0],
+        ContentBlock::Paragraph { text: imported, .. } if *imported == text
+    ));
+}
+
+#[test]
+fn test_markdown_code_keeps_language() {
+    let mut document = sample().0;
+    let code = ContentBlock::Code { language: Some("sql".to_string()), text: "SELECT 1;".to_string(), id: None };
+    document.content.sections[0].content = vec![code, list(false, &["- [ ] not a task"])];
+    let markdown = render_markdown(&document);
+    assert!(markdown.contains("```sql\nSELECT 1;\n```"), "{}", markdown);
+
+    let (imported, _) = markdown_to_document(&markdown, None, "fallback").unwrap();
+    let content = &imported.content.sections[0].content;
+    assert!(matches!(&content[0], ContentBlock::Code { language: Some(language), .. } if language == "sql"));
+    assert!(matches!(&content[1], ContentBlock::List { items, .. } if items[0].checked.is_none() && items[0].text.plain_text() == "- [ ] not a task"));
+}
+
+/// Text cells by column index; empty strings are left out
+fn text_row(values: &[&str]) -> TableRow {
+    TableRow::new(
+        values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(i, v)| (format!("col_{}", i), CellValue::Text(v.to_string())))
+            .collect(),
+    )
+}
+
+/// Header rows, then per row its role, level, spans and cell texts
+fn table_structure(block: &ContentBlock) -> (Option<Vec<Vec<HeaderCell>>>, Vec<String>) {
+    let ContentBlock::Table { columns, header_rows, rows, .. } = block else { panic!("not a table: {:?}", block) };
+    let rows = rows
+        .iter()
+        .map(|r| {
+            let mut spans: Vec<(&String, &CellSpan)> = r.format.spans.iter().collect();
+            spans.sort_by_key(|(column, _)| *column);
+            let cells: Vec<&str> = columns.iter().map(|c| r.cells.get(&c.id).map_or("", |v| v.display_text())).collect();
+            format!("{:?} {} {:?} {:?}", r.format.role, r.format.level, spans, cells)
+        })
+        .collect();
+    (header_rows.clone(), rows)
+}
+
+#[test]
+fn test_docx_keeps_merged_cells_header_rows_and_row_roles() {
+    let mut document = sample().0;
+    let columns = vec![column(0, "Region", CellType::Text), column(1, "Q1", CellType::Text), column(2, "Q2", CellType::Text)];
+    let table = ContentBlock::Table {
+        id: "regions".to_string(),
+        caption: Some("Revenue by region".to_string()),
+        columns,
+        header_rows: Some(vec![vec![HeaderCell::new("", 1), HeaderCell::new("FY2025", 2)]]),
+        rows: vec![
+            text_row(&["Europe", "10", "12"]).with_span("col_0", 1, 2),
+            text_row(&["", "Not split"]).with_span("col_1", 2, 1).with_level(1),
+            text_row(&["Subtotal", "10", "12"]).with_role(RowRole::Subtotal),
+            text_row(&["Total", "20", "24"]).with_role(RowRole::Total),
+        ],
+        footer: None,
+    };
+    document.content.sections[0].content = vec![table.clone()];
+    let bytes = render_docx(&document, &HashMap::new()).unwrap();
+    let (imported, _) = docx_to_document(std::io::Cursor::new(bytes), "fallback").unwrap();
+
+    let imported = &imported.content.sections[0].content[0];
+    assert_eq!(table_structure(imported), table_structure(&table));
+    assert!(imported.validate().is_ok());
+}
+
+/// Write a one-sheet XLSX workbook named "Regions" to `path`
+fn write_xlsx(path: &std::path::Path, sheet: &str) {
+    let parts = [
+        (
+            "[Content_Types].xml",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+             <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+             <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+             <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+             <Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/></Types>",
+        ),
+        (
+            "_rels/.rels",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+             <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/></Relationships>",
+        ),
+        (
+            "xl/workbook.xml",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+             xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"><sheets><sheet name=\"Regions\" sheetId=\"1\" r:id=\"rId1\"/></sheets></workbook>",
+        ),
+        (
+            "xl/_rels/workbook.xml.rels",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+             <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/></Relationships>",
+        ),
+        ("xl/worksheets/sheet1.xml", sheet),
+    ];
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for (name, data) in parts {
+        zip.start_file(name, zip::write::FileOptions::default()).unwrap();
+        zip.write_all(data.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+/// Import a one-sheet workbook and read back its table
+fn import_xlsx(sheet: &str) -> ContentBlock {
+    let dir = tempfile::tempdir().unwrap();
+    let (input, output) = (dir.path().join("regions.xlsx"), dir.path().join("regions.tdf"));
+    write_xlsx(&input, sheet);
+    convert_excel_to_tdf(&input, &output, None, None, None).unwrap();
+    let (mut document, _, _) = ArchiveReader::read(&output).unwrap();
+    document.content.sections.remove(0).content.remove(0)
+}
+
+fn inline_cell(r: &str, text: &str) -> String {
+    format!("<c r=\"{}\" t=\"inlineStr\"><is><t>{}</t></is></c>", r, text)
+}
+
+fn number_cell(r: &str, value: f64) -> String {
+    format!("<c r=\"{}\"><v>{}</v></c>", r, value)
+}
+
+fn worksheet(body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">{}</worksheet>",
+        body
+    )
+}
+
+#[test]
+fn test_excel_import_reads_merged_ranges_and_outline_levels() {
+    let (cell, number) = (inline_cell, number_cell);
+    let table = import_xlsx(&worksheet(&format!(
+        "<sheetData>\
+         <row r=\"1\">{}{}</row><row r=\"2\">{}{}</row>\
+         <row r=\"3\">{}{}{}</row><row r=\"4\" outlineLevel=\"1\">{}{}{}</row>\
+         <row r=\"5\" outlineLevel=\"1\">{}</row><row r=\"6\">{}{}{}</row>\
+         </sheetData><mergeCells count=\"4\"><mergeCell ref=\"A1:A2\"/><mergeCell ref=\"B1:C1\"/>\
+         <mergeCell ref=\"A4:A5\"/><mergeCell ref=\"B5:C5\"/></mergeCells>",
+        cell("A1", "Region"),
+        cell("B1", "FY2025"),
+        cell("B2", "Q1"),
+        cell("C2", "Q2"),
+        cell("A3", "Revenue"),
+        number("B3", 30.0),
+        number("C3", 36.0),
+        cell("A4", "Europe"),
+        number("B4", 10.0),
+        number("C4", 12.0),
+        cell("B5", "Not split"),
+        cell("A6", "Total revenue"),
+        number("B6", 40.0),
+        number("C6", 48.0),
+    )));
+
+    let (header_rows, rows) = table_structure(&table);
+    assert_eq!(header_rows, Some(vec![vec![HeaderCell::new("", 1), HeaderCell::new("FY2025", 2)]]));
+    let ContentBlock::Table { columns, .. } = &table else { unreachable!() };
+    let headers: Vec<&str> = columns.iter().map(|c| c.header.as_str()).collect();
+    assert_eq!(headers, ["Region", "Q1", "Q2"]);
+    assert_eq!(
+        rows,
+        [
+            r#"Data 0 [] ["Revenue", "30", "36"]"#,
+            r#"Data 1 [("col_0", CellSpan { colspan: 1, rowspan: 2 })] ["Europe", "10", "12"]"#,
+            r#"Data 1 [("col_1", CellSpan { colspan: 2, rowspan: 1 })] ["", "Not split", ""]"#,
+            r#"Total 0 [] ["Total revenue", "40", "48"]"#,
+        ]
+    );
+}
+
+#[test]
+fn test_excel_import_keeps_formulas_over_the_table() {
+    let formula = |r: &str, f: &str, value: f64| format!("<c r=\"{}\"><f>{}</f><v>{}</v></c>", r, f, value);
+    let table = import_xlsx(&worksheet(&format!(
+        "<sheetData><row r=\"1\">{}{}</row><row r=\"2\">{}{}</row><row r=\"3\">{}{}</row>\
+         <row r=\"4\">{}{}</row><row r=\"5\">{}{}</row></sheetData>",
+        inline_cell("A1", "Item"),
+        inline_cell("B1", "Amount"),
+        inline_cell("A2", "Rent"),
+        number_cell("B2", 1200.5),
+        inline_cell("A3", "Power"),
+        number_cell("B3", 300.0),
+        inline_cell("A4", "Total"),
+        formula("B4", "SUM($B$2:B3)", 1500.5),
+        inline_cell("A5", "Looked up"),
+        formula("B5", "VLOOKUP(A2,A2:B3,2)", 1200.5),
+    )));
+
+    let ContentBlock::Table { columns, rows, .. } = &table else { panic!("not a table") };
+    assert_eq!(
+        rows[2].cells["col_1"],
+        CellValue::Formula { formula: "SUM(B1:B2)".to_string(), raw: "1500.5".parse().unwrap(), display: "1500.5".to_string() }
+    );
+    // Functions the evaluator does not know keep only their value
+    assert!(matches!(rows[3].cells["col_1"], CellValue::Number { raw, .. } | CellValue::Currency { raw, .. } if raw.to_string() == "1200.5"));
+    assert!(tdf_core::formula::check_table("sheet_0", columns, rows).iter().all(|check| !check.failed()));
+}
+
+fn tagged_report() -> Document {
+    let fy = |year: i32| XbrlPeriod::Duration {
+        start: chrono::NaiveDate::from_ymd_opt(year, 1, 1).unwrap(),
+        end: chrono::NaiveDate::from_ymd_opt(year, 12, 31).unwrap(),
+    };
+    let tag = |concept: &str, year: i32, decimals: Option<i32>| XbrlTag {
+        concept: concept.to_string(),
+        period: fy(year),
+        entity: None,
+        unit: None,
+        decimals,
+    };
+    let euros = |id: &str, header: &str| TableColumn {
+        id: id.to_string(),
+        header: header.to_string(),
+        cell_type: CellType::Currency,
+        currency: Some("EUR".to_string()),
+    };
+    let columns = vec![column(0, "Line item", CellType::Text), euros("col_1", "2024"), euros("col_2", "2023")];
+    let line = |label: &str, values: [(&str, &str); 2]| {
+        let mut cells = HashMap::from([("col_0".to_string(), CellValue::Text(label.to_string()))]);
+        for (i, (raw, display)) in values.into_iter().enumerate() {
+            cells.insert(format!("col_{}", i + 1), number(raw, display));
+        }
+        TableRow::new(cells)
+    };
+    let rows = vec![
+        line("Revenue", [("4250000", "€4,250K"), ("3980000", "€3,980K")])
+            .with_tag("col_1", tag("ifrs-full:Revenue", 2024, Some(-3)))
+            .with_tag("col_2", tag("ifrs-full:Revenue", 2023, Some(-3))),
+        line("Loss for the year", [("-125000.50", "(125,000.50)"), ("0", "\u{2014}")])
+            .with_tag("col_1", tag("ifrs-full:ProfitLoss", 2024, None))
+            .with_tag("col_2", tag("ifrs-full:ProfitLoss", 2023, None)),
+    ];
+    let sections = vec![Section {
+        id: "statements".to_string(),
+        title: Some("Statements".to_string()),
+        content: vec![
+            ContentBlock::Table { id: "income".to_string(), caption: None, columns, header_rows: None, rows, footer: None },
+            ContentBlock::Paragraph {
+                text: "Example Holdings SA".into(),
+                id: Some("entity-name".to_string()),
+                xbrl: Some(tag("ifrs-full:NameOfReportingEntity", 2024, None)),
+            },
+        ],
+        encrypted: None,
+    }];
+    let mut document = Document::new("Annual report".to_string(), "en".to_string(), DocumentContent { sections }, String::new());
+    document.manifest.xbrl = Some(XbrlInfo {
+        schema_refs: vec!["https://xbrl.ifrs.org/taxonomy/2024-03-27/full_ifrs_entry_point_2024-03-27.xsd".to_string()],
+        namespaces: [("ifrs-full".to_string(), "https://xbrl.ifrs.org/taxonomy/2024-03-27/ifrs-full".to_string())].into(),
+        entity: Some(XbrlEntity { scheme: "http://standards.iso.org/iso/17442".to_string(), identifier: "5299000J2N45DDNE4Y28".to_string() }),
+    });
+    document
+}
+
+/// Facts as comparable text, however their values are shown
+fn fact_keys(facts: &[Fact]) -> Vec<String> {
+    let mut keys: Vec<String> = facts
+        .iter()
+        .map(|fact| {
+            let value = match &fact.value {
+                FactValue::Numeric { value, unit, decimals, .. } => format!("{} {} {:?}", value.normalize(), unit, decimals),
+                FactValue::Text(text) => text.clone(),
+            };
+            format!("{} {} {} = {}", fact.concept, fact.entity.identifier, fact.period, value)
+        })
+        .collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn test_xbrl_instance_round_trip() {
+    let document = tagged_report();
+    document.validate().unwrap();
+    let facts = tdf_core::xbrl::facts(&document).unwrap();
+    assert_eq!(facts.len(), 5);
+
+    let imported = xbrl_to_document(&render_instance(&document).unwrap(), "Annual report").unwrap();
+    imported.validate().unwrap();
+    assert_eq!(imported.manifest.xbrl, document.manifest.xbrl);
+    assert_eq!(fact_keys(&tdf_core::xbrl::facts(&imported).unwrap()), fact_keys(&facts));
+}
+
+#[test]
+fn test_inline_xbrl_marks_up_shown_amounts() {
+    let page = render_inline(&tagged_report()).unwrap();
+    assert!(page.contains(
+        "€<ix:nonFraction name=\"ifrs-full:Revenue\" contextRef=\"ctx1\" unitRef=\"unit1\" decimals=\"-3\" \
+         format=\"ixt:num-dot-decimal\" scale=\"3\">4,250</ix:nonFraction>K"
+    ));
+    assert!(page.contains("sign=\"-\">125,000.50</ix:nonFraction>)"));
+    assert!(page.contains("format=\"ixt:fixed-zero\" scale=\"0\">\u{2014}</ix:nonFraction>"));
+    assert!(page.contains("<ix:nonNumeric name=\"ifrs-full:NameOfReportingEntity\" contextRef=\"ctx1\">Example Holdings SA</ix:nonNumeric>"));
+
+    // A document without a taxonomy has nothing to file
+    let mut untagged = tagged_report();
+    untagged.manifest.xbrl = None;
+    assert!(render_instance(&untagged).is_err());
+}
@@ -1,10 +1,17 @@
 use crate::document::Document;
 use crate::error::{TdfError, TdfResult};
 use crate::merkle::{HashAlgorithm, MerkleTree};
-use crate::signature::{SignatureBlock, SignatureManager, SignatureScope};
+use crate::signature::{DocumentSigner, SignatureBlock, SignatureManager, SignatureScope, SignerOutput};
 use crate::timestamp::{TimestampProvider, verify_timestamp_token_with_config, TimestampValidationConfig};
 use crate::revocation::{RevocationList, RevocationManager};
 use crate::config::SecurityConfig;
+use crate::certificate::{verify_signature_certificate, CertificateChain, SignerCertificateReport, TrustAnchors};
+use crate::encryption::{
+    decrypt_sections, ContentKey, EncryptionConfig, EncryptionHeader, EncryptionInfo, Identity,
+    SectionEncryptionConfig, SectionReport,
+};
+use crate::lint;
+use crate::redaction::{self, RedactionInfo, RedactionSalts, REDACTION_FILE};
 use ed25519_dalek::SigningKey;
 use k256::ecdsa::SigningKey as Secp256k1SigningKey;
 // CBOR helpers using ciborium (replaces unmaintained serde_cbor)
@@ -17,7 +24,7 @@ fn cbor_to_vec<T: serde::Serialize>(value: &T) -> crate::error::TdfResult<Vec<u8
 fn cbor_from_slice<T: serde::de::DeserializeOwned>(data: &[u8]) -> crate::error::TdfResult<T> {
     ciborium::from_reader(data).map_err(|e| e.into())
 }
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
@@ -32,6 +39,7 @@ const DATA_FILE: &str = "data.json";
 const HASHES_FILE: &str = "hashes.bin";
 const SIGNATURES_FILE: &str = "signatures.cbor";
 const REVOCATION_FILE: &str = "revocation.cbor";
+const ENCRYPTION_FILE: &str = "encryption.cbor";
 const ASSETS_IMAGES_DIR: &str = "assets/images/";
 const ASSETS_FONTS_DIR: &str = "assets/fonts/";
 
@@ -40,6 +48,10 @@ pub struct ArchiveBuilder {
     assets: HashMap<String, Vec<u8>>,
     revocation_list: Option<RevocationList>,
     security_config: SecurityConfig,
+    signer_certificate: Option<CertificateChain>,
+    encryption: Option<EncryptionConfig>,
+    section_encryption: Option<SectionEncryptionConfig>,
+    redactable: bool,
 }
 
 impl ArchiveBuilder {
@@ -49,6 +61,10 @@ impl ArchiveBuilder {
             assets: HashMap::new(),
             revocation_list: None,
             security_config: SecurityConfig::default(),
+            signer_certificate: None,
+            encryption: None,
+            section_encryption: None,
+            redactable: false,
         }
     }
 
@@ -62,6 +78,49 @@ impl ArchiveBuilder {
         self
     }
 
+    /// Attach the signer's X.509 certificate chain (leaf first) to the signature
+    ///
+    /// The leaf certificate key must be the key used for signing; this is
+    /// checked when the archive is built.
+    pub fn with_signer_certificate(mut self, chain: CertificateChain) -> Self {
+        self.signer_certificate = Some(chain);
+        self
+    }
+
+    /// Encrypt the payload for the given recipients
+    ///
+    /// Content, styles, layout, data and assets are encrypted with a random
+    /// content key that is wrapped for each recipient. The manifest, Merkle
+    /// tree and signatures stay readable, so the archive verifies without
+    /// decrypting. Documents without a classification are marked
+    /// `Confidential`.
+    pub fn with_encryption(mut self, config: EncryptionConfig) -> Self {
+        self.encryption = Some(config);
+        self
+    }
+
+    /// Encrypt individual sections for different audience groups
+    ///
+    /// Each assigned section is replaced by a placeholder holding its
+    /// ciphertext. Since the ciphertext is part of the content component,
+    /// one signature covers every section, and readers outside a group can
+    /// still verify the whole document. Can be combined with `with_encryption`.
+    pub fn with_section_encryption(mut self, config: SectionEncryptionConfig) -> Self {
+        self.section_encryption = Some(config);
+        self
+    }
+
+    /// Commit to each content block and table cell individually
+    ///
+    /// The content component of the Merkle tree becomes a skeleton of
+    /// salted block commitments, so blocks and cells can later be removed
+    /// with `redaction::redact_archive` without invalidating signatures.
+    /// Cannot be combined with `with_encryption`.
+    pub fn with_redaction(mut self) -> Self {
+        self.redactable = true;
+        self
+    }
+
     /// Add an asset to the archive
     ///
     /// Security Fix (CVE-TDF-021): Returns Result to allow callers to handle
@@ -127,32 +186,134 @@ impl ArchiveBuilder {
         signature_algorithm: Option<crate::signature::SignatureAlgorithm>,
         timestamp_provider: Option<&dyn TimestampProvider>,
     ) -> TdfResult<()> {
+        let algo = signature_algorithm.unwrap_or_else(|| {
+            if secp256k1_key.is_some() {
+                crate::signature::SignatureAlgorithm::Secp256k1
+            } else {
+                crate::signature::SignatureAlgorithm::Ed25519
+            }
+        });
+
+        let signer: Option<&dyn DocumentSigner> = match algo {
+            crate::signature::SignatureAlgorithm::Ed25519 => ed25519_key.map(|k| k as &dyn DocumentSigner),
+            crate::signature::SignatureAlgorithm::Secp256k1 => secp256k1_key.map(|k| k as &dyn DocumentSigner),
+            _ => None,
+        };
+
+        let identity = signer_id.zip(signer_name);
+        self.build_internal(output_path, signer.zip(identity), timestamp_provider)
+    }
+
+    /// Build the archive and sign it with an external signer
+    ///
+    /// The private key never has to be loaded into this process: the signer
+    /// (HSM, PKCS#11 token, remote signing service) receives only the v2
+    /// signing payload.
+    ///
+    /// # Arguments
+    /// * `output_path` - Where to write the archive
+    /// * `signer` - The signer producing the document signature
+    /// * `signer_id` - Signer DID
+    /// * `signer_name` - Display name of the signer
+    /// * `timestamp_provider` - Optional timestamp source
+    pub fn build_with_signer(
+        &mut self,
+        output_path: &Path,
+        signer: &dyn DocumentSigner,
+        signer_id: String,
+        signer_name: String,
+        timestamp_provider: Option<&dyn TimestampProvider>,
+    ) -> TdfResult<()> {
+        self.build_internal(output_path, Some((signer, (signer_id, signer_name))), timestamp_provider)
+    }
+
+    fn build_internal(
+        &mut self,
+        output_path: &Path,
+        signing: Option<(&dyn DocumentSigner, (String, String))>,
+        timestamp_provider: Option<&dyn TimestampProvider>,
+    ) -> TdfResult<()> {
 
         // Validate document
         self.document.validate()?;
+        let asset_entries: HashSet<String> = self.assets.keys().map(|path| asset_archive_path(path)).collect();
+        lint::lint_document(&self.document, Some(&asset_entries)).into_result()?;
+
+        // Encrypt sections for their audience groups (before hashing content)
+        if let Some(config) = self.section_encryption.take() {
+            config.apply(&mut self.document)?;
+        }
+
+        if self.redactable && self.encryption.is_some() {
+            return Err(TdfError::InvalidDocument(
+                "Redactable documents cannot use document-level encryption".to_string(),
+            ));
+        }
+        self.document.manifest.redaction = self.redactable.then(RedactionInfo::default);
+
+        // Prepare encryption (the manifest records the cipher, so set it before hashing)
+        let encryption = match self.encryption {
+            Some(ref config) => {
+                let content_key = ContentKey::generate(config.cipher)?;
+                let header = content_key.seal_header(&config.recipients)?;
+                self.document.manifest.encryption = Some(EncryptionInfo::new(config.cipher));
+                if self.document.manifest.classification.is_none() {
+                    self.document.manifest.classification = Some(crate::document::Classification::Confidential);
+                }
+                Some((content_key, cbor_to_vec(&header)?))
+            }
+            None => {
+                self.document.manifest.encryption = None;
+                None
+            }
+        };
+        let document_id = self.document.manifest.document.id.clone();
+        let seal = |name: &str, bytes: Vec<u8>| -> TdfResult<Vec<u8>> {
+            match encryption {
+                Some((ref content_key, _)) => content_key.encrypt_component(&document_id, name, &bytes),
+                None => Ok(bytes),
+            }
+        };
 
-        // Serialize components
+        // Serialize components (the manifest is hashed without its root hash,
+        // which may be set when rebuilding a document read from an archive)
+        self.document.manifest.integrity.root_hash = String::new();
         let manifest_bytes = cbor_to_vec(&self.document.manifest)?;
-        let content_bytes = cbor_to_vec(&self.document.content)?;
-        let styles_bytes = self.document.styles.as_bytes().to_vec();
+        let content_bytes = seal(CONTENT_FILE, cbor_to_vec(&self.document.content)?)?;
+
+        // Redactable content is hashed as its skeleton of salted block commitments
+        let (content_leaf, redaction_bytes) = if self.redactable {
+            let content = redaction::content_value(&content_bytes)?;
+            let salts = RedactionSalts::generate(&content)?;
+            let commitment = redaction::content_commitment(&content, &salts)?;
+            (commitment.leaf, Some(cbor_to_vec(&salts)?))
+        } else {
+            (content_bytes.clone(), None)
+        };
+        let styles_bytes = seal(STYLES_FILE, self.document.styles.as_bytes().to_vec())?;
 
         let layout_bytes = if let Some(ref layout) = self.document.layout {
-            Some(cbor_to_vec(layout)?)
+            Some(seal(LAYOUT_FILE, cbor_to_vec(layout)?)?)
         } else {
             None
         };
 
         let data_bytes = if let Some(ref data) = self.document.data {
-            Some(serde_json::to_vec(data)?)
+            Some(seal(DATA_FILE, serde_json::to_vec(data)?)?)
         } else {
             None
         };
 
+        let mut assets = HashMap::new();
+        for (path, data) in &self.assets {
+            assets.insert(path.clone(), seal(&asset_archive_path(path), data.clone())?);
+        }
+
         // Build component map for Merkle tree (before updating manifest with root hash)
         let mut components = HashMap::new();
         // Use original manifest bytes (without root hash)
         components.insert("manifest".to_string(), manifest_bytes.clone());
-        components.insert("content".to_string(), content_bytes.clone());
+        components.insert("content".to_string(), content_leaf);
         components.insert("styles".to_string(), styles_bytes.clone());
 
         if let Some(ref layout) = layout_bytes {
@@ -164,7 +325,7 @@ impl ArchiveBuilder {
         }
 
         // Add asset hashes
-        for (path, data) in &self.assets {
+        for (path, data) in &assets {
             components.insert(format!("asset:{}", path), data.clone());
         }
 
@@ -184,49 +345,20 @@ impl ArchiveBuilder {
 
         // Create signatures
         let mut signatures = Vec::new();
-        let algo = signature_algorithm.unwrap_or_else(|| {
-            if secp256k1_key.is_some() {
-                crate::signature::SignatureAlgorithm::Secp256k1
-            } else {
-                crate::signature::SignatureAlgorithm::Ed25519
-            }
-        });
-
-        if let (Some(id), Some(name)) = (signer_id, signer_name) {
-            let signature = match algo {
-                crate::signature::SignatureAlgorithm::Ed25519 => {
-                    if let Some(key) = ed25519_key {
-                        Some(SignatureManager::sign_ed25519_with_timestamp(
-                            key,
-                            &root_hash,
-                            id.clone(),
-                            name.clone(),
-                            SignatureScope::Full,
-                            timestamp_provider,
-                        ))
-                    } else {
-                        None
-                    }
-                }
-                crate::signature::SignatureAlgorithm::Secp256k1 => {
-                    if let Some(key) = secp256k1_key {
-                        // secp256k1 signing with timestamp (similar pattern)
-                        Some(SignatureManager::sign_secp256k1(
-                            key,
-                            &root_hash,
-                            id.clone(),
-                            name.clone(),
-                            SignatureScope::Full,
-                        ))
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            };
-            if let Some(sig) = signature {
-                signatures.push(sig);
+        if let Some((signer, (id, name))) = signing {
+            let (mut sig, output) = SignatureManager::sign_with_signer(
+                signer,
+                &root_hash,
+                id,
+                name,
+                SignatureScope::Full,
+                timestamp_provider,
+            )?;
+            if let Some(ref chain) = self.signer_certificate {
+                check_certificate_key(chain, &output)?;
+                sig.signer.certificate = Some(chain.to_pem());
             }
+            signatures.push(sig);
         }
 
         let signature_block = SignatureBlock { signatures };
@@ -243,7 +375,9 @@ impl ArchiveBuilder {
             styles_bytes.len() as u64,
             layout_bytes.as_ref().map(|b| b.len() as u64).unwrap_or(0),
             data_bytes.as_ref().map(|b| b.len() as u64).unwrap_or(0),
-            self.assets.values().map(|v| v.len() as u64).sum::<u64>(),
+            assets.values().map(|v| v.len() as u64).sum::<u64>(),
+            encryption.as_ref().map(|(_, header)| header.len() as u64).unwrap_or(0),
+            redaction_bytes.as_ref().map(|b| b.len() as u64).unwrap_or(0),
             hashes_binary.len() as u64,
             signatures_bytes.len() as u64,
             self.revocation_list.as_ref().map(|r| RevocationManager::to_cbor(r).unwrap().len() as u64).unwrap_or(0),
@@ -299,21 +433,21 @@ impl ArchiveBuilder {
             zip.write_all(&revocation_bytes)?;
         }
 
-        // Write assets
-        for (path, data) in &self.assets {
-            let full_path = if path.starts_with("assets/images/") {
-                path.clone()
-            } else if path.starts_with("assets/fonts/") {
-                path.clone()
-            } else if path.ends_with(".webp") || path.ends_with(".avif") || path.ends_with(".png") {
-                format!("{}{}", ASSETS_IMAGES_DIR, path)
-            } else if path.ends_with(".woff2") {
-                format!("{}{}", ASSETS_FONTS_DIR, path)
-            } else {
-                format!("assets/{}", path)
-            };
+        // Write wrapped content keys (if encrypted)
+        if let Some((_, ref header_bytes)) = encryption {
+            zip.start_file(ENCRYPTION_FILE, options)?;
+            zip.write_all(header_bytes)?;
+        }
+
+        // Write block and cell salts (if redactable)
+        if let Some(ref salts) = redaction_bytes {
+            zip.start_file(REDACTION_FILE, options)?;
+            zip.write_all(salts)?;
+        }
 
-            zip.start_file(&full_path, options)?;
+        // Write assets
+        for (path, data) in &assets {
+            zip.start_file(asset_archive_path(path), options)?;
             zip.write_all(data)?;
         }
 
@@ -323,188 +457,255 @@ impl ArchiveBuilder {
     }
 }
 
-pub struct ArchiveReader;
-
-impl ArchiveReader {
-    pub fn read(path: &Path) -> TdfResult<(Document, MerkleTree, SignatureBlock)> {
-        let file = File::open(path)?;
-        let mut zip = zip::ZipArchive::new(file)?;
-
-        // Read manifest
-        let manifest_bytes = {
-            let mut manifest_file = zip.by_name(MANIFEST_FILE)
-                .map_err(|_| TdfError::MissingFile(MANIFEST_FILE.to_string()))?;
-            let mut bytes = Vec::new();
-            manifest_file.read_to_end(&mut bytes)?;
-            bytes
-        };
-        let manifest: crate::document::Manifest = cbor_from_slice(&manifest_bytes)?;
+/// Archive entry name for an asset path
+fn asset_archive_path(path: &str) -> String {
+    if path.starts_with(ASSETS_IMAGES_DIR) || path.starts_with(ASSETS_FONTS_DIR) {
+        path.to_string()
+    } else if path.ends_with(".webp") || path.ends_with(".avif") || path.ends_with(".png") {
+        format!("{}{}", ASSETS_IMAGES_DIR, path)
+    } else if path.ends_with(".woff2") {
+        format!("{}{}", ASSETS_FONTS_DIR, path)
+    } else {
+        format!("assets/{}", path)
+    }
+}
 
-        // Read content
-        let content_bytes = {
-            let mut content_file = zip.by_name(CONTENT_FILE)
-                .map_err(|_| TdfError::MissingFile(CONTENT_FILE.to_string()))?;
-            let mut bytes = Vec::new();
-            content_file.read_to_end(&mut bytes)?;
-            bytes
-        };
-        let content: crate::content::DocumentContent = cbor_from_slice(&content_bytes)?;
+/// Check that the certificate's leaf key is the key that produced the signature
+fn check_certificate_key(chain: &CertificateChain, output: &SignerOutput) -> TdfResult<()> {
+    match output.algorithm {
+        crate::signature::SignatureAlgorithm::Ed25519 => {
+            let key: [u8; 32] = output.public_key.as_slice().try_into().map_err(|_| {
+                TdfError::SignerError("Ed25519 public key must be 32 bytes".to_string())
+            })?;
+            let key = ed25519_dalek::VerifyingKey::from_bytes(&key)
+                .map_err(|e| TdfError::SignerError(format!("Invalid Ed25519 public key: {}", e)))?;
+            chain.check_leaf_key_ed25519(&key)
+        }
+        crate::signature::SignatureAlgorithm::Secp256k1 => {
+            let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(&output.public_key)
+                .map_err(|e| TdfError::SignerError(format!("Invalid secp256k1 public key: {}", e)))?;
+            chain.check_leaf_key_secp256k1(&key)
+        }
+        _ => Ok(()),
+    }
+}
 
-        // Read styles
-        let styles = {
-            let mut styles_file = zip.by_name(STYLES_FILE)
-                .map_err(|_| TdfError::MissingFile(STYLES_FILE.to_string()))?;
-            let mut s = String::new();
-            styles_file.read_to_string(&mut s)?;
-            s
-        };
+pub struct ArchiveReader;
 
-        // Read layout (optional)
-        let layout = {
-            if let Ok(mut layout_file) = zip.by_name(LAYOUT_FILE) {
-                let mut layout_bytes = Vec::new();
-                layout_file.read_to_end(&mut layout_bytes)?;
-                Some(cbor_from_slice(&layout_bytes)?)
-            } else {
-                None
-            }
-        };
+type Zip = zip::ZipArchive<File>;
 
-        // Read data (optional)
-        let data = {
-            if let Ok(mut data_file) = zip.by_name(DATA_FILE) {
-                let mut data_bytes = Vec::new();
-                data_file.read_to_end(&mut data_bytes)?;
-                Some(serde_json::from_slice(&data_bytes)?)
-            } else {
-                None
-            }
-        };
+/// Read an archive entry, failing with `MissingFile` if absent
+fn read_entry(zip: &mut Zip, name: &str) -> TdfResult<Vec<u8>> {
+    let mut file = zip.by_name(name).map_err(|_| TdfError::MissingFile(name.to_string()))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
 
-        // Read hashes
-        let hashes_bytes = {
-            let mut hashes_file = zip.by_name(HASHES_FILE)
-                .map_err(|_| TdfError::MissingFile(HASHES_FILE.to_string()))?;
+/// Read an optional archive entry
+fn read_optional_entry(zip: &mut Zip, name: &str) -> TdfResult<Option<Vec<u8>>> {
+    match zip.by_name(name) {
+        Ok(mut file) => {
             let mut bytes = Vec::new();
-            hashes_file.read_to_end(&mut bytes)?;
-            bytes
-        };
-        let merkle_tree = MerkleTree::from_binary(&hashes_bytes)?;
+            file.read_to_end(&mut bytes)?;
+            Ok(Some(bytes))
+        }
+        Err(_) => Ok(None),
+    }
+}
 
-        // Read signatures
-        let signatures_bytes = {
-            let mut signatures_file = zip.by_name(SIGNATURES_FILE)
-                .map_err(|_| TdfError::MissingFile(SIGNATURES_FILE.to_string()))?;
-            let mut bytes = Vec::new();
-            signatures_file.read_to_end(&mut bytes)?;
-            bytes
-        };
-        let signature_block: SignatureBlock = cbor_from_slice(&signatures_bytes)?;
+/// Manifest, Merkle tree and signatures (readable without decrypting)
+fn read_envelope(zip: &mut Zip) -> TdfResult<(crate::document::Manifest, MerkleTree, SignatureBlock)> {
+    let manifest = cbor_from_slice(&read_entry(zip, MANIFEST_FILE)?)?;
+    let merkle_tree = MerkleTree::from_binary(&read_entry(zip, HASHES_FILE)?)?;
+    let signature_block = cbor_from_slice(&read_entry(zip, SIGNATURES_FILE)?)?;
+    Ok((manifest, merkle_tree, signature_block))
+}
 
-        let document = Document {
-            manifest,
-            content,
-            styles,
-            layout,
-            data,
-        };
+/// Content key of an encrypted archive, `None` for plain archives
+fn open_content_key(
+    zip: &mut Zip,
+    manifest: &crate::document::Manifest,
+    identity: Option<&Identity>,
+) -> TdfResult<Option<ContentKey>> {
+    let Some(ref info) = manifest.encryption else {
+        return Ok(None);
+    };
+    let identity = identity.ok_or_else(|| {
+        TdfError::EncryptionError("Document is encrypted; a recipient key or passphrase is required".to_string())
+    })?;
+    let header: EncryptionHeader = cbor_from_slice(&read_entry(zip, ENCRYPTION_FILE)?)?;
+    ContentKey::open_header(&header, info, identity).map(Some)
+}
 
-        Ok((document, merkle_tree, signature_block))
+fn read_document(zip: &mut Zip, identity: Option<&Identity>) -> TdfResult<(Document, MerkleTree, SignatureBlock)> {
+    let (manifest, merkle_tree, signature_block) = read_envelope(zip)?;
+    let content_key = open_content_key(zip, &manifest, identity)?;
+    let document_id = manifest.document.id.clone();
+    let open = |name: &str, bytes: Vec<u8>| -> TdfResult<Vec<u8>> {
+        match content_key {
+            Some(ref key) => key.decrypt_component(&document_id, name, &bytes),
+            None => Ok(bytes),
+        }
+    };
+
+    let content = cbor_from_slice(&open(CONTENT_FILE, read_entry(zip, CONTENT_FILE)?)?)?;
+    let styles = String::from_utf8(open(STYLES_FILE, read_entry(zip, STYLES_FILE)?)?)
+        .map_err(|_| TdfError::InvalidDocument("styles.css is not valid UTF-8".to_string()))?;
+    let layout = match read_optional_entry(zip, LAYOUT_FILE)? {
+        Some(bytes) => Some(cbor_from_slice(&open(LAYOUT_FILE, bytes)?)?),
+        None => None,
+    };
+    let data = match read_optional_entry(zip, DATA_FILE)? {
+        Some(bytes) => Some(serde_json::from_slice(&open(DATA_FILE, bytes)?)?),
+        None => None,
+    };
+
+    let document = Document {
+        manifest,
+        content,
+        styles,
+        layout,
+        data,
+    };
+    Ok((document, merkle_tree, signature_block))
+}
+
+impl ArchiveReader {
+    /// Read a document
+    ///
+    /// Encrypted documents fail with `TdfError::EncryptionError`; use
+    /// `read_decrypted`, or `read_envelope` for the signed parts only.
+    pub fn read(path: &Path) -> TdfResult<(Document, MerkleTree, SignatureBlock)> {
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        read_document(&mut zip, None)
     }
 
-    /// Read document with revocation list
-    pub fn read_with_revocation(path: &Path) -> TdfResult<(Document, MerkleTree, SignatureBlock, Option<RevocationList>)> {
-        let file = File::open(path)?;
-        let mut zip = zip::ZipArchive::new(file)?;
+    /// Read and decrypt a document with a recipient identity
+    ///
+    /// Plain documents are read as with `read`; the identity is not used.
+    ///
+    /// # Returns
+    /// * The decrypted document (its manifest keeps the `encryption` marker)
+    /// * `Err(TdfError::EncryptionError)` if the identity is not a recipient
+    ///   or a component was modified
+    pub fn read_decrypted(path: &Path, identity: &Identity) -> TdfResult<(Document, MerkleTree, SignatureBlock)> {
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        read_document(&mut zip, Some(identity))
+    }
 
-        // Read manifest
-        let manifest_bytes = {
-            let mut manifest_file = zip.by_name(MANIFEST_FILE)
-                .map_err(|_| TdfError::MissingFile(MANIFEST_FILE.to_string()))?;
-            let mut bytes = Vec::new();
-            manifest_file.read_to_end(&mut bytes)?;
-            bytes
-        };
-        let manifest: crate::document::Manifest = cbor_from_slice(&manifest_bytes)?;
+    /// Read the manifest, Merkle tree and signatures without the payload
+    ///
+    /// Works for encrypted documents without a key, so signatures can be
+    /// checked by anyone holding the file.
+    pub fn read_envelope(path: &Path) -> TdfResult<(crate::document::Manifest, MerkleTree, SignatureBlock)> {
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        read_envelope(&mut zip)
+    }
 
-        // Read content
-        let content_bytes = {
-            let mut content_file = zip.by_name(CONTENT_FILE)
-                .map_err(|_| TdfError::MissingFile(CONTENT_FILE.to_string()))?;
-            let mut bytes = Vec::new();
-            content_file.read_to_end(&mut bytes)?;
-            bytes
-        };
-        let content: crate::content::DocumentContent = cbor_from_slice(&content_bytes)?;
+    /// Read the wrapped content keys of an encrypted document
+    ///
+    /// # Returns
+    /// * `Ok(None)` for plain documents
+    pub fn read_encryption_header(path: &Path) -> TdfResult<Option<EncryptionHeader>> {
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        match read_optional_entry(&mut zip, ENCRYPTION_FILE)? {
+            Some(bytes) => Ok(Some(cbor_from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
 
-        // Read styles
-        let styles = {
-            let mut styles_file = zip.by_name(STYLES_FILE)
-                .map_err(|_| TdfError::MissingFile(STYLES_FILE.to_string()))?;
-            let mut s = String::new();
-            styles_file.read_to_string(&mut s)?;
-            s
-        };
+    /// Read the assets, decrypting them when the document is encrypted
+    ///
+    /// # Returns
+    /// * Asset data keyed by archive entry name (e.g. `assets/images/logo.png`)
+    pub fn read_assets(path: &Path, identity: Option<&Identity>) -> TdfResult<HashMap<String, Vec<u8>>> {
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        let manifest: crate::document::Manifest = cbor_from_slice(&read_entry(&mut zip, MANIFEST_FILE)?)?;
+        let content_key = open_content_key(&mut zip, &manifest, identity)?;
+
+        let names: Vec<String> = zip
+            .file_names()
+            .filter(|name| name.starts_with("assets/") && !name.ends_with('/'))
+            .map(str::to_string)
+            .collect();
+        let mut assets = HashMap::new();
+        for name in names {
+            let bytes = read_entry(&mut zip, &name)?;
+            let bytes = match content_key {
+                Some(ref key) => key.decrypt_component(&manifest.document.id, &name, &bytes)?,
+                None => bytes,
+            };
+            assets.insert(name, bytes);
+        }
+        Ok(assets)
+    }
 
-        // Read layout (optional)
-        let layout = {
-            if let Ok(mut layout_file) = zip.by_name(LAYOUT_FILE) {
-                let mut layout_bytes = Vec::new();
-                layout_file.read_to_end(&mut layout_bytes)?;
-                Some(cbor_from_slice(&layout_bytes)?)
-            } else {
-                None
+    /// Read a document, decrypting the sections the reader is entitled to
+    ///
+    /// Integrity is checked over the stored ciphertext, and signatures are
+    /// verified against `verifying_keys` (skipped when empty), so the result
+    /// holds for every section regardless of which could be decrypted.
+    /// Identities are also tried for document-level encryption.
+    ///
+    /// # Arguments
+    /// * `identities` - Recipient keys and/or passphrases of the reader
+    /// * `verifying_keys` - Signer id and Ed25519 key pairs for signature checks
+    pub fn read_selective(
+        path: &Path,
+        identities: &[Identity],
+        verifying_keys: &[(String, ed25519_dalek::VerifyingKey)],
+    ) -> TdfResult<SelectiveReadReport> {
+        let verification = Self::verify(path)?;
+
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        let (mut document, _, signature_block) = if verification.encrypted {
+            let mut opened = None;
+            for identity in identities {
+                match read_document(&mut zip, Some(identity)) {
+                    Ok(result) => {
+                        opened = Some(result);
+                        break;
+                    }
+                    Err(TdfError::EncryptionError(_)) => continue,
+                    Err(e) => return Err(e),
+                }
             }
+            opened.ok_or_else(|| {
+                TdfError::EncryptionError("Document is encrypted and none of the identities is a recipient".to_string())
+            })?
+        } else {
+            read_document(&mut zip, None)?
         };
 
-        // Read data (optional)
-        let data = {
-            if let Ok(mut data_file) = zip.by_name(DATA_FILE) {
-                let mut data_bytes = Vec::new();
-                data_file.read_to_end(&mut data_bytes)?;
-                Some(serde_json::from_slice(&data_bytes)?)
-            } else {
-                None
-            }
+        let signatures = if verifying_keys.is_empty() {
+            Vec::new()
+        } else {
+            let root_hash = hex::decode(&verification.root_hash)
+                .map_err(|e| TdfError::InvalidDocument(format!("Invalid root hash hex: {}", e)))?;
+            SignatureManager::verify_signature_block(&signature_block, &root_hash, verifying_keys)?
         };
+        let sections = decrypt_sections(&mut document, identities)?;
 
-        // Read hashes
-        let hashes_bytes = {
-            let mut hashes_file = zip.by_name(HASHES_FILE)
-                .map_err(|_| TdfError::MissingFile(HASHES_FILE.to_string()))?;
-            let mut bytes = Vec::new();
-            hashes_file.read_to_end(&mut bytes)?;
-            bytes
-        };
-        let merkle_tree = MerkleTree::from_binary(&hashes_bytes)?;
+        Ok(SelectiveReadReport {
+            document,
+            sections,
+            integrity_valid: verification.integrity_valid,
+            root_hash: verification.root_hash,
+            signature_count: signature_block.signatures.len(),
+            signatures,
+        })
+    }
 
-        // Read signatures
-        let signatures_bytes = {
-            let mut signatures_file = zip.by_name(SIGNATURES_FILE)
-                .map_err(|_| TdfError::MissingFile(SIGNATURES_FILE.to_string()))?;
-            let mut bytes = Vec::new();
-            signatures_file.read_to_end(&mut bytes)?;
-            bytes
-        };
-        let signature_block: SignatureBlock = cbor_from_slice(&signatures_bytes)?;
+    /// Read document with revocation list
+    pub fn read_with_revocation(path: &Path) -> TdfResult<(Document, MerkleTree, SignatureBlock, Option<RevocationList>)> {
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        let (document, merkle_tree, signature_block) = read_document(&mut zip, None)?;
 
         // Read revocation list (optional)
-        let revocation_list = {
-            if let Ok(mut revocation_file) = zip.by_name(REVOCATION_FILE) {
-                let mut revocation_bytes = Vec::new();
-                revocation_file.read_to_end(&mut revocation_bytes)?;
-                Some(RevocationManager::from_cbor(&revocation_bytes)?)
-            } else {
-                None
-            }
-        };
-
-        let document = Document {
-            manifest,
-            content,
-            styles,
-            layout,
-            data,
+        let revocation_list = match read_optional_entry(&mut zip, REVOCATION_FILE)? {
+            Some(bytes) => Some(RevocationManager::from_cbor(&bytes)?),
+            None => None,
         };
 
         Ok((document, merkle_tree, signature_block, revocation_list))
@@ -522,6 +723,20 @@ impl ArchiveReader {
         path: &Path,
         security_config: SecurityConfig,
         revocation_manager: Option<&RevocationManager>,
+    ) -> TdfResult<VerificationReport> {
+        Self::verify_with_trust_anchors(path, security_config, revocation_manager, None)
+    }
+
+    /// Verify with X.509 trust anchors for signer certificate chains
+    ///
+    /// Signatures that carry a certificate chain are checked against the leaf
+    /// key and, when `trust_anchors` is given, the chain is validated at the
+    /// signature timestamp. Results are returned in `signer_certificates`.
+    pub fn verify_with_trust_anchors(
+        path: &Path,
+        security_config: SecurityConfig,
+        revocation_manager: Option<&RevocationManager>,
+        trust_anchors: Option<&TrustAnchors>,
     ) -> TdfResult<VerificationReport> {
         // Check file size before opening
         let metadata = std::fs::metadata(path)?;
@@ -577,8 +792,30 @@ impl ArchiveReader {
             bytes
         };
 
+        // Redactable content is hashed as its skeleton of block commitments;
+        // malformed commitments or salts fall back to the raw bytes, which
+        // fails the integrity check
+        let (content_leaf, redacted_blocks) = if manifest.redaction.is_some() && manifest.encryption.is_none() {
+            let salts_bytes = {
+                let mut salts_file = zip.by_name(REDACTION_FILE)
+                    .map_err(|_| TdfError::MissingFile(REDACTION_FILE.to_string()))?;
+                let mut bytes = Vec::new();
+                salts_file.read_to_end(&mut bytes)?;
+                bytes
+            };
+            let commitment = cbor_from_slice::<RedactionSalts>(&salts_bytes).and_then(|salts| {
+                redaction::content_commitment(&redaction::content_value(&content_bytes)?, &salts)
+            });
+            match commitment {
+                Ok(commitment) => (commitment.leaf, commitment.redacted),
+                Err(_) => (content_bytes.clone(), Vec::new()),
+            }
+        } else {
+            (content_bytes.clone(), Vec::new())
+        };
+
         components.insert("manifest".to_string(), manifest_bytes_for_hash);
-        components.insert("content".to_string(), content_bytes);
+        components.insert("content".to_string(), content_leaf);
         components.insert("styles".to_string(), styles_bytes);
 
         // Read layout (if present)
@@ -691,26 +928,65 @@ impl ArchiveReader {
                 timestamp_warnings.push(format!("Signature {}: {}", sig.signer.id, e));
             }
         }
+
+        // Check signer certificate chains (if attached); an unreadable
+        // certificate is recorded against its signer only
+        let signer_certificates: Vec<SignerCertificateReport> = signature_block
+            .signatures
+            .iter()
+            .filter_map(|sig| verify_signature_certificate(sig, &root_hash, trust_anchors))
+            .collect();
         
-        // Reconstruct document for report
-        let document = Document {
-            manifest,
-            content: cbor_from_slice(&components["content"])?,
-            styles: String::from_utf8_lossy(&components["styles"]).to_string(),
-            layout: None,
-            data: None,
+        // Reconstruct document for report (encrypted payloads stay unread)
+        let encrypted = manifest.encryption.is_some();
+        let document = if encrypted {
+            Document {
+                manifest,
+                content: crate::content::DocumentContent { sections: Vec::new() },
+                styles: String::new(),
+                layout: None,
+                data: None,
+            }
+        } else {
+            Document {
+                manifest,
+                content: cbor_from_slice(&content_bytes)?,
+                styles: String::from_utf8_lossy(&components["styles"]).to_string(),
+                layout: None,
+                data: None,
+            }
         };
 
+        let formulas = crate::formula::verify_formulas(&document.content);
         Ok(VerificationReport {
             integrity_valid,
             root_hash: hex::encode(&root_hash),
             signature_count: signature_block.signatures.len(),
             document,
             timestamp_warnings,
+            signer_certificates,
+            encrypted,
+            redacted_blocks,
+            formulas,
         })
     }
 }
 
+/// Result of `ArchiveReader::read_selective`
+#[derive(Debug)]
+pub struct SelectiveReadReport {
+    /// Document with entitled sections decrypted; others remain placeholders
+    pub document: Document,
+    /// Access outcome per section, in document order
+    pub sections: Vec<SectionReport>,
+    /// Merkle tree matches the stored (encrypted) components
+    pub integrity_valid: bool,
+    pub root_hash: String,
+    pub signature_count: usize,
+    /// Signature results over the root hash (empty when no keys were given)
+    pub signatures: Vec<crate::signature::VerificationResult>,
+}
+
 #[derive(Debug)]
 pub struct VerificationReport {
     pub integrity_valid: bool,
@@ -718,5 +994,13 @@ pub struct VerificationReport {
     pub signature_count: usize,
     pub document: Document,
     pub timestamp_warnings: Vec<String>,
+    /// Certificate details for signatures that carry an X.509 chain
+    pub signer_certificates: Vec<SignerCertificateReport>,
+    /// Payload is encrypted; `document` carries only the manifest
+    pub encrypted: bool,
+    /// Redacted block ids and table cells (`<table>/<row>/<column>`)
+    pub redacted_blocks: Vec<String>,
+    /// Every formula cell re-computed against its stored value
+    pub formulas: Vec<crate::formula::FormulaCheck>,
 }
 
@@ -0,0 +1,846 @@
+//! X.509 certificate chain support for document signatures
+//!
+//! Signatures may carry the signer's certificate chain in `SignerInfo.certificate`
+//! (PEM, leaf first). At verification time the chain is:
+//! - bound to the signature: the leaf public key must be the key that produced it
+//! - validated against configured trust anchors at the signature timestamp
+//! - summarised (subject, issuer, serial, key usage) for the verification report
+//!
+//! Certificate signatures are verified with pure-Rust primitives (Ed25519,
+//! ECDSA P-256/P-384, RSA PKCS#1 v1.5) so the module also builds for WASM.
+
+use crate::error::{TdfError, TdfResult};
+use crate::signature::{DocumentSignature, SignatureAlgorithm, SignatureManager};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, TimeZone, Utc};
+use ed25519_dalek::VerifyingKey;
+use k256::ecdsa::VerifyingKey as Secp256k1VerifyingKey;
+use x509_parser::oid_registry::{
+    Oid, OID_EC_P256, OID_KEY_TYPE_EC_PUBLIC_KEY, OID_NIST_EC_P384, OID_PKCS1_SHA256WITHRSA,
+    OID_PKCS1_SHA384WITHRSA, OID_PKCS1_SHA512WITHRSA, OID_SIG_ECDSA_WITH_SHA256,
+    OID_SIG_ECDSA_WITH_SHA384, OID_SIG_ED25519,
+};
+use x509_parser::pem::Pem;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// Maximum number of certificates accepted in a chain (DoS protection)
+pub const MAX_CHAIN_LENGTH: usize = 10;
+
+/// secp256k1 named curve OID (1.3.132.0.10)
+const OID_CURVE_SECP256K1: &str = "1.3.132.0.10";
+
+/// An X.509 certificate chain, leaf first, stored as DER
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificateChain {
+    certificates: Vec<Vec<u8>>,
+}
+
+/// Public key extracted from a leaf certificate
+#[derive(Debug, Clone)]
+pub enum CertificatePublicKey {
+    Ed25519(VerifyingKey),
+    Secp256k1(Secp256k1VerifyingKey),
+    /// Key type that cannot produce TDF signatures (OID of the key algorithm)
+    Unsupported(String),
+}
+
+/// Summary of a single certificate for reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// Serial number, colon-separated hex
+    pub serial: String,
+    /// Key usage flags (e.g. "Digital Signature", "Non Repudiation"); empty if absent
+    pub key_usage: Vec<String>,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    pub is_ca: bool,
+}
+
+/// Outcome of validating a chain against trust anchors
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainStatus {
+    /// Chain builds to a trust anchor and every certificate was valid at the signature time
+    Trusted { anchor_subject: String },
+    /// Chain could not be validated
+    Untrusted { reason: String },
+    /// No trust anchors were configured
+    NotChecked,
+}
+
+/// Certificate section of a verification report for one signature
+#[derive(Debug, Clone)]
+pub struct SignerCertificateReport {
+    pub signer_id: String,
+    /// Leaf certificate details; `None` if the attached chain could not be parsed
+    pub leaf: Option<CertificateInfo>,
+    pub chain_length: usize,
+    pub chain_status: ChainStatus,
+    /// Whether the document signature verifies under the leaf certificate key
+    pub signature_valid: bool,
+    pub errors: Vec<String>,
+}
+
+impl SignerCertificateReport {
+    /// True when the chain is trusted and the signature verifies under the leaf key
+    pub fn is_trusted(&self) -> bool {
+        self.signature_valid && matches!(self.chain_status, ChainStatus::Trusted { .. })
+    }
+}
+
+/// Set of trusted root (or pinned intermediate) certificates
+#[derive(Debug, Clone, Default)]
+pub struct TrustAnchors {
+    anchors: Vec<Vec<u8>>,
+}
+
+fn parse_der(der: &[u8]) -> TdfResult<X509Certificate<'_>> {
+    let (rest, cert) = X509Certificate::from_der(der)
+        .map_err(|e| TdfError::CertificateError(format!("Invalid X.509 certificate: {}", e)))?;
+    if !rest.is_empty() {
+        return Err(TdfError::CertificateError(
+            "Trailing data after X.509 certificate".to_string(),
+        ));
+    }
+    Ok(cert)
+}
+
+/// Split PEM or concatenated DER input into individual DER certificates
+fn split_certificates(data: &[u8]) -> TdfResult<Vec<Vec<u8>>> {
+    let mut certificates = Vec::new();
+
+    if data.trim_ascii_start().starts_with(b"-----BEGIN") {
+        for pem in Pem::iter_from_buffer(data) {
+            let pem = pem.map_err(|e| TdfError::CertificateError(format!("Invalid PEM: {}", e)))?;
+            if pem.label == "CERTIFICATE" {
+                certificates.push(pem.contents);
+            }
+        }
+    } else {
+        let mut remaining = data;
+        while !remaining.is_empty() {
+            let (rest, _) = X509Certificate::from_der(remaining)
+                .map_err(|e| TdfError::CertificateError(format!("Invalid DER certificate: {}", e)))?;
+            let consumed = remaining.len() - rest.len();
+            certificates.push(remaining[..consumed].to_vec());
+            remaining = rest;
+        }
+    }
+
+    if certificates.is_empty() {
+        return Err(TdfError::CertificateError("No certificates found".to_string()));
+    }
+    if certificates.len() > MAX_CHAIN_LENGTH {
+        return Err(TdfError::CertificateError(format!(
+            "Certificate chain length {} exceeds limit {}",
+            certificates.len(),
+            MAX_CHAIN_LENGTH
+        )));
+    }
+    Ok(certificates)
+}
+
+fn asn1_time_to_utc(time: x509_parser::time::ASN1Time) -> DateTime<Utc> {
+    Utc.timestamp_opt(time.timestamp(), 0)
+        .single()
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+fn is_valid_at(cert: &X509Certificate<'_>, at: DateTime<Utc>) -> bool {
+    let validity = cert.validity();
+    let not_before = asn1_time_to_utc(validity.not_before);
+    let not_after = asn1_time_to_utc(validity.not_after);
+    at >= not_before && at <= not_after
+}
+
+/// Verify that `cert` was signed by the key in `issuer`
+fn verify_issued_by(cert: &X509Certificate<'_>, issuer: &X509Certificate<'_>) -> TdfResult<()> {
+    use p256::ecdsa::signature::Verifier;
+
+    let message = cert.tbs_certificate.as_ref();
+    let signature = cert.signature_value.data.as_ref();
+    let spki = issuer.public_key();
+    let key_data = spki.subject_public_key.data.as_ref();
+    let sig_oid = &cert.signature_algorithm.algorithm;
+
+    let fail = |e: String| TdfError::CertificateError(format!(
+        "Certificate '{}' signature check failed: {}",
+        cert.subject(),
+        e
+    ));
+
+    if *sig_oid == OID_SIG_ED25519 {
+        let key_bytes: [u8; 32] = key_data
+            .try_into()
+            .map_err(|_| fail("invalid Ed25519 issuer key".to_string()))?;
+        let key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| fail(e.to_string()))?;
+        let sig = ed25519_dalek::Signature::from_slice(signature).map_err(|e| fail(e.to_string()))?;
+        return key.verify(message, &sig).map_err(|e| fail(e.to_string()));
+    }
+
+    if *sig_oid == OID_SIG_ECDSA_WITH_SHA256 || *sig_oid == OID_SIG_ECDSA_WITH_SHA384 {
+        if spki.algorithm.algorithm != OID_KEY_TYPE_EC_PUBLIC_KEY {
+            return Err(fail("issuer key is not an EC key".to_string()));
+        }
+        let curve = spki
+            .algorithm
+            .parameters
+            .as_ref()
+            .and_then(|p| p.as_oid().ok())
+            .ok_or_else(|| fail("missing EC curve parameters".to_string()))?;
+
+        if curve == OID_EC_P256 && *sig_oid == OID_SIG_ECDSA_WITH_SHA256 {
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key_data).map_err(|e| fail(e.to_string()))?;
+            let sig = p256::ecdsa::DerSignature::from_bytes(signature).map_err(|e| fail(e.to_string()))?;
+            return key.verify(message, &sig).map_err(|e| fail(e.to_string()));
+        }
+        if curve == OID_NIST_EC_P384 && *sig_oid == OID_SIG_ECDSA_WITH_SHA384 {
+            let key = p384::ecdsa::VerifyingKey::from_sec1_bytes(key_data).map_err(|e| fail(e.to_string()))?;
+            let sig = p384::ecdsa::DerSignature::from_bytes(signature).map_err(|e| fail(e.to_string()))?;
+            return key.verify(message, &sig).map_err(|e| fail(e.to_string()));
+        }
+        return Err(TdfError::UnsupportedSignatureAlgorithm(format!(
+            "ECDSA curve {} with signature algorithm {}",
+            curve, sig_oid
+        )));
+    }
+
+    if *sig_oid == OID_PKCS1_SHA256WITHRSA
+        || *sig_oid == OID_PKCS1_SHA384WITHRSA
+        || *sig_oid == OID_PKCS1_SHA512WITHRSA
+    {
+        use rsa::pkcs1::DecodeRsaPublicKey;
+        use rsa::pkcs1v15::{Signature as RsaSignature, VerifyingKey as RsaVerifyingKey};
+
+        let key = rsa::RsaPublicKey::from_pkcs1_der(key_data).map_err(|e| fail(e.to_string()))?;
+        let sig = RsaSignature::try_from(signature).map_err(|e| fail(e.to_string()))?;
+        let result = if *sig_oid == OID_PKCS1_SHA256WITHRSA {
+            RsaVerifyingKey::<sha2::Sha256>::new(key).verify(message, &sig)
+        } else if *sig_oid == OID_PKCS1_SHA384WITHRSA {
+            RsaVerifyingKey::<sha2::Sha384>::new(key).verify(message, &sig)
+        } else {
+            RsaVerifyingKey::<sha2::Sha512>::new(key).verify(message, &sig)
+        };
+        return result.map_err(|e| fail(e.to_string()));
+    }
+
+    Err(TdfError::UnsupportedSignatureAlgorithm(format!(
+        "Certificate signature algorithm {}",
+        sig_oid
+    )))
+}
+
+impl CertificateInfo {
+    fn from_certificate(cert: &X509Certificate<'_>) -> Self {
+        let key_usage = match cert.key_usage() {
+            Ok(Some(ext)) => ext
+                .value
+                .to_string()
+                .split(", ")
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        CertificateInfo {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            serial: cert.raw_serial_as_string(),
+            key_usage,
+            not_before: asn1_time_to_utc(cert.validity().not_before),
+            not_after: asn1_time_to_utc(cert.validity().not_after),
+            is_ca: cert.is_ca(),
+        }
+    }
+
+    /// Parse a single DER-encoded certificate
+    pub fn from_der(der: &[u8]) -> TdfResult<Self> {
+        Ok(Self::from_certificate(&parse_der(der)?))
+    }
+}
+
+impl CertificateChain {
+    /// Build a chain from DER certificates, leaf first
+    pub fn from_der_certificates(certificates: Vec<Vec<u8>>) -> TdfResult<Self> {
+        if certificates.is_empty() {
+            return Err(TdfError::CertificateError("Certificate chain is empty".to_string()));
+        }
+        if certificates.len() > MAX_CHAIN_LENGTH {
+            return Err(TdfError::CertificateError(format!(
+                "Certificate chain length {} exceeds limit {}",
+                certificates.len(),
+                MAX_CHAIN_LENGTH
+            )));
+        }
+        for der in &certificates {
+            parse_der(der)?;
+        }
+        Ok(CertificateChain { certificates })
+    }
+
+    /// Parse a chain from PEM (one or more CERTIFICATE blocks) or concatenated DER
+    pub fn parse(data: &[u8]) -> TdfResult<Self> {
+        Self::from_der_certificates(split_certificates(data)?)
+    }
+
+    /// Load a chain from a PEM or DER file
+    pub fn from_file(path: &std::path::Path) -> TdfResult<Self> {
+        let data = std::fs::read(path)?;
+        Self::parse(&data)
+    }
+
+    /// Encode the chain as PEM, the format stored in `SignerInfo.certificate`
+    pub fn to_pem(&self) -> String {
+        let mut out = String::new();
+        for der in &self.certificates {
+            out.push_str("-----BEGIN CERTIFICATE-----\n");
+            let encoded = STANDARD.encode(der);
+            for line in encoded.as_bytes().chunks(64) {
+                out.push_str(&String::from_utf8_lossy(line));
+                out.push('\n');
+            }
+            out.push_str("-----END CERTIFICATE-----\n");
+        }
+        out
+    }
+
+    /// DER certificates, leaf first
+    pub fn certificates(&self) -> &[Vec<u8>] {
+        &self.certificates
+    }
+
+    pub fn len(&self) -> usize {
+        self.certificates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.certificates.is_empty()
+    }
+
+    /// Summary of the leaf certificate
+    pub fn leaf_info(&self) -> TdfResult<CertificateInfo> {
+        CertificateInfo::from_der(&self.certificates[0])
+    }
+
+    /// Extract the leaf certificate's subject public key
+    pub fn leaf_public_key(&self) -> TdfResult<CertificatePublicKey> {
+        let leaf = parse_der(&self.certificates[0])?;
+        let spki = leaf.public_key();
+        let key_data = spki.subject_public_key.data.as_ref();
+
+        if spki.algorithm.algorithm == OID_SIG_ED25519 {
+            let bytes: [u8; 32] = key_data.try_into().map_err(|_| {
+                TdfError::CertificateError("Ed25519 certificate key must be 32 bytes".to_string())
+            })?;
+            let key = VerifyingKey::from_bytes(&bytes)
+                .map_err(|e| TdfError::CertificateError(format!("Invalid Ed25519 certificate key: {}", e)))?;
+            return Ok(CertificatePublicKey::Ed25519(key));
+        }
+
+        if spki.algorithm.algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY {
+            let curve = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|p| p.as_oid().ok());
+            let secp256k1: Oid = OID_CURVE_SECP256K1
+                .parse()
+                .map_err(|_| TdfError::CertificateError("Invalid curve OID".to_string()))?;
+            if curve.as_ref() == Some(&secp256k1) {
+                let key = Secp256k1VerifyingKey::from_sec1_bytes(key_data)
+                    .map_err(|e| TdfError::CertificateError(format!("Invalid secp256k1 certificate key: {}", e)))?;
+                return Ok(CertificatePublicKey::Secp256k1(key));
+            }
+        }
+
+        Ok(CertificatePublicKey::Unsupported(spki.algorithm.algorithm.to_id_string()))
+    }
+
+    /// Check that the leaf certificate key is the Ed25519 key used for signing
+    pub fn check_leaf_key_ed25519(&self, key: &VerifyingKey) -> TdfResult<()> {
+        match self.leaf_public_key()? {
+            CertificatePublicKey::Ed25519(leaf) if leaf == *key => Ok(()),
+            _ => Err(TdfError::CertificateError(
+                "Leaf certificate public key does not match the Ed25519 signing key".to_string(),
+            )),
+        }
+    }
+
+    /// Check that the leaf certificate key is the secp256k1 key used for signing
+    pub fn check_leaf_key_secp256k1(&self, key: &Secp256k1VerifyingKey) -> TdfResult<()> {
+        match self.leaf_public_key()? {
+            CertificatePublicKey::Secp256k1(leaf) if leaf == *key => Ok(()),
+            _ => Err(TdfError::CertificateError(
+                "Leaf certificate public key does not match the secp256k1 signing key".to_string(),
+            )),
+        }
+    }
+
+    /// Validate the chain against trust anchors at a given time (normally the signature timestamp)
+    ///
+    /// Each certificate must be within its validity period at `at`, be signed by the next
+    /// certificate in the path, and the path must end at a trust anchor (any of the anchors
+    /// with the issuer's subject). Issuing certificates must be CAs with keyCertSign (when key
+    /// usage is present) whose path length constraint allows the intermediates below them; the
+    /// leaf must allow digitalSignature or nonRepudiation (when key usage is present).
+    ///
+    /// # Returns
+    /// * `Ok(subject)` of the trust anchor that terminated the path
+    /// * `Err(TdfError::CertificateError)` describing the first failure
+    pub fn validate(&self, trust_anchors: &TrustAnchors, at: DateTime<Utc>) -> TdfResult<String> {
+        if trust_anchors.is_empty() {
+            return Err(TdfError::CertificateError("No trust anchors configured".to_string()));
+        }
+
+        let chain: Vec<X509Certificate<'_>> = self
+            .certificates
+            .iter()
+            .map(|der| parse_der(der))
+            .collect::<TdfResult<_>>()?;
+        let anchors: Vec<X509Certificate<'_>> = trust_anchors
+            .anchors
+            .iter()
+            .map(|der| parse_der(der))
+            .collect::<TdfResult<_>>()?;
+
+        let leaf = &chain[0];
+        if let Ok(Some(ku)) = leaf.key_usage() {
+            if !ku.value.digital_signature() && !ku.value.non_repudiation() {
+                return Err(TdfError::CertificateError(format!(
+                    "Leaf certificate '{}' key usage does not permit signing",
+                    leaf.subject()
+                )));
+            }
+        }
+
+        let mut current = 0usize;
+        for _ in 0..=MAX_CHAIN_LENGTH {
+            let cert = &chain[current];
+            if !is_valid_at(cert, at) {
+                return Err(TdfError::CertificateError(format!(
+                    "Certificate '{}' was not valid at {}",
+                    cert.subject(),
+                    at.to_rfc3339()
+                )));
+            }
+
+            // The certificate itself is a pinned anchor
+            if trust_anchors.anchors.iter().any(|a| *a == self.certificates[current]) {
+                return Ok(cert.subject().to_string());
+            }
+
+            // Issued directly by a trust anchor; several anchors may share a
+            // subject (re-issued roots, CA key rollover), so try each of them
+            let mut anchor_error = None;
+            for anchor in anchors.iter().filter(|a| a.subject().as_raw() == cert.issuer().as_raw()) {
+                match check_anchor(cert, anchor, current, at) {
+                    Ok(()) => return Ok(anchor.subject().to_string()),
+                    Err(e) => anchor_error = Some(e),
+                }
+            }
+            if let Some(e) = anchor_error {
+                return Err(e);
+            }
+
+            // Otherwise the issuer must be the next certificate in the supplied chain
+            let next = current + 1;
+            let issuer = chain.get(next).ok_or_else(|| {
+                TdfError::CertificateError(format!(
+                    "Certificate chain for '{}' does not lead to a trust anchor",
+                    leaf.subject()
+                ))
+            })?;
+            if issuer.subject().as_raw() != cert.issuer().as_raw() {
+                return Err(TdfError::CertificateError(format!(
+                    "Certificate '{}' is not issued by '{}'",
+                    cert.subject(),
+                    issuer.subject()
+                )));
+            }
+            check_issuer_constraints(issuer, current)?;
+            verify_issued_by(cert, issuer)?;
+            current = next;
+        }
+
+        Err(TdfError::DepthLimitExceeded(
+            "Certificate path exceeds maximum length".to_string(),
+        ))
+    }
+}
+
+/// Check that `anchor` issued `cert` and was usable at `at`
+fn check_anchor(cert: &X509Certificate<'_>, anchor: &X509Certificate<'_>, intermediates: usize, at: DateTime<Utc>) -> TdfResult<()> {
+    check_issuer_constraints(anchor, intermediates)?;
+    if !is_valid_at(anchor, at) {
+        return Err(TdfError::CertificateError(format!(
+            "Trust anchor '{}' was not valid at {}",
+            anchor.subject(),
+            at.to_rfc3339()
+        )));
+    }
+    verify_issued_by(cert, anchor)
+}
+
+/// Check that `issuer` may issue certificates, with `intermediates` CA
+/// certificates between it and the leaf
+fn check_issuer_constraints(issuer: &X509Certificate<'_>, intermediates: usize) -> TdfResult<()> {
+    if !issuer.is_ca() {
+        return Err(TdfError::CertificateError(format!(
+            "Issuer '{}' is not a CA certificate",
+            issuer.subject()
+        )));
+    }
+    if let Ok(Some(bc)) = issuer.basic_constraints() {
+        if let Some(limit) = bc.value.path_len_constraint {
+            if intermediates > limit as usize {
+                return Err(TdfError::CertificateError(format!(
+                    "Issuer '{}' path length constraint {} allows fewer than {} intermediate certificate(s)",
+                    issuer.subject(),
+                    limit,
+                    intermediates
+                )));
+            }
+        }
+    }
+    if let Ok(Some(ku)) = issuer.key_usage() {
+        if !ku.value.key_cert_sign() {
+            return Err(TdfError::CertificateError(format!(
+                "Issuer '{}' key usage does not permit certificate signing",
+                issuer.subject()
+            )));
+        }
+    }
+    Ok(())
+}
+
+impl TrustAnchors {
+    pub fn new() -> Self {
+        TrustAnchors { anchors: Vec::new() }
+    }
+
+    /// Add a DER-encoded trust anchor certificate
+    pub fn add_der(&mut self, der: Vec<u8>) -> TdfResult<()> {
+        parse_der(&der)?;
+        if !self.anchors.contains(&der) {
+            self.anchors.push(der);
+        }
+        Ok(())
+    }
+
+    /// Parse trust anchors from PEM (one or more certificates) or concatenated DER
+    pub fn parse(data: &[u8]) -> TdfResult<Self> {
+        let mut anchors = TrustAnchors::new();
+        for der in split_certificates(data)? {
+            anchors.add_der(der)?;
+        }
+        Ok(anchors)
+    }
+
+    /// Load trust anchors from a PEM or DER file
+    pub fn from_file(path: &std::path::Path) -> TdfResult<Self> {
+        let data = std::fs::read(path)?;
+        Self::parse(&data)
+    }
+
+    pub fn len(&self) -> usize {
+        self.anchors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+}
+
+/// Verify the certificate attached to a signature
+///
+/// Parses `signature.signer.certificate`, verifies the document signature with the
+/// leaf certificate key and, if trust anchors are given, validates the chain at the
+/// signature timestamp.
+///
+/// # Returns
+/// * `None` if the signature carries no certificate
+/// * `Some(report)` otherwise; failures, including a certificate that cannot
+///   be parsed, are recorded in the report
+pub fn verify_signature_certificate(
+    signature: &DocumentSignature,
+    root_hash: &[u8],
+    trust_anchors: Option<&TrustAnchors>,
+) -> Option<SignerCertificateReport> {
+    let pem = signature.signer.certificate.as_ref()?;
+
+    let parsed = CertificateChain::parse(pem.as_bytes())
+        .and_then(|chain| Ok((chain.leaf_info()?, chain.leaf_public_key()?, chain)));
+    let (leaf, leaf_key, chain) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let reason = format!("Certificate could not be read: {}", e);
+            return Some(SignerCertificateReport {
+                signer_id: signature.signer.id.clone(),
+                leaf: None,
+                chain_length: 0,
+                chain_status: ChainStatus::Untrusted { reason: reason.clone() },
+                signature_valid: false,
+                errors: vec![reason],
+            });
+        }
+    };
+    let mut errors = Vec::new();
+
+    let signature_valid = match (leaf_key, &signature.algorithm) {
+        (CertificatePublicKey::Ed25519(key), SignatureAlgorithm::Ed25519) => {
+            match SignatureManager::verify_ed25519(signature, root_hash, &key) {
+                Ok(valid) => valid,
+                Err(e) => {
+                    errors.push(format!("Signature does not verify under certificate key: {}", e));
+                    false
+                }
+            }
+        }
+        (CertificatePublicKey::Secp256k1(key), SignatureAlgorithm::Secp256k1) => {
+            match SignatureManager::verify_secp256k1(signature, root_hash, &key) {
+                Ok(valid) => valid,
+                Err(e) => {
+                    errors.push(format!("Signature does not verify under certificate key: {}", e));
+                    false
+                }
+            }
+        }
+        (key, algorithm) => {
+            errors.push(format!(
+                "Certificate key type {:?} does not match signature algorithm {:?}",
+                key, algorithm
+            ));
+            false
+        }
+    };
+
+    let chain_status = match trust_anchors {
+        Some(anchors) => match chain.validate(anchors, signature.timestamp.time) {
+            Ok(anchor_subject) => ChainStatus::Trusted { anchor_subject },
+            Err(e) => {
+                errors.push(e.to_string());
+                ChainStatus::Untrusted { reason: e.to_string() }
+            }
+        },
+        None => ChainStatus::NotChecked,
+    };
+
+    Some(SignerCertificateReport {
+        signer_id: signature.signer.id.clone(),
+        leaf: Some(leaf),
+        chain_length: chain.len(),
+        chain_status,
+        signature_valid,
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature::SignatureScope;
+    use ed25519_dalek::pkcs8::EncodePrivateKey;
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+    use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair, KeyUsagePurpose};
+
+    struct TestPki {
+        root_der: Vec<u8>,
+        chain: CertificateChain,
+        signing_key: SigningKey,
+    }
+
+    fn ca_params(name: &str) -> CertificateParams {
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+        params
+    }
+
+    fn build_pki(leaf_usage: KeyUsagePurpose) -> TestPki {
+        build_pki_with_root(leaf_usage, BasicConstraints::Unconstrained)
+    }
+
+    fn build_pki_with_root(leaf_usage: KeyUsagePurpose, root_constraints: BasicConstraints) -> TestPki {
+        let root_key = KeyPair::generate().unwrap();
+        let mut root_params = ca_params("Test Root CA");
+        root_params.is_ca = IsCa::Ca(root_constraints);
+        let root = root_params.self_signed(&root_key).unwrap();
+
+        let intermediate_key = KeyPair::generate().unwrap();
+        let intermediate = ca_params("Test Issuing CA")
+            .signed_by(&intermediate_key, &root, &root_key)
+            .unwrap();
+
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let pkcs8 = signing_key.to_pkcs8_der().unwrap();
+        let leaf_key = KeyPair::try_from(pkcs8.as_bytes()).unwrap();
+        let mut leaf_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        leaf_params.distinguished_name.push(DnType::CommonName, "CFO Jane Smith");
+        leaf_params.is_ca = IsCa::ExplicitNoCa;
+        leaf_params.key_usages = vec![leaf_usage];
+        let leaf = leaf_params
+            .signed_by(&leaf_key, &intermediate, &intermediate_key)
+            .unwrap();
+
+        let chain = CertificateChain::from_der_certificates(vec![
+            leaf.der().to_vec(),
+            intermediate.der().to_vec(),
+        ])
+        .unwrap();
+
+        TestPki {
+            root_der: root.der().to_vec(),
+            chain,
+            signing_key,
+        }
+    }
+
+    fn anchors(pki: &TestPki) -> TrustAnchors {
+        let mut anchors = TrustAnchors::new();
+        anchors.add_der(pki.root_der.clone()).unwrap();
+        anchors
+    }
+
+    #[test]
+    fn test_pem_round_trip() {
+        let pki = build_pki(KeyUsagePurpose::DigitalSignature);
+        let pem = pki.chain.to_pem();
+        let parsed = CertificateChain::parse(pem.as_bytes()).unwrap();
+        assert_eq!(parsed, pki.chain);
+        assert_eq!(parsed.len(), 2);
+    }
+
+    #[test]
+    fn test_leaf_key_matches_signing_key() {
+        let pki = build_pki(KeyUsagePurpose::DigitalSignature);
+        assert!(pki.chain.check_leaf_key_ed25519(&pki.signing_key.verifying_key()).is_ok());
+
+        let other = SigningKey::generate(&mut OsRng);
+        assert!(pki.chain.check_leaf_key_ed25519(&other.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_chain_validates_to_anchor() {
+        let pki = build_pki(KeyUsagePurpose::DigitalSignature);
+        let subject = pki.chain.validate(&anchors(&pki), Utc::now()).unwrap();
+        assert!(subject.contains("Test Root CA"));
+
+        let info = pki.chain.leaf_info().unwrap();
+        assert!(info.subject.contains("CFO Jane Smith"));
+        assert!(info.issuer.contains("Test Issuing CA"));
+        assert!(!info.serial.is_empty());
+        assert_eq!(info.key_usage, vec!["Digital Signature".to_string()]);
+    }
+
+    #[test]
+    fn test_chain_rejected_without_matching_anchor() {
+        let pki = build_pki(KeyUsagePurpose::DigitalSignature);
+        let other = build_pki(KeyUsagePurpose::DigitalSignature);
+        assert!(pki.chain.validate(&anchors(&other), Utc::now()).is_err());
+        assert!(pki.chain.validate(&TrustAnchors::new(), Utc::now()).is_err());
+    }
+
+    #[test]
+    fn test_chain_validates_against_any_anchor_with_issuer_subject() {
+        // A re-issued root with the same subject but another key comes first
+        let pki = build_pki(KeyUsagePurpose::DigitalSignature);
+        let old_root = ca_params("Test Root CA").self_signed(&KeyPair::generate().unwrap()).unwrap();
+        let mut anchors = TrustAnchors::new();
+        anchors.add_der(old_root.der().to_vec()).unwrap();
+        anchors.add_der(pki.root_der.clone()).unwrap();
+        assert!(pki.chain.validate(&anchors, Utc::now()).unwrap().contains("Test Root CA"));
+
+        let mut only_old = TrustAnchors::new();
+        only_old.add_der(old_root.der().to_vec()).unwrap();
+        assert!(pki.chain.validate(&only_old, Utc::now()).is_err());
+    }
+
+    #[test]
+    fn test_path_length_constraint_enforced() {
+        // The root issues an intermediate CA, so it needs a path length of at least 1
+        let pki = build_pki_with_root(KeyUsagePurpose::DigitalSignature, BasicConstraints::Constrained(0));
+        let result = pki.chain.validate(&anchors(&pki), Utc::now());
+        assert!(result.unwrap_err().to_string().contains("path length"));
+
+        let pki = build_pki_with_root(KeyUsagePurpose::DigitalSignature, BasicConstraints::Constrained(1));
+        assert!(pki.chain.validate(&anchors(&pki), Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn test_chain_rejected_outside_validity() {
+        let pki = build_pki(KeyUsagePurpose::DigitalSignature);
+        let long_ago = Utc.with_ymd_and_hms(1960, 1, 1, 0, 0, 0).unwrap();
+        let result = pki.chain.validate(&anchors(&pki), long_ago);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("not valid at"));
+    }
+
+    #[test]
+    fn test_leaf_key_usage_enforced() {
+        let pki = build_pki(KeyUsagePurpose::KeyEncipherment);
+        let result = pki.chain.validate(&anchors(&pki), Utc::now());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("key usage"));
+    }
+
+    #[test]
+    fn test_verify_signature_certificate_report() {
+        let pki = build_pki(KeyUsagePurpose::ContentCommitment);
+        let root_hash = b"test_root_hash_for_signing_12345";
+        let mut signature = SignatureManager::sign_ed25519(
+            &pki.signing_key,
+            root_hash,
+            "did:web:cfo.acme.com".to_string(),
+            "CFO".to_string(),
+            SignatureScope::Full,
+        );
+        signature.signer.certificate = Some(pki.chain.to_pem());
+
+        let report = verify_signature_certificate(&signature, root_hash, Some(&anchors(&pki))).unwrap();
+        assert!(report.is_trusted(), "errors: {:?}", report.errors);
+        assert_eq!(report.chain_length, 2);
+        assert_eq!(report.leaf.as_ref().unwrap().key_usage, vec!["Non Repudiation".to_string()]);
+
+        // A certificate for a different key must not validate the signature
+        let other = build_pki(KeyUsagePurpose::DigitalSignature);
+        signature.signer.certificate = Some(other.chain.to_pem());
+        let report = verify_signature_certificate(&signature, root_hash, Some(&anchors(&other)))
+            .unwrap();
+        assert!(!report.signature_valid);
+        assert!(!report.is_trusted());
+    }
+
+    #[test]
+    fn test_malformed_certificate_is_reported_per_signer() {
+        let pki = build_pki(KeyUsagePurpose::ContentCommitment);
+        let root_hash = b"test_root_hash_for_signing_12345";
+        let mut good = SignatureManager::sign_ed25519(
+            &pki.signing_key,
+            root_hash,
+            "did:web:cfo.acme.com".to_string(),
+            "CFO".to_string(),
+            SignatureScope::Full,
+        );
+        good.signer.certificate = Some(pki.chain.to_pem());
+        let mut broken = good.clone();
+        broken.signer.id = "did:web:ceo.acme.com".to_string();
+        broken.signer.certificate =
+            Some("-----BEGIN CERTIFICATE-----\nnot a certificate\n-----END CERTIFICATE-----\n".to_string());
+
+        let reports: Vec<SignerCertificateReport> = [broken, good]
+            .iter()
+            .filter_map(|sig| verify_signature_certificate(sig, root_hash, Some(&anchors(&pki))))
+            .collect();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].signer_id, "did:web:ceo.acme.com");
+        assert!(reports[0].leaf.is_none());
+        assert!(!reports[0].signature_valid);
+        assert!(matches!(reports[0].chain_status, ChainStatus::Untrusted { .. }));
+        assert!(!reports[0].errors.is_empty());
+        assert!(reports[1].is_trusted(), "errors: {:?}", reports[1].errors);
+    }
+}
@@ -0,0 +1,719 @@
+//! Bar, line and pie charts drawn from table data
+//!
+//! A `Chart` block names a `Table` block, the column labelling its rows
+//! and the columns plotted as series. `resolve` reads the values from the
+//! table's data rows (subtotal and total rows are left out), so a chart
+//! always shows the signed figures; empty, redacted and text cells leave
+//! a gap. `layout` places bars, lines, slices, axes, labels and the legend
+//! as drawing primitives, which `render_svg` writes as SVG and the PDF
+//! exporter draws as vectors.
+//!
+//! Coordinates are in points with the origin at the top left of the chart.
+
+use crate::content::{
+    CellType, CellValue, ChartAxes, ChartFormat, ChartSeries, ChartType, ContentBlock, Decimal, DocumentContent, RowRole,
+    TableColumn, TableRow,
+};
+use crate::diagram;
+use crate::html::escape;
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::fmt::Write;
+
+/// Size of a chart
+pub const WIDTH: f32 = 420.0;
+pub const HEIGHT: f32 = 260.0;
+
+/// Font sizes, in points
+pub const TITLE_SIZE: f32 = 10.0;
+pub const LABEL_SIZE: f32 = 8.0;
+pub const VALUE_SIZE: f32 = 7.0;
+
+/// Line height of axis labels and legend entries
+const LINE: f32 = 12.0;
+
+/// Series colours used in order when a series sets none
+pub const PALETTE: [Color; 8] = [
+    Color(0x1f, 0x4e, 0x79),
+    Color(0xc5, 0x5a, 0x11),
+    Color(0x54, 0x82, 0x35),
+    Color(0x7f, 0x60, 0x00),
+    Color(0x70, 0x30, 0xa0),
+    Color(0x2e, 0x75, 0xb6),
+    Color(0xa5, 0xa5, 0xa5),
+    Color(0xbf, 0x90, 0x00),
+];
+
+const TEXT: Color = Color(0x22, 0x22, 0x22);
+const MUTED: Color = Color(0x59, 0x59, 0x59);
+const GRID: Color = Color(0xdd, 0xdd, 0xdd);
+
+/// Size, weight and colour of a text
+#[derive(Clone, Copy)]
+struct TextStyle {
+    size: f32,
+    bold: bool,
+    color: Color,
+}
+
+const TITLE: TextStyle = TextStyle { size: TITLE_SIZE, bold: true, color: TEXT };
+const LABEL: TextStyle = TextStyle { size: LABEL_SIZE, bold: false, color: TEXT };
+const AXIS: TextStyle = TextStyle { size: LABEL_SIZE, bold: false, color: MUTED };
+const VALUE: TextStyle = TextStyle { size: VALUE_SIZE, bold: false, color: TEXT };
+const SLICE_VALUE: TextStyle = TextStyle { size: VALUE_SIZE, bold: false, color: Color(0xff, 0xff, 0xff) };
+
+/// Largest arc of a pie slice drawn as one polygon edge, in radians
+const ARC_STEP: f64 = 0.05;
+
+/// An sRGB colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    /// Parse `#rrggbb`
+    pub fn parse(text: &str) -> Option<Color> {
+        let hex = text.strip_prefix('#').filter(|h| h.len() == 6 && h.chars().all(|c| c.is_ascii_hexdigit()))?;
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Color(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    /// `#rrggbb`
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// A plotted cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartValue {
+    pub value: Decimal,
+    /// The cell's display text, used for data labels
+    pub display: String,
+}
+
+/// Values of one series, one per category
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesData {
+    pub label: String,
+    pub color: Color,
+    /// `None` where the cell is empty, redacted or not a number
+    pub values: Vec<Option<ChartValue>>,
+}
+
+/// Data of a chart, read from its table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartData {
+    pub categories: Vec<String>,
+    pub series: Vec<SeriesData>,
+}
+
+/// Read a chart's data from its table in `content`
+///
+/// # Errors
+/// A message when the table does not exist, a column is not in the table
+/// or a series column holds text or dates.
+pub fn resolve(content: &DocumentContent, table: &str, category: &str, series: &[ChartSeries]) -> Result<ChartData, String> {
+    let (columns, rows) = content
+        .sections
+        .iter()
+        .flat_map(|section| &section.content)
+        .find_map(|block| match block {
+            ContentBlock::Table { id, columns, rows, .. } if id == table => Some((columns, rows)),
+            _ => None,
+        })
+        .ok_or_else(|| format!("table '{}' does not exist", table))?;
+    let column = |id: &str| -> Result<&TableColumn, String> {
+        columns
+            .iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| format!("column '{}' is not in table '{}'", id, table))
+    };
+    column(category)?;
+    let rows: Vec<&TableRow> = rows.iter().filter(|row| row.format.role == RowRole::Data).collect();
+    let categories = rows
+        .iter()
+        .map(|row| row.cells.get(category).map(|cell| cell.display_text().to_string()).unwrap_or_default())
+        .collect();
+
+    let mut data = Vec::with_capacity(series.len());
+    for (index, s) in series.iter().enumerate() {
+        let source = column(&s.column)?;
+        if matches!(source.cell_type, CellType::Text | CellType::Date) {
+            return Err(format!("column '{}' of table '{}' is not numeric", s.column, table));
+        }
+        let values = rows
+            .iter()
+            .map(|row| match row.cells.get(&s.column)? {
+                CellValue::Number { raw, display }
+                | CellValue::Currency { raw, display, .. }
+                | CellValue::Percentage { raw, display }
+                | CellValue::Formula { raw, display, .. } => Some(ChartValue { value: *raw, display: display.clone() }),
+                _ => None,
+            })
+            .collect();
+        data.push(SeriesData {
+            label: s.label.clone().unwrap_or_else(|| source.header.clone()),
+            color: s.color.as_deref().and_then(Color::parse).unwrap_or(PALETTE[index % PALETTE.len()]),
+            values,
+        });
+    }
+    Ok(ChartData { categories, series: data })
+}
+
+/// Data of every chart in `content`, keyed by chart id
+pub fn collect(content: &DocumentContent) -> HashMap<String, Result<ChartData, String>> {
+    content
+        .sections
+        .iter()
+        .flat_map(|section| &section.content)
+        .filter_map(|block| match block {
+            ContentBlock::Chart { id, table, category, series, .. } => Some((id.clone(), resolve(content, table, category, series))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Where a text's `x` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// A drawing primitive
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Rect { x: f32, y: f32, width: f32, height: f32, fill: Color },
+    Polygon { points: Vec<(f32, f32)>, fill: Color },
+    /// Open polyline
+    Line { points: Vec<(f32, f32)>, color: Color, width: f32 },
+    /// One line of text; `y` is its baseline
+    Text { x: f32, y: f32, text: String, size: f32, anchor: Anchor, bold: bool, color: Color },
+}
+
+/// Primitives of one chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartGeometry {
+    pub width: f32,
+    pub height: f32,
+    pub shapes: Vec<Shape>,
+}
+
+/// Approximate width of `text` in points at `size` (see `diagram::label_width`)
+pub fn text_width(text: &str, size: f32) -> f32 {
+    diagram::label_width(text) * size / diagram::LABEL_SIZE
+}
+
+/// Lay out a chart, measuring text with `text_width`
+pub fn layout(
+    chart_type: ChartType,
+    title: Option<&str>,
+    data: &ChartData,
+    axes: Option<&ChartAxes>,
+    format: Option<&ChartFormat>,
+) -> ChartGeometry {
+    layout_with(chart_type, title, data, axes, format, text_width)
+}
+
+/// Lay out a chart
+///
+/// `measure` gives the width in points of a text at a font size.
+pub fn layout_with(
+    chart_type: ChartType,
+    title: Option<&str>,
+    data: &ChartData,
+    axes: Option<&ChartAxes>,
+    format: Option<&ChartFormat>,
+    measure: impl Fn(&str, f32) -> f32,
+) -> ChartGeometry {
+    let format = format.cloned().unwrap_or_default();
+    let axes = axes.cloned().unwrap_or_default();
+    let mut chart = Canvas { shapes: Vec::new(), measure: &measure };
+    let mut top = 0.0;
+    if let Some(title) = title {
+        chart.text(WIDTH / 2.0, TITLE_SIZE, title, Anchor::Middle, TITLE);
+        top = TITLE_SIZE * 1.8;
+    }
+
+    // Legend along the bottom, wrapping onto further lines
+    let entries: Vec<(String, Color)> = match chart_type {
+        ChartType::Pie => data
+            .categories
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.clone(), PALETTE[i % PALETTE.len()]))
+            .collect(),
+        ChartType::Bar | ChartType::Line => data.series.iter().map(|s| (s.label.clone(), s.color)).collect(),
+    };
+    let legend = format.legend.unwrap_or(chart_type == ChartType::Pie || data.series.len() > 1);
+    let mut bottom = HEIGHT;
+    if legend && !entries.is_empty() {
+        let widths: Vec<f32> = entries.iter().map(|(label, _)| 12.0 + measure(label, LABEL_SIZE)).collect();
+        let mut lines: Vec<Vec<usize>> = vec![Vec::new()];
+        let mut used = 0.0;
+        for (i, width) in widths.iter().enumerate() {
+            let line = lines.last_mut().expect("at least one line");
+            if !line.is_empty() && used + width > WIDTH {
+                lines.push(vec![i]);
+                used = width + 12.0;
+            } else {
+                line.push(i);
+                used += width + 12.0;
+            }
+        }
+        bottom -= lines.len() as f32 * LINE;
+        for (n, line) in lines.iter().enumerate() {
+            let total = line.iter().map(|&i| widths[i] + 12.0).sum::<f32>() - 12.0;
+            let mut x = (WIDTH - total) / 2.0;
+            let baseline = bottom + n as f32 * LINE + LINE * 0.75;
+            for &i in line {
+                let (label, color) = &entries[i];
+                chart.shapes.push(Shape::Rect { x, y: baseline - 7.0, width: 8.0, height: 8.0, fill: *color });
+                chart.text(x + 12.0, baseline, label, Anchor::Start, LABEL);
+                x += widths[i] + 12.0;
+            }
+        }
+        bottom -= 4.0;
+    }
+
+    match chart_type {
+        ChartType::Pie => chart.pie(data, top, bottom, format.data_labels.unwrap_or(false)),
+        ChartType::Bar | ChartType::Line => chart.cartesian(chart_type, data, &axes, &format, top, bottom),
+    }
+    ChartGeometry { width: WIDTH, height: HEIGHT, shapes: chart.shapes }
+}
+
+struct Canvas<'m, M: Fn(&str, f32) -> f32> {
+    shapes: Vec<Shape>,
+    measure: &'m M,
+}
+
+impl<M: Fn(&str, f32) -> f32> Canvas<'_, M> {
+    fn text(&mut self, x: f32, y: f32, text: &str, anchor: Anchor, style: TextStyle) {
+        let TextStyle { size, bold, color } = style;
+        self.shapes.push(Shape::Text { x, y, text: text.to_string(), size, anchor, bold, color });
+    }
+
+    /// `text` shortened with an ellipsis to fit `width`
+    fn fit(&self, text: &str, size: f32, width: f32) -> String {
+        if (self.measure)(text, size) <= width {
+            return text.to_string();
+        }
+        let mut chars: Vec<char> = text.chars().collect();
+        while !chars.is_empty() {
+            chars.pop();
+            let shortened = format!("{}\u{2026}", chars.iter().collect::<String>().trim_end());
+            if (self.measure)(&shortened, size) <= width {
+                return shortened;
+            }
+        }
+        String::new()
+    }
+
+    fn pie(&mut self, data: &ChartData, top: f32, bottom: f32, data_labels: bool) {
+        let Some(series) = data.series.first() else { return };
+        let slices: Vec<(usize, f64, &ChartValue)> = series
+            .values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.as_ref().map(|v| (i, v.value.to_f64(), v)))
+            .filter(|(_, value, _)| *value > 0.0)
+            .collect();
+        let total: f64 = slices.iter().map(|(_, value, _)| value).sum();
+        if total <= 0.0 {
+            self.text(WIDTH / 2.0, (top + bottom) / 2.0, "No data", Anchor::Middle, AXIS);
+            return;
+        }
+        let (cx, cy) = (WIDTH as f64 / 2.0, (top + bottom) as f64 / 2.0);
+        let radius = ((bottom - top) as f64 / 2.0 - 4.0).max(10.0);
+        let point = |angle: f64, r: f64| ((cx + r * angle.cos()) as f32, (cy + r * angle.sin()) as f32);
+        let mut start = -FRAC_PI_2;
+        for &(category, value, cell) in &slices {
+            let sweep = value / total * TAU;
+            let steps = (sweep / ARC_STEP).ceil().max(1.0) as usize;
+            let mut points = vec![(cx as f32, cy as f32)];
+            points.extend((0..=steps).map(|k| point(start + sweep * k as f64 / steps as f64, radius)));
+            self.shapes.push(Shape::Polygon { points, fill: PALETTE[category % PALETTE.len()] });
+            if data_labels {
+                let (x, y) = point(start + sweep / 2.0, radius * 0.65);
+                self.text(x, y + VALUE_SIZE / 3.0, &cell.display, Anchor::Middle, SLICE_VALUE);
+            }
+            start += sweep;
+        }
+    }
+
+    fn cartesian(&mut self, chart_type: ChartType, data: &ChartData, axes: &ChartAxes, format: &ChartFormat, top: f32, bottom: f32) {
+        let stacked = chart_type == ChartType::Bar && format.stacked.unwrap_or(false);
+        let count = data.categories.len();
+        let value = |s: &SeriesData, i: usize| s.values.get(i).and_then(|v| v.as_ref()).map(|v| v.value.to_f64());
+
+        // Value range, including zero and any stacked totals
+        let mut extremes = vec![0.0f64];
+        for i in 0..count {
+            if stacked {
+                let values: Vec<f64> = data.series.iter().filter_map(|s| value(s, i)).collect();
+                extremes.push(values.iter().filter(|v| **v > 0.0).sum());
+                extremes.push(values.iter().filter(|v| **v < 0.0).sum());
+            } else {
+                extremes.extend(data.series.iter().filter_map(|s| value(s, i)));
+            }
+        }
+        let mut low = extremes.iter().cloned().fold(f64::INFINITY, f64::min);
+        let mut high = extremes.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        if let Some(min) = axes.min {
+            low = min.to_f64();
+        }
+        if let Some(max) = axes.max {
+            high = max.to_f64();
+        }
+        if high <= low {
+            high = low + 1.0;
+        }
+        let step = nice_step(high - low);
+        if axes.min.is_none() {
+            low = (low / step).floor() * step;
+        }
+        if axes.max.is_none() {
+            high = (high / step).ceil() * step;
+        }
+        let decimals = if step >= 1.0 { 0 } else { (-step.log10().floor()) as usize };
+        let first = (low / step).ceil() as i64;
+        let last = (high / step + 1e-9).floor() as i64;
+        let ticks: Vec<(f64, String)> = (first..=last).map(|k| (k as f64 * step, tick_label(k as f64 * step, decimals))).collect();
+
+        // Plot area inside the axis labels
+        let mut top = top;
+        let mut bottom = bottom;
+        if let Some(ref label) = axes.x_label {
+            self.text(WIDTH / 2.0, bottom - 2.0, label, Anchor::Middle, AXIS);
+            bottom -= LINE;
+        }
+        let axis_bottom = bottom - LINE;
+        let label_width = ticks.iter().map(|(_, t)| (self.measure)(t, LABEL_SIZE)).fold(0.0, f32::max);
+        let left = label_width + 6.0;
+        if let Some(ref label) = axes.y_label {
+            self.text(0.0, top + LABEL_SIZE, label, Anchor::Start, AXIS);
+            top += LINE;
+        }
+        let plot_top = top + LABEL_SIZE / 2.0;
+        let right = WIDTH - 4.0;
+        let (plot_width, plot_height) = (right - left, (axis_bottom - plot_top).max(1.0));
+        let y = |v: f64| axis_bottom - ((v.clamp(low, high) - low) / (high - low)) as f32 * plot_height;
+
+        for (v, label) in &ticks {
+            let ty = y(*v);
+            let color = if *v == 0.0 { MUTED } else { GRID };
+            self.shapes.push(Shape::Line { points: vec![(left, ty), (right, ty)], color, width: 0.5 });
+            self.text(left - 4.0, ty + LABEL_SIZE / 3.0, label, Anchor::End, AXIS);
+        }
+        self.shapes.push(Shape::Line { points: vec![(left, plot_top), (left, axis_bottom)], color: MUTED, width: 0.75 });
+        self.shapes.push(Shape::Line { points: vec![(left, axis_bottom), (right, axis_bottom)], color: MUTED, width: 0.75 });
+        if count == 0 {
+            return;
+        }
+
+        let band = plot_width / count as f32;
+        for (i, category) in data.categories.iter().enumerate() {
+            let text = self.fit(category, LABEL_SIZE, band - 2.0);
+            self.text(left + band * (i as f32 + 0.5), axis_bottom + LINE * 0.8, &text, Anchor::Middle, LABEL);
+        }
+        let data_labels = format.data_labels.unwrap_or(false);
+        let mut labels = Vec::new();
+
+        match chart_type {
+            ChartType::Bar => {
+                let group = band * 0.7;
+                let bar = if stacked { group } else { group / data.series.len().max(1) as f32 };
+                let mut stack = vec![(0.0f64, 0.0f64); count];
+                for (n, series) in data.series.iter().enumerate() {
+                    for (i, cell) in series.values.iter().enumerate().take(count) {
+                        let Some(cell) = cell else { continue };
+                        let v = cell.value.to_f64();
+                        let (from, to) = if stacked {
+                            let base = if v >= 0.0 { &mut stack[i].0 } else { &mut stack[i].1 };
+                            let from = *base;
+                            *base += v;
+                            (from, *base)
+                        } else {
+                            (0.0, v)
+                        };
+                        let x = left + band * i as f32 + (band - group) / 2.0 + if stacked { 0.0 } else { bar * n as f32 };
+                        let (y0, y1) = (y(from.max(to)), y(from.min(to)));
+                        self.shapes.push(Shape::Rect { x, y: y0, width: bar, height: y1 - y0, fill: series.color });
+                        if data_labels {
+                            let label_y = if stacked { (y0 + y1) / 2.0 + VALUE_SIZE / 3.0 } else if v >= 0.0 { y0 - 2.0 } else { y1 + VALUE_SIZE };
+                            labels.push((x + bar / 2.0, label_y, cell.display.clone()));
+                        }
+                    }
+                }
+            }
+            ChartType::Line => {
+                for series in &data.series {
+                    // Gaps break the line
+                    let mut run: Vec<(f32, f32)> = Vec::new();
+                    for (i, cell) in series.values.iter().enumerate().take(count) {
+                        match cell {
+                            Some(cell) => {
+                                let point = (left + band * (i as f32 + 0.5), y(cell.value.to_f64()));
+                                self.shapes.push(Shape::Rect { x: point.0 - 2.0, y: point.1 - 2.0, width: 4.0, height: 4.0, fill: series.color });
+                                if data_labels {
+                                    labels.push((point.0, point.1 - 4.0, cell.display.clone()));
+                                }
+                                run.push(point);
+                            }
+                            None => self.line(&mut run, series.color),
+                        }
+                    }
+                    self.line(&mut run, series.color);
+                }
+            }
+            ChartType::Pie => {}
+        }
+        for (x, y, text) in labels {
+            self.text(x, y, &text, Anchor::Middle, VALUE);
+        }
+    }
+
+    /// Draw a run of line chart points and start a new one
+    fn line(&mut self, run: &mut Vec<(f32, f32)>, color: Color) {
+        if run.len() > 1 {
+            self.shapes.push(Shape::Line { points: std::mem::take(run), color, width: 1.5 });
+        }
+        run.clear();
+    }
+}
+
+/// 1, 2 or 5 times a power of ten, giving about five ticks over `range`
+fn nice_step(range: f64) -> f64 {
+    let raw = range / 5.0;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = match raw / magnitude {
+        n if n <= 1.0 => 1.0,
+        n if n <= 2.0 => 2.0,
+        n if n <= 5.0 => 5.0,
+        _ => 10.0,
+    };
+    step * magnitude
+}
+
+/// Tick value with `decimals` places and thousands separators
+fn tick_label(value: f64, decimals: usize) -> String {
+    let text = format!("{:.*}", decimals, value.abs());
+    let (whole, fraction) = text.split_once('.').map_or((text.as_str(), None), |(w, f)| (w, Some(f)));
+    let mut grouped = String::new();
+    for (i, c) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let negative = value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0');
+    format!("{}{}{}", if negative { "-" } else { "" }, grouped, fraction.map(|f| format!(".{}", f)).unwrap_or_default())
+}
+
+/// Render a laid out chart as a standalone SVG element
+///
+/// The title is also given as `title` for assistive technology.
+pub fn render_svg(geometry: &ChartGeometry, title: Option<&str>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"chart\" width=\"{:.1}\" height=\"{:.1}\" viewBox=\"0 0 {:.1} {:.1}\" role=\"img\" font-family=\"Helvetica, Arial, sans-serif\">",
+        geometry.width, geometry.height, geometry.width, geometry.height
+    );
+    if let Some(title) = title {
+        let _ = writeln!(out, "<title>{}</title>", escape(title));
+    }
+    let points = |points: &[(f32, f32)]| points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect::<Vec<_>>().join(" ");
+    for shape in &geometry.shapes {
+        let _ = match shape {
+            Shape::Rect { x, y, width, height, fill } => writeln!(
+                out,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+                x,
+                y,
+                width,
+                height,
+                fill.hex()
+            ),
+            Shape::Polygon { points: p, fill } => {
+                writeln!(out, "<polygon points=\"{}\" fill=\"{}\" stroke=\"#fff\" stroke-width=\"1\"/>", points(p), fill.hex())
+            }
+            Shape::Line { points: p, color, width } => writeln!(
+                out,
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                points(p),
+                color.hex(),
+                width
+            ),
+            Shape::Text { x, y, text, size, anchor, bold, color } => {
+                let anchor = match anchor {
+                    Anchor::Start => "start",
+                    Anchor::Middle => "middle",
+                    Anchor::End => "end",
+                };
+                let weight = if *bold { " font-weight=\"bold\"" } else { "" };
+                writeln!(
+                    out,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" font-size=\"{}\"{} fill=\"{}\">{}</text>",
+                    x,
+                    y,
+                    anchor,
+                    size,
+                    weight,
+                    color.hex(),
+                    escape(text)
+                )
+            }
+        };
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{RowFormat, Section};
+
+    fn number(raw: &str) -> CellValue {
+        CellValue::Number { raw: raw.parse().unwrap(), display: raw.to_string() }
+    }
+
+    fn content() -> DocumentContent {
+        let column = |id: &str, cell_type: CellType| TableColumn { id: id.to_string(), header: id.to_uppercase(), cell_type, currency: None };
+        let row = |quarter: &str, revenue: Option<CellValue>, costs: CellValue| {
+            let mut cells = HashMap::from([("quarter".to_string(), CellValue::Text(quarter.to_string())), ("costs".to_string(), costs)]);
+            if let Some(revenue) = revenue {
+                cells.insert("revenue".to_string(), revenue);
+            }
+            TableRow::new(cells)
+        };
+        let mut total = row("Total", Some(number("700")), number("300"));
+        total.format = RowFormat { role: RowRole::Total, ..Default::default() };
+        let table = ContentBlock::Table {
+            id: "results".to_string(),
+            caption: None,
+            columns: vec![
+                column("quarter", CellType::Text),
+                column("revenue", CellType::Currency),
+                column("costs", CellType::Number),
+                column("due", CellType::Date),
+            ],
+            header_rows: None,
+            rows: vec![
+                row("Q1", Some(number("200")), number("100")),
+                row("Q2", None, CellValue::Redacted { redacted: "c".to_string() }),
+                row("Q3", Some(number("500")), number("-200")),
+                total,
+            ],
+            footer: None,
+        };
+        DocumentContent { sections: vec![Section { id: "s".to_string(), title: None, content: vec![table], encrypted: None }] }
+    }
+
+    fn series(column: &str) -> ChartSeries {
+        ChartSeries { column: column.to_string(), label: None, color: None }
+    }
+
+    #[test]
+    fn test_resolve_reads_data_rows() {
+        let data = resolve(&content(), "results", "quarter", &[series("revenue"), series("costs")]).unwrap();
+        assert_eq!(data.categories, ["Q1", "Q2", "Q3"]);
+        assert_eq!(data.series[0].label, "REVENUE");
+        assert_eq!(data.series[0].color, PALETTE[0]);
+        let values: Vec<Option<String>> = data.series[1].values.iter().map(|v| v.as_ref().map(|v| v.value.to_string())).collect();
+        assert_eq!(values, [Some("100".to_string()), None, Some("-200".to_string())]);
+        assert_eq!(data.series[0].values[1], None);
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let content = content();
+        let error = |table: &str, category: &str, column: &str| resolve(&content, table, category, &[series(column)]).unwrap_err();
+        assert_eq!(error("missing", "quarter", "revenue"), "table 'missing' does not exist");
+        assert_eq!(error("results", "year", "revenue"), "column 'year' is not in table 'results'");
+        assert_eq!(error("results", "quarter", "profit"), "column 'profit' is not in table 'results'");
+        assert_eq!(error("results", "quarter", "due"), "column 'due' of table 'results' is not numeric");
+    }
+
+    #[test]
+    fn test_bar_chart_grouped_and_stacked() {
+        let data = resolve(&content(), "results", "quarter", &[series("revenue"), series("costs")]).unwrap();
+        let bars = |format: Option<&ChartFormat>| -> Vec<(f32, f32, f32)> {
+            layout(ChartType::Bar, None, &data, None, format)
+                .shapes
+                .iter()
+                .filter_map(|s| match s {
+                    Shape::Rect { x, y, height, width, .. } if *width > 8.0 => Some((*x, *y, *height)),
+                    _ => None,
+                })
+                .collect()
+        };
+        // Four values (one gap per series), side by side
+        let grouped = bars(None);
+        assert_eq!(grouped.len(), 4);
+        assert!(grouped[0].0 < grouped[2].0);
+        // Stacked bars of the same category share their x
+        let stacked = bars(Some(&ChartFormat { stacked: Some(true), ..Default::default() }));
+        assert_eq!(stacked.len(), 4);
+        assert_eq!(stacked[0].0, stacked[2].0);
+        // Q1 costs sit on top of Q1 revenue
+        assert!((stacked[2].1 + stacked[2].2 - stacked[0].1).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_axis_ticks_cover_the_data() {
+        let data = resolve(&content(), "results", "quarter", &[series("revenue"), series("costs")]).unwrap();
+        let geometry = layout(ChartType::Line, Some("Results"), &data, None, None);
+        let texts: Vec<&str> = geometry
+            .shapes
+            .iter()
+            .filter_map(|s| match s {
+                Shape::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        for tick in ["-200", "0", "600"] {
+            assert!(texts.contains(&tick), "{:?}", texts);
+        }
+        assert!(texts.contains(&"Results") && texts.contains(&"REVENUE") && texts.contains(&"COSTS"));
+        // The gap at Q2 breaks both lines, leaving single points
+        let lines = geometry.shapes.iter().filter(|s| matches!(s, Shape::Line { width, .. } if *width == 1.5)).count();
+        assert_eq!(lines, 0);
+
+        let axes = ChartAxes { min: Some("0".parse().unwrap()), max: Some("1000".parse().unwrap()), ..Default::default() };
+        let geometry = layout(ChartType::Bar, None, &data, Some(&axes), None);
+        assert!(geometry.shapes.iter().any(|s| matches!(s, Shape::Text { text, .. } if text == "1,000")));
+    }
+
+    #[test]
+    fn test_pie_slices_and_svg() {
+        let data = resolve(&content(), "results", "quarter", &[series("revenue")]).unwrap();
+        let format = ChartFormat { data_labels: Some(true), ..Default::default() };
+        let geometry = layout(ChartType::Pie, Some("Revenue <share>"), &data, None, Some(&format));
+        let slices = geometry.shapes.iter().filter(|s| matches!(s, Shape::Polygon { .. })).count();
+        assert_eq!(slices, 2);
+        let svg = render_svg(&geometry, Some("Revenue <share>"));
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"chart\""));
+        assert!(svg.contains("<title>Revenue &lt;share&gt;</title>"));
+        assert!(svg.contains(">200</text>") && svg.contains(">500</text>"));
+        assert!(svg.contains("fill=\"#1f4e79\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_colors_and_tick_labels() {
+        assert_eq!(Color::parse("#1F4e79"), Some(Color(0x1f, 0x4e, 0x79)));
+        assert_eq!(Color::parse("1f4e79"), None);
+        assert_eq!(Color::parse("#1f4e7"), None);
+        assert_eq!(Color(0, 128, 255).hex(), "#0080ff");
+        assert_eq!(tick_label(1234567.0, 0), "1,234,567");
+        assert_eq!(tick_label(-2500.0, 0), "-2,500");
+        assert_eq!(tick_label(0.25, 2), "0.25");
+        assert_eq!(tick_label(-0.0, 1), "0.0");
+        assert_eq!(nice_step(730.0), 200.0);
+        assert!((nice_step(0.9) - 0.2).abs() < 1e-12);
+    }
+}
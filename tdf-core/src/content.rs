@@ -1,5 +1,14 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::currency::is_iso_4217;
+use crate::encryption::EncryptedSection;
+use crate::form::ChoiceOption;
+use crate::xbrl::XbrlTag;
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::fmt;
+
+pub use crate::decimal::Decimal;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
@@ -7,29 +16,59 @@ pub enum ContentBlock {
     #[serde(rename = "heading")]
     Heading {
         level: u8,
-        text: String,
+        text: InlineText,
         #[serde(skip_serializing_if = "Option::is_none")]
         id: Option<String>,
     },
     #[serde(rename = "paragraph")]
     Paragraph {
-        text: String,
+        text: InlineText,
         #[serde(skip_serializing_if = "Option::is_none")]
         id: Option<String>,
+        /// XBRL fact the paragraph's text reports (see `crate::xbrl`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        xbrl: Option<XbrlTag>,
     },
     #[serde(rename = "list")]
     List {
         ordered: bool,
-        items: Vec<String>,
+        items: Vec<ListItem>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+    /// Quoted passage, optionally attributed to its source
+    #[serde(rename = "quote")]
+    Quote {
+        text: InlineText,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        attribution: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         id: Option<String>,
     },
+    /// Preformatted text shown in a monospace face, line breaks kept
+    #[serde(rename = "code")]
+    Code {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+    /// Following blocks start on a new page
+    #[serde(rename = "page_break")]
+    PageBreak,
+    /// Thematic break between blocks
+    #[serde(rename = "horizontal_rule")]
+    HorizontalRule,
     #[serde(rename = "table")]
     Table {
         id: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         caption: Option<String>,
         columns: Vec<TableColumn>,
+        /// Grouping header rows above the column headers, outermost first
+        #[serde(skip_serializing_if = "Option::is_none")]
+        header_rows: Option<Vec<Vec<HeaderCell>>>,
         rows: Vec<TableRow>,
         #[serde(skip_serializing_if = "Option::is_none")]
         footer: Option<Vec<String>>,
@@ -45,6 +84,24 @@ pub enum ContentBlock {
         #[serde(skip_serializing_if = "Option::is_none")]
         layout: Option<DiagramLayout>,
     },
+    /// Chart drawn from columns of a `Table` block, so the plotted figures
+    /// are the signed table values (see `crate::chart`)
+    #[serde(rename = "chart")]
+    Chart {
+        id: String,
+        chart_type: ChartType,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        /// Id of the table block holding the data
+        table: String,
+        /// Column naming each data row: the x axis categories or pie slices
+        category: String,
+        series: Vec<ChartSeries>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        axes: Option<ChartAxes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        format: Option<ChartFormat>,
+    },
     #[serde(rename = "figure")]
     Figure {
         id: String,
@@ -60,6 +117,344 @@ pub enum ContentBlock {
         id: String,
         text: String,
     },
+    /// Block removed after signing; the commitment keeps the signature valid
+    #[serde(rename = "redacted")]
+    Redacted {
+        id: String,
+        commitment: String,
+    },
+    /// Form field for free text; the value is kept in a submission, not in
+    /// the template (see `crate::form`)
+    #[serde(rename = "text_field")]
+    TextField {
+        id: String,
+        label: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        required: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        help: Option<String>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        multiline: bool,
+        /// Most characters the value may have
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_length: Option<usize>,
+        /// Regular expression the whole value must match
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pattern: Option<String>,
+    },
+    /// Form field for an exact decimal, optionally an amount in `currency`
+    #[serde(rename = "number_field")]
+    NumberField {
+        id: String,
+        label: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        required: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        help: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<Decimal>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<Decimal>,
+        /// Most decimal places the value may have
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u32>,
+        /// ISO 4217 code of an amount
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        currency: Option<String>,
+    },
+    /// Form field for a calendar date, entered as `YYYY-MM-DD`
+    #[serde(rename = "date_field")]
+    DateField {
+        id: String,
+        label: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        required: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        help: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<NaiveDate>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<NaiveDate>,
+    },
+    /// Form field ticked or left clear; a required checkbox must be ticked,
+    /// as for an attestation
+    #[serde(rename = "checkbox")]
+    Checkbox {
+        id: String,
+        label: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        required: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        help: Option<String>,
+    },
+    /// Form field picking one of `options`, or several when `multiple`
+    #[serde(rename = "choice")]
+    Choice {
+        id: String,
+        label: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        required: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        help: Option<String>,
+        options: Vec<ChoiceOption>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        multiple: bool,
+    },
+    /// Place for the filler's signature, filled by signing the submission
+    #[serde(rename = "signature_field")]
+    SignatureField {
+        id: String,
+        label: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        required: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        help: Option<String>,
+        /// Capacity the filler signs in, e.g. "Director"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        role: Option<String>,
+    },
+}
+
+/// Deepest list nesting, counting the top-level list as 1
+pub const MAX_LIST_DEPTH: usize = 9;
+
+/// Item of a list
+///
+/// An item without a checkbox or sublist serializes as its bare text, so
+/// flat lists read and hash as they did before items had structure.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(from = "ListItemRepr", into = "ListItemRepr")]
+pub struct ListItem {
+    pub text: InlineText,
+    /// Checkbox state of a task list item; `None` when it has no checkbox
+    pub checked: Option<bool>,
+    /// List nested under the item
+    pub sublist: Option<NestedList>,
+}
+
+/// List nested under a list item
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NestedList {
+    pub ordered: bool,
+    pub items: Vec<ListItem>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ListItemRepr {
+    Text(InlineText),
+    Item {
+        text: InlineText,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        checked: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sublist: Option<NestedList>,
+    },
+}
+
+impl From<ListItemRepr> for ListItem {
+    fn from(repr: ListItemRepr) -> Self {
+        match repr {
+            ListItemRepr::Text(text) => ListItem { text, checked: None, sublist: None },
+            ListItemRepr::Item { text, checked, sublist } => ListItem { text, checked, sublist },
+        }
+    }
+}
+
+impl From<ListItem> for ListItemRepr {
+    fn from(item: ListItem) -> Self {
+        match item {
+            ListItem { text, checked: None, sublist: None } => ListItemRepr::Text(text),
+            ListItem { text, checked, sublist } => ListItemRepr::Item { text, checked, sublist },
+        }
+    }
+}
+
+impl ListItem {
+    /// A task list item with a checkbox
+    pub fn task(text: impl Into<InlineText>, checked: bool) -> Self {
+        ListItem { text: text.into(), checked: Some(checked), sublist: None }
+    }
+
+    /// The item with `sublist` nested under it
+    pub fn with_sublist(mut self, ordered: bool, items: Vec<ListItem>) -> Self {
+        self.sublist = Some(NestedList { ordered, items });
+        self
+    }
+
+    /// Text of the item followed by that of its sublist items, in reading order
+    pub fn texts(&self) -> Vec<&InlineText> {
+        let mut texts = vec![&self.text];
+        if let Some(list) = &self.sublist {
+            texts.extend(list.items.iter().flat_map(ListItem::texts));
+        }
+        texts
+    }
+
+    /// Nesting depth of the item's sublists; 0 for an item without one
+    pub fn depth(&self) -> usize {
+        self.sublist
+            .as_ref()
+            .map_or(0, |list| 1 + list.items.iter().map(ListItem::depth).max().unwrap_or(0))
+    }
+}
+
+impl<T: Into<InlineText>> From<T> for ListItem {
+    fn from(text: T) -> Self {
+        ListItem { text: text.into(), checked: None, sublist: None }
+    }
+}
+
+/// Inline text of a heading, paragraph or list item
+///
+/// Unformatted text is a plain string, as it was before runs existed, so
+/// older documents read unchanged and keep their hashes. Text with marks,
+/// links or references is a list of runs. In either form `{{id}}` in text
+/// still refers to the footnote with that id.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum InlineText {
+    Plain(String),
+    Runs(Vec<InlineRun>),
+}
+
+/// A piece of inline text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum InlineRun {
+    /// Text with formatting marks, optionally a hyperlink
+    #[serde(rename = "text")]
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        marks: Vec<Mark>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        link: Option<String>,
+    },
+    /// Reference to a footnote block, shown as its number
+    #[serde(rename = "footnote_ref")]
+    FootnoteRef { id: String },
+    /// Reference to another block or section of the document
+    #[serde(rename = "xref")]
+    CrossRef { target: String, text: String },
+}
+
+/// Formatting of a text run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Mark {
+    Bold,
+    Italic,
+    Underline,
+    Strike,
+    Code,
+    Superscript,
+    Subscript,
+}
+
+impl InlineRun {
+    /// An unformatted text run
+    pub fn text(text: impl Into<String>) -> Self {
+        InlineRun::Text { text: text.into(), marks: Vec::new(), link: None }
+    }
+
+    /// A text run with `marks`
+    pub fn marked(text: impl Into<String>, marks: &[Mark]) -> Self {
+        let mut marks = marks.to_vec();
+        marks.sort();
+        marks.dedup();
+        InlineRun::Text { text: text.into(), marks, link: None }
+    }
+}
+
+impl InlineText {
+    /// Build inline text from runs
+    ///
+    /// Adjacent text runs with the same marks and link are merged and empty
+    /// ones dropped; text without marks, links or references becomes
+    /// `Plain`.
+    pub fn from_runs(runs: impl IntoIterator<Item = InlineRun>) -> Self {
+        let mut merged: Vec<InlineRun> = Vec::new();
+        for run in runs {
+            if let InlineRun::Text { text, marks, link } = &run {
+                if text.is_empty() {
+                    continue;
+                }
+                if let Some(InlineRun::Text { text: last, marks: last_marks, link: last_link }) = merged.last_mut() {
+                    if last_marks == marks && last_link == link {
+                        last.push_str(text);
+                        continue;
+                    }
+                }
+            }
+            merged.push(run);
+        }
+        let plain = merged
+            .iter()
+            .all(|run| matches!(run, InlineRun::Text { marks, link: None, .. } if marks.is_empty()));
+        if plain {
+            InlineText::Plain(merged.iter().map(InlineText::run_text).collect())
+        } else {
+            InlineText::Runs(merged)
+        }
+    }
+
+    /// The runs of the text; plain text is a single unformatted run
+    pub fn runs(&self) -> Vec<InlineRun> {
+        match self {
+            InlineText::Plain(text) if text.is_empty() => Vec::new(),
+            InlineText::Plain(text) => vec![InlineRun::text(text.clone())],
+            InlineText::Runs(runs) => runs.clone(),
+        }
+    }
+
+    /// Text without formatting; footnote references become `{{id}}`
+    /// markers and cross-references their text
+    pub fn plain_text(&self) -> Cow<'_, str> {
+        match self {
+            InlineText::Plain(text) => Cow::Borrowed(text),
+            InlineText::Runs(runs) => Cow::Owned(runs.iter().map(InlineText::run_text).collect()),
+        }
+    }
+
+    /// Whether the text is empty
+    pub fn is_empty(&self) -> bool {
+        match self {
+            InlineText::Plain(text) => text.is_empty(),
+            InlineText::Runs(runs) => runs.iter().all(|run| matches!(run, InlineRun::Text { text, .. } if text.is_empty())),
+        }
+    }
+
+    fn run_text(run: &InlineRun) -> Cow<'_, str> {
+        match run {
+            InlineRun::Text { text, .. } | InlineRun::CrossRef { text, .. } => Cow::Borrowed(text),
+            InlineRun::FootnoteRef { id } => Cow::Owned(format!("{{{{{}}}}}", id)),
+        }
+    }
+}
+
+impl Default for InlineText {
+    fn default() -> Self {
+        InlineText::Plain(String::new())
+    }
+}
+
+impl From<String> for InlineText {
+    fn from(text: String) -> Self {
+        InlineText::Plain(text)
+    }
+}
+
+impl From<&str> for InlineText {
+    fn from(text: &str) -> Self {
+        InlineText::Plain(text.to_string())
+    }
+}
+
+impl fmt::Display for InlineText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.plain_text())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -72,33 +467,474 @@ pub struct TableColumn {
     pub currency: Option<String>,
 }
 
+/// Reserved row key holding the row's `RowFormat`; never a column id
+pub const ROW_FORMAT_KEY: &str = "_row";
+
+/// Deepest outline level of a table row, as in spreadsheet outlines
+pub const MAX_ROW_LEVEL: u8 = 7;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TableRow {
     #[serde(flatten)]
     pub cells: HashMap<String, CellValue>,
+    /// Role, outline level and cell spans; omitted for plain data rows
+    #[serde(rename = "_row", default, skip_serializing_if = "RowFormat::is_plain")]
+    pub format: RowFormat,
+}
+
+/// Structure of a table row beyond its cell values
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RowFormat {
+    #[serde(default, skip_serializing_if = "RowRole::is_data")]
+    pub role: RowRole,
+    /// Outline level; line items under a heading row are one level deeper
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub level: u8,
+    /// Cells spanning more than one column or row, by column id
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub spans: HashMap<String, CellSpan>,
+    /// XBRL facts reported by cells, by column id (see `crate::xbrl`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tags: HashMap<String, XbrlTag>,
+}
+
+/// What a table row holds
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RowRole {
+    #[default]
+    Data,
+    Subtotal,
+    Total,
+}
+
+/// Extent of a merged cell, anchored at its top-left cell
+///
+/// The cells it covers to the right and below must be absent from their rows.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CellSpan {
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub colspan: usize,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub rowspan: usize,
+}
+
+/// Cell of a grouping header row
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeaderCell {
+    pub text: String,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub colspan: usize,
+}
+
+/// A header cell to draw, after merging empty group cells into the column
+/// header below them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderSlot<'a> {
+    pub text: &'a str,
+    /// Index of the first column under the cell
+    pub column: usize,
+    pub colspan: usize,
+    pub rowspan: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+fn is_one(n: &usize) -> bool {
+    *n == 1
+}
+
+fn is_zero(n: &u8) -> bool {
+    *n == 0
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+impl RowRole {
+    pub fn is_data(&self) -> bool {
+        *self == RowRole::Data
+    }
+}
+
+impl RowFormat {
+    /// A data row at the top level without spans or tags
+    pub fn is_plain(&self) -> bool {
+        *self == RowFormat::default()
+    }
+}
+
+impl Default for CellSpan {
+    fn default() -> Self {
+        CellSpan { colspan: 1, rowspan: 1 }
+    }
+}
+
+impl HeaderCell {
+    pub fn new(text: impl Into<String>, colspan: usize) -> Self {
+        HeaderCell { text: text.into(), colspan }
+    }
 }
 
+impl TableRow {
+    /// A plain data row
+    pub fn new(cells: HashMap<String, CellValue>) -> Self {
+        TableRow { cells, format: RowFormat::default() }
+    }
+
+    pub fn with_role(mut self, role: RowRole) -> Self {
+        self.format.role = role;
+        self
+    }
+
+    pub fn with_level(mut self, level: u8) -> Self {
+        self.format.level = level;
+        self
+    }
+
+    /// The row with the cell in `column` merged across `colspan` columns
+    /// and `rowspan` rows
+    pub fn with_span(mut self, column: impl Into<String>, colspan: usize, rowspan: usize) -> Self {
+        self.format.spans.insert(column.into(), CellSpan { colspan, rowspan });
+        self
+    }
+
+    /// The row with the cell in `column` tagged as reporting an XBRL fact
+    pub fn with_tag(mut self, column: impl Into<String>, tag: XbrlTag) -> Self {
+        self.format.tags.insert(column.into(), tag);
+        self
+    }
+
+    /// Span of the cell in `column`; 1x1 unless merged
+    pub fn span(&self, column: &str) -> CellSpan {
+        self.format.spans.get(column).copied().unwrap_or_default()
+    }
+}
+
+/// Cells hidden under another cell's span, as `(row, column)` indexes,
+/// with the position of the cell covering them
+pub fn covered_cells(columns: &[TableColumn], rows: &[TableRow]) -> HashMap<(usize, usize), (usize, usize)> {
+    let mut covered = HashMap::new();
+    for (r, row) in rows.iter().enumerate() {
+        for (c, column) in columns.iter().enumerate() {
+            let span = row.span(&column.id);
+            for dr in 0..span.rowspan {
+                for dc in 0..span.colspan {
+                    if dr + dc > 0 {
+                        covered.insert((r + dr, c + dc), (r, c));
+                    }
+                }
+            }
+        }
+    }
+    covered
+}
+
+/// Header cells to draw, row by row, with the column headers last
+///
+/// A column whose group cells are all empty (and single-column) gets one
+/// header cell spanning every header row, so a merged "Region" header
+/// above two header rows is drawn once.
+pub fn header_layout<'a>(columns: &'a [TableColumn], header_rows: &'a [Vec<HeaderCell>]) -> Vec<Vec<HeaderSlot<'a>>> {
+    let groups: Vec<Vec<(usize, &HeaderCell)>> = header_rows
+        .iter()
+        .map(|row| {
+            let mut column = 0;
+            row.iter()
+                .map(|cell| {
+                    let start = column;
+                    column += cell.colspan;
+                    (start, cell)
+                })
+                .collect()
+        })
+        .collect();
+    // Columns whose group cells are all single-column and empty
+    let tall: Vec<bool> = (0..columns.len())
+        .map(|c| {
+            groups.iter().all(|row| {
+                row.iter()
+                    .find(|(start, cell)| (*start..start + cell.colspan).contains(&c))
+                    .is_none_or(|(_, cell)| cell.colspan == 1 && cell.text.is_empty())
+            })
+        })
+        .collect();
+
+    let mut layout: Vec<Vec<HeaderSlot>> = Vec::new();
+    for (depth, row) in groups.iter().enumerate() {
+        let mut slots = Vec::new();
+        for &(column, cell) in row {
+            if cell.colspan == 1 && tall.get(column).copied().unwrap_or(false) {
+                if depth == 0 {
+                    slots.push(HeaderSlot { text: &columns[column].header, column, colspan: 1, rowspan: groups.len() + 1 });
+                }
+                continue;
+            }
+            slots.push(HeaderSlot { text: &cell.text, column, colspan: cell.colspan, rowspan: 1 });
+        }
+        layout.push(slots);
+    }
+    layout.push(
+        columns
+            .iter()
+            .enumerate()
+            .filter(|(c, _)| groups.is_empty() || !tall[*c])
+            .map(|(column, c)| HeaderSlot { text: &c.header, column, colspan: 1, rowspan: 1 })
+            .collect(),
+    );
+    layout
+}
+
+/// Cell content. `raw` amounts are exact decimals (see `crate::decimal`);
+/// documents that stored them as floats still read.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum CellValue {
     Text(String),
-    Number {
-        raw: f64,
+    /// Expression over cells of the same table (see `crate::formula`) with
+    /// its computed value; before `Number`, which would otherwise match it
+    /// and drop the formula
+    Formula {
+        formula: String,
+        raw: Decimal,
         display: String,
     },
+    // Before `Number`, which would otherwise also match currency cells and
+    // drop their code
     Currency {
-        raw: f64,
+        raw: Decimal,
         display: String,
         currency: String,
     },
+    Number {
+        raw: Decimal,
+        display: String,
+    },
     Percentage {
-        raw: f64,
+        raw: Decimal,
         display: String,
     },
     Date {
         raw: String,
         display: String,
     },
+    /// Cell removed after signing, holding its commitment
+    Redacted {
+        redacted: String,
+    },
+}
+
+impl ContentBlock {
+    /// Block id, if the block has one
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            ContentBlock::Heading { id, .. }
+            | ContentBlock::Paragraph { id, .. }
+            | ContentBlock::List { id, .. }
+            | ContentBlock::Quote { id, .. }
+            | ContentBlock::Code { id, .. } => id.as_deref(),
+            ContentBlock::Table { id, .. }
+            | ContentBlock::Diagram { id, .. }
+            | ContentBlock::Chart { id, .. }
+            | ContentBlock::Figure { id, .. }
+            | ContentBlock::Footnote { id, .. }
+            | ContentBlock::Redacted { id, .. }
+            | ContentBlock::TextField { id, .. }
+            | ContentBlock::NumberField { id, .. }
+            | ContentBlock::DateField { id, .. }
+            | ContentBlock::Checkbox { id, .. }
+            | ContentBlock::Choice { id, .. }
+            | ContentBlock::SignatureField { id, .. } => Some(id),
+            ContentBlock::PageBreak | ContentBlock::HorizontalRule => None,
+        }
+    }
+
+    /// Serialized `type` tag of the block
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ContentBlock::Heading { .. } => "heading",
+            ContentBlock::Paragraph { .. } => "paragraph",
+            ContentBlock::List { .. } => "list",
+            ContentBlock::Table { .. } => "table",
+            ContentBlock::Diagram { .. } => "diagram",
+            ContentBlock::Chart { .. } => "chart",
+            ContentBlock::Figure { .. } => "figure",
+            ContentBlock::Footnote { .. } => "footnote",
+            ContentBlock::Redacted { .. } => "redacted",
+            ContentBlock::Quote { .. } => "quote",
+            ContentBlock::Code { .. } => "code",
+            ContentBlock::PageBreak => "page_break",
+            ContentBlock::HorizontalRule => "horizontal_rule",
+            ContentBlock::TextField { .. } => "text_field",
+            ContentBlock::NumberField { .. } => "number_field",
+            ContentBlock::DateField { .. } => "date_field",
+            ContentBlock::Checkbox { .. } => "checkbox",
+            ContentBlock::Choice { .. } => "choice",
+            ContentBlock::SignatureField { .. } => "signature_field",
+        }
+    }
+
+    /// Whether the block is a form field, whose value a submission holds
+    pub fn is_field(&self) -> bool {
+        crate::form::FormField::from_block(self).is_some()
+    }
+
+    /// Check the block's structure
+    ///
+    /// Lists must not nest deeper than [`MAX_LIST_DEPTH`] or contain empty
+    /// sublists, quotes must have text and a code language must be a single
+    /// word. Charts need at least one series (exactly one for a pie), colours
+    /// written as `#rrggbb` and an axis minimum below its maximum; that their
+    /// table and columns exist is checked by `crate::lint`. Form fields need
+    /// a label and consistent constraints: a minimum not above the maximum,
+    /// a valid pattern and currency, and distinct choice options.
+    pub fn validate(&self) -> Result<(), String> {
+        let label = || self.id().map(|id| format!("{} '{}'", self.type_name(), id)).unwrap_or_else(|| self.type_name().to_string());
+        match self {
+            ContentBlock::List { items, .. } => {
+                let depth = 1 + items.iter().map(ListItem::depth).max().unwrap_or(0);
+                if depth > MAX_LIST_DEPTH {
+                    return Err(format!("{} nests {} levels deep (max {})", label(), depth, MAX_LIST_DEPTH));
+                }
+                fn empty_sublist(items: &[ListItem]) -> bool {
+                    items.iter().filter_map(|item| item.sublist.as_ref()).any(|list| list.items.is_empty() || empty_sublist(&list.items))
+                }
+                if empty_sublist(items) {
+                    return Err(format!("{} has an empty sublist", label()));
+                }
+            }
+            ContentBlock::Quote { text, .. } if text.plain_text().trim().is_empty() => {
+                return Err(format!("{} has no text", label()));
+            }
+            ContentBlock::Code { language: Some(language), .. }
+                if language.is_empty() || language.chars().any(|c| c.is_whitespace() || c == '`') =>
+            {
+                return Err(format!("{} has invalid language '{}'", label(), language));
+            }
+            ContentBlock::Chart { chart_type, series, axes, .. } => {
+                if series.is_empty() {
+                    return Err(format!("{} has no series", label()));
+                }
+                if *chart_type == ChartType::Pie && series.len() > 1 {
+                    return Err(format!("{} is a pie chart with {} series (expected one)", label(), series.len()));
+                }
+                if let Some(series) = series.iter().find(|s| s.color.as_deref().is_some_and(|c| crate::chart::Color::parse(c).is_none())) {
+                    return Err(format!(
+                        "{} series '{}' has invalid colour '{}' (expected #rrggbb)",
+                        label(),
+                        series.column,
+                        series.color.as_deref().unwrap_or_default()
+                    ));
+                }
+                if let Some(ChartAxes { min: Some(min), max: Some(max), .. }) = axes {
+                    if min >= max {
+                        return Err(format!("{} axis minimum {} is not below its maximum {}", label(), min, max));
+                    }
+                }
+            }
+            ContentBlock::Paragraph { xbrl: Some(tag), .. } => {
+                tag.validate().map_err(|e| format!("{} {}", label(), e))?;
+            }
+            ContentBlock::Table { columns, header_rows, rows, .. } => {
+                validate_table(columns, header_rows.as_deref().unwrap_or_default(), rows).map_err(|e| format!("{} {}", label(), e))?;
+            }
+            _ if self.is_field() => {
+                crate::form::FormField::from_block(self)
+                    .map_or(Ok(()), |field| field.validate())
+                    .map_err(|e| format!("{} {}", label(), e))?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn validate_table(columns: &[TableColumn], header_rows: &[Vec<HeaderCell>], rows: &[TableRow]) -> Result<(), String> {
+    if let Some(column) = columns.iter().find(|c| c.id == ROW_FORMAT_KEY) {
+        return Err(format!("uses the reserved column id '{}'", column.id));
+    }
+    for column in columns {
+        if let Some(code) = column.currency.as_deref().filter(|code| !is_iso_4217(code)) {
+            return Err(format!("column '{}' has unknown currency '{}' (expected an ISO 4217 code)", column.id, code));
+        }
+    }
+    for (i, row) in header_rows.iter().enumerate() {
+        let width: usize = row.iter().map(|cell| cell.colspan).sum();
+        if row.iter().any(|cell| cell.colspan == 0) || width != columns.len() {
+            return Err(format!("header row {} spans {} columns, expected {}", i + 1, width, columns.len()));
+        }
+    }
+
+    let index: HashMap<&str, usize> = columns.iter().enumerate().map(|(i, c)| (c.id.as_str(), i)).collect();
+    let mut covered = HashSet::new();
+    for (r, row) in rows.iter().enumerate() {
+        if row.format.level > MAX_ROW_LEVEL {
+            return Err(format!("row {} has outline level {} (max {})", r + 1, row.format.level, MAX_ROW_LEVEL));
+        }
+        for (column, span) in &row.format.spans {
+            let c = *index.get(column.as_str()).ok_or_else(|| format!("row {} spans unknown column '{}'", r + 1, column))?;
+            if span.colspan == 0 || span.rowspan == 0 || c + span.colspan > columns.len() || r + span.rowspan > rows.len() {
+                return Err(format!("row {} cell '{}' spans past the table", r + 1, column));
+            }
+            for dr in 0..span.rowspan {
+                for dc in 0..span.colspan {
+                    if dr + dc > 0 && !covered.insert((r + dr, c + dc)) {
+                        return Err(format!("row {} cell '{}' overlaps another span", r + 1, column));
+                    }
+                }
+            }
+        }
+        for (column, tag) in &row.format.tags {
+            if !index.contains_key(column.as_str()) {
+                return Err(format!("row {} tags unknown column '{}'", r + 1, column));
+            }
+            tag.validate().map_err(|e| format!("row {} cell '{}': {}", r + 1, column, e))?;
+        }
+    }
+    for &(r, c) in &covered {
+        let (row, column) = (&rows[r], &columns[c].id);
+        if row.cells.contains_key(column) || row.format.spans.contains_key(column) {
+            return Err(format!("row {} cell '{}' is covered by a span", r + 1, column));
+        }
+    }
+    for (r, row) in rows.iter().enumerate() {
+        for (column, cell) in &row.cells {
+            match cell {
+                CellValue::Formula { formula, .. } => {
+                    crate::formula::check_references(formula, columns.len(), rows.len())
+                        .map_err(|e| format!("row {} cell '{}': {}", r + 1, column, e))?;
+                }
+                CellValue::Currency { currency, .. } if !is_iso_4217(currency) => {
+                    return Err(format!(
+                        "row {} cell '{}' has unknown currency '{}' (expected an ISO 4217 code)",
+                        r + 1,
+                        column,
+                        currency
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+impl CellValue {
+    /// Text shown for the cell
+    pub fn display_text(&self) -> &str {
+        match self {
+            CellValue::Text(text) => text,
+            CellValue::Number { display, .. }
+            | CellValue::Formula { display, .. }
+            | CellValue::Currency { display, .. }
+            | CellValue::Percentage { display, .. }
+            | CellValue::Date { display, .. } => display,
+            CellValue::Redacted { .. } => "[redacted]",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -157,6 +993,52 @@ pub enum EdgeType {
     Dotted,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartType {
+    Bar,
+    Line,
+    Pie,
+}
+
+/// Table column plotted as one series of a chart
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChartSeries {
+    pub column: String,
+    /// Legend label; the column header when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Colour as `#rrggbb`; from the default palette when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ChartAxes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_label: Option<String>,
+    /// Bounds of the value axis; fitted to the data and zero when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Decimal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ChartFormat {
+    /// Stack the series of a bar chart instead of grouping them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stacked: Option<bool>,
+    /// Show a legend; by default pies and charts with several series have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legend: Option<bool>,
+    /// Print each value's display text by its bar, point or slice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_labels: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DiagramLayout {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -193,5 +1075,9 @@ pub struct Section {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     pub content: Vec<ContentBlock>,
+    /// Present when the section is encrypted for an audience group; title
+    /// and content are then empty until decrypted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted: Option<EncryptedSection>,
 }
 
@@ -0,0 +1,391 @@
+//! Exact decimal numbers for table amounts
+//!
+//! A `Decimal` is an integer count of units at a decimal scale, so
+//! `1200.50` is 120050 at scale 2. It is serialized as a string that keeps
+//! the scale ("1200.50"), which CBOR and JSON carry without loss.
+//!
+//! Documents written before amounts were exact hold them as floating-point
+//! numbers; those still deserialize, taking the shortest decimal text that
+//! reads back as the same double (`0.1` rather than
+//! `0.1000000000000000055511151231257827`).
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Most significant digits a `Decimal` holds (all fit in an `i128`)
+pub const MAX_DIGITS: u32 = 38;
+
+/// Decimal places kept by multiplication and division; longer results are
+/// rounded half away from zero
+pub const MAX_SCALE: u32 = 18;
+
+/// Exact decimal number: `units / 10^scale`
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    units: i128,
+    scale: u32,
+}
+
+fn pow10(exp: u32) -> Option<i128> {
+    10i128.checked_pow(exp)
+}
+
+/// `value / 10^exp`, rounded half away from zero
+fn div_round(value: i128, exp: u32) -> i128 {
+    match pow10(exp) {
+        Some(divisor) => {
+            let (quotient, remainder) = (value / divisor, value % divisor);
+            if remainder.unsigned_abs() * 2 >= divisor.unsigned_abs() {
+                quotient + value.signum()
+            } else {
+                quotient
+            }
+        }
+        None => 0,
+    }
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal { units: 0, scale: 0 };
+
+    /// `units / 10^scale`; `None` past `MAX_DIGITS` digits or places
+    pub fn new(units: i128, scale: u32) -> Option<Self> {
+        let fits = pow10(MAX_DIGITS).is_some_and(|limit| units.unsigned_abs() < limit.unsigned_abs());
+        (fits && scale <= MAX_DIGITS).then_some(Decimal { units, scale })
+    }
+
+    /// Unscaled integer value
+    pub fn units(&self) -> i128 {
+        self.units
+    }
+
+    /// Digits after the decimal point, trailing zeros included
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Shortest decimal that reads back as `value`; `None` for NaN,
+    /// infinities and magnitudes past `MAX_DIGITS`
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        format!("{}", value).parse().ok()
+    }
+
+    /// Nearest double, for consumers that only take floating point
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.units == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.units < 0
+    }
+
+    /// Whole number with no fractional part
+    pub fn is_integer(&self) -> bool {
+        pow10(self.scale).is_none_or(|p| self.units % p == 0)
+    }
+
+    pub fn abs(&self) -> Decimal {
+        Decimal { units: self.units.abs(), scale: self.scale }
+    }
+
+    pub fn checked_neg(&self) -> Option<Decimal> {
+        Some(Decimal { units: self.units.checked_neg()?, scale: self.scale })
+    }
+
+    /// Same value written with exactly `scale` places; `None` if that
+    /// would drop digits or overflow
+    pub fn with_scale(&self, scale: u32) -> Option<Decimal> {
+        if scale >= self.scale {
+            let units = self.units.checked_mul(pow10(scale - self.scale)?)?;
+            return Decimal::new(units, scale);
+        }
+        let divisor = pow10(self.scale - scale)?;
+        (self.units % divisor == 0).then_some(Decimal { units: self.units / divisor, scale })
+    }
+
+    /// Same value without trailing fractional zeros
+    pub fn normalize(&self) -> Decimal {
+        let mut d = *self;
+        while d.scale > 0 && d.units % 10 == 0 {
+            d.units /= 10;
+            d.scale -= 1;
+        }
+        d
+    }
+
+    /// Rounded half away from zero to `digits` places; negative `digits`
+    /// round to tens, hundreds, ... as spreadsheets do
+    pub fn round(&self, digits: i32) -> Option<Decimal> {
+        if digits >= self.scale as i32 {
+            return Some(*self);
+        }
+        let drop = (self.scale as i32 - digits) as u32;
+        let rounded = div_round(self.units, drop);
+        if digits >= 0 {
+            Decimal::new(rounded, digits as u32)
+        } else {
+            Decimal::new(rounded.checked_mul(pow10(digits.unsigned_abs())?)?, 0)
+        }
+    }
+
+    /// `self * 10^exp`, exact
+    pub fn shift(&self, exp: i32) -> Option<Decimal> {
+        if exp <= 0 {
+            return Decimal::new(self.units, self.scale.checked_add(exp.unsigned_abs())?);
+        }
+        let exp = exp as u32;
+        if exp <= self.scale {
+            Decimal::new(self.units, self.scale - exp)
+        } else {
+            Decimal::new(self.units.checked_mul(pow10(exp - self.scale)?)?, 0)
+        }
+    }
+
+    fn aligned(&self, other: &Decimal) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        Some((self.with_scale(scale)?.units, other.with_scale(scale)?.units, scale))
+    }
+
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.aligned(other)?;
+        Decimal::new(a.checked_add(b)?, scale)
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.aligned(other)?;
+        Decimal::new(a.checked_sub(b)?, scale)
+    }
+
+    pub fn checked_mul(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b) = (self.normalize(), other.normalize());
+        let scale = a.scale + b.scale;
+        let units = a.units.checked_mul(b.units)?;
+        if scale <= MAX_SCALE {
+            Decimal::new(units, scale)
+        } else {
+            Decimal::new(div_round(units, scale - MAX_SCALE), MAX_SCALE)
+        }
+    }
+
+    /// Quotient to `MAX_SCALE` places, or as many as fit for very large
+    /// dividends; `None` when dividing by zero
+    pub fn checked_div(&self, other: &Decimal) -> Option<Decimal> {
+        if other.units == 0 {
+            return None;
+        }
+        // a/10^sa / (b/10^sb) = a * 10^(sb + places + 1 - sa) / b at scale
+        // places + 1; truncating to that one spare digit is enough, since
+        // any remainder beyond it cannot turn a digit below 5 into a half
+        let (a, b) = (self.normalize(), other.normalize());
+        (0..=MAX_SCALE).rev().find_map(|places| {
+            let exp = (b.scale + places + 1).checked_sub(a.scale)?;
+            let numerator = a.units.checked_mul(pow10(exp)?)?;
+            Decimal::new(div_round(numerator / b.units, 1), places).map(|d| d.normalize())
+        })
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Integer parts first, then fractions at a common scale; neither
+        // step can overflow since both scales are at most MAX_DIGITS
+        let split = |d: &Decimal| match pow10(d.scale) {
+            Some(p) => (d.units / p, d.units % p),
+            None => (0, d.units),
+        };
+        let ((ia, fa), (ib, fb)) = (split(self), split(other));
+        ia.cmp(&ib).then_with(|| {
+            let scale = self.scale.max(other.scale);
+            let widen = |f: i128, s: u32| pow10(scale - s).map_or(f, |p| f * p);
+            widen(fa, self.scale).cmp(&widen(fb, other.scale))
+        })
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Decimal { units: value as i128, scale: 0 }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.units.unsigned_abs().to_string();
+        let sign = if self.units < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let padded = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = padded.split_at(padded.len() - scale);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
+
+/// Text that is not a plain decimal number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError(String);
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid decimal '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    /// Optional sign, digits, and an optional fraction: `-1200.50`, `.5`.
+    /// No exponents, grouping separators or surrounding spaces.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseDecimalError(s.chars().take(64).collect());
+        let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+        let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.len() + fraction.len() == 0 || !all_digits(whole) || !all_digits(fraction) {
+            return Err(err());
+        }
+        let whole = whole.trim_start_matches('0');
+        if (whole.len() + fraction.len()) as u32 > MAX_DIGITS {
+            return Err(err());
+        }
+        let units: i128 = format!("0{}{}", whole, fraction).parse().map_err(|_| err())?;
+        let units = if s.starts_with('-') { -units } else { units };
+        Ok(Decimal { units, scale: fraction.len() as u32 })
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+struct DecimalVisitor;
+
+impl Visitor<'_> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal string or a number")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Decimal, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Decimal, E> {
+        Ok(Decimal::from(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Decimal, E> {
+        Ok(Decimal { units: v as i128, scale: 0 })
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Decimal, E> {
+        Decimal::from_f64(v).ok_or_else(|| E::custom(format!("number {} has no decimal form", v)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_keep_scale() {
+        assert_eq!(d("1200.50").to_string(), "1200.50");
+        assert_eq!(d("-0.05").to_string(), "-0.05");
+        assert_eq!(d(".5").to_string(), "0.5");
+        assert_eq!(d("+7").to_string(), "7");
+        assert_eq!(d("1200.50"), d("1200.5"));
+        for bad in ["", ".", "-", "1e5", "1,200", " 1", "1.2.3", "12345678901234567890123456789012345678901"] {
+            assert!(bad.parse::<Decimal>().is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_is_exact() {
+        assert_eq!(d("0.1").checked_add(&d("0.2")).unwrap().to_string(), "0.3");
+        assert_eq!(d("1200.50").checked_sub(&d("0.25")).unwrap().to_string(), "1200.25");
+        assert_eq!(d("19.99").checked_mul(&d("3")).unwrap().to_string(), "59.97");
+        assert_eq!(d("10").checked_div(&d("4")).unwrap().to_string(), "2.5");
+        assert_eq!(d("2").checked_div(&d("3")).unwrap().to_string(), "0.666666666666666667");
+        assert_eq!(d("-2").checked_div(&d("3")).unwrap().to_string(), "-0.666666666666666667");
+        assert!(d("1").checked_div(&Decimal::ZERO).is_none());
+        assert_eq!(d("100000000000000000000000").checked_div(&d("8")).unwrap().to_string(), "12500000000000000000000");
+        assert!(d("99999999999999999999999999999999999999").checked_add(&d("1")).is_none());
+    }
+
+    #[test]
+    fn test_round_half_away_from_zero() {
+        assert_eq!(d("2.675").round(2).unwrap().to_string(), "2.68");
+        assert_eq!(d("-2.675").round(2).unwrap().to_string(), "-2.68");
+        assert_eq!(d("1.005").round(2).unwrap().to_string(), "1.01");
+        assert_eq!(d("1250").round(-2).unwrap().to_string(), "1300");
+        assert_eq!(d("1.5").round(3).unwrap().to_string(), "1.5");
+        assert_eq!(d("12.5").shift(2).unwrap().to_string(), "1250");
+        assert_eq!(d("12.5").shift(-3).unwrap().to_string(), "0.0125");
+    }
+
+    #[test]
+    fn test_ordering_across_scales() {
+        let mut values = [d("-1.2"), d("0.2"), d("-1.5"), d("-0.5"), d("1.10"), d("1.1"), d("3")];
+        values.sort();
+        let sorted: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(sorted, ["-1.5", "-1.2", "-0.5", "0.2", "1.10", "1.1", "3"]);
+    }
+
+    #[test]
+    fn test_serializes_as_string_and_reads_legacy_floats() {
+        assert_eq!(serde_json::to_string(&d("1200.50")).unwrap(), "\"1200.50\"");
+        let exact: Decimal = serde_json::from_str("\"1200.50\"").unwrap();
+        assert_eq!(exact.scale(), 2);
+        let legacy: Decimal = serde_json::from_str("0.1").unwrap();
+        assert_eq!(legacy.to_string(), "0.1");
+        let integer: Decimal = serde_json::from_str("-42").unwrap();
+        assert_eq!(integer.to_string(), "-42");
+        assert!(serde_json::from_str::<Decimal>("\"2025-01-01\"").is_err());
+
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&1234.5f64, &mut cbor).unwrap();
+        let from_cbor: Decimal = ciborium::from_reader(cbor.as_slice()).unwrap();
+        assert_eq!(from_cbor.to_string(), "1234.5");
+    }
+}
@@ -0,0 +1,922 @@
+//! Layout and SVG rendering of `Diagram` blocks
+//!
+//! Hierarchical diagrams and flowcharts get a layered (Sugiyama) layout:
+//! edges that close a cycle are reversed, nodes are ranked by the longest
+//! path from a source, edges spanning several ranks are routed through
+//! virtual nodes, ranks are ordered by barycentre sweeps (keeping the order
+//! with the fewest crossings) and nodes are then pulled towards their
+//! neighbours. Relationship diagrams get a force-directed layout
+//! (Fruchterman-Reingold) started from a circle in node order, followed by
+//! overlap removal. Both are deterministic for a given diagram.
+//!
+//! Coordinates are in points with the origin at the top left of the
+//! diagram. The PDF and HTML exporters and the WASM build share the layout;
+//! the PDF exporter measures labels with its own font metrics through
+//! `layout_with`.
+
+use crate::content::{
+    DiagramEdge, DiagramLayout, DiagramNode, DiagramShape, DiagramType, EdgeType, LayoutDirection, LayoutSpacing,
+};
+use crate::html::escape;
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+use std::fmt::Write;
+
+/// Font size of node labels, in points
+pub const LABEL_SIZE: f32 = 8.0;
+
+/// Line height of node labels, in points
+pub const LABEL_LINE: f32 = LABEL_SIZE * 1.3;
+
+/// Font size of edge labels, in points
+pub const EDGE_LABEL_SIZE: f32 = 7.0;
+
+/// Barycentre sweeps (alternately down and up) when ordering ranks
+const ORDER_SWEEPS: usize = 12;
+
+/// Passes pulling nodes towards their neighbours in the adjacent rank
+const ALIGN_PASSES: usize = 8;
+
+/// Iterations of the force-directed layout
+const FORCE_ITERATIONS: usize = 300;
+
+/// Pull of every node towards the centre, keeping unconnected parts close
+const GRAVITY: f64 = 0.05;
+
+/// Passes pushing overlapping nodes apart after the force-directed layout
+const OVERLAP_PASSES: usize = 100;
+
+/// Space around the diagram in SVG output, in points
+const SVG_MARGIN: f32 = 4.0;
+
+/// A placed node
+#[derive(Debug, Clone)]
+pub struct NodeBox {
+    pub id: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub lines: Vec<String>,
+    pub shape: DiagramShape,
+    /// The node's `style`, emitted as a class in SVG
+    pub style: Option<String>,
+}
+
+impl NodeBox {
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Where the line from the centre towards `target` leaves the shape
+    fn boundary_towards(&self, target: (f32, f32)) -> (f32, f32) {
+        let (cx, cy) = self.center();
+        let (dx, dy) = (target.0 - cx, target.1 - cy);
+        if dx == 0.0 && dy == 0.0 {
+            return (cx, cy);
+        }
+        let (rx, ry) = (self.width / 2.0, self.height / 2.0);
+        let t = match self.shape {
+            DiagramShape::Circle => 1.0 / ((dx / rx).powi(2) + (dy / ry).powi(2)).sqrt(),
+            DiagramShape::Diamond => 1.0 / (dx.abs() / rx + dy.abs() / ry),
+            DiagramShape::Box | DiagramShape::Rounded => {
+                let tx = if dx != 0.0 { rx / dx.abs() } else { f32::INFINITY };
+                let ty = if dy != 0.0 { ry / dy.abs() } else { f32::INFINITY };
+                tx.min(ty)
+            }
+        };
+        (cx + dx * t, cy + dy * t)
+    }
+}
+
+/// A routed edge, drawn as a polyline with an arrowhead at its last point
+#[derive(Debug, Clone)]
+pub struct EdgePath {
+    /// Id of the source node
+    pub from: String,
+    /// Id of the target node
+    pub to: String,
+    /// At least two points, from the source's outline to the target's
+    pub points: Vec<(f32, f32)>,
+    pub edge_type: EdgeType,
+    pub label: Option<String>,
+}
+
+impl EdgePath {
+    /// Halfway along the path, where the label goes
+    pub fn midpoint(&self) -> (f32, f32) {
+        let length = |(a, b): (&(f32, f32), &(f32, f32))| ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+        let total: f32 = self.points.iter().zip(self.points.iter().skip(1)).map(length).sum();
+        let mut remaining = total / 2.0;
+        for (a, b) in self.points.iter().zip(self.points.iter().skip(1)) {
+            let segment = length((a, b));
+            if segment > 0.0 && remaining <= segment {
+                let t = remaining / segment;
+                return (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+            }
+            remaining -= segment;
+        }
+        self.points.first().copied().unwrap_or_default()
+    }
+}
+
+/// Placed nodes and edges of one diagram
+#[derive(Debug, Clone)]
+pub struct DiagramGeometry {
+    pub width: f32,
+    pub height: f32,
+    pub nodes: Vec<NodeBox>,
+    pub edges: Vec<EdgePath>,
+}
+
+/// Approximate width of `text` in points at `LABEL_SIZE` in a sans-serif face
+///
+/// For output whose font is chosen by the reader (SVG in a browser):
+/// narrow, wide and capital letters are told apart and everything else
+/// counts as an average Helvetica glyph.
+pub fn label_width(text: &str) -> f32 {
+    let ems: f32 = text
+        .chars()
+        .map(|c| match c {
+            'f' | 'i' | 'j' | 'l' | 'r' | 't' | 'I' | ' ' | '.' | ',' | ':' | ';' | '\'' | '|' | '!' | '(' | ')' => 0.3,
+            'm' | 'w' | 'M' | 'W' | '@' | '%' => 0.86,
+            'A'..='Z' => 0.68,
+            _ => 0.556,
+        })
+        .sum();
+    ems * LABEL_SIZE
+}
+
+/// Place the nodes of a diagram and route its edges, measuring labels with `label_width`
+pub fn layout(
+    diagram_type: &DiagramType,
+    nodes: &[DiagramNode],
+    edges: &[DiagramEdge],
+    options: Option<&DiagramLayout>,
+) -> DiagramGeometry {
+    layout_with(diagram_type, nodes, edges, options, label_width)
+}
+
+/// Place the nodes of a diagram and route its edges
+///
+/// `measure` gives the width in points of one label line at `LABEL_SIZE`.
+/// Edges whose ends are not both nodes of the diagram, and edges from a
+/// node to itself, are left out.
+pub fn layout_with(
+    diagram_type: &DiagramType,
+    nodes: &[DiagramNode],
+    edges: &[DiagramEdge],
+    options: Option<&DiagramLayout>,
+    measure: impl Fn(&str) -> f32,
+) -> DiagramGeometry {
+    let (gap, rank_gap) = match options.and_then(|l| l.spacing.clone()) {
+        Some(LayoutSpacing::Compact) => (12.0, 24.0),
+        Some(LayoutSpacing::Wide) => (40.0, 64.0),
+        _ => (24.0, 40.0),
+    };
+    let direction = options
+        .and_then(|l| l.direction.clone())
+        .unwrap_or(LayoutDirection::TopDown);
+
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (n.id.as_str(), i)).collect();
+    let routed: Vec<(&DiagramEdge, (usize, usize))> = edges
+        .iter()
+        .filter_map(|e| Some((e, (*index.get(e.from.as_str())?, *index.get(e.to.as_str())?))))
+        .filter(|(_, (from, to))| from != to)
+        .collect();
+    let links: Vec<(usize, usize)> = routed.iter().map(|&(_, link)| link).collect();
+
+    let mut boxes: Vec<NodeBox> = nodes.iter().map(|n| node_box(n, &measure)).collect();
+    let waypoints = match diagram_type {
+        DiagramType::Relationship => force_directed(&mut boxes, &links, gap, rank_gap),
+        DiagramType::Hierarchical | DiagramType::Flowchart => layered(&mut boxes, &links, &direction, gap, rank_gap),
+    };
+
+    let paths = routed
+        .iter()
+        .zip(waypoints)
+        .map(|(&(edge, (from, to)), via)| {
+            let (from, to) = (&boxes[from], &boxes[to]);
+            let first = via.first().copied().unwrap_or_else(|| to.center());
+            let last = via.last().copied().unwrap_or_else(|| from.center());
+            let mut points = vec![from.boundary_towards(first)];
+            points.extend(via);
+            points.push(to.boundary_towards(last));
+            EdgePath {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                points,
+                edge_type: edge.edge_type.clone(),
+                label: edge.label.clone(),
+            }
+        })
+        .collect();
+
+    let width = boxes.iter().map(|b| b.x + b.width).fold(0.0, f32::max);
+    let height = boxes.iter().map(|b| b.y + b.height).fold(0.0, f32::max);
+    DiagramGeometry { width, height, nodes: boxes, edges: paths }
+}
+
+fn node_box(node: &DiagramNode, measure: &impl Fn(&str) -> f32) -> NodeBox {
+    let lines: Vec<String> = node.label.lines().map(str::to_string).collect();
+    let text = lines.iter().map(|l| measure(l)).fold(0.0, f32::max);
+    let mut width = (text + 16.0).max(56.0);
+    let mut height = lines.len().max(1) as f32 * LABEL_LINE + 11.0;
+    let shape = node.shape.clone().unwrap_or(DiagramShape::Box);
+    match shape {
+        DiagramShape::Diamond => {
+            width *= 1.5;
+            height *= 1.8;
+        }
+        DiagramShape::Circle => {
+            width *= 1.2;
+            height = height.max(width * 0.6);
+        }
+        DiagramShape::Box | DiagramShape::Rounded => {}
+    }
+    NodeBox {
+        id: node.id.clone(),
+        x: 0.0,
+        y: 0.0,
+        width,
+        height,
+        lines,
+        shape,
+        style: node.style.clone(),
+    }
+}
+
+/// Edges with those closing a cycle reversed, found by depth-first search
+fn acyclic(count: usize, links: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut outgoing = vec![Vec::new(); count];
+    for (i, &(from, _)) in links.iter().enumerate() {
+        outgoing[from].push(i);
+    }
+    // 0 = unvisited, 1 = on the current path, 2 = finished
+    let mut state = vec![0u8; count];
+    let mut reversed = vec![false; links.len()];
+    for root in 0..count {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        let mut stack = vec![(root, 0)];
+        while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+            match outgoing[node].get(*next) {
+                Some(&edge) => {
+                    *next += 1;
+                    let to = links[edge].1;
+                    match state[to] {
+                        0 => {
+                            state[to] = 1;
+                            stack.push((to, 0));
+                        }
+                        1 => reversed[edge] = true,
+                        _ => {}
+                    }
+                }
+                None => {
+                    state[node] = 2;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    links
+        .iter()
+        .zip(reversed)
+        .map(|(&(from, to), back)| if back { (to, from) } else { (from, to) })
+        .collect()
+}
+
+/// Rank of every node of an acyclic graph: the longest path to it from a
+/// node without predecessors
+fn ranks(count: usize, links: &[(usize, usize)]) -> Vec<usize> {
+    let mut incoming = vec![0usize; count];
+    for &(_, to) in links {
+        incoming[to] += 1;
+    }
+    let mut rank = vec![0; count];
+    let mut ready: Vec<usize> = (0..count).filter(|&i| incoming[i] == 0).collect();
+    while let Some(node) = ready.pop() {
+        for &(from, to) in links.iter().filter(|l| l.0 == node) {
+            rank[to] = rank[to].max(rank[from] + 1);
+            incoming[to] -= 1;
+            if incoming[to] == 0 {
+                ready.push(to);
+            }
+        }
+    }
+    rank
+}
+
+/// Edge crossings between adjacent ranks for the given positions
+fn crossings(segments: &[(usize, usize)], rank: &[usize], position: &[usize]) -> usize {
+    let mut by_rank: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    for &(upper, lower) in segments {
+        by_rank.entry(rank[upper]).or_default().push((position[upper], position[lower]));
+    }
+    by_rank
+        .values()
+        .map(|pairs| {
+            let mut count = 0;
+            for (i, a) in pairs.iter().enumerate() {
+                count += pairs[i + 1..]
+                    .iter()
+                    .filter(|b| (a.0 < b.0 && a.1 > b.1) || (a.0 > b.0 && a.1 < b.1))
+                    .count();
+            }
+            count
+        })
+        .sum()
+}
+
+/// Layered layout; returns the waypoints of every link, in link direction
+fn layered(
+    boxes: &mut [NodeBox],
+    links: &[(usize, usize)],
+    direction: &LayoutDirection,
+    gap: f32,
+    rank_gap: f32,
+) -> Vec<Vec<(f32, f32)>> {
+    let horizontal = matches!(direction, LayoutDirection::LeftRight | LayoutDirection::RightLeft);
+    let count = boxes.len();
+    let oriented = acyclic(count, links);
+    let node_rank = ranks(count, &oriented);
+
+    // Vertices are the nodes followed by one virtual node for every rank a
+    // long edge passes through
+    let mut rank = node_rank.clone();
+    let mut chains = Vec::with_capacity(oriented.len());
+    for &(from, to) in &oriented {
+        let mut chain = vec![from];
+        for r in node_rank[from] + 1..node_rank[to] {
+            rank.push(r);
+            chain.push(rank.len() - 1);
+        }
+        chain.push(to);
+        chains.push(chain);
+    }
+    let vertices = rank.len();
+    let segments: Vec<(usize, usize)> = chains.iter().flat_map(|c| c.windows(2).map(|w| (w[0], w[1]))).collect();
+    let mut above = vec![Vec::new(); vertices];
+    let mut below = vec![Vec::new(); vertices];
+    for &(upper, lower) in &segments {
+        below[upper].push(lower);
+        above[lower].push(upper);
+    }
+
+    let rank_count = node_rank.iter().max().map(|r| r + 1).unwrap_or(0);
+    let mut layers: Vec<Vec<usize>> = vec![Vec::new(); rank_count];
+    for (v, &r) in rank.iter().enumerate() {
+        layers[r].push(v);
+    }
+    let mut position = vec![0usize; vertices];
+    let index_positions = |layers: &[Vec<usize>], position: &mut [usize]| {
+        for layer in layers {
+            for (p, &v) in layer.iter().enumerate() {
+                position[v] = p;
+            }
+        }
+    };
+    index_positions(&layers, &mut position);
+
+    // Order ranks by the mean position of their neighbours, sweeping down
+    // and up, and keep the order with the fewest crossings
+    let mut best = layers.clone();
+    let mut fewest = crossings(&segments, &rank, &position);
+    for sweep in 0..ORDER_SWEEPS {
+        if fewest == 0 {
+            break;
+        }
+        let (order, neighbours): (Vec<usize>, _) = if sweep % 2 == 0 {
+            ((1..rank_count).collect(), &above)
+        } else {
+            ((0..rank_count.saturating_sub(1)).rev().collect(), &below)
+        };
+        for r in order {
+            let key = |v: usize| {
+                let adjacent: &Vec<usize> = &neighbours[v];
+                if adjacent.is_empty() {
+                    position[v] as f32
+                } else {
+                    adjacent.iter().map(|&n| position[n] as f32).sum::<f32>() / adjacent.len() as f32
+                }
+            };
+            let mut keyed: Vec<(f32, usize)> = layers[r].iter().map(|&v| (key(v), v)).collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[r] = keyed.into_iter().map(|(_, v)| v).collect();
+            for (p, &v) in layers[r].iter().enumerate() {
+                position[v] = p;
+            }
+        }
+        let found = crossings(&segments, &rank, &position);
+        if found < fewest {
+            fewest = found;
+            best = layers.clone();
+        }
+    }
+    let layers = best;
+
+    // Extent of each vertex across the flow (breadth) and along it (depth)
+    let breadth_of = |v: usize| match boxes.get(v) {
+        Some(b) if horizontal => b.height,
+        Some(b) => b.width,
+        None => 0.0,
+    };
+    let depth_of = |v: usize| match boxes.get(v) {
+        Some(b) if horizontal => b.width,
+        Some(b) => b.height,
+        None => 0.0,
+    };
+
+    // Pack every rank, centred on the widest, then pull vertices towards
+    // the mean of their neighbours without reordering or overlapping them
+    let mut centre = vec![0.0f32; vertices];
+    let packed: Vec<f32> = layers
+        .iter()
+        .map(|layer| layer.iter().map(|&v| breadth_of(v)).sum::<f32>() + gap * layer.len().saturating_sub(1) as f32)
+        .collect();
+    let widest = packed.iter().cloned().fold(0.0, f32::max);
+    for (layer, breadth) in layers.iter().zip(&packed) {
+        let mut cursor = (widest - breadth) / 2.0;
+        for &v in layer {
+            centre[v] = cursor + breadth_of(v) / 2.0;
+            cursor += breadth_of(v) + gap;
+        }
+    }
+    for pass in 0..ALIGN_PASSES {
+        let (order, neighbours): (Vec<usize>, _) = if pass % 2 == 0 {
+            ((1..rank_count).collect(), &above)
+        } else {
+            ((0..rank_count.saturating_sub(1)).rev().collect(), &below)
+        };
+        for r in order {
+            let layer = &layers[r];
+            let desired: Vec<f32> = layer
+                .iter()
+                .map(|&v| {
+                    let adjacent: &Vec<usize> = &neighbours[v];
+                    if adjacent.is_empty() {
+                        centre[v]
+                    } else {
+                        adjacent.iter().map(|&n| centre[n]).sum::<f32>() / adjacent.len() as f32
+                    }
+                })
+                .collect();
+            let separation = |i: usize| (breadth_of(layer[i]) + breadth_of(layer[i + 1])) / 2.0 + gap;
+            // Both sweeps keep the separations, so their mean does too
+            let mut forward = desired.clone();
+            for i in 1..layer.len() {
+                forward[i] = forward[i].max(forward[i - 1] + separation(i - 1));
+            }
+            let mut backward = desired;
+            for i in (0..layer.len().saturating_sub(1)).rev() {
+                backward[i] = backward[i].min(backward[i + 1] - separation(i));
+            }
+            for (i, &v) in layer.iter().enumerate() {
+                centre[v] = (forward[i] + backward[i]) / 2.0;
+            }
+        }
+    }
+    let start = (0..vertices)
+        .map(|v| centre[v] - breadth_of(v) / 2.0)
+        .fold(f32::INFINITY, f32::min);
+    let start = if start.is_finite() { start } else { 0.0 };
+    for c in centre.iter_mut() {
+        *c -= start;
+    }
+    let breadth = (0..vertices).map(|v| centre[v] + breadth_of(v) / 2.0).fold(0.0, f32::max);
+
+    let depths: Vec<f32> = layers
+        .iter()
+        .map(|layer| layer.iter().map(|&v| depth_of(v)).fold(0.0, f32::max))
+        .collect();
+    let mut offsets = Vec::with_capacity(rank_count);
+    let mut offset = 0.0;
+    for depth in &depths {
+        offsets.push(offset);
+        offset += depth + rank_gap;
+    }
+    let depth = (offset - rank_gap).max(0.0);
+    let (width, height) = if horizontal { (depth, breadth) } else { (breadth, depth) };
+
+    let place = |along: f32, across: f32| {
+        let (x, y) = if horizontal { (along, across) } else { (across, along) };
+        match direction {
+            LayoutDirection::BottomUp => (x, height - y),
+            LayoutDirection::RightLeft => (width - x, y),
+            LayoutDirection::TopDown | LayoutDirection::LeftRight => (x, y),
+        }
+    };
+    for (v, b) in boxes.iter_mut().enumerate() {
+        let r = rank[v];
+        let (cx, cy) = place(offsets[r] + depths[r] / 2.0, centre[v]);
+        b.x = cx - b.width / 2.0;
+        b.y = cy - b.height / 2.0;
+    }
+
+    chains
+        .iter()
+        .zip(links.iter().zip(&oriented))
+        .map(|(chain, (link, oriented))| {
+            let mut via: Vec<(f32, f32)> = chain[1..chain.len() - 1]
+                .iter()
+                .map(|&v| place(offsets[rank[v]] + depths[rank[v]] / 2.0, centre[v]))
+                .collect();
+            if link != oriented {
+                via.reverse();
+            }
+            via
+        })
+        .collect()
+}
+
+/// Force-directed layout; edges are straight, so there are no waypoints
+fn force_directed(boxes: &mut [NodeBox], links: &[(usize, usize)], gap: f32, rank_gap: f32) -> Vec<Vec<(f32, f32)>> {
+    let count = boxes.len();
+    if count > 0 {
+        let half = |b: &NodeBox| (b.width as f64 / 2.0, b.height as f64 / 2.0);
+        let mean_diagonal = boxes.iter().map(|b| (b.width as f64).hypot(b.height as f64)).sum::<f64>() / count as f64;
+        let ideal = mean_diagonal + rank_gap as f64;
+        let radius = if count > 1 { (ideal * count as f64 / TAU).max(ideal / 2.0) } else { 0.0 };
+        let mut position: Vec<(f64, f64)> = (0..count)
+            .map(|i| {
+                let angle = TAU * i as f64 / count as f64 - PI / 2.0;
+                (radius * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+
+        let mut temperature = ideal;
+        let cooling = temperature / FORCE_ITERATIONS as f64;
+        for _ in 0..FORCE_ITERATIONS {
+            let mut shift = vec![(0.0f64, 0.0f64); count];
+            for i in 0..count {
+                for j in i + 1..count {
+                    let (dx, dy) = (position[i].0 - position[j].0, position[i].1 - position[j].1);
+                    let distance = dx.hypot(dy).max(0.01);
+                    let force = ideal * ideal / distance / distance;
+                    shift[i].0 += dx * force;
+                    shift[i].1 += dy * force;
+                    shift[j].0 -= dx * force;
+                    shift[j].1 -= dy * force;
+                }
+            }
+            for &(a, b) in links {
+                let (dx, dy) = (position[a].0 - position[b].0, position[a].1 - position[b].1);
+                let force = dx.hypot(dy) / ideal;
+                shift[a].0 -= dx * force;
+                shift[a].1 -= dy * force;
+                shift[b].0 += dx * force;
+                shift[b].1 += dy * force;
+            }
+            for (p, s) in position.iter_mut().zip(&shift) {
+                let s = (s.0 - p.0 * GRAVITY, s.1 - p.1 * GRAVITY);
+                let length = s.0.hypot(s.1);
+                if length > 0.0 {
+                    let step = length.min(temperature) / length;
+                    p.0 += s.0 * step;
+                    p.1 += s.1 * step;
+                }
+            }
+            temperature -= cooling;
+        }
+
+        // Push overlapping nodes apart along the axis that overlaps least
+        let gap = gap as f64;
+        for _ in 0..OVERLAP_PASSES {
+            let mut moved = false;
+            for i in 0..count {
+                for j in i + 1..count {
+                    let ((wi, hi), (wj, hj)) = (half(&boxes[i]), half(&boxes[j]));
+                    let (dx, dy) = (position[i].0 - position[j].0, position[i].1 - position[j].1);
+                    let (over_x, over_y) = (wi + wj + gap - dx.abs(), hi + hj + gap - dy.abs());
+                    if over_x <= 0.0 || over_y <= 0.0 {
+                        continue;
+                    }
+                    moved = true;
+                    if over_x < over_y {
+                        let push = over_x / 2.0 * if dx < 0.0 { -1.0 } else { 1.0 };
+                        position[i].0 += push;
+                        position[j].0 -= push;
+                    } else {
+                        let push = over_y / 2.0 * if dy < 0.0 { -1.0 } else { 1.0 };
+                        position[i].1 += push;
+                        position[j].1 -= push;
+                    }
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+
+        let left = boxes.iter().zip(&position).map(|(b, p)| p.0 - half(b).0).fold(f64::INFINITY, f64::min);
+        let top = boxes.iter().zip(&position).map(|(b, p)| p.1 - half(b).1).fold(f64::INFINITY, f64::min);
+        for (b, p) in boxes.iter_mut().zip(&position) {
+            let (w, h) = half(b);
+            b.x = (p.0 - w - left) as f32;
+            b.y = (p.1 - h - top) as f32;
+        }
+    }
+    vec![Vec::new(); links.len()]
+}
+
+/// Characters of `id` usable in an SVG fragment identifier
+fn fragment(id: &str) -> String {
+    let id: String = id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if id.is_empty() {
+        "diagram".to_string()
+    } else {
+        id
+    }
+}
+
+/// Render placed nodes and edges as a standalone SVG element
+///
+/// `id` names the arrowhead marker, so several diagrams can share a page.
+/// Colours and strokes are attributes and need no style sheet; edges carry
+/// the `diagram-edge` class and nodes `diagram-node` plus their `style`.
+/// The title and a plain-text list of the edges are given as `title` and
+/// `desc` for assistive technology.
+pub fn render_svg(geometry: &DiagramGeometry, id: &str, title: Option<&str>) -> String {
+    let marker = format!("{}-arrow", fragment(id));
+    let labels: HashMap<&str, String> = geometry.nodes.iter().map(|n| (n.id.as_str(), n.lines.join(" "))).collect();
+    let label = |id: &str| labels.get(id).cloned().unwrap_or_else(|| id.to_string());
+    let (width, height) = (geometry.width + 2.0 * SVG_MARGIN, geometry.height + 2.0 * SVG_MARGIN);
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"diagram\" width=\"{:.1}\" height=\"{:.1}\" viewBox=\"{:.1} {:.1} {:.1} {:.1}\" role=\"img\" font-family=\"Helvetica, Arial, sans-serif\">",
+        width, height, -SVG_MARGIN, -SVG_MARGIN, width, height
+    );
+    if let Some(title) = title {
+        let _ = writeln!(out, "<title>{}</title>", escape(title));
+    }
+    if !geometry.edges.is_empty() {
+        let description: Vec<String> = geometry
+            .edges
+            .iter()
+            .map(|edge| {
+                let line = format!("{} \u{2192} {}", label(&edge.from), label(&edge.to));
+                match edge.label {
+                    Some(ref text) => format!("{} ({})", line, text),
+                    None => line,
+                }
+            })
+            .collect();
+        let _ = writeln!(out, "<desc>{}</desc>", escape(&description.join("; ")));
+    }
+    let _ = writeln!(
+        out,
+        "<defs><marker id=\"{}\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"6\" markerHeight=\"6\" orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\" fill=\"#595959\"/></marker></defs>",
+        marker
+    );
+
+    for edge in &geometry.edges {
+        let points: Vec<String> = edge.points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+        let dash = match edge.edge_type {
+            EdgeType::Solid => "",
+            EdgeType::Dashed => " stroke-dasharray=\"6 3\"",
+            EdgeType::Dotted => " stroke-dasharray=\"1 3\" stroke-linecap=\"round\"",
+        };
+        let _ = writeln!(
+            out,
+            "<polyline class=\"diagram-edge\" points=\"{}\" fill=\"none\" stroke=\"#595959\" stroke-width=\"1\"{} marker-end=\"url(#{})\"/>",
+            points.join(" "),
+            dash,
+            marker
+        );
+    }
+
+    for node in &geometry.nodes {
+        let class = match node.style {
+            Some(ref style) => format!("diagram-node {}", escape(style)),
+            None => "diagram-node".to_string(),
+        };
+        let _ = writeln!(out, "<g class=\"{}\">", class);
+        let paint = "fill=\"#edf2fc\" stroke=\"#334d80\" stroke-width=\"1\"";
+        let (x, y, w, h) = (node.x, node.y, node.width, node.height);
+        let _ = match node.shape {
+            DiagramShape::Box => writeln!(out, "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" {}/>", x, y, w, h, paint),
+            DiagramShape::Rounded => writeln!(
+                out,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{:.1}\" {}/>",
+                x,
+                y,
+                w,
+                h,
+                (w.min(h) * 0.25).min(8.5),
+                paint
+            ),
+            DiagramShape::Circle => writeln!(
+                out,
+                "<ellipse cx=\"{:.1}\" cy=\"{:.1}\" rx=\"{:.1}\" ry=\"{:.1}\" {}/>",
+                x + w / 2.0,
+                y + h / 2.0,
+                w / 2.0,
+                h / 2.0,
+                paint
+            ),
+            DiagramShape::Diamond => writeln!(
+                out,
+                "<polygon points=\"{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" {}/>",
+                x + w / 2.0,
+                y,
+                x + w,
+                y + h / 2.0,
+                x + w / 2.0,
+                y + h,
+                x,
+                y + h / 2.0,
+                paint
+            ),
+        };
+        let mut baseline = y + (h - node.lines.len() as f32 * LABEL_LINE) / 2.0 + LABEL_LINE * 0.75;
+        for line in &node.lines {
+            let _ = writeln!(
+                out,
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" font-size=\"{}\" fill=\"#000\">{}</text>",
+                x + w / 2.0,
+                baseline,
+                LABEL_SIZE,
+                escape(line)
+            );
+            baseline += LABEL_LINE;
+        }
+        out.push_str("</g>\n");
+    }
+
+    // Edge labels last, so no node covers them
+    for edge in &geometry.edges {
+        if let Some(ref text) = edge.label {
+            let (x, y) = edge.midpoint();
+            let _ = writeln!(
+                out,
+                "<text class=\"diagram-edge-label\" x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" font-size=\"{}\" font-style=\"italic\" fill=\"#404040\" stroke=\"#fff\" stroke-width=\"3\" paint-order=\"stroke\">{}</text>",
+                x,
+                y + EDGE_LABEL_SIZE / 3.0,
+                EDGE_LABEL_SIZE,
+                escape(text)
+            );
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Lay out a `Diagram` block's parts and render them with `render_svg`
+pub fn diagram_svg(
+    id: &str,
+    diagram_type: &DiagramType,
+    title: Option<&str>,
+    nodes: &[DiagramNode],
+    edges: &[DiagramEdge],
+    options: Option<&DiagramLayout>,
+) -> String {
+    render_svg(&layout(diagram_type, nodes, edges, options), id, title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> DiagramNode {
+        DiagramNode { id: id.to_string(), label: id.to_uppercase(), shape: None, style: None }
+    }
+
+    fn edge(from: &str, to: &str) -> DiagramEdge {
+        DiagramEdge { from: from.to_string(), to: to.to_string(), edge_type: EdgeType::Solid, label: None }
+    }
+
+    fn overlaps(a: &NodeBox, b: &NodeBox) -> bool {
+        a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+    }
+
+    fn segments_cross(p: ((f32, f32), (f32, f32)), q: ((f32, f32), (f32, f32))) -> bool {
+        let side = |a: (f32, f32), b: (f32, f32), c: (f32, f32)| (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+        side(p.0, p.1, q.0) * side(p.0, p.1, q.1) < 0.0 && side(q.0, q.1, p.0) * side(q.0, q.1, p.1) < 0.0
+    }
+
+    #[test]
+    fn test_layered_ranks_follow_edges() {
+        let nodes = vec![node("a"), node("b"), node("c")];
+        let edges = vec![edge("a", "b"), edge("b", "c")];
+        let geometry = layout(&DiagramType::Flowchart, &nodes, &edges, None);
+        let y: Vec<f32> = geometry.nodes.iter().map(|n| n.y).collect();
+        assert!(y[0] < y[1] && y[1] < y[2]);
+
+        let options = DiagramLayout { direction: Some(LayoutDirection::RightLeft), spacing: None };
+        let geometry = layout(&DiagramType::Flowchart, &nodes, &edges, Some(&options));
+        let x: Vec<f32> = geometry.nodes.iter().map(|n| n.x).collect();
+        assert!(x[0] > x[1] && x[1] > x[2]);
+    }
+
+    #[test]
+    fn test_long_edges_are_routed_around_ranks() {
+        let nodes = vec![node("a"), node("b"), node("c"), node("d")];
+        let edges = vec![edge("a", "b"), edge("b", "c"), edge("c", "d"), edge("a", "d")];
+        let geometry = layout(&DiagramType::Flowchart, &nodes, &edges, None);
+        let long = &geometry.edges[3];
+        assert_eq!(long.points.len(), 4, "two virtual nodes between ranks 0 and 3");
+        for &(x, y) in &long.points[1..3] {
+            assert!(!geometry.nodes.iter().any(|n| x > n.x && x < n.x + n.width && y > n.y && y < n.y + n.height));
+        }
+    }
+
+    #[test]
+    fn test_barycentre_ordering_removes_crossings() {
+        // Listed so that a naive order crosses a1->b2 with a2->b1
+        let nodes = vec![node("a1"), node("a2"), node("b1"), node("b2")];
+        let edges = vec![edge("a1", "b2"), edge("a2", "b1")];
+        let geometry = layout(&DiagramType::Hierarchical, &nodes, &edges, None);
+        let (p, q) = (&geometry.edges[0].points, &geometry.edges[1].points);
+        assert!(!segments_cross((p[0], p[1]), (q[0], q[1])));
+    }
+
+    #[test]
+    fn test_cycles_and_dangling_edges() {
+        let nodes = vec![node("a"), node("b"), node("c")];
+        let edges = vec![edge("a", "b"), edge("b", "c"), edge("c", "a"), edge("a", "a"), edge("a", "missing")];
+        let geometry = layout(&DiagramType::Flowchart, &nodes, &edges, None);
+        assert_eq!(geometry.edges.len(), 3);
+        // The reversed edge still points from c to a
+        let back = &geometry.edges[2];
+        let (c, a) = (geometry.nodes[2].center(), geometry.nodes[0].center());
+        let (start, end) = (back.points[0], *back.points.last().unwrap());
+        assert!((start.1 - c.1).abs() < (start.1 - a.1).abs());
+        assert!((end.1 - a.1).abs() < (end.1 - c.1).abs());
+    }
+
+    #[test]
+    fn test_force_directed_is_deterministic_and_separates_nodes() {
+        let nodes: Vec<DiagramNode> = ["a", "b", "c", "d", "e", "f"].iter().map(|id| node(id)).collect();
+        let edges = vec![edge("a", "b"), edge("a", "c"), edge("a", "d"), edge("e", "f"), edge("b", "c")];
+        let first = layout(&DiagramType::Relationship, &nodes, &edges, None);
+        let second = layout(&DiagramType::Relationship, &nodes, &edges, None);
+        assert_eq!(render_svg(&first, "d", None), render_svg(&second, "d", None));
+        for (i, a) in first.nodes.iter().enumerate() {
+            assert!(a.x >= 0.0 && a.y >= 0.0);
+            for b in &first.nodes[i + 1..] {
+                assert!(!overlaps(a, b), "{} overlaps {}", a.id, b.id);
+            }
+        }
+        assert!(first.edges.iter().all(|e| e.points.len() == 2));
+    }
+
+    #[test]
+    fn test_edges_end_on_shape_outlines() {
+        let mut circle = node("c");
+        circle.shape = Some(DiagramShape::Circle);
+        let mut diamond = node("d");
+        diamond.shape = Some(DiagramShape::Diamond);
+        let geometry = layout(&DiagramType::Flowchart, &[circle, diamond], &[edge("c", "d")], None);
+        let (c, d) = (&geometry.nodes[0], &geometry.nodes[1]);
+        let (start, end) = (geometry.edges[0].points[0], geometry.edges[0].points[1]);
+        let ellipse = ((start.0 - c.center().0) / (c.width / 2.0)).powi(2) + ((start.1 - c.center().1) / (c.height / 2.0)).powi(2);
+        assert!((ellipse - 1.0).abs() < 1e-3);
+        let rhombus = (end.0 - d.center().0).abs() / (d.width / 2.0) + (end.1 - d.center().1).abs() / (d.height / 2.0);
+        assert!((rhombus - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_svg_shapes_edge_styles_and_escaping() {
+        let mut decision = node("q");
+        decision.label = "Approve <€1k>?".to_string();
+        decision.shape = Some(DiagramShape::Diamond);
+        decision.style = Some("primary".to_string());
+        let nodes = vec![node("start"), decision, node("end")];
+        let mut dashed = edge("start", "q");
+        dashed.edge_type = EdgeType::Dashed;
+        let mut dotted = edge("q", "end");
+        dotted.edge_type = EdgeType::Dotted;
+        dotted.label = Some("yes & done".to_string());
+        let svg = diagram_svg("flow 1", &DiagramType::Flowchart, Some("Flow"), &nodes, &[dashed, dotted], None);
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("<title>Flow</title>"));
+        assert!(svg.contains("<marker id=\"flow1-arrow\""));
+        assert!(svg.contains("marker-end=\"url(#flow1-arrow)\""));
+        assert!(svg.contains("stroke-dasharray=\"6 3\""));
+        assert!(svg.contains("stroke-dasharray=\"1 3\""));
+        assert!(svg.contains("<polygon points="));
+        assert!(svg.contains("class=\"diagram-node primary\""));
+        assert!(svg.contains("Approve &lt;€1k&gt;?"));
+        assert!(svg.contains(">yes &amp; done</text>"));
+        assert!(svg.contains("<desc>START \u{2192} Approve &lt;€1k&gt;?; Approve &lt;€1k&gt;? \u{2192} END (yes &amp; done)</desc>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_empty_diagram() {
+        for diagram_type in [DiagramType::Hierarchical, DiagramType::Relationship] {
+            let geometry = layout(&diagram_type, &[], &[], None);
+            assert_eq!((geometry.width, geometry.height), (0.0, 0.0));
+            assert!(render_svg(&geometry, "empty", None).contains("</svg>"));
+        }
+    }
+}
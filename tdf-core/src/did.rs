@@ -0,0 +1,764 @@
+//! Offline DID resolution for signer identifiers
+//!
+//! Signer IDs are DIDs (`did:key:z6Mk...`, `did:web:cfo.acme.com`). This module
+//! resolves them to DID documents so verification can confirm that the key
+//! which produced a `DocumentSignature` is listed by the signer's DID document,
+//! instead of relying on a key passed out of band.
+//!
+//! - `did:key` is resolved locally (the key is encoded in the identifier)
+//! - `did:web` is resolved through a `DidDocumentFetcher`: a local cache
+//!   directory, an HTTP fetcher (`did-web-http` feature), or any custom source
+
+use crate::error::{TdfError, TdfResult};
+use crate::signature::{DocumentSignature, SignatureAlgorithm, SignatureManager};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::VerifyingKey;
+use k256::ecdsa::VerifyingKey as Secp256k1VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Multicodec prefix for Ed25519 public keys (0xed, varint-encoded)
+const MULTICODEC_ED25519_PUB: [u8; 2] = [0xed, 0x01];
+/// Multicodec prefix for compressed secp256k1 public keys (0xe7, varint-encoded)
+const MULTICODEC_SECP256K1_PUB: [u8; 2] = [0xe7, 0x01];
+
+/// Maximum DID document size accepted from a fetcher
+pub const MAX_DID_DOCUMENT_SIZE: usize = 256 * 1024;
+
+/// A W3C DID document (the subset needed for signature verification)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    pub id: String,
+    #[serde(default)]
+    pub verification_method: Vec<VerificationMethod>,
+    /// Verification relationships; entries are method IDs or embedded methods
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertion_method: Vec<VerificationRelationship>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authentication: Vec<VerificationRelationship>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub controller: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_multibase: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_base58: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_jwk: Option<PublicKeyJwk>,
+}
+
+/// Entry in a verification relationship: a reference or an embedded method
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum VerificationRelationship {
+    Reference(String),
+    Embedded(VerificationMethod),
+}
+
+/// JSON Web Key (OKP/Ed25519 and EC/secp256k1 only)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PublicKeyJwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+/// Public key decoded from a verification method
+#[derive(Debug, Clone, PartialEq)]
+pub enum DidPublicKey {
+    Ed25519(VerifyingKey),
+    Secp256k1(Secp256k1VerifyingKey),
+}
+
+/// Resolves a DID to its DID document
+pub trait DidResolver: Send + Sync {
+    /// DID method handled by this resolver (e.g. "key", "web")
+    fn method(&self) -> &str;
+
+    fn resolve(&self, did: &str) -> TdfResult<DidDocument>;
+}
+
+/// Source of raw DID documents for `did:web`
+pub trait DidDocumentFetcher: Send + Sync {
+    /// Fetch the document at the `did:web` resolution URL
+    fn fetch(&self, url: &str) -> TdfResult<Vec<u8>>;
+}
+
+fn did_error(msg: impl Into<String>) -> TdfError {
+    TdfError::DidResolution(msg.into())
+}
+
+impl DidPublicKey {
+    /// Decode a multicodec-prefixed key (as used by did:key and publicKeyMultibase)
+    fn from_multicodec(bytes: &[u8]) -> TdfResult<Self> {
+        if let Some(key) = bytes.strip_prefix(&MULTICODEC_ED25519_PUB) {
+            let key: [u8; 32] = key
+                .try_into()
+                .map_err(|_| did_error("Ed25519 multicodec key must be 32 bytes"))?;
+            let key = VerifyingKey::from_bytes(&key)
+                .map_err(|e| did_error(format!("Invalid Ed25519 key: {}", e)))?;
+            return Ok(DidPublicKey::Ed25519(key));
+        }
+        if let Some(key) = bytes.strip_prefix(&MULTICODEC_SECP256K1_PUB) {
+            let key = Secp256k1VerifyingKey::from_sec1_bytes(key)
+                .map_err(|e| did_error(format!("Invalid secp256k1 key: {}", e)))?;
+            return Ok(DidPublicKey::Secp256k1(key));
+        }
+        Err(did_error("Unsupported multicodec key type"))
+    }
+
+    fn to_multicodec(&self) -> Vec<u8> {
+        match self {
+            DidPublicKey::Ed25519(key) => [&MULTICODEC_ED25519_PUB[..], key.as_bytes()].concat(),
+            DidPublicKey::Secp256k1(key) => {
+                [&MULTICODEC_SECP256K1_PUB[..], &key.to_sec1_bytes()[..]].concat()
+            }
+        }
+    }
+
+    /// Encode as a base58btc multibase string (`z...`)
+    pub fn to_multibase(&self) -> String {
+        format!("z{}", bs58::encode(self.to_multicodec()).into_string())
+    }
+
+    /// Encode as the equivalent `did:key` identifier
+    pub fn to_did_key(&self) -> String {
+        format!("did:key:{}", self.to_multibase())
+    }
+
+    /// Signature algorithm this key verifies
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match self {
+            DidPublicKey::Ed25519(_) => SignatureAlgorithm::Ed25519,
+            DidPublicKey::Secp256k1(_) => SignatureAlgorithm::Secp256k1,
+        }
+    }
+}
+
+fn decode_multibase(value: &str) -> TdfResult<Vec<u8>> {
+    let encoded = value
+        .strip_prefix('z')
+        .ok_or_else(|| did_error("Only base58btc ('z') multibase keys are supported"))?;
+    bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| did_error(format!("Invalid base58 key: {}", e)))
+}
+
+impl PublicKeyJwk {
+    pub fn to_public_key(&self) -> TdfResult<DidPublicKey> {
+        let x = URL_SAFE_NO_PAD
+            .decode(&self.x)
+            .map_err(|e| did_error(format!("Invalid JWK 'x': {}", e)))?;
+        match (self.kty.as_str(), self.crv.as_str()) {
+            ("OKP", "Ed25519") => {
+                let key: [u8; 32] = x
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| did_error("Ed25519 JWK 'x' must be 32 bytes"))?;
+                let key = VerifyingKey::from_bytes(&key)
+                    .map_err(|e| did_error(format!("Invalid Ed25519 key: {}", e)))?;
+                Ok(DidPublicKey::Ed25519(key))
+            }
+            ("EC", "secp256k1") => {
+                let y = self
+                    .y
+                    .as_ref()
+                    .ok_or_else(|| did_error("secp256k1 JWK requires 'y'"))?;
+                let y = URL_SAFE_NO_PAD
+                    .decode(y)
+                    .map_err(|e| did_error(format!("Invalid JWK 'y': {}", e)))?;
+                let mut sec1 = Vec::with_capacity(65);
+                sec1.push(0x04);
+                sec1.extend_from_slice(&x);
+                sec1.extend_from_slice(&y);
+                let key = Secp256k1VerifyingKey::from_sec1_bytes(&sec1)
+                    .map_err(|e| did_error(format!("Invalid secp256k1 key: {}", e)))?;
+                Ok(DidPublicKey::Secp256k1(key))
+            }
+            (kty, crv) => Err(did_error(format!("Unsupported JWK key type {}/{}", kty, crv))),
+        }
+    }
+}
+
+impl VerificationMethod {
+    /// Decode the public key of this method
+    pub fn public_key(&self) -> TdfResult<DidPublicKey> {
+        if let Some(ref jwk) = self.public_key_jwk {
+            return jwk.to_public_key();
+        }
+        if let Some(ref multibase) = self.public_key_multibase {
+            return DidPublicKey::from_multicodec(&decode_multibase(multibase)?);
+        }
+        if let Some(ref base58) = self.public_key_base58 {
+            // Ed25519VerificationKey2018: raw key bytes without multicodec prefix
+            let raw = bs58::decode(base58)
+                .into_vec()
+                .map_err(|e| did_error(format!("Invalid base58 key: {}", e)))?;
+            let key: [u8; 32] = raw
+                .as_slice()
+                .try_into()
+                .map_err(|_| did_error("publicKeyBase58 must be a 32-byte Ed25519 key"))?;
+            let key = VerifyingKey::from_bytes(&key)
+                .map_err(|e| did_error(format!("Invalid Ed25519 key: {}", e)))?;
+            return Ok(DidPublicKey::Ed25519(key));
+        }
+        Err(did_error(format!("Verification method {} has no public key", self.id)))
+    }
+}
+
+impl DidDocument {
+    /// Parse a DID document from JSON
+    pub fn from_json(data: &[u8]) -> TdfResult<Self> {
+        if data.len() > MAX_DID_DOCUMENT_SIZE {
+            return Err(TdfError::SizeExceeded(format!(
+                "DID document size {} exceeds limit {}",
+                data.len(),
+                MAX_DID_DOCUMENT_SIZE
+            )));
+        }
+        serde_json::from_slice(data).map_err(|e| did_error(format!("Invalid DID document: {}", e)))
+    }
+
+    fn resolve_relationship<'a>(&'a self, entry: &'a VerificationRelationship) -> Option<&'a VerificationMethod> {
+        match entry {
+            VerificationRelationship::Embedded(method) => Some(method),
+            VerificationRelationship::Reference(reference) => {
+                let fragment = reference.strip_prefix(&self.id).unwrap_or(reference);
+                self.verification_method
+                    .iter()
+                    .find(|m| m.id == *reference || m.id.strip_prefix(&self.id) == Some(fragment))
+            }
+        }
+    }
+
+    /// Verification methods authorised to sign documents
+    ///
+    /// If the document declares `assertionMethod`, only those methods are
+    /// returned; otherwise all verification methods are candidates.
+    pub fn signing_methods(&self) -> Vec<&VerificationMethod> {
+        if self.assertion_method.is_empty() {
+            self.verification_method.iter().collect()
+        } else {
+            self.assertion_method
+                .iter()
+                .filter_map(|entry| self.resolve_relationship(entry))
+                .collect()
+        }
+    }
+}
+
+/// Resolver for `did:key` (no network or storage required)
+#[derive(Debug, Clone, Default)]
+pub struct DidKeyResolver;
+
+impl DidKeyResolver {
+    pub fn new() -> Self {
+        DidKeyResolver
+    }
+}
+
+impl DidResolver for DidKeyResolver {
+    fn method(&self) -> &str {
+        "key"
+    }
+
+    fn resolve(&self, did: &str) -> TdfResult<DidDocument> {
+        let multibase = did
+            .strip_prefix("did:key:")
+            .ok_or_else(|| did_error(format!("Not a did:key identifier: {}", did)))?;
+        let key = DidPublicKey::from_multicodec(&decode_multibase(multibase)?)?;
+        let method_type = match key {
+            DidPublicKey::Ed25519(_) => "Ed25519VerificationKey2020",
+            DidPublicKey::Secp256k1(_) => "EcdsaSecp256k1VerificationKey2019",
+        };
+        let method_id = format!("{}#{}", did, multibase);
+
+        Ok(DidDocument {
+            id: did.to_string(),
+            verification_method: vec![VerificationMethod {
+                id: method_id.clone(),
+                method_type: method_type.to_string(),
+                controller: did.to_string(),
+                public_key_multibase: Some(multibase.to_string()),
+                public_key_base58: None,
+                public_key_jwk: None,
+            }],
+            assertion_method: vec![VerificationRelationship::Reference(method_id.clone())],
+            authentication: vec![VerificationRelationship::Reference(method_id)],
+        })
+    }
+}
+
+/// Compute the `did:web` resolution URL for a DID
+///
+/// `did:web:example.com` → `https://example.com/.well-known/did.json`
+/// `did:web:example.com:user:alice` → `https://example.com/user/alice/did.json`
+/// A port is percent-encoded in the host segment (`did:web:localhost%3A8443`).
+pub fn did_web_url(did: &str, scheme: &str) -> TdfResult<String> {
+    let specific = did
+        .strip_prefix("did:web:")
+        .ok_or_else(|| did_error(format!("Not a did:web identifier: {}", did)))?;
+    let mut segments = specific.split(':');
+    let host = segments
+        .next()
+        .filter(|h| !h.is_empty())
+        .ok_or_else(|| did_error("did:web identifier has no host"))?
+        .replace("%3A", ":")
+        .replace("%3a", ":");
+    if host.contains('/') || host.contains('@') {
+        return Err(did_error(format!("Invalid did:web host: {}", host)));
+    }
+
+    let path: Vec<&str> = segments.collect();
+    if path.iter().any(|s| s.is_empty() || *s == "." || *s == ".." || s.contains('/')) {
+        return Err(did_error(format!("Invalid did:web path in {}", did)));
+    }
+
+    if path.is_empty() {
+        Ok(format!("{}://{}/.well-known/did.json", scheme, host))
+    } else {
+        Ok(format!("{}://{}/{}/did.json", scheme, host, path.join("/")))
+    }
+}
+
+/// Resolver for `did:web` backed by a pluggable document fetcher
+pub struct DidWebResolver {
+    fetcher: Box<dyn DidDocumentFetcher>,
+    scheme: &'static str,
+}
+
+impl DidWebResolver {
+    pub fn new(fetcher: Box<dyn DidDocumentFetcher>) -> Self {
+        DidWebResolver { fetcher, scheme: "https" }
+    }
+
+    /// Resolve over plain HTTP instead of HTTPS
+    ///
+    /// WARNING: for local testing only. did:web relies on TLS for authenticity.
+    pub fn with_insecure_http(mut self) -> Self {
+        self.scheme = "http";
+        self
+    }
+}
+
+impl DidResolver for DidWebResolver {
+    fn method(&self) -> &str {
+        "web"
+    }
+
+    fn resolve(&self, did: &str) -> TdfResult<DidDocument> {
+        let url = did_web_url(did, self.scheme)?;
+        let data = self.fetcher.fetch(&url)?;
+        let document = DidDocument::from_json(&data)?;
+        if document.id != did {
+            return Err(did_error(format!(
+                "DID document id '{}' does not match requested DID '{}'",
+                document.id, did
+            )));
+        }
+        Ok(document)
+    }
+}
+
+/// Local DID document cache directory
+///
+/// Documents are stored by URL path: `https://cfo.acme.com/.well-known/did.json`
+/// is read from `<dir>/cfo.acme.com/.well-known/did.json`. When an upstream
+/// fetcher is configured, cache misses are fetched and written to the cache.
+pub struct DidDocumentCache {
+    dir: PathBuf,
+    upstream: Option<Box<dyn DidDocumentFetcher>>,
+}
+
+impl DidDocumentCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DidDocumentCache { dir: dir.into(), upstream: None }
+    }
+
+    pub fn with_upstream(mut self, upstream: Box<dyn DidDocumentFetcher>) -> Self {
+        self.upstream = Some(upstream);
+        self
+    }
+
+    /// Cache file path for a resolution URL
+    pub fn path_for_url(&self, url: &str) -> TdfResult<PathBuf> {
+        let without_scheme = url
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .ok_or_else(|| did_error(format!("Invalid DID document URL: {}", url)))?;
+        let mut path = self.dir.clone();
+        for segment in without_scheme.split('/') {
+            if segment.is_empty() || segment == ".." || segment.contains('\\') {
+                return Err(TdfError::InvalidPath(format!("Invalid DID document URL: {}", url)));
+            }
+            // Windows does not allow ':' in file names
+            path.push(segment.replace(':', "_"));
+        }
+        Ok(path)
+    }
+
+    /// Store a DID document in the cache under its did:web location
+    pub fn store(&self, document: &DidDocument) -> TdfResult<PathBuf> {
+        let path = self.path_for_url(&did_web_url(&document.id, "https")?)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_vec_pretty(document)?)?;
+        Ok(path)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl DidDocumentFetcher for DidDocumentCache {
+    fn fetch(&self, url: &str) -> TdfResult<Vec<u8>> {
+        let path = self.path_for_url(url)?;
+        if path.exists() {
+            return Ok(std::fs::read(&path)?);
+        }
+        match &self.upstream {
+            Some(upstream) => {
+                let data = upstream.fetch(url)?;
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, &data)?;
+                Ok(data)
+            }
+            None => Err(did_error(format!("DID document not in cache: {}", url))),
+        }
+    }
+}
+
+/// Blocking HTTP(S) fetcher for did:web documents
+#[cfg(feature = "did-web-http")]
+pub struct HttpDidFetcher {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "did-web-http")]
+impl HttpDidFetcher {
+    pub fn new() -> Self {
+        HttpDidFetcher {
+            agent: ureq::AgentBuilder::new()
+                .timeout(std::time::Duration::from_secs(10))
+                .redirects(0)
+                .build(),
+        }
+    }
+}
+
+#[cfg(feature = "did-web-http")]
+impl Default for HttpDidFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "did-web-http")]
+impl DidDocumentFetcher for HttpDidFetcher {
+    fn fetch(&self, url: &str) -> TdfResult<Vec<u8>> {
+        use std::io::Read;
+
+        let response = self
+            .agent
+            .get(url)
+            .call()
+            .map_err(|e| did_error(format!("Failed to fetch {}: {}", url, e)))?;
+        let mut data = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_DID_DOCUMENT_SIZE as u64 + 1)
+            .read_to_end(&mut data)?;
+        if data.len() > MAX_DID_DOCUMENT_SIZE {
+            return Err(TdfError::SizeExceeded(format!(
+                "DID document at {} exceeds {} bytes",
+                url, MAX_DID_DOCUMENT_SIZE
+            )));
+        }
+        Ok(data)
+    }
+}
+
+/// Dispatches to per-method resolvers
+#[derive(Default)]
+pub struct CompositeResolver {
+    resolvers: Vec<Box<dyn DidResolver>>,
+}
+
+impl CompositeResolver {
+    pub fn new() -> Self {
+        CompositeResolver { resolvers: Vec::new() }
+    }
+
+    pub fn with_resolver(mut self, resolver: Box<dyn DidResolver>) -> Self {
+        self.resolvers.push(resolver);
+        self
+    }
+}
+
+impl DidResolver for CompositeResolver {
+    fn method(&self) -> &str {
+        "*"
+    }
+
+    fn resolve(&self, did: &str) -> TdfResult<DidDocument> {
+        let method = did
+            .strip_prefix("did:")
+            .and_then(|rest| rest.split(':').next())
+            .ok_or_else(|| did_error(format!("Not a DID: {}", did)))?;
+        self.resolvers
+            .iter()
+            .find(|r| r.method() == method)
+            .ok_or_else(|| did_error(format!("No resolver for DID method '{}'", method)))?
+            .resolve(did)
+    }
+}
+
+/// Result of verifying a signature against the signer's DID document
+#[derive(Debug, Clone, PartialEq)]
+pub struct DidVerification {
+    pub did: String,
+    /// ID of the verification method whose key produced the signature
+    pub verification_method: String,
+}
+
+/// Verify a signature using keys listed in the signer's DID document
+///
+/// Resolves `signature.signer.id` and tries each signing method of the matching
+/// algorithm. Succeeds only if one of them verifies the signature.
+pub fn verify_signature_with_did(
+    signature: &DocumentSignature,
+    root_hash: &[u8],
+    resolver: &dyn DidResolver,
+) -> TdfResult<DidVerification> {
+    let document = resolver.resolve(&signature.signer.id)?;
+    let mut last_error = None;
+
+    for method in document.signing_methods() {
+        let key = match method.public_key() {
+            Ok(key) if key.algorithm() == signature.algorithm => key,
+            Ok(_) => continue,
+            Err(e) => {
+                last_error = Some(e);
+                continue;
+            }
+        };
+        let result = match key {
+            DidPublicKey::Ed25519(ref k) => SignatureManager::verify_ed25519(signature, root_hash, k),
+            DidPublicKey::Secp256k1(ref k) => SignatureManager::verify_secp256k1(signature, root_hash, k),
+        };
+        match result {
+            Ok(true) => {
+                return Ok(DidVerification {
+                    did: document.id.clone(),
+                    verification_method: method.id.clone(),
+                })
+            }
+            Ok(false) => {}
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Err(TdfError::SignatureFailure(format!(
+        "No key in DID document {} verifies the signature{}",
+        document.id,
+        last_error.map(|e| format!(" (last error: {})", e)).unwrap_or_default()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature::SignatureScope;
+    use ed25519_dalek::SigningKey;
+    use k256::ecdsa::SigningKey as Secp256k1SigningKey;
+    use rand::rngs::OsRng;
+    use tempfile::TempDir;
+
+    const ROOT_HASH: &[u8] = b"test_root_hash_for_signing_12345";
+
+    fn web_document(did: &str, key: &VerifyingKey) -> DidDocument {
+        DidDocument {
+            id: did.to_string(),
+            verification_method: vec![VerificationMethod {
+                id: format!("{}#key-1", did),
+                method_type: "JsonWebKey2020".to_string(),
+                controller: did.to_string(),
+                public_key_multibase: None,
+                public_key_base58: None,
+                public_key_jwk: Some(PublicKeyJwk {
+                    kty: "OKP".to_string(),
+                    crv: "Ed25519".to_string(),
+                    x: URL_SAFE_NO_PAD.encode(key.as_bytes()),
+                    y: None,
+                }),
+            }],
+            assertion_method: vec![VerificationRelationship::Reference("#key-1".to_string())],
+            authentication: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_did_key_round_trip_ed25519() {
+        let key = SigningKey::generate(&mut OsRng).verifying_key();
+        let did = DidPublicKey::Ed25519(key).to_did_key();
+        assert!(did.starts_with("did:key:z6Mk"));
+
+        let document = DidKeyResolver::new().resolve(&did).unwrap();
+        let methods = document.signing_methods();
+        assert_eq!(methods.len(), 1);
+        assert_eq!(methods[0].public_key().unwrap(), DidPublicKey::Ed25519(key));
+    }
+
+    #[test]
+    fn test_did_key_known_vector() {
+        // Test vector from the did:key specification
+        let did = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+        let document = DidKeyResolver::new().resolve(did).unwrap();
+        assert!(matches!(
+            document.verification_method[0].public_key().unwrap(),
+            DidPublicKey::Ed25519(_)
+        ));
+    }
+
+    #[test]
+    fn test_did_key_secp256k1_signature() {
+        let signing_key = Secp256k1SigningKey::random(&mut OsRng);
+        let did = DidPublicKey::Secp256k1(*signing_key.verifying_key()).to_did_key();
+        let signature = SignatureManager::sign_secp256k1(
+            &signing_key,
+            ROOT_HASH,
+            did.clone(),
+            "Signer".to_string(),
+            SignatureScope::Full,
+        );
+        let result = verify_signature_with_did(&signature, ROOT_HASH, &DidKeyResolver::new()).unwrap();
+        assert_eq!(result.did, did);
+    }
+
+    #[test]
+    fn test_did_web_url_mapping() {
+        assert_eq!(
+            did_web_url("did:web:cfo.acme.com", "https").unwrap(),
+            "https://cfo.acme.com/.well-known/did.json"
+        );
+        assert_eq!(
+            did_web_url("did:web:acme.com:people:cfo", "https").unwrap(),
+            "https://acme.com/people/cfo/did.json"
+        );
+        assert_eq!(
+            did_web_url("did:web:localhost%3A8443", "https").unwrap(),
+            "https://localhost:8443/.well-known/did.json"
+        );
+        assert!(did_web_url("did:web:acme.com:..:etc", "https").is_err());
+        assert!(did_web_url("did:key:z6Mk", "https").is_err());
+    }
+
+    #[test]
+    fn test_did_web_from_cache_verifies_signature() {
+        let dir = TempDir::new().unwrap();
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let did = "did:web:cfo.acme.com";
+
+        let cache = DidDocumentCache::new(dir.path());
+        cache.store(&web_document(did, &signing_key.verifying_key())).unwrap();
+
+        let resolver = CompositeResolver::new()
+            .with_resolver(Box::new(DidKeyResolver::new()))
+            .with_resolver(Box::new(DidWebResolver::new(Box::new(DidDocumentCache::new(dir.path())))));
+
+        let signature = SignatureManager::sign_ed25519(
+            &signing_key,
+            ROOT_HASH,
+            did.to_string(),
+            "CFO".to_string(),
+            SignatureScope::Full,
+        );
+        let result = verify_signature_with_did(&signature, ROOT_HASH, &resolver).unwrap();
+        assert_eq!(result.verification_method, format!("{}#key-1", did));
+
+        // A key not listed in the DID document is rejected
+        let other_key = SigningKey::generate(&mut OsRng);
+        let forged = SignatureManager::sign_ed25519(
+            &other_key,
+            ROOT_HASH,
+            did.to_string(),
+            "CFO".to_string(),
+            SignatureScope::Full,
+        );
+        assert!(verify_signature_with_did(&forged, ROOT_HASH, &resolver).is_err());
+    }
+
+    #[test]
+    fn test_did_web_cache_miss_without_upstream() {
+        let dir = TempDir::new().unwrap();
+        let resolver = DidWebResolver::new(Box::new(DidDocumentCache::new(dir.path())));
+        assert!(resolver.resolve("did:web:unknown.example").is_err());
+    }
+
+    #[test]
+    fn test_did_web_rejects_mismatched_document_id() {
+        let dir = TempDir::new().unwrap();
+        let key = SigningKey::generate(&mut OsRng).verifying_key();
+        let cache = DidDocumentCache::new(dir.path());
+        let path = cache.store(&web_document("did:web:cfo.acme.com", &key)).unwrap();
+
+        // Serve the CFO document at a different DID's location
+        let other = cache
+            .path_for_url(&did_web_url("did:web:attacker.example", "https").unwrap())
+            .unwrap();
+        std::fs::create_dir_all(other.parent().unwrap()).unwrap();
+        std::fs::copy(path, other).unwrap();
+
+        let resolver = DidWebResolver::new(Box::new(cache));
+        assert!(resolver.resolve("did:web:attacker.example").is_err());
+    }
+
+    #[cfg(feature = "did-web-http")]
+    #[test]
+    fn test_did_web_over_local_http_with_cache() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let port = server.server_addr().to_ip().unwrap().port();
+        let did = format!("did:web:127.0.0.1%3A{}", port);
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let body = serde_json::to_vec(&web_document(&did, &signing_key.verifying_key())).unwrap();
+
+        let handle = std::thread::spawn(move || {
+            // Serve exactly one request; the second resolution must come from the cache
+            let request = server.recv().unwrap();
+            assert_eq!(request.url(), "/.well-known/did.json");
+            request.respond(tiny_http::Response::from_data(body)).unwrap();
+        });
+
+        let dir = TempDir::new().unwrap();
+        let make_resolver = || {
+            DidWebResolver::new(Box::new(
+                DidDocumentCache::new(dir.path()).with_upstream(Box::new(HttpDidFetcher::new())),
+            ))
+            .with_insecure_http()
+        };
+
+        let signature = SignatureManager::sign_ed25519(
+            &signing_key,
+            ROOT_HASH,
+            did.clone(),
+            "CFO".to_string(),
+            SignatureScope::Full,
+        );
+        assert!(verify_signature_with_did(&signature, ROOT_HASH, &make_resolver()).is_ok());
+        handle.join().unwrap();
+        assert!(verify_signature_with_did(&signature, ROOT_HASH, &make_resolver()).is_ok());
+    }
+}
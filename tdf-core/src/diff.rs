@@ -0,0 +1,689 @@
+//! Structural diff between two TDF documents
+//!
+//! Sections are matched by id and blocks by their `id` (blocks without an
+//! id are matched by content), so reordering shows up as moves rather than
+//! as removals and additions. Matched blocks are compared in detail:
+//! word-level diffs for text, line diffs for list items and row/cell diffs
+//! for tables. Manifest fields, styles, assets and signatures are compared
+//! too, so a reviewer sees everything that changed before countersigning.
+
+use crate::archive::ArchiveReader;
+use crate::content::{ContentBlock, ListItem, Section, TableRow};
+use crate::document::{Document, Manifest};
+use crate::error::TdfResult;
+use crate::signature::SignatureBlock;
+use serde::Serialize;
+use serde_json::Value;
+use similar::{capture_diff_slices, Algorithm, ChangeTag, DiffOp, TextDiff};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+/// How an item changed between the old and the new document
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+    /// Moved to another position (and possibly also modified)
+    Moved,
+}
+
+/// Operation of a text fragment in a text diff
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TextOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// Fragment of a text diff; consecutive fragments with the same op are merged
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TextChange {
+    pub op: TextOp,
+    pub text: String,
+}
+
+/// Changed leaf value, with dotted path (e.g. `document.title`)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Position of a block
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BlockLocation {
+    pub section: String,
+    pub index: usize,
+}
+
+/// Row or cell change in a table; rows are indexed in the document they belong to
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum TableChange {
+    RowAdded { row: usize },
+    RowRemoved { row: usize },
+    Cell { row: usize, column: String, old: Option<String>, new: Option<String> },
+}
+
+/// Change to a content block
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BlockDiff {
+    /// Block id, or `<section id>/<index>` for blocks without one
+    pub key: String,
+    pub block_type: String,
+    pub kind: ChangeKind,
+    pub old: Option<BlockLocation>,
+    pub new: Option<BlockLocation>,
+    /// Top-level block fields whose values differ
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Word diff of headings, paragraphs and footnotes; line diff of list items
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub text: Vec<TextChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub table: Vec<TableChange>,
+}
+
+/// Change to a section
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SectionDiff {
+    pub id: String,
+    pub kind: ChangeKind,
+    pub old_index: Option<usize>,
+    pub new_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<FieldChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<BlockDiff>,
+}
+
+/// Asset added, removed or modified
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AssetChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old_size: Option<usize>,
+    pub new_size: Option<usize>,
+}
+
+/// Signature present in only one of the documents
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SignatureChange {
+    pub signer_id: String,
+    pub signer_name: String,
+    pub time: String,
+    pub kind: ChangeKind,
+}
+
+/// Everything that differs between two documents
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct DocumentDiff {
+    pub manifest: Vec<FieldChange>,
+    pub sections: Vec<SectionDiff>,
+    /// Line diff of the stylesheet (empty when unchanged)
+    pub styles: Vec<TextChange>,
+    pub assets: Vec<AssetChange>,
+    pub signatures: Vec<SignatureChange>,
+}
+
+impl DocumentDiff {
+    /// No differences at all
+    pub fn is_empty(&self) -> bool {
+        self.manifest.is_empty()
+            && self.sections.is_empty()
+            && self.styles.is_empty()
+            && self.assets.is_empty()
+            && self.signatures.is_empty()
+    }
+
+    /// Differences in content, styles or assets (ignoring manifest and signatures)
+    pub fn content_changed(&self) -> bool {
+        !self.sections.is_empty() || !self.styles.is_empty() || !self.assets.is_empty()
+    }
+}
+
+/// Compare manifests, content and styles of two documents
+pub fn diff_documents(old: &Document, new: &Document) -> DocumentDiff {
+    DocumentDiff {
+        manifest: diff_manifests(&old.manifest, &new.manifest),
+        sections: diff_sections(&old.content.sections, &new.content.sections),
+        styles: if old.styles == new.styles { Vec::new() } else { text_diff(&old.styles, &new.styles, false) },
+        assets: Vec::new(),
+        signatures: Vec::new(),
+    }
+}
+
+/// Compare two TDF archives, including assets and signatures
+///
+/// Documents with document-level encryption must be decrypted first.
+pub fn diff_archives(old: &Path, new: &Path) -> TdfResult<DocumentDiff> {
+    let (old_doc, _, old_signatures) = ArchiveReader::read(old)?;
+    let (new_doc, _, new_signatures) = ArchiveReader::read(new)?;
+    let mut diff = diff_documents(&old_doc, &new_doc);
+    diff.assets = diff_assets(&ArchiveReader::read_assets(old, None)?, &ArchiveReader::read_assets(new, None)?);
+    diff.signatures = diff_signatures(&old_signatures, &new_signatures);
+    Ok(diff)
+}
+
+/// Compare assets by path and content
+pub fn diff_assets(old: &HashMap<String, Vec<u8>>, new: &HashMap<String, Vec<u8>>) -> Vec<AssetChange> {
+    let paths: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (old_data, new_data) = (old.get(path), new.get(path));
+            let kind = match (old_data, new_data) {
+                (Some(a), Some(b)) if a == b => return None,
+                (Some(_), Some(_)) => ChangeKind::Modified,
+                (Some(_), None) => ChangeKind::Removed,
+                _ => ChangeKind::Added,
+            };
+            Some(AssetChange {
+                path: path.clone(),
+                kind,
+                old_size: old_data.map(Vec::len),
+                new_size: new_data.map(Vec::len),
+            })
+        })
+        .collect()
+}
+
+/// Signatures present in only one block (matched by signer and signature value)
+pub fn diff_signatures(old: &SignatureBlock, new: &SignatureBlock) -> Vec<SignatureChange> {
+    let key = |s: &crate::signature::DocumentSignature| (s.signer.id.clone(), s.signature.clone());
+    let old_keys: BTreeSet<_> = old.signatures.iter().map(key).collect();
+    let new_keys: BTreeSet<_> = new.signatures.iter().map(key).collect();
+
+    let change = |s: &crate::signature::DocumentSignature, kind| SignatureChange {
+        signer_id: s.signer.id.clone(),
+        signer_name: s.signer.name.clone(),
+        time: s.timestamp.time.to_rfc3339(),
+        kind,
+    };
+    old.signatures
+        .iter()
+        .filter(|s| !new_keys.contains(&key(s)))
+        .map(|s| change(s, ChangeKind::Removed))
+        .chain(new.signatures.iter().filter(|s| !old_keys.contains(&key(s))).map(|s| change(s, ChangeKind::Added)))
+        .collect()
+}
+
+/// Leaf values of a JSON value by dotted path (arrays are compared whole)
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&path, child, out);
+            }
+        }
+        Value::String(s) => {
+            out.insert(prefix.to_string(), s.clone());
+        }
+        Value::Null => {}
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+fn diff_manifests(old: &Manifest, new: &Manifest) -> Vec<FieldChange> {
+    let (mut old_fields, mut new_fields) = (BTreeMap::new(), BTreeMap::new());
+    flatten("", &serde_json::to_value(old).unwrap_or(Value::Null), &mut old_fields);
+    flatten("", &serde_json::to_value(new).unwrap_or(Value::Null), &mut new_fields);
+
+    let fields: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+    fields
+        .into_iter()
+        .filter(|field| old_fields.get(*field) != new_fields.get(*field))
+        .map(|field| FieldChange {
+            field: field.clone(),
+            old: old_fields.get(field).cloned(),
+            new: new_fields.get(field).cloned(),
+        })
+        .collect()
+}
+
+/// Word diff (or line diff) with consecutive fragments merged
+fn text_diff(old: &str, new: &str, words: bool) -> Vec<TextChange> {
+    let diff = if words { TextDiff::from_words(old, new) } else { TextDiff::from_lines(old, new) };
+    let mut changes: Vec<TextChange> = Vec::new();
+    for change in diff.iter_all_changes() {
+        let op = match change.tag() {
+            ChangeTag::Equal => TextOp::Equal,
+            ChangeTag::Insert => TextOp::Insert,
+            ChangeTag::Delete => TextOp::Delete,
+        };
+        match changes.last_mut() {
+            Some(last) if last.op == op => last.text.push_str(change.value()),
+            _ => changes.push(TextChange { op, text: change.value().to_string() }),
+        }
+    }
+    changes
+}
+
+/// Canonical JSON (sorted keys) used to match blocks and rows by content
+fn canonical<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value).map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Top-level fields (other than `type`) whose values differ
+fn changed_fields(old: &ContentBlock, new: &ContentBlock) -> Vec<String> {
+    let (Ok(Value::Object(old)), Ok(Value::Object(new))) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return Vec::new();
+    };
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).filter(|k| *k != "type").collect();
+    keys.into_iter().filter(|k| old.get(*k) != new.get(*k)).cloned().collect()
+}
+
+fn diff_rows(columns: &[String], old: &[TableRow], new: &[TableRow]) -> Vec<TableChange> {
+    let old_keys: Vec<String> = old.iter().map(canonical).collect();
+    let new_keys: Vec<String> = new.iter().map(canonical).collect();
+    let mut changes = Vec::new();
+
+    for op in capture_diff_slices(Algorithm::Myers, &old_keys, &new_keys) {
+        let (old_range, new_range) = match op {
+            DiffOp::Equal { .. } => continue,
+            DiffOp::Delete { old_index, old_len, .. } => (old_index..old_index + old_len, 0..0),
+            DiffOp::Insert { new_index, new_len, .. } => (0..0, new_index..new_index + new_len),
+            DiffOp::Replace { old_index, old_len, new_index, new_len } => {
+                (old_index..old_index + old_len, new_index..new_index + new_len)
+            }
+        };
+        let paired = old_range.len().min(new_range.len());
+        for (o, n) in old_range.clone().zip(new_range.clone()) {
+            let (old_cells, new_cells) = (&old[o].cells, &new[n].cells);
+            let mut keys: Vec<&String> = columns.iter().filter(|c| old_cells.contains_key(*c) || new_cells.contains_key(*c)).collect();
+            let mut others: Vec<&String> = old_cells.keys().chain(new_cells.keys()).filter(|k| !columns.contains(k)).collect();
+            others.sort();
+            others.dedup();
+            keys.extend(others);
+            for column in keys {
+                let (a, b) = (old_cells.get(column), new_cells.get(column));
+                if a != b {
+                    changes.push(TableChange::Cell {
+                        row: n,
+                        column: column.clone(),
+                        old: a.map(|c| c.display_text().to_string()),
+                        new: b.map(|c| c.display_text().to_string()),
+                    });
+                }
+            }
+        }
+        changes.extend(old_range.skip(paired).map(|row| TableChange::RowRemoved { row }));
+        changes.extend(new_range.skip(paired).map(|row| TableChange::RowAdded { row }));
+    }
+    changes
+}
+
+/// One line per list item, indented by nesting level, with checkboxes
+fn list_lines(items: &[ListItem], depth: usize, out: &mut String) {
+    for item in items {
+        let checkbox = match item.checked {
+            Some(true) => "[x] ",
+            Some(false) => "[ ] ",
+            None => "",
+        };
+        out.push_str(&format!("{}{}{}\n", "  ".repeat(depth), checkbox, item.text));
+        if let Some(list) = &item.sublist {
+            list_lines(&list.items, depth + 1, out);
+        }
+    }
+}
+
+/// Detailed comparison of two versions of a block
+fn compare_blocks(key: String, kind: ChangeKind, old: (&ContentBlock, BlockLocation), new: (&ContentBlock, BlockLocation)) -> BlockDiff {
+    let (old_block, new_block) = (old.0, new.0);
+    let mut diff = BlockDiff {
+        key,
+        block_type: new_block.type_name().to_string(),
+        kind,
+        old: Some(old.1),
+        new: Some(new.1),
+        fields: changed_fields(old_block, new_block),
+        text: Vec::new(),
+        table: Vec::new(),
+    };
+    match (old_block, new_block) {
+        (ContentBlock::Heading { text: a, .. }, ContentBlock::Heading { text: b, .. })
+        | (ContentBlock::Paragraph { text: a, .. }, ContentBlock::Paragraph { text: b, .. })
+            if a != b =>
+        {
+            // Formatting-only changes show in `fields`; this is the wording
+            diff.text = text_diff(&a.plain_text(), &b.plain_text(), true);
+        }
+        (ContentBlock::Footnote { text: a, .. }, ContentBlock::Footnote { text: b, .. }) if a != b => {
+            diff.text = text_diff(a, b, true);
+        }
+        (ContentBlock::Quote { text: a, .. }, ContentBlock::Quote { text: b, .. }) if a != b => {
+            diff.text = text_diff(&a.plain_text(), &b.plain_text(), true);
+        }
+        (ContentBlock::Code { text: a, .. }, ContentBlock::Code { text: b, .. }) if a != b => {
+            diff.text = text_diff(a, b, false);
+        }
+        (ContentBlock::List { items: a, .. }, ContentBlock::List { items: b, .. }) if a != b => {
+            let (mut old_lines, mut new_lines) = (String::new(), String::new());
+            list_lines(a, 0, &mut old_lines);
+            list_lines(b, 0, &mut new_lines);
+            diff.text = text_diff(&old_lines, &new_lines, false);
+        }
+        (ContentBlock::Table { rows: a, .. }, ContentBlock::Table { rows: b, columns, .. }) => {
+            let columns: Vec<String> = columns.iter().map(|c| c.id.clone()).collect();
+            diff.table = diff_rows(&columns, a, b);
+        }
+        _ => {}
+    }
+    diff
+}
+
+fn block_key(block: &ContentBlock, location: &BlockLocation) -> String {
+    block.id().map(str::to_string).unwrap_or_else(|| format!("{}/{}", location.section, location.index))
+}
+
+fn location(section: &Section, index: usize) -> BlockLocation {
+    BlockLocation { section: section.id.clone(), index }
+}
+
+/// Block-level changes between two versions of a section
+///
+/// Unmatched blocks are appended to `removed`/`added` so moves across
+/// sections can be detected afterwards.
+fn diff_blocks<'a>(
+    old: &'a Section,
+    new: &'a Section,
+    changes: &mut Vec<BlockDiff>,
+    removed: &mut Vec<(&'a ContentBlock, BlockLocation)>,
+    added: &mut Vec<(&'a ContentBlock, BlockLocation)>,
+) {
+    let key = |block: &ContentBlock| block.id().map(str::to_string).unwrap_or_else(|| format!("~{}", canonical(block)));
+    let old_keys: Vec<String> = old.content.iter().map(key).collect();
+    let new_keys: Vec<String> = new.content.iter().map(key).collect();
+
+    for op in capture_diff_slices(Algorithm::Myers, &old_keys, &new_keys) {
+        match op {
+            DiffOp::Equal { old_index, new_index, len } => {
+                for i in 0..len {
+                    let (a, b) = (&old.content[old_index + i], &new.content[new_index + i]);
+                    if a != b {
+                        let new_location = location(new, new_index + i);
+                        changes.push(compare_blocks(
+                            block_key(b, &new_location),
+                            ChangeKind::Modified,
+                            (a, location(old, old_index + i)),
+                            (b, new_location),
+                        ));
+                    }
+                }
+            }
+            DiffOp::Delete { old_index, old_len, .. } => {
+                removed.extend((old_index..old_index + old_len).map(|i| (&old.content[i], location(old, i))));
+            }
+            DiffOp::Insert { new_index, new_len, .. } => {
+                added.extend((new_index..new_index + new_len).map(|i| (&new.content[i], location(new, i))));
+            }
+            DiffOp::Replace { old_index, old_len, new_index, new_len } => {
+                // Blocks without ids replaced in place by a block of the same
+                // type are edits rather than a removal and an addition
+                let mut paired = 0;
+                while paired < old_len.min(new_len) {
+                    let (a, b) = (&old.content[old_index + paired], &new.content[new_index + paired]);
+                    if a.id().is_some() || b.id().is_some() || a.type_name() != b.type_name() {
+                        break;
+                    }
+                    let new_location = location(new, new_index + paired);
+                    changes.push(compare_blocks(
+                        block_key(b, &new_location),
+                        ChangeKind::Modified,
+                        (a, location(old, old_index + paired)),
+                        (b, new_location),
+                    ));
+                    paired += 1;
+                }
+                removed.extend((old_index + paired..old_index + old_len).map(|i| (&old.content[i], location(old, i))));
+                added.extend((new_index + paired..new_index + new_len).map(|i| (&new.content[i], location(new, i))));
+            }
+        }
+    }
+}
+
+fn diff_sections(old: &[Section], new: &[Section]) -> Vec<SectionDiff> {
+    let old_ids: Vec<&str> = old.iter().map(|s| s.id.as_str()).collect();
+    let new_ids: Vec<&str> = new.iter().map(|s| s.id.as_str()).collect();
+    let new_index: HashMap<&str, usize> = new_ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    let old_index: HashMap<&str, usize> = old_ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    // Sections kept in relative order; other common sections moved
+    let mut in_order = BTreeSet::new();
+    for op in capture_diff_slices(Algorithm::Myers, &old_ids, &new_ids) {
+        if let DiffOp::Equal { new_index, len, .. } = op {
+            in_order.extend(new_ids[new_index..new_index + len].iter().copied());
+        }
+    }
+
+    let mut sections: Vec<SectionDiff> = Vec::new();
+    let mut block_changes: HashMap<String, Vec<BlockDiff>> = HashMap::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+
+    for (n, section) in new.iter().enumerate() {
+        match old_index.get(section.id.as_str()) {
+            Some(&o) => {
+                let previous = &old[o];
+                let mut changes = Vec::new();
+                diff_blocks(previous, section, &mut changes, &mut removed, &mut added);
+                block_changes.insert(section.id.clone(), changes);
+                let title = (previous.title != section.title).then(|| FieldChange {
+                    field: "title".to_string(),
+                    old: previous.title.clone(),
+                    new: section.title.clone(),
+                });
+                let kind = if in_order.contains(section.id.as_str()) { ChangeKind::Modified } else { ChangeKind::Moved };
+                sections.push(SectionDiff {
+                    id: section.id.clone(),
+                    kind,
+                    old_index: Some(o),
+                    new_index: Some(n),
+                    title,
+                    blocks: Vec::new(),
+                });
+                if previous.encrypted != section.encrypted {
+                    block_changes.get_mut(&section.id).expect("inserted above").push(BlockDiff {
+                        key: section.id.clone(),
+                        block_type: "encrypted".to_string(),
+                        kind: ChangeKind::Modified,
+                        old: None,
+                        new: None,
+                        fields: vec!["encrypted".to_string()],
+                        text: Vec::new(),
+                        table: Vec::new(),
+                    });
+                }
+            }
+            None => sections.push(SectionDiff {
+                id: section.id.clone(),
+                kind: ChangeKind::Added,
+                old_index: None,
+                new_index: Some(n),
+                title: section.title.clone().map(|t| FieldChange { field: "title".to_string(), old: None, new: Some(t) }),
+                blocks: Vec::new(),
+            }),
+        }
+    }
+    for (o, section) in old.iter().enumerate() {
+        if !new_index.contains_key(section.id.as_str()) {
+            sections.push(SectionDiff {
+                id: section.id.clone(),
+                kind: ChangeKind::Removed,
+                old_index: Some(o),
+                new_index: None,
+                title: section.title.clone().map(|t| FieldChange { field: "title".to_string(), old: Some(t), new: None }),
+                blocks: Vec::new(),
+            });
+        }
+    }
+
+    // Blocks removed in one place and added in another with the same id moved
+    let mut moved_from: HashMap<&str, (&ContentBlock, BlockLocation)> = HashMap::new();
+    let mut still_removed = Vec::new();
+    for (block, at) in removed {
+        match block.id() {
+            Some(id) if added.iter().any(|(b, _)| b.id() == Some(id)) => {
+                moved_from.insert(id, (block, at));
+            }
+            _ => still_removed.push((block, at)),
+        }
+    }
+    for (block, at) in added {
+        let entry = block_changes.entry(at.section.clone()).or_default();
+        match block.id().and_then(|id| moved_from.remove(id)) {
+            Some(from) => entry.push(compare_blocks(block_key(block, &at), ChangeKind::Moved, from, (block, at))),
+            None => entry.push(BlockDiff {
+                key: block_key(block, &at),
+                block_type: block.type_name().to_string(),
+                kind: ChangeKind::Added,
+                old: None,
+                new: Some(at),
+                fields: Vec::new(),
+                text: Vec::new(),
+                table: Vec::new(),
+            }),
+        }
+    }
+    for (block, at) in still_removed {
+        block_changes.entry(at.section.clone()).or_default().push(BlockDiff {
+            key: block_key(block, &at),
+            block_type: block.type_name().to_string(),
+            kind: ChangeKind::Removed,
+            old: Some(at),
+            new: None,
+            fields: Vec::new(),
+            text: Vec::new(),
+            table: Vec::new(),
+        });
+    }
+
+    // Attach block changes (in document order) and keep only changed sections
+    for section in &mut sections {
+        if let Some(mut changes) = block_changes.remove(&section.id) {
+            let position = |b: &BlockDiff| {
+                b.new.as_ref().map(|l| (l.index, 1)).or(b.old.as_ref().map(|l| (l.index, 0))).unwrap_or((0, 0))
+            };
+            changes.sort_by_key(position);
+            section.blocks = changes;
+        }
+    }
+    sections.retain(|s| s.kind != ChangeKind::Modified || s.title.is_some() || !s.blocks.is_empty());
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{CellType, CellValue, DocumentContent, TableColumn};
+
+    fn paragraph(text: &str, id: Option<&str>) -> ContentBlock {
+        ContentBlock::Paragraph { text: text.into(), id: id.map(str::to_string), xbrl: None }
+    }
+
+    fn section(id: &str, content: Vec<ContentBlock>) -> Section {
+        Section { id: id.to_string(), title: Some(id.to_string()), content, encrypted: None }
+    }
+
+    fn document(sections: Vec<Section>) -> Document {
+        Document::new("Report".to_string(), "en".to_string(), DocumentContent { sections }, String::new())
+    }
+
+    fn table(amounts: &[(&str, &str)]) -> ContentBlock {
+        let rows = amounts
+            .iter()
+            .map(|(item, amount)| {
+                TableRow::new(HashMap::from([
+                    ("item".to_string(), CellValue::Text(item.to_string())),
+                    ("amount".to_string(), CellValue::Text(amount.to_string())),
+                ]))
+            })
+            .collect();
+        let column = |id: &str| TableColumn { id: id.to_string(), header: id.to_string(), cell_type: CellType::Text, currency: None };
+        ContentBlock::Table { id: "t1".to_string(), caption: None, columns: vec![column("item"), column("amount")], header_rows: None, rows, footer: None }
+    }
+
+    #[test]
+    fn test_identical_documents() {
+        let doc = document(vec![section("s1", vec![paragraph("Hello", None)])]);
+        assert!(diff_documents(&doc, &doc).is_empty());
+    }
+
+    #[test]
+    fn test_paragraph_text_diff() {
+        let old = document(vec![section("s1", vec![paragraph("Revenue was $10.2M in Q3", Some("p1"))])]);
+        let mut new = old.clone();
+        new.content.sections[0].content[0] = paragraph("Revenue was $10.4M in Q3", Some("p1"));
+
+        let diff = diff_documents(&old, &new);
+        let block = &diff.sections[0].blocks[0];
+        assert_eq!((block.key.as_str(), block.kind), ("p1", ChangeKind::Modified));
+        assert_eq!(block.fields, ["text"]);
+        let deleted: Vec<_> = block.text.iter().filter(|c| c.op == TextOp::Delete).map(|c| c.text.as_str()).collect();
+        let inserted: Vec<_> = block.text.iter().filter(|c| c.op == TextOp::Insert).map(|c| c.text.as_str()).collect();
+        assert_eq!((deleted, inserted), (vec!["$10.2M"], vec!["$10.4M"]));
+        assert!(diff.manifest.is_empty());
+    }
+
+    #[test]
+    fn test_block_add_remove_and_move() {
+        let old = document(vec![
+            section("s1", vec![paragraph("A", Some("a")), paragraph("B", Some("b")), paragraph("C", Some("c"))]),
+            section("s2", vec![paragraph("D", Some("d"))]),
+        ]);
+        let new = document(vec![
+            section("s1", vec![paragraph("C", Some("c")), paragraph("A", Some("a")), paragraph("New", None)]),
+            section("s2", vec![paragraph("D", Some("d")), paragraph("B", Some("b"))]),
+        ]);
+
+        let diff = diff_documents(&old, &new);
+        let kinds: Vec<(&str, &str, ChangeKind)> = diff
+            .sections
+            .iter()
+            .flat_map(|s| s.blocks.iter().map(move |b| (s.id.as_str(), b.key.as_str(), b.kind)))
+            .collect();
+        assert!(kinds.contains(&("s2", "b", ChangeKind::Moved)));
+        assert!(kinds.contains(&("s1", "s1/2", ChangeKind::Added)));
+        assert!(kinds.iter().any(|(_, key, kind)| (*key == "a" || *key == "c") && *kind == ChangeKind::Moved));
+        assert!(!kinds.iter().any(|(_, _, kind)| *kind == ChangeKind::Removed));
+    }
+
+    #[test]
+    fn test_table_cell_diff() {
+        let old = document(vec![section("s1", vec![table(&[("Cash", "100"), ("Inventory", "50")])])]);
+        let new = document(vec![section("s1", vec![table(&[("Cash", "120"), ("Inventory", "50"), ("Receivables", "30")])])]);
+
+        let block = &diff_documents(&old, &new).sections[0].blocks[0];
+        assert_eq!(block.key, "t1");
+        assert_eq!(
+            block.table,
+            [
+                TableChange::Cell { row: 0, column: "amount".to_string(), old: Some("100".to_string()), new: Some("120".to_string()) },
+                TableChange::RowAdded { row: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sections_manifest_and_styles() {
+        let old = document(vec![section("s1", vec![]), section("s2", vec![]), section("s3", vec![])]);
+        let mut new = document(vec![section("s2", vec![]), section("s1", vec![]), section("s4", vec![])]);
+        new.manifest.document.title = "Report (restated)".to_string();
+        new.styles = "h1 { color: red; }\n".to_string();
+
+        let diff = diff_documents(&old, &new);
+        let kinds: BTreeMap<&str, ChangeKind> = diff.sections.iter().map(|s| (s.id.as_str(), s.kind)).collect();
+        assert_eq!(kinds["s3"], ChangeKind::Removed);
+        assert_eq!(kinds["s4"], ChangeKind::Added);
+        assert!(kinds.values().filter(|k| **k == ChangeKind::Moved).count() == 1);
+        assert!(diff.manifest.iter().any(|c| c.field == "document.title" && c.new.as_deref() == Some("Report (restated)")));
+        assert!(diff.styles.iter().any(|c| c.op == TextOp::Insert));
+    }
+}
@@ -1,4 +1,8 @@
 use crate::content::DocumentContent;
+use crate::encryption::EncryptionInfo;
+use crate::redaction::RedactionInfo;
+use crate::revision::RevisionInfo;
+use crate::xbrl::XbrlInfo;
 use crate::error::{TdfError, TdfResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -21,6 +25,18 @@ pub struct Manifest {
     pub authors: Vec<Author>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub classification: Option<Classification>,
+    /// Set when the payload components are encrypted (see `encryption`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionInfo>,
+    /// Set when blocks carry salted commitments and can be redacted (see `redaction`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<RedactionInfo>,
+    /// Link to the previous revision (see `revision`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<RevisionInfo>,
+    /// Taxonomy of the content's XBRL tags (see `xbrl`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xbrl: Option<XbrlInfo>,
     pub integrity: IntegrityBlock,
 }
 
@@ -148,6 +164,10 @@ impl Document {
             },
             authors: Vec::new(),
             classification: None,
+            encryption: None,
+            redaction: None,
+            revision: None,
+            xbrl: None,
             integrity: IntegrityBlock {
                 root_hash: String::new(),
                 algorithm: HashAlgorithm::Sha256,
@@ -178,12 +198,31 @@ impl Document {
             return Err(TdfError::InvalidDocument("Document title is required".to_string()));
         }
 
+        if let Some(ref revision) = self.manifest.revision {
+            if revision.number < 2
+                || revision.previous_id == self.manifest.document.id
+                || revision.previous_root_hash.is_empty()
+            {
+                return Err(TdfError::InvalidDocument(
+                    "Revision must link to a different, built predecessor".to_string(),
+                ));
+            }
+        }
+
         if self.content.sections.is_empty() {
             return Err(TdfError::InvalidDocument(
                 "Document must have at least one section".to_string(),
             ));
         }
 
+        for section in &self.content.sections {
+            for block in &section.content {
+                block.validate().map_err(|e| {
+                    TdfError::InvalidDocument(format!("Section '{}': {}", section.id, e))
+                })?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,829 @@
+//! Document-level encryption for Confidential and Restricted documents
+//!
+//! An encrypted TDF archive keeps its manifest, Merkle tree and signatures in
+//! clear, while the payload is unreadable without a recipient key:
+//!
+//! - Payload components (content, styles, layout, data and assets) are
+//!   encrypted with a random 256-bit content key using AES-256-GCM or
+//!   XChaCha20-Poly1305. Each component is stored as `nonce || ciphertext`
+//!   and bound to the document id and its archive name as associated data,
+//!   so ciphertexts cannot be swapped between components or documents.
+//! - The content key is wrapped once per recipient and stored in
+//!   `encryption.cbor`: to an X25519 public key (ephemeral-static ECDH,
+//!   HKDF-SHA256, XChaCha20-Poly1305) or to a passphrase (Argon2id/scrypt).
+//! - The Merkle tree is computed over the ciphertext, so integrity and
+//!   signatures verify without decrypting. The manifest records the cipher
+//!   in its `encryption` field, which is covered by the signature.
+//!
+//! Ed25519 keys double as recipient keys: they are converted to their
+//! X25519 (Montgomery) form, so existing TDF key pairs can receive documents.
+//!
+//! Individual sections can also be encrypted for different audiences within
+//! one document (`SectionEncryptionConfig`). An encrypted section keeps only
+//! its id in clear; its title and blocks are replaced by an `EncryptedSection`
+//! holding the ciphertext and the section key wrapped for each member of the
+//! audience group. The ciphertext is part of `content.cbor`, so the document
+//! signature commits to every section whether or not a reader can decrypt it.
+
+use crate::content::{ContentBlock, Section};
+use crate::did::DidPublicKey;
+use crate::document::Document;
+use crate::error::{TdfError, TdfResult};
+use crate::key_file::{Kdf, KdfParams};
+use crate::secure_random::generate_secure_bytes;
+use aes_gcm::Aes256Gcm;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+/// Current encryption format version
+pub const ENCRYPTION_VERSION: u8 = 1;
+/// Maximum recipient entries accepted from an archive
+pub const MAX_RECIPIENTS: usize = 1024;
+
+const CONTENT_KEY_LEN: usize = 32;
+const WRAP_NONCE_LEN: usize = 24;
+const X25519_WRAP_INFO: &[u8] = b"tdf-encryption-v1 x25519";
+const PASSPHRASE_WRAP_AAD: &[u8] = b"tdf-encryption-v1 passphrase";
+const COMPONENT_AAD_PREFIX: &[u8] = b"tdf-encrypted-component-v1";
+
+/// AEAD used for payload components
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ContentCipher {
+    #[default]
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    #[serde(rename = "xchacha20-poly1305")]
+    XChaCha20Poly1305,
+}
+
+impl ContentCipher {
+    fn nonce_len(self) -> usize {
+        match self {
+            ContentCipher::Aes256Gcm => 12,
+            ContentCipher::XChaCha20Poly1305 => 24,
+        }
+    }
+}
+
+impl std::str::FromStr for ContentCipher {
+    type Err = TdfError;
+
+    fn from_str(s: &str) -> TdfResult<Self> {
+        match s.to_lowercase().as_str() {
+            "aes-256-gcm" | "aes256gcm" | "aes" => Ok(ContentCipher::Aes256Gcm),
+            "xchacha20-poly1305" | "xchacha20" | "xchacha" => Ok(ContentCipher::XChaCha20Poly1305),
+            _ => Err(TdfError::InvalidDocument(format!(
+                "Unknown cipher: {}. Use: aes-256-gcm, xchacha20-poly1305",
+                s
+            ))),
+        }
+    }
+}
+
+/// Encryption marker stored in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptionInfo {
+    pub version: u8,
+    pub cipher: ContentCipher,
+}
+
+impl EncryptionInfo {
+    pub fn new(cipher: ContentCipher) -> Self {
+        EncryptionInfo { version: ENCRYPTION_VERSION, cipher }
+    }
+}
+
+/// Wrapped content keys, stored in `encryption.cbor`
+///
+/// The header is not part of the Merkle tree: recipients can be added or
+/// removed without re-signing, and tampering can only deny access since
+/// every wrapped key is authenticated.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptionHeader {
+    pub version: u8,
+    pub recipients: Vec<RecipientStanza>,
+}
+
+/// The content key wrapped for one recipient
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RecipientStanza {
+    X25519 {
+        /// Hex identifier of the recipient public key (see `recipient_id`)
+        key_id: String,
+        /// Base64 ephemeral X25519 public key
+        ephemeral_public_key: String,
+        /// Base64 `nonce || ciphertext` of the content key
+        wrapped_key: String,
+    },
+    Passphrase {
+        kdf: KdfParams,
+        /// Base64 `nonce || ciphertext` of the content key
+        wrapped_key: String,
+    },
+}
+
+/// A party the content key is wrapped for
+#[derive(Clone)]
+pub enum Recipient {
+    X25519(PublicKey),
+    Passphrase {
+        passphrase: Zeroizing<String>,
+        kdf: KdfParams,
+    },
+}
+
+impl std::fmt::Debug for Recipient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recipient::X25519(key) => write!(f, "Recipient::X25519({})", recipient_id(key)),
+            Recipient::Passphrase { .. } => write!(f, "Recipient::Passphrase"),
+        }
+    }
+}
+
+impl Recipient {
+    /// Recipient for an Ed25519 public key (converted to X25519)
+    pub fn from_ed25519(key: &VerifyingKey) -> Self {
+        Recipient::X25519(PublicKey::from(key.to_montgomery().to_bytes()))
+    }
+
+    /// Recipient for a decoded public key
+    ///
+    /// Only Ed25519 keys can receive documents; secp256k1 keys are signing-only.
+    pub fn from_public_key(key: &DidPublicKey) -> TdfResult<Self> {
+        match key {
+            DidPublicKey::Ed25519(key) => Ok(Self::from_ed25519(key)),
+            DidPublicKey::Secp256k1(_) => Err(TdfError::EncryptionError(
+                "secp256k1 keys cannot be encryption recipients; use an Ed25519 key".to_string(),
+            )),
+        }
+    }
+
+    /// Passphrase recipient with recommended KDF parameters
+    pub fn passphrase(passphrase: &str, kdf: Kdf) -> TdfResult<Self> {
+        Self::passphrase_with_params(passphrase, KdfParams::new(kdf)?)
+    }
+
+    /// Passphrase recipient with explicit KDF parameters
+    pub fn passphrase_with_params(passphrase: &str, kdf: KdfParams) -> TdfResult<Self> {
+        if passphrase.is_empty() {
+            return Err(TdfError::PolicyViolation("Passphrase must not be empty".to_string()));
+        }
+        Ok(Recipient::Passphrase { passphrase: Zeroizing::new(passphrase.to_string()), kdf })
+    }
+}
+
+/// A secret that can unwrap the content key
+pub enum Identity {
+    X25519(StaticSecret),
+    Passphrase(Zeroizing<String>),
+}
+
+impl Identity {
+    /// Identity for an Ed25519 signing key (converted to X25519)
+    pub fn from_ed25519(key: &SigningKey) -> Self {
+        Identity::X25519(StaticSecret::from(key.to_scalar_bytes()))
+    }
+
+    pub fn passphrase(passphrase: &str) -> Self {
+        Identity::Passphrase(Zeroizing::new(passphrase.to_string()))
+    }
+}
+
+/// Recipient selection for `ArchiveBuilder::with_encryption`
+#[derive(Debug, Clone, Default)]
+pub struct EncryptionConfig {
+    pub cipher: ContentCipher,
+    pub recipients: Vec<Recipient>,
+}
+
+impl EncryptionConfig {
+    pub fn new(cipher: ContentCipher) -> Self {
+        EncryptionConfig { cipher, recipients: Vec::new() }
+    }
+
+    pub fn with_recipient(mut self, recipient: Recipient) -> Self {
+        self.recipients.push(recipient);
+        self
+    }
+}
+
+/// Identifier of an X25519 recipient key (hex of the first 8 bytes of its SHA-256)
+pub fn recipient_id(key: &PublicKey) -> String {
+    hex::encode(&Sha256::digest(key.as_bytes())[..8])
+}
+
+fn encryption_error(msg: impl Into<String>) -> TdfError {
+    TdfError::EncryptionError(msg.into())
+}
+
+/// Random per-document content key
+pub(crate) struct ContentKey {
+    key: Zeroizing<[u8; CONTENT_KEY_LEN]>,
+    cipher: ContentCipher,
+}
+
+impl ContentKey {
+    pub(crate) fn generate(cipher: ContentCipher) -> TdfResult<Self> {
+        let mut key = Zeroizing::new([0u8; CONTENT_KEY_LEN]);
+        key.copy_from_slice(&generate_secure_bytes(CONTENT_KEY_LEN)?);
+        Ok(ContentKey { key, cipher })
+    }
+
+    /// Encrypt a payload component as `nonce || ciphertext`
+    ///
+    /// # Arguments
+    /// * `document_id` - Manifest document id, bound as associated data
+    /// * `name` - Archive entry name of the component, bound as associated data
+    /// * `plaintext` - Serialized component
+    pub(crate) fn encrypt_component(&self, document_id: &str, name: &str, plaintext: &[u8]) -> TdfResult<Vec<u8>> {
+        let nonce = generate_secure_bytes(self.cipher.nonce_len())?;
+        let aad = component_aad(document_id, name);
+        let payload = Payload { msg: plaintext, aad: &aad };
+        let ciphertext = match self.cipher {
+            ContentCipher::Aes256Gcm => {
+                let nonce: [u8; 12] = nonce.as_slice().try_into().expect("nonce length");
+                Aes256Gcm::new_from_slice(self.key.as_ref())
+                    .map_err(|_| encryption_error("Invalid content key"))?
+                    .encrypt(&aes_gcm::Nonce::from(nonce), payload)
+            }
+            ContentCipher::XChaCha20Poly1305 => {
+                let nonce: [u8; 24] = nonce.as_slice().try_into().expect("nonce length");
+                XChaCha20Poly1305::new_from_slice(self.key.as_ref())
+                    .map_err(|_| encryption_error("Invalid content key"))?
+                    .encrypt(&XNonce::from(nonce), payload)
+            }
+        }
+        .map_err(|_| encryption_error(format!("Failed to encrypt {}", name)))?;
+
+        let mut out = nonce;
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    /// Decrypt a payload component written by `encrypt_component`
+    pub(crate) fn decrypt_component(&self, document_id: &str, name: &str, data: &[u8]) -> TdfResult<Vec<u8>> {
+        let nonce_len = self.cipher.nonce_len();
+        if data.len() < nonce_len {
+            return Err(encryption_error(format!("Encrypted {} is truncated", name)));
+        }
+        let (nonce, ciphertext) = data.split_at(nonce_len);
+        let aad = component_aad(document_id, name);
+        let payload = Payload { msg: ciphertext, aad: &aad };
+        match self.cipher {
+            ContentCipher::Aes256Gcm => {
+                let nonce: [u8; 12] = nonce.try_into().expect("nonce length");
+                Aes256Gcm::new_from_slice(self.key.as_ref())
+                    .map_err(|_| encryption_error("Invalid content key"))?
+                    .decrypt(&aes_gcm::Nonce::from(nonce), payload)
+            }
+            ContentCipher::XChaCha20Poly1305 => {
+                let nonce: [u8; 24] = nonce.try_into().expect("nonce length");
+                XChaCha20Poly1305::new_from_slice(self.key.as_ref())
+                    .map_err(|_| encryption_error("Invalid content key"))?
+                    .decrypt(&XNonce::from(nonce), payload)
+            }
+        }
+        .map_err(|_| encryption_error(format!("Failed to decrypt {}: corrupted or modified", name)))
+    }
+
+    /// Wrap the content key for every recipient
+    pub(crate) fn seal_header(&self, recipients: &[Recipient]) -> TdfResult<EncryptionHeader> {
+        Ok(EncryptionHeader { version: ENCRYPTION_VERSION, recipients: self.wrap_all(recipients)? })
+    }
+
+    fn wrap_all(&self, recipients: &[Recipient]) -> TdfResult<Vec<RecipientStanza>> {
+        if recipients.is_empty() {
+            return Err(encryption_error("At least one recipient is required"));
+        }
+        if recipients.len() > MAX_RECIPIENTS {
+            return Err(encryption_error(format!("Too many recipients (max {})", MAX_RECIPIENTS)));
+        }
+        recipients.iter().map(|r| self.wrap(r)).collect()
+    }
+
+    fn wrap(&self, recipient: &Recipient) -> TdfResult<RecipientStanza> {
+        match recipient {
+            Recipient::X25519(public_key) => {
+                let ephemeral = StaticSecret::random_from_rng(rand_core::OsRng);
+                let ephemeral_public = PublicKey::from(&ephemeral);
+                let kek = x25519_kek(&ephemeral, public_key, &ephemeral_public, public_key)?;
+                Ok(RecipientStanza::X25519 {
+                    key_id: recipient_id(public_key),
+                    ephemeral_public_key: STANDARD.encode(ephemeral_public.as_bytes()),
+                    wrapped_key: wrap_key(&kek, self.key.as_ref(), X25519_WRAP_INFO)?,
+                })
+            }
+            Recipient::Passphrase { passphrase, kdf } => {
+                let kek = kdf.derive(passphrase.as_bytes())?;
+                Ok(RecipientStanza::Passphrase {
+                    kdf: kdf.clone(),
+                    wrapped_key: wrap_key(&kek, self.key.as_ref(), PASSPHRASE_WRAP_AAD)?,
+                })
+            }
+        }
+    }
+
+    /// Recover the content key from the header with a recipient identity
+    ///
+    /// # Returns
+    /// * The content key for `info.cipher`
+    /// * `Err(TdfError::EncryptionError)` if no recipient entry opens with `identity`
+    pub(crate) fn open_header(
+        header: &EncryptionHeader,
+        info: &EncryptionInfo,
+        identity: &Identity,
+    ) -> TdfResult<Self> {
+        if header.version != ENCRYPTION_VERSION || info.version != ENCRYPTION_VERSION {
+            return Err(encryption_error(format!(
+                "Unsupported encryption version {}",
+                header.version.max(info.version)
+            )));
+        }
+        Self::open_stanzas(&header.recipients, info.cipher, identity)?.ok_or_else(|| {
+            encryption_error(match identity {
+                Identity::X25519(_) => "The document is not encrypted to this key",
+                Identity::Passphrase(_) => "Wrong passphrase or the document has no passphrase recipient",
+            })
+        })
+    }
+
+    /// Try each recipient entry; `None` when none opens with `identity`
+    fn open_stanzas(
+        stanzas: &[RecipientStanza],
+        cipher: ContentCipher,
+        identity: &Identity,
+    ) -> TdfResult<Option<Self>> {
+        if stanzas.len() > MAX_RECIPIENTS {
+            return Err(encryption_error(format!("Too many recipients (max {})", MAX_RECIPIENTS)));
+        }
+
+        for stanza in stanzas {
+            let key = match (stanza, identity) {
+                (
+                    RecipientStanza::X25519 { key_id, ephemeral_public_key, wrapped_key },
+                    Identity::X25519(secret),
+                ) => {
+                    let own_public = PublicKey::from(secret);
+                    if *key_id != recipient_id(&own_public) {
+                        continue;
+                    }
+                    let ephemeral: [u8; 32] = decode(ephemeral_public_key, "ephemeral key")?
+                        .try_into()
+                        .map_err(|_| encryption_error("Ephemeral key must be 32 bytes"))?;
+                    let ephemeral = PublicKey::from(ephemeral);
+                    let kek = x25519_kek(secret, &ephemeral, &ephemeral, &own_public)?;
+                    unwrap_key(&kek, wrapped_key, X25519_WRAP_INFO)
+                }
+                (RecipientStanza::Passphrase { kdf, wrapped_key }, Identity::Passphrase(passphrase)) => {
+                    let kek = kdf.derive(passphrase.as_bytes())?;
+                    unwrap_key(&kek, wrapped_key, PASSPHRASE_WRAP_AAD)
+                }
+                _ => continue,
+            };
+            if let Some(key) = key? {
+                return Ok(Some(ContentKey { key, cipher }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn component_aad(document_id: &str, name: &str) -> Vec<u8> {
+    let mut aad = COMPONENT_AAD_PREFIX.to_vec();
+    for part in [document_id, name] {
+        aad.push(0);
+        aad.extend_from_slice(part.as_bytes());
+    }
+    aad
+}
+
+fn decode(value: &str, what: &str) -> TdfResult<Vec<u8>> {
+    STANDARD
+        .decode(value)
+        .map_err(|e| encryption_error(format!("Invalid {}: {}", what, e)))
+}
+
+/// Derive the key-encryption key for an X25519 recipient
+///
+/// The ephemeral and recipient public keys are mixed into the HKDF salt so
+/// the wrapped key is bound to both.
+fn x25519_kek(
+    secret: &StaticSecret,
+    peer: &PublicKey,
+    ephemeral_public: &PublicKey,
+    recipient_public: &PublicKey,
+) -> TdfResult<Zeroizing<[u8; CONTENT_KEY_LEN]>> {
+    let shared = secret.diffie_hellman(peer);
+    if !shared.was_contributory() {
+        return Err(encryption_error("X25519 key agreement produced a low-order result"));
+    }
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public.as_bytes());
+    salt[32..].copy_from_slice(recipient_public.as_bytes());
+
+    let mut kek = Zeroizing::new([0u8; CONTENT_KEY_LEN]);
+    Hkdf::<Sha256>::new(Some(&salt), shared.as_bytes())
+        .expand(X25519_WRAP_INFO, kek.as_mut())
+        .map_err(|_| encryption_error("HKDF expansion failed"))?;
+    Ok(kek)
+}
+
+fn wrap_key(kek: &[u8; CONTENT_KEY_LEN], key: &[u8], aad: &[u8]) -> TdfResult<String> {
+    let nonce: [u8; WRAP_NONCE_LEN] = generate_secure_bytes(WRAP_NONCE_LEN)?
+        .try_into()
+        .map_err(|_| encryption_error("Nonce generation failed"))?;
+    let ciphertext = XChaCha20Poly1305::new_from_slice(kek)
+        .map_err(|_| encryption_error("Invalid key-encryption key"))?
+        .encrypt(&XNonce::from(nonce), Payload { msg: key, aad })
+        .map_err(|_| encryption_error("Content key wrapping failed"))?;
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&ciphertext);
+    Ok(STANDARD.encode(out))
+}
+
+/// Unwrap a content key; `None` when the key-encryption key does not match
+fn unwrap_key(
+    kek: &[u8; CONTENT_KEY_LEN],
+    wrapped: &str,
+    aad: &[u8],
+) -> TdfResult<Option<Zeroizing<[u8; CONTENT_KEY_LEN]>>> {
+    let wrapped = decode(wrapped, "wrapped key")?;
+    if wrapped.len() < WRAP_NONCE_LEN {
+        return Err(encryption_error("Wrapped key is truncated"));
+    }
+    let (nonce, ciphertext) = wrapped.split_at(WRAP_NONCE_LEN);
+    let nonce: [u8; WRAP_NONCE_LEN] = nonce.try_into().expect("nonce length");
+    let plaintext = match XChaCha20Poly1305::new_from_slice(kek)
+        .map_err(|_| encryption_error("Invalid key-encryption key"))?
+        .decrypt(&XNonce::from(nonce), Payload { msg: ciphertext, aad })
+    {
+        Ok(plaintext) => Zeroizing::new(plaintext),
+        Err(_) => return Ok(None),
+    };
+
+    let mut key = Zeroizing::new([0u8; CONTENT_KEY_LEN]);
+    if plaintext.len() != CONTENT_KEY_LEN {
+        return Err(encryption_error("Wrapped content key has the wrong length"));
+    }
+    key.copy_from_slice(&plaintext);
+    Ok(Some(key))
+}
+
+/// Ciphertext of an encrypted section, stored in place of its title and blocks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptedSection {
+    /// Audience group the section is encrypted for (e.g. "directors")
+    pub group: String,
+    pub cipher: ContentCipher,
+    /// Section key wrapped for each member of the group
+    pub recipients: Vec<RecipientStanza>,
+    /// Base64 `nonce || ciphertext` of the section title and blocks
+    pub ciphertext: String,
+}
+
+/// Plaintext of an encrypted section
+#[derive(Serialize, Deserialize)]
+struct SectionBody {
+    title: Option<String>,
+    content: Vec<ContentBlock>,
+}
+
+/// Recipients entitled to read a group of sections
+#[derive(Debug, Clone)]
+pub struct AccessGroup {
+    pub name: String,
+    pub recipients: Vec<Recipient>,
+}
+
+/// Section-to-audience assignment for `ArchiveBuilder::with_section_encryption`
+#[derive(Debug, Clone, Default)]
+pub struct SectionEncryptionConfig {
+    pub cipher: ContentCipher,
+    pub groups: Vec<AccessGroup>,
+    /// `(section id, group name)` pairs
+    pub sections: Vec<(String, String)>,
+}
+
+impl SectionEncryptionConfig {
+    pub fn new(cipher: ContentCipher) -> Self {
+        SectionEncryptionConfig { cipher, ..Default::default() }
+    }
+
+    /// Define an audience group
+    pub fn with_group(mut self, name: impl Into<String>, recipients: Vec<Recipient>) -> Self {
+        self.groups.push(AccessGroup { name: name.into(), recipients });
+        self
+    }
+
+    /// Encrypt a section for a previously defined group
+    pub fn with_section(mut self, section_id: impl Into<String>, group: impl Into<String>) -> Self {
+        self.sections.push((section_id.into(), group.into()));
+        self
+    }
+
+    /// Encrypt the assigned sections of a document in place
+    ///
+    /// # Returns
+    /// * `Err(TdfError::EncryptionError)` if a section or group does not exist,
+    ///   or a section is already encrypted
+    pub fn apply(&self, document: &mut Document) -> TdfResult<()> {
+        for (section_id, group_name) in &self.sections {
+            let group = self
+                .groups
+                .iter()
+                .find(|g| g.name == *group_name)
+                .ok_or_else(|| encryption_error(format!("Unknown access group '{}'", group_name)))?;
+            let document_id = document.manifest.document.id.clone();
+            let section = document
+                .content
+                .sections
+                .iter_mut()
+                .find(|s| s.id == *section_id)
+                .ok_or_else(|| encryption_error(format!("Unknown section '{}'", section_id)))?;
+            encrypt_section(section, &document_id, &group.name, &group.recipients, self.cipher)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a reader could see a section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionAccess {
+    /// Not encrypted
+    Plain,
+    /// Encrypted and opened with one of the reader's identities
+    Decrypted,
+    /// Encrypted for a group the reader is not in; shown as a placeholder
+    Locked,
+}
+
+/// Access outcome for one section
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionReport {
+    pub id: String,
+    /// Audience group of an encrypted section
+    pub group: Option<String>,
+    pub access: SectionAccess,
+}
+
+fn section_aad_name(section_id: &str) -> String {
+    format!("section:{}", section_id)
+}
+
+/// Encrypt a section's title and blocks for a group of recipients
+///
+/// The section keeps its id; title and content are cleared and the
+/// ciphertext is stored in `Section::encrypted`.
+pub fn encrypt_section(
+    section: &mut Section,
+    document_id: &str,
+    group: &str,
+    recipients: &[Recipient],
+    cipher: ContentCipher,
+) -> TdfResult<()> {
+    if section.encrypted.is_some() {
+        return Err(encryption_error(format!("Section '{}' is already encrypted", section.id)));
+    }
+    let body = SectionBody { title: section.title.take(), content: std::mem::take(&mut section.content) };
+    let mut plaintext = Zeroizing::new(Vec::new());
+    ciborium::into_writer(&body, &mut *plaintext)
+        .map_err(|e| encryption_error(format!("Failed to serialize section: {}", e)))?;
+
+    let key = ContentKey::generate(cipher)?;
+    let ciphertext = key.encrypt_component(document_id, &section_aad_name(&section.id), &plaintext)?;
+    section.encrypted = Some(EncryptedSection {
+        group: group.to_string(),
+        cipher,
+        recipients: key.wrap_all(recipients)?,
+        ciphertext: STANDARD.encode(ciphertext),
+    });
+    Ok(())
+}
+
+/// Decrypt every encrypted section that one of the identities can open
+///
+/// Sections the reader is not entitled to stay as placeholders. A section
+/// whose key opens but whose ciphertext fails authentication is an error.
+///
+/// # Returns
+/// * Per-section access report, in document order
+pub fn decrypt_sections(document: &mut Document, identities: &[Identity]) -> TdfResult<Vec<SectionReport>> {
+    let document_id = document.manifest.document.id.clone();
+    let mut reports = Vec::with_capacity(document.content.sections.len());
+
+    for section in &mut document.content.sections {
+        let Some(encrypted) = section.encrypted.clone() else {
+            reports.push(SectionReport { id: section.id.clone(), group: None, access: SectionAccess::Plain });
+            continue;
+        };
+
+        let mut key = None;
+        for identity in identities {
+            key = ContentKey::open_stanzas(&encrypted.recipients, encrypted.cipher, identity)?;
+            if key.is_some() {
+                break;
+            }
+        }
+
+        let access = match key {
+            Some(key) => {
+                let ciphertext = decode(&encrypted.ciphertext, "section ciphertext")?;
+                let plaintext = Zeroizing::new(key.decrypt_component(
+                    &document_id,
+                    &section_aad_name(&section.id),
+                    &ciphertext,
+                )?);
+                let body: SectionBody = ciborium::from_reader(plaintext.as_slice())
+                    .map_err(|e| encryption_error(format!("Invalid section '{}': {}", section.id, e)))?;
+                section.title = body.title;
+                section.content = body.content;
+                section.encrypted = None;
+                SectionAccess::Decrypted
+            }
+            None => SectionAccess::Locked,
+        };
+        reports.push(SectionReport { id: section.id.clone(), group: Some(encrypted.group), access });
+    }
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn cheap_passphrase_recipient(passphrase: &str) -> Recipient {
+        // Cheap parameters to keep tests fast
+        let kdf = KdfParams::Scrypt { log_n: 10, r: 8, p: 1, salt: STANDARD.encode([7u8; 16]) };
+        Recipient::passphrase_with_params(passphrase, kdf).unwrap()
+    }
+
+    #[test]
+    fn test_component_round_trip_both_ciphers() {
+        for cipher in [ContentCipher::Aes256Gcm, ContentCipher::XChaCha20Poly1305] {
+            let key = ContentKey::generate(cipher).unwrap();
+            let sealed = key.encrypt_component("doc-1", "content", b"secret payload").unwrap();
+            assert_eq!(sealed.len(), cipher.nonce_len() + b"secret payload".len() + 16);
+            assert_eq!(key.decrypt_component("doc-1", "content", &sealed).unwrap(), b"secret payload");
+        }
+    }
+
+    #[test]
+    fn test_component_bound_to_name_and_document() {
+        let key = ContentKey::generate(ContentCipher::Aes256Gcm).unwrap();
+        let sealed = key.encrypt_component("doc-1", "content", b"payload").unwrap();
+        assert!(key.decrypt_component("doc-1", "styles", &sealed).is_err());
+        assert!(key.decrypt_component("doc-2", "content", &sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(key.decrypt_component("doc-1", "content", &tampered).is_err());
+    }
+
+    #[test]
+    fn test_ed25519_recipient_unwraps_content_key() {
+        let alice = SigningKey::generate(&mut OsRng);
+        let bob = SigningKey::generate(&mut OsRng);
+        let info = EncryptionInfo::new(ContentCipher::XChaCha20Poly1305);
+
+        let key = ContentKey::generate(info.cipher).unwrap();
+        let header = key
+            .seal_header(&[Recipient::from_ed25519(&alice.verifying_key())])
+            .unwrap();
+        let sealed = key.encrypt_component("doc", "content", b"for alice").unwrap();
+
+        let opened = ContentKey::open_header(&header, &info, &Identity::from_ed25519(&alice)).unwrap();
+        assert_eq!(opened.decrypt_component("doc", "content", &sealed).unwrap(), b"for alice");
+
+        let err = ContentKey::open_header(&header, &info, &Identity::from_ed25519(&bob));
+        assert!(matches!(err, Err(TdfError::EncryptionError(_))));
+    }
+
+    #[test]
+    fn test_passphrase_recipient() {
+        let info = EncryptionInfo::new(ContentCipher::Aes256Gcm);
+        let key = ContentKey::generate(info.cipher).unwrap();
+        let header = key.seal_header(&[cheap_passphrase_recipient("correct horse")]).unwrap();
+
+        assert!(ContentKey::open_header(&header, &info, &Identity::passphrase("correct horse")).is_ok());
+        assert!(ContentKey::open_header(&header, &info, &Identity::passphrase("wrong")).is_err());
+    }
+
+    #[test]
+    fn test_header_cbor_round_trip_and_tampering() {
+        let alice = SigningKey::generate(&mut OsRng);
+        let info = EncryptionInfo::new(ContentCipher::Aes256Gcm);
+        let key = ContentKey::generate(info.cipher).unwrap();
+        let header = key
+            .seal_header(&[
+                Recipient::from_ed25519(&alice.verifying_key()),
+                cheap_passphrase_recipient("pass"),
+            ])
+            .unwrap();
+
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&header, &mut bytes).unwrap();
+        let decoded: EncryptionHeader = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(decoded, header);
+
+        // Swapping the ephemeral key breaks the wrapped key's authentication
+        let mut tampered = decoded.clone();
+        if let RecipientStanza::X25519 { ephemeral_public_key, .. } = &mut tampered.recipients[0] {
+            let other = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+            *ephemeral_public_key = STANDARD.encode(other.as_bytes());
+        }
+        assert!(ContentKey::open_header(&tampered, &info, &Identity::from_ed25519(&alice)).is_err());
+    }
+
+    fn test_document(sections: &[&str]) -> Document {
+        let sections = sections
+            .iter()
+            .map(|id| Section {
+                id: id.to_string(),
+                title: Some(format!("Title {}", id)),
+                content: vec![ContentBlock::Paragraph { text: format!("Body {}", id).into(), id: None, xbrl: None }],
+                encrypted: None,
+            })
+            .collect();
+        Document::new(
+            "Pack".to_string(),
+            "en".to_string(),
+            crate::content::DocumentContent { sections },
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_section_encryption_per_group() {
+        let director = SigningKey::generate(&mut OsRng);
+        let auditor = SigningKey::generate(&mut OsRng);
+        let mut document = test_document(&["intro", "strategy", "audit"]);
+
+        SectionEncryptionConfig::new(ContentCipher::Aes256Gcm)
+            .with_group("directors", vec![Recipient::from_ed25519(&director.verifying_key())])
+            .with_group("auditors", vec![Recipient::from_ed25519(&auditor.verifying_key())])
+            .with_section("strategy", "directors")
+            .with_section("audit", "auditors")
+            .apply(&mut document)
+            .unwrap();
+
+        let placeholder = &document.content.sections[1];
+        assert!(placeholder.title.is_none() && placeholder.content.is_empty());
+        assert_eq!(placeholder.encrypted.as_ref().unwrap().group, "directors");
+
+        let mut view = document.clone();
+        let reports = decrypt_sections(&mut view, &[Identity::from_ed25519(&director)]).unwrap();
+        let access: Vec<_> = reports.iter().map(|r| r.access).collect();
+        assert_eq!(access, [SectionAccess::Plain, SectionAccess::Decrypted, SectionAccess::Locked]);
+        assert_eq!(view.content.sections[1].title.as_deref(), Some("Title strategy"));
+        assert!(view.content.sections[2].encrypted.is_some());
+    }
+
+    #[test]
+    fn test_section_ciphertext_bound_to_section_id() {
+        let director = SigningKey::generate(&mut OsRng);
+        let mut document = test_document(&["a", "b"]);
+        SectionEncryptionConfig::new(ContentCipher::XChaCha20Poly1305)
+            .with_group("directors", vec![Recipient::from_ed25519(&director.verifying_key())])
+            .with_section("a", "directors")
+            .with_section("b", "directors")
+            .apply(&mut document)
+            .unwrap();
+
+        // Swapping ciphertexts between sections is detected
+        let sections = &mut document.content.sections;
+        let first = sections[0].encrypted.take();
+        sections[0].encrypted = sections[1].encrypted.take();
+        sections[1].encrypted = first;
+        assert!(decrypt_sections(&mut document, &[Identity::from_ed25519(&director)]).is_err());
+    }
+
+    #[test]
+    fn test_section_encryption_rejects_unknown_targets() {
+        let mut document = test_document(&["a"]);
+        let recipient = cheap_passphrase_recipient("pw");
+        let unknown_section = SectionEncryptionConfig::new(ContentCipher::Aes256Gcm)
+            .with_group("g", vec![recipient.clone()])
+            .with_section("missing", "g");
+        assert!(unknown_section.apply(&mut document).is_err());
+
+        let unknown_group = SectionEncryptionConfig::new(ContentCipher::Aes256Gcm).with_section("a", "nobody");
+        assert!(unknown_group.apply(&mut document).is_err());
+    }
+
+    #[test]
+    fn test_empty_recipients_rejected() {
+        let key = ContentKey::generate(ContentCipher::Aes256Gcm).unwrap();
+        assert!(key.seal_header(&[]).is_err());
+        assert!(Recipient::passphrase_with_params("", KdfParams::new(Kdf::Scrypt).unwrap()).is_err());
+    }
+}
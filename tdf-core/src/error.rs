@@ -81,6 +81,21 @@ pub enum TdfError {
 
     #[error("Depth limit exceeded: {0}")]
     DepthLimitExceeded(String),
+
+    #[error("Certificate error: {0}")]
+    CertificateError(String),
+
+    #[error("DID resolution error: {0}")]
+    DidResolution(String),
+
+    #[error("Signer error: {0}")]
+    SignerError(String),
+
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
+    #[error("Formula error: {0}")]
+    FormulaError(String),
 }
 
 pub type TdfResult<T> = Result<T, TdfError>;
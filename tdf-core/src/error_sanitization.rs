@@ -62,6 +62,11 @@ pub fn sanitize_error(error: &TdfError) -> String {
         TdfError::IntegerOverflow(_) => "Integer overflow detected".to_string(),
         TdfError::ParseError(_) => "Parse error".to_string(),
         TdfError::DepthLimitExceeded(_) => "Depth limit exceeded".to_string(),
+        TdfError::CertificateError(_) => "Certificate validation failed".to_string(),
+        TdfError::DidResolution(_) => "Signer identity resolution failed".to_string(),
+        TdfError::SignerError(_) => "Signing operation failed".to_string(),
+        TdfError::EncryptionError(_) => "Encryption operation failed".to_string(),
+        TdfError::FormulaError(_) => "Formula evaluation failed".to_string(),
     }
 }
 
@@ -140,6 +145,11 @@ pub fn error_code(error: &TdfError) -> &'static str {
         TdfError::IntegerOverflow(_) => "ERR_OVERFLOW",
         TdfError::ParseError(_) => "ERR_PARSE",
         TdfError::DepthLimitExceeded(_) => "ERR_DEPTH",
+        TdfError::CertificateError(_) => "ERR_CERTIFICATE",
+        TdfError::DidResolution(_) => "ERR_DID_RESOLUTION",
+        TdfError::SignerError(_) => "ERR_SIGNER",
+        TdfError::EncryptionError(_) => "ERR_ENCRYPTION",
+        TdfError::FormulaError(_) => "ERR_FORMULA",
     }
 }
 
@@ -0,0 +1,636 @@
+//! Form fields and signed submissions
+//!
+//! A template document lays out typed form fields (`text_field`,
+//! `number_field`, `date_field`, `checkbox`, `choice` and
+//! `signature_field` blocks) with their constraints. The template is signed
+//! like any other document; the fields carry no values.
+//!
+//! A counterparty completing the form produces a [`Submission`]: the field
+//! values, the id and root hash of the template they were entered against,
+//! and the filler's signature over
+//!
+//! ```text
+//! SHA-256("tdf-submission-v1" || CBOR(submission without its signature))
+//! ```
+//!
+//! Submissions are stored as `submissions/<id>.cbor` in the template's
+//! archive. They are outside the Merkle tree, so adding one leaves the
+//! template's root hash and signatures valid, and several counterparties can
+//! return submissions against the same signed template. Signing a submission
+//! is what fills its signature fields.
+
+use crate::content::{ContentBlock, Decimal};
+use crate::currency::is_iso_4217;
+use crate::document::Document;
+use crate::error::{TdfError, TdfResult};
+use crate::signature::{
+    DocumentSignature, DocumentSigner, SignatureBlock, SignatureManager, SignatureScope, VerificationResult,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use ed25519_dalek::VerifyingKey;
+use k256::ecdsa::VerifyingKey as Secp256k1VerifyingKey;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Archive directory holding submissions, one `<id>.cbor` entry each
+pub const SUBMISSIONS_DIR: &str = "submissions/";
+/// Current submission format version
+pub const SUBMISSION_VERSION: u8 = 1;
+const DIGEST_DOMAIN: &[u8] = b"tdf-submission-v1";
+
+/// Option of a `choice` field
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChoiceOption {
+    /// Value stored in a submission
+    pub value: String,
+    /// Text shown for the option; the value when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl ChoiceOption {
+    pub fn new(value: impl Into<String>, label: impl Into<String>) -> Self {
+        ChoiceOption { value: value.into(), label: Some(label.into()) }
+    }
+
+    /// Text shown for the option
+    pub fn text(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.value)
+    }
+}
+
+/// A form field block, seen independently of its kind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormField<'a> {
+    pub id: &'a str,
+    pub label: &'a str,
+    pub required: bool,
+    pub help: Option<&'a str>,
+    pub kind: FieldKind<'a>,
+}
+
+/// Kind of a form field with its constraints
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind<'a> {
+    Text { multiline: bool, max_length: Option<usize>, pattern: Option<&'a str> },
+    Number { min: Option<Decimal>, max: Option<Decimal>, decimals: Option<u32>, currency: Option<&'a str> },
+    Date { min: Option<NaiveDate>, max: Option<NaiveDate> },
+    Checkbox,
+    Choice { options: &'a [ChoiceOption], multiple: bool },
+    Signature { role: Option<&'a str> },
+}
+
+fn form_error(msg: impl Into<String>) -> TdfError {
+    TdfError::InvalidDocument(format!("Form: {}", msg.into()))
+}
+
+impl<'a> FormField<'a> {
+    /// The field a block lays out; `None` for other blocks
+    pub fn from_block(block: &'a ContentBlock) -> Option<Self> {
+        let field = |id: &'a String, label: &'a String, required: &bool, help: &'a Option<String>, kind| FormField {
+            id,
+            label,
+            required: *required,
+            help: help.as_deref(),
+            kind,
+        };
+        Some(match block {
+            ContentBlock::TextField { id, label, required, help, multiline, max_length, pattern } => field(
+                id,
+                label,
+                required,
+                help,
+                FieldKind::Text { multiline: *multiline, max_length: *max_length, pattern: pattern.as_deref() },
+            ),
+            ContentBlock::NumberField { id, label, required, help, min, max, decimals, currency } => field(
+                id,
+                label,
+                required,
+                help,
+                FieldKind::Number { min: *min, max: *max, decimals: *decimals, currency: currency.as_deref() },
+            ),
+            ContentBlock::DateField { id, label, required, help, min, max } => {
+                field(id, label, required, help, FieldKind::Date { min: *min, max: *max })
+            }
+            ContentBlock::Checkbox { id, label, required, help } => field(id, label, required, help, FieldKind::Checkbox),
+            ContentBlock::Choice { id, label, required, help, options, multiple } => {
+                field(id, label, required, help, FieldKind::Choice { options, multiple: *multiple })
+            }
+            ContentBlock::SignatureField { id, label, required, help, role } => {
+                field(id, label, required, help, FieldKind::Signature { role: role.as_deref() })
+            }
+            _ => return None,
+        })
+    }
+
+    /// Check that the field's constraints are consistent
+    pub fn validate(&self) -> Result<(), String> {
+        if self.label.trim().is_empty() {
+            return Err("has no label".to_string());
+        }
+        match self.kind {
+            FieldKind::Text { pattern: Some(pattern), .. } => {
+                regex::Regex::new(pattern).map_err(|e| format!("has invalid pattern '{}': {}", pattern, e))?;
+            }
+            FieldKind::Number { min, max, currency, .. } => {
+                if let (Some(min), Some(max)) = (min, max) {
+                    if min > max {
+                        return Err(format!("minimum {} is above its maximum {}", min, max));
+                    }
+                }
+                if let Some(code) = currency.filter(|code| !is_iso_4217(code)) {
+                    return Err(format!("has unknown currency '{}' (expected an ISO 4217 code)", code));
+                }
+            }
+            FieldKind::Date { min: Some(min), max: Some(max) } if min > max => {
+                return Err(format!("earliest date {} is after its latest {}", min, max));
+            }
+            FieldKind::Choice { options, .. } => {
+                if options.is_empty() {
+                    return Err("has no options".to_string());
+                }
+                let mut seen = HashSet::new();
+                if let Some(option) = options.iter().find(|o| o.value.is_empty() || !seen.insert(o.value.as_str())) {
+                    return Err(format!("has an empty or repeated option value '{}'", option.value));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Short description of what the field accepts, shown next to it when
+    /// the template is rendered; renderers mark required fields themselves
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        match self.kind {
+            FieldKind::Text { max_length, .. } => {
+                parts.push("text".to_string());
+                parts.extend(max_length.map(|n| format!("up to {} characters", n)));
+            }
+            FieldKind::Number { min, max, decimals, currency } => {
+                parts.push(currency.map_or("number".to_string(), |code| format!("amount in {}", code)));
+                parts.extend(min.map(|min| format!("at least {}", min)));
+                parts.extend(max.map(|max| format!("at most {}", max)));
+                parts.extend(decimals.map(|n| format!("up to {} decimal places", n)));
+            }
+            FieldKind::Date { min, max } => {
+                parts.push("date, YYYY-MM-DD".to_string());
+                parts.extend(min.map(|min| format!("from {}", min)));
+                parts.extend(max.map(|max| format!("until {}", max)));
+            }
+            FieldKind::Checkbox => parts.push(if self.required { "must be ticked" } else { "tick if applicable" }.to_string()),
+            FieldKind::Choice { options, multiple } => {
+                let texts: Vec<&str> = options.iter().map(ChoiceOption::text).collect();
+                let verb = if multiple { "any of" } else { "one of" };
+                parts.push(format!("{}: {}", verb, texts.join(", ")));
+            }
+            FieldKind::Signature { role } => {
+                parts.push(role.map_or("signature".to_string(), |role| format!("signature of the {}", role)));
+            }
+        }
+        parts.join(", ")
+    }
+
+    /// Check a submitted value against the field and return it in the form
+    /// it is stored: a string (numbers as exact decimals, dates as
+    /// `YYYY-MM-DD`), a boolean for a checkbox, or a list of option values
+    /// for a multiple choice
+    ///
+    /// Blank values (`null`, `""`, `[]`) of optional fields are `Ok(None)`.
+    pub fn check_value(&self, value: &Value) -> Result<Option<Value>, String> {
+        let blank = match value {
+            Value::Null => true,
+            Value::String(s) => s.is_empty(),
+            Value::Array(items) => items.is_empty(),
+            _ => false,
+        };
+        if let FieldKind::Signature { .. } = self.kind {
+            return Err("is filled by signing the submission, not with a value".to_string());
+        }
+        if blank {
+            return if self.required { Err("is required".to_string()) } else { Ok(None) };
+        }
+        let text = || value.as_str().ok_or_else(|| format!("expects a string, got {}", value));
+        let stored = match self.kind {
+            FieldKind::Text { multiline, max_length, pattern } => {
+                let text = text()?;
+                if !multiline && text.contains('\n') {
+                    return Err("takes a single line".to_string());
+                }
+                if let Some(max) = max_length.filter(|max| text.chars().count() > *max) {
+                    return Err(format!("is longer than {} characters", max));
+                }
+                if let Some(pattern) = pattern {
+                    let anchored = regex::Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| e.to_string())?;
+                    if !anchored.is_match(text) {
+                        return Err(format!("does not match the pattern '{}'", pattern));
+                    }
+                }
+                Value::String(text.to_string())
+            }
+            FieldKind::Number { min, max, decimals, .. } => {
+                let raw = match value {
+                    Value::Number(n) => n.to_string(),
+                    Value::String(s) => s.trim().to_string(),
+                    _ => return Err(format!("expects a number, got {}", value)),
+                };
+                let number: Decimal = raw.parse().map_err(|_| format!("'{}' is not a decimal number", raw))?;
+                if let Some(min) = min.filter(|min| number < *min) {
+                    return Err(format!("{} is below the minimum {}", number, min));
+                }
+                if let Some(max) = max.filter(|max| number > *max) {
+                    return Err(format!("{} is above the maximum {}", number, max));
+                }
+                if let Some(places) = decimals.filter(|places| number.normalize().scale() > *places) {
+                    return Err(format!("{} has more than {} decimal places", number, places));
+                }
+                Value::String(number.to_string())
+            }
+            FieldKind::Date { min, max } => {
+                let text = text()?;
+                let date = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                    .map_err(|_| format!("'{}' is not a date (expected YYYY-MM-DD)", text))?;
+                if let Some(min) = min.filter(|min| date < *min) {
+                    return Err(format!("{} is before {}", date, min));
+                }
+                if let Some(max) = max.filter(|max| date > *max) {
+                    return Err(format!("{} is after {}", date, max));
+                }
+                Value::String(date.to_string())
+            }
+            FieldKind::Checkbox => {
+                let ticked = value.as_bool().ok_or_else(|| format!("expects true or false, got {}", value))?;
+                if self.required && !ticked {
+                    return Err("must be ticked".to_string());
+                }
+                Value::Bool(ticked)
+            }
+            FieldKind::Choice { options, multiple } => {
+                let known = |v: &Value| -> Result<String, String> {
+                    let v = v.as_str().ok_or_else(|| format!("expects option values, got {}", v))?;
+                    if options.iter().any(|o| o.value == v) {
+                        Ok(v.to_string())
+                    } else {
+                        Err(format!("has no option '{}'", v))
+                    }
+                };
+                match (value, multiple) {
+                    (Value::Array(items), true) => {
+                        let mut chosen = Vec::new();
+                        for item in items {
+                            let v = known(item)?;
+                            if chosen.contains(&v) {
+                                return Err(format!("lists option '{}' twice", v));
+                            }
+                            chosen.push(v);
+                        }
+                        Value::Array(chosen.into_iter().map(Value::String).collect())
+                    }
+                    (Value::Array(_), false) => return Err("takes a single option".to_string()),
+                    (v, true) => Value::Array(vec![Value::String(known(v)?)]),
+                    (v, false) => Value::String(known(v)?),
+                }
+            }
+            FieldKind::Signature { .. } => unreachable!("handled above"),
+        };
+        Ok(Some(stored))
+    }
+}
+
+/// Form fields of a document, in reading order
+///
+/// Fields inside encrypted sections are only seen once decrypted.
+pub fn fields(document: &Document) -> Vec<FormField<'_>> {
+    document
+        .content
+        .sections
+        .iter()
+        .flat_map(|section| &section.content)
+        .filter_map(FormField::from_block)
+        .collect()
+}
+
+/// Template a submission was entered against
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateRef {
+    /// `manifest.document.id` of the template
+    pub id: String,
+    /// Merkle root of the template, as in `manifest.integrity.root_hash`
+    pub root_hash: String,
+}
+
+/// Values a counterparty entered into a template's fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    pub version: u8,
+    pub id: String,
+    pub template: TemplateRef,
+    pub submitted: DateTime<Utc>,
+    /// Stored value of each filled field, by field id
+    pub values: BTreeMap<String, Value>,
+    /// Filler's signature over [`Submission::digest`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<DocumentSignature>,
+}
+
+impl Submission {
+    /// An unsigned submission of `values` to a built template
+    ///
+    /// Values are checked against the fields and stored in normal form
+    /// (see [`FormField::check_value`]); unknown fields, invalid values and
+    /// missing required values are rejected. Required signature fields are
+    /// filled by [`Submission::sign`].
+    pub fn new(template: &Document, values: BTreeMap<String, Value>) -> TdfResult<Self> {
+        let root_hash = &template.manifest.integrity.root_hash;
+        if root_hash.is_empty() {
+            return Err(form_error("template has no root hash; read it from its archive"));
+        }
+        let (values, problems) = check_values(template, &values);
+        if !problems.is_empty() {
+            return Err(form_error(problems.join("; ")));
+        }
+        Ok(Submission {
+            version: SUBMISSION_VERSION,
+            id: uuid::Uuid::new_v4().to_string(),
+            template: TemplateRef { id: template.manifest.document.id.clone(), root_hash: root_hash.clone() },
+            submitted: Utc::now(),
+            values,
+            signature: None,
+        })
+    }
+
+    /// Hash the filler signs: the submission without its signature
+    pub fn digest(&self) -> TdfResult<Vec<u8>> {
+        let unsigned = Submission { signature: None, ..self.clone() };
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&unsigned, &mut cbor).map_err(|e| TdfError::ParseError(format!("CBOR: {}", e)))?;
+        let mut hasher = Sha256::new();
+        hasher.update(DIGEST_DOMAIN);
+        hasher.update(&cbor);
+        Ok(hasher.finalize().to_vec())
+    }
+
+    /// Sign the submission as the filler, replacing any earlier signature
+    pub fn sign(&mut self, signer: &dyn DocumentSigner, signer_id: String, signer_name: String) -> TdfResult<()> {
+        let digest = self.digest()?;
+        let (signature, _) =
+            SignatureManager::sign_with_signer(signer, &digest, signer_id, signer_name, SignatureScope::Full, None)?;
+        self.signature = Some(signature);
+        Ok(())
+    }
+
+    /// Verify the filler's signature; `None` when the submission is unsigned
+    pub fn verify_signature(
+        &self,
+        ed25519_keys: &[(String, VerifyingKey)],
+        secp256k1_keys: &[(String, Secp256k1VerifyingKey)],
+    ) -> TdfResult<Option<VerificationResult>> {
+        let Some(signature) = &self.signature else {
+            return Ok(None);
+        };
+        let block = SignatureBlock { signatures: vec![signature.clone()] };
+        let results =
+            SignatureManager::verify_signature_block_mixed(&block, &self.digest()?, ed25519_keys, secp256k1_keys, None)?;
+        Ok(results.into_iter().next())
+    }
+
+    /// Problems with the submission as an answer to `template`: a different
+    /// template, unknown fields, values failing their field's constraints,
+    /// missing required values and required signature fields left unsigned
+    pub fn check(&self, template: &Document) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.version != SUBMISSION_VERSION {
+            problems.push(format!("unsupported submission version {}", self.version));
+        }
+        if self.template.id != template.manifest.document.id {
+            problems.push(format!("submitted to template '{}', not '{}'", self.template.id, template.manifest.document.id));
+        }
+        if self.template.root_hash != template.manifest.integrity.root_hash {
+            problems.push("submitted to a different revision of the template".to_string());
+        }
+        let (stored, value_problems) = check_values(template, &self.values);
+        problems.extend(value_problems);
+        if problems.is_empty() && stored != self.values {
+            problems.push("values are not in stored form".to_string());
+        }
+        if self.signature.is_none() {
+            problems.extend(
+                fields(template)
+                    .iter()
+                    .filter(|f| f.required && matches!(f.kind, FieldKind::Signature { .. }))
+                    .map(|f| format!("field '{}' is required but the submission is unsigned", f.id)),
+            );
+        }
+        problems
+    }
+}
+
+/// Stored form of `values` and the problems found checking them
+fn check_values(template: &Document, values: &BTreeMap<String, Value>) -> (BTreeMap<String, Value>, Vec<String>) {
+    let fields = fields(template);
+    let mut problems: Vec<String> = values
+        .keys()
+        .filter(|id| !fields.iter().any(|f| f.id == id.as_str()))
+        .map(|id| format!("template has no field '{}'", id))
+        .collect();
+    let mut stored = BTreeMap::new();
+    for field in &fields {
+        let value = values.get(field.id);
+        if value.is_none() && matches!(field.kind, FieldKind::Signature { .. }) {
+            continue;
+        }
+        match field.check_value(value.unwrap_or(&Value::Null)) {
+            Ok(Some(value)) => {
+                stored.insert(field.id.to_string(), value);
+            }
+            Ok(None) => {}
+            Err(e) => problems.push(format!("field '{}' {}", field.id, e)),
+        }
+    }
+    (stored, problems)
+}
+
+/// Verify a template archive and read it with its verified root hash
+///
+/// Fails if the archive does not verify or is encrypted (fields of
+/// encrypted sections cannot be checked without decrypting them).
+pub fn read_template(path: &Path) -> TdfResult<Document> {
+    let report = crate::archive::ArchiveReader::verify(path)?;
+    if !report.integrity_valid {
+        return Err(form_error("template failed integrity verification"));
+    }
+    if report.encrypted {
+        return Err(form_error("encrypted templates must be decrypted before they are filled in"));
+    }
+    let mut template = report.document;
+    template.manifest.integrity.root_hash = report.root_hash;
+    Ok(template)
+}
+
+/// Copy the template archive `input` to `output` with a signed submission
+/// added
+///
+/// The template must verify, the submission must be signed and answer this
+/// revision of the template without problems (see [`Submission::check`]),
+/// and its id must not already be in the archive. The template's entries
+/// are copied unchanged, so its root hash and signatures stay valid.
+/// `input` and `output` may be the same path.
+pub fn add_submission(input: &Path, output: &Path, submission: &Submission) -> TdfResult<()> {
+    let template = read_template(input)?;
+    if submission.signature.is_none() {
+        return Err(form_error("submission is not signed"));
+    }
+    let problems = submission.check(&template);
+    if !problems.is_empty() {
+        return Err(form_error(problems.join("; ")));
+    }
+
+    let name = format!("{}{}.cbor", SUBMISSIONS_DIR, submission.id);
+    let mut zip = zip::ZipArchive::new(std::fs::File::open(input)?)?;
+    if zip.by_name(&name).is_ok() {
+        return Err(form_error(format!("submission '{}' is already in the archive", submission.id)));
+    }
+    let mut entry = Vec::new();
+    ciborium::into_writer(submission, &mut entry).map_err(|e| TdfError::ParseError(format!("CBOR: {}", e)))?;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+    for i in 0..zip.len() {
+        writer.raw_copy_file(zip.by_index(i)?)?;
+    }
+    writer.start_file(name, options)?;
+    writer.write_all(&entry)?;
+    let bytes = writer.finish()?.into_inner();
+    drop(zip);
+    std::fs::write(output, bytes)?;
+    Ok(())
+}
+
+/// Submissions stored in an archive, oldest first
+pub fn read_submissions(path: &Path) -> TdfResult<Vec<Submission>> {
+    let mut zip = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+    let names: Vec<String> = zip
+        .file_names()
+        .filter(|name| name.starts_with(SUBMISSIONS_DIR) && name.ends_with(".cbor"))
+        .map(str::to_string)
+        .collect();
+    let mut submissions = Vec::new();
+    for name in names {
+        let mut bytes = Vec::new();
+        zip.by_name(&name)?.read_to_end(&mut bytes)?;
+        let submission: Submission = ciborium::from_reader(bytes.as_slice())
+            .map_err(|e| TdfError::ParseError(format!("CBOR in {}: {}", name, e)))?;
+        submissions.push(submission);
+    }
+    submissions.sort_by(|a, b| a.submitted.cmp(&b.submitted).then_with(|| a.id.cmp(&b.id)));
+    Ok(submissions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{DocumentContent, Section};
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+    use serde_json::json;
+
+    fn template() -> Document {
+        let blocks: Vec<ContentBlock> = serde_json::from_value(json!([
+            { "type": "text_field", "id": "name", "label": "Legal name", "required": true, "max_length": 40 },
+            { "type": "text_field", "id": "lei", "label": "LEI", "pattern": "[A-Z0-9]{20}" },
+            { "type": "number_field", "id": "turnover", "label": "Turnover", "min": "0", "decimals": 2, "currency": "EUR" },
+            { "type": "date_field", "id": "incorporated", "label": "Incorporated", "max": "2026-12-31" },
+            { "type": "choice", "id": "sector", "label": "Sector", "multiple": true,
+              "options": [{ "value": "fin", "label": "Finance" }, { "value": "mfg" }] },
+            { "type": "checkbox", "id": "attest", "label": "The above is true", "required": true },
+            { "type": "signature_field", "id": "sig", "label": "Signature", "required": true, "role": "director" }
+        ]))
+        .unwrap();
+        let content = DocumentContent {
+            sections: vec![Section { id: "kyc".to_string(), title: None, content: blocks, encrypted: None }],
+        };
+        let mut document = Document::new("KYC".to_string(), "en".to_string(), content, String::new());
+        document.manifest.integrity.root_hash = "ab".repeat(32);
+        document
+    }
+
+    fn values() -> BTreeMap<String, Value> {
+        serde_json::from_value(json!({
+            "name": "Acme Ltd",
+            "lei": "5493001KJTIIGC8Y1R12",
+            "turnover": 1200.5,
+            "incorporated": "2001-04-01",
+            "sector": "fin",
+            "attest": true
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_values_are_checked_and_stored_normalised() {
+        let document = template();
+        let submission = Submission::new(&document, values()).unwrap();
+        assert_eq!(submission.values["turnover"], json!("1200.5"));
+        assert_eq!(submission.values["sector"], json!(["fin"]));
+        assert_eq!(submission.template.root_hash, document.manifest.integrity.root_hash);
+
+        let reject = |field: &str, value: Value| {
+            let mut values = values();
+            values.insert(field.to_string(), value);
+            Submission::new(&document, values).unwrap_err().to_string()
+        };
+        assert!(reject("lei", json!("not-an-lei")).contains("does not match"));
+        assert!(reject("turnover", json!("-1")).contains("below the minimum"));
+        assert!(reject("turnover", json!("1.005")).contains("more than 2 decimal places"));
+        assert!(reject("incorporated", json!("2027-01-01")).contains("is after"));
+        assert!(reject("sector", json!(["fin", "oil"])).contains("no option 'oil'"));
+        assert!(reject("attest", json!(false)).contains("must be ticked"));
+        assert!(reject("name", json!("")).contains("field 'name' is required"));
+        assert!(reject("sig", json!("Jane")).contains("filled by signing"));
+        assert!(reject("extra", json!(1)).contains("no field 'extra'"));
+    }
+
+    #[test]
+    fn test_signed_submission_verifies_and_detects_changes() {
+        let document = template();
+        let mut submission = Submission::new(&document, values()).unwrap();
+        assert_eq!(submission.check(&document), ["field 'sig' is required but the submission is unsigned"]);
+
+        let key = SigningKey::generate(&mut OsRng);
+        submission.sign(&key, "did:web:acme.example".to_string(), "Jane Director".to_string()).unwrap();
+        assert!(submission.check(&document).is_empty());
+        let keys = [("did:web:acme.example".to_string(), key.verifying_key())];
+        assert!(matches!(submission.verify_signature(&keys, &[]).unwrap(), Some(VerificationResult::Valid { .. })));
+
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&submission, &mut bytes).unwrap();
+        let mut read: Submission = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert!(matches!(read.verify_signature(&keys, &[]).unwrap(), Some(VerificationResult::Valid { .. })));
+
+        read.values.insert("name".to_string(), json!("Other Ltd"));
+        assert!(matches!(read.verify_signature(&keys, &[]).unwrap(), Some(VerificationResult::Invalid { .. })));
+
+        let mut revised = document.clone();
+        revised.manifest.integrity.root_hash = "cd".repeat(32);
+        assert_eq!(submission.check(&revised), ["submitted to a different revision of the template"]);
+    }
+
+    #[test]
+    fn test_field_constraints_are_validated() {
+        let invalid = |block: Value| ContentBlock::validate(&serde_json::from_value(block).unwrap()).unwrap_err();
+        assert!(invalid(json!({ "type": "text_field", "id": "a", "label": "A", "pattern": "[" })).contains("invalid pattern"));
+        assert!(invalid(json!({ "type": "number_field", "id": "b", "label": "B", "min": 5, "max": 1 })).contains("above its maximum"));
+        assert!(invalid(json!({ "type": "number_field", "id": "c", "label": "C", "currency": "XXY" })).contains("unknown currency"));
+        assert!(invalid(json!({ "type": "choice", "id": "d", "label": "D", "options": [] })).contains("has no options"));
+        assert!(invalid(json!({ "type": "choice", "id": "e", "label": "E", "options": [{ "value": "x" }, { "value": "x" }] }))
+            .contains("repeated option value 'x'"));
+        assert!(invalid(json!({ "type": "checkbox", "id": "f", "label": " " })).contains("checkbox 'f' has no label"));
+    }
+}
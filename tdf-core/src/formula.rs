@@ -0,0 +1,894 @@
+//! Formula cells
+//!
+//! A formula cell stores an expression over other cells of the same table
+//! next to the value it was computed to (`raw`) and the text shown for it
+//! (`display`). Expressions are numbers, `+ - * /`, parentheses, cell
+//! references (`B3`) and ranges (`B1:B12`) used as arguments of SUM, AVG
+//! (or AVERAGE), MIN, MAX, COUNT, ROUND and ABS.
+//!
+//! References are A1-style: the letter is the column's position in the
+//! table and the number is the data row, counting from 1 (header rows are
+//! not counted). Referenced formula cells are evaluated in turn, never
+//! taken from their stored `raw`, so a chain of totals is checked end to
+//! end.
+//!
+//! The evaluator is sandboxed and deterministic: it has no I/O, clock or
+//! randomness, computes in exact decimals (`crate::decimal`; quotients are
+//! rounded to 18 places), and bounds the formula length, nesting depth,
+//! range size and reference chain length. Every reader therefore computes
+//! the same value, and `verify_formulas` flags any formula whose stored
+//! `raw` or `display` disagrees with it.
+
+use crate::content::{CellValue, ContentBlock, DocumentContent, TableColumn, TableRow};
+use crate::decimal::Decimal;
+use crate::error::{TdfError, TdfResult};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Longest accepted formula, in bytes
+pub const MAX_FORMULA_LEN: usize = 1024;
+/// Deepest accepted nesting of parentheses, calls and operators
+pub const MAX_FORMULA_DEPTH: usize = 32;
+/// Most cells a single range may cover
+pub const MAX_RANGE_CELLS: usize = 100_000;
+/// Longest chain of formulas referencing formulas
+pub const MAX_REFERENCE_DEPTH: usize = 64;
+/// Most decimal places (or, negative, whole-number places) ROUND accepts
+pub const MAX_ROUND_DIGITS: i64 = 18;
+
+/// Cell position, zero-based
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellRef {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut letters = Vec::new();
+        let mut column = self.column + 1;
+        while column > 0 {
+            letters.push(b'A' + ((column - 1) % 26) as u8);
+            column = (column - 1) / 26;
+        }
+        letters.reverse();
+        write!(f, "{}{}", String::from_utf8_lossy(&letters), self.row + 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Sum,
+    Avg,
+    Min,
+    Max,
+    Count,
+    Round,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "SUM" => Some(Function::Sum),
+            "AVG" | "AVERAGE" => Some(Function::Avg),
+            "MIN" => Some(Function::Min),
+            "MAX" => Some(Function::Max),
+            "COUNT" => Some(Function::Count),
+            "ROUND" => Some(Function::Round),
+            "ABS" => Some(Function::Abs),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Function::Sum => "SUM",
+            Function::Avg => "AVG",
+            Function::Min => "MIN",
+            Function::Max => "MAX",
+            Function::Count => "COUNT",
+            Function::Round => "ROUND",
+            Function::Abs => "ABS",
+        }
+    }
+}
+
+/// Parsed formula
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(Decimal),
+    Cell(CellRef),
+    /// First and last cell of a rectangular range
+    Range(CellRef, CellRef),
+    Negate(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    /// The same expression with every reference moved by `map`, or `None`
+    /// if `map` rejects one of them
+    pub fn rebase(&self, map: &impl Fn(CellRef) -> Option<CellRef>) -> Option<Expr> {
+        Some(match self {
+            Expr::Number(n) => Expr::Number(*n),
+            Expr::Cell(cell) => Expr::Cell(map(*cell)?),
+            Expr::Range(first, last) => Expr::Range(map(*first)?, map(*last)?),
+            Expr::Negate(inner) => Expr::Negate(Box::new(inner.rebase(map)?)),
+            Expr::Binary(op, a, b) => Expr::Binary(*op, Box::new(a.rebase(map)?), Box::new(b.rebase(map)?)),
+            Expr::Call(function, args) => {
+                Expr::Call(*function, args.iter().map(|arg| arg.rebase(map)).collect::<Option<_>>()?)
+            }
+        })
+    }
+
+    /// Every cell referenced, directly or as a range corner
+    pub fn references(&self) -> Vec<CellRef> {
+        match self {
+            Expr::Number(_) => Vec::new(),
+            Expr::Cell(cell) => vec![*cell],
+            Expr::Range(first, last) => vec![*first, *last],
+            Expr::Negate(inner) => inner.references(),
+            Expr::Binary(_, a, b) => [a.references(), b.references()].concat(),
+            Expr::Call(_, args) => args.iter().flat_map(Expr::references).collect(),
+        }
+    }
+}
+
+/// Canonical text of the expression, which parses back to the same value
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Cell(cell) => write!(f, "{}", cell),
+            Expr::Range(first, last) => write!(f, "{}:{}", first, last),
+            Expr::Negate(inner) => match **inner {
+                Expr::Binary(..) => write!(f, "-({})", inner),
+                _ => write!(f, "-{}", inner),
+            },
+            Expr::Binary(op, a, b) => {
+                // Operators are left-associative, so a right operand of the
+                // same precedence keeps its parentheses
+                let precedence = |e: &Expr| match e {
+                    Expr::Binary(Operator::Add | Operator::Subtract, ..) => 1,
+                    Expr::Binary(..) => 2,
+                    _ => 3,
+                };
+                let this = precedence(self);
+                if precedence(a) < this {
+                    write!(f, "({})", a)?;
+                } else {
+                    write!(f, "{}", a)?;
+                }
+                let symbol = match op {
+                    Operator::Add => "+",
+                    Operator::Subtract => "-",
+                    Operator::Multiply => "*",
+                    Operator::Divide => "/",
+                };
+                if precedence(b) <= this {
+                    write!(f, " {} ({})", symbol, b)
+                } else {
+                    write!(f, " {} {}", symbol, b)
+                }
+            }
+            Expr::Call(function, args) => {
+                write!(f, "{}(", function.name())?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn formula_error(message: impl Into<String>) -> TdfError {
+    TdfError::FormulaError(message.into())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Decimal),
+    Cell(CellRef),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(formula: &str) -> TdfResult<Vec<Token>> {
+    let chars: Vec<char> = formula.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text.parse().map_err(|_| formula_error(format!("invalid number '{}'", text)))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_ascii_alphabetic() || c == '$' {
+            // `$` marks absolute references in spreadsheets; positions here
+            // are always absolute
+            let start = i;
+            let mut letters = String::new();
+            while i < chars.len() && (chars[i].is_ascii_alphabetic() || chars[i] == '$') {
+                if chars[i] != '$' {
+                    letters.push(chars[i]);
+                }
+                i += 1;
+            }
+            let digits_start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[digits_start..i].iter().collect();
+            if digits.is_empty() {
+                if letters.len() != i - start {
+                    return Err(formula_error(format!("invalid name '{}'", chars[start..i].iter().collect::<String>())));
+                }
+                tokens.push(Token::Name(letters));
+            } else {
+                tokens.push(Token::Cell(cell_ref(&letters, &digits)?));
+            }
+        } else if "+-*/():,".contains(c) {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        } else {
+            return Err(formula_error(format!("unexpected character '{}'", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Position of an A1-style reference from its column letters and row digits
+fn cell_ref(letters: &str, digits: &str) -> TdfResult<CellRef> {
+    let invalid = || formula_error(format!("invalid cell reference '{}{}'", letters, digits));
+    if letters.len() > 3 {
+        return Err(invalid());
+    }
+    let column = letters
+        .bytes()
+        .fold(0usize, |n, b| n * 26 + (b.to_ascii_uppercase() - b'A') as usize + 1);
+    let row: usize = digits.parse().map_err(|_| invalid())?;
+    if row == 0 {
+        return Err(invalid());
+    }
+    Ok(CellRef { row: row - 1, column: column - 1 })
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> TdfResult<()> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(formula_error(format!("expected '{}'", symbol)))
+        }
+    }
+
+    fn descend(&mut self) -> TdfResult<()> {
+        self.depth += 1;
+        if self.depth > MAX_FORMULA_DEPTH {
+            return Err(formula_error(format!("nested deeper than {}", MAX_FORMULA_DEPTH)));
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self) -> TdfResult<Expr> {
+        self.descend()?;
+        let mut expr = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                Operator::Add
+            } else if self.eat('-') {
+                Operator::Subtract
+            } else {
+                break;
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> TdfResult<Expr> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                Operator::Multiply
+            } else if self.eat('/') {
+                Operator::Divide
+            } else {
+                break;
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> TdfResult<Expr> {
+        if self.eat('-') {
+            self.descend()?;
+            let inner = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expr::Negate(Box::new(inner)));
+        }
+        if self.eat('+') {
+            self.descend()?;
+            let inner = self.unary()?;
+            self.depth -= 1;
+            return Ok(inner);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> TdfResult<Expr> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Cell(first)) => {
+                if self.eat(':') {
+                    match self.next() {
+                        Some(Token::Cell(last)) => Ok(Expr::Range(first, last)),
+                        _ => Err(formula_error(format!("range from {} has no end cell", first))),
+                    }
+                } else {
+                    Ok(Expr::Cell(first))
+                }
+            }
+            Some(Token::Name(name)) => {
+                let function = Function::from_name(&name).ok_or_else(|| formula_error(format!("unknown function '{}'", name)))?;
+                self.expect('(')?;
+                let mut args = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.expression()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                check_arity(function, args.len())?;
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Symbol('(')) => {
+                let expr = self.expression()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(Token::Symbol(c)) => Err(formula_error(format!("unexpected '{}'", c))),
+            None => Err(formula_error("unexpected end of formula")),
+        }
+    }
+}
+
+fn check_arity(function: Function, count: usize) -> TdfResult<()> {
+    let valid = match function {
+        Function::Round => (1..=2).contains(&count),
+        Function::Abs => count == 1,
+        _ => count >= 1,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(formula_error(format!("{} takes {} argument(s), got {}", function.name(), arity(function), count)))
+    }
+}
+
+fn arity(function: Function) -> &'static str {
+    match function {
+        Function::Round => "1 or 2",
+        Function::Abs => "1",
+        _ => "at least 1",
+    }
+}
+
+/// Parse a formula; a leading `=` is allowed
+pub fn parse(formula: &str) -> TdfResult<Expr> {
+    if formula.len() > MAX_FORMULA_LEN {
+        return Err(formula_error(format!("longer than {} bytes", MAX_FORMULA_LEN)));
+    }
+    let text = formula.trim();
+    let text = text.strip_prefix('=').unwrap_or(text);
+    let mut parser = Parser { tokens: tokenize(text)?, position: 0, depth: 0 };
+    let expr = parser.expression()?;
+    if let Some(token) = parser.peek() {
+        return Err(formula_error(format!("unexpected {:?} after the formula", token)));
+    }
+    Ok(expr)
+}
+
+/// Why a formula could not be evaluated
+#[derive(Debug, Clone, PartialEq)]
+enum Failure {
+    Error(String),
+    /// A referenced cell is redacted, so the value cannot be recomputed
+    Redacted(CellRef),
+}
+
+/// Evaluates the formula cells of one table, caching each cell's value
+struct Evaluator<'a> {
+    columns: &'a [TableColumn],
+    rows: &'a [TableRow],
+    values: HashMap<CellRef, Result<Decimal, Failure>>,
+    visiting: HashSet<CellRef>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(columns: &'a [TableColumn], rows: &'a [TableRow]) -> Self {
+        Evaluator { columns, rows, values: HashMap::new(), visiting: HashSet::new() }
+    }
+
+    fn cell(&self, at: CellRef) -> Result<Option<&'a CellValue>, Failure> {
+        let column = self
+            .columns
+            .get(at.column)
+            .ok_or_else(|| Failure::Error(format!("{} is outside the table", at)))?;
+        let row = self.rows.get(at.row).ok_or_else(|| Failure::Error(format!("{} is outside the table", at)))?;
+        Ok(row.cells.get(&column.id))
+    }
+
+    /// Value of a formula cell, evaluating it once
+    fn formula_value(&mut self, at: CellRef, formula: &str) -> Result<Decimal, Failure> {
+        if let Some(value) = self.values.get(&at) {
+            return value.clone();
+        }
+        if !self.visiting.insert(at) {
+            return Err(Failure::Error(format!("circular reference through {}", at)));
+        }
+        let value = if self.visiting.len() > MAX_REFERENCE_DEPTH {
+            Err(Failure::Error(format!("formulas reference each other deeper than {}", MAX_REFERENCE_DEPTH)))
+        } else {
+            parse(formula)
+                .map_err(|e| Failure::Error(format!("{}: {}", at, e)))
+                .and_then(|expr| self.evaluate(&expr))
+        };
+        self.visiting.remove(&at);
+        self.values.insert(at, value.clone());
+        value
+    }
+
+    /// Numeric value of a referenced cell; `None` for empty and text cells
+    fn number(&mut self, at: CellRef) -> Result<Option<Decimal>, Failure> {
+        match self.cell(at)? {
+            None => Ok(None),
+            Some(CellValue::Number { raw, .. } | CellValue::Currency { raw, .. } | CellValue::Percentage { raw, .. }) => {
+                Ok(Some(*raw))
+            }
+            Some(CellValue::Formula { formula, .. }) => self.formula_value(at, formula).map(Some),
+            Some(CellValue::Redacted { .. }) => Err(Failure::Redacted(at)),
+            Some(CellValue::Text(_) | CellValue::Date { .. }) => Ok(None),
+        }
+    }
+
+    /// A reference used in arithmetic: empty cells count as zero, text is
+    /// an error
+    fn scalar(&mut self, at: CellRef) -> Result<Decimal, Failure> {
+        match (self.number(at)?, self.cell(at)?) {
+            (Some(value), _) => Ok(value),
+            (None, None) => Ok(Decimal::ZERO),
+            (None, Some(_)) => Err(Failure::Error(format!("{} is not a number", at))),
+        }
+    }
+
+    /// Numbers of a function argument; ranges skip empty and text cells
+    fn values(&mut self, arg: &Expr) -> Result<Vec<Decimal>, Failure> {
+        let Expr::Range(first, last) = arg else { return Ok(vec![self.evaluate(arg)?]) };
+        let (top, bottom) = (first.row.min(last.row), first.row.max(last.row));
+        let (left, right) = (first.column.min(last.column), first.column.max(last.column));
+        if (bottom - top + 1).saturating_mul(right - left + 1) > MAX_RANGE_CELLS {
+            return Err(Failure::Error(format!("range {}:{} covers more than {} cells", first, last, MAX_RANGE_CELLS)));
+        }
+        let mut values = Vec::new();
+        for row in top..=bottom {
+            for column in left..=right {
+                if let Some(value) = self.number(CellRef { row, column })? {
+                    values.push(value);
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Decimal, Failure> {
+        let value = match expr {
+            Expr::Number(n) => Some(*n),
+            Expr::Cell(at) => Some(self.scalar(*at)?),
+            Expr::Range(first, last) => {
+                return Err(Failure::Error(format!("range {}:{} used outside a function", first, last)))
+            }
+            Expr::Negate(inner) => self.evaluate(inner)?.checked_neg(),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (self.evaluate(a)?, self.evaluate(b)?);
+                match op {
+                    Operator::Add => a.checked_add(&b),
+                    Operator::Subtract => a.checked_sub(&b),
+                    Operator::Multiply => a.checked_mul(&b),
+                    Operator::Divide if b.is_zero() => return Err(Failure::Error("division by zero".to_string())),
+                    Operator::Divide => a.checked_div(&b),
+                }
+            }
+            Expr::Call(function, args) => return self.call(*function, args),
+        };
+        value.ok_or_else(overflow)
+    }
+
+    fn call(&mut self, function: Function, args: &[Expr]) -> Result<Decimal, Failure> {
+        match function {
+            Function::Round => {
+                let value = self.evaluate(&args[0])?;
+                let digits = match args.get(1) {
+                    Some(arg) => self.evaluate(arg)?,
+                    None => Decimal::ZERO,
+                };
+                if !digits.is_integer() || digits.abs() > Decimal::from(MAX_ROUND_DIGITS) {
+                    return Err(Failure::Error(format!(
+                        "ROUND digits must be a whole number up to {}, got {}",
+                        MAX_ROUND_DIGITS, digits
+                    )));
+                }
+                let digits = digits.with_scale(0).map_or(0, |d| d.units() as i32);
+                value.round(digits).ok_or_else(overflow)
+            }
+            Function::Abs => Ok(self.evaluate(&args[0])?.abs()),
+            _ => {
+                let mut values = Vec::new();
+                for arg in args {
+                    values.extend(self.values(arg)?);
+                }
+                let sum = || values.iter().try_fold(Decimal::ZERO, |sum, v| sum.checked_add(v)).ok_or_else(overflow);
+                match function {
+                    Function::Sum => sum(),
+                    Function::Count => Ok(Decimal::from(values.len() as i64)),
+                    _ if values.is_empty() => Err(Failure::Error(format!("{} of no numbers", function.name()))),
+                    Function::Avg => sum()?.checked_div(&Decimal::from(values.len() as i64)).ok_or_else(overflow),
+                    Function::Min => Ok(values.iter().copied().min().unwrap_or(Decimal::ZERO)),
+                    _ => Ok(values.iter().copied().max().unwrap_or(Decimal::ZERO)),
+                }
+            }
+        }
+    }
+}
+
+fn overflow() -> Failure {
+    Failure::Error(format!("result has more than {} digits", crate::decimal::MAX_DIGITS))
+}
+
+/// Evaluate `formula` against the cells of a table
+pub fn evaluate(formula: &str, columns: &[TableColumn], rows: &[TableRow]) -> TdfResult<Decimal> {
+    let expr = parse(formula)?;
+    Evaluator::new(columns, rows).evaluate(&expr).map_err(|failure| match failure {
+        Failure::Error(message) => formula_error(message),
+        Failure::Redacted(at) => formula_error(format!("{} is redacted", at)),
+    })
+}
+
+/// Check that every reference of `formula` lies inside a table of
+/// `columns` x `rows` cells
+pub fn check_references(formula: &str, columns: usize, rows: usize) -> TdfResult<()> {
+    for at in parse(formula)?.references() {
+        if at.column >= columns || at.row >= rows {
+            return Err(formula_error(format!("{} is outside the table", at)));
+        }
+    }
+    Ok(())
+}
+
+/// Result of re-computing one formula cell
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum FormulaOutcome {
+    /// Stored `raw` and `display` agree with the computed value
+    Matches,
+    /// Stored `raw` or `display` differs from the computed value
+    Mismatch { computed: Decimal },
+    /// The formula cannot be evaluated
+    Error { message: String },
+    /// A referenced cell is redacted, so the formula cannot be re-computed
+    Unverifiable { message: String },
+}
+
+/// A formula cell and the result of re-computing it
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FormulaCheck {
+    /// Table block id
+    pub table: String,
+    /// Row index, zero-based as in redaction targets
+    pub row: usize,
+    /// Column id
+    pub column: String,
+    pub formula: String,
+    pub display: String,
+    #[serde(flatten)]
+    pub outcome: FormulaOutcome,
+}
+
+impl FormulaCheck {
+    /// Cell key `<table>/<row>/<column>`
+    pub fn key(&self) -> String {
+        format!("{}/{}/{}", self.table, self.row, self.column)
+    }
+
+    /// Mismatches and errors fail verification; unverifiable formulas do not
+    pub fn failed(&self) -> bool {
+        matches!(self.outcome, FormulaOutcome::Mismatch { .. } | FormulaOutcome::Error { .. })
+    }
+}
+
+/// Re-compute every formula cell of one table
+pub fn check_table(table: &str, columns: &[TableColumn], rows: &[TableRow]) -> Vec<FormulaCheck> {
+    let mut evaluator = Evaluator::new(columns, rows);
+    let mut checks = Vec::new();
+    for (r, row) in rows.iter().enumerate() {
+        for (c, column) in columns.iter().enumerate() {
+            let Some(CellValue::Formula { formula, raw, display }) = row.cells.get(&column.id) else { continue };
+            let outcome = match evaluator.formula_value(CellRef { row: r, column: c }, formula) {
+                Ok(computed) if same_value(raw, &computed) && display_matches(display, &computed) => FormulaOutcome::Matches,
+                Ok(computed) => FormulaOutcome::Mismatch { computed },
+                Err(Failure::Error(message)) => FormulaOutcome::Error { message },
+                Err(Failure::Redacted(at)) => FormulaOutcome::Unverifiable { message: format!("{} is redacted", at) },
+            };
+            checks.push(FormulaCheck {
+                table: table.to_string(),
+                row: r,
+                column: column.id.clone(),
+                formula: formula.clone(),
+                display: display.clone(),
+                outcome,
+            });
+        }
+    }
+    checks
+}
+
+/// Re-compute every formula cell of every table in the document
+pub fn verify_formulas(content: &DocumentContent) -> Vec<FormulaCheck> {
+    content
+        .sections
+        .iter()
+        .flat_map(|section| &section.content)
+        .filter_map(|block| match block {
+            ContentBlock::Table { id, columns, rows, .. } => Some(check_table(id, columns, rows)),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Whether a stored `raw` is the computed value, rounded to the places
+/// `raw` is written with
+fn same_value(stored: &Decimal, computed: &Decimal) -> bool {
+    computed.round(stored.scale() as i32).is_some_and(|rounded| rounded == *stored)
+}
+
+/// Whether `display` shows `value` at the precision it is written with
+///
+/// Currency symbols, codes and spaces are ignored; `(1,234)` and a minus
+/// sign are negative; a trailing `%` divides by 100; a `K`, `M` or `B`
+/// right after the digits scales by thousands, millions or billions. When
+/// a display has both `,` and `.` the later one is the decimal separator;
+/// a lone `,` is a decimal separator unless three digits follow it.
+pub fn display_matches(display: &str, value: &Decimal) -> bool {
+    let Some((shown, exponent)) = display_number(display) else { return false };
+    // `shown` is the value times 10^exponent, at the places it is written with
+    value.shift(exponent).and_then(|scaled| scaled.round(shown.scale() as i32)).is_some_and(|scaled| scaled == shown)
+}
+
+/// Number shown by a display string, without its scale, and the power of
+/// ten the value is multiplied by to get it (2 for `%`, -3 for `K`, ...)
+fn display_number(display: &str) -> Option<(Decimal, i32)> {
+    let text = display.trim();
+    let first = text.find(|c: char| c.is_ascii_digit())?;
+    let last = text.rfind(|c: char| c.is_ascii_digit())?;
+    let digits: String = text[first..=last].chars().filter(|c| c.is_ascii_digit() || *c == '.' || *c == ',').collect();
+    if text[first..=last].chars().any(|c| !(c.is_ascii_digit() || ".,' \u{a0}\u{202f}".contains(c))) {
+        return None;
+    }
+
+    let decimal = match (digits.rfind('.'), digits.rfind(',')) {
+        (Some(dot), Some(comma)) => Some(dot.max(comma)),
+        (Some(dot), None) if digits.matches('.').count() == 1 => Some(dot),
+        (None, Some(comma)) if digits.matches(',').count() == 1 && digits.len() - comma - 1 != 3 => Some(comma),
+        _ => None,
+    };
+    let (whole, fraction) = match decimal {
+        Some(at) => (&digits[..at], &digits[at + 1..]),
+        None => (digits.as_str(), ""),
+    };
+    let whole: String = whole.chars().filter(char::is_ascii_digit).collect();
+    let (before, after) = (&text[..first], &text[last + 1..]);
+    let negative = before.contains(['-', '\u{2212}']) || (before.contains('(') && after.contains(')'));
+    let sign = if negative { "-" } else { "" };
+    let number: Decimal = format!("{}{}.{}", sign, if whole.is_empty() { "0" } else { &whole }, fraction).parse().ok()?;
+
+    let mut exponent = match after.chars().next() {
+        Some('K' | 'k') => -3,
+        Some('M') => -6,
+        Some('B') => -9,
+        _ => 0,
+    };
+    if after.contains('%') {
+        exponent += 2;
+    }
+    Some((number, exponent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::CellType;
+
+    fn table(values: &[[&str; 2]]) -> (Vec<TableColumn>, Vec<TableRow>) {
+        let columns: Vec<TableColumn> = ["item", "amount"]
+            .iter()
+            .map(|id| TableColumn { id: id.to_string(), header: id.to_string(), cell_type: CellType::Number, currency: None })
+            .collect();
+        let rows = values
+            .iter()
+            .map(|[item, amount]| {
+                let mut cells = HashMap::from([("item".to_string(), CellValue::Text(item.to_string()))]);
+                let cell = match amount.strip_prefix('=') {
+                    Some(formula) => CellValue::Formula { formula: formula.to_string(), raw: Decimal::ZERO, display: String::new() },
+                    None => CellValue::Number { raw: amount.parse().unwrap(), display: amount.to_string() },
+                };
+                cells.insert("amount".to_string(), cell);
+                TableRow::new(cells)
+            })
+            .collect();
+        (columns, rows)
+    }
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_print() {
+        for formula in ["SUM(B1:B3)", "B1 + B2 * 2", "(B1 + B2) * 2", "B1 - (B2 - B3)", "ROUND(AVG(B1:B3) / 3, 2)", "-(B1 + 2)"] {
+            let expr = parse(formula).unwrap();
+            assert_eq!(expr.to_string(), formula);
+            assert_eq!(parse(&expr.to_string()).unwrap(), expr);
+        }
+        assert_eq!(parse("=sum($B$1:b2)").unwrap().to_string(), "SUM(B1:B2)");
+        assert_eq!(CellRef { row: 0, column: 27 }.to_string(), "AB1");
+
+        for bad in ["SUM(B1:B3", "B0", "NOW()", "B1 +", "1..2", "ROUND()", "ABS(1, 2)", "B1;B2", "B1:", "A$"] {
+            assert!(matches!(parse(bad), Err(TdfError::FormulaError(_))), "{}", bad);
+        }
+        let deep = format!("{}1{}", "(".repeat(40), ")".repeat(40));
+        assert!(parse(&deep).is_err());
+        assert!(parse(&"1+".repeat(600)).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_functions_and_references() {
+        let (columns, rows) = table(&[["a", "10"], ["b", "20"], ["c", "30.5"], ["sum", "=SUM(B1:B3)"], ["x", "=B4 * 2"]]);
+        let eval = |f: &str| evaluate(f, &columns, &rows);
+        assert_eq!(eval("SUM(B1:B3)").unwrap(), d("60.5"));
+        assert_eq!(eval("B5").unwrap(), d("121"));
+        assert_eq!(eval("AVERAGE(B1:B2)").unwrap(), d("15"));
+        assert_eq!(eval("MIN(B1:B3, 5)").unwrap(), d("5"));
+        assert_eq!(eval("MAX(B1:B3)").unwrap(), d("30.5"));
+        // Text cells in ranges are skipped
+        assert_eq!(eval("COUNT(A1:B3)").unwrap(), d("3"));
+        assert_eq!(eval("ROUND(2.675, 2)").unwrap(), d("2.68"));
+        assert_eq!(eval("ROUND(-2.5)").unwrap(), d("-3"));
+        assert_eq!(eval("ROUND(1234, -2)").unwrap(), d("1200"));
+        assert_eq!(eval("ABS(B1 - B2)").unwrap(), d("10"));
+        assert_eq!(eval("-B1 + 3 * (2 - 1) / 4").unwrap(), d("-9.25"));
+
+        assert!(eval("B1 / (B2 - 20)").is_err());
+        assert!(eval("A1 + 1").is_err());
+        assert!(eval("B9").is_err());
+        assert!(eval("B1:B3").is_err());
+        assert!(eval("ROUND(1, 0.5)").is_err());
+        // Exact decimals: no binary rounding residue
+        assert_eq!(eval("0.1 + 0.2").unwrap().to_string(), "0.3");
+        assert_eq!(eval("B1 / 3").unwrap().to_string(), "3.333333333333333333");
+        assert!(eval("AVG(A1:A3)").is_err());
+    }
+
+    #[test]
+    fn test_circular_and_deep_references() {
+        let (columns, rows) = table(&[["a", "=B2"], ["b", "=B1 + 1"]]);
+        assert!(matches!(&check_table("t", &columns, &rows)[0].outcome, FormulaOutcome::Error { message } if message.contains("circular")));
+
+        let chain = |length: usize| {
+            let mut rows: Vec<[String; 2]> = (0..length).map(|i| ["x".to_string(), format!("=B{} + 1", i + 2)]).collect();
+            rows.push(["end".to_string(), "0".to_string()]);
+            rows
+        };
+        for (length, ok) in [(MAX_REFERENCE_DEPTH, true), (MAX_REFERENCE_DEPTH + 1, false)] {
+            let rows = chain(length);
+            let rows: Vec<[&str; 2]> = rows.iter().map(|[a, b]| [a.as_str(), b.as_str()]).collect();
+            let (columns, rows) = table(&rows);
+            assert_eq!(evaluate("B1", &columns, &rows).is_ok(), ok, "{}", length);
+        }
+    }
+
+    #[test]
+    fn test_check_table_flags_stored_values() {
+        let (columns, mut rows) = table(&[["a", "10"], ["b", "20"], ["total", "=SUM(B1:B2)"]]);
+        let mut set = |raw: &str, display: &str| {
+            rows[2].cells.insert(
+                "amount".to_string(),
+                CellValue::Formula { formula: "SUM(B1:B2)".to_string(), raw: d(raw), display: display.to_string() },
+            );
+            check_table("t", &columns, &rows).remove(0)
+        };
+        assert_eq!(set("30", "30").outcome, FormulaOutcome::Matches);
+        assert_eq!(set("30.00", "€30.00").outcome, FormulaOutcome::Matches);
+        assert_eq!(set("30", "31").outcome, FormulaOutcome::Mismatch { computed: d("30") });
+        assert_eq!(set("31", "30").outcome, FormulaOutcome::Mismatch { computed: d("30") });
+        assert_eq!(set("30.0000001", "30").outcome, FormulaOutcome::Mismatch { computed: d("30") });
+        let check = set("30", "30");
+        assert_eq!(check.key(), "t/2/amount");
+        assert!(!check.failed());
+
+        rows[0].cells.insert("amount".to_string(), CellValue::Redacted { redacted: "00".repeat(32) });
+        let check = check_table("t", &columns, &rows).remove(0);
+        assert!(matches!(check.outcome, FormulaOutcome::Unverifiable { .. }));
+        assert!(!check.failed());
+    }
+
+    #[test]
+    fn test_display_matches() {
+        assert!(display_matches("€1,024,000", &d("1024000")));
+        assert!(display_matches("1.024.000,50 €", &d("1024000.5")));
+        assert!(display_matches("EUR 860,000.00", &d("860000")));
+        assert!(display_matches("(1,250)", &d("-1250")));
+        assert!(display_matches("-3.5", &d("-3.5")));
+        assert!(display_matches("+14.9%", &d("0.1487")));
+        assert!(display_matches("€542K", &d("541900")));
+        assert!(display_matches("1.2M", &d("1234567")));
+        assert!(display_matches("12,5", &d("12.5")));
+        assert!(display_matches("0.33", &d("1").checked_div(&d("3")).unwrap()));
+
+        assert!(!display_matches("€1,025,000", &d("1024000")));
+        assert!(!display_matches("-3.5", &d("3.5")));
+        assert!(!display_matches("14.9%", &d("14.9")));
+        assert!(!display_matches("n/a", &d("0")));
+        assert!(!display_matches("12 or 13", &d("12")));
+    }
+}
@@ -0,0 +1,795 @@
+//! HTML rendering of documents
+//!
+//! Produces a standalone page with every asset inlined as a data URI, so the
+//! file can be opened offline. Class names follow the TypeScript viewer
+//! (`document-section`, `tdf-table`, `footnote`, ...) so a document's
+//! `styles.css` applies to both; it is included after `DEFAULT_STYLES`.
+//! Footnotes referenced with `{{fn:id}}` markers or footnote reference runs
+//! are numbered in reading order and listed at the end; unreferenced
+//! footnotes stay where they are. Formatting marks become phrase elements
+//! (`strong`, `em`, `code`, `sup`, ...) and only safe links are rendered.
+//! Task list items get disabled checkboxes and page breaks become an empty
+//! `page-break` element that starts a new page when printed. Merged table
+//! cells get `colspan`/`rowspan`, subtotal and total rows a `subtotal` or
+//! `total` class and outlined rows a `level-N` class on their first cell.
+//! Diagrams and charts are drawn as inline SVG by `crate::diagram` and
+//! `crate::chart`. Form fields are drawn empty and read-only with a hint of
+//! what they accept; their values belong to submissions (see `crate::form`).
+//!
+//! `render_body` is deterministic for a given document and assets, which
+//! lets a self-verifying page re-render its body from the embedded archive
+//! and compare it with what it displays.
+
+use crate::content::{
+    covered_cells, header_layout, CellType, CellValue, ContentBlock, DocumentContent, HeaderCell, InlineRun,
+    InlineText, ListItem, Mark, RowRole, Section, TableColumn, TableRow,
+};
+use crate::chart::{self, ChartData};
+use crate::diagram;
+use crate::form::{FieldKind, FormField};
+use crate::document::Document;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Styles applied before the document's own `styles.css`
+pub const DEFAULT_STYLES: &str = "\
+body { font-family: Helvetica, Arial, sans-serif; line-height: 1.5; color: #222; max-width: 52em; margin: 2em auto; padding: 0 1em; }
+.document-title { font-size: 24pt; font-weight: bold; margin-bottom: 0.5em; }
+.document-meta { margin-bottom: 1em; color: #666; font-size: 0.9em; }
+.document-meta p { margin: 0.2em 0; }
+.document-authors { margin-bottom: 1em; }
+.document-section { margin-bottom: 2em; }
+.tdf-table { border-collapse: collapse; width: 100%; margin: 1em 0; }
+.tdf-table caption { font-weight: bold; text-align: left; margin-bottom: 0.3em; }
+.tdf-table th, .tdf-table td { padding: 6px 8px; border: 1px solid #ddd; vertical-align: top; }
+.tdf-table th { background-color: #f5f5f5; font-weight: bold; text-align: left; }
+.tdf-table td.numeric { text-align: right; }
+.tdf-table th[colspan] { text-align: center; }
+.tdf-table tr.subtotal td { font-weight: bold; }
+.tdf-table tr.total td { font-weight: bold; border-top: 2px solid #444; }
+.tdf-table td.level-1 { padding-left: 1.5em; } .tdf-table td.level-2 { padding-left: 2.5em; }
+.tdf-table td.level-3 { padding-left: 3.5em; } .tdf-table td.level-4 { padding-left: 4.5em; }
+.tdf-table td.level-5 { padding-left: 5.5em; } .tdf-table td.level-6 { padding-left: 6.5em; }
+.tdf-table td.level-7 { padding-left: 7.5em; }
+.tdf-table tfoot td { font-weight: bold; background-color: #fafafa; }
+figure { margin: 1em 0; text-align: center; }
+figure img { max-width: 100%; }
+figcaption { font-style: italic; font-size: 0.9em; color: #555; }
+.diagram-container { border: 1px solid #ddd; padding: 0.5em 1em; margin: 1em 0; }
+.diagram-container svg, .chart-container svg { max-width: 100%; height: auto; }
+.chart-error { color: #c62828; font-style: italic; }
+.footnote { font-size: 0.9em; color: #666; margin-top: 0.5em; }
+.footnotes { border-top: 1px solid #ccc; margin-top: 2em; font-size: 0.9em; color: #444; }
+.redacted-block, .redacted-cell { background: #e0e0e0; color: #555; font-style: italic; }
+.redacted-block { padding: 0.3em 0.6em; margin: 0.5em 0; }
+.encrypted-section { color: #666; font-style: italic; }
+blockquote { margin: 1em 0; padding: 0 1em; border-left: 3px solid #ccc; color: #444; }
+blockquote footer { font-size: 0.9em; color: #666; }
+pre { background: #f6f6f6; padding: 0.6em 0.8em; overflow-x: auto; }
+li.task { list-style: none; }
+.form-field { margin: 0.8em 0; }
+.form-field .field-label { display: block; font-weight: bold; }
+.form-field .field-required { color: #c62828; margin-left: 0.2em; }
+.form-field .field-input { display: block; min-height: 1.6em; border-bottom: 1px solid #888; }
+.form-field .field-input.multiline { min-height: 4.8em; border: 1px solid #888; }
+.form-field .field-input.signature { min-height: 3em; }
+.form-field .field-hint, .form-field .field-help { display: block; font-size: 0.85em; color: #666; }
+.page-break { break-after: page; }
+";
+
+/// Escape text for use in HTML content and double-quoted attributes
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Media type of an asset, from its file extension
+fn media_type(name: &str) -> &'static str {
+    let extension = name.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Footnote texts and the numbers assigned to them in reading order
+#[derive(Debug, Default)]
+struct Footnotes {
+    text: HashMap<String, String>,
+    referenced: HashSet<String>,
+    /// Footnote ids in the order they were first referenced
+    order: Vec<String>,
+}
+
+impl Footnotes {
+    fn collect(content: &DocumentContent) -> Self {
+        let mut notes = Footnotes::default();
+        let blocks = || content.sections.iter().flat_map(|s| s.content.iter());
+        for block in blocks() {
+            if let ContentBlock::Footnote { id, text } = block {
+                notes.text.insert(id.clone(), text.clone());
+            }
+        }
+        for block in blocks() {
+            let texts: Vec<&InlineText> = match block {
+                ContentBlock::Heading { text, .. } | ContentBlock::Paragraph { text, .. } => vec![text],
+                ContentBlock::List { items, .. } => items.iter().flat_map(ListItem::texts).collect(),
+                ContentBlock::Quote { text, .. } => vec![text],
+                _ => continue,
+            };
+            for run in texts.into_iter().flat_map(InlineText::runs) {
+                let ids: Vec<String> = match &run {
+                    InlineRun::Text { text, .. } => notes.markers(text).into_iter().map(|(_, _, id)| id.to_string()).collect(),
+                    InlineRun::FootnoteRef { id } if notes.text.contains_key(id) => vec![id.clone()],
+                    _ => Vec::new(),
+                };
+                notes.referenced.extend(ids);
+            }
+        }
+        notes
+    }
+
+    /// Number of a referenced footnote, assigned on first use
+    fn number(&mut self, id: &str) -> usize {
+        match self.order.iter().position(|n| n == id) {
+            Some(i) => i + 1,
+            None => {
+                self.order.push(id.to_string());
+                self.order.len()
+            }
+        }
+    }
+
+    /// `{{id}}` markers naming a known footnote, as (start, end, id)
+    fn markers<'t>(&self, text: &'t str) -> Vec<(usize, usize, &'t str)> {
+        let mut found = Vec::new();
+        let mut from = 0;
+        while let Some(start) = text[from..].find("{{").map(|i| from + i) {
+            let Some(len) = text[start + 2..].find("}}") else { break };
+            let id = &text[start + 2..start + 2 + len];
+            let end = start + 2 + len + 2;
+            if self.text.contains_key(id) {
+                found.push((start, end, id));
+            }
+            from = end;
+        }
+        found
+    }
+
+    /// Escape `text`, turning footnote markers into numbered links
+    fn expand(&mut self, text: &str) -> String {
+        let mut out = String::new();
+        let mut last = 0;
+        for (start, end, id) in self.markers(text) {
+            let number = self.number(id);
+            out.push_str(&escape(&text[last..start]));
+            let _ = write!(out, "<sup class=\"footnote-ref\"><a href=\"#fn-{0}\">{0}</a></sup>", number);
+            last = end;
+        }
+        out.push_str(&escape(&text[last..]));
+        out.replace('\n', "<br>")
+    }
+
+    /// Render inline text: marks as phrase elements, safe links as anchors
+    /// and footnote references as numbered links
+    fn inline(&mut self, text: &InlineText) -> String {
+        let mut out = String::new();
+        for run in text.runs() {
+            match run {
+                InlineRun::Text { text, marks, link } => {
+                    let mut html = self.expand(&text);
+                    for mark in marks.iter().rev() {
+                        let tag = mark_tag(*mark);
+                        html = format!("<{0}>{1}</{0}>", tag, html);
+                    }
+                    match link {
+                        Some(href) if is_safe_link(&href) => {
+                            let _ = write!(out, "<a href=\"{}\">{}</a>", escape(&href), html);
+                        }
+                        _ => out.push_str(&html),
+                    }
+                }
+                InlineRun::FootnoteRef { id } if self.text.contains_key(&id) => {
+                    let number = self.number(&id);
+                    let _ = write!(out, "<sup class=\"footnote-ref\"><a href=\"#fn-{0}\">{0}</a></sup>", number);
+                }
+                InlineRun::FootnoteRef { id } => {
+                    let _ = write!(out, "<sup class=\"footnote-ref\">{}</sup>", escape(&id));
+                }
+                InlineRun::CrossRef { target, text } => {
+                    let _ = write!(out, "<a class=\"xref\" href=\"#{}\">{}</a>", escape(&target), escape(&text));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// HTML element for a formatting mark
+fn mark_tag(mark: Mark) -> &'static str {
+    match mark {
+        Mark::Bold => "strong",
+        Mark::Italic => "em",
+        Mark::Underline => "u",
+        Mark::Strike => "s",
+        Mark::Code => "code",
+        Mark::Superscript => "sup",
+        Mark::Subscript => "sub",
+    }
+}
+
+/// Whether a link target may be followed from rendered output: web and
+/// mail links, fragments and relative paths, but no `javascript:`, `data:`
+/// or other schemes
+pub fn is_safe_link(href: &str) -> bool {
+    let href = href.trim();
+    let scheme_end = href.find([':', '/', '?', '#']);
+    match scheme_end {
+        Some(i) if href[i..].starts_with(':') => {
+            matches!(href[..i].to_ascii_lowercase().as_str(), "http" | "https" | "mailto" | "tel")
+        }
+        _ => !href.is_empty(),
+    }
+}
+
+/// Renders the blocks of one document
+struct Renderer<'a> {
+    out: String,
+    notes: Footnotes,
+    assets: &'a HashMap<String, Vec<u8>>,
+    /// Data of every chart, by chart id
+    charts: HashMap<String, Result<ChartData, String>>,
+}
+
+impl Renderer<'_> {
+    fn id_attribute(id: Option<&str>) -> String {
+        id.map(|id| format!(" id=\"{}\"", escape(id))).unwrap_or_default()
+    }
+
+    fn section(&mut self, section: &Section) {
+        let _ = writeln!(self.out, "<section class=\"document-section\" id=\"{}\">", escape(&section.id));
+        if let Some(ref title) = section.title {
+            let _ = writeln!(self.out, "<h2>{}</h2>", escape(title));
+        }
+        if let Some(ref encrypted) = section.encrypted {
+            let _ = writeln!(
+                self.out,
+                "<p class=\"encrypted-section\">Encrypted section for {}</p>",
+                escape(&encrypted.group)
+            );
+        }
+        for block in &section.content {
+            self.block(block);
+        }
+        self.out.push_str("</section>\n");
+    }
+
+    fn block(&mut self, block: &ContentBlock) {
+        let id = Self::id_attribute(block.id());
+        match block {
+            ContentBlock::Heading { level, text, .. } => {
+                let level = (*level).clamp(1, 6);
+                let text = self.notes.inline(text);
+                let _ = writeln!(self.out, "<h{0}{1}>{2}</h{0}>", level, id, text);
+            }
+            ContentBlock::Paragraph { text, .. } => {
+                let text = self.notes.inline(text);
+                let _ = writeln!(self.out, "<p{}>{}</p>", id, text);
+            }
+            ContentBlock::List { ordered, items, .. } => self.list(&id, *ordered, items),
+            ContentBlock::Quote { text, attribution, .. } => {
+                let text = self.notes.inline(text);
+                let _ = write!(self.out, "<blockquote{}><p>{}</p>", id, text);
+                if let Some(attribution) = attribution {
+                    let _ = write!(self.out, "<footer>{}</footer>", escape(attribution));
+                }
+                self.out.push_str("</blockquote>\n");
+            }
+            ContentBlock::Code { language, text, .. } => {
+                let class = language
+                    .as_ref()
+                    .map(|l| format!(" class=\"language-{}\"", escape(l)))
+                    .unwrap_or_default();
+                let _ = writeln!(self.out, "<pre{}><code{}>{}</code></pre>", id, class, escape(text));
+            }
+            ContentBlock::PageBreak => self.out.push_str("<div class=\"page-break\"></div>\n"),
+            ContentBlock::HorizontalRule => self.out.push_str("<hr>\n"),
+            ContentBlock::Table { caption, columns, header_rows, rows, footer, .. } => {
+                let header_rows = header_rows.as_deref().unwrap_or_default();
+                self.table(&id, caption.as_deref(), columns, header_rows, rows, footer.as_deref());
+            }
+            ContentBlock::Diagram { id: diagram_id, diagram_type, title, nodes, edges, layout } => {
+                let svg = diagram::diagram_svg(diagram_id, diagram_type, title.as_deref(), nodes, edges, layout.as_ref());
+                self.diagram(&id, title.as_deref(), &svg);
+            }
+            ContentBlock::Chart { id: chart_id, chart_type, title, axes, format, .. } => {
+                let svg = match self.charts.get(chart_id) {
+                    Some(Ok(data)) => Ok(chart::render_svg(
+                        &chart::layout(*chart_type, title.as_deref(), data, axes.as_ref(), format.as_ref()),
+                        title.as_deref(),
+                    )),
+                    Some(Err(e)) => Err(e.clone()),
+                    None => Err("chart data is missing".to_string()),
+                };
+                self.chart(&id, svg);
+            }
+            ContentBlock::Figure { asset, alt, caption, width, .. } => {
+                self.figure(&id, asset, alt, caption.as_deref(), *width);
+            }
+            ContentBlock::Footnote { id: note, text } => {
+                // Referenced footnotes are listed at the end instead
+                if !self.notes.referenced.contains(note) {
+                    let _ = writeln!(self.out, "<div class=\"footnote\"{}>{}</div>", id, escape(text));
+                }
+            }
+            ContentBlock::Redacted { id: block_id, .. } => {
+                let _ = writeln!(self.out, "<div class=\"redacted-block\"{}>Redacted ({})</div>", id, escape(block_id));
+            }
+            ContentBlock::TextField { .. }
+            | ContentBlock::NumberField { .. }
+            | ContentBlock::DateField { .. }
+            | ContentBlock::Checkbox { .. }
+            | ContentBlock::Choice { .. }
+            | ContentBlock::SignatureField { .. } => {
+                if let Some(field) = FormField::from_block(block) {
+                    self.field(&id, &field);
+                }
+            }
+        }
+    }
+
+    /// Empty, read-only form field: values live in submissions
+    fn field(&mut self, id: &str, field: &FormField) {
+        let input = match field.kind {
+            FieldKind::Checkbox => "<input type=\"checkbox\" disabled>",
+            FieldKind::Text { multiline: true, .. } => "<span class=\"field-input multiline\"></span>",
+            FieldKind::Signature { .. } => "<span class=\"field-input signature\"></span>",
+            _ => "<span class=\"field-input\"></span>",
+        };
+        let required = if field.required { "<span class=\"field-required\">*</span>" } else { "" };
+        let _ = write!(
+            self.out,
+            "<div class=\"form-field\"{}><span class=\"field-label\">{}{}</span>{}<span class=\"field-hint\">{}</span>",
+            id,
+            escape(field.label),
+            required,
+            input,
+            escape(&field.describe())
+        );
+        if let Some(help) = field.help {
+            let _ = write!(self.out, "<span class=\"field-help\">{}</span>", escape(help));
+        }
+        self.out.push_str("</div>\n");
+    }
+
+    fn list(&mut self, id: &str, ordered: bool, items: &[ListItem]) {
+        let tag = if ordered { "ol" } else { "ul" };
+        let _ = writeln!(self.out, "<{}{}>", tag, id);
+        for item in items {
+            let text = self.notes.inline(&item.text);
+            match item.checked {
+                Some(checked) => {
+                    let checked = if checked { " checked" } else { "" };
+                    let _ = write!(self.out, "<li class=\"task\"><input type=\"checkbox\" disabled{}> {}", checked, text);
+                }
+                None => {
+                    let _ = write!(self.out, "<li>{}", text);
+                }
+            }
+            if let Some(sublist) = &item.sublist {
+                self.out.push('\n');
+                self.list("", sublist.ordered, &sublist.items);
+            }
+            self.out.push_str("</li>\n");
+        }
+        let _ = writeln!(self.out, "</{}>", tag);
+    }
+
+    fn table(
+        &mut self,
+        id: &str,
+        caption: Option<&str>,
+        columns: &[TableColumn],
+        header_rows: &[Vec<HeaderCell>],
+        rows: &[TableRow],
+        footer: Option<&[String]>,
+    ) {
+        let numeric: Vec<bool> = columns
+            .iter()
+            .map(|c| matches!(c.cell_type, CellType::Number | CellType::Currency | CellType::Percentage))
+            .collect();
+        let class = |i: usize| if numeric.get(i).copied().unwrap_or(false) { " class=\"numeric\"" } else { "" };
+        let spans = |colspan: usize, rowspan: usize| {
+            let mut attributes = String::new();
+            if colspan > 1 {
+                let _ = write!(attributes, " colspan=\"{}\"", colspan);
+            }
+            if rowspan > 1 {
+                let _ = write!(attributes, " rowspan=\"{}\"", rowspan);
+            }
+            attributes
+        };
+
+        let _ = writeln!(self.out, "<table class=\"tdf-table\"{}>", id);
+        if let Some(caption) = caption {
+            let _ = writeln!(self.out, "<caption>{}</caption>", escape(caption));
+        }
+        self.out.push_str("<thead>");
+        for slots in header_layout(columns, header_rows) {
+            self.out.push_str("<tr>");
+            for slot in slots {
+                let _ = write!(self.out, "<th{}>{}</th>", spans(slot.colspan, slot.rowspan), escape(slot.text));
+            }
+            self.out.push_str("</tr>");
+        }
+        self.out.push_str("</thead>\n<tbody>\n");
+        let covered = covered_cells(columns, rows);
+        for (r, row) in rows.iter().enumerate() {
+            match row.format.role {
+                RowRole::Data => self.out.push_str("<tr>"),
+                RowRole::Subtotal => self.out.push_str("<tr class=\"subtotal\">"),
+                RowRole::Total => self.out.push_str("<tr class=\"total\">"),
+            }
+            for (i, column) in columns.iter().enumerate() {
+                if covered.contains_key(&(r, i)) {
+                    continue;
+                }
+                let span = row.span(&column.id);
+                let span = spans(span.colspan, span.rowspan);
+                let class = match (i, row.format.level) {
+                    (0, level) if level > 0 => format!(" class=\"level-{}\"", level),
+                    _ => class(i).to_string(),
+                };
+                match row.cells.get(&column.id) {
+                    Some(CellValue::Redacted { .. }) => {
+                        let _ = write!(self.out, "<td class=\"redacted-cell\"{}>Redacted</td>", span);
+                    }
+                    Some(cell) => {
+                        let _ = write!(self.out, "<td{}{}>{}</td>", class, span, escape(cell.display_text()));
+                    }
+                    None => {
+                        let _ = write!(self.out, "<td{}{}></td>", class, span);
+                    }
+                }
+            }
+            self.out.push_str("</tr>\n");
+        }
+        self.out.push_str("</tbody>\n");
+        if let Some(footer) = footer {
+            self.out.push_str("<tfoot><tr>");
+            for (i, cell) in footer.iter().enumerate() {
+                let _ = write!(self.out, "<td{}>{}</td>", class(i), escape(cell));
+            }
+            self.out.push_str("</tr></tfoot>\n");
+        }
+        self.out.push_str("</table>\n");
+    }
+
+    fn diagram(&mut self, id: &str, title: Option<&str>, svg: &str) {
+        let _ = writeln!(self.out, "<figure class=\"diagram-container\"{}>", id);
+        if let Some(title) = title {
+            let _ = writeln!(self.out, "<figcaption>{}</figcaption>", escape(title));
+        }
+        self.out.push_str(svg);
+        self.out.push_str("</figure>\n");
+    }
+
+    /// A chart's SVG, or why its data could not be read
+    fn chart(&mut self, id: &str, svg: Result<String, String>) {
+        let _ = writeln!(self.out, "<figure class=\"chart-container\"{}>", id);
+        match svg {
+            Ok(svg) => self.out.push_str(&svg),
+            Err(e) => {
+                let _ = writeln!(self.out, "<p class=\"chart-error\">Chart cannot be drawn: {}</p>", escape(&e));
+            }
+        }
+        self.out.push_str("</figure>\n");
+    }
+
+    fn figure(&mut self, id: &str, asset: &str, alt: &str, caption: Option<&str>, width: Option<u32>) {
+        let name = asset.trim_start_matches('/');
+        let data = [name.to_string(), format!("assets/images/{}", name), format!("assets/{}", name)]
+            .iter()
+            .find_map(|candidate| self.assets.get(candidate));
+        let width = width.map(|w| format!(" width=\"{}\"", w)).unwrap_or_default();
+        let _ = writeln!(self.out, "<figure{}>", id);
+        match data {
+            Some(bytes) => {
+                let _ = writeln!(
+                    self.out,
+                    "<img src=\"data:{};base64,{}\" alt=\"{}\"{}>",
+                    media_type(name),
+                    STANDARD.encode(bytes),
+                    escape(alt),
+                    width
+                );
+            }
+            None => {
+                let _ = writeln!(self.out, "<div class=\"redacted-block\">Missing image: {}</div>", escape(asset));
+            }
+        }
+        if let Some(caption) = caption {
+            let _ = writeln!(self.out, "<figcaption>{}</figcaption>", escape(caption));
+        }
+        self.out.push_str("</figure>\n");
+    }
+
+    fn footnotes(&mut self) {
+        if self.notes.order.is_empty() {
+            return;
+        }
+        self.out.push_str("<section class=\"footnotes\">\n<ol>\n");
+        for (i, id) in self.notes.order.iter().enumerate() {
+            let text = self.notes.text.get(id).map(String::as_str).unwrap_or("");
+            let _ = writeln!(self.out, "<li id=\"fn-{}\">{}</li>", i + 1, escape(text));
+        }
+        self.out.push_str("</ol>\n</section>\n");
+    }
+}
+
+/// Render the title block, sections and footnotes of `document`
+///
+/// # Arguments
+/// * `document` - Document to render
+/// * `assets` - Archive assets keyed by entry name, inlined for figures
+///
+/// # Returns
+/// An HTML fragment for the content of the page's `<main>` element
+pub fn render_body(document: &Document, assets: &HashMap<String, Vec<u8>>) -> String {
+    let mut renderer = Renderer {
+        out: String::new(),
+        notes: Footnotes::collect(&document.content),
+        assets,
+        charts: chart::collect(&document.content),
+    };
+    let manifest = &document.manifest;
+    let meta = &manifest.document;
+    let out = &mut renderer.out;
+    let _ = writeln!(out, "<h1 class=\"document-title\">{}</h1>", escape(&meta.title));
+    let _ = writeln!(
+        out,
+        "<div class=\"document-meta\">\n<p><strong>Document ID:</strong> <code>{}</code></p>\n\
+         <p><strong>Created:</strong> {}</p>\n<p><strong>Modified:</strong> {}</p>\n</div>",
+        escape(&meta.id),
+        meta.created.format("%Y-%m-%d %H:%M:%S UTC"),
+        meta.modified.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    if !manifest.authors.is_empty() {
+        let names: Vec<String> = manifest.authors.iter().map(|a| escape(&a.name)).collect();
+        let _ = writeln!(out, "<div class=\"document-authors\"><strong>Authors:</strong> {}</div>", names.join(", "));
+    }
+    for section in &document.content.sections {
+        renderer.section(section);
+    }
+    renderer.footnotes();
+    renderer.out
+}
+
+/// Render `document` as a standalone HTML page
+///
+/// # Arguments
+/// * `document` - Document to render
+/// * `assets` - Archive assets keyed by entry name, inlined for figures
+/// * `trailer` - Extra markup placed after `<main>`, such as scripts
+pub fn render_page(document: &Document, assets: &HashMap<String, Vec<u8>>, trailer: Option<&str>) -> String {
+    let meta = &document.manifest.document;
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <meta name=\"generator\" content=\"TDF\">\n<title>{}</title>\n\
+         <style>\n{}</style>\n<style>\n{}</style>\n</head>\n<body>\n\
+         <main id=\"tdf-document\" class=\"document-container\">\n{}</main>\n{}</body>\n</html>\n",
+        escape(&meta.language),
+        escape(&meta.title),
+        DEFAULT_STYLES,
+        // A style element ends at the first "</style" in any case, wherever
+        // it appears; with every "<" escaped the stylesheet cannot close it
+        document.styles.replace('<', "\\3c "),
+        render_body(document, assets),
+        trailer.unwrap_or("")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::Section;
+    use crate::document::Document;
+
+    fn document(blocks: Vec<ContentBlock>) -> Document {
+        let content = DocumentContent {
+            sections: vec![Section { id: "s1".to_string(), title: Some("Intro".to_string()), content: blocks, encrypted: None }],
+        };
+        Document::new("Q3 <Report>".to_string(), "en".to_string(), content, "h1 { color: red; }".to_string())
+    }
+
+    #[test]
+    fn test_escapes_text_and_applies_styles() {
+        let doc = document(vec![ContentBlock::Paragraph { text: "A & B <script>".into(), id: None, xbrl: None }]);
+        let page = render_page(&doc, &HashMap::new(), None);
+        assert!(page.contains("<title>Q3 &lt;Report&gt;</title>"));
+        assert!(page.contains("<p>A &amp; B &lt;script&gt;</p>"));
+        assert!(page.contains("h1 { color: red; }"));
+        assert!(!page.contains("<script>"));
+    }
+
+    #[test]
+    fn test_styles_cannot_close_style_element() {
+        let mut doc = document(Vec::new());
+        doc.styles = "p { color: red; }</StYlE><script>alert(1)</script>".to_string();
+        let page = render_page(&doc, &HashMap::new(), None);
+        assert!(!page.to_lowercase().contains("</style><script>"));
+        assert!(!page.contains("<script>"));
+        assert!(page.contains("p { color: red; }\\3c /StYlE>"));
+    }
+
+    #[test]
+    fn test_footnotes_numbered_in_reading_order() {
+        let doc = document(vec![
+            ContentBlock::Paragraph { text: "First{{fn:b}} then{{fn:a}} again{{fn:b}}".into(), id: None, xbrl: None },
+            ContentBlock::Footnote { id: "fn:a".to_string(), text: "Note A".to_string() },
+            ContentBlock::Footnote { id: "fn:b".to_string(), text: "Note B".to_string() },
+            ContentBlock::Footnote { id: "fn:c".to_string(), text: "Standalone".to_string() },
+        ]);
+        let body = render_body(&doc, &HashMap::new());
+        assert!(body.contains("First<sup class=\"footnote-ref\"><a href=\"#fn-1\">1</a></sup>"));
+        assert!(body.contains("then<sup class=\"footnote-ref\"><a href=\"#fn-2\">2</a></sup>"));
+        assert!(body.contains("again<sup class=\"footnote-ref\"><a href=\"#fn-1\">1</a></sup>"));
+        assert!(body.contains("<li id=\"fn-1\">Note B</li>"));
+        assert!(body.contains("<li id=\"fn-2\">Note A</li>"));
+        // Unreferenced footnotes stay in place
+        assert!(body.contains("<div class=\"footnote\" id=\"fn:c\">Standalone</div>"));
+        assert!(!body.contains("<div class=\"footnote\" id=\"fn:a\">"));
+    }
+
+    #[test]
+    fn test_diagrams_render_as_inline_svg() {
+        use crate::content::{DiagramEdge, DiagramNode, DiagramType, EdgeType};
+        let node = |id: &str| DiagramNode { id: id.to_string(), label: id.to_uppercase(), shape: None, style: None };
+        let doc = document(vec![ContentBlock::Diagram {
+            id: "org".to_string(),
+            diagram_type: DiagramType::Hierarchical,
+            title: Some("Team".to_string()),
+            nodes: vec![node("ceo"), node("cfo")],
+            edges: vec![DiagramEdge { from: "ceo".to_string(), to: "cfo".to_string(), edge_type: EdgeType::Dashed, label: None }],
+            layout: None,
+        }]);
+        let body = render_body(&doc, &HashMap::new());
+        assert!(body.contains("<figure class=\"diagram-container\" id=\"org\">\n<figcaption>Team</figcaption>\n<svg "));
+        assert!(body.contains("<desc>CEO \u{2192} CFO</desc>"));
+        assert!(body.contains("stroke-dasharray=\"6 3\" marker-end=\"url(#org-arrow)\""));
+        assert!(body.contains("</svg>\n</figure>"));
+    }
+
+    #[test]
+    fn test_tables_figures_and_redactions() {
+        let mut cells = HashMap::new();
+        cells.insert("item".to_string(), CellValue::Text("Widgets".to_string()));
+        cells.insert("amount".to_string(), CellValue::Redacted { redacted: "abc".to_string() });
+        let doc = document(vec![
+            ContentBlock::Table {
+                id: "t1".to_string(),
+                caption: Some("Sales".to_string()),
+                columns: vec![
+                    TableColumn { id: "item".to_string(), header: "Item".to_string(), cell_type: CellType::Text, currency: None },
+                    TableColumn { id: "amount".to_string(), header: "Amount".to_string(), cell_type: CellType::Currency, currency: None },
+                ],
+                header_rows: None,
+                rows: vec![TableRow::new(cells)],
+                footer: Some(vec!["Total".to_string(), "100".to_string()]),
+            },
+            ContentBlock::Figure {
+                id: "f1".to_string(),
+                asset: "logo.png".to_string(),
+                alt: "Logo".to_string(),
+                caption: None,
+                width: Some(120),
+            },
+            ContentBlock::Redacted { id: "p9".to_string(), commitment: "c".to_string() },
+        ]);
+        let mut assets = HashMap::new();
+        assets.insert("assets/images/logo.png".to_string(), vec![1, 2, 3]);
+        let body = render_body(&doc, &assets);
+        assert!(body.contains("<caption>Sales</caption>"));
+        assert!(body.contains("<td>Widgets</td><td class=\"redacted-cell\">Redacted</td>"));
+        assert!(body.contains("<tfoot><tr><td>Total</td><td class=\"numeric\">100</td></tr></tfoot>"));
+        assert!(body.contains("<img src=\"data:image/png;base64,AQID\" alt=\"Logo\" width=\"120\">"));
+        assert!(body.contains("<div class=\"redacted-block\" id=\"p9\">Redacted (p9)</div>"));
+        assert_eq!(body, render_body(&doc, &assets));
+    }
+
+    #[test]
+    fn test_table_spans_header_rows_and_row_roles() {
+        use crate::content::{HeaderCell, RowRole};
+
+        let column = |id: &str, header: &str| TableColumn { id: id.to_string(), header: header.to_string(), cell_type: CellType::Text, currency: None };
+        let cells = |values: &[(&str, &str)]| values.iter().map(|(k, v)| (k.to_string(), CellValue::Text(v.to_string()))).collect();
+        let doc = document(vec![ContentBlock::Table {
+            id: "t1".to_string(),
+            caption: None,
+            columns: vec![column("item", "Item"), column("q1", "Q1"), column("q2", "Q2")],
+            header_rows: Some(vec![vec![HeaderCell::new("", 1), HeaderCell::new("FY2025", 2)]]),
+            rows: vec![
+                TableRow::new(cells(&[("item", "Revenue")])).with_span("item", 1, 2),
+                TableRow::new(cells(&[("q1", "Both quarters")])).with_span("q1", 2, 1).with_level(1),
+                TableRow::new(cells(&[("item", "Total"), ("q1", "9"), ("q2", "9")])).with_role(RowRole::Total),
+            ],
+            footer: None,
+        }]);
+        let body = render_body(&doc, &HashMap::new());
+        assert!(body.contains("<thead><tr><th rowspan=\"2\">Item</th><th colspan=\"2\">FY2025</th></tr><tr><th>Q1</th><th>Q2</th></tr></thead>"));
+        assert!(body.contains("<tr><td rowspan=\"2\">Revenue</td><td></td><td></td></tr>"));
+        assert!(body.contains("<tr><td colspan=\"2\">Both quarters</td></tr>"));
+        assert!(body.contains("<tr class=\"total\"><td>Total</td>"));
+    }
+
+    #[test]
+    fn test_rich_text_marks_links_and_references() {
+        use crate::content::{InlineRun, InlineText, Mark};
+
+        let text = InlineText::Runs(vec![
+            InlineRun::marked("Net", &[Mark::Bold, Mark::Italic]),
+            InlineRun::text(" income"),
+            InlineRun::FootnoteRef { id: "fn:1".to_string() },
+            InlineRun::text(" see "),
+            InlineRun::CrossRef { target: "t1".to_string(), text: "Table 1".to_string() },
+            InlineRun::Text { text: "site".to_string(), marks: Vec::new(), link: Some("https://example.com/?a=1&b=2".to_string()) },
+            InlineRun::Text { text: "bad".to_string(), marks: Vec::new(), link: Some("javascript:alert(1)".to_string()) },
+        ]);
+        let doc = document(vec![
+            ContentBlock::Heading { level: 2, text: InlineText::Runs(vec![InlineRun::marked("H2O", &[Mark::Code])]), id: None },
+            ContentBlock::Paragraph { text, id: None, xbrl: None },
+            ContentBlock::Footnote { id: "fn:1".to_string(), text: "Audited".to_string() },
+        ]);
+        let body = render_body(&doc, &HashMap::new());
+        assert!(body.contains("<h2><code>H2O</code></h2>"));
+        assert!(body.contains("<strong><em>Net</em></strong> income<sup class=\"footnote-ref\"><a href=\"#fn-1\">1</a></sup>"));
+        assert!(body.contains("<a class=\"xref\" href=\"#t1\">Table 1</a>"));
+        assert!(body.contains("<a href=\"https://example.com/?a=1&amp;b=2\">site</a>"));
+        assert!(body.contains("bad") && !body.contains("javascript:"));
+        assert!(body.contains("<li id=\"fn-1\">Audited</li>"));
+    }
+
+    #[test]
+    fn test_nested_lists_quotes_code_and_breaks() {
+        let items = vec![
+            ListItem::task("Close books", true).with_sublist(true, vec!["Accruals".into(), ListItem::task("Review{{fn:1}}", false)]),
+            "Publish".into(),
+        ];
+        let doc = document(vec![
+            ContentBlock::List { ordered: false, items, id: Some("todo".to_string()) },
+            ContentBlock::Quote { text: "Growth <was> strong".into(), attribution: Some("CEO".to_string()), id: None },
+            ContentBlock::PageBreak,
+            ContentBlock::Code { language: Some("sql".to_string()), text: "SELECT *\nFROM t -- a<b".to_string(), id: None },
+            ContentBlock::HorizontalRule,
+            ContentBlock::Footnote { id: "fn:1".to_string(), text: "By audit".to_string() },
+        ]);
+        let body = render_body(&doc, &HashMap::new());
+        assert!(body.contains("<ul id=\"todo\">\n<li class=\"task\"><input type=\"checkbox\" disabled checked> Close books\n<ol>\n<li>Accruals</li>\n"));
+        assert!(body.contains("<li class=\"task\"><input type=\"checkbox\" disabled> Review<sup class=\"footnote-ref\"><a href=\"#fn-1\">1</a></sup></li>\n</ol>\n</li>\n<li>Publish</li>\n</ul>"));
+        assert!(body.contains("<blockquote><p>Growth &lt;was&gt; strong</p><footer>CEO</footer></blockquote>"));
+        assert!(body.contains("<div class=\"page-break\"></div>"));
+        assert!(body.contains("<pre><code class=\"language-sql\">SELECT *\nFROM t -- a&lt;b</code></pre>"));
+        assert!(body.contains("<hr>"));
+    }
+
+    #[test]
+    fn test_safe_links() {
+        assert!(is_safe_link("https://example.com"));
+        assert!(is_safe_link("mailto:ir@example.com"));
+        assert!(is_safe_link("#note-3"));
+        assert!(is_safe_link("reports/q3.pdf"));
+        assert!(!is_safe_link("javascript:alert(1)"));
+        assert!(!is_safe_link(" JavaScript:alert(1)"));
+        assert!(!is_safe_link("data:text/html,<script>"));
+        assert!(!is_safe_link(""));
+    }
+}
@@ -0,0 +1,544 @@
+//! Passphrase-encrypted private key files
+//!
+//! Security Fix: private keys were written as raw 32-byte secrets, readable
+//! by anyone with access to the file. Keys are now stored in an encrypted
+//! container:
+//!
+//! - Key derivation: Argon2id (default) or scrypt, with a random 16-byte salt
+//! - Encryption: XChaCha20-Poly1305 with a random 24-byte nonce
+//! - The header (algorithm, public key, KDF parameters) is authenticated as
+//!   associated data, so it cannot be altered without detection
+//!
+//! Legacy raw key files are still readable so they can be migrated.
+//!
+//! ```json
+//! {
+//!   "format": "tdf-encrypted-key",
+//!   "version": 1,
+//!   "algorithm": "ed25519",
+//!   "public_key": "<base64>",
+//!   "kdf": { "name": "argon2id", "m_cost": 65536, "t_cost": 3, "p_cost": 1, "salt": "<base64>" },
+//!   "cipher": "xchacha20-poly1305",
+//!   "nonce": "<base64>",
+//!   "ciphertext": "<base64>"
+//! }
+//! ```
+
+use crate::error::{TdfError, TdfResult};
+use crate::key_format::PrivateKey;
+use crate::secure_key::SecureKey;
+use crate::secure_random::generate_secure_bytes;
+use crate::signature::SignatureAlgorithm;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use ed25519_dalek::SigningKey;
+use k256::ecdsa::SigningKey as Secp256k1SigningKey;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Format identifier of encrypted key files
+pub const KEY_FILE_FORMAT: &str = "tdf-encrypted-key";
+/// Current encrypted key file version
+pub const KEY_FILE_VERSION: u8 = 1;
+/// AEAD used for key files
+pub const KEY_FILE_CIPHER: &str = "xchacha20-poly1305";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const DERIVED_KEY_LEN: usize = 32;
+
+// Upper bounds on KDF cost accepted from key files, so a crafted file
+// cannot make decryption consume unbounded memory or time.
+const MAX_ARGON2_M_COST: u32 = 1024 * 1024; // 1 GiB (KiB units)
+const MAX_ARGON2_T_COST: u32 = 16;
+const MAX_ARGON2_P_COST: u32 = 16;
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+
+/// Password-based key derivation parameters
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "name", rename_all = "lowercase")]
+pub enum KdfParams {
+    Argon2id {
+        /// Memory cost in KiB
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+        salt: String,
+    },
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+}
+
+/// Key derivation function selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Kdf {
+    #[default]
+    Argon2id,
+    Scrypt,
+}
+
+impl std::str::FromStr for Kdf {
+    type Err = TdfError;
+
+    fn from_str(s: &str) -> TdfResult<Self> {
+        match s.to_lowercase().as_str() {
+            "argon2id" | "argon2" => Ok(Kdf::Argon2id),
+            "scrypt" => Ok(Kdf::Scrypt),
+            _ => Err(TdfError::InvalidDocument(format!("Unknown KDF: {}. Use: argon2id, scrypt", s))),
+        }
+    }
+}
+
+impl KdfParams {
+    /// Recommended parameters with a fresh random salt
+    ///
+    /// Argon2id: 64 MiB, 3 passes (RFC 9106 second recommended option).
+    /// scrypt: N = 2^17, r = 8, p = 1.
+    pub fn new(kdf: Kdf) -> TdfResult<Self> {
+        let salt = STANDARD.encode(generate_secure_bytes(SALT_LEN)?);
+        Ok(match kdf {
+            Kdf::Argon2id => KdfParams::Argon2id { m_cost: 64 * 1024, t_cost: 3, p_cost: 1, salt },
+            Kdf::Scrypt => KdfParams::Scrypt { log_n: 17, r: 8, p: 1, salt },
+        })
+    }
+
+    fn salt(&self) -> TdfResult<Vec<u8>> {
+        let salt = match self {
+            KdfParams::Argon2id { salt, .. } | KdfParams::Scrypt { salt, .. } => salt,
+        };
+        STANDARD
+            .decode(salt)
+            .map_err(|e| TdfError::InvalidDocument(format!("Invalid KDF salt: {}", e)))
+    }
+
+    /// Derive the key-encryption key from a passphrase
+    pub(crate) fn derive(&self, passphrase: &[u8]) -> TdfResult<Zeroizing<[u8; DERIVED_KEY_LEN]>> {
+        let salt = self.salt()?;
+        if salt.len() < SALT_LEN {
+            return Err(TdfError::InvalidDocument("KDF salt too short".to_string()));
+        }
+        let mut key = Zeroizing::new([0u8; DERIVED_KEY_LEN]);
+
+        match *self {
+            KdfParams::Argon2id { m_cost, t_cost, p_cost, .. } => {
+                if m_cost > MAX_ARGON2_M_COST || t_cost > MAX_ARGON2_T_COST || p_cost > MAX_ARGON2_P_COST {
+                    return Err(TdfError::PolicyViolation("Argon2id parameters exceed limits".to_string()));
+                }
+                let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(DERIVED_KEY_LEN))
+                    .map_err(|e| TdfError::InvalidDocument(format!("Invalid Argon2id parameters: {}", e)))?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(passphrase, &salt, key.as_mut())
+                    .map_err(|e| TdfError::InvalidDocument(format!("Argon2id failed: {}", e)))?;
+            }
+            KdfParams::Scrypt { log_n, r, p, .. } => {
+                if log_n > MAX_SCRYPT_LOG_N || r > MAX_SCRYPT_R || p > MAX_SCRYPT_P {
+                    return Err(TdfError::PolicyViolation("scrypt parameters exceed limits".to_string()));
+                }
+                let params = scrypt::Params::new(log_n, r, p, DERIVED_KEY_LEN)
+                    .map_err(|e| TdfError::InvalidDocument(format!("Invalid scrypt parameters: {}", e)))?;
+                scrypt::scrypt(passphrase, &salt, &params, key.as_mut())
+                    .map_err(|e| TdfError::InvalidDocument(format!("scrypt failed: {}", e)))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// A passphrase-encrypted private key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptedKeyFile {
+    pub format: String,
+    pub version: u8,
+    pub algorithm: SignatureAlgorithm,
+    /// Base64 public key (Ed25519: 32 bytes, secp256k1: SEC1 compressed)
+    pub public_key: String,
+    pub kdf: KdfParams,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Authenticated header of an encrypted key file
+#[derive(Serialize)]
+struct KeyFileHeader<'a> {
+    format: &'a str,
+    version: u8,
+    algorithm: &'a SignatureAlgorithm,
+    public_key: &'a str,
+    kdf: &'a KdfParams,
+    cipher: &'a str,
+}
+
+impl EncryptedKeyFile {
+    /// Encrypt a private key under a passphrase
+    ///
+    /// # Arguments
+    /// * `secret` - Raw private key bytes
+    /// * `algorithm` - Algorithm of the key
+    /// * `public_key` - Matching public key (stored in clear for identification)
+    /// * `passphrase` - Passphrase to derive the encryption key from
+    /// * `kdf` - KDF parameters (use `KdfParams::new`)
+    pub fn encrypt(
+        secret: &SecureKey,
+        algorithm: SignatureAlgorithm,
+        public_key: &[u8],
+        passphrase: &[u8],
+        kdf: KdfParams,
+    ) -> TdfResult<Self> {
+        if passphrase.is_empty() {
+            return Err(TdfError::PolicyViolation("Passphrase must not be empty".to_string()));
+        }
+
+        let mut file = EncryptedKeyFile {
+            format: KEY_FILE_FORMAT.to_string(),
+            version: KEY_FILE_VERSION,
+            algorithm,
+            public_key: STANDARD.encode(public_key),
+            kdf,
+            cipher: KEY_FILE_CIPHER.to_string(),
+            nonce: STANDARD.encode(generate_secure_bytes(NONCE_LEN)?),
+            ciphertext: String::new(),
+        };
+
+        let key = file.kdf.derive(passphrase)?;
+        let nonce = file.nonce_bytes()?;
+        let aad = file.header_bytes()?;
+        let ciphertext = cipher(&key)?
+            .encrypt(&nonce, Payload { msg: secret.as_bytes(), aad: &aad })
+            .map_err(|_| TdfError::InvalidDocument("Key encryption failed".to_string()))?;
+        file.ciphertext = STANDARD.encode(ciphertext);
+        Ok(file)
+    }
+
+    /// Encrypt an Ed25519 signing key
+    pub fn from_ed25519(key: &SigningKey, passphrase: &[u8], kdf: KdfParams) -> TdfResult<Self> {
+        let secret = SecureKey::new(key.to_bytes().to_vec());
+        Self::encrypt(&secret, SignatureAlgorithm::Ed25519, key.verifying_key().as_bytes(), passphrase, kdf)
+    }
+
+    /// Encrypt a secp256k1 signing key
+    pub fn from_secp256k1(key: &Secp256k1SigningKey, passphrase: &[u8], kdf: KdfParams) -> TdfResult<Self> {
+        let secret = SecureKey::new(key.to_bytes().to_vec());
+        let public_key = key.verifying_key().to_sec1_bytes();
+        Self::encrypt(&secret, SignatureAlgorithm::Secp256k1, &public_key, passphrase, kdf)
+    }
+
+    /// Encrypt a private key of any supported algorithm
+    pub fn from_private_key(key: &PrivateKey, passphrase: &[u8], kdf: KdfParams) -> TdfResult<Self> {
+        match key {
+            PrivateKey::Ed25519(key) => Self::from_ed25519(key, passphrase, kdf),
+            PrivateKey::Secp256k1(key) => Self::from_secp256k1(key, passphrase, kdf),
+        }
+    }
+
+    /// Decrypt the private key
+    ///
+    /// # Returns
+    /// * The raw private key, zeroized on drop
+    /// * `Err(TdfError::SignatureFailure)` if the passphrase is wrong or the file was modified
+    pub fn decrypt(&self, passphrase: &[u8]) -> TdfResult<SecureKey> {
+        if self.format != KEY_FILE_FORMAT || self.version != KEY_FILE_VERSION {
+            return Err(TdfError::InvalidDocument(format!(
+                "Unsupported key file format {} v{}",
+                self.format, self.version
+            )));
+        }
+        if self.cipher != KEY_FILE_CIPHER {
+            return Err(TdfError::InvalidDocument(format!("Unsupported key file cipher {}", self.cipher)));
+        }
+
+        let key = self.kdf.derive(passphrase)?;
+        let nonce = self.nonce_bytes()?;
+        let aad = self.header_bytes()?;
+        let ciphertext = STANDARD
+            .decode(&self.ciphertext)
+            .map_err(|e| TdfError::InvalidDocument(format!("Invalid key ciphertext: {}", e)))?;
+
+        let plaintext = cipher(&key)?
+            .decrypt(&nonce, Payload { msg: &ciphertext, aad: &aad })
+            .map_err(|_| {
+                TdfError::SignatureFailure("Wrong passphrase or corrupted key file".to_string())
+            })?;
+        Ok(SecureKey::new(plaintext))
+    }
+
+    /// Decrypt an Ed25519 signing key and check it against the stored public key
+    pub fn decrypt_ed25519(&self, passphrase: &[u8]) -> TdfResult<SigningKey> {
+        if self.algorithm != SignatureAlgorithm::Ed25519 {
+            return Err(TdfError::UnsupportedSignatureAlgorithm(format!(
+                "Key file holds a {:?} key, expected Ed25519",
+                self.algorithm
+            )));
+        }
+        let key = ed25519_from_secret(&self.decrypt(passphrase)?)?;
+        self.check_public_key(key.verifying_key().as_bytes())?;
+        Ok(key)
+    }
+
+    /// Decrypt a secp256k1 signing key and check it against the stored public key
+    pub fn decrypt_secp256k1(&self, passphrase: &[u8]) -> TdfResult<Secp256k1SigningKey> {
+        if self.algorithm != SignatureAlgorithm::Secp256k1 {
+            return Err(TdfError::UnsupportedSignatureAlgorithm(format!(
+                "Key file holds a {:?} key, expected secp256k1",
+                self.algorithm
+            )));
+        }
+        let key = secp256k1_from_secret(&self.decrypt(passphrase)?)?;
+        self.check_public_key(&key.verifying_key().to_sec1_bytes())?;
+        Ok(key)
+    }
+
+    /// Decrypt the key for the algorithm recorded in the header
+    pub fn decrypt_private_key(&self, passphrase: &[u8]) -> TdfResult<PrivateKey> {
+        match self.algorithm {
+            SignatureAlgorithm::Ed25519 => Ok(PrivateKey::Ed25519(self.decrypt_ed25519(passphrase)?)),
+            SignatureAlgorithm::Secp256k1 => Ok(PrivateKey::Secp256k1(self.decrypt_secp256k1(passphrase)?)),
+            ref other => Err(TdfError::UnsupportedSignatureAlgorithm(format!("{:?}", other))),
+        }
+    }
+
+    /// Public key bytes stored in the header
+    pub fn public_key_bytes(&self) -> TdfResult<Vec<u8>> {
+        STANDARD
+            .decode(&self.public_key)
+            .map_err(|e| TdfError::InvalidDocument(format!("Invalid key file public key: {}", e)))
+    }
+
+    fn check_public_key(&self, public_key: &[u8]) -> TdfResult<()> {
+        if self.public_key_bytes()? != public_key {
+            return Err(TdfError::InvalidDocument(
+                "Key file public key does not match the decrypted private key".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn nonce_bytes(&self) -> TdfResult<XNonce> {
+        let nonce: [u8; NONCE_LEN] = STANDARD
+            .decode(&self.nonce)
+            .map_err(|e| TdfError::InvalidDocument(format!("Invalid key file nonce: {}", e)))?
+            .try_into()
+            .map_err(|_| TdfError::InvalidDocument(format!("Key file nonce must be {} bytes", NONCE_LEN)))?;
+        Ok(XNonce::from(nonce))
+    }
+
+    fn header_bytes(&self) -> TdfResult<Vec<u8>> {
+        Ok(serde_json::to_vec(&KeyFileHeader {
+            format: &self.format,
+            version: self.version,
+            algorithm: &self.algorithm,
+            public_key: &self.public_key,
+            kdf: &self.kdf,
+            cipher: &self.cipher,
+        })?)
+    }
+
+    pub fn to_json(&self) -> TdfResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(data: &[u8]) -> TdfResult<Self> {
+        let file: Self = serde_json::from_slice(data)?;
+        if file.format != KEY_FILE_FORMAT {
+            return Err(TdfError::InvalidDocument(format!("Not a TDF key file: {}", file.format)));
+        }
+        Ok(file)
+    }
+
+    /// Write the key file, readable only by the owner on Unix
+    pub fn write(&self, path: &Path) -> TdfResult<()> {
+        write_private_file(path, self.to_json()?.as_bytes())
+    }
+}
+
+fn cipher(key: &[u8; DERIVED_KEY_LEN]) -> TdfResult<XChaCha20Poly1305> {
+    XChaCha20Poly1305::new_from_slice(key)
+        .map_err(|_| TdfError::InvalidDocument("Invalid key encryption key".to_string()))
+}
+
+/// Contents of a private key file
+pub enum StoredKey {
+    /// Passphrase-encrypted key container
+    Encrypted(EncryptedKeyFile),
+    /// Unencrypted standard key (PKCS#8, SEC1 or JWK); should be migrated
+    Plain(PrivateKey),
+    /// Legacy unencrypted 32-byte key; should be migrated
+    Legacy(SecureKey),
+}
+
+/// Reads only the `format` field, so secret JWK members are never copied
+#[derive(Deserialize)]
+struct FormatProbe {
+    format: Option<String>,
+}
+
+impl StoredKey {
+    /// Detect the key file format
+    pub fn parse(data: Vec<u8>) -> TdfResult<Self> {
+        let data = SecureKey::new(data);
+        if data.len() == 32 {
+            return Ok(StoredKey::Legacy(data));
+        }
+        let probe = serde_json::from_slice::<FormatProbe>(data.as_bytes()).ok();
+        if probe.is_some_and(|p| p.format.is_some()) {
+            return Ok(StoredKey::Encrypted(EncryptedKeyFile::from_json(data.as_bytes())?));
+        }
+        Ok(StoredKey::Plain(PrivateKey::decode(data.as_bytes(), None)?))
+    }
+
+    pub fn read(path: &Path) -> TdfResult<Self> {
+        Self::parse(std::fs::read(path)?)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, StoredKey::Encrypted(_))
+    }
+}
+
+/// Build an Ed25519 signing key from raw secret bytes
+pub fn ed25519_from_secret(secret: &SecureKey) -> TdfResult<SigningKey> {
+    let bytes: &[u8; 32] = secret
+        .as_bytes()
+        .try_into()
+        .map_err(|_| TdfError::InvalidDocument("Ed25519 signing key must be 32 bytes".to_string()))?;
+    Ok(SigningKey::from_bytes(bytes))
+}
+
+/// Build a secp256k1 signing key from raw secret bytes
+pub fn secp256k1_from_secret(secret: &SecureKey) -> TdfResult<Secp256k1SigningKey> {
+    Secp256k1SigningKey::from_slice(secret.as_bytes())
+        .map_err(|e| TdfError::InvalidDocument(format!("Invalid secp256k1 signing key: {}", e)))
+}
+
+/// Write a file that holds secret material (mode 0600 on Unix)
+///
+/// The data goes to a new temporary file next to `path`, which is then
+/// renamed over it: an existing file never keeps its old permissions, and a
+/// crash mid-write leaves the previous contents in place.
+pub fn write_private_file(path: &Path, data: &[u8]) -> TdfResult<()> {
+    use std::io::Write;
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| TdfError::InvalidDocument(format!("Not a file path: {}", path.display())))?;
+    let temp = dir.join(format!(".{}.{}.tmp", name.to_string_lossy(), uuid::Uuid::new_v4().simple()));
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let written = options.open(&temp).and_then(|mut file| {
+        file.write_all(data)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, path)
+    });
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp);
+        return Err(e.into());
+    }
+    #[cfg(unix)]
+    std::fs::File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    /// Cheap parameters so tests run quickly in debug builds
+    fn test_kdf(kdf: Kdf) -> KdfParams {
+        let salt = STANDARD.encode([7u8; SALT_LEN]);
+        match kdf {
+            Kdf::Argon2id => KdfParams::Argon2id { m_cost: 256, t_cost: 1, p_cost: 1, salt },
+            Kdf::Scrypt => KdfParams::Scrypt { log_n: 4, r: 8, p: 1, salt },
+        }
+    }
+
+    #[test]
+    fn test_ed25519_round_trip_argon2id() {
+        let key = SigningKey::generate(&mut OsRng);
+        let file = EncryptedKeyFile::from_ed25519(&key, b"correct horse", test_kdf(Kdf::Argon2id)).unwrap();
+        let parsed = EncryptedKeyFile::from_json(file.to_json().unwrap().as_bytes()).unwrap();
+        assert_eq!(parsed.decrypt_ed25519(b"correct horse").unwrap().to_bytes(), key.to_bytes());
+        assert!(!file.to_json().unwrap().contains(&STANDARD.encode(key.to_bytes())));
+    }
+
+    #[test]
+    fn test_secp256k1_round_trip_scrypt() {
+        let key = Secp256k1SigningKey::random(&mut OsRng);
+        let file = EncryptedKeyFile::from_secp256k1(&key, b"pass", test_kdf(Kdf::Scrypt)).unwrap();
+        assert_eq!(file.decrypt_secp256k1(b"pass").unwrap().to_bytes(), key.to_bytes());
+        assert!(file.decrypt_ed25519(b"pass").is_err());
+    }
+
+    #[test]
+    fn test_wrong_passphrase_rejected() {
+        let key = SigningKey::generate(&mut OsRng);
+        let file = EncryptedKeyFile::from_ed25519(&key, b"right", test_kdf(Kdf::Argon2id)).unwrap();
+        assert!(matches!(file.decrypt(b"wrong"), Err(TdfError::SignatureFailure(_))));
+    }
+
+    #[test]
+    fn test_header_is_authenticated() {
+        let key = SigningKey::generate(&mut OsRng);
+        let mut file = EncryptedKeyFile::from_ed25519(&key, b"pass", test_kdf(Kdf::Argon2id)).unwrap();
+        // Swapping the advertised public key must break decryption
+        let other = SigningKey::generate(&mut OsRng);
+        file.public_key = STANDARD.encode(other.verifying_key().as_bytes());
+        assert!(file.decrypt(b"pass").is_err());
+    }
+
+    #[test]
+    fn test_excessive_kdf_cost_rejected() {
+        let key = SigningKey::generate(&mut OsRng);
+        let mut file = EncryptedKeyFile::from_ed25519(&key, b"pass", test_kdf(Kdf::Scrypt)).unwrap();
+        file.kdf = KdfParams::Scrypt { log_n: 30, r: 8, p: 1, salt: STANDARD.encode([0u8; SALT_LEN]) };
+        assert!(matches!(file.decrypt(b"pass"), Err(TdfError::PolicyViolation(_))));
+    }
+
+    #[test]
+    fn test_stored_key_detection() {
+        let key = SigningKey::generate(&mut OsRng);
+        assert!(matches!(StoredKey::parse(key.to_bytes().to_vec()).unwrap(), StoredKey::Legacy(_)));
+
+        let file = EncryptedKeyFile::from_ed25519(&key, b"pass", test_kdf(Kdf::Argon2id)).unwrap();
+        let stored = StoredKey::parse(file.to_json().unwrap().into_bytes()).unwrap();
+        assert!(stored.is_encrypted());
+        assert!(StoredKey::parse(vec![0u8; 17]).is_err());
+
+        let pem = PrivateKey::Ed25519(key).encode(crate::key_format::KeyFormat::Pem).unwrap();
+        assert!(matches!(StoredKey::parse(pem.as_bytes().to_vec()).unwrap(), StoredKey::Plain(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_file_replaces_readable_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key.pem");
+        std::fs::write(&path, b"old contents that are longer").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private_file(&path, b"secret").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"secret");
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -5,6 +5,11 @@ pub mod document;
 pub mod error;
 pub mod merkle;
 pub mod signature;
+pub mod certificate;
+pub mod did;
+pub mod remote_signer;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
 pub mod archive;
 pub mod timestamp;
 pub mod multiparty;
@@ -13,6 +18,22 @@ pub mod config;
 pub mod whitelist;
 pub mod io;
 pub mod secure_key;
+pub mod key_file;
+pub mod key_format;
+pub mod encryption;
+pub mod redaction;
+pub mod revision;
+pub mod diff;
+pub mod decimal;
+pub mod currency;
+pub mod html;
+pub mod diagram;
+pub mod chart;
+pub mod formula;
+pub mod lint;
+pub mod xbrl;
+pub mod form;
+pub mod qr;
 pub mod integer_safety;
 pub mod secure_random;
 pub mod error_sanitization;
@@ -0,0 +1,518 @@
+//! Semantic checks of document content
+//!
+//! `Document::validate` checks each block on its own. `lint_document`
+//! checks what needs the whole document: section and block ids are unique,
+//! figures point at assets in the archive, diagram edges at nodes of their
+//! diagram, table cells at declared columns of a matching type, charts at
+//! a readable table and its numeric columns, XBRL tags at the taxonomy in
+//! the manifest, and layout elements at sections or blocks. It also flags
+//! heading levels outside 1-6, figures without alt text, sections without
+//! an id and XBRL facts reported twice with different values.
+//!
+//! Findings are errors or warnings. `ArchiveBuilder::build` refuses a
+//! document with errors; warnings are reported by `tdf lint` only.
+//! Encrypted sections and redacted blocks and cells are skipped, since
+//! their content cannot be read.
+
+use crate::chart;
+use crate::content::{CellType, CellValue, ContentBlock, TableColumn, TableRow};
+use crate::document::Document;
+use crate::error::{TdfError, TdfResult};
+use crate::xbrl::{self, Fact};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Deepest heading level, as in HTML
+pub const MAX_HEADING_LEVEL: u8 = 6;
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Reported, but the document can still be built
+    Warning,
+    /// The document is broken and is not built
+    Error,
+}
+
+/// A problem found in a document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Rule that found it, e.g. `duplicate-id`
+    pub rule: &'static str,
+    /// Section id, block id (or `<section id>/<index>` for blocks without
+    /// one), table cell `<table>/<row>/<column>` or `layout/<index>`
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}[{}] {}: {}", severity, self.rule, self.location, self.message)
+    }
+}
+
+/// Findings of a document, in document order
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LintReport {
+    pub findings: Vec<Finding>,
+}
+
+impl LintReport {
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|f| f.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|f| f.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// `Err(InvalidDocument)` listing the errors, if there are any
+    pub fn into_result(self) -> TdfResult<()> {
+        let errors: Vec<String> = self.errors().map(|f| format!("{}: {}", f.location, f.message)).collect();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(TdfError::InvalidDocument(format!(
+            "{} content error(s) (run `tdf lint` for details): {}",
+            errors.len(),
+            errors.join("; ")
+        )))
+    }
+}
+
+/// Check a document's content
+///
+/// # Arguments
+/// * `document` - The document to check
+/// * `assets` - Archive entry names of the document's assets (e.g.
+///   `assets/images/logo.png`); `None` skips the figure asset check
+pub fn lint_document(document: &Document, assets: Option<&HashSet<String>>) -> LintReport {
+    let mut linter = Linter { findings: Vec::new(), sections: HashMap::new(), blocks: HashMap::new() };
+    for (number, section) in document.content.sections.iter().enumerate() {
+        if section.id.is_empty() {
+            linter.push(Severity::Warning, "missing-id", format!("section {}", number + 1), "section has no id".to_string());
+        }
+        Linter::id(&mut linter.sections, &mut linter.findings, &section.id, section.id.clone());
+        if section.encrypted.is_some() {
+            continue;
+        }
+        for (index, block) in section.content.iter().enumerate() {
+            let location = block.id().map(str::to_string).unwrap_or_else(|| format!("{}/{}", section.id, index));
+            if let Some(id) = block.id() {
+                Linter::id(&mut linter.blocks, &mut linter.findings, id, location.clone());
+            }
+            if let ContentBlock::Chart { table, category, series, .. } = block {
+                if let Err(message) = chart::resolve(&document.content, table, category, series) {
+                    linter.push(Severity::Error, "chart-source", location.clone(), message);
+                }
+            }
+            linter.block(block, &location, assets);
+        }
+    }
+    let tagged = xbrl::tagged_facts(document);
+    let mut reported: HashMap<_, (&Fact, &str)> = HashMap::new();
+    for (location, fact) in &tagged {
+        match fact {
+            Err(message) => linter.push(Severity::Error, "xbrl-tag", location.clone(), message.clone()),
+            Ok(fact) => match reported.get(&fact.key()) {
+                Some((first, at)) if !first.same_value(fact) => {
+                    let message = format!("'{}' for {} is also reported with another value at {}", fact.concept, fact.period, at);
+                    linter.push(Severity::Warning, "xbrl-duplicate", location.clone(), message);
+                }
+                Some(_) => {}
+                None => {
+                    reported.insert(fact.key(), (fact, location));
+                }
+            },
+        }
+    }
+    if let Some(layout) = &document.layout {
+        for (index, element) in layout.elements.iter().enumerate() {
+            let id = element.ref_id.as_str();
+            if !linter.sections.contains_key(id) && !linter.blocks.contains_key(id) {
+                linter.push(
+                    Severity::Error,
+                    "unresolved-layout-ref",
+                    format!("layout/{}", index),
+                    format!("ref_id '{}' is not a section or block id", element.ref_id),
+                );
+            }
+        }
+    }
+    LintReport { findings: linter.findings }
+}
+
+struct Linter<'a> {
+    findings: Vec<Finding>,
+    /// Section ids seen so far
+    sections: HashMap<&'a str, String>,
+    /// Block ids seen so far, with where they were first used
+    blocks: HashMap<&'a str, String>,
+}
+
+impl<'a> Linter<'a> {
+    fn push(&mut self, severity: Severity, rule: &'static str, location: String, message: String) {
+        self.findings.push(Finding { severity, rule, location, message });
+    }
+
+    /// Record a section or block id. Sections and blocks are separate
+    /// namespaces: importers name a sheet's section after its table.
+    fn id(seen: &mut HashMap<&'a str, String>, findings: &mut Vec<Finding>, id: &'a str, location: String) {
+        if id.is_empty() {
+            return;
+        }
+        match seen.get(id) {
+            Some(first) => findings.push(Finding {
+                severity: Severity::Error,
+                rule: "duplicate-id",
+                message: format!("id '{}' is already used by {}", id, first),
+                location,
+            }),
+            None => {
+                seen.insert(id, location);
+            }
+        }
+    }
+
+    fn block(&mut self, block: &ContentBlock, location: &str, assets: Option<&HashSet<String>>) {
+        match block {
+            ContentBlock::Heading { level, .. } if !(1..=MAX_HEADING_LEVEL).contains(level) => {
+                let message = format!("heading level {} is outside 1-{}", level, MAX_HEADING_LEVEL);
+                self.push(Severity::Error, "heading-level", location.to_string(), message);
+            }
+            ContentBlock::Figure { asset, alt, .. } => {
+                if alt.trim().is_empty() {
+                    self.push(Severity::Warning, "missing-alt", location.to_string(), "figure has no alt text".to_string());
+                }
+                if asset.contains("://") {
+                    let message = format!("asset '{}' is external and not covered by the signature", asset);
+                    self.push(Severity::Warning, "external-asset", location.to_string(), message);
+                } else if assets.is_some_and(|assets| !has_asset(assets, asset)) {
+                    let message = format!("asset '{}' is not in the archive", asset);
+                    self.push(Severity::Error, "missing-asset", location.to_string(), message);
+                }
+            }
+            ContentBlock::Diagram { nodes, edges, .. } => {
+                let mut node_ids = HashSet::new();
+                for node in nodes {
+                    if !node_ids.insert(node.id.as_str()) {
+                        let message = format!("node id '{}' is used more than once", node.id);
+                        self.push(Severity::Error, "duplicate-node", location.to_string(), message);
+                    }
+                }
+                for (index, edge) in edges.iter().enumerate() {
+                    for end in [&edge.from, &edge.to] {
+                        if !node_ids.contains(end.as_str()) {
+                            let message = format!("edge {} references unknown node '{}'", index + 1, end);
+                            self.push(Severity::Error, "unknown-node", location.to_string(), message);
+                        }
+                    }
+                }
+            }
+            ContentBlock::Table { columns, rows, .. } => self.table(location, columns, rows),
+            _ => {}
+        }
+    }
+
+    fn table(&mut self, table: &str, columns: &[TableColumn], rows: &[TableRow]) {
+        let types: HashMap<&str, &CellType> = columns.iter().map(|c| (c.id.as_str(), &c.cell_type)).collect();
+        for (r, row) in rows.iter().enumerate() {
+            // Sorted, so findings come out in the same order on every run
+            let mut cells: Vec<(&String, &CellValue)> = row.cells.iter().collect();
+            cells.sort_by_key(|(column, _)| columns.iter().position(|c| &c.id == *column).unwrap_or(usize::MAX));
+            for (column, cell) in cells {
+                let location = format!("{}/{}/{}", table, r, column);
+                let Some(cell_type) = types.get(column.as_str()) else {
+                    let message = format!("column '{}' is not declared", column);
+                    self.push(Severity::Error, "unknown-column", location, message);
+                    continue;
+                };
+                match cell_matches(cell, cell_type) {
+                    Some(true) => {}
+                    // Importers keep values they cannot parse as text
+                    None => {
+                        let message = format!("text in a {} column", type_name(cell_type));
+                        self.push(Severity::Warning, "cell-type", location, message);
+                    }
+                    Some(false) => {
+                        let message = format!("{} in a {} column", value_name(cell), type_name(cell_type));
+                        self.push(Severity::Error, "cell-type", location, message);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether an asset is in the archive, resolved as the exporters do
+fn has_asset(assets: &HashSet<String>, asset: &str) -> bool {
+    let name = asset.trim_start_matches('/');
+    [name.to_string(), format!("assets/images/{}", name), format!("assets/{}", name)]
+        .iter()
+        .any(|candidate| assets.contains(candidate))
+}
+
+/// Whether a cell's value fits its column type; `None` for text in a
+/// typed column
+///
+/// Amount columns accept any amount: percentage cells and currency cells
+/// without a code read back as plain numbers, and formulas compute amounts.
+/// Text columns accept `{ raw, display }` strings, which read back as dates.
+fn cell_matches(cell: &CellValue, cell_type: &CellType) -> Option<bool> {
+    let amount = matches!(
+        cell,
+        CellValue::Number { .. } | CellValue::Currency { .. } | CellValue::Percentage { .. } | CellValue::Formula { .. }
+    );
+    match (cell, cell_type) {
+        (CellValue::Redacted { .. }, _) | (CellValue::Text(_), CellType::Text) => Some(true),
+        (CellValue::Text(_), _) => None,
+        (CellValue::Date { .. }, CellType::Date | CellType::Text) => Some(true),
+        (_, CellType::Number | CellType::Currency | CellType::Percentage | CellType::Formula) => Some(amount),
+        _ => Some(false),
+    }
+}
+
+fn type_name(cell_type: &CellType) -> &'static str {
+    match cell_type {
+        CellType::Text => "text",
+        CellType::Number => "number",
+        CellType::Currency => "currency",
+        CellType::Percentage => "percentage",
+        CellType::Date => "date",
+        CellType::Formula => "formula",
+    }
+}
+
+fn value_name(cell: &CellValue) -> &'static str {
+    match cell {
+        CellValue::Text(_) => "text",
+        CellValue::Formula { .. } => "a formula",
+        CellValue::Currency { .. } => "a currency amount",
+        CellValue::Number { .. } => "a number",
+        CellValue::Percentage { .. } => "a percentage",
+        CellValue::Date { .. } => "a date",
+        CellValue::Redacted { .. } => "a redacted cell",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{ChartSeries, ChartType, DiagramEdge, DiagramNode, DiagramType, DocumentContent, EdgeType, Section};
+    use crate::document::{Layout, LayoutElement, Margins, Orientation, PageLayout, PageSize, Position};
+
+    fn document(blocks: Vec<ContentBlock>) -> Document {
+        let content = DocumentContent {
+            sections: vec![Section { id: "sec-1".to_string(), title: None, content: blocks, encrypted: None }],
+        };
+        Document::new("Lint".to_string(), "en".to_string(), content, String::new())
+    }
+
+    fn rules(report: &LintReport) -> Vec<(Severity, &str, &str)> {
+        report.findings.iter().map(|f| (f.severity, f.rule, f.location.as_str())).collect()
+    }
+
+    fn paragraph(id: &str) -> ContentBlock {
+        ContentBlock::Paragraph { text: "text".into(), id: Some(id.to_string()), xbrl: None }
+    }
+
+    #[test]
+    fn test_clean_document_has_no_findings() {
+        let doc = document(vec![
+            ContentBlock::Heading { level: 1, text: "Title".into(), id: Some("h1".to_string()) },
+            paragraph("p1"),
+            ContentBlock::Figure {
+                id: "fig-1".to_string(),
+                asset: "logo.png".to_string(),
+                alt: "Logo".to_string(),
+                caption: None,
+                width: None,
+            },
+        ]);
+        let assets = HashSet::from(["assets/images/logo.png".to_string()]);
+        assert_eq!(lint_document(&doc, Some(&assets)), LintReport::default());
+        assert!(lint_document(&doc, Some(&assets)).into_result().is_ok());
+    }
+
+    #[test]
+    fn test_ids_headings_figures_and_layout() {
+        let mut doc = document(vec![
+            ContentBlock::Heading { level: 7, text: "Deep".into(), id: None },
+            paragraph("p1"),
+            paragraph("p1"),
+            // Sections and blocks are separate namespaces
+            paragraph("sec-1"),
+            ContentBlock::Figure { id: "fig-1".to_string(), asset: "chart.png".to_string(), alt: " ".to_string(), caption: None, width: None },
+            ContentBlock::Figure {
+                id: "fig-2".to_string(),
+                asset: "https://example.com/a.png".to_string(),
+                alt: "Remote".to_string(),
+                caption: None,
+                width: None,
+            },
+        ]);
+        doc.layout = Some(Layout {
+            version: 1,
+            pages: PageLayout {
+                size: PageSize::A4,
+                orientation: Orientation::Portrait,
+                margins: Margins { top: "1cm".into(), bottom: "1cm".into(), left: "1cm".into(), right: "1cm".into() },
+            },
+            elements: ["p1", "missing"]
+                .iter()
+                .map(|id| LayoutElement { ref_id: id.to_string(), page: 1, position: Position { x: "0".into(), y: "0".into() }, size: None })
+                .collect(),
+        });
+        let report = lint_document(&doc, Some(&HashSet::new()));
+        assert_eq!(
+            rules(&report),
+            [
+                (Severity::Error, "heading-level", "sec-1/0"),
+                (Severity::Error, "duplicate-id", "p1"),
+                (Severity::Warning, "missing-alt", "fig-1"),
+                (Severity::Error, "missing-asset", "fig-1"),
+                (Severity::Warning, "external-asset", "fig-2"),
+                (Severity::Error, "unresolved-layout-ref", "layout/1"),
+            ]
+        );
+        assert_eq!(report.warnings().count(), 2);
+        // Without the asset list, assets are not checked
+        assert!(!rules(&lint_document(&doc, None)).iter().any(|(_, rule, _)| *rule == "missing-asset"));
+        let error = report.into_result().unwrap_err().to_string();
+        assert!(error.contains("4 content error(s)") && error.contains("fig-1: asset 'chart.png' is not in the archive"), "{}", error);
+    }
+
+    #[test]
+    fn test_diagram_edges_and_table_cells() {
+        let node = |id: &str| DiagramNode { id: id.to_string(), label: id.to_string(), shape: None, style: None };
+        let edge = |from: &str, to: &str| DiagramEdge { from: from.to_string(), to: to.to_string(), edge_type: EdgeType::Solid, label: None };
+        let column = |id: &str, cell_type: CellType| TableColumn { id: id.to_string(), header: id.to_string(), cell_type, currency: None };
+        let amount = |raw: &str| CellValue::Number { raw: raw.parse().unwrap(), display: raw.to_string() };
+        let doc = document(vec![
+            ContentBlock::Diagram {
+                id: "flow".to_string(),
+                diagram_type: DiagramType::Flowchart,
+                title: None,
+                nodes: vec![node("a"), node("b"), node("b")],
+                edges: vec![edge("a", "b"), edge("b", "c")],
+                layout: None,
+            },
+            ContentBlock::Table {
+                id: "costs".to_string(),
+                caption: None,
+                columns: vec![column("item", CellType::Text), column("amount", CellType::Currency), column("due", CellType::Date)],
+                header_rows: None,
+                rows: vec![
+                    TableRow::new(HashMap::from([
+                        ("item".to_string(), CellValue::Text("Rent".to_string())),
+                        ("amount".to_string(), amount("1200.50")),
+                        ("due".to_string(), CellValue::Date { raw: "2025-01-31".to_string(), display: "31 Jan".to_string() }),
+                    ])),
+                    TableRow::new(HashMap::from([
+                        ("amount".to_string(), CellValue::Text("n/a".to_string())),
+                        ("due".to_string(), amount("3")),
+                        ("note".to_string(), CellValue::Text("extra".to_string())),
+                    ])),
+                ],
+                footer: None,
+            },
+        ]);
+        assert_eq!(
+            rules(&lint_document(&doc, None)),
+            [
+                (Severity::Error, "duplicate-node", "flow"),
+                (Severity::Error, "unknown-node", "flow"),
+                (Severity::Warning, "cell-type", "costs/1/amount"),
+                (Severity::Error, "cell-type", "costs/1/due"),
+                (Severity::Error, "unknown-column", "costs/1/note"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chart_sources() {
+        let column = |id: &str, cell_type: CellType| TableColumn { id: id.to_string(), header: id.to_string(), cell_type, currency: None };
+        let chart = |id: &str, table: &str, column: &str| ContentBlock::Chart {
+            id: id.to_string(),
+            chart_type: ChartType::Bar,
+            title: None,
+            table: table.to_string(),
+            category: "item".to_string(),
+            series: vec![ChartSeries { column: column.to_string(), label: None, color: None }],
+            axes: None,
+            format: None,
+        };
+        let doc = document(vec![
+            // Charts may come before their table
+            chart("ok", "costs", "amount"),
+            chart("no-table", "missing", "amount"),
+            chart("no-column", "costs", "total"),
+            chart("text-series", "costs", "item"),
+            ContentBlock::Table {
+                id: "costs".to_string(),
+                caption: None,
+                columns: vec![column("item", CellType::Text), column("amount", CellType::Currency)],
+                header_rows: None,
+                rows: Vec::new(),
+                footer: None,
+            },
+        ]);
+        let report = lint_document(&doc, None);
+        assert_eq!(
+            rules(&report),
+            [
+                (Severity::Error, "chart-source", "no-table"),
+                (Severity::Error, "chart-source", "no-column"),
+                (Severity::Error, "chart-source", "text-series"),
+            ]
+        );
+        assert_eq!(report.findings[0].message, "table 'missing' does not exist");
+    }
+
+    #[test]
+    fn test_xbrl_tags() {
+        use crate::content::TableRow;
+        use crate::xbrl::{XbrlEntity, XbrlInfo, XbrlPeriod, XbrlTag};
+        use std::collections::BTreeMap;
+
+        let tag = |concept: &str| XbrlTag {
+            concept: concept.to_string(),
+            period: XbrlPeriod::Instant { instant: chrono::NaiveDate::from_ymd_opt(2025, 6, 30).unwrap() },
+            entity: None,
+            unit: None,
+            decimals: None,
+        };
+        let amount = |raw: &str| CellValue::Currency { raw: raw.parse().unwrap(), display: raw.to_string(), currency: "EUR".to_string() };
+        let row = |raw: &str, concept: &str| TableRow::new(HashMap::from([("cash".to_string(), amount(raw))])).with_tag("cash", tag(concept));
+        let mut doc = document(vec![ContentBlock::Table {
+            id: "bs".to_string(),
+            caption: None,
+            columns: vec![TableColumn { id: "cash".to_string(), header: "Cash".to_string(), cell_type: CellType::Currency, currency: None }],
+            header_rows: None,
+            rows: vec![row("100", "ifrs-full:Cash"), row("100", "ifrs-full:Cash"), row("120", "ifrs-full:Cash"), row("5", "us-gaap:Cash")],
+            footer: None,
+        }]);
+        let report = lint_document(&doc, None);
+        assert!(report.findings.iter().all(|f| f.rule == "xbrl-tag" && f.message.contains("no XBRL taxonomy")));
+
+        doc.manifest.xbrl = Some(XbrlInfo {
+            schema_refs: Vec::new(),
+            namespaces: BTreeMap::from([("ifrs-full".to_string(), "https://xbrl.ifrs.org/taxonomy/2024-03-27/ifrs-full".to_string())]),
+            entity: Some(XbrlEntity { scheme: "http://standards.iso.org/iso/17442".to_string(), identifier: "LEI".to_string() }),
+        });
+        let report = lint_document(&doc, None);
+        assert_eq!(rules(&report), [(Severity::Warning, "xbrl-duplicate", "bs/2/cash"), (Severity::Error, "xbrl-tag", "bs/3/cash")]);
+        assert_eq!(report.findings[0].message, "'ifrs-full:Cash' for 2025-06-30 is also reported with another value at bs/0/cash");
+    }
+}
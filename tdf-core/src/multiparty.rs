@@ -1,5 +1,8 @@
 use crate::error::{TdfError, TdfResult};
-use crate::signature::{DocumentSignature, SignatureBlock, SignatureManager, VerificationResult};
+use crate::signature::{
+    DocumentSignature, DocumentSigner, SignatureAlgorithm, SignatureBlock, SignatureManager, SignatureScope,
+    VerificationResult,
+};
 use crate::revocation::RevocationManager;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -251,6 +254,61 @@ impl MultiPartySigningSession {
         self.add_signature(signature)
     }
 
+    /// Counter-sign the session's document with an external signer
+    ///
+    /// Existing signatures are verified first (as in
+    /// `add_signature_verified_mixed`), so an HSM or remote signer never
+    /// signs a session whose earlier signatures are invalid.
+    ///
+    /// # Arguments
+    /// * `signer` - The signer producing the new signature
+    /// * `signer_id` / `signer_name` - Identity of the new signer
+    /// * `ed25519_keys` / `secp256k1_keys` - Verifying keys for existing signatures
+    /// * `revocation_manager` - Optional revocation manager to check key validity
+    pub fn sign_with_signer(
+        &mut self,
+        signer: &dyn DocumentSigner,
+        signer_id: String,
+        signer_name: String,
+        ed25519_keys: &[(String, ed25519_dalek::VerifyingKey)],
+        secp256k1_keys: &[(String, k256::ecdsa::VerifyingKey)],
+        revocation_manager: Option<&RevocationManager>,
+    ) -> TdfResult<()> {
+        let (signature, output) = SignatureManager::sign_with_signer(
+            signer,
+            &self.root_hash,
+            signer_id.clone(),
+            signer_name,
+            SignatureScope::Full,
+            None,
+        )?;
+
+        let mut ed25519_keys = ed25519_keys.to_vec();
+        let mut secp256k1_keys = secp256k1_keys.to_vec();
+        match output.algorithm {
+            SignatureAlgorithm::Ed25519 => {
+                let key: [u8; 32] = output.public_key.as_slice().try_into().map_err(|_| {
+                    TdfError::SignerError("Ed25519 public key must be 32 bytes".to_string())
+                })?;
+                let key = ed25519_dalek::VerifyingKey::from_bytes(&key)
+                    .map_err(|e| TdfError::SignerError(format!("Invalid Ed25519 public key: {}", e)))?;
+                ed25519_keys.push((signer_id, key));
+            }
+            SignatureAlgorithm::Secp256k1 => {
+                let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(&output.public_key)
+                    .map_err(|e| TdfError::SignerError(format!("Invalid secp256k1 public key: {}", e)))?;
+                secp256k1_keys.push((signer_id, key));
+            }
+            SignatureAlgorithm::RsaPss => {
+                return Err(TdfError::UnsupportedSignatureAlgorithm(
+                    "RSA-PSS not yet supported".to_string()
+                ));
+            }
+        }
+
+        self.add_signature_verified_mixed(signature, &ed25519_keys, &secp256k1_keys, revocation_manager)
+    }
+
     pub fn is_complete(&self) -> bool {
         self.signatures.len() == self.required_signers.len()
     }
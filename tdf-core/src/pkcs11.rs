@@ -0,0 +1,541 @@
+//! PKCS#11 signer for hardware security modules and smart cards
+//!
+//! Loads a PKCS#11 module (e.g. SoftHSM's `libsofthsm2.so`, a YubiHSM or
+//! cloud HSM client library) and signs the v2 signing payload with a private
+//! key that never leaves the token.
+//!
+//! Supported keys:
+//! - Ed25519 (`CKK_EC_EDWARDS`, mechanism `CKM_EDDSA`)
+//! - secp256k1 (`CKK_EC`, mechanism `CKM_ECDSA` over SHA-256 of the payload)
+//!
+//! Only the handful of PKCS#11 v2.40 entry points needed for signing are bound.
+
+use crate::error::{TdfError, TdfResult};
+use crate::signature::{DocumentSigner, SignatureAlgorithm, SignerOutput};
+use sha2::{Digest, Sha256};
+use std::ffi::c_void;
+use std::os::raw::c_ulong;
+use std::path::Path;
+use std::ptr;
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+type CkUlong = c_ulong;
+type CkRv = CkUlong;
+type CkSlotId = CkUlong;
+type CkSessionHandle = CkUlong;
+type CkObjectHandle = CkUlong;
+
+const CKR_OK: CkRv = 0x000;
+const CKR_USER_ALREADY_LOGGED_IN: CkRv = 0x100;
+const CKR_CRYPTOKI_ALREADY_INITIALIZED: CkRv = 0x191;
+
+const CKF_RW_SESSION: CkUlong = 0x2;
+const CKF_SERIAL_SESSION: CkUlong = 0x4;
+const CKU_USER: CkUlong = 1;
+
+const CKA_CLASS: CkUlong = 0x000;
+const CKA_LABEL: CkUlong = 0x003;
+const CKA_KEY_TYPE: CkUlong = 0x100;
+const CKA_EC_PARAMS: CkUlong = 0x180;
+const CKA_EC_POINT: CkUlong = 0x181;
+
+const CKO_PUBLIC_KEY: CkUlong = 2;
+const CKO_PRIVATE_KEY: CkUlong = 3;
+const CKK_EC: CkUlong = 0x03;
+const CKK_EC_EDWARDS: CkUlong = 0x40;
+
+const CKM_ECDSA: CkUlong = 0x1041;
+const CKM_EDDSA: CkUlong = 0x1057;
+
+/// DER-encoded OID 1.3.132.0.10 (secp256k1)
+const SECP256K1_OID: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+#[repr(C)]
+struct CkVersion {
+    major: u8,
+    minor: u8,
+}
+
+#[repr(C)]
+struct CkAttribute {
+    attr_type: CkUlong,
+    value: *mut c_void,
+    value_len: CkUlong,
+}
+
+#[repr(C)]
+struct CkMechanism {
+    mechanism: CkUlong,
+    parameter: *mut c_void,
+    parameter_len: CkUlong,
+}
+
+#[repr(C)]
+struct CkTokenInfo {
+    label: [u8; 32],
+    manufacturer_id: [u8; 32],
+    model: [u8; 16],
+    serial_number: [u8; 16],
+    flags: CkUlong,
+    counters: [CkUlong; 10],
+    hardware_version: CkVersion,
+    firmware_version: CkVersion,
+    utc_time: [u8; 16],
+}
+
+type Unused = *const c_void;
+
+/// Leading part of `CK_FUNCTION_LIST` (PKCS#11 v2.40), in declaration order
+///
+/// Only read through the pointer returned by `C_GetFunctionList`; entries
+/// after `C_Sign` are never accessed and are therefore not declared.
+#[repr(C)]
+struct CkFunctionList {
+    version: CkVersion,
+    c_initialize: Option<unsafe extern "C" fn(*mut c_void) -> CkRv>,
+    c_finalize: Option<unsafe extern "C" fn(*mut c_void) -> CkRv>,
+    _c_get_info: Unused,
+    _c_get_function_list: Unused,
+    c_get_slot_list: Option<unsafe extern "C" fn(u8, *mut CkSlotId, *mut CkUlong) -> CkRv>,
+    _c_get_slot_info: Unused,
+    c_get_token_info: Option<unsafe extern "C" fn(CkSlotId, *mut CkTokenInfo) -> CkRv>,
+    _c_get_mechanism_list: Unused,
+    _c_get_mechanism_info: Unused,
+    _c_init_token: Unused,
+    _c_init_pin: Unused,
+    _c_set_pin: Unused,
+    c_open_session: Option<
+        unsafe extern "C" fn(CkSlotId, CkUlong, *mut c_void, *const c_void, *mut CkSessionHandle) -> CkRv,
+    >,
+    c_close_session: Option<unsafe extern "C" fn(CkSessionHandle) -> CkRv>,
+    _c_close_all_sessions: Unused,
+    _c_get_session_info: Unused,
+    _c_get_operation_state: Unused,
+    _c_set_operation_state: Unused,
+    c_login: Option<unsafe extern "C" fn(CkSessionHandle, CkUlong, *const u8, CkUlong) -> CkRv>,
+    c_logout: Option<unsafe extern "C" fn(CkSessionHandle) -> CkRv>,
+    _c_create_object: Unused,
+    _c_copy_object: Unused,
+    _c_destroy_object: Unused,
+    _c_get_object_size: Unused,
+    c_get_attribute_value:
+        Option<unsafe extern "C" fn(CkSessionHandle, CkObjectHandle, *mut CkAttribute, CkUlong) -> CkRv>,
+    _c_set_attribute_value: Unused,
+    c_find_objects_init: Option<unsafe extern "C" fn(CkSessionHandle, *mut CkAttribute, CkUlong) -> CkRv>,
+    c_find_objects:
+        Option<unsafe extern "C" fn(CkSessionHandle, *mut CkObjectHandle, CkUlong, *mut CkUlong) -> CkRv>,
+    c_find_objects_final: Option<unsafe extern "C" fn(CkSessionHandle) -> CkRv>,
+    _c_encrypt_init: Unused,
+    _c_encrypt: Unused,
+    _c_encrypt_update: Unused,
+    _c_encrypt_final: Unused,
+    _c_decrypt_init: Unused,
+    _c_decrypt: Unused,
+    _c_decrypt_update: Unused,
+    _c_decrypt_final: Unused,
+    _c_digest_init: Unused,
+    _c_digest: Unused,
+    _c_digest_update: Unused,
+    _c_digest_key: Unused,
+    _c_digest_final: Unused,
+    c_sign_init: Option<unsafe extern "C" fn(CkSessionHandle, *mut CkMechanism, CkObjectHandle) -> CkRv>,
+    c_sign: Option<unsafe extern "C" fn(CkSessionHandle, *const u8, CkUlong, *mut u8, *mut CkUlong) -> CkRv>,
+}
+
+fn pkcs11_error(msg: impl Into<String>) -> TdfError {
+    TdfError::SignerError(msg.into())
+}
+
+fn check(rv: CkRv, operation: &str) -> TdfResult<()> {
+    if rv == CKR_OK {
+        Ok(())
+    } else {
+        Err(pkcs11_error(format!("{} failed: CKR 0x{:x}", operation, rv)))
+    }
+}
+
+/// Resolve an optional function pointer from the function list
+macro_rules! func {
+    ($list:expr, $name:ident) => {
+        $list
+            .$name
+            .ok_or_else(|| pkcs11_error(concat!("PKCS#11 module does not provide ", stringify!($name))))?
+    };
+}
+
+/// Configuration for a PKCS#11 signing key
+#[derive(Clone)]
+pub struct Pkcs11Config {
+    /// Token label (as set with `softhsm2-util --init-token --label`)
+    pub token_label: String,
+    /// Label of the private key (and its matching public key object)
+    pub key_label: String,
+    /// User PIN
+    pub pin: Zeroizing<String>,
+}
+
+/// Signer backed by a key on a PKCS#11 token
+///
+/// Each signature opens a session, logs in, signs and closes the session, so
+/// no session state is shared between calls.
+pub struct Pkcs11Signer {
+    functions: *const CkFunctionList,
+    slot: CkSlotId,
+    config: Pkcs11Config,
+    algorithm: SignatureAlgorithm,
+    public_key: Vec<u8>,
+    lock: Mutex<()>,
+    // Whether `open` initialized the module (and so must finalize it)
+    initialized: bool,
+    // Keeps the module loaded while `functions` is in use; dropped last
+    _library: libloading::Library,
+}
+
+// SAFETY: the function list is immutable after loading and every call into
+// the module is serialized through `lock` (the module is initialized without
+// CKF_OS_LOCKING_OK, so it may not be thread-safe on its own).
+unsafe impl Send for Pkcs11Signer {}
+unsafe impl Sync for Pkcs11Signer {}
+
+/// An open session; logged out (if this session logged in) and closed on drop
+struct Session<'a> {
+    functions: &'a CkFunctionList,
+    handle: CkSessionHandle,
+    logged_in: bool,
+}
+
+impl Drop for Session<'_> {
+    fn drop(&mut self) {
+        // SAFETY: handle was returned by C_OpenSession and is closed once
+        unsafe {
+            if self.logged_in {
+                if let Some(logout) = self.functions.c_logout {
+                    logout(self.handle);
+                }
+            }
+            if let Some(close) = self.functions.c_close_session {
+                close(self.handle);
+            }
+        }
+    }
+}
+
+impl Pkcs11Signer {
+    /// Load a PKCS#11 module and locate the signing key
+    ///
+    /// # Arguments
+    /// * `module_path` - Path to the PKCS#11 shared library
+    /// * `config` - Token label, key label and PIN
+    pub fn open(module_path: &Path, config: Pkcs11Config) -> TdfResult<Self> {
+        // SAFETY: loading a PKCS#11 module runs its initializers; the caller
+        // chooses which module to trust.
+        let library = unsafe { libloading::Library::new(module_path) }
+            .map_err(|e| pkcs11_error(format!("Failed to load PKCS#11 module: {}", e)))?;
+
+        let mut functions: *const CkFunctionList = ptr::null();
+        // SAFETY: C_GetFunctionList has this signature in every PKCS#11 module
+        unsafe {
+            let get_function_list: libloading::Symbol<
+                unsafe extern "C" fn(*mut *const CkFunctionList) -> CkRv,
+            > = library
+                .get(b"C_GetFunctionList\0")
+                .map_err(|e| pkcs11_error(format!("Not a PKCS#11 module: {}", e)))?;
+            check(get_function_list(&mut functions), "C_GetFunctionList")?;
+        }
+        if functions.is_null() {
+            return Err(pkcs11_error("C_GetFunctionList returned no function list"));
+        }
+        // SAFETY: non-null pointer to the module's static function list
+        let list = unsafe { &*functions };
+
+        // SAFETY: null init args request single-threaded use, enforced by `lock`
+        let rv = unsafe { func!(list, c_initialize)(ptr::null_mut()) };
+        if rv != CKR_CRYPTOKI_ALREADY_INITIALIZED {
+            check(rv, "C_Initialize")?;
+        }
+
+        // From here on, dropping `signer` on error finalizes the module if we initialized it
+        let mut signer = Pkcs11Signer {
+            functions,
+            slot: 0,
+            config,
+            algorithm: SignatureAlgorithm::Ed25519,
+            public_key: Vec::new(),
+            lock: Mutex::new(()),
+            initialized: rv == CKR_OK,
+            _library: library,
+        };
+        signer.slot = Self::find_slot(list, &signer.config.token_label)?;
+        let (algorithm, public_key) = signer.read_public_key()?;
+        signer.algorithm = algorithm;
+        signer.public_key = public_key;
+        Ok(signer)
+    }
+
+    /// Algorithm of the token key
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        self.algorithm.clone()
+    }
+
+    /// Public key (Ed25519: 32 bytes, secp256k1: SEC1)
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    fn functions(&self) -> &CkFunctionList {
+        // SAFETY: checked non-null in `open`; valid while `_library` is loaded
+        unsafe { &*self.functions }
+    }
+
+    fn find_slot(list: &CkFunctionList, token_label: &str) -> TdfResult<CkSlotId> {
+        let get_slot_list = func!(list, c_get_slot_list);
+        let get_token_info = func!(list, c_get_token_info);
+
+        let mut count: CkUlong = 0;
+        // SAFETY: first call queries the slot count with a null buffer
+        check(unsafe { get_slot_list(1, ptr::null_mut(), &mut count) }, "C_GetSlotList")?;
+        let mut slots = vec![0 as CkSlotId; count as usize];
+        // SAFETY: buffer holds `count` slot IDs
+        check(unsafe { get_slot_list(1, slots.as_mut_ptr(), &mut count) }, "C_GetSlotList")?;
+        slots.truncate(count as usize);
+
+        for slot in slots {
+            // SAFETY: CkTokenInfo is plain data; zeroed is a valid initial value
+            let mut info: CkTokenInfo = unsafe { std::mem::zeroed() };
+            // SAFETY: info is a valid CK_TOKEN_INFO out-parameter
+            if unsafe { get_token_info(slot, &mut info) } != CKR_OK {
+                continue;
+            }
+            // Token labels are blank-padded to 32 bytes
+            let label = String::from_utf8_lossy(&info.label);
+            if label.trim_end() == token_label {
+                return Ok(slot);
+            }
+        }
+        Err(pkcs11_error(format!("No PKCS#11 token with label '{}'", token_label)))
+    }
+
+    fn open_session(&self) -> TdfResult<Session<'_>> {
+        let list = self.functions();
+        let mut handle: CkSessionHandle = 0;
+        // SAFETY: no application callback; handle is an out-parameter
+        check(
+            unsafe {
+                func!(list, c_open_session)(
+                    self.slot,
+                    CKF_SERIAL_SESSION | CKF_RW_SESSION,
+                    ptr::null_mut(),
+                    ptr::null(),
+                    &mut handle,
+                )
+            },
+            "C_OpenSession",
+        )?;
+        let mut session = Session { functions: list, handle, logged_in: false };
+
+        let pin = self.config.pin.as_bytes();
+        // SAFETY: pin buffer is valid for its length
+        let rv = unsafe { func!(list, c_login)(handle, CKU_USER, pin.as_ptr(), pin.len() as CkUlong) };
+        if rv != CKR_USER_ALREADY_LOGGED_IN {
+            check(rv, "C_Login")?;
+            session.logged_in = true;
+        }
+        Ok(session)
+    }
+
+    fn find_object(&self, session: &Session<'_>, class: CkUlong) -> TdfResult<CkObjectHandle> {
+        let list = self.functions();
+        let mut class = class;
+        let mut label = self.config.key_label.clone().into_bytes();
+        let mut template = [
+            CkAttribute {
+                attr_type: CKA_CLASS,
+                value: &mut class as *mut CkUlong as *mut c_void,
+                value_len: std::mem::size_of::<CkUlong>() as CkUlong,
+            },
+            CkAttribute {
+                attr_type: CKA_LABEL,
+                value: label.as_mut_ptr() as *mut c_void,
+                value_len: label.len() as CkUlong,
+            },
+        ];
+
+        let mut object: CkObjectHandle = 0;
+        let mut found: CkUlong = 0;
+        // SAFETY: template entries point to live buffers for the whole search
+        unsafe {
+            check(
+                func!(list, c_find_objects_init)(session.handle, template.as_mut_ptr(), template.len() as CkUlong),
+                "C_FindObjectsInit",
+            )?;
+            let rv = func!(list, c_find_objects)(session.handle, &mut object, 1, &mut found);
+            func!(list, c_find_objects_final)(session.handle);
+            check(rv, "C_FindObjects")?;
+        }
+        if found == 0 {
+            return Err(pkcs11_error(format!("Key '{}' not found on token", self.config.key_label)));
+        }
+        Ok(object)
+    }
+
+    fn get_attribute(&self, session: &Session<'_>, object: CkObjectHandle, attr_type: CkUlong) -> TdfResult<Vec<u8>> {
+        let get_attribute_value = func!(self.functions(), c_get_attribute_value);
+        let mut attribute = CkAttribute { attr_type, value: ptr::null_mut(), value_len: 0 };
+        // SAFETY: first call queries the length with a null buffer
+        check(
+            unsafe { get_attribute_value(session.handle, object, &mut attribute, 1) },
+            "C_GetAttributeValue",
+        )?;
+        let mut value = vec![0u8; attribute.value_len as usize];
+        attribute.value = value.as_mut_ptr() as *mut c_void;
+        // SAFETY: buffer holds value_len bytes
+        check(
+            unsafe { get_attribute_value(session.handle, object, &mut attribute, 1) },
+            "C_GetAttributeValue",
+        )?;
+        value.truncate(attribute.value_len as usize);
+        Ok(value)
+    }
+
+    fn read_public_key(&self) -> TdfResult<(SignatureAlgorithm, Vec<u8>)> {
+        let _guard = self.lock.lock().map_err(|_| pkcs11_error("PKCS#11 lock poisoned"))?;
+        let session = self.open_session()?;
+        let object = self.find_object(&session, CKO_PUBLIC_KEY)?;
+
+        let key_type = self.get_attribute(&session, object, CKA_KEY_TYPE)?;
+        let key_type = CkUlong::from_ne_bytes(
+            key_type
+                .as_slice()
+                .try_into()
+                .map_err(|_| pkcs11_error("Invalid CKA_KEY_TYPE"))?,
+        );
+        let point = unwrap_ec_point(self.get_attribute(&session, object, CKA_EC_POINT)?);
+
+        match key_type {
+            CKK_EC_EDWARDS if point.len() == 32 => Ok((SignatureAlgorithm::Ed25519, point)),
+            CKK_EC => {
+                let params = self.get_attribute(&session, object, CKA_EC_PARAMS)?;
+                if params != SECP256K1_OID {
+                    return Err(pkcs11_error("Only secp256k1 EC keys are supported"));
+                }
+                Ok((SignatureAlgorithm::Secp256k1, point))
+            }
+            _ => Err(pkcs11_error(format!("Unsupported PKCS#11 key type 0x{:x}", key_type))),
+        }
+    }
+}
+
+/// Strip the DER OCTET STRING wrapper that most modules put around CKA_EC_POINT
+fn unwrap_ec_point(point: Vec<u8>) -> Vec<u8> {
+    match point.as_slice() {
+        [0x04, len, rest @ ..] if *len as usize == rest.len() && matches!(rest.len(), 32 | 33 | 65) => rest.to_vec(),
+        _ => point,
+    }
+}
+
+impl DocumentSigner for Pkcs11Signer {
+    fn sign_payload(&self, payload: &[u8]) -> TdfResult<SignerOutput> {
+        let _guard = self.lock.lock().map_err(|_| pkcs11_error("PKCS#11 lock poisoned"))?;
+        let session = self.open_session()?;
+        let key = self.find_object(&session, CKO_PRIVATE_KEY)?;
+
+        // k256 signs SHA-256(message); raw CKM_ECDSA expects the digest
+        let (mechanism, data) = match self.algorithm {
+            SignatureAlgorithm::Ed25519 => (CKM_EDDSA, payload.to_vec()),
+            _ => (CKM_ECDSA, Sha256::digest(payload).to_vec()),
+        };
+        let mut mechanism = CkMechanism { mechanism, parameter: ptr::null_mut(), parameter_len: 0 };
+
+        let list = self.functions();
+        let sign = func!(list, c_sign);
+        let mut signature = vec![0u8; 128];
+        let mut signature_len = signature.len() as CkUlong;
+        // SAFETY: mechanism, data and signature buffers outlive the calls
+        unsafe {
+            check(func!(list, c_sign_init)(session.handle, &mut mechanism, key), "C_SignInit")?;
+            check(
+                sign(session.handle, data.as_ptr(), data.len() as CkUlong, signature.as_mut_ptr(), &mut signature_len),
+                "C_Sign",
+            )?;
+        }
+        signature.truncate(signature_len as usize);
+
+        Ok(SignerOutput {
+            algorithm: self.algorithm.clone(),
+            signature,
+            public_key: self.public_key.clone(),
+        })
+    }
+}
+
+impl Drop for Pkcs11Signer {
+    fn drop(&mut self) {
+        // Another user of the module initialized it first; leave it to them
+        if !self.initialized {
+            return;
+        }
+        // SAFETY: matches the C_Initialize in `open`; the library is still loaded
+        unsafe {
+            if let Some(finalize) = self.functions().c_finalize {
+                finalize(ptr::null_mut());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwrap_ec_point() {
+        let raw = vec![0xab; 32];
+        let mut wrapped = vec![0x04, 32];
+        wrapped.extend_from_slice(&raw);
+        assert_eq!(unwrap_ec_point(wrapped), raw);
+
+        // An uncompressed SEC1 point (0x04 || X || Y) is left alone
+        let mut sec1 = vec![0x04];
+        sec1.extend_from_slice(&[1u8; 64]);
+        assert_eq!(unwrap_ec_point(sec1.clone()), sec1);
+    }
+
+    /// Requires SoftHSM with a token and an Ed25519 key, e.g.:
+    ///
+    /// ```text
+    /// softhsm2-util --init-token --free --label tdf --pin 1234 --so-pin 1234
+    /// pkcs11-tool --module $TDF_PKCS11_MODULE --login --pin 1234 \
+    ///     --keypairgen --key-type EC:edwards25519 --label tdf-signing
+    /// TDF_PKCS11_MODULE=/usr/lib/softhsm/libsofthsm2.so cargo test --features pkcs11 -- --ignored
+    /// ```
+    #[test]
+    #[ignore]
+    fn test_softhsm_signing() {
+        use crate::signature::{SignatureManager, SignatureScope};
+
+        let module = std::env::var("TDF_PKCS11_MODULE").expect("TDF_PKCS11_MODULE not set");
+        let signer = Pkcs11Signer::open(
+            Path::new(&module),
+            Pkcs11Config {
+                token_label: std::env::var("TDF_PKCS11_TOKEN").unwrap_or_else(|_| "tdf".to_string()),
+                key_label: std::env::var("TDF_PKCS11_KEY").unwrap_or_else(|_| "tdf-signing".to_string()),
+                pin: Zeroizing::new(std::env::var("TDF_PKCS11_PIN").unwrap_or_else(|_| "1234".to_string())),
+            },
+        )
+        .unwrap();
+
+        // sign_with_signer verifies the token's signature against its public key
+        let (signature, output) = SignatureManager::sign_with_signer(
+            &signer,
+            b"test_root_hash_for_signing_12345",
+            "did:web:hsm.example".to_string(),
+            "HSM".to_string(),
+            SignatureScope::Full,
+            None,
+        )
+        .unwrap();
+        assert_eq!(signature.algorithm, signer.algorithm());
+        assert_eq!(output.public_key, signer.public_key());
+    }
+}
@@ -0,0 +1,506 @@
+//! QR code encoding for verification stamps
+//!
+//! A small encoder for what exported documents need: byte-mode data at
+//! error correction level M, in the smallest of versions 1-10 that fits
+//! (up to 213 bytes). The layout follows ISO/IEC 18004: finder, timing and
+//! alignment patterns, Reed-Solomon codewords interleaved across blocks,
+//! and the data mask with the lowest penalty score.
+
+use crate::error::{TdfError, TdfResult};
+
+/// Largest supported version
+const MAX_VERSION: usize = 10;
+
+/// Level M error correction per version: (ECC codewords per block, blocks
+/// in group 1, data codewords per group 1 block, blocks in group 2)
+///
+/// Group 2 blocks hold one data codeword more than group 1 blocks.
+const BLOCKS_M: [(usize, usize, usize, usize); MAX_VERSION] = [
+    (10, 1, 16, 0),
+    (16, 1, 28, 0),
+    (26, 1, 44, 0),
+    (18, 2, 32, 0),
+    (24, 2, 43, 0),
+    (16, 4, 27, 0),
+    (18, 4, 31, 0),
+    (22, 2, 38, 2),
+    (22, 3, 36, 2),
+    (26, 4, 43, 1),
+];
+
+/// Alignment pattern centres per version (version 1 has none)
+const ALIGNMENT: [&[usize]; MAX_VERSION] = [
+    &[],
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+];
+
+/// A QR code symbol: a square grid of dark (true) and light modules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+    version: usize,
+    size: usize,
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode `data` in byte mode at error correction level M
+    ///
+    /// # Returns
+    /// * The symbol of the smallest version that holds the data, or an
+    ///   error if it needs more than version 10 (213 bytes)
+    pub fn encode(data: &[u8]) -> TdfResult<Self> {
+        let version = (1..=MAX_VERSION)
+            .find(|&v| data_capacity_bits(v) >= 4 + count_bits(v) + data.len() * 8)
+            .ok_or_else(|| {
+                TdfError::InvalidDocument(format!(
+                    "{} bytes do not fit in a version {} QR code",
+                    data.len(),
+                    MAX_VERSION
+                ))
+            })?;
+        let codewords = interleave(version, &data_codewords(version, data));
+
+        let mut grid = Grid::new(version);
+        grid.draw_function_patterns();
+        grid.draw_codewords(&codewords);
+
+        // Pick the mask with the lowest penalty
+        let mut best: Option<(usize, Grid)> = None;
+        for mask in 0..8 {
+            let mut candidate = grid.clone();
+            candidate.apply_mask(mask);
+            candidate.draw_format(mask);
+            let penalty = candidate.penalty();
+            if best.as_ref().map(|(p, _)| penalty < *p).unwrap_or(true) {
+                best = Some((penalty, candidate));
+            }
+        }
+        let grid = best.expect("eight masks were tried").1;
+        Ok(QrCode { version, size: grid.size, modules: grid.modules })
+    }
+
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Width and height in modules, excluding the quiet zone
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the module at column `x`, row `y` is dark
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && self.modules[y * self.size + x]
+    }
+}
+
+fn count_bits(version: usize) -> usize {
+    if version < 10 {
+        8
+    } else {
+        16
+    }
+}
+
+fn data_codeword_count(version: usize) -> usize {
+    let (_, blocks1, len1, blocks2) = BLOCKS_M[version - 1];
+    blocks1 * len1 + blocks2 * (len1 + 1)
+}
+
+fn data_capacity_bits(version: usize) -> usize {
+    data_codeword_count(version) * 8
+}
+
+/// Mode indicator, character count, data, terminator and pad codewords
+fn data_codewords(version: usize, data: &[u8]) -> Vec<u8> {
+    let mut bits: Vec<bool> = Vec::new();
+    let mut push = |value: usize, width: usize| {
+        for i in (0..width).rev() {
+            bits.push((value >> i) & 1 == 1);
+        }
+    };
+    push(0b0100, 4);
+    push(data.len(), count_bits(version));
+    for &byte in data {
+        push(byte as usize, 8);
+    }
+    let capacity = data_capacity_bits(version);
+    let terminator = (capacity - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator));
+    while !bits.len().is_multiple_of(8) {
+        bits.push(false);
+    }
+
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0u8, |acc, &b| (acc << 1) | b as u8))
+        .collect();
+    for pad in [0xEC, 0x11].iter().cycle() {
+        if codewords.len() >= capacity / 8 {
+            break;
+        }
+        codewords.push(*pad);
+    }
+    codewords
+}
+
+/// Multiply in GF(2^8) with the QR polynomial x^8 + x^4 + x^3 + x^2 + 1
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u16 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u16 >> i) & 1) * x as u16;
+    }
+    z as u8
+}
+
+/// Reed-Solomon generator polynomial of `degree`, highest coefficient omitted
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_mul(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+    result
+}
+
+/// Error correction codewords of `data`
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_mul(d, factor);
+        }
+    }
+    result
+}
+
+/// Split data into blocks, add error correction and interleave the codewords
+fn interleave(version: usize, data: &[u8]) -> Vec<u8> {
+    let (ecc_len, blocks1, len1, blocks2) = BLOCKS_M[version - 1];
+    let divisor = rs_divisor(ecc_len);
+    let mut blocks: Vec<(&[u8], Vec<u8>)> = Vec::new();
+    let mut offset = 0;
+    for i in 0..blocks1 + blocks2 {
+        let len = if i < blocks1 { len1 } else { len1 + 1 };
+        let block = &data[offset..offset + len];
+        blocks.push((block, rs_remainder(block, &divisor)));
+        offset += len;
+    }
+
+    let mut result = Vec::new();
+    for i in 0..=len1 {
+        result.extend(blocks.iter().filter_map(|(block, _)| block.get(i)));
+    }
+    for i in 0..ecc_len {
+        result.extend(blocks.iter().map(|(_, ecc)| ecc[i]));
+    }
+    result
+}
+
+#[derive(Clone)]
+struct Grid {
+    version: usize,
+    size: usize,
+    modules: Vec<bool>,
+    function: Vec<bool>,
+}
+
+impl Grid {
+    fn new(version: usize) -> Self {
+        let size = version * 4 + 17;
+        Grid { version, size, modules: vec![false; size * size], function: vec![false; size * size] }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        self.draw_finder(3, 3);
+        self.draw_finder(size - 4, 3);
+        self.draw_finder(3, size - 4);
+
+        let centres = ALIGNMENT[self.version - 1];
+        let last = centres.len().saturating_sub(1);
+        for (i, &cx) in centres.iter().enumerate() {
+            for (j, &cy) in centres.iter().enumerate() {
+                // Skip the three corners taken by finder patterns
+                if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                    continue;
+                }
+                for dy in 0..5 {
+                    for dx in 0..5 {
+                        let dist = (dx as isize - 2).abs().max((dy as isize - 2).abs());
+                        self.set_function(cx + dx - 2, cy + dy - 2, dist != 1);
+                    }
+                }
+            }
+        }
+
+        // Reserve the format areas (drawn per mask) and set the dark module
+        self.draw_format(0);
+        self.draw_version();
+    }
+
+    /// Finder pattern with its separator, centred at (x, y)
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4isize..=4 {
+            for dx in -4isize..=4 {
+                let (xx, yy) = (x as isize + dx, y as isize + dy);
+                if xx < 0 || yy < 0 || xx >= self.size as isize || yy >= self.size as isize {
+                    continue;
+                }
+                let dist = dx.abs().max(dy.abs());
+                self.set_function(xx as usize, yy as usize, dist != 2 && dist != 4);
+            }
+        }
+    }
+
+    /// Format information: level M, the mask, BCH(15,5) check bits
+    fn draw_format(&mut self, mask: usize) {
+        // Level M is 00
+        let data = mask;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+        let size = self.size;
+
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Version information (versions 7 and up): BCH(18,6) check bits
+    fn draw_version(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+        let mut rem = self.version;
+        for _ in 0..12 {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+        }
+        let bits = (self.version << 12) | rem;
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 == 1;
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// Place codeword bits in the two-column zigzag, skipping function modules
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let total = codewords.len() * 8;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.function[y * size + x] && i < total {
+                        self.modules[y * size + x] = (codewords[i >> 3] >> (7 - (i & 7))) & 1 == 1;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let i = y * self.size + x;
+                if invert && !self.function[i] {
+                    self.modules[i] = !self.modules[i];
+                }
+            }
+        }
+    }
+
+    /// Penalty score of ISO/IEC 18004 section 7.8.3
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        let line = |i: usize, j: usize, horizontal: bool| if horizontal { self.get(j, i) } else { self.get(i, j) };
+        const FINDER: [bool; 7] = [true, false, true, true, true, false, true];
+
+        for horizontal in [true, false] {
+            for i in 0..size {
+                // Runs of five or more modules of one colour
+                let mut run = 1;
+                for j in 1..size {
+                    if line(i, j, horizontal) == line(i, j - 1, horizontal) {
+                        run += 1;
+                    } else {
+                        if run >= 5 {
+                            penalty += run - 2;
+                        }
+                        run = 1;
+                    }
+                }
+                if run >= 5 {
+                    penalty += run - 2;
+                }
+                // Finder-like 1:1:3:1:1 patterns with four light modules on one side
+                for j in 0..size.saturating_sub(6) {
+                    if (0..7).all(|k| line(i, j + k, horizontal) == FINDER[k]) {
+                        let light = |from: isize| (from..from + 4).all(|k| k < 0 || k >= size as isize || !line(i, k as usize, horizontal));
+                        if light(j as isize - 4) || light(j as isize + 7) {
+                            penalty += 40;
+                        }
+                    }
+                }
+            }
+        }
+
+        // 2x2 blocks of one colour
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = self.get(x, y);
+                if c == self.get(x + 1, y) && c == self.get(x, y + 1) && c == self.get(x + 1, y + 1) {
+                    penalty += 3;
+                }
+            }
+        }
+
+        // Balance of dark and light modules
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&m| m).count();
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty + (deviation.div_ceil(total)).saturating_sub(1) * 10
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reed_solomon_matches_reference() {
+        // "HELLO WORLD" at 1-M (ISO/IEC 18004 worked example)
+        let data = [0x20, 0x5B, 0x0B, 0x78, 0xD1, 0x72, 0xDC, 0x4D, 0x43, 0x40, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11];
+        let ecc = rs_remainder(&data, &rs_divisor(10));
+        assert_eq!(ecc, [0xC4, 0x23, 0x27, 0x77, 0xEB, 0xD7, 0xE7, 0xE2, 0x5D, 0x17]);
+    }
+
+    #[test]
+    fn test_version_selection_and_padding() {
+        assert_eq!(QrCode::encode(&[b'a'; 14]).unwrap().version(), 1);
+        assert_eq!(QrCode::encode(&[b'a'; 15]).unwrap().version(), 2);
+        assert_eq!(QrCode::encode(&[b'a'; 213]).unwrap().version(), 10);
+        assert!(QrCode::encode(&[b'a'; 214]).is_err());
+
+        let codewords = data_codewords(1, b"A");
+        assert_eq!(codewords.len(), 16);
+        assert_eq!(&codewords[..3], &[0x40, 0x14, 0x10]);
+        assert_eq!(&codewords[3..5], &[0xEC, 0x11]);
+    }
+
+    #[test]
+    fn test_function_patterns_and_format() {
+        let code = QrCode::encode(b"tdf:verify?id=00000000-0000-0000-0000-000000000000").unwrap();
+        let size = code.size();
+        assert_eq!(size, code.version() * 4 + 17);
+        // Finder pattern corners and separators
+        for (x, y) in [(0, 0), (size - 7, 0), (0, size - 7)] {
+            assert!(code.is_dark(x, y) && code.is_dark(x + 6, y + 6) && code.is_dark(x + 3, y + 3));
+            assert!(!code.is_dark(x + 1, y + 1));
+        }
+        assert!(!code.is_dark(7, 7));
+        // Timing pattern and the dark module
+        assert!((8..size - 8).all(|i| code.is_dark(i, 6) == (i % 2 == 0)));
+        assert!(code.is_dark(8, size - 8));
+
+        // Both copies of the format information decode to level M
+        let first: usize = (0..15).fold(0, |acc, i| {
+            let (x, y) = match i {
+                0..=5 => (8, i),
+                6 => (8, 7),
+                7 => (8, 8),
+                8 => (7, 8),
+                _ => (14 - i, 8),
+            };
+            acc | (code.is_dark(x, y) as usize) << i
+        });
+        let second: usize = (0..15).fold(0, |acc, i| {
+            let (x, y) = if i < 8 { (size - 1 - i, 8) } else { (8, size - 15 + i) };
+            acc | (code.is_dark(x, y) as usize) << i
+        });
+        assert_eq!(first, second);
+        assert_eq!((first ^ 0x5412) >> 13, 0b00);
+    }
+
+    #[test]
+    fn test_version_information() {
+        let code = QrCode::encode(&[b'x'; 120]).unwrap();
+        assert_eq!(code.version(), 7);
+        // Version 7 information is 000111 110010 010100 (0x07C94)
+        let bits: usize = (0..18).fold(0, |acc, i| acc | (code.is_dark(code.size() - 11 + i % 3, i / 3) as usize) << i);
+        assert_eq!(bits, 0x07C94);
+    }
+}
@@ -0,0 +1,541 @@
+//! Redactable content with salted leaf commitments
+//!
+//! In a redactable document every content block, and every table cell, is
+//! committed to individually:
+//!
+//! ```text
+//! cell  = SHA-256("tdf-redaction-v1 cell"  || salt || canonical(cell))
+//! block = SHA-256("tdf-redaction-v1 block" || salt || canonical(block'))
+//! ```
+//!
+//! where `block'` is the block with each table cell replaced by its cell
+//! commitment (a row's `_row` format is part of the block, not a cell),
+//! and salts are random 32-byte values stored in
+//! `redaction.cbor`. The Merkle "content" leaf is the canonical skeleton of
+//! the sections with their block commitments instead of the raw content.
+//!
+//! Redacting a block (or cell) replaces it with a placeholder holding its
+//! commitment and drops its salt. The skeleton, and therefore the Merkle
+//! root and every signature, is unchanged, while verification proves that
+//! the remaining content is exactly what was signed. Salts stop anyone from
+//! confirming a guess of a redacted value (e.g. a salary) by hashing it.
+//!
+//! Commitments are computed over the generic CBOR value of the content, so
+//! they do not depend on how typed structures round-trip.
+
+use crate::content::ROW_FORMAT_KEY;
+use crate::error::{TdfError, TdfResult};
+use crate::secure_random::generate_secure_bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Current redaction scheme version
+pub const REDACTION_VERSION: u8 = 1;
+/// Archive entry holding the salts of unredacted blocks and cells
+pub const REDACTION_FILE: &str = "redaction.cbor";
+
+const SALT_LEN: usize = 32;
+const BLOCK_DOMAIN: &[u8] = b"tdf-redaction-v1 block";
+const CELL_DOMAIN: &[u8] = b"tdf-redaction-v1 cell";
+const REDACTED_BLOCK_TYPE: &str = "redacted";
+const REDACTED_CELL_KEY: &str = "redacted";
+
+/// Redaction marker stored in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RedactionInfo {
+    pub version: u8,
+}
+
+impl Default for RedactionInfo {
+    fn default() -> Self {
+        RedactionInfo { version: REDACTION_VERSION }
+    }
+}
+
+/// Salts of the blocks and cells that are still present
+///
+/// Blocks are keyed by position (`<section id>/<block index>`), cells by
+/// `<block key>/<row index>/<column id>`. Positions are stable because
+/// redacted blocks keep their place as placeholders.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RedactionSalts {
+    pub version: u8,
+    /// Hex salts of blocks
+    pub blocks: BTreeMap<String, String>,
+    /// Hex salts of table cells
+    pub cells: BTreeMap<String, String>,
+}
+
+/// A block or cell to redact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedactionTarget {
+    /// Block id, or `<section id>/<block index>` for blocks without an id
+    Block(String),
+    /// Cell of a table block, by row index and column id
+    Cell { table: String, row: usize, column: String },
+}
+
+impl std::str::FromStr for RedactionTarget {
+    type Err = TdfError;
+
+    /// Parse `<table>/<row>/<column>` as a cell and anything else as a block
+    fn from_str(s: &str) -> TdfResult<Self> {
+        let parts: Vec<&str> = s.rsplitn(3, '/').collect();
+        if let [column, row, table] = parts[..] {
+            if let Ok(row) = row.parse() {
+                return Ok(RedactionTarget::Cell { table: table.to_string(), row, column: column.to_string() });
+            }
+        }
+        Ok(RedactionTarget::Block(s.to_string()))
+    }
+}
+
+/// Content leaf and redaction details of a redactable document
+#[derive(Debug, Clone)]
+pub struct ContentCommitment {
+    /// Canonical skeleton hashed as the Merkle "content" leaf
+    pub leaf: Vec<u8>,
+    /// Ids of redacted blocks and cells (`<table>/<row>/<column>`)
+    pub redacted: Vec<String>,
+}
+
+fn redaction_error(msg: impl Into<String>) -> TdfError {
+    TdfError::InvalidDocument(format!("Redaction: {}", msg.into()))
+}
+
+fn canonical(value: &Value) -> TdfResult<Vec<u8>> {
+    // serde_json maps are sorted by key, giving a canonical encoding
+    Ok(serde_json::to_vec(value)?)
+}
+
+fn commit(domain: &[u8], salt_hex: &str, value: &Value) -> TdfResult<String> {
+    let salt = hex::decode(salt_hex).map_err(|_| redaction_error("invalid salt"))?;
+    if salt.len() != SALT_LEN {
+        return Err(redaction_error("salt must be 32 bytes"));
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    hasher.update(&salt);
+    hasher.update(canonical(value)?);
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn check_commitment(commitment: &Value) -> TdfResult<String> {
+    let commitment = commitment.as_str().ok_or_else(|| redaction_error("commitment must be a string"))?;
+    match hex::decode(commitment) {
+        Ok(bytes) if bytes.len() == 32 => Ok(commitment.to_string()),
+        _ => Err(redaction_error("commitment must be 32 hex-encoded bytes")),
+    }
+}
+
+fn new_salt() -> TdfResult<String> {
+    Ok(hex::encode(generate_secure_bytes(SALT_LEN)?))
+}
+
+/// Sections of a content value
+fn sections(content: &Value) -> TdfResult<&Vec<Value>> {
+    content
+        .get("sections")
+        .and_then(Value::as_array)
+        .ok_or_else(|| redaction_error("content has no sections"))
+}
+
+fn sections_mut(content: &mut Value) -> TdfResult<&mut Vec<Value>> {
+    content
+        .get_mut("sections")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| redaction_error("content has no sections"))
+}
+
+fn section_id(section: &Value) -> TdfResult<&str> {
+    section.get("id").and_then(Value::as_str).ok_or_else(|| redaction_error("section without id"))
+}
+
+fn blocks(section: &Value) -> &[Value] {
+    section.get("content").and_then(Value::as_array).map(Vec::as_slice).unwrap_or(&[])
+}
+
+fn block_type(block: &Value) -> Option<&str> {
+    block.get("type").and_then(Value::as_str)
+}
+
+fn block_id(block: &Value) -> Option<&str> {
+    block.get("id").and_then(Value::as_str)
+}
+
+fn is_redacted_cell(cell: &Value) -> bool {
+    cell.as_object().is_some_and(|o| o.len() == 1 && o.contains_key(REDACTED_CELL_KEY))
+}
+
+/// Table rows of a block as `(row index, row object)`; the row objects
+/// include the row format under `ROW_FORMAT_KEY`
+fn rows(block: &Value) -> impl Iterator<Item = (usize, &Map<String, Value>)> {
+    block
+        .get("rows")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(i, row)| row.as_object().map(|row| (i, row)))
+}
+
+impl RedactionSalts {
+    /// Fresh salts for every block and table cell of a content value
+    pub fn generate(content: &Value) -> TdfResult<Self> {
+        let mut salts = RedactionSalts { version: REDACTION_VERSION, ..Default::default() };
+        for section in sections(content)? {
+            let section_id = section_id(section)?;
+            for (index, block) in blocks(section).iter().enumerate() {
+                if block_type(block) == Some(REDACTED_BLOCK_TYPE) {
+                    continue;
+                }
+                let key = format!("{}/{}", section_id, index);
+                for (row, cells) in rows(block) {
+                    for (column, cell) in cells.iter().filter(|(column, _)| *column != ROW_FORMAT_KEY) {
+                        if !is_redacted_cell(cell) {
+                            salts.cells.insert(format!("{}/{}/{}", key, row, column), new_salt()?);
+                        }
+                    }
+                }
+                salts.blocks.insert(key, new_salt()?);
+            }
+        }
+        Ok(salts)
+    }
+
+    fn block_salt(&self, key: &str) -> TdfResult<&str> {
+        self.blocks
+            .get(key)
+            .map(String::as_str)
+            .ok_or_else(|| redaction_error(format!("missing salt for block {}", key)))
+    }
+
+    fn cell_salt(&self, key: &str) -> TdfResult<&str> {
+        self.cells
+            .get(key)
+            .map(String::as_str)
+            .ok_or_else(|| redaction_error(format!("missing salt for cell {}", key)))
+    }
+}
+
+/// Commitment of one block; collects redacted cell ids into `redacted`
+fn block_commitment(
+    block: &Value,
+    key: &str,
+    salts: &RedactionSalts,
+    redacted: &mut Vec<String>,
+) -> TdfResult<String> {
+    if block_type(block) == Some(REDACTED_BLOCK_TYPE) {
+        redacted.push(block_id(block).unwrap_or(key).to_string());
+        return check_commitment(block.get("commitment").unwrap_or(&Value::Null));
+    }
+
+    let mut committed = block.clone();
+    if let Some(rows) = committed.get_mut("rows").and_then(Value::as_array_mut) {
+        let table = block_id(block).unwrap_or(key).to_string();
+        for (row_index, row) in rows.iter_mut().enumerate() {
+            let Some(cells) = row.as_object_mut() else { continue };
+            for (column, cell) in cells.iter_mut().filter(|(column, _)| *column != ROW_FORMAT_KEY) {
+                let commitment = if is_redacted_cell(cell) {
+                    redacted.push(format!("{}/{}/{}", table, row_index, column));
+                    check_commitment(&cell[REDACTED_CELL_KEY])?
+                } else {
+                    let salt = salts.cell_salt(&format!("{}/{}/{}", key, row_index, column))?;
+                    commit(CELL_DOMAIN, salt, cell)?
+                };
+                *cell = Value::String(commitment);
+            }
+        }
+    }
+    commit(BLOCK_DOMAIN, salts.block_salt(key)?, &committed)
+}
+
+/// Compute the Merkle content leaf of a redactable document
+///
+/// # Arguments
+/// * `content` - Content as a generic value (decoded from `content.cbor`)
+/// * `salts` - Salts of the blocks and cells that are present
+///
+/// # Returns
+/// * The canonical skeleton (sections with block commitments) and the
+///   redacted block and cell ids
+pub fn content_commitment(content: &Value, salts: &RedactionSalts) -> TdfResult<ContentCommitment> {
+    let mut redacted = Vec::new();
+    let mut skeleton = Vec::new();
+
+    for section in sections(content)? {
+        let section_id = section_id(section)?;
+        let mut commitments = Vec::new();
+        for (index, block) in blocks(section).iter().enumerate() {
+            let key = format!("{}/{}", section_id, index);
+            commitments.push(Value::String(block_commitment(block, &key, salts, &mut redacted)?));
+        }
+
+        let mut entry = section.as_object().cloned().unwrap_or_default();
+        entry.remove("content");
+        entry.insert("blocks".to_string(), Value::Array(commitments));
+        skeleton.push(Value::Object(entry));
+    }
+
+    let mut root = Map::new();
+    root.insert("scheme".to_string(), Value::String(format!("tdf-redaction-v{}", REDACTION_VERSION)));
+    root.insert("sections".to_string(), Value::Array(skeleton));
+    Ok(ContentCommitment { leaf: canonical(&Value::Object(root))?, redacted })
+}
+
+/// Decode `content.cbor` as a generic value
+pub fn content_value(content_cbor: &[u8]) -> TdfResult<Value> {
+    ciborium::from_reader(content_cbor).map_err(|e| TdfError::ParseError(format!("CBOR: {}", e)))
+}
+
+fn to_cbor<T: Serialize>(value: &T) -> TdfResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    ciborium::into_writer(value, &mut buf).map_err(|e| TdfError::ParseError(format!("CBOR: {}", e)))?;
+    Ok(buf)
+}
+
+/// Position of a block matching a target id (`id` field or positional key)
+fn find_block(content: &Value, target: &str) -> TdfResult<(usize, usize, String)> {
+    for (s, section) in sections(content)?.iter().enumerate() {
+        let section_id = section_id(section)?;
+        for (b, block) in blocks(section).iter().enumerate() {
+            let key = format!("{}/{}", section_id, b);
+            if block_id(block) == Some(target) || key == target {
+                return Ok((s, b, key));
+            }
+        }
+    }
+    Err(redaction_error(format!("no block '{}'", target)))
+}
+
+/// Redact blocks and cells of a content value in place
+///
+/// # Returns
+/// * Ids of the newly redacted blocks and cells
+pub fn redact_content(
+    content: &mut Value,
+    salts: &mut RedactionSalts,
+    targets: &[RedactionTarget],
+) -> TdfResult<Vec<String>> {
+    let mut redacted = Vec::new();
+
+    for target in targets {
+        match target {
+            RedactionTarget::Block(id) => {
+                let (s, b, key) = find_block(content, id)?;
+                let block = &mut sections_mut(content)?[s]["content"][b];
+                if block_type(block) == Some(REDACTED_BLOCK_TYPE) {
+                    return Err(redaction_error(format!("block '{}' is already redacted", id)));
+                }
+                let commitment = block_commitment(block, &key, salts, &mut Vec::new())?;
+                let label = block_id(block).unwrap_or(&key).to_string();
+
+                let mut placeholder = Map::new();
+                placeholder.insert("type".to_string(), Value::String(REDACTED_BLOCK_TYPE.to_string()));
+                placeholder.insert("id".to_string(), Value::String(label.clone()));
+                placeholder.insert("commitment".to_string(), Value::String(commitment));
+                *block = Value::Object(placeholder);
+
+                salts.blocks.remove(&key);
+                let cell_prefix = format!("{}/", key);
+                salts.cells.retain(|k, _| !k.starts_with(&cell_prefix));
+                redacted.push(label);
+            }
+            RedactionTarget::Cell { table, row, column } => {
+                let (s, b, key) = find_block(content, table)?;
+                let cell_key = format!("{}/{}/{}", key, row, column);
+                let cell = sections_mut(content)?[s]["content"][b]
+                    .get_mut("rows")
+                    .and_then(|rows| rows.get_mut(*row))
+                    .filter(|_| column != ROW_FORMAT_KEY)
+                    .and_then(|row| row.get_mut(column.as_str()))
+                    .ok_or_else(|| redaction_error(format!("no cell {}/{}/{}", table, row, column)))?;
+                if is_redacted_cell(cell) {
+                    return Err(redaction_error(format!("cell {}/{}/{} is already redacted", table, row, column)));
+                }
+                let commitment = commit(CELL_DOMAIN, salts.cell_salt(&cell_key)?, cell)?;
+
+                let mut placeholder = Map::new();
+                placeholder.insert(REDACTED_CELL_KEY.to_string(), Value::String(commitment));
+                *cell = Value::Object(placeholder);
+
+                salts.cells.remove(&cell_key);
+                redacted.push(format!("{}/{}/{}", table, row, column));
+            }
+        }
+    }
+    Ok(redacted)
+}
+
+/// Redact blocks and cells of a signed archive without invalidating its signatures
+///
+/// Only `content.cbor` and `redaction.cbor` are rewritten; the manifest,
+/// Merkle tree, signatures and other entries are copied unchanged. The
+/// content leaf is recomputed to check that the root still matches.
+///
+/// # Returns
+/// * Ids of the redacted blocks and cells
+/// * `Err` if the document is not redactable or a target does not exist
+pub fn redact_archive(input: &Path, output: &Path, targets: &[RedactionTarget]) -> TdfResult<Vec<String>> {
+    if targets.is_empty() {
+        return Err(redaction_error("nothing to redact"));
+    }
+
+    let mut zip = zip::ZipArchive::new(std::fs::File::open(input)?)?;
+    let read = |zip: &mut zip::ZipArchive<std::fs::File>, name: &str| -> TdfResult<Vec<u8>> {
+        let mut file = zip.by_name(name).map_err(|_| TdfError::MissingFile(name.to_string()))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    };
+
+    let manifest: crate::document::Manifest = ciborium::from_reader(read(&mut zip, "manifest.cbor")?.as_slice())
+        .map_err(|e| TdfError::ParseError(format!("CBOR: {}", e)))?;
+    if manifest.redaction.is_none() {
+        return Err(redaction_error("document was not created as redactable"));
+    }
+    if manifest.encryption.is_some() {
+        return Err(redaction_error("encrypted documents must be decrypted before redaction"));
+    }
+
+    let mut content = content_value(&read(&mut zip, "content.cbor")?)?;
+    let mut salts: RedactionSalts = ciborium::from_reader(read(&mut zip, REDACTION_FILE)?.as_slice())
+        .map_err(|e| TdfError::ParseError(format!("CBOR: {}", e)))?;
+
+    let before = content_commitment(&content, &salts)?;
+    let redacted = redact_content(&mut content, &mut salts, targets)?;
+    let after = content_commitment(&content, &salts)?;
+    if before.leaf != after.leaf {
+        return Err(TdfError::IntegrityFailure("Redaction changed the content commitment".to_string()));
+    }
+
+    let mut writer = zip::ZipWriter::new(std::fs::File::create(output)?);
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+    for i in 0..zip.len() {
+        let file = zip.by_index(i)?;
+        match file.name() {
+            "content.cbor" => {
+                drop(file);
+                writer.start_file("content.cbor", options)?;
+                writer.write_all(&to_cbor(&content)?)?;
+            }
+            REDACTION_FILE => {
+                drop(file);
+                writer.start_file(REDACTION_FILE, options)?;
+                writer.write_all(&to_cbor(&salts)?)?;
+            }
+            _ => writer.raw_copy_file(file)?,
+        }
+    }
+    writer.finish()?;
+    Ok(redacted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_content() -> Value {
+        json!({
+            "sections": [{
+                "id": "s1",
+                "title": "Payroll",
+                "content": [
+                    { "type": "paragraph", "text": "Public summary", "id": "p1" },
+                    { "type": "paragraph", "text": "Jane Doe, 123 Main St" },
+                    { "type": "table", "id": "salaries", "columns": [], "rows": [
+                        { "name": "Jane", "salary": { "raw": 120000.0, "display": "120,000" } },
+                        { "name": "John", "salary": { "raw": 95000.0, "display": "95,000" } }
+                    ]}
+                ]
+            }]
+        })
+    }
+
+    #[test]
+    fn test_redaction_preserves_commitment() {
+        let mut content = sample_content();
+        let mut salts = RedactionSalts::generate(&content).unwrap();
+        let before = content_commitment(&content, &salts).unwrap();
+        assert!(before.redacted.is_empty());
+
+        let targets = [
+            RedactionTarget::Block("s1/1".to_string()),
+            "salaries/0/salary".parse().unwrap(),
+        ];
+        let redacted = redact_content(&mut content, &mut salts, &targets).unwrap();
+        assert_eq!(redacted, ["s1/1", "salaries/0/salary"]);
+
+        let after = content_commitment(&content, &salts).unwrap();
+        assert_eq!(before.leaf, after.leaf);
+        assert_eq!(after.redacted, ["s1/1", "salaries/0/salary"]);
+        assert!(!serde_json::to_string(&content).unwrap().contains("Main St"));
+        assert!(!salts.blocks.contains_key("s1/1"));
+    }
+
+    #[test]
+    fn test_modified_content_changes_commitment() {
+        let mut content = sample_content();
+        let salts = RedactionSalts::generate(&content).unwrap();
+        let before = content_commitment(&content, &salts).unwrap();
+
+        content["sections"][0]["content"][0]["text"] = json!("Altered summary");
+        assert_ne!(before.leaf, content_commitment(&content, &salts).unwrap().leaf);
+    }
+
+    #[test]
+    fn test_forged_placeholder_changes_commitment() {
+        let mut content = sample_content();
+        let salts = RedactionSalts::generate(&content).unwrap();
+        let before = content_commitment(&content, &salts).unwrap();
+
+        content["sections"][0]["content"][0] =
+            json!({ "type": "redacted", "id": "p1", "commitment": hex::encode([0u8; 32]) });
+        assert_ne!(before.leaf, content_commitment(&content, &salts).unwrap().leaf);
+    }
+
+    #[test]
+    fn test_unknown_and_repeated_targets_rejected() {
+        let mut content = sample_content();
+        let mut salts = RedactionSalts::generate(&content).unwrap();
+        assert!(redact_content(&mut content, &mut salts, &[RedactionTarget::Block("nope".to_string())]).is_err());
+
+        let target = [RedactionTarget::Block("p1".to_string())];
+        redact_content(&mut content, &mut salts, &target).unwrap();
+        assert!(redact_content(&mut content, &mut salts, &target).is_err());
+    }
+
+    #[test]
+    fn test_row_format_is_committed_with_its_block() {
+        let mut content = sample_content();
+        content["sections"][0]["content"][2]["rows"][1]["_row"] = json!({ "role": "total" });
+        let mut salts = RedactionSalts::generate(&content).unwrap();
+        assert!(!salts.cells.keys().any(|key| key.ends_with("/_row")));
+        let before = content_commitment(&content, &salts).unwrap();
+
+        let target = ["salaries/1/_row".parse().unwrap()];
+        assert!(redact_content(&mut content, &mut salts, &target).is_err());
+        redact_content(&mut content, &mut salts, &["salaries/1/salary".parse().unwrap()]).unwrap();
+        assert_eq!(before.leaf, content_commitment(&content, &salts).unwrap().leaf);
+
+        content["sections"][0]["content"][2]["rows"][1]["_row"] = json!({ "role": "subtotal" });
+        assert_ne!(before.leaf, content_commitment(&content, &salts).unwrap().leaf);
+    }
+
+    #[test]
+    fn test_target_parsing() {
+        assert_eq!(
+            "t/2/amount".parse::<RedactionTarget>().unwrap(),
+            RedactionTarget::Cell { table: "t".to_string(), row: 2, column: "amount".to_string() }
+        );
+        assert_eq!("p-1".parse::<RedactionTarget>().unwrap(), RedactionTarget::Block("p-1".to_string()));
+        assert_eq!("s1/3".parse::<RedactionTarget>().unwrap(), RedactionTarget::Block("s1/3".to_string()));
+    }
+}
@@ -0,0 +1,428 @@
+//! Remote signing protocol (client and reference server)
+//!
+//! A minimal JSON-over-HTTP protocol for signing services that hold keys
+//! outside the document tooling (HSM-backed signing hosts, cloud KMS proxies).
+//!
+//! ```text
+//! POST /v1/sign
+//! Authorization: Bearer <token>          (if the server requires one)
+//! { "payload": "<base64 v2 signing payload>" }
+//!
+//! 200 OK
+//! { "algorithm": "ed25519", "signature": "<base64>", "public_key": "<base64>" }
+//! ```
+//!
+//! The server only signs 32-byte payloads (the SHA-256 v2 signing payload),
+//! and the client verifies every returned signature before using it. The
+//! client only sends its bearer token over HTTPS, or plain HTTP to a
+//! loopback address.
+
+use crate::error::{TdfError, TdfResult};
+use crate::signature::{SignatureAlgorithm, SignerOutput};
+#[cfg(feature = "remote-signer")]
+use crate::signature::DocumentSigner;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+/// Protocol path for signing requests
+pub const SIGN_PATH: &str = "/v1/sign";
+
+/// Size of the v2 signing payload (SHA-256 digest)
+pub const SIGNING_PAYLOAD_SIZE: usize = 32;
+
+/// Maximum size of a request or response body
+pub const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignRequest {
+    /// Base64-encoded signing payload
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignResponse {
+    pub algorithm: SignatureAlgorithm,
+    /// Base64-encoded signature
+    pub signature: String,
+    /// Base64-encoded public key
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+impl SignResponse {
+    pub fn from_output(output: &SignerOutput) -> Self {
+        SignResponse {
+            algorithm: output.algorithm.clone(),
+            signature: STANDARD.encode(&output.signature),
+            public_key: STANDARD.encode(&output.public_key),
+        }
+    }
+
+    pub fn into_output(self) -> TdfResult<SignerOutput> {
+        Ok(SignerOutput {
+            algorithm: self.algorithm,
+            signature: STANDARD
+                .decode(&self.signature)
+                .map_err(|e| TdfError::SignerError(format!("Invalid base64 signature: {}", e)))?,
+            public_key: STANDARD
+                .decode(&self.public_key)
+                .map_err(|e| TdfError::SignerError(format!("Invalid base64 public key: {}", e)))?,
+        })
+    }
+}
+
+/// Client for a remote signing service
+#[cfg(feature = "remote-signer")]
+pub struct RemoteSigner {
+    url: String,
+    bearer_token: Option<zeroize::Zeroizing<String>>,
+    expected_public_key: Option<Vec<u8>>,
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "remote-signer")]
+impl RemoteSigner {
+    /// Create a client for the service at `base_url` (e.g. `https://signer.internal:8443`)
+    pub fn new(base_url: impl Into<String>) -> Self {
+        RemoteSigner {
+            url: format!("{}{}", base_url.into().trim_end_matches('/'), SIGN_PATH),
+            bearer_token: None,
+            expected_public_key: None,
+            agent: ureq::AgentBuilder::new()
+                .timeout(std::time::Duration::from_secs(30))
+                .redirects(0)
+                .build(),
+        }
+    }
+
+    /// Authenticate with a bearer token; it is only sent over HTTPS, or plain
+    /// HTTP to a loopback address
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(zeroize::Zeroizing::new(token.into()));
+        self
+    }
+
+    /// Pin the public key the service must sign with
+    pub fn with_expected_public_key(mut self, public_key: Vec<u8>) -> Self {
+        self.expected_public_key = Some(public_key);
+        self
+    }
+}
+
+/// Whether a bearer token sent to `url` stays off the network in clear text
+#[cfg(feature = "remote-signer")]
+fn is_protected_url(url: &str) -> bool {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return false;
+    };
+    if scheme.eq_ignore_ascii_case("https") {
+        return true;
+    }
+    if !scheme.eq_ignore_ascii_case("http") {
+        return false;
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    let host = match host_port.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+#[cfg(feature = "remote-signer")]
+impl DocumentSigner for RemoteSigner {
+    fn sign_payload(&self, payload: &[u8]) -> TdfResult<SignerOutput> {
+        use std::io::Read;
+
+        if self.bearer_token.is_some() && !is_protected_url(&self.url) {
+            return Err(TdfError::SignerError(format!(
+                "Refusing to send the bearer token to {} (use https, or http only to a loopback address)",
+                self.url
+            )));
+        }
+
+        let body = serde_json::to_string(&SignRequest { payload: STANDARD.encode(payload) })?;
+        let mut request = self.agent.post(&self.url).set("Content-Type", "application/json");
+        if let Some(ref token) = self.bearer_token {
+            request = request.set("Authorization", &format!("Bearer {}", token.as_str()));
+        }
+
+        let response = match request.send_string(&body) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(code, response)) => {
+                let message = response
+                    .into_string()
+                    .ok()
+                    .and_then(|body| serde_json::from_str::<ErrorResponse>(&body).ok())
+                    .map(|e| e.error)
+                    .unwrap_or_default();
+                return Err(TdfError::SignerError(format!(
+                    "Remote signer returned HTTP {}: {}",
+                    code, message
+                )));
+            }
+            Err(e) => {
+                return Err(TdfError::SignerError(format!("Remote signer unreachable: {}", e)));
+            }
+        };
+
+        let mut data = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_MESSAGE_SIZE as u64 + 1)
+            .read_to_end(&mut data)?;
+        if data.len() > MAX_MESSAGE_SIZE {
+            return Err(TdfError::SizeExceeded("Remote signer response too large".to_string()));
+        }
+
+        let output = serde_json::from_slice::<SignResponse>(&data)?.into_output()?;
+        if let Some(ref expected) = self.expected_public_key {
+            if &output.public_key != expected {
+                return Err(TdfError::SignerError(
+                    "Remote signer used an unexpected public key".to_string(),
+                ));
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Reference remote signing server
+///
+/// Wraps any `DocumentSigner` (typically an in-memory key or a PKCS#11
+/// token) and serves the signing protocol. Intended for development and as
+/// a reference for production implementations; it serves plain HTTP, so put
+/// it behind a TLS-terminating proxy outside of localhost.
+#[cfg(feature = "remote-signer")]
+pub struct RemoteSignerServer {
+    server: tiny_http::Server,
+    signer: Box<dyn DocumentSigner>,
+    bearer_token: Option<zeroize::Zeroizing<String>>,
+}
+
+#[cfg(feature = "remote-signer")]
+impl RemoteSignerServer {
+    /// Bind the server (use port 0 to pick a free port)
+    pub fn bind(addr: &str, signer: Box<dyn DocumentSigner>) -> TdfResult<Self> {
+        let server = tiny_http::Server::http(addr)
+            .map_err(|e| TdfError::SignerError(format!("Failed to bind {}: {}", addr, e)))?;
+        Ok(RemoteSignerServer { server, signer, bearer_token: None })
+    }
+
+    /// Require `Authorization: Bearer <token>` on every request
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(zeroize::Zeroizing::new(token.into()));
+        self
+    }
+
+    pub fn local_addr(&self) -> TdfResult<std::net::SocketAddr> {
+        self.server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| TdfError::SignerError("Server is not bound to an IP address".to_string()))
+    }
+
+    /// Serve requests until `unblock` is called
+    pub fn serve(&self) {
+        for request in self.server.incoming_requests() {
+            self.handle(request);
+        }
+    }
+
+    /// Stop a running `serve` loop
+    pub fn unblock(&self) {
+        self.server.unblock();
+    }
+
+    fn authorized(&self, request: &tiny_http::Request) -> bool {
+        use subtle::ConstantTimeEq;
+
+        let Some(ref token) = self.bearer_token else {
+            return true;
+        };
+        let expected = format!("Bearer {}", token.as_str());
+        request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .map(|h| bool::from(h.value.as_str().as_bytes().ct_eq(expected.as_bytes())))
+            .unwrap_or(false)
+    }
+
+    fn handle(&self, mut request: tiny_http::Request) {
+        use std::io::Read;
+
+        let result = if *request.method() != tiny_http::Method::Post || request.url() != SIGN_PATH {
+            Err((404, "Not found".to_string()))
+        } else if !self.authorized(&request) {
+            Err((401, "Unauthorized".to_string()))
+        } else {
+            let mut body = Vec::new();
+            match request
+                .as_reader()
+                .take(MAX_MESSAGE_SIZE as u64 + 1)
+                .read_to_end(&mut body)
+            {
+                Ok(_) if body.len() <= MAX_MESSAGE_SIZE => self.sign_request(&body),
+                Ok(_) => Err((413, "Request too large".to_string())),
+                Err(e) => Err((400, e.to_string())),
+            }
+        };
+
+        let (status, body) = match result {
+            Ok(response) => (200, serde_json::to_vec(&response)),
+            Err((status, error)) => (status, serde_json::to_vec(&ErrorResponse { error })),
+        };
+        let header = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("static header is valid");
+        let response = tiny_http::Response::from_data(body.unwrap_or_default())
+            .with_status_code(status)
+            .with_header(header);
+        // The client may have disconnected; nothing to do in that case
+        let _ = request.respond(response);
+    }
+
+    fn sign_request(&self, body: &[u8]) -> Result<SignResponse, (u16, String)> {
+        let request: SignRequest =
+            serde_json::from_slice(body).map_err(|e| (400, format!("Invalid request: {}", e)))?;
+        let payload = STANDARD
+            .decode(&request.payload)
+            .map_err(|e| (400, format!("Invalid base64 payload: {}", e)))?;
+        if payload.len() != SIGNING_PAYLOAD_SIZE {
+            return Err((
+                400,
+                format!("Payload must be {} bytes, got {}", SIGNING_PAYLOAD_SIZE, payload.len()),
+            ));
+        }
+        // Do not leak signer internals (token paths, slot IDs) to clients
+        let output = self
+            .signer
+            .sign_payload(&payload)
+            .map_err(|e| (500, crate::error_sanitization::sanitize_error(&e)))?;
+        Ok(SignResponse::from_output(&output))
+    }
+}
+
+#[cfg(all(test, feature = "remote-signer"))]
+mod tests {
+    use super::*;
+    use crate::signature::{SignatureManager, SignatureScope};
+    use ed25519_dalek::SigningKey;
+    use k256::ecdsa::SigningKey as Secp256k1SigningKey;
+    use rand::rngs::OsRng;
+    use std::sync::Arc;
+
+    const ROOT_HASH: &[u8] = b"test_root_hash_for_signing_12345";
+
+    fn start(server: RemoteSignerServer) -> (Arc<RemoteSignerServer>, String, std::thread::JoinHandle<()>) {
+        let server = Arc::new(server);
+        let url = format!("http://{}", server.local_addr().unwrap());
+        let background = Arc::clone(&server);
+        let handle = std::thread::spawn(move || background.serve());
+        (server, url, handle)
+    }
+
+    #[test]
+    fn test_remote_signer_round_trip() {
+        let key = SigningKey::generate(&mut OsRng);
+        let verifying_key = key.verifying_key();
+        let server = RemoteSignerServer::bind("127.0.0.1:0", Box::new(key))
+            .unwrap()
+            .with_bearer_token("secret");
+        let (server, url, handle) = start(server);
+
+        let signer = RemoteSigner::new(&url)
+            .with_bearer_token("secret")
+            .with_expected_public_key(verifying_key.to_bytes().to_vec());
+        let (signature, _) = SignatureManager::sign_with_signer(
+            &signer,
+            ROOT_HASH,
+            "did:web:cfo.acme.com".to_string(),
+            "CFO".to_string(),
+            SignatureScope::Full,
+            None,
+        )
+        .unwrap();
+        assert!(SignatureManager::verify_ed25519(&signature, ROOT_HASH, &verifying_key).unwrap());
+
+        server.unblock();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_remote_signer_secp256k1() {
+        let key = Secp256k1SigningKey::random(&mut OsRng);
+        let verifying_key = *key.verifying_key();
+        let server = RemoteSignerServer::bind("127.0.0.1:0", Box::new(key)).unwrap();
+        let (server, url, handle) = start(server);
+
+        let (signature, _) = SignatureManager::sign_with_signer(
+            &RemoteSigner::new(&url),
+            ROOT_HASH,
+            "did:key:z".to_string(),
+            "Signer".to_string(),
+            SignatureScope::Full,
+            None,
+        )
+        .unwrap();
+        assert!(SignatureManager::verify_secp256k1(&signature, ROOT_HASH, &verifying_key).unwrap());
+
+        server.unblock();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_remote_signer_rejects_bad_token_and_pinned_key() {
+        let key = SigningKey::generate(&mut OsRng);
+        let server = RemoteSignerServer::bind("127.0.0.1:0", Box::new(key))
+            .unwrap()
+            .with_bearer_token("secret");
+        let (server, url, handle) = start(server);
+
+        let payload = [7u8; SIGNING_PAYLOAD_SIZE];
+        let err = RemoteSigner::new(&url)
+            .with_bearer_token("wrong")
+            .sign_payload(&payload)
+            .unwrap_err();
+        assert!(err.to_string().contains("401"));
+
+        let other = SigningKey::generate(&mut OsRng).verifying_key();
+        assert!(RemoteSigner::new(&url)
+            .with_bearer_token("secret")
+            .with_expected_public_key(other.to_bytes().to_vec())
+            .sign_payload(&payload)
+            .is_err());
+
+        // Arbitrary-length data is refused
+        assert!(RemoteSigner::new(&url)
+            .with_bearer_token("secret")
+            .sign_payload(b"not a signing payload")
+            .is_err());
+
+        server.unblock();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_bearer_token_not_sent_in_clear_to_remote_host() {
+        let err = RemoteSigner::new("http://signer.example.com:8080")
+            .with_bearer_token("secret")
+            .sign_payload(&[0u8; SIGNING_PAYLOAD_SIZE])
+            .unwrap_err();
+        assert!(err.to_string().contains("Refusing to send the bearer token"));
+
+        assert!(is_protected_url("https://signer.example.com"));
+        assert!(is_protected_url("http://127.0.0.1:8443/v1/sign"));
+        assert!(is_protected_url("http://[::1]:8443/v1/sign"));
+        assert!(is_protected_url("HTTP://localhost/v1/sign"));
+        assert!(!is_protected_url("http://localhost.example.com/v1/sign"));
+        assert!(!is_protected_url("http://127.0.0.1@signer.example.com/v1/sign"));
+        assert!(!is_protected_url("ftp://127.0.0.1/v1/sign"));
+    }
+}
@@ -0,0 +1,407 @@
+//! Revision history: successors linked to their predecessor by hash
+//!
+//! A revised document is a new document (with its own id) whose manifest
+//! records the predecessor's id, its Merkle root hash, the revision number
+//! and the reason for the change. Since the manifest is covered by the
+//! Merkle tree, the link is signed along with the new content, and a
+//! successor cannot be re-pointed at a different predecessor.
+//!
+//! `check_history` orders a set of revisions into chains and flags forks
+//! (two successors of one document), gaps (a predecessor missing from the
+//! set), cycles and links that do not match the predecessor that was found.
+
+use crate::document::{Document, Manifest};
+use crate::error::{TdfError, TdfResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// Link from a document to the revision it supersedes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RevisionInfo {
+    /// Revision number; the first version of a document is revision 1
+    pub number: u32,
+    pub previous_id: String,
+    /// Merkle root hash (hex) of the previous revision
+    pub previous_root_hash: String,
+    /// Why the document was revised
+    pub reason: String,
+}
+
+impl RevisionInfo {
+    /// Link to `previous`, which must have been built (its root hash is set)
+    pub fn successor_of(previous: &Manifest, reason: impl Into<String>) -> TdfResult<Self> {
+        if previous.integrity.root_hash.is_empty() {
+            return Err(TdfError::InvalidDocument(
+                "Previous revision has no root hash; read it from a built archive".to_string(),
+            ));
+        }
+        let reason = reason.into();
+        if reason.trim().is_empty() {
+            return Err(TdfError::InvalidDocument("A revision reason is required".to_string()));
+        }
+        Ok(RevisionInfo {
+            number: revision_number(previous)
+                .checked_add(1)
+                .ok_or_else(|| TdfError::InvalidDocument("Revision number overflow".to_string()))?,
+            previous_id: previous.document.id.clone(),
+            previous_root_hash: previous.integrity.root_hash.clone(),
+            reason,
+        })
+    }
+}
+
+/// Revision number of a document (1 when it has no predecessor)
+pub fn revision_number(manifest: &Manifest) -> u32 {
+    manifest.revision.as_ref().map(|r| r.number).unwrap_or(1)
+}
+
+/// Make `document` the successor of `previous`
+///
+/// A document that reuses the predecessor's id (e.g. one read back and
+/// edited) gets a fresh id, since every revision is a distinct document.
+///
+/// # Arguments
+/// * `document` - The new revision, before it is built
+/// * `previous` - Manifest of the verified previous revision
+/// * `reason` - Why the document was revised
+pub fn revise(document: &mut Document, previous: &Manifest, reason: impl Into<String>) -> TdfResult<()> {
+    let revision = RevisionInfo::successor_of(previous, reason)?;
+    if document.manifest.document.id == previous.document.id {
+        document.manifest.document.id = uuid::Uuid::new_v4().to_string();
+    }
+    document.manifest.document.modified = chrono::Utc::now();
+    document.manifest.revision = Some(revision);
+    Ok(())
+}
+
+/// One document in a set of revisions
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// Where the document came from (e.g. a file name)
+    pub label: String,
+    pub id: String,
+    /// Verified Merkle root hash (hex)
+    pub root_hash: String,
+    pub revision: Option<RevisionInfo>,
+}
+
+impl HistoryEntry {
+    /// Entry for a manifest, using the root hash it records
+    ///
+    /// Pass the root hash from `ArchiveReader::verify` instead when the
+    /// archive has not been verified.
+    pub fn from_manifest(label: impl Into<String>, manifest: &Manifest) -> Self {
+        HistoryEntry {
+            label: label.into(),
+            id: manifest.document.id.clone(),
+            root_hash: manifest.integrity.root_hash.clone(),
+            revision: manifest.revision.clone(),
+        }
+    }
+
+    pub fn number(&self) -> u32 {
+        self.revision.as_ref().map(|r| r.number).unwrap_or(1)
+    }
+}
+
+/// Problem found in a set of revisions
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryIssue {
+    /// Several documents claim the same predecessor
+    Fork { previous_id: String, successors: Vec<String> },
+    /// The predecessor of a document is not in the set
+    Gap { id: String, missing_id: String },
+    /// The predecessor's root hash differs from the one the successor signed
+    RootHashMismatch { id: String, previous_id: String },
+    /// Revision number is not one more than the predecessor's
+    RevisionNumber { id: String, expected: u32, found: u32 },
+    /// The predecessor already has the highest possible revision number
+    RevisionOverflow { id: String, previous_id: String },
+    /// Documents that are each other's predecessors (e.g. A → B → A)
+    Cycle { ids: Vec<String> },
+    /// The same document id appears in more than one file
+    DuplicateId { id: String, labels: Vec<String> },
+}
+
+impl fmt::Display for HistoryIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryIssue::Fork { previous_id, successors } => {
+                write!(f, "fork: {} has {} successors ({})", previous_id, successors.len(), successors.join(", "))
+            }
+            HistoryIssue::Gap { id, missing_id } => write!(f, "gap: predecessor {} of {} is missing", missing_id, id),
+            HistoryIssue::RootHashMismatch { id, previous_id } => {
+                write!(f, "{} links to a different version of {} (root hash mismatch)", id, previous_id)
+            }
+            HistoryIssue::RevisionNumber { id, expected, found } => {
+                write!(f, "{} is revision {} but should be {}", id, found, expected)
+            }
+            HistoryIssue::RevisionOverflow { id, previous_id } => {
+                write!(f, "{} cannot follow {}: revision number overflow", id, previous_id)
+            }
+            HistoryIssue::Cycle { ids } => write!(f, "cycle: {} → {}", ids.join(" → "), ids[0]),
+            HistoryIssue::DuplicateId { id, labels } => write!(f, "{} appears in {}", id, labels.join(", ")),
+        }
+    }
+}
+
+/// Result of `check_history`
+#[derive(Debug, Clone, Default)]
+pub struct HistoryReport {
+    /// Revision chains as document ids, oldest first; a fork yields one chain per branch
+    pub chains: Vec<Vec<String>>,
+    pub issues: Vec<HistoryIssue>,
+}
+
+impl HistoryReport {
+    /// A single chain without forks, gaps or mismatched links
+    pub fn is_linear(&self) -> bool {
+        self.issues.is_empty() && self.chains.len() == 1
+    }
+}
+
+/// Order a set of revisions into chains and check every link
+///
+/// # Returns
+/// * Chains starting at documents whose predecessor is not in the set,
+///   and the issues found
+pub fn check_history(entries: &[HistoryEntry]) -> HistoryReport {
+    let mut report = HistoryReport::default();
+
+    let mut by_id: BTreeMap<&str, Vec<&HistoryEntry>> = BTreeMap::new();
+    for entry in entries {
+        by_id.entry(&entry.id).or_default().push(entry);
+    }
+    for (id, same) in &by_id {
+        if same.len() > 1 {
+            report.issues.push(HistoryIssue::DuplicateId {
+                id: id.to_string(),
+                labels: same.iter().map(|e| e.label.clone()).collect(),
+            });
+        }
+    }
+    let by_id: HashMap<&str, &HistoryEntry> = by_id.into_iter().map(|(id, same)| (id, same[0])).collect();
+
+    let mut successors: BTreeMap<&str, Vec<&HistoryEntry>> = BTreeMap::new();
+    let mut roots = Vec::new();
+    let mut unique: Vec<&HistoryEntry> = by_id.values().copied().collect();
+    unique.sort_by(|a, b| (a.number(), &a.id).cmp(&(b.number(), &b.id)));
+
+    for &entry in &unique {
+        let Some(ref revision) = entry.revision else {
+            roots.push(entry);
+            continue;
+        };
+        match by_id.get(revision.previous_id.as_str()) {
+            Some(previous) => {
+                if previous.root_hash != revision.previous_root_hash {
+                    report.issues.push(HistoryIssue::RootHashMismatch {
+                        id: entry.id.clone(),
+                        previous_id: previous.id.clone(),
+                    });
+                }
+                match previous.number().checked_add(1) {
+                    Some(expected) if expected != revision.number => {
+                        report.issues.push(HistoryIssue::RevisionNumber {
+                            id: entry.id.clone(),
+                            expected,
+                            found: revision.number,
+                        });
+                    }
+                    Some(_) => {}
+                    None => report.issues.push(HistoryIssue::RevisionOverflow {
+                        id: entry.id.clone(),
+                        previous_id: previous.id.clone(),
+                    }),
+                }
+                successors.entry(&previous.id).or_default().push(entry);
+            }
+            None => {
+                report.issues.push(HistoryIssue::Gap {
+                    id: entry.id.clone(),
+                    missing_id: revision.previous_id.clone(),
+                });
+                roots.push(entry);
+            }
+        }
+    }
+
+    for (previous_id, next) in &successors {
+        if next.len() > 1 {
+            report.issues.push(HistoryIssue::Fork {
+                previous_id: previous_id.to_string(),
+                successors: next.iter().map(|e| e.id.clone()).collect(),
+            });
+        }
+    }
+
+    // Walk every root-to-leaf path (ids are unique, so links cannot cycle
+    // back to a root; the depth bound guards against malformed input)
+    let mut stack: Vec<Vec<String>> = roots.iter().map(|root| vec![root.id.clone()]).collect();
+    stack.reverse();
+    while let Some(chain) = stack.pop() {
+        let last = chain.last().expect("chains are never empty");
+        match successors.get(last.as_str()) {
+            Some(next) if chain.len() <= entries.len() => {
+                for entry in next.iter().rev() {
+                    let mut branch = chain.clone();
+                    branch.push(entry.id.clone());
+                    stack.push(branch);
+                }
+            }
+            _ => report.chains.push(chain),
+        }
+    }
+
+    // Documents in a cycle all have their predecessor in the set, so no
+    // root leads to them; follow predecessor links from each unreached one
+    let reached: HashSet<&str> = report.chains.iter().flatten().map(String::as_str).collect();
+    let mut in_cycle: HashSet<&str> = HashSet::new();
+    for &entry in &unique {
+        if reached.contains(entry.id.as_str()) || in_cycle.contains(entry.id.as_str()) {
+            continue;
+        }
+        let mut path: Vec<&str> = Vec::new();
+        let mut current = entry;
+        loop {
+            if let Some(start) = path.iter().position(|id| *id == current.id) {
+                let mut ids: Vec<String> = path[start..].iter().rev().map(|id| id.to_string()).collect();
+                let first = ids.iter().enumerate().min_by_key(|(_, id)| *id).map_or(0, |(i, _)| i);
+                ids.rotate_left(first);
+                in_cycle.extend(&path[start..]);
+                report.issues.push(HistoryIssue::Cycle { ids });
+                break;
+            }
+            if in_cycle.contains(current.id.as_str()) {
+                break;
+            }
+            path.push(&current.id);
+            match current.revision.as_ref().and_then(|r| by_id.get(r.previous_id.as_str())) {
+                Some(previous) => current = previous,
+                None => break,
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::DocumentContent;
+
+    fn built(title: &str) -> Document {
+        let mut document = Document::new(title.to_string(), "en".to_string(), DocumentContent { sections: vec![] }, String::new());
+        document.manifest.integrity.root_hash = hex::encode(title.as_bytes());
+        document
+    }
+
+    fn successor(previous: &Document, title: &str, reason: &str) -> Document {
+        let mut next = built(title);
+        revise(&mut next, &previous.manifest, reason).unwrap();
+        next
+    }
+
+    fn entry(document: &Document) -> HistoryEntry {
+        HistoryEntry::from_manifest(&document.manifest.document.title, &document.manifest)
+    }
+
+    #[test]
+    fn test_successor_links_to_predecessor() {
+        let v1 = built("v1");
+        let v2 = successor(&v1, "v2", "Corrected revenue figure");
+        let revision = v2.manifest.revision.as_ref().unwrap();
+        assert_eq!(revision.number, 2);
+        assert_eq!(revision.previous_id, v1.manifest.document.id);
+        assert_eq!(revision.previous_root_hash, v1.manifest.integrity.root_hash);
+        assert_eq!(revision_number(&successor(&v2, "v3", "Typo").manifest), 3);
+
+        let mut unbuilt = Document::new("x".to_string(), "en".to_string(), DocumentContent { sections: vec![] }, String::new());
+        assert!(RevisionInfo::successor_of(&unbuilt.manifest, "reason").is_err());
+        assert!(RevisionInfo::successor_of(&v1.manifest, " ").is_err());
+
+        // Reusing the predecessor's id gets a fresh one
+        unbuilt.manifest.document.id = v1.manifest.document.id.clone();
+        revise(&mut unbuilt, &v1.manifest, "Edited copy").unwrap();
+        assert_ne!(unbuilt.manifest.document.id, v1.manifest.document.id);
+    }
+
+    #[test]
+    fn test_linear_history() {
+        let v1 = built("v1");
+        let v2 = successor(&v1, "v2", "Restated");
+        let v3 = successor(&v2, "v3", "Restated again");
+
+        let report = check_history(&[entry(&v3), entry(&v1), entry(&v2)]);
+        assert!(report.is_linear(), "{:?}", report.issues);
+        let ids: Vec<_> = [&v1, &v2, &v3].iter().map(|d| d.manifest.document.id.clone()).collect();
+        assert_eq!(report.chains, vec![ids]);
+    }
+
+    #[test]
+    fn test_fork_gap_and_mismatch_detected() {
+        let v1 = built("v1");
+        let v2 = successor(&v1, "v2", "Restated");
+        let v2b = successor(&v1, "v2b", "Competing restatement");
+        let v3 = successor(&v2, "v3", "Restated again");
+
+        let report = check_history(&[entry(&v1), entry(&v2), entry(&v2b)]);
+        assert!(matches!(report.issues[..], [HistoryIssue::Fork { ref successors, .. }] if successors.len() == 2));
+        assert_eq!(report.chains.len(), 2);
+
+        let report = check_history(&[entry(&v1), entry(&v3)]);
+        assert!(matches!(report.issues[..], [HistoryIssue::Gap { ref missing_id, .. }] if *missing_id == v2.manifest.document.id));
+
+        let mut tampered = entry(&v1);
+        tampered.root_hash = "00".repeat(32);
+        let report = check_history(&[tampered, entry(&v2)]);
+        assert!(matches!(report.issues[..], [HistoryIssue::RootHashMismatch { .. }]));
+    }
+
+    fn linked(id: &str, number: u32, previous: &HistoryEntry) -> HistoryEntry {
+        HistoryEntry {
+            label: id.to_string(),
+            id: id.to_string(),
+            root_hash: hex::encode(id.as_bytes()),
+            revision: Some(RevisionInfo {
+                number,
+                previous_id: previous.id.clone(),
+                previous_root_hash: previous.root_hash.clone(),
+                reason: "Restated".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_revision_number_overflow_reported() {
+        let v1 = entry(&built("v1"));
+        let last = linked("last", u32::MAX, &v1);
+        let next = linked("next", u32::MAX, &last);
+
+        let report = check_history(&[v1, last, next]);
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| matches!(issue, HistoryIssue::RevisionOverflow { id, previous_id } if id == "next" && previous_id == "last")));
+    }
+
+    #[test]
+    fn test_cycle_reported() {
+        let placeholder = entry(&built("placeholder"));
+        let b = linked("b", 2, &placeholder);
+        let a = linked("a", 3, &b);
+        let b = linked("b", 2, &a);
+
+        let report = check_history(&[a, b]);
+        assert!(report.chains.is_empty());
+        assert!(report.issues.contains(&HistoryIssue::Cycle { ids: vec!["a".to_string(), "b".to_string()] }));
+        assert!(!report.is_linear());
+
+        let a = linked("a", 2, &placeholder);
+        let own = HistoryEntry { revision: Some(RevisionInfo { previous_id: "a".to_string(), ..a.revision.clone().unwrap() }), ..a };
+        let report = check_history(&[own]);
+        assert!(report.issues.contains(&HistoryIssue::Cycle { ids: vec!["a".to_string()] }));
+    }
+}
@@ -8,6 +8,7 @@
 use crate::error::{TdfError, TdfResult};
 use crate::timestamp::{create_timestamp_token, TimestampToken, TimestampProvider};
 use crate::revocation::RevocationManager;
+use crate::certificate::CertificateChain;
 use chrono::{DateTime, Utc};
 use ed25519_dalek::{Signature, Signer as Ed25519Signer, SigningKey, Verifier as Ed25519Verifier, VerifyingKey};
 use k256::ecdsa::{SigningKey as Secp256k1SigningKey, VerifyingKey as Secp256k1VerifyingKey, Signature as Secp256k1Signature};
@@ -214,6 +215,119 @@ pub enum SignatureAlgorithm {
     RsaPss,
 }
 
+/// Output of an external signing operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignerOutput {
+    pub algorithm: SignatureAlgorithm,
+    /// Ed25519: 64-byte signature. secp256k1: DER or 64-byte r||s
+    pub signature: Vec<u8>,
+    /// Ed25519: 32-byte key. secp256k1: SEC1-encoded key (compressed or uncompressed)
+    pub public_key: Vec<u8>,
+}
+
+/// A signer that never exposes its private key to this process
+///
+/// Implementations receive the v2 signing payload (see
+/// `compute_signing_payload`) and return the raw signature together with the
+/// algorithm and public key used. Hardware tokens (PKCS#11), remote signing
+/// services and in-memory keys all implement this trait.
+pub trait DocumentSigner: Send + Sync {
+    /// Sign the v2 signing payload
+    fn sign_payload(&self, payload: &[u8]) -> TdfResult<SignerOutput>;
+}
+
+impl DocumentSigner for SigningKey {
+    fn sign_payload(&self, payload: &[u8]) -> TdfResult<SignerOutput> {
+        let signature: Signature = self.sign(payload);
+        Ok(SignerOutput {
+            algorithm: SignatureAlgorithm::Ed25519,
+            signature: signature.to_bytes().to_vec(),
+            public_key: self.verifying_key().to_bytes().to_vec(),
+        })
+    }
+}
+
+impl DocumentSigner for Secp256k1SigningKey {
+    fn sign_payload(&self, payload: &[u8]) -> TdfResult<SignerOutput> {
+        use k256::ecdsa::signature::Signer;
+
+        let signature: Secp256k1Signature = self.sign(payload);
+        Ok(SignerOutput {
+            algorithm: SignatureAlgorithm::Secp256k1,
+            signature: signature.to_der().as_bytes().to_vec(),
+            public_key: self.verifying_key().to_sec1_bytes().to_vec(),
+        })
+    }
+}
+
+impl SignerOutput {
+    /// Check the signature against the returned public key and encode it
+    ///
+    /// External signers are not trusted to return a valid signature: a faulty
+    /// token or a compromised signing service must not produce a document
+    /// carrying an unverifiable signature. secp256k1 signatures are normalized
+    /// to low-S DER, the format `verify_secp256k1` expects.
+    ///
+    /// # Returns
+    /// * The signature bytes in the stored encoding
+    pub fn verify_and_encode(&self, payload: &[u8]) -> TdfResult<Vec<u8>> {
+        match self.algorithm {
+            SignatureAlgorithm::Ed25519 => {
+                let key: [u8; 32] = self.public_key.as_slice().try_into().map_err(|_| {
+                    TdfError::SignerError("Ed25519 public key must be 32 bytes".to_string())
+                })?;
+                let key = VerifyingKey::from_bytes(&key)
+                    .map_err(|e| TdfError::SignerError(format!("Invalid Ed25519 public key: {}", e)))?;
+                let sig = Signature::from_slice(&self.signature)
+                    .map_err(|e| TdfError::SignerError(format!("Invalid Ed25519 signature: {}", e)))?;
+                key.verify(payload, &sig).map_err(|e| {
+                    TdfError::SignerError(format!("Signer returned an invalid signature: {}", e))
+                })?;
+                Ok(sig.to_bytes().to_vec())
+            }
+            SignatureAlgorithm::Secp256k1 => {
+                use k256::ecdsa::signature::Verifier;
+
+                let key = Secp256k1VerifyingKey::from_sec1_bytes(&self.public_key)
+                    .map_err(|e| TdfError::SignerError(format!("Invalid secp256k1 public key: {}", e)))?;
+                let sig = if self.signature.len() == 64 {
+                    Secp256k1Signature::from_slice(&self.signature)
+                } else {
+                    Secp256k1Signature::from_der(&self.signature)
+                }
+                .map_err(|e| TdfError::SignerError(format!("Invalid secp256k1 signature: {}", e)))?;
+                let sig = sig.normalize_s().unwrap_or(sig);
+                key.verify(payload, &sig).map_err(|e| {
+                    TdfError::SignerError(format!("Signer returned an invalid signature: {}", e))
+                })?;
+                Ok(sig.to_der().as_bytes().to_vec())
+            }
+            SignatureAlgorithm::RsaPss => Err(TdfError::UnsupportedSignatureAlgorithm(
+                "RsaPss is not supported for document signatures".to_string(),
+            )),
+        }
+    }
+}
+
+/// Obtain the signing timestamp, falling back to local time
+fn acquire_timestamp(root_hash: &[u8], timestamp_provider: Option<&dyn TimestampProvider>) -> TimestampInfo {
+    if let Some(provider) = timestamp_provider {
+        provider.get_timestamp(root_hash)
+            .map(|t| t.into())
+            .unwrap_or_else(|_| TimestampInfo {
+                time: Utc::now(),
+                authority: None,
+                proof: None,
+            })
+    } else {
+        TimestampInfo {
+            time: Utc::now(),
+            authority: None,
+            proof: None,
+        }
+    }
+}
+
 pub struct SignatureManager;
 
 impl SignatureManager {
@@ -252,21 +366,7 @@ impl SignatureManager {
         timestamp_provider: Option<&dyn TimestampProvider>,
     ) -> DocumentSignature {
         // Get timestamp FIRST (so it's bound to the signature)
-        let timestamp = if let Some(provider) = timestamp_provider {
-            provider.get_timestamp(root_hash)
-                .map(|t| t.into())
-                .unwrap_or_else(|_| TimestampInfo {
-                    time: Utc::now(),
-                    authority: None,
-                    proof: None,
-                })
-        } else {
-            TimestampInfo {
-                time: Utc::now(),
-                authority: None,
-                proof: None,
-            }
-        };
+        let timestamp = acquire_timestamp(root_hash, timestamp_provider);
 
         // Compute signing payload with timestamp binding (v2 format)
         let signing_payload = compute_signing_payload(
@@ -295,6 +395,57 @@ impl SignatureManager {
         }
     }
 
+    /// Sign a document with an external signer (HSM, remote service, in-memory key)
+    ///
+    /// The signer only sees the v2 signing payload. Its output is verified
+    /// against the returned public key before the signature is accepted.
+    ///
+    /// # Arguments
+    /// * `signer` - The signer producing the signature
+    /// * `root_hash` - Merkle root hash of the document
+    /// * `signer_id` - Signer DID bound into the payload
+    /// * `signer_name` - Display name of the signer
+    /// * `scope` - Signature scope bound into the payload
+    /// * `timestamp_provider` - Optional timestamp source
+    ///
+    /// # Returns
+    /// * The signature and the signer's output (for public key checks)
+    pub fn sign_with_signer(
+        signer: &dyn DocumentSigner,
+        root_hash: &[u8],
+        signer_id: String,
+        signer_name: String,
+        scope: SignatureScope,
+        timestamp_provider: Option<&dyn TimestampProvider>,
+    ) -> TdfResult<(DocumentSignature, SignerOutput)> {
+        let timestamp = acquire_timestamp(root_hash, timestamp_provider);
+
+        let signing_payload = compute_signing_payload(
+            root_hash,
+            &timestamp.time,
+            &signer_id,
+            &scope,
+        );
+
+        let output = signer.sign_payload(&signing_payload)?;
+        let signature_bytes = output.verify_and_encode(&signing_payload)?;
+
+        let signature = DocumentSignature {
+            version: SIGNATURE_VERSION_CURRENT,
+            signer: SignerInfo {
+                id: signer_id,
+                name: signer_name,
+                certificate: None,
+            },
+            timestamp,
+            scope,
+            algorithm: output.algorithm.clone(),
+            root_hash: hex::encode(root_hash),
+            signature: STANDARD.encode(signature_bytes),
+        };
+        Ok((signature, output))
+    }
+
     /// Verify an Ed25519 signature with version-aware payload construction
     ///
     /// Security Fixes:
@@ -455,6 +606,27 @@ impl SignatureManager {
         Ok(true)
     }
 
+    /// Check that an attached certificate's leaf key is the Ed25519 verifying key
+    ///
+    /// Signatures without a certificate pass unchanged.
+    fn check_certificate_ed25519(signature: &DocumentSignature, verifying_key: &VerifyingKey) -> TdfResult<()> {
+        match &signature.signer.certificate {
+            Some(pem) => CertificateChain::parse(pem.as_bytes())?.check_leaf_key_ed25519(verifying_key),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that an attached certificate's leaf key is the secp256k1 verifying key
+    fn check_certificate_secp256k1(
+        signature: &DocumentSignature,
+        verifying_key: &Secp256k1VerifyingKey,
+    ) -> TdfResult<()> {
+        match &signature.signer.certificate {
+            Some(pem) => CertificateChain::parse(pem.as_bytes())?.check_leaf_key_secp256k1(verifying_key),
+            None => Ok(()),
+        }
+    }
+
     pub fn verify_signature_block(
         block: &SignatureBlock,
         root_hash: &[u8],
@@ -504,7 +676,9 @@ impl SignatureManager {
 
                     match key_opt {
                         Some(key) => {
-                            match Self::verify_ed25519(sig, root_hash, key) {
+                            match Self::verify_ed25519(sig, root_hash, key)
+                                .and_then(|valid| Self::check_certificate_ed25519(sig, key).map(|_| valid))
+                            {
                                 Ok(true) => VerificationResult::Valid {
                                     signer: sig.signer.name.clone(),
                                     timestamp: sig.timestamp.time,
@@ -534,7 +708,9 @@ impl SignatureManager {
 
                     match key_opt {
                         Some(key) => {
-                            match Self::verify_secp256k1(sig, root_hash, key) {
+                            match Self::verify_secp256k1(sig, root_hash, key)
+                                .and_then(|valid| Self::check_certificate_secp256k1(sig, key).map(|_| valid))
+                            {
                                 Ok(true) => VerificationResult::Valid {
                                     signer: sig.signer.name.clone(),
                                     timestamp: sig.timestamp.time,
@@ -890,5 +1066,29 @@ mod tests {
         assert!(covered.contains(&"section-3"));
         assert!(!covered.contains(&"section-2"));
     }
-}
 
+    /// A signer that returns signatures made with a different key
+    struct LyingSigner(SigningKey, SigningKey);
+
+    impl DocumentSigner for LyingSigner {
+        fn sign_payload(&self, payload: &[u8]) -> TdfResult<SignerOutput> {
+            let mut output = self.0.sign_payload(payload)?;
+            output.public_key = self.1.verifying_key().to_bytes().to_vec();
+            Ok(output)
+        }
+    }
+
+    #[test]
+    fn test_invalid_signer_output_rejected() {
+        let signer = LyingSigner(SigningKey::generate(&mut OsRng), SigningKey::generate(&mut OsRng));
+        let result = SignatureManager::sign_with_signer(
+            &signer,
+            b"test_root_hash_for_signing_12345",
+            "did:key:z".to_string(),
+            "Signer".to_string(),
+            SignatureScope::Full,
+            None,
+        );
+        assert!(matches!(result, Err(TdfError::SignerError(_))));
+    }
+}
@@ -0,0 +1,424 @@
+//! XBRL tagging of reported facts
+//!
+//! Table cells (through their row's `_row.tags`) and paragraphs (`xbrl`)
+//! can carry an `XbrlTag` naming the taxonomy concept they report, with
+//! the period, entity, unit and accuracy of the fact. The manifest's
+//! `XbrlInfo` declares the taxonomy: its entry points, the namespace of
+//! each concept prefix and the entity facts report on by default.
+//!
+//! `facts` reads the tagged values of a document back as XBRL facts, which
+//! `tdf-convert` writes as an XBRL instance or inline XBRL. Since the tags
+//! are part of the content, they are covered by the document's signature.
+
+use crate::content::{CellType, CellValue, ContentBlock, Decimal, InlineText, TableColumn};
+use crate::document::Document;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Namespace of the `xbrli` prefix (units such as `xbrli:pure`)
+pub const XBRLI_NS: &str = "http://www.xbrl.org/2003/instance";
+/// Namespace of the `iso4217` prefix (currency units)
+pub const ISO4217_NS: &str = "http://www.xbrl.org/2003/iso4217";
+/// Prefixes every XBRL document may use without declaring them
+pub const BUILTIN_PREFIXES: [(&str, &str); 2] = [("xbrli", XBRLI_NS), ("iso4217", ISO4217_NS)];
+
+/// Taxonomy a document's XBRL tags refer to
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct XbrlInfo {
+    /// Taxonomy entry points, written as `link:schemaRef`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schema_refs: Vec<String>,
+    /// Namespace URI of each concept and unit prefix
+    #[serde(default)]
+    pub namespaces: BTreeMap<String, String>,
+    /// Entity of facts whose tag names none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<XbrlEntity>,
+}
+
+/// Concept a table cell or paragraph reports, and the context of the fact
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct XbrlTag {
+    /// Taxonomy concept as a QName, e.g. `ifrs-full:Revenue`
+    pub concept: String,
+    pub period: XbrlPeriod,
+    /// Reporting entity; the manifest's `xbrl.entity` when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<XbrlEntity>,
+    /// Unit of a numeric fact as a measure QName or `numerator/denominator`
+    /// (`iso4217:EUR/xbrli:shares`); currency and percentage cells default to
+    /// `iso4217:<code>` and `xbrli:pure`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Accuracy of a numeric fact in decimal places (`-3` for thousands);
+    /// exact (`INF`) when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<i32>,
+}
+
+/// Date or date range a fact is reported for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(untagged)]
+pub enum XbrlPeriod {
+    /// Balance at the end of a day
+    Instant { instant: NaiveDate },
+    /// Flow over a range of days, both included
+    Duration { start: NaiveDate, end: NaiveDate },
+}
+
+/// Entity a fact is reported for, such as a company by its LEI
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XbrlEntity {
+    /// Identifier scheme, e.g. `http://standards.iso.org/iso/17442` for LEIs
+    pub scheme: String,
+    pub identifier: String,
+}
+
+/// Unit of a numeric fact: measures multiplied in the numerator, divided
+/// by those of the denominator
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XbrlUnit {
+    pub numerators: Vec<String>,
+    pub denominators: Vec<String>,
+}
+
+/// A value reported for a concept
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fact {
+    pub concept: String,
+    pub entity: XbrlEntity,
+    pub period: XbrlPeriod,
+    pub value: FactValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FactValue {
+    /// Amount with the text it is shown as
+    Numeric { value: Decimal, display: String, unit: XbrlUnit, decimals: Option<i32> },
+    /// Text, or a date as `YYYY-MM-DD`
+    Text(String),
+}
+
+/// Whether `name` is a prefixed XML name (`prefix:local`)
+pub fn is_qname(name: &str) -> bool {
+    let ncname = |part: &str| {
+        let mut chars = part.chars();
+        chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    };
+    name.split_once(':').is_some_and(|(prefix, local)| ncname(prefix) && ncname(local))
+}
+
+/// Prefix of a QName
+pub fn prefix(name: &str) -> &str {
+    name.split_once(':').map(|(prefix, _)| prefix).unwrap_or_default()
+}
+
+impl XbrlTag {
+    /// Check that the concept and unit are QNames and the period runs forward
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_qname(&self.concept) {
+            return Err(format!("XBRL concept '{}' is not a prefixed name", self.concept));
+        }
+        if let XbrlPeriod::Duration { start, end } = self.period {
+            if start > end {
+                return Err(format!("XBRL period of '{}' ends before it starts", self.concept));
+            }
+        }
+        if let Some(unit) = &self.unit {
+            if unit.parse::<XbrlUnit>().is_err() {
+                return Err(format!("XBRL unit '{}' of '{}' is not a measure or a ratio of measures", unit, self.concept));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl XbrlPeriod {
+    /// Last day of the period
+    pub fn end(&self) -> NaiveDate {
+        match self {
+            XbrlPeriod::Instant { instant } => *instant,
+            XbrlPeriod::Duration { end, .. } => *end,
+        }
+    }
+}
+
+impl fmt::Display for XbrlPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XbrlPeriod::Instant { instant } => write!(f, "{}", instant),
+            XbrlPeriod::Duration { start, end } => write!(f, "{} to {}", start, end),
+        }
+    }
+}
+
+impl XbrlUnit {
+    /// Unit of a single measure
+    pub fn measure(measure: impl Into<String>) -> Self {
+        XbrlUnit { numerators: vec![measure.into()], denominators: Vec::new() }
+    }
+
+    /// All measures of the unit
+    pub fn measures(&self) -> impl Iterator<Item = &str> {
+        self.numerators.iter().chain(&self.denominators).map(String::as_str)
+    }
+}
+
+impl std::str::FromStr for XbrlUnit {
+    type Err = ();
+
+    /// Parse `a`, `a*b` or `a/b` with QName measures
+    fn from_str(unit: &str) -> Result<Self, ()> {
+        let measures = |part: &str| -> Result<Vec<String>, ()> {
+            part.split('*').map(|m| if is_qname(m.trim()) { Ok(m.trim().to_string()) } else { Err(()) }).collect()
+        };
+        match unit.split_once('/') {
+            Some((numerator, denominator)) => Ok(XbrlUnit { numerators: measures(numerator)?, denominators: measures(denominator)? }),
+            None => Ok(XbrlUnit { numerators: measures(unit)?, denominators: Vec::new() }),
+        }
+    }
+}
+
+impl fmt::Display for XbrlUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.numerators.join("*"))?;
+        if !self.denominators.is_empty() {
+            write!(f, "/{}", self.denominators.join("*"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Fact {
+    /// Concept, entity, period and unit: facts agreeing on these report the
+    /// same thing and must have the same value
+    pub fn key(&self) -> (&str, &XbrlEntity, XbrlPeriod, Option<&XbrlUnit>) {
+        let unit = match &self.value {
+            FactValue::Numeric { unit, .. } => Some(unit),
+            FactValue::Text(_) => None,
+        };
+        (&self.concept, &self.entity, self.period, unit)
+    }
+
+    /// Whether two facts have the same value, however it is shown
+    pub fn same_value(&self, other: &Fact) -> bool {
+        match (&self.value, &other.value) {
+            (FactValue::Numeric { value: a, .. }, FactValue::Numeric { value: b, .. }) => a == b,
+            (a, b) => a == b,
+        }
+    }
+}
+
+/// The fact a tagged table cell reports; `None` for a redacted cell
+///
+/// # Errors
+/// A message when the cell is empty, a numeric fact has no unit or the
+/// tag does not resolve against the taxonomy (see `resolve`).
+pub fn cell_fact(info: Option<&XbrlInfo>, tag: &XbrlTag, column: &TableColumn, cell: Option<&CellValue>) -> Result<Option<Fact>, String> {
+    let unit = |default: Option<String>| -> Result<XbrlUnit, String> {
+        let unit = tag.unit.clone().or(default).ok_or_else(|| format!("numeric fact '{}' has no unit", tag.concept))?;
+        unit.parse().map_err(|_| format!("invalid unit '{}'", unit))
+    };
+    let numeric = |value: &Decimal, display: &str, unit: XbrlUnit| FactValue::Numeric {
+        value: *value,
+        display: display.to_string(),
+        unit,
+        decimals: tag.decimals,
+    };
+    let column_currency = column.currency.as_ref().filter(|_| column.cell_type == CellType::Currency).map(|code| format!("iso4217:{}", code));
+    let value = match cell {
+        None => return Err(format!("tagged cell of '{}' is empty", tag.concept)),
+        Some(CellValue::Redacted { .. }) => return Ok(None),
+        Some(CellValue::Number { raw, display } | CellValue::Formula { raw, display, .. }) => numeric(raw, display, unit(column_currency)?),
+        Some(CellValue::Currency { raw, display, currency }) => numeric(raw, display, unit(Some(format!("iso4217:{}", currency)))?),
+        Some(CellValue::Percentage { raw, display }) => numeric(raw, display, unit(Some("xbrli:pure".to_string()))?),
+        Some(CellValue::Text(_)) if tag.unit.is_some() => {
+            return Err(format!("cell tagged as numeric fact '{}' holds text", tag.concept));
+        }
+        Some(CellValue::Text(text)) => FactValue::Text(text.clone()),
+        Some(CellValue::Date { raw, .. }) => FactValue::Text(raw.clone()),
+    };
+    resolve(info, tag, value).map(Some)
+}
+
+/// The fact a tagged paragraph reports, its text without formatting
+pub fn paragraph_fact(info: Option<&XbrlInfo>, tag: &XbrlTag, text: &InlineText) -> Result<Fact, String> {
+    if tag.unit.is_some() {
+        return Err(format!("paragraph tagged as '{}' has a unit; only table cells report amounts", tag.concept));
+    }
+    resolve(info, tag, FactValue::Text(text.plain_text().into_owned()))
+}
+
+/// Complete a tagged value with the taxonomy's default entity
+///
+/// # Errors
+/// A message when the document has no taxonomy, a concept or unit prefix
+/// is not declared in it or no entity applies.
+pub fn resolve(info: Option<&XbrlInfo>, tag: &XbrlTag, value: FactValue) -> Result<Fact, String> {
+    let info = info.ok_or_else(|| "the manifest declares no XBRL taxonomy".to_string())?;
+    let declared = |name: &str| {
+        let prefix = prefix(name);
+        info.namespaces.contains_key(prefix) || BUILTIN_PREFIXES.iter().any(|(builtin, _)| *builtin == prefix)
+    };
+    if !declared(&tag.concept) {
+        return Err(format!("prefix of concept '{}' is not declared", tag.concept));
+    }
+    if let FactValue::Numeric { unit, .. } = &value {
+        if let Some(measure) = unit.measures().find(|m| !declared(m)) {
+            return Err(format!("prefix of unit measure '{}' is not declared", measure));
+        }
+    }
+    let entity = tag
+        .entity
+        .clone()
+        .or_else(|| info.entity.clone())
+        .ok_or_else(|| format!("fact '{}' has no entity and the taxonomy names no default", tag.concept))?;
+    Ok(Fact { concept: tag.concept.clone(), entity, period: tag.period, value })
+}
+
+/// Every tagged cell and paragraph of readable sections, in document order,
+/// with its location (`<table>/<row>/<column>`, or the paragraph's id or
+/// `<section id>/<index>`) and the fact it reports
+///
+/// Redacted cells report nothing and are left out.
+pub fn tagged_facts(document: &Document) -> Vec<(String, Result<Fact, String>)> {
+    let info = document.manifest.xbrl.as_ref();
+    let mut facts = Vec::new();
+    for section in document.content.sections.iter().filter(|s| s.encrypted.is_none()) {
+        for (index, block) in section.content.iter().enumerate() {
+            let location = || block.id().map(str::to_string).unwrap_or_else(|| format!("{}/{}", section.id, index));
+            match block {
+                ContentBlock::Paragraph { text, xbrl: Some(tag), .. } => facts.push((location(), paragraph_fact(info, tag, text))),
+                ContentBlock::Table { columns, rows, .. } => {
+                    for (r, row) in rows.iter().enumerate() {
+                        for column in columns {
+                            let Some(tag) = row.format.tags.get(&column.id) else { continue };
+                            let cell_location = format!("{}/{}/{}", location(), r, column.id);
+                            match cell_fact(info, tag, column, row.cells.get(&column.id)) {
+                                Ok(None) => {}
+                                Ok(Some(fact)) => facts.push((cell_location, Ok(fact))),
+                                Err(e) => facts.push((cell_location, Err(e))),
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    facts
+}
+
+/// The document's facts, in document order
+///
+/// # Errors
+/// `<location>: <message>` for the first tag that does not resolve.
+pub fn facts(document: &Document) -> Result<Vec<Fact>, String> {
+    tagged_facts(document)
+        .into_iter()
+        .map(|(location, fact)| fact.map_err(|e| format!("{}: {}", location, e)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{DocumentContent, Section, TableRow};
+    use std::collections::HashMap;
+
+    fn tag(concept: &str, unit: Option<&str>) -> XbrlTag {
+        XbrlTag {
+            concept: concept.to_string(),
+            period: XbrlPeriod::Duration {
+                start: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+                end: NaiveDate::from_ymd_opt(2025, 6, 30).unwrap(),
+            },
+            entity: None,
+            unit: unit.map(str::to_string),
+            decimals: Some(-3),
+        }
+    }
+
+    fn info() -> XbrlInfo {
+        XbrlInfo {
+            schema_refs: vec!["https://example.com/taxonomy.xsd".to_string()],
+            namespaces: BTreeMap::from([("ifrs-full".to_string(), "https://xbrl.ifrs.org/taxonomy/2024-03-27/ifrs-full".to_string())]),
+            entity: Some(XbrlEntity { scheme: "http://standards.iso.org/iso/17442".to_string(), identifier: "5299000000000000000A".to_string() }),
+        }
+    }
+
+    #[test]
+    fn test_tags_parse_and_validate() {
+        let parsed: XbrlTag = serde_json::from_str(
+            r#"{"concept": "ifrs-full:Revenue", "period": {"start": "2025-01-01", "end": "2025-06-30"}, "unit": "iso4217:EUR", "decimals": -3}"#,
+        )
+        .unwrap();
+        assert!(parsed.validate().is_ok());
+        assert_eq!(parsed.period.to_string(), "2025-01-01 to 2025-06-30");
+        let instant: XbrlPeriod = serde_json::from_str(r#"{"instant": "2025-06-30"}"#).unwrap();
+        assert_eq!(instant, XbrlPeriod::Instant { instant: NaiveDate::from_ymd_opt(2025, 6, 30).unwrap() });
+
+        let ratio: XbrlUnit = "iso4217:EUR/xbrli:shares".parse().unwrap();
+        assert_eq!(ratio.denominators, ["xbrli:shares"]);
+        assert_eq!(ratio.to_string(), "iso4217:EUR/xbrli:shares");
+        assert!("EUR".parse::<XbrlUnit>().is_err());
+        assert!(tag("Revenue", None).validate().unwrap_err().contains("not a prefixed name"));
+        assert!(tag("ifrs-full:Revenue", Some("iso4217:EUR/")).validate().is_err());
+    }
+
+    #[test]
+    fn test_cell_facts() {
+        let column = TableColumn { id: "h1".to_string(), header: "H1".to_string(), cell_type: CellType::Currency, currency: Some("EUR".to_string()) };
+        let amount = CellValue::Number { raw: "542000".parse().unwrap(), display: "542,000".to_string() };
+
+        // A number in a currency column takes the column's currency
+        let fact = cell_fact(Some(&info()), &tag("ifrs-full:Revenue", None), &column, Some(&amount)).unwrap().unwrap();
+        let FactValue::Numeric { unit, decimals, .. } = &fact.value else { panic!("not numeric") };
+        assert_eq!(unit.to_string(), "iso4217:EUR");
+        assert_eq!(*decimals, Some(-3));
+        assert_eq!(fact.entity.identifier, "5299000000000000000A");
+
+        let text_column = TableColumn { cell_type: CellType::Number, currency: None, ..column.clone() };
+        let error = cell_fact(Some(&info()), &tag("ifrs-full:Revenue", None), &text_column, Some(&amount)).unwrap_err();
+        assert!(error.contains("has no unit"), "{}", error);
+        let error = cell_fact(Some(&info()), &tag("us-gaap:Revenues", None), &column, Some(&amount)).unwrap_err();
+        assert!(error.contains("'us-gaap:Revenues' is not declared"), "{}", error);
+        assert!(cell_fact(None, &tag("ifrs-full:Revenue", None), &column, Some(&amount)).unwrap_err().contains("no XBRL taxonomy"));
+        let redacted = CellValue::Redacted { redacted: "00".to_string() };
+        assert_eq!(cell_fact(Some(&info()), &tag("ifrs-full:Revenue", None), &column, Some(&redacted)), Ok(None));
+    }
+
+    #[test]
+    fn test_document_facts() {
+        let column = |id: &str, cell_type| TableColumn { id: id.to_string(), header: id.to_string(), cell_type, currency: None };
+        let row = TableRow::new(HashMap::from([
+            ("item".to_string(), CellValue::Text("Revenue".to_string())),
+            ("h1".to_string(), CellValue::Currency { raw: "542000".parse().unwrap(), display: "€542,000".to_string(), currency: "EUR".to_string() }),
+        ]))
+        .with_tag("h1", tag("ifrs-full:Revenue", None));
+        let blocks = vec![
+            ContentBlock::Paragraph { text: "Acme SA".into(), id: None, xbrl: Some(tag("ifrs-full:NameOfReportingEntity", None)) },
+            ContentBlock::Table {
+                id: "pl".to_string(),
+                caption: None,
+                columns: vec![column("item", CellType::Text), column("h1", CellType::Currency)],
+                header_rows: None,
+                rows: vec![row],
+                footer: None,
+            },
+        ];
+        let content = DocumentContent { sections: vec![Section { id: "s".to_string(), title: None, content: blocks, encrypted: None }] };
+        let mut document = Document::new("Facts".to_string(), "en".to_string(), content, String::new());
+        assert!(facts(&document).unwrap_err().starts_with("s/0: the manifest declares no XBRL taxonomy"));
+
+        document.manifest.xbrl = Some(info());
+        let facts = facts(&document).unwrap();
+        assert_eq!(facts.len(), 2);
+        assert_eq!(facts[0].value, FactValue::Text("Acme SA".to_string()));
+        assert_eq!(facts[1].concept, "ifrs-full:Revenue");
+        assert_eq!(tagged_facts(&document)[1].0, "pl/0/h1");
+    }
+}
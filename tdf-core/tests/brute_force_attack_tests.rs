@@ -3,7 +3,7 @@
 
 use tdf_core::*;
 use tdf_core::archive::{ArchiveBuilder, ArchiveReader};
-use tdf_core::content::{ContentBlock, DocumentContent, Section};
+use tdf_core::content::{ContentBlock, DocumentContent, InlineText, Section};
 use tdf_core::document::Document;
 use tdf_core::signature::{SignatureManager, VerificationResult};
 use ed25519_dalek::{SigningKey, VerifyingKey};
@@ -46,6 +46,10 @@ fn create_test_document() -> Document {
             },
             authors: vec![],
             classification: None,
+            encryption: None,
+            redaction: None,
+            revision: None,
+            xbrl: None,
             integrity: document::IntegrityBlock {
                 root_hash: String::new(),
                 algorithm: document::HashAlgorithm::Sha256,
@@ -56,9 +60,11 @@ fn create_test_document() -> Document {
                 id: "sec-1".to_string(),
                 title: Some("Critical Section".to_string()),
                 content: vec![ContentBlock::Paragraph {
-                    text: "This is highly sensitive financial data that must remain untampered.".to_string(),
+                    text: "This is highly sensitive financial data that must remain untampered.".into(),
                     id: Some("p-1".to_string()),
+                    xbrl: None,
                 }],
+                encrypted: None,
             }],
         },
         styles: "body { color: black; }".to_string(),
@@ -100,7 +106,7 @@ fn attack_content_tampering(iteration: u32) -> bool {
     
     let mut content: DocumentContent = cbor_from_slice(&content_bytes).unwrap();
     if let Some(section) = content.sections.first_mut() {
-        if let Some(ContentBlock::Paragraph { text, .. }) = section.content.first_mut() {
+        if let Some(ContentBlock::Paragraph { text: InlineText::Plain(text), .. }) = section.content.first_mut() {
             // Random tampering
             let mut rng = rand::thread_rng();
             let tamper_pos = rng.gen_range(0..text.len().min(50));
@@ -506,7 +512,7 @@ fn attack_signature_replay(iteration: u32) -> bool {
     let mut doc2 = document;
     if let Some(section) = doc2.content.sections.first_mut() {
         if let Some(ContentBlock::Paragraph { text, .. }) = section.content.first_mut() {
-            *text = "DIFFERENT CONTENT - Should invalidate signature".to_string();
+            *text = "DIFFERENT CONTENT - Should invalidate signature".into();
         }
     }
     
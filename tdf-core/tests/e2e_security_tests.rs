@@ -35,14 +35,16 @@ fn create_financial_document() -> Document {
                 content: vec![
                     ContentBlock::Heading {
                         level: 1,
-                        text: "Q2 2025 Financial Report".to_string(),
+                        text: "Q2 2025 Financial Report".into(),
                         id: Some("h1".to_string()),
                     },
                     ContentBlock::Paragraph {
-                        text: "Revenue increased by 15% compared to Q1.".to_string(),
+                        text: "Revenue increased by 15% compared to Q1.".into(),
                         id: Some("p1".to_string()),
+                        xbrl: None,
                     },
                 ],
+                encrypted: None,
             },
             Section {
                 id: "financial-statements".to_string(),
@@ -52,10 +54,12 @@ fn create_financial_document() -> Document {
                         id: "tbl-revenue".to_string(),
                         caption: Some("Revenue by Region".to_string()),
                         columns: vec![],
+                        header_rows: None,
                         rows: vec![],
                         footer: None,
                     },
                 ],
+                encrypted: None,
             },
         ],
     };
@@ -111,7 +115,7 @@ fn test_e2e_content_tampering_detection() {
         if let Some(ContentBlock::Paragraph { text, .. }) = section.content.iter_mut().find(|b| {
             matches!(b, ContentBlock::Paragraph { .. })
         }) {
-            *text = "Revenue increased by 50% compared to Q1.".to_string(); // Fraudulent claim
+            *text = "Revenue increased by 50% compared to Q1.".into(); // Fraudulent claim
         }
     }
     let tampered_content = cbor_to_vec(&content).unwrap();
@@ -206,7 +210,7 @@ fn test_e2e_man_in_the_middle_attack() {
         if let Some(ContentBlock::Paragraph { text, .. }) = section.content.iter_mut().find(|b| {
             matches!(b, ContentBlock::Paragraph { .. })
         }) {
-            *text = "Revenue DECREASED by 10%.".to_string();
+            *text = "Revenue DECREASED by 10%.".into();
         }
     }
     let _tampered_content = cbor_to_vec(&content).unwrap();
@@ -780,7 +784,7 @@ fn test_e2e_complete_workflow_under_attack() {
         if let Some(ContentBlock::Paragraph { text, .. }) = section.content.iter_mut().find(|b| {
             matches!(b, ContentBlock::Paragraph { .. })
         }) {
-            *text = "TAMPERED AFTER SIGNING".to_string();
+            *text = "TAMPERED AFTER SIGNING".into();
         }
     }
     let tampered_content = cbor_to_vec(&content).unwrap();
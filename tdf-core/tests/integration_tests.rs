@@ -16,9 +16,11 @@ fn test_document_creation_and_verification() {
             id: "sec-1".to_string(),
             title: Some("Test Section".to_string()),
             content: vec![ContentBlock::Paragraph {
-                text: "This is a test paragraph.".to_string(),
+                text: "This is a test paragraph.".into(),
                 id: Some("p-1".to_string()),
+                xbrl: None,
             }],
+            encrypted: None,
         }],
     };
 
@@ -68,14 +70,16 @@ fn test_document_round_trip() {
             content: vec![
                 ContentBlock::Heading {
                     level: 1,
-                    text: "Test Heading".to_string(),
+                    text: "Test Heading".into(),
                     id: Some("h-1".to_string()),
                 },
                 ContentBlock::Paragraph {
-                    text: "Test paragraph content.".to_string(),
+                    text: "Test paragraph content.".into(),
                     id: Some("p-1".to_string()),
+                    xbrl: None,
                 },
             ],
+            encrypted: None,
         }],
     };
 
@@ -124,9 +128,11 @@ fn test_tamper_detection() {
             id: "sec-1".to_string(),
             title: Some("Test".to_string()),
             content: vec![ContentBlock::Paragraph {
-                text: "Original content".to_string(),
+                text: "Original content".into(),
                 id: Some("p-1".to_string()),
+                xbrl: None,
             }],
+            encrypted: None,
         }],
     };
 
@@ -172,3 +178,879 @@ fn test_tamper_detection() {
     // For a proper test, we'd need to modify the actual content.cbor or hashes.bin
 }
 
+
+#[test]
+fn test_signer_certificate_chain_round_trip() {
+    use ed25519_dalek::pkcs8::EncodePrivateKey;
+    use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair, KeyUsagePurpose};
+    use tdf_core::certificate::{CertificateChain, ChainStatus, TrustAnchors};
+    use tdf_core::config::SecurityConfig;
+
+    // Corporate CA issuing a signing certificate for the document key
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.distinguished_name.push(DnType::CommonName, "ACME Corporate CA");
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    ca_params.key_usages = vec![KeyUsagePurpose::KeyCertSign];
+    let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let leaf_key = KeyPair::try_from(signing_key.to_pkcs8_der().unwrap().as_bytes()).unwrap();
+    let mut leaf_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    leaf_params.distinguished_name.push(DnType::CommonName, "CFO");
+    leaf_params.is_ca = IsCa::ExplicitNoCa;
+    leaf_params.key_usages = vec![KeyUsagePurpose::ContentCommitment];
+    let leaf_cert = leaf_params.signed_by(&leaf_key, &ca_cert, &ca_key).unwrap();
+
+    let chain = CertificateChain::from_der_certificates(vec![leaf_cert.der().to_vec()]).unwrap();
+    let mut anchors = TrustAnchors::new();
+    anchors.add_der(ca_cert.der().to_vec()).unwrap();
+
+    let content = DocumentContent {
+        sections: vec![Section {
+            id: "sec-1".to_string(),
+            title: None,
+            content: vec![ContentBlock::Paragraph {
+                text: "Signed under a qualified certificate.".into(),
+                id: None,
+                xbrl: None,
+            }],
+            encrypted: None,
+        }],
+    };
+    let document = Document::new("Cert Test".to_string(), "en".to_string(), content, String::new());
+
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("cert.tdf");
+
+    // A certificate for a different key is rejected at build time
+    let other_key = SigningKey::generate(&mut OsRng);
+    let mut builder = ArchiveBuilder::new(document.clone()).with_signer_certificate(chain.clone());
+    assert!(builder
+        .build(&output_path, Some(&other_key), Some("did:web:cfo.acme.com".to_string()), Some("CFO".to_string()))
+        .is_err());
+
+    let mut builder = ArchiveBuilder::new(document).with_signer_certificate(chain);
+    builder
+        .build(&output_path, Some(&signing_key), Some("did:web:cfo.acme.com".to_string()), Some("CFO".to_string()))
+        .unwrap();
+
+    let report = ArchiveReader::verify_with_trust_anchors(
+        &output_path,
+        SecurityConfig::default(),
+        None,
+        Some(&anchors),
+    )
+    .unwrap();
+    assert!(report.integrity_valid);
+    assert_eq!(report.signer_certificates.len(), 1);
+    let cert_report = &report.signer_certificates[0];
+    assert!(cert_report.is_trusted(), "errors: {:?}", cert_report.errors);
+    assert!(matches!(cert_report.chain_status, ChainStatus::Trusted { .. }));
+    assert!(cert_report.leaf.as_ref().unwrap().issuer.contains("ACME Corporate CA"));
+
+    // Without anchors the certificate is still reported but not trusted
+    let report = ArchiveReader::verify(&output_path).unwrap();
+    assert_eq!(report.signer_certificates[0].chain_status, ChainStatus::NotChecked);
+}
+
+#[test]
+fn test_build_with_external_signer() {
+    use k256::ecdsa::SigningKey as Secp256k1SigningKey;
+    use tdf_core::signature::{DocumentSigner, SignatureManager, SignerOutput};
+    use tdf_core::TdfResult;
+
+    /// Stand-in for an HSM: the builder only ever sees the signing payload
+    struct CountingSigner {
+        key: Secp256k1SigningKey,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl DocumentSigner for CountingSigner {
+        fn sign_payload(&self, payload: &[u8]) -> TdfResult<SignerOutput> {
+            assert_eq!(payload.len(), 32);
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.key.sign_payload(payload)
+        }
+    }
+
+    let content = DocumentContent {
+        sections: vec![Section {
+            id: "sec-1".to_string(),
+            title: None,
+            content: vec![ContentBlock::Paragraph {
+                text: "Signed by an external signer.".into(),
+                id: None,
+                xbrl: None,
+            }],
+            encrypted: None,
+        }],
+    };
+    let document = Document::new("Signer Test".to_string(), "en".to_string(), content, String::new());
+
+    let signer = CountingSigner {
+        key: Secp256k1SigningKey::random(&mut OsRng),
+        calls: std::sync::atomic::AtomicUsize::new(0),
+    };
+    let verifying_key = *signer.key.verifying_key();
+
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("hsm.tdf");
+    ArchiveBuilder::new(document)
+        .build_with_signer(&output_path, &signer, "did:web:hsm.acme.com".to_string(), "Treasury".to_string(), None)
+        .unwrap();
+    assert_eq!(signer.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let (doc, _, sig_block) = ArchiveReader::read(&output_path).unwrap();
+    let root_hash = hex::decode(&doc.manifest.integrity.root_hash).unwrap();
+    assert_eq!(sig_block.signatures.len(), 1);
+    assert!(SignatureManager::verify_secp256k1(&sig_block.signatures[0], &root_hash, &verifying_key).unwrap());
+}
+
+#[test]
+fn test_encrypted_document_round_trip() {
+    use tdf_core::encryption::{ContentCipher, EncryptionConfig, Identity, Recipient};
+    use tdf_core::signature::{SignatureManager, VerificationResult};
+
+    let content = DocumentContent {
+        sections: vec![Section {
+            id: "sec-1".to_string(),
+            title: Some("Board Minutes".to_string()),
+            content: vec![ContentBlock::Paragraph {
+                text: "Acquisition target: Example Corp".into(),
+                id: Some("p-1".to_string()),
+                xbrl: None,
+            }],
+            encrypted: None,
+        }],
+    };
+    let document = Document::new(
+        "Minutes".to_string(),
+        "en".to_string(),
+        content,
+        "body { color: black; }".to_string(),
+    );
+
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let recipient_key = SigningKey::generate(&mut OsRng);
+    let outsider_key = SigningKey::generate(&mut OsRng);
+
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("encrypted.tdf");
+
+    let mut builder = ArchiveBuilder::new(document).with_encryption(
+        EncryptionConfig::new(ContentCipher::XChaCha20Poly1305)
+            .with_recipient(Recipient::from_ed25519(&recipient_key.verifying_key())),
+    );
+    builder
+        .add_asset("logo.png".to_string(), b"\x89PNG fake image".to_vec())
+        .unwrap();
+    builder
+        .build(
+            &output_path,
+            Some(&signing_key),
+            Some("did:web:issuer.example".to_string()),
+            Some("Issuer".to_string()),
+        )
+        .unwrap();
+
+    // The payload is not readable from the raw archive
+    let raw = fs::read(&output_path).unwrap();
+    assert!(!raw.windows(12).any(|w| w == b"Example Corp"));
+
+    // Integrity and signatures verify without decrypting
+    let report = ArchiveReader::verify(&output_path).unwrap();
+    assert!(report.integrity_valid);
+    assert!(report.encrypted);
+    let (manifest, _, sig_block) = ArchiveReader::read_envelope(&output_path).unwrap();
+    assert_eq!(
+        manifest.classification,
+        Some(tdf_core::document::Classification::Confidential)
+    );
+    let root_hash = hex::decode(&manifest.integrity.root_hash).unwrap();
+    let results = SignatureManager::verify_signature_block(
+        &sig_block,
+        &root_hash,
+        &[("did:web:issuer.example".to_string(), signing_key.verifying_key())],
+    )
+    .unwrap();
+    assert!(matches!(results[0], VerificationResult::Valid { .. }));
+
+    // Reading requires a recipient identity
+    assert!(ArchiveReader::read(&output_path).is_err());
+    assert!(ArchiveReader::read_decrypted(&output_path, &Identity::from_ed25519(&outsider_key)).is_err());
+
+    let identity = Identity::from_ed25519(&recipient_key);
+    let (doc, _, _) = ArchiveReader::read_decrypted(&output_path, &identity).unwrap();
+    assert_eq!(doc.styles, "body { color: black; }");
+    match &doc.content.sections[0].content[0] {
+        ContentBlock::Paragraph { text, .. } => assert_eq!(text.plain_text(), "Acquisition target: Example Corp"),
+        other => panic!("unexpected block {:?}", other),
+    }
+
+    let assets = ArchiveReader::read_assets(&output_path, Some(&identity)).unwrap();
+    assert_eq!(assets["assets/images/logo.png"], b"\x89PNG fake image");
+
+    // Rebuilding without encryption yields a plain, verifiable archive
+    let plain_path = temp_dir.path().join("decrypted.tdf");
+    ArchiveBuilder::new(doc).build(&plain_path, None, None, None).unwrap();
+    let report = ArchiveReader::verify(&plain_path).unwrap();
+    assert!(report.integrity_valid);
+    assert!(!report.encrypted);
+    assert!(ArchiveReader::read(&plain_path).is_ok());
+}
+
+#[test]
+fn test_section_encryption_for_audience_groups() {
+    use tdf_core::encryption::{ContentCipher, Identity, Recipient, SectionAccess, SectionEncryptionConfig};
+    use tdf_core::signature::VerificationResult;
+
+    let section = |id: &str, text: &str| Section {
+        id: id.to_string(),
+        title: Some(id.to_string()),
+        content: vec![ContentBlock::Paragraph { text: text.into(), id: None, xbrl: None }],
+        encrypted: None,
+    };
+    let content = DocumentContent {
+        sections: vec![
+            section("agenda", "Agenda for the meeting"),
+            section("acquisition", "Acquisition of Example Corp"),
+            section("audit-findings", "Material weakness in controls"),
+        ],
+    };
+    let document = Document::new("Board Pack".to_string(), "en".to_string(), content, String::new());
+
+    let issuer = SigningKey::generate(&mut OsRng);
+    let director = SigningKey::generate(&mut OsRng);
+    let auditor = SigningKey::generate(&mut OsRng);
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("board-pack.tdf");
+    let mut builder = ArchiveBuilder::new(document).with_section_encryption(
+        SectionEncryptionConfig::new(ContentCipher::Aes256Gcm)
+            .with_group(
+                "directors",
+                vec![Recipient::from_ed25519(&director.verifying_key())],
+            )
+            .with_group(
+                "auditors",
+                vec![
+                    Recipient::from_ed25519(&auditor.verifying_key()),
+                    Recipient::from_ed25519(&director.verifying_key()),
+                ],
+            )
+            .with_section("acquisition", "directors")
+            .with_section("audit-findings", "auditors"),
+    );
+    builder
+        .build(&path, Some(&issuer), Some("did:web:board.example".to_string()), Some("Secretary".to_string()))
+        .unwrap();
+
+    let raw = fs::read(&path).unwrap();
+    assert!(!raw.windows(12).any(|w| w == b"Example Corp"));
+
+    let keys = [("did:web:board.example".to_string(), issuer.verifying_key())];
+
+    // The auditor sees the agenda and audit findings, not the acquisition
+    let report = ArchiveReader::read_selective(&path, &[Identity::from_ed25519(&auditor)], &keys).unwrap();
+    assert!(report.integrity_valid);
+    assert!(matches!(report.signatures[0], VerificationResult::Valid { .. }));
+    let access: Vec<_> = report.sections.iter().map(|s| s.access).collect();
+    assert_eq!(access, [SectionAccess::Plain, SectionAccess::Locked, SectionAccess::Decrypted]);
+    assert!(report.document.content.sections[1].encrypted.is_some());
+
+    // The director sees everything; the same signature verifies
+    let report = ArchiveReader::read_selective(&path, &[Identity::from_ed25519(&director)], &keys).unwrap();
+    assert!(report.sections.iter().all(|s| s.access != SectionAccess::Locked));
+    assert!(matches!(report.signatures[0], VerificationResult::Valid { .. }));
+
+    // Without any identity, integrity and signature still verify
+    let report = ArchiveReader::read_selective(&path, &[], &keys).unwrap();
+    assert!(report.integrity_valid);
+    assert!(matches!(report.signatures[0], VerificationResult::Valid { .. }));
+    assert_eq!(report.sections.iter().filter(|s| s.access == SectionAccess::Locked).count(), 2);
+}
+
+#[test]
+fn test_redaction_keeps_signature_valid() {
+    use std::collections::HashMap;
+    use tdf_core::content::{CellType, CellValue, TableColumn, TableRow};
+    use tdf_core::redaction::{redact_archive, RedactionTarget};
+    use tdf_core::signature::VerificationResult;
+
+    let row = |name: &str, salary: i64| {
+        TableRow::new(HashMap::from([
+            ("name".to_string(), CellValue::Text(name.to_string())),
+            (
+                "salary".to_string(),
+                CellValue::Currency { raw: salary.into(), display: format!("${}", salary), currency: "USD".to_string() },
+            ),
+        ]))
+    };
+    let content = DocumentContent {
+        sections: vec![Section {
+            id: "findings".to_string(),
+            title: Some("Findings".to_string()),
+            content: vec![
+                ContentBlock::Paragraph { text: "The review found no issues.".into(), id: Some("summary".to_string()), xbrl: None },
+                ContentBlock::Paragraph { text: "Whistleblower: Jane Roe".into(), id: None, xbrl: None },
+                ContentBlock::Table {
+                    id: "salaries".to_string(),
+                    caption: None,
+                    columns: vec![
+                        TableColumn { id: "name".to_string(), header: "Name".to_string(), cell_type: CellType::Text, currency: None },
+                        TableColumn {
+                            id: "salary".to_string(),
+                            header: "Salary".to_string(),
+                            cell_type: CellType::Currency,
+                            currency: Some("USD".to_string()),
+                        },
+                    ],
+                    header_rows: None,
+                    rows: vec![row("Jane", 120000), row("John", 95000)],
+                    footer: None,
+                },
+            ],
+            encrypted: None,
+        }],
+    };
+    let document = Document::new("Investigation".to_string(), "en".to_string(), content, String::new());
+
+    let signer = SigningKey::generate(&mut OsRng);
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("report.tdf");
+    ArchiveBuilder::new(document)
+        .with_redaction()
+        .build(&path, Some(&signer), Some("did:web:audit.example".to_string()), Some("Auditor".to_string()))
+        .unwrap();
+    let original = ArchiveReader::verify(&path).unwrap();
+    assert!(original.integrity_valid);
+    assert!(original.redacted_blocks.is_empty());
+
+    let redacted_path = temp_dir.path().join("report.redacted.tdf");
+    let targets = [RedactionTarget::Block("findings/1".to_string()), "salaries/0/salary".parse().unwrap()];
+    let redacted = redact_archive(&path, &redacted_path, &targets).unwrap();
+    assert_eq!(redacted, ["findings/1", "salaries/0/salary"]);
+
+    let raw = fs::read(&redacted_path).unwrap();
+    assert!(!raw.windows(8).any(|w| w == b"Jane Roe"));
+
+    // Same root hash, the original signature still verifies
+    let keys = [("did:web:audit.example".to_string(), signer.verifying_key())];
+    let report = ArchiveReader::read_selective(&redacted_path, &[], &keys).unwrap();
+    assert!(report.integrity_valid);
+    assert_eq!(report.root_hash, original.root_hash);
+    assert!(matches!(report.signatures[0], VerificationResult::Valid { .. }));
+
+    let verification = ArchiveReader::verify(&redacted_path).unwrap();
+    assert_eq!(verification.redacted_blocks, ["findings/1", "salaries/0/salary"]);
+    let blocks = &verification.document.content.sections[0].content;
+    assert!(matches!(blocks[1], ContentBlock::Redacted { .. }));
+    if let ContentBlock::Table { rows, .. } = &blocks[2] {
+        assert!(matches!(rows[0].cells["salary"], CellValue::Redacted { .. }));
+        assert_eq!(rows[1].cells["name"], CellValue::Text("John".to_string()));
+    } else {
+        panic!("expected table");
+    }
+
+    // Redaction can be repeated on the redacted copy; unknown ids are rejected
+    let again = temp_dir.path().join("report.redacted2.tdf");
+    redact_archive(&redacted_path, &again, &[RedactionTarget::Block("salaries".to_string())]).unwrap();
+    assert_eq!(ArchiveReader::verify(&again).unwrap().root_hash, original.root_hash);
+    assert!(redact_archive(&redacted_path, &again, &[RedactionTarget::Block("missing".to_string())]).is_err());
+}
+
+#[test]
+fn test_revision_chain_across_archives() {
+    use tdf_core::revision::{check_history, revise, HistoryEntry, HistoryIssue};
+
+    let statement = |text: &str| {
+        let content = DocumentContent {
+            sections: vec![Section {
+                id: "income".to_string(),
+                title: Some("Income Statement".to_string()),
+                content: vec![ContentBlock::Paragraph { text: text.into(), id: None, xbrl: None }],
+                encrypted: None,
+            }],
+        };
+        Document::new("Q3 Statement".to_string(), "en".to_string(), content, String::new())
+    };
+    let temp_dir = TempDir::new().unwrap();
+    let signer = SigningKey::generate(&mut OsRng);
+    let build = |document: Document, name: &str| {
+        let path = temp_dir.path().join(name);
+        ArchiveBuilder::new(document)
+            .build(&path, Some(&signer), Some("did:web:cfo.example".to_string()), Some("CFO".to_string()))
+            .unwrap();
+        let report = ArchiveReader::verify(&path).unwrap();
+        assert!(report.integrity_valid);
+        let mut manifest = report.document.manifest;
+        manifest.integrity.root_hash = report.root_hash;
+        manifest
+    };
+
+    let v1 = build(statement("Revenue: $10.2M"), "v1.tdf");
+    let mut corrected = statement("Revenue: $10.4M");
+    revise(&mut corrected, &v1, "Corrected revenue").unwrap();
+    let v2 = build(corrected, "v2.tdf");
+
+    // The link survives the archive round trip and is covered by the root hash
+    let revision = v2.revision.clone().unwrap();
+    assert_eq!((revision.number, revision.previous_id.as_str()), (2, v1.document.id.as_str()));
+    assert_eq!(revision.previous_root_hash, v1.integrity.root_hash);
+
+    let entries = [HistoryEntry::from_manifest("v2.tdf", &v2), HistoryEntry::from_manifest("v1.tdf", &v1)];
+    let report = check_history(&entries);
+    assert!(report.is_linear());
+    assert_eq!(report.chains, vec![vec![v1.document.id.clone(), v2.document.id.clone()]]);
+
+    // A successor of a different v1 (same id, other content) is detected
+    let mut other_v1 = statement("Revenue: $9.9M");
+    other_v1.manifest.document.id = v1.document.id.clone();
+    let other_v1 = build(other_v1, "v1-other.tdf");
+    let report = check_history(&[HistoryEntry::from_manifest("v1-other.tdf", &other_v1), entries[0].clone()]);
+    assert!(matches!(report.issues[..], [HistoryIssue::RootHashMismatch { .. }]));
+}
+
+#[test]
+fn test_currency_cells_keep_their_code() {
+    use tdf_core::content::{CellValue, TableRow};
+
+    let row: TableRow = serde_json::from_str(
+        r#"{"amount": {"raw": 12.5, "display": "€12.50", "currency": "EUR"}, "units": {"raw": 3, "display": "3"}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        row.cells["amount"],
+        CellValue::Currency { raw: "12.5".parse().unwrap(), display: "€12.50".to_string(), currency: "EUR".to_string() }
+    );
+    assert_eq!(row.cells["units"], CellValue::Number { raw: 3.into(), display: "3".to_string() });
+
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&row, &mut bytes).unwrap();
+    assert_eq!(ciborium::from_reader::<TableRow, _>(&bytes[..]).unwrap(), row);
+}
+
+#[test]
+fn test_amounts_are_exact_decimals_with_iso_currency_codes() {
+    use tdf_core::content::{CellValue, TableRow};
+
+    // Amounts are written as decimal strings and keep their scale
+    let row: TableRow = serde_json::from_str(
+        r#"{"amount": {"raw": "1200.50", "display": "€1,200.50", "currency": "EUR"}, "legacy": {"raw": 0.1, "display": "0.1"}}"#,
+    )
+    .unwrap();
+    let CellValue::Currency { raw, .. } = &row.cells["amount"] else { panic!("not a currency cell") };
+    assert_eq!(raw.to_string(), "1200.50");
+    let CellValue::Number { raw: legacy, .. } = &row.cells["legacy"] else { panic!("not a number cell") };
+    assert_eq!(legacy.to_string(), "0.1");
+    let json = serde_json::to_value(&row).unwrap();
+    assert_eq!(json["amount"]["raw"], "1200.50");
+    assert_eq!(json["legacy"]["raw"], "0.1");
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&row, &mut bytes).unwrap();
+    let read: TableRow = ciborium::from_reader(&bytes[..]).unwrap();
+    assert_eq!(serde_json::to_value(&read).unwrap(), json);
+
+    // Unknown currency codes are rejected at build time
+    let temp_dir = TempDir::new().unwrap();
+    for (currency, ok) in [("EUR", true), ("EURO", false)] {
+        let block: ContentBlock = serde_json::from_str(&format!(
+            r#"{{"type": "table", "id": "costs", "columns": [{{"id": "amount", "header": "Amount", "type": "currency"}}],
+              "rows": [{{"amount": {{"raw": "12.50", "display": "12.50", "currency": "{}"}}}}]}}"#,
+            currency
+        ))
+        .unwrap();
+        let content = DocumentContent {
+            sections: vec![Section { id: "sec-1".to_string(), title: None, content: vec![block], encrypted: None }],
+        };
+        let document = Document::new("Costs".to_string(), "en".to_string(), content, String::new());
+        let result = ArchiveBuilder::new(document).build(&temp_dir.path().join("costs.tdf"), None, None, None);
+        assert_eq!(result.is_ok(), ok, "{}", currency);
+        if let Err(e) = result {
+            assert!(e.to_string().contains("unknown currency 'EURO'"), "{}", e);
+        }
+    }
+}
+
+#[test]
+fn test_inline_text_is_backward_compatible() {
+    use tdf_core::content::{InlineRun, InlineText, Mark};
+
+    // Plain text still reads from and writes to a string
+    let block: ContentBlock = serde_json::from_str(r#"{"type": "paragraph", "text": "Revenue{{fn:1}}"}"#).unwrap();
+    assert_eq!(block, ContentBlock::Paragraph { text: "Revenue{{fn:1}}".into(), id: None, xbrl: None });
+    assert_eq!(serde_json::to_string(&block).unwrap(), r#"{"type":"paragraph","text":"Revenue{{fn:1}}"}"#);
+
+    let rich = ContentBlock::List {
+        ordered: false,
+        items: vec![
+            "Plain item".into(),
+            InlineText::from_runs(vec![
+                InlineRun::marked("Bold", &[Mark::Bold]),
+                InlineRun::text(" and "),
+                InlineRun::Text { text: "linked".to_string(), marks: vec![Mark::Italic], link: Some("https://example.com".to_string()) },
+                InlineRun::FootnoteRef { id: "fn:2".to_string() },
+                InlineRun::CrossRef { target: "balance-sheet".to_string(), text: "balance sheet".to_string() },
+            ])
+            .into(),
+        ],
+        id: None,
+    };
+    let json = serde_json::to_value(&rich).unwrap();
+    assert_eq!(json["items"][0], "Plain item");
+    assert_eq!(json["items"][1][0], serde_json::json!({"type": "text", "text": "Bold", "marks": ["bold"]}));
+    assert_eq!(json["items"][1][1], serde_json::json!({"type": "text", "text": " and "}));
+    assert_eq!(json["items"][1][3], serde_json::json!({"type": "footnote_ref", "id": "fn:2"}));
+    assert_eq!(serde_json::from_value::<ContentBlock>(json).unwrap(), rich);
+
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&rich, &mut bytes).unwrap();
+    assert_eq!(ciborium::from_reader::<ContentBlock, _>(&bytes[..]).unwrap(), rich);
+
+    let ContentBlock::List { items, .. } = &rich else { unreachable!() };
+    assert_eq!(items[1].text.plain_text(), "Bold and linked{{fn:2}}balance sheet");
+}
+
+#[test]
+fn test_nested_lists_and_structural_blocks() {
+    use tdf_core::content::{ListItem, MAX_LIST_DEPTH};
+
+    // Items without a checkbox or sublist stay bare strings
+    let list = ContentBlock::List {
+        ordered: true,
+        items: vec![
+            "Plain".into(),
+            ListItem::task("Reconcile", false).with_sublist(false, vec!["Bank".into(), ListItem::task("Cash", true)]),
+        ],
+        id: None,
+    };
+    let json = serde_json::to_value(&list).unwrap();
+    assert_eq!(json["items"][0], "Plain");
+    assert_eq!(
+        json["items"][1],
+        serde_json::json!({"text": "Reconcile", "checked": false, "sublist": {"ordered": false, "items": ["Bank", {"text": "Cash", "checked": true}]}})
+    );
+    assert_eq!(serde_json::from_value::<ContentBlock>(json).unwrap(), list);
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&list, &mut bytes).unwrap();
+    assert_eq!(ciborium::from_reader::<ContentBlock, _>(&bytes[..]).unwrap(), list);
+
+    let blocks: Vec<ContentBlock> = serde_json::from_str(
+        r#"[{"type": "quote", "text": "Steady", "attribution": "CFO"},
+            {"type": "code", "language": "sql", "text": "SELECT 1"},
+            {"type": "page_break"},
+            {"type": "horizontal_rule"}]"#,
+    )
+    .unwrap();
+    assert_eq!(blocks[0], ContentBlock::Quote { text: "Steady".into(), attribution: Some("CFO".to_string()), id: None });
+    assert_eq!(blocks[2], ContentBlock::PageBreak);
+    assert_eq!(serde_json::to_string(&blocks[3]).unwrap(), r#"{"type":"horizontal_rule"}"#);
+    assert!(blocks.iter().all(|b| b.validate().is_ok() && b.id().is_none()));
+
+    // Validation rejects lists nested too deep, empty sublists, empty
+    // quotes and code languages that are not one word
+    let mut item = ListItem::from("leaf");
+    for _ in 0..MAX_LIST_DEPTH {
+        item = ListItem::from("level").with_sublist(false, vec![item]);
+    }
+    let deep = ContentBlock::List { ordered: false, items: vec![item], id: Some("deep".to_string()) };
+    assert!(deep.validate().unwrap_err().contains("list 'deep'"));
+    let empty = ContentBlock::List { ordered: false, items: vec![ListItem::from("a").with_sublist(true, Vec::new())], id: None };
+    assert!(empty.validate().is_err());
+    assert!(ContentBlock::Quote { text: " ".into(), attribution: None, id: None }.validate().is_err());
+    assert!(ContentBlock::Code { language: Some("rust 2021".to_string()), text: String::new(), id: None }.validate().is_err());
+
+    let mut document = Document::new(
+        "Report".to_string(),
+        "en".to_string(),
+        DocumentContent { sections: vec![Section { id: "s1".to_string(), title: None, content: vec![deep], encrypted: None }] },
+        String::new(),
+    );
+    assert!(matches!(document.validate(), Err(tdf_core::error::TdfError::InvalidDocument(msg)) if msg.contains("Section 's1'")));
+    document.content.sections[0].content = blocks;
+    assert!(document.validate().is_ok());
+}
+
+#[test]
+fn test_inline_runs_merge_and_collapse_to_plain() {
+    use tdf_core::content::{InlineRun, InlineText, Mark};
+
+    let plain = InlineText::from_runs(vec![InlineRun::text("One "), InlineRun::text(""), InlineRun::text("two")]);
+    assert_eq!(plain, InlineText::Plain("One two".to_string()));
+
+    let merged = InlineText::from_runs(vec![
+        InlineRun::marked("a", &[Mark::Italic, Mark::Bold]),
+        InlineRun::marked("b", &[Mark::Bold, Mark::Italic]),
+        InlineRun::text("c"),
+    ]);
+    assert_eq!(merged, InlineText::Runs(vec![InlineRun::marked("ab", &[Mark::Bold, Mark::Italic]), InlineRun::text("c")]));
+    assert!(InlineText::from_runs(Vec::new()).is_empty());
+}
+
+#[test]
+fn test_table_spans_header_rows_and_row_roles() {
+    use std::collections::HashMap;
+    use tdf_core::content::{covered_cells, CellSpan, CellType, CellValue, HeaderCell, RowRole, TableColumn, TableRow};
+
+    // Rows without a format serialize as before; the format sits under `_row`
+    let row: TableRow = serde_json::from_str(
+        r#"{"item": "Revenue", "q1": "10", "_row": {"role": "subtotal", "level": 1, "spans": {"q1": {"colspan": 2}}}}"#,
+    )
+    .unwrap();
+    assert_eq!(row.format.role, RowRole::Subtotal);
+    assert_eq!(row.span("q1"), CellSpan { colspan: 2, rowspan: 1 });
+    assert_eq!(row.span("item"), CellSpan::default());
+    assert!(!row.cells.contains_key("_row"));
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&row, &mut bytes).unwrap();
+    assert_eq!(ciborium::from_reader::<TableRow, _>(&bytes[..]).unwrap(), row);
+    let plain = TableRow::new(HashMap::from([("item".to_string(), CellValue::Text("Cost".to_string()))]));
+    assert_eq!(serde_json::to_string(&plain).unwrap(), r#"{"item":"Cost"}"#);
+
+    let column = |id: &str| TableColumn { id: id.to_string(), header: id.to_string(), cell_type: CellType::Text, currency: None };
+    let text = |values: &[(&str, &str)]| -> HashMap<String, CellValue> {
+        values.iter().map(|(k, v)| (k.to_string(), CellValue::Text(v.to_string()))).collect()
+    };
+    let table = |header_rows: Option<Vec<Vec<HeaderCell>>>, rows: Vec<TableRow>| ContentBlock::Table {
+        id: "t".to_string(),
+        caption: None,
+        columns: vec![column("item"), column("q1"), column("q2")],
+        header_rows,
+        rows,
+        footer: None,
+    };
+    let groups = Some(vec![vec![HeaderCell::new("", 1), HeaderCell::new("FY2025", 2)]]);
+    let valid = vec![
+        TableRow::new(text(&[("item", "Revenue"), ("q1", "10")])).with_span("item", 1, 2).with_span("q1", 2, 1),
+        TableRow::new(text(&[("q1", "4"), ("q2", "6")])).with_level(1),
+        TableRow::new(text(&[("item", "Total"), ("q1", "14"), ("q2", "6")])).with_role(RowRole::Total),
+    ];
+    assert!(table(groups.clone(), valid.clone()).validate().is_ok());
+    let columns = [column("item"), column("q1"), column("q2")];
+    let covered = covered_cells(&columns, &valid);
+    assert_eq!(covered.len(), 2);
+    assert_eq!(covered[&(1, 0)], (0, 0));
+    assert_eq!(covered[&(0, 2)], (0, 1));
+
+    // Header rows must cover every column exactly
+    let narrow = Some(vec![vec![HeaderCell::new("FY2025", 2)]]);
+    assert!(table(narrow, valid.clone()).validate().unwrap_err().contains("header row 1"));
+    // Spans stay inside the table, do not overlap and hide no values
+    let past = vec![TableRow::new(text(&[("q2", "1")])).with_span("q2", 2, 1)];
+    assert!(table(None, past).validate().unwrap_err().contains("spans past the table"));
+    let mut hidden = valid.clone();
+    hidden[1].cells.insert("item".to_string(), CellValue::Text("Hidden".to_string()));
+    assert!(table(None, hidden).validate().unwrap_err().contains("covered by a span"));
+    let mut overlapping = valid.clone();
+    overlapping[1] = overlapping[1].clone().with_span("item", 1, 1);
+    assert!(table(None, overlapping).validate().is_err());
+    let unknown = vec![TableRow::new(HashMap::new()).with_span("q9", 1, 1)];
+    assert!(table(None, unknown).validate().unwrap_err().contains("unknown column 'q9'"));
+    let deep = vec![TableRow::new(HashMap::new()).with_level(8)];
+    assert!(table(None, deep).validate().unwrap_err().contains("outline level 8"));
+    let reserved = ContentBlock::Table {
+        id: "t".to_string(),
+        caption: None,
+        columns: vec![column("_row")],
+        header_rows: None,
+        rows: Vec::new(),
+        footer: None,
+    };
+    assert!(reserved.validate().unwrap_err().contains("reserved column id"));
+}
+
+#[test]
+fn test_signed_formula_totals_are_recomputed() {
+    use tdf_core::content::{CellValue, TableRow};
+    use tdf_core::formula::FormulaOutcome;
+
+    let json = |total_display: &str| {
+        format!(
+            r#"{{"type": "table", "id": "costs", "columns": [
+                {{"id": "item", "header": "Item", "type": "text"}},
+                {{"id": "amount", "header": "Amount", "type": "currency", "currency": "EUR"}}],
+              "rows": [
+                {{"item": "Rent", "amount": {{"raw": 1200, "display": "€1,200.00", "currency": "EUR"}}}},
+                {{"item": "Power", "amount": {{"raw": 300.5, "display": "€300.50", "currency": "EUR"}}}},
+                {{"item": "Total", "amount": {{"formula": "SUM(B1:B2)", "raw": 1500.5, "display": "{}"}}}}]}}"#,
+            total_display
+        )
+    };
+    let block: ContentBlock = serde_json::from_str(&json("€1,500.50")).unwrap();
+    let ContentBlock::Table { rows, .. } = &block else { panic!("not a table") };
+    assert_eq!(
+        rows[2].cells["amount"],
+        CellValue::Formula { formula: "SUM(B1:B2)".to_string(), raw: "1500.5".parse().unwrap(), display: "€1,500.50".to_string() }
+    );
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&rows[2], &mut bytes).unwrap();
+    assert_eq!(&ciborium::from_reader::<TableRow, _>(&bytes[..]).unwrap(), &rows[2]);
+
+    // A mistyped total still builds and signs, but verification re-computes it
+    let temp_dir = TempDir::new().unwrap();
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let mut reports = Vec::new();
+    for (name, display) in [("right.tdf", "€1,500.50"), ("wrong.tdf", "€1,600.50")] {
+        let block: ContentBlock = serde_json::from_str(&json(display)).unwrap();
+        let content = DocumentContent {
+            sections: vec![Section { id: "sec-1".to_string(), title: None, content: vec![block], encrypted: None }],
+        };
+        let document = Document::new("Costs".to_string(), "en".to_string(), content, String::new());
+        let path = temp_dir.path().join(name);
+        ArchiveBuilder::new(document)
+            .build(&path, Some(&signing_key), Some("did:web:test.com".to_string()), Some("Signer".to_string()))
+            .unwrap();
+        reports.push(ArchiveReader::verify(&path).unwrap());
+    }
+    assert!(reports.iter().all(|r| r.integrity_valid));
+    assert_eq!(reports[0].formulas[0].outcome, FormulaOutcome::Matches);
+    assert_eq!(reports[1].formulas[0].outcome, FormulaOutcome::Mismatch { computed: "1500.5".parse().unwrap() });
+    assert_eq!(reports[1].formulas[0].key(), "costs/2/amount");
+    assert!(reports[1].formulas[0].failed());
+
+    // References outside the table are rejected when building
+    let block: ContentBlock = serde_json::from_str(&json("€1,500.50").replace("SUM(B1:B2)", "SUM(B1:B9)")).unwrap();
+    let err = block.validate().unwrap_err().to_string();
+    assert!(err.contains("B9 is outside the table"), "{}", err);
+}
+
+#[test]
+fn test_build_rejects_content_errors() {
+    use tdf_core::lint::{lint_document, Severity};
+
+    let figure = |id: &str, alt: &str| ContentBlock::Figure {
+        id: id.to_string(),
+        asset: "logo.png".to_string(),
+        alt: alt.to_string(),
+        caption: None,
+        width: None,
+    };
+    let document = |blocks: Vec<ContentBlock>| {
+        let content = DocumentContent {
+            sections: vec![Section { id: "sec-1".to_string(), title: None, content: blocks, encrypted: None }],
+        };
+        Document::new("Lint".to_string(), "en".to_string(), content, String::new())
+    };
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("lint.tdf");
+
+    // The figure's asset must be added to the archive
+    let mut builder = ArchiveBuilder::new(document(vec![figure("fig-1", "Logo")]));
+    let error = builder.build(&output, None, None, None).unwrap_err().to_string();
+    assert!(error.contains("fig-1: asset 'logo.png' is not in the archive"), "{}", error);
+    builder.add_asset("logo.png".to_string(), b"\x89PNG fake image".to_vec()).unwrap();
+    builder.build(&output, None, None, None).unwrap();
+
+    // Warnings do not stop the build; duplicate ids do
+    let (read, _, _) = ArchiveReader::read(&output).unwrap();
+    let assets = ArchiveReader::read_assets(&output, None).unwrap().into_keys().collect();
+    assert!(lint_document(&read, Some(&assets)).findings.is_empty());
+    let mut builder = ArchiveBuilder::new(document(vec![figure("fig-1", ""), figure("fig-1", "Logo")]));
+    builder.add_asset("logo.png".to_string(), b"\x89PNG fake image".to_vec()).unwrap();
+    let report = lint_document(&document(vec![figure("fig-1", ""), figure("fig-1", "Logo")]), Some(&assets));
+    assert_eq!(report.warnings().count(), 1);
+    assert_eq!(report.errors().map(|f| f.rule).collect::<Vec<_>>(), ["duplicate-id"]);
+    assert!(report.findings.iter().all(|f| f.severity == Severity::Error || f.rule == "missing-alt"));
+    assert!(builder.build(&output, None, None, None).unwrap_err().to_string().contains("id 'fig-1' is already used"));
+}
+
+#[test]
+fn test_charts_draw_table_data() {
+    use std::collections::HashMap;
+    use tdf_core::content::ChartType;
+
+    let blocks: Vec<ContentBlock> = serde_json::from_str(
+        r##"[{"type": "table", "id": "revenue", "columns": [
+                {"id": "quarter", "header": "Quarter", "type": "text"},
+                {"id": "amount", "header": "Amount", "type": "number"}],
+              "rows": [
+                {"quarter": "Q1", "amount": {"raw": 120, "display": "120"}},
+                {"quarter": "Q2", "amount": {"raw": 150, "display": "150"}},
+                {"quarter": "Total", "amount": {"raw": 270, "display": "270"}, "_row": {"role": "total"}}]},
+            {"type": "chart", "id": "revenue-chart", "chart_type": "bar", "title": "Revenue",
+             "table": "revenue", "category": "quarter", "series": [{"column": "amount", "color": "#1f77b4"}]}]"##,
+    )
+    .unwrap();
+    let ContentBlock::Chart { chart_type, series, .. } = &blocks[1] else { panic!("not a chart") };
+    assert_eq!(*chart_type, ChartType::Bar);
+    assert_eq!(series[0].color.as_deref(), Some("#1f77b4"));
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&blocks[1], &mut bytes).unwrap();
+    assert_eq!(ciborium::from_reader::<ContentBlock, _>(&bytes[..]).unwrap(), blocks[1]);
+
+    let document = |blocks: Vec<ContentBlock>| {
+        let content = DocumentContent {
+            sections: vec![Section { id: "sec-1".to_string(), title: None, content: blocks, encrypted: None }],
+        };
+        Document::new("Charts".to_string(), "en".to_string(), content, String::new())
+    };
+
+    // Only data rows are drawn, so the total does not become a bar
+    let html = tdf_core::html::render_body(&document(blocks.clone()), &HashMap::new());
+    let chart = &html[html.find(r#"<figure class="chart-container" id="revenue-chart">"#).expect("chart figure")..];
+    assert!(chart.contains(">Q2<") && !chart.contains(">Total<"), "{}", chart);
+
+    // A chart whose source table is missing does not build
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("chart.tdf");
+    ArchiveBuilder::new(document(blocks.clone())).build(&output, None, None, None).unwrap();
+    let error = ArchiveBuilder::new(document(blocks[1..].to_vec())).build(&output, None, None, None).unwrap_err().to_string();
+    assert!(error.contains("revenue-chart: table 'revenue' does not exist"), "{}", error);
+}
+
+#[test]
+fn test_form_submission_keeps_template_signature_valid() {
+    use std::collections::{BTreeMap, HashMap};
+    use tdf_core::form::{add_submission, read_submissions, read_template, Submission};
+    use tdf_core::signature::VerificationResult;
+
+    let blocks: Vec<ContentBlock> = serde_json::from_str(
+        r#"[{"type": "text_field", "id": "name", "label": "Legal name", "required": true},
+            {"type": "number_field", "id": "holding", "label": "Holding", "max": 100, "decimals": 2},
+            {"type": "checkbox", "id": "attest", "label": "I confirm the above", "required": true},
+            {"type": "signature_field", "id": "sig", "label": "Signature", "required": true}]"#,
+    )
+    .unwrap();
+    let content = DocumentContent {
+        sections: vec![Section { id: "kyc".to_string(), title: Some("Beneficial owner".to_string()), content: blocks, encrypted: None }],
+    };
+    let document = Document::new("KYC".to_string(), "en".to_string(), content, String::new());
+    let html = tdf_core::html::render_body(&document, &HashMap::new());
+    assert!(html.contains(r#"<div class="form-field" id="holding"><span class="field-label">Holding</span>"#), "{}", html);
+
+    let issuer = SigningKey::generate(&mut OsRng);
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("kyc.tdf");
+    ArchiveBuilder::new(document)
+        .build(&path, Some(&issuer), Some("did:web:bank.example".to_string()), Some("Bank".to_string()))
+        .unwrap();
+    let template = read_template(&path).unwrap();
+    let root_hash = ArchiveReader::verify(&path).unwrap().root_hash;
+
+    let values: BTreeMap<String, serde_json::Value> =
+        serde_json::from_str(r#"{"name": "Jane Roe", "holding": "25.5", "attest": true}"#).unwrap();
+    let mut submission = Submission::new(&template, values).unwrap();
+    let filler = SigningKey::generate(&mut OsRng);
+    let submitted = temp_dir.path().join("kyc.submitted.tdf");
+    assert!(add_submission(&path, &submitted, &submission).unwrap_err().to_string().contains("not signed"));
+    submission.sign(&filler, "did:web:roe.example".to_string(), "Jane Roe".to_string()).unwrap();
+    add_submission(&path, &submitted, &submission).unwrap();
+
+    // The template's root hash and signature are untouched
+    let keys = [("did:web:bank.example".to_string(), issuer.verifying_key())];
+    let report = ArchiveReader::read_selective(&submitted, &[], &keys).unwrap();
+    assert!(report.integrity_valid);
+    assert_eq!(report.root_hash, root_hash);
+    assert!(matches!(report.signatures[0], VerificationResult::Valid { .. }));
+
+    let read = read_submissions(&submitted).unwrap();
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0].values["holding"], "25.5");
+    assert!(read[0].check(&template).is_empty());
+    let filler_keys = [("did:web:roe.example".to_string(), filler.verifying_key())];
+    assert!(matches!(read[0].verify_signature(&filler_keys, &[]).unwrap(), Some(VerificationResult::Valid { .. })));
+
+    // The same submission cannot be added twice
+    assert!(add_submission(&submitted, &submitted, &submission).unwrap_err().to_string().contains("already in the archive"));
+}
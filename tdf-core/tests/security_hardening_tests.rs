@@ -24,6 +24,10 @@ fn create_test_document() -> Document {
             },
             authors: vec![],
             classification: None,
+            encryption: None,
+            redaction: None,
+            revision: None,
+            xbrl: None,
             integrity: document::IntegrityBlock {
                 root_hash: String::new(),
                 algorithm: document::HashAlgorithm::Sha256,
@@ -34,9 +38,11 @@ fn create_test_document() -> Document {
                 id: "section1".to_string(),
                 title: Some("Test Section".to_string()),
                 content: vec![content::ContentBlock::Paragraph {
-                    text: "Test content".to_string(),
+                    text: "Test content".into(),
                     id: Some("p1".to_string()),
+                    xbrl: None,
                 }],
+                encrypted: None,
             }],
         },
         styles: "body { }".to_string(),
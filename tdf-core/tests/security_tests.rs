@@ -35,9 +35,11 @@ fn create_test_document() -> Document {
             id: "sec-1".to_string(),
             title: Some("Security Test Section".to_string()),
             content: vec![ContentBlock::Paragraph {
-                text: "This is original secure content that must not be tampered with.".to_string(),
+                text: "This is original secure content that must not be tampered with.".into(),
                 id: Some("p-1".to_string()),
+                xbrl: None,
             }],
+            encrypted: None,
         }],
     };
 
@@ -97,7 +99,7 @@ fn test_tamper_content_modification() {
     let mut content: DocumentContent = cbor_from_slice(&content_bytes).unwrap();
     if let Some(section) = content.sections.first_mut() {
         if let Some(ContentBlock::Paragraph { text, .. }) = section.content.first_mut() {
-            *text = "TAMPERED CONTENT - This should be detected!".to_string();
+            *text = "TAMPERED CONTENT - This should be detected!".into();
         }
     }
     let tampered_content = cbor_to_vec(&content).unwrap();
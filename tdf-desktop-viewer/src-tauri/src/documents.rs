@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use tdf_core::archive::ArchiveReader;
+use tdf_core::formula::FormulaOutcome;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerificationDetails {
@@ -11,6 +12,8 @@ pub struct VerificationDetails {
     pub signature_count: usize,
     pub signatures: Vec<SignatureInfo>,
     pub timestamp_warnings: Vec<String>,
+    /// Formula cells whose stored value disagrees with their formula
+    pub formula_errors: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,11 +50,25 @@ pub fn verify_document_enhanced(file_path: &Path) -> Result<VerificationDetails,
         }
     }
 
+    let formula_errors = report
+        .formulas
+        .iter()
+        .filter(|check| check.failed())
+        .map(|check| match &check.outcome {
+            FormulaOutcome::Mismatch { computed } => {
+                format!("{} = {} shows {} but computes {}", check.key(), check.formula, check.display, computed)
+            }
+            FormulaOutcome::Error { message } => format!("{} = {}: {}", check.key(), check.formula, message),
+            _ => check.key(),
+        })
+        .collect();
+
     Ok(VerificationDetails {
         integrity_valid: report.integrity_valid,
         root_hash: report.root_hash,
         signature_count: report.signature_count,
         signatures,
         timestamp_warnings: report.timestamp_warnings,
+        formula_errors,
     })
 }
@@ -3,10 +3,14 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tdf_core::error::TdfResult;
-use ed25519_dalek::{SigningKey, VerifyingKey, SECRET_KEY_LENGTH, PUBLIC_KEY_LENGTH};
+use tdf_core::error::{TdfError, TdfResult};
+use tdf_core::key_file::{write_private_file, EncryptedKeyFile, Kdf, KdfParams, StoredKey};
+use tdf_core::key_format::{decode_public_key, public_key_fingerprint, raw_public_key, PrivateKey};
+use tdf_core::signature::SignatureAlgorithm;
+use ed25519_dalek::SigningKey;
 use k256::ecdsa::SigningKey as Secp256k1SigningKey;
 use rand_core::OsRng;
+use zeroize::Zeroizing;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyInfo {
@@ -88,8 +92,24 @@ pub fn list_keys() -> TdfResult<Vec<KeyInfo>> {
     Ok(keys)
 }
 
-pub fn generate_key(name: String, algorithm: String, signer_id: Option<String>) -> TdfResult<KeyInfo> {
+/// Require a non-empty password for storing a private key
+fn require_password(password: Option<String>) -> TdfResult<Zeroizing<String>> {
+    match password {
+        Some(password) if !password.is_empty() => Ok(Zeroizing::new(password)),
+        _ => Err(TdfError::PolicyViolation(
+            "A password is required to store a private key".to_string(),
+        )),
+    }
+}
+
+pub fn generate_key(
+    name: String,
+    algorithm: String,
+    signer_id: Option<String>,
+    password: Option<String>,
+) -> TdfResult<KeyInfo> {
     let keys_dir = get_keys_directory()?;
+    let password = require_password(password)?;
     
     // Generate unique key ID
     let key_id = format!("{}-{}", 
@@ -106,23 +126,22 @@ pub fn generate_key(name: String, algorithm: String, signer_id: Option<String>)
             let signing_key = SigningKey::generate(&mut OsRng);
             let verifying_key = signing_key.verifying_key();
 
-            // Write signing key (private)
-            fs::write(&signing_key_path, signing_key.to_bytes())?;
+            // Write signing key (private), encrypted with the password
+            EncryptedKeyFile::from_ed25519(&signing_key, password.as_bytes(), KdfParams::new(Kdf::default())?)?
+                .write(&signing_key_path)?;
 
             // Write verifying key (public)
             fs::write(&verifying_key_path, verifying_key.to_bytes())?;
         }
         "secp256k1" => {
-            use k256::elliptic_curve::SecretKey;
-            use k256::Secp256k1;
-            let signing_key = SecretKey::<Secp256k1>::random(&mut OsRng);
-            let verifying_key = signing_key.public_key();
+            let signing_key = Secp256k1SigningKey::random(&mut OsRng);
 
-            // Write signing key (private) - 32 bytes
-            fs::write(&signing_key_path, signing_key.to_bytes().as_slice())?;
+            // Write signing key (private), encrypted with the password
+            EncryptedKeyFile::from_secp256k1(&signing_key, password.as_bytes(), KdfParams::new(Kdf::default())?)?
+                .write(&signing_key_path)?;
 
             // Write verifying key (public) - SEC1 format
-            fs::write(&verifying_key_path, verifying_key.to_sec1_bytes())?;
+            fs::write(&verifying_key_path, signing_key.verifying_key().to_sec1_bytes())?;
         }
         _ => {
             return Err(tdf_core::error::TdfError::UnsupportedSignatureAlgorithm(algorithm));
@@ -149,27 +168,39 @@ pub fn generate_key(name: String, algorithm: String, signer_id: Option<String>)
     })
 }
 
-pub fn import_key(path: &Path, name: String, _password: Option<String>) -> TdfResult<KeyInfo> {
+fn algorithm_name(algorithm: SignatureAlgorithm) -> &'static str {
+    match algorithm {
+        SignatureAlgorithm::Secp256k1 => "secp256k1",
+        _ => "ed25519",
+    }
+}
+
+/// Import a key file: TDF encrypted key, PKCS#8/SEC1/SPKI (PEM or DER), JWK or raw
+pub fn import_key(path: &Path, name: String, password: Option<String>) -> TdfResult<KeyInfo> {
     let keys_dir = get_keys_directory()?;
-    
-    // Determine if it's a signing or verifying key
-    let is_signing = path.file_name()
-        .and_then(|n| n.to_str())
-        .map(|n| n.ends_with(".signing"))
-        .unwrap_or(false);
+
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
     // Read key data
-    let key_data = fs::read(path)?;
+    let key_data = Zeroizing::new(fs::read(path)?);
 
-    // Determine algorithm based on key size
-    let algorithm = if key_data.len() == SECRET_KEY_LENGTH || key_data.len() == PUBLIC_KEY_LENGTH {
-        "ed25519"
-    } else if key_data.len() == 32 || key_data.len() == 33 || key_data.len() == 65 {
-        "secp256k1"
+    // Raw Ed25519 public and private keys are both 32 bytes; only *.signing is private
+    let raw_public = match key_data.len() {
+        32 => !file_name.ends_with(".signing"),
+        33 | 65 => true,
+        _ => false,
+    };
+    let stored = if raw_public {
+        None
     } else {
-        return Err(tdf_core::error::TdfError::SignatureFailure(
-            format!("Unknown key format: {} bytes", key_data.len())
-        ));
+        match StoredKey::parse(key_data.to_vec()) {
+            Ok(stored) => Some(stored),
+            // Not a private key: SPKI or public JWK
+            Err(e) => {
+                decode_public_key(&key_data).map_err(|_| e)?;
+                None
+            }
+        }
     };
 
     // Generate unique key ID
@@ -182,47 +213,37 @@ pub fn import_key(path: &Path, name: String, _password: Option<String>) -> TdfRe
     let verifying_key_path = keys_dir.join(format!("{}.verifying", key_id));
     let metadata_path = keys_dir.join(format!("{}.meta.json", key_id));
 
-    if is_signing {
-        // Import signing key
-        fs::write(&signing_key_path, &key_data)?;
-        
-        // Derive verifying key
-        match algorithm {
-            "ed25519" => {
-                if key_data.len() != SECRET_KEY_LENGTH {
-                    return Err(tdf_core::error::TdfError::SignatureFailure(
-                        format!("Invalid Ed25519 key length: expected {}, got {}", SECRET_KEY_LENGTH, key_data.len())
-                    ));
+    let public_key = match stored {
+        Some(stored) => {
+            let password = require_password(password)?;
+            let encrypted = match stored {
+                // Decrypt once to check the password and the stored public key
+                StoredKey::Encrypted(file) => {
+                    file.decrypt_private_key(password.as_bytes())?;
+                    file
                 }
-                let key_bytes: [u8; SECRET_KEY_LENGTH] = key_data.as_slice()
-                    .try_into()
-                    .map_err(|_| tdf_core::error::TdfError::SignatureFailure("Invalid Ed25519 key length".to_string()))?;
-                let signing_key = SigningKey::from_bytes(&key_bytes);
-                let verifying_key = signing_key.verifying_key();
-                fs::write(&verifying_key_path, verifying_key.to_bytes())?;
-            }
-            "secp256k1" => {
-                if key_data.len() != 32 {
-                    return Err(tdf_core::error::TdfError::SignatureFailure(
-                        format!("Invalid secp256k1 key length: expected 32, got {}", key_data.len())
-                    ));
+                // Unencrypted keys are encrypted with the password on import
+                StoredKey::Plain(key) => {
+                    EncryptedKeyFile::from_private_key(&key, password.as_bytes(), KdfParams::new(Kdf::default())?)?
                 }
-                use k256::elliptic_curve::SecretKey;
-                use k256::Secp256k1;
-                let key_bytes: &[u8; 32] = key_data.as_slice()
-                    .try_into()
-                    .map_err(|_| tdf_core::error::TdfError::SignatureFailure("Invalid secp256k1 key length".to_string()))?;
-                let signing_key = SecretKey::<Secp256k1>::from_bytes(key_bytes.into())
-                    .map_err(|e| tdf_core::error::TdfError::SignatureFailure(format!("Invalid secp256k1 key: {}", e)))?;
-                let verifying_key = signing_key.public_key();
-                fs::write(&verifying_key_path, verifying_key.to_sec1_bytes())?;
-            }
-            _ => unreachable!()
+                StoredKey::Legacy(secret) => {
+                    let algorithm = if file_name.ends_with(".secp256k1.signing") {
+                        SignatureAlgorithm::Secp256k1
+                    } else {
+                        SignatureAlgorithm::Ed25519
+                    };
+                    let key = PrivateKey::from_secret(&secret, algorithm)?;
+                    EncryptedKeyFile::from_private_key(&key, password.as_bytes(), KdfParams::new(Kdf::default())?)?
+                }
+            };
+            encrypted.write(&signing_key_path)?;
+            decode_public_key(&encrypted.public_key_bytes()?)?
         }
-    } else {
         // Import verifying key only
-        fs::write(&verifying_key_path, &key_data)?;
-    }
+        None => decode_public_key(&key_data)?,
+    };
+    fs::write(&verifying_key_path, raw_public_key(&public_key))?;
+    let algorithm = algorithm_name(public_key.algorithm());
 
     // Write metadata
     let metadata = KeyMetadata {
@@ -254,8 +275,9 @@ pub fn export_key(key_id: &str, path: &Path) -> TdfResult<()> {
         ));
     }
 
+    // The stored key is already encrypted; keep it owner-readable only
     let key_data = fs::read(&signing_key_path)?;
-    fs::write(path, key_data)?;
+    write_private_file(path, &key_data)?;
 
     Ok(())
 }
@@ -305,8 +327,6 @@ fn load_key_metadata(key_id: &str) -> TdfResult<KeyMetadata> {
 }
 
 fn compute_fingerprint(key_id: &str) -> TdfResult<String> {
-    use sha2::{Sha256, Digest};
-    
     let keys_dir = get_keys_directory()?;
     let verifying_key_path = keys_dir.join(format!("{}.verifying", key_id));
 
@@ -314,10 +334,9 @@ fn compute_fingerprint(key_id: &str) -> TdfResult<String> {
         return Ok("N/A".to_string());
     }
 
+    // Same fingerprint as `tdf key fingerprint`: first 16 bytes of SHA-256 over the raw public key
     let key_data = fs::read(&verifying_key_path)?;
-    let hash = Sha256::digest(&key_data);
-    
-    // Return first 16 bytes as hex
-    let fingerprint: Vec<String> = hash[..16].iter().map(|b| format!("{:02x}", b)).collect();
-    Ok(fingerprint.join(""))
+    Ok(decode_public_key(&key_data)
+        .map(|key| public_key_fingerprint(&key))
+        .unwrap_or_else(|_| "N/A".to_string()))
 }
@@ -43,8 +43,13 @@ async fn list_keys_command() -> Result<Vec<KeyInfo>, String> {
 }
 
 #[tauri::command]
-async fn generate_key_command(name: String, algorithm: String, signer_id: Option<String>) -> Result<KeyInfo, String> {
-    generate_key(name, algorithm, signer_id).map_err(|e| format!("Failed to generate key: {}", e))
+async fn generate_key_command(
+    name: String,
+    algorithm: String,
+    signer_id: Option<String>,
+    password: Option<String>,
+) -> Result<KeyInfo, String> {
+    generate_key(name, algorithm, signer_id, password).map_err(|e| format!("Failed to generate key: {}", e))
 }
 
 #[tauri::command]
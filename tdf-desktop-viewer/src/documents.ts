@@ -168,6 +168,7 @@ async function verifyCurrentDocument() {
                 valid: boolean;
             }>;
             timestamp_warnings: string[];
+            formula_errors: string[];
         };
         
         const verificationPanel = document.getElementById('verification-panel');
@@ -176,7 +177,7 @@ async function verifyCurrentDocument() {
             renderVerificationPanel(result, verificationPanel);
         }
         
-        if (result.integrity_valid && result.signatures.every(s => s.valid)) {
+        if (result.integrity_valid && result.signatures.every(s => s.valid) && result.formula_errors.length === 0) {
             updateStatus('✓ Document verified successfully', 'success');
         } else {
             updateStatus('✗ Document verification failed', 'error');
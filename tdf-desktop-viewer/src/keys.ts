@@ -115,6 +115,12 @@ function showGenerateKeyDialog() {
             <input type="text" id="signerId" class="form-input" placeholder="did:web:example.com">
             <div class="form-help">Decentralized Identifier for this key</div>
         </div>
+        <div class="form-group">
+            <label class="form-label">Password</label>
+            <input type="password" id="newKeyPassword" class="form-input" required>
+            <input type="password" id="newKeyPasswordConfirm" class="form-input" placeholder="Confirm password" required>
+            <div class="form-help">The private key is stored encrypted with this password</div>
+        </div>
     `;
 
     const footer = document.createElement('div');
@@ -129,6 +135,8 @@ function showGenerateKeyDialog() {
     const keyNameInput = document.getElementById('keyName') as HTMLInputElement;
     const algorithmSelect = document.getElementById('keyAlgorithm') as HTMLSelectElement;
     const signerIdInput = document.getElementById('signerId') as HTMLInputElement;
+    const passwordInput = document.getElementById('newKeyPassword') as HTMLInputElement;
+    const passwordConfirmInput = document.getElementById('newKeyPasswordConfirm') as HTMLInputElement;
 
     if (generateBtn) {
         generateBtn.addEventListener('click', async () => {
@@ -136,14 +144,26 @@ function showGenerateKeyDialog() {
             const algorithm = algorithmSelect?.value || 'ed25519';
             const signerId = signerIdInput?.value.trim() || undefined;
 
+            const password = passwordInput?.value || '';
+
             if (!name) {
                 alert('Please enter a key name');
                 return;
             }
 
+            if (!password) {
+                alert('Please enter a password for the private key');
+                return;
+            }
+
+            if (password !== passwordConfirmInput?.value) {
+                alert('Passwords do not match');
+                return;
+            }
+
             try {
                 updateStatus('Generating key...', 'info');
-                await invoke('generate_key', { name, algorithm, signerId });
+                await invoke('generate_key', { name, algorithm, signerId, password });
                 updateStatus('Key generated successfully', 'success');
                 closeModal();
                 await refreshKeyList();
@@ -168,7 +188,7 @@ async function showImportKeyDialog() {
         const selected = await open({
             filters: [{
                 name: 'TDF Keys',
-                extensions: ['signing', 'verifying']
+                extensions: ['signing', 'verifying', 'pem', 'der', 'jwk', 'json']
             }],
             multiple: false,
         });
@@ -189,9 +209,9 @@ async function showImportKeyDialog() {
                 <div class="form-help">A friendly name for this imported key</div>
             </div>
             <div class="form-group">
-                <label class="form-label">Password (Optional)</label>
-                <input type="password" id="keyPassword" class="form-input" placeholder="Leave empty if not encrypted">
-                <div class="form-help">If the key file is password-protected, enter the password here</div>
+                <label class="form-label">Password</label>
+                <input type="password" id="keyPassword" class="form-input" placeholder="Not needed for public keys">
+                <div class="form-help">Unlocks an encrypted private key; unencrypted private keys are encrypted with it on import</div>
             </div>
         `;
 
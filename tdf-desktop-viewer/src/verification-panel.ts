@@ -12,6 +12,7 @@ export interface VerificationResult {
         valid: boolean;
     }>;
     timestamp_warnings: string[];
+    formula_errors: string[];
 }
 
 export function renderVerificationPanel(result: VerificationResult, container: HTMLElement) {
@@ -66,6 +67,15 @@ export function renderVerificationPanel(result: VerificationResult, container: H
                 </div>
             `}
             
+            ${result.formula_errors.length > 0 ? `
+                <div class="result-item error">
+                    <strong>✗ Computed values do not match their formulas:</strong>
+                    <ul>
+                        ${result.formula_errors.map(e => `<li>${escapeHtml(e)}</li>`).join('')}
+                    </ul>
+                </div>
+            ` : ''}
+
             ${result.timestamp_warnings.length > 0 ? `
                 <div class="result-item warning">
                     <strong>⚠️ Timestamp Warnings:</strong>
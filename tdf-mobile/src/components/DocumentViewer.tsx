@@ -1,6 +1,6 @@
 import React from 'react';
 import { View, Text, ScrollView, StyleSheet } from 'react-native';
-import { TdfDocument } from 'tdf-ts';
+import { TdfDocument, ListItem, TableBlock, inlinePlainText, listItemParts, headerLayout, coveredCells } from 'tdf-ts';
 
 interface DocumentViewerProps {
   document: TdfDocument;
@@ -70,52 +70,127 @@ function renderBlock(block: any): React.ReactNode {
   switch (block.type) {
     case 'heading':
       const Heading = `h${block.level}` as keyof JSX.IntrinsicElements;
-      return <Heading style={styles[`heading${block.level}`]}>{block.text}</Heading>;
+      return <Heading style={styles[`heading${block.level}`]}>{inlinePlainText(block.text)}</Heading>;
 
     case 'paragraph':
-      return <Text style={styles.paragraph}>{block.text}</Text>;
+      return <Text style={styles.paragraph}>{inlinePlainText(block.text)}</Text>;
 
     case 'list':
-      return (
-        <View style={styles.list}>
-          {block.items.map((item: string, idx: number) => (
-            <Text key={idx} style={styles.listItem}>
-              {block.ordered ? `${idx + 1}. ` : '• '}{item}
-            </Text>
-          ))}
-        </View>
-      );
+      return renderList(block.ordered, block.items);
 
     case 'table':
+      return renderTable(block);
+
+    case 'footnote':
+      return <Text style={styles.footnote}>{block.text}</Text>;
+
+    case 'quote':
       return (
-        <View style={styles.table}>
-          <View style={styles.tableHeader}>
-            {block.columns.map((col: any) => (
-              <Text key={col.id} style={styles.tableHeaderCell}>
-                {col.header}
-              </Text>
-            ))}
-          </View>
-          {block.rows.map((row: any, rowIdx: number) => (
-            <View key={rowIdx} style={styles.tableRow}>
-              {block.columns.map((col: any) => (
-                <Text key={col.id} style={styles.tableCell}>
-                  {row[col.id]?.display || row[col.id]?.value || ''}
-                </Text>
-              ))}
-            </View>
-          ))}
+        <View style={styles.quote}>
+          <Text style={styles.quoteText}>{inlinePlainText(block.text)}</Text>
+          {block.attribution && <Text style={styles.footnote}>— {block.attribution}</Text>}
         </View>
       );
 
-    case 'footnote':
-      return <Text style={styles.footnote}>{block.text}</Text>;
+    case 'code':
+      return <Text style={styles.code}>{block.text}</Text>;
+
+    case 'page_break':
+    case 'horizontal_rule':
+      return <View style={styles.rule} />;
 
     default:
       return <Text style={styles.unknown}>[Unsupported block type: {block.type}]</Text>;
   }
 }
 
+/**
+ * Flex rows cannot span rows, so a column span widens its cell and the
+ * cells below a row span are left blank
+ */
+function renderTable(table: TableBlock) {
+  const covered = coveredCells(table);
+  // Flex width of each cell drawn under a span: the anchor's colspan
+  const widths = new Map<string, number>();
+  table.rows.forEach((row, r) => {
+    table.columns.forEach((col, c) => {
+      const span = row._row?.spans?.[col.id];
+      if (!span) return;
+      for (let dr = 0; dr < (span.rowspan ?? 1); dr++) {
+        widths.set(`${r + dr},${c}`, span.colspan ?? 1);
+      }
+    });
+  });
+  const layout = headerLayout(table);
+  return (
+    <View style={styles.table}>
+      {layout.map((slots, depth) => (
+        <View key={`h${depth}`} style={styles.tableHeader}>
+          {table.columns.map((_, c) => {
+            const slot = slots.find((s) => s.column === c);
+            const inside = slots.some((s) => c > s.column && c < s.column + s.colspan);
+            if (inside) return null;
+            // Tall headers are drawn in the first header row only
+            const text = slot && (slot.rowspan === 1 || depth === 0) ? slot.text : '';
+            return (
+              <Text key={c} style={[styles.tableHeaderCell, { flex: slot?.colspan ?? 1 }]}>
+                {text}
+              </Text>
+            );
+          })}
+        </View>
+      ))}
+      {table.rows.map((row: any, rowIdx: number) => {
+        const format = row._row;
+        const strong = format?.role === 'subtotal' || format?.role === 'total';
+        return (
+          <View key={rowIdx} style={[styles.tableRow, format?.role === 'total' && styles.tableTotalRow]}>
+            {table.columns.map((col, c) => {
+              const key = `${rowIdx},${c}`;
+              const width = widths.get(key);
+              // Cells to the right of a span are part of the spanning cell
+              if (covered.has(key) && width === undefined) return null;
+              const cell = covered.has(key) ? undefined : row[col.id];
+              return (
+                <Text
+                  key={col.id}
+                  style={[
+                    styles.tableCell,
+                    { flex: width ?? 1 },
+                    strong && styles.tableStrongCell,
+                    c === 0 && format?.level ? { paddingLeft: 8 + format.level * 12 } : null,
+                  ]}
+                >
+                  {cell?.display || cell?.value || ''}
+                </Text>
+              );
+            })}
+          </View>
+        );
+      })}
+    </View>
+  );
+}
+
+function renderList(ordered: boolean, items: ListItem[]): React.ReactNode {
+  return (
+    <View style={styles.list}>
+      {items.map((item: ListItem, idx: number) => {
+        const { text, checked, sublist } = listItemParts(item);
+        const marker = checked !== undefined ? (checked ? '☒ ' : '☐ ') : ordered ? `${idx + 1}. ` : '• ';
+        return (
+          <View key={idx}>
+            <Text style={styles.listItem}>
+              {marker}{inlinePlainText(text)}
+            </Text>
+            {sublist && renderList(sublist.ordered, sublist.items)}
+          </View>
+        );
+      })}
+    </View>
+  );
+}
+
 const styles = StyleSheet.create({
   container: {
     backgroundColor: 'white',
@@ -267,6 +342,13 @@ const styles = StyleSheet.create({
     borderRightWidth: 1,
     borderRightColor: '#e0e0e0',
   },
+  tableStrongCell: {
+    fontWeight: 'bold',
+  },
+  tableTotalRow: {
+    borderTopWidth: 2,
+    borderTopColor: '#333',
+  },
   footnote: {
     fontSize: 12,
     color: '#666',
@@ -274,6 +356,30 @@ const styles = StyleSheet.create({
     marginLeft: 16,
     marginTop: 4,
   },
+  quote: {
+    borderLeftWidth: 3,
+    borderLeftColor: '#ccc',
+    paddingLeft: 12,
+    marginBottom: 12,
+  },
+  quoteText: {
+    fontSize: 14,
+    lineHeight: 20,
+    color: '#444',
+    fontStyle: 'italic',
+  },
+  code: {
+    fontFamily: 'monospace',
+    fontSize: 12,
+    backgroundColor: '#f5f5f5',
+    padding: 8,
+    marginBottom: 12,
+  },
+  rule: {
+    borderBottomWidth: 1,
+    borderBottomColor: '#ddd',
+    marginVertical: 12,
+  },
   unknown: {
     fontSize: 12,
     color: '#999',
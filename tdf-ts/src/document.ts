@@ -13,9 +13,59 @@ export interface Manifest {
   document: DocumentMeta;
   authors: Author[];
   classification?: Classification;
+  encryption?: EncryptionInfo;
+  redaction?: RedactionInfo;
+  revision?: RevisionInfo;
+  xbrl?: XbrlInfo;
   integrity: IntegrityBlock;
 }
 
+/** XBRL taxonomy the document's tagged facts report against */
+export interface XbrlInfo {
+  schema_refs?: string[];
+  /** Namespace URI of each concept prefix */
+  namespaces: { [prefix: string]: string };
+  /** Entity facts report on unless their tag names one */
+  entity?: XbrlEntity;
+}
+
+export interface XbrlEntity {
+  scheme: string;
+  identifier: string;
+}
+
+export type XbrlPeriod = { instant: string } | { start: string; end: string };
+
+/** Taxonomy concept a cell or paragraph reports */
+export interface XbrlTag {
+  /** Prefixed concept name, e.g. `ifrs-full:Revenue` */
+  concept: string;
+  period: XbrlPeriod;
+  entity?: XbrlEntity;
+  /** Unit measure(s), e.g. `iso4217:EUR` or `iso4217:EUR/xbrli:shares` */
+  unit?: string;
+  /** Accuracy in decimal places (`-3` for thousands); exact when absent */
+  decimals?: number;
+}
+
+/** Link to the revision this document supersedes */
+export interface RevisionInfo {
+  number: number;
+  previous_id: string;
+  previous_root_hash: string;
+  reason: string;
+}
+
+/** Set when blocks and cells carry salted commitments and can be redacted */
+export interface RedactionInfo {
+  version: number;
+}
+
+export interface EncryptionInfo {
+  version: number;
+  cipher: 'aes-256-gcm' | 'xchacha20-poly1305';
+}
+
 export interface DocumentMeta {
   id: string;
   title: string;
@@ -45,6 +95,15 @@ export interface Section {
   id: string;
   title?: string;
   content: ContentBlock[];
+  encrypted?: EncryptedSection;
+}
+
+/** Ciphertext of a section encrypted for an audience group */
+export interface EncryptedSection {
+  group: string;
+  cipher: 'aes-256-gcm' | 'xchacha20-poly1305';
+  recipients: unknown[];
+  ciphertext: string;
 }
 
 export type ContentBlock =
@@ -53,38 +112,94 @@ export type ContentBlock =
   | ListBlock
   | TableBlock
   | DiagramBlock
+  | ChartBlock
   | FigureBlock
-  | FootnoteBlock;
+  | FootnoteBlock
+  | QuoteBlock
+  | CodeBlock
+  | PageBreakBlock
+  | HorizontalRuleBlock
+  | RedactedBlock
+  | FormFieldBlock;
 
 export interface HeadingBlock {
   type: 'heading';
   level: number;
-  text: string;
+  text: InlineText;
   id?: string;
 }
 
 export interface ParagraphBlock {
   type: 'paragraph';
-  text: string;
+  text: InlineText;
   id?: string;
+  xbrl?: XbrlTag;
 }
 
 export interface ListBlock {
   type: 'list';
   ordered: boolean;
-  items: string[];
+  items: ListItem[];
   id?: string;
 }
 
+/** Plain items are bare text; task items and items with a sublist are objects */
+export type ListItem = InlineText | { text: InlineText; checked?: boolean; sublist?: NestedList };
+
+export interface NestedList {
+  ordered: boolean;
+  items: ListItem[];
+}
+
+/** Normalize a list item to its object form */
+export function listItemParts(item: ListItem): { text: InlineText; checked?: boolean; sublist?: NestedList } {
+  return typeof item === 'object' && !Array.isArray(item) ? item : { text: item };
+}
+
+/** Plain text, or runs when the text has marks, links or references */
+export type InlineText = string | InlineRun[];
+
+export type InlineRun =
+  | { type: 'text'; text: string; marks?: Mark[]; link?: string }
+  | { type: 'footnote_ref'; id: string }
+  | { type: 'xref'; target: string; text: string };
+
+export type Mark = 'bold' | 'italic' | 'underline' | 'strike' | 'code' | 'superscript' | 'subscript';
+
+/** Text without formatting; footnote references become `{{id}}` markers */
+export function inlinePlainText(text: InlineText): string {
+  if (typeof text === 'string') {
+    return text;
+  }
+  return text
+    .map((run) => {
+      switch (run.type) {
+        case 'text':
+        case 'xref':
+          return run.text;
+        case 'footnote_ref':
+          return `{{${run.id}}}`;
+      }
+    })
+    .join('');
+}
+
 export interface TableBlock {
   type: 'table';
   id: string;
   caption?: string;
   columns: TableColumn[];
+  /** Group header rows drawn above the column headers */
+  header_rows?: HeaderCell[][];
   rows: TableRow[];
   footer?: string[];
 }
 
+export interface HeaderCell {
+  text: string;
+  colspan?: number;
+}
+
 export interface TableColumn {
   id: string;
   header: string;
@@ -92,18 +207,97 @@ export interface TableColumn {
   currency?: string;
 }
 
-export interface TableRow {
-  [key: string]: CellValue;
+/** Cells by column id; `_row` holds the row's role, outline level, spans and XBRL tags */
+export type TableRow = { [key: string]: CellValue } & { _row?: RowFormat };
+
+export interface RowFormat {
+  role?: 'data' | 'subtotal' | 'total';
+  level?: number;
+  spans?: { [column: string]: CellSpan };
+  tags?: { [column: string]: XbrlTag };
+}
+
+export interface CellSpan {
+  colspan?: number;
+  rowspan?: number;
+}
+
+export interface HeaderSlot {
+  text: string;
+  column: number;
+  colspan: number;
+  rowspan: number;
+}
+
+/** Positions `"row,column"` covered by another cell's span */
+export function coveredCells(table: TableBlock): Set<string> {
+  const covered = new Set<string>();
+  table.rows.forEach((row, r) => {
+    table.columns.forEach((column, c) => {
+      const span = row._row?.spans?.[column.id];
+      for (let dr = 0; dr < (span?.rowspan ?? 1); dr++) {
+        for (let dc = 0; dc < (span?.colspan ?? 1); dc++) {
+          if (dr + dc > 0) covered.add(`${r + dr},${c + dc}`);
+        }
+      }
+    });
+  });
+  return covered;
+}
+
+/**
+ * Header cells to draw, row by row, with the column headers last.
+ * A column whose group cells are all empty gets one header cell spanning
+ * every header row.
+ */
+export function headerLayout(table: TableBlock): HeaderSlot[][] {
+  const groups = (table.header_rows ?? []).map((row) => {
+    let column = 0;
+    return row.map((cell) => {
+      const slot = { text: cell.text, column, colspan: cell.colspan ?? 1, rowspan: 1 };
+      column += slot.colspan;
+      return slot;
+    });
+  });
+  const tall = table.columns.map((_, c) =>
+    groups.every((row) => {
+      const cell = row.find((slot) => c >= slot.column && c < slot.column + slot.colspan);
+      return !cell || (cell.colspan === 1 && cell.text === '');
+    })
+  );
+  const layout = groups.map((row, depth) =>
+    row.flatMap((slot) => {
+      if (slot.colspan === 1 && tall[slot.column]) {
+        return depth === 0 ? [{ ...slot, text: table.columns[slot.column].header, rowspan: groups.length + 1 }] : [];
+      }
+      return [slot];
+    })
+  );
+  layout.push(
+    table.columns
+      .map((column, c) => ({ text: column.header, column: c, colspan: 1, rowspan: 1 }))
+      .filter((slot) => groups.length === 0 || !tall[slot.column])
+  );
+  return layout;
 }
 
 export type CellType = 'text' | 'number' | 'currency' | 'percentage' | 'date' | 'formula';
 
+/**
+ * Exact decimal amount written as a string (`"1200.50"`). Documents from
+ * before amounts were exact hold a number instead.
+ */
+export type DecimalValue = string | number;
+
 export type CellValue =
   | { type: 'text'; value: string }
-  | { type: 'number'; raw: number; display: string }
-  | { type: 'currency'; raw: number; display: string; currency: string }
-  | { type: 'percentage'; raw: number; display: string }
-  | { type: 'date'; raw: string; display: string };
+  /** Expression over cells of the same table, with its computed value */
+  | { formula: string; raw: DecimalValue; display: string }
+  | { type: 'number'; raw: DecimalValue; display: string }
+  | { type: 'currency'; raw: DecimalValue; display: string; currency: string }
+  | { type: 'percentage'; raw: DecimalValue; display: string }
+  | { type: 'date'; raw: string; display: string }
+  | { redacted: string };
 
 export interface DiagramBlock {
   type: 'diagram';
@@ -134,6 +328,41 @@ export interface DiagramLayout {
   spacing?: 'compact' | 'normal' | 'wide';
 }
 
+/** Chart drawn from the data rows of a table in the same document */
+export interface ChartBlock {
+  type: 'chart';
+  id: string;
+  chart_type: 'bar' | 'line' | 'pie';
+  title?: string;
+  /** Id of the source table */
+  table: string;
+  /** Column holding the category labels */
+  category: string;
+  series: ChartSeries[];
+  axes?: ChartAxes;
+  format?: ChartFormat;
+}
+
+export interface ChartSeries {
+  column: string;
+  label?: string;
+  /** Colour as `#rrggbb` */
+  color?: string;
+}
+
+export interface ChartAxes {
+  x_label?: string;
+  y_label?: string;
+  min?: DecimalValue;
+  max?: DecimalValue;
+}
+
+export interface ChartFormat {
+  stacked?: boolean;
+  legend?: boolean;
+  data_labels?: boolean;
+}
+
 export interface FigureBlock {
   type: 'figure';
   id: string;
@@ -149,6 +378,108 @@ export interface FootnoteBlock {
   text: string;
 }
 
+export interface QuoteBlock {
+  type: 'quote';
+  text: InlineText;
+  attribution?: string;
+  id?: string;
+}
+
+export interface CodeBlock {
+  type: 'code';
+  language?: string;
+  text: string;
+  id?: string;
+}
+
+export interface PageBreakBlock {
+  type: 'page_break';
+}
+
+export interface HorizontalRuleBlock {
+  type: 'horizontal_rule';
+}
+
+/** Block removed after signing; the commitment keeps the signature valid */
+export interface RedactedBlock {
+  type: 'redacted';
+  id: string;
+  commitment: string;
+}
+
+/** Form field of a template; its value is kept in a submission */
+export type FormFieldBlock =
+  | TextFieldBlock
+  | NumberFieldBlock
+  | DateFieldBlock
+  | CheckboxBlock
+  | ChoiceBlock
+  | SignatureFieldBlock;
+
+interface FieldBase {
+  id: string;
+  label: string;
+  required?: boolean;
+  help?: string;
+}
+
+export interface TextFieldBlock extends FieldBase {
+  type: 'text_field';
+  multiline?: boolean;
+  max_length?: number;
+  /** Regular expression the whole value must match */
+  pattern?: string;
+}
+
+export interface NumberFieldBlock extends FieldBase {
+  type: 'number_field';
+  min?: DecimalValue;
+  max?: DecimalValue;
+  decimals?: number;
+  /** ISO 4217 code of an amount */
+  currency?: string;
+}
+
+export interface DateFieldBlock extends FieldBase {
+  type: 'date_field';
+  /** `YYYY-MM-DD` */
+  min?: string;
+  max?: string;
+}
+
+export interface CheckboxBlock extends FieldBase {
+  type: 'checkbox';
+}
+
+export interface ChoiceBlock extends FieldBase {
+  type: 'choice';
+  options: ChoiceOption[];
+  multiple?: boolean;
+}
+
+export interface ChoiceOption {
+  value: string;
+  label?: string;
+}
+
+/** Filled by signing the submission */
+export interface SignatureFieldBlock extends FieldBase {
+  type: 'signature_field';
+  role?: string;
+}
+
+/** Values a counterparty entered, stored as `submissions/<id>.cbor` */
+export interface Submission {
+  version: number;
+  id: string;
+  template: { id: string; root_hash: string };
+  submitted: string;
+  /** Numbers and dates as strings, multiple choices as arrays */
+  values: Record<string, string | boolean | string[]>;
+  /** Filler's signature, shaped like an entry of `signatures.cbor` */
+  signature?: any;
+}
+
 export interface Layout {
   version: number;
   pages: PageLayout;
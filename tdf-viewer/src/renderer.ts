@@ -1,4 +1,4 @@
-import { TdfDocument, ContentBlock, TableBlock, DiagramBlock } from 'tdf-ts';
+import { TdfDocument, ContentBlock, TableBlock, InlineText, ListItem, listItemParts, headerLayout, coveredCells, Mark, FormFieldBlock, DiagramBlock } from 'tdf-ts';
 import { renderDiagram } from './diagram';
 
 export function renderDocument(doc: TdfDocument, container: HTMLElement) {
@@ -61,6 +61,13 @@ export function renderDocument(doc: TdfDocument, container: HTMLElement) {
             sectionEl.appendChild(sectionTitle);
         }
 
+        if (section.encrypted) {
+            const placeholder = document.createElement('p');
+            placeholder.className = 'encrypted-section';
+            placeholder.textContent = `Encrypted section for ${section.encrypted.group}`;
+            sectionEl.appendChild(placeholder);
+        }
+
         for (const block of section.content) {
             const blockEl = renderBlock(block);
             sectionEl.appendChild(blockEl);
@@ -85,32 +92,70 @@ function renderBlock(block: ContentBlock): HTMLElement {
     switch (block.type) {
         case 'heading':
             const h = document.createElement(`h${block.level}`);
-            h.textContent = block.text;
+            renderInline(h, block.text);
             if (block.id) h.id = block.id;
             return h;
 
         case 'paragraph':
             const p = document.createElement('p');
-            p.textContent = block.text;
+            renderInline(p, block.text);
             if (block.id) p.id = block.id;
             return p;
 
         case 'list':
-            const list = document.createElement(block.ordered ? 'ol' : 'ul');
-            for (const item of block.items) {
-                const li = document.createElement('li');
-                li.textContent = item;
-                list.appendChild(li);
-            }
+            const list = renderList(block.ordered, block.items);
             if (block.id) list.id = block.id;
             return list;
 
+        case 'quote':
+            const quote = document.createElement('blockquote');
+            const quoteText = document.createElement('p');
+            renderInline(quoteText, block.text);
+            quote.appendChild(quoteText);
+            if (block.attribution) {
+                const attribution = document.createElement('footer');
+                attribution.textContent = `\u2014 ${block.attribution}`;
+                quote.appendChild(attribution);
+            }
+            if (block.id) quote.id = block.id;
+            return quote;
+
+        case 'code':
+            const pre = document.createElement('pre');
+            const code = document.createElement('code');
+            if (block.language) code.className = `language-${block.language}`;
+            code.textContent = block.text;
+            pre.appendChild(code);
+            if (block.id) pre.id = block.id;
+            return pre;
+
+        case 'page_break':
+            const pageBreak = document.createElement('div');
+            pageBreak.className = 'page-break';
+            return pageBreak;
+
+        case 'horizontal_rule':
+            return document.createElement('hr');
+
         case 'table':
             return renderTable(block);
 
         case 'diagram':
             return renderDiagram(block);
 
+        case 'chart':
+            const chart = document.createElement('figure');
+            chart.className = 'chart-container';
+            chart.id = block.id;
+            const chartCaption = document.createElement('figcaption');
+            chartCaption.textContent = block.title ?? `${block.chart_type} chart`;
+            const source = document.createElement('a');
+            source.href = `#${block.table}`;
+            source.textContent = ` (data: ${block.table})`;
+            chartCaption.appendChild(source);
+            chart.appendChild(chartCaption);
+            return chart;
+
         case 'figure':
             const figure = document.createElement('figure');
             const img = document.createElement('img');
@@ -133,6 +178,20 @@ function renderBlock(block: ContentBlock): HTMLElement {
             fn.textContent = block.text;
             return fn;
 
+        case 'redacted':
+            const redacted = document.createElement('div');
+            redacted.className = 'redacted-block';
+            redacted.textContent = `Redacted (${block.id})`;
+            return redacted;
+
+        case 'text_field':
+        case 'number_field':
+        case 'date_field':
+        case 'checkbox':
+        case 'choice':
+        case 'signature_field':
+            return renderField(block);
+
         default:
             const div = document.createElement('div');
             div.textContent = JSON.stringify(block);
@@ -140,41 +199,184 @@ function renderBlock(block: ContentBlock): HTMLElement {
     }
 }
 
+/** Empty, read-only form field; its values are kept in submissions */
+function renderField(block: FormFieldBlock): HTMLElement {
+    const field = document.createElement('div');
+    field.className = 'form-field';
+    field.id = block.id;
+    const label = document.createElement('span');
+    label.className = 'field-label';
+    label.textContent = block.label;
+    if (block.required) {
+        const mark = document.createElement('span');
+        mark.className = 'field-required';
+        mark.textContent = '*';
+        label.appendChild(mark);
+    }
+    field.appendChild(label);
+    if (block.type === 'checkbox') {
+        const box = document.createElement('input');
+        box.type = 'checkbox';
+        box.disabled = true;
+        field.appendChild(box);
+    } else {
+        const input = document.createElement('span');
+        input.className = 'field-input';
+        if (block.type === 'text_field' && block.multiline) input.classList.add('multiline');
+        if (block.type === 'signature_field') input.classList.add('signature');
+        field.appendChild(input);
+    }
+    if (block.type === 'choice') {
+        const hint = document.createElement('span');
+        hint.className = 'field-hint';
+        hint.textContent = `${block.multiple ? 'Any of' : 'One of'}: ${block.options.map((o) => o.label ?? o.value).join(', ')}`;
+        field.appendChild(hint);
+    }
+    if (block.help) {
+        const help = document.createElement('span');
+        help.className = 'field-help';
+        help.textContent = block.help;
+        field.appendChild(help);
+    }
+    return field;
+}
+
+function renderList(ordered: boolean, items: ListItem[]): HTMLElement {
+    const list = document.createElement(ordered ? 'ol' : 'ul');
+    for (const item of items) {
+        const { text, checked, sublist } = listItemParts(item);
+        const li = document.createElement('li');
+        if (checked !== undefined) {
+            const box = document.createElement('input');
+            box.type = 'checkbox';
+            box.checked = checked;
+            box.disabled = true;
+            li.className = 'task';
+            li.appendChild(box);
+        }
+        renderInline(li, text);
+        if (sublist) li.appendChild(renderList(sublist.ordered, sublist.items));
+        list.appendChild(li);
+    }
+    return list;
+}
+
+const MARK_TAGS: Record<Mark, string> = {
+    bold: 'strong',
+    italic: 'em',
+    underline: 'u',
+    strike: 's',
+    code: 'code',
+    superscript: 'sup',
+    subscript: 'sub',
+};
+
+function isSafeLink(href: string): boolean {
+    // Parse the scheme the way the browser's URL parser does: leading and
+    // trailing controls or spaces are dropped, and tabs and newlines anywhere
+    // are removed, so `" java\tscript:"` still names `javascript`
+    const url = href.replace(/^[\x00-\x20]+|[\x00-\x20]+$/g, '').replace(/[\t\n\r]/g, '');
+    const schemeEnd = url.search(/[:/?#]/);
+    if (schemeEnd !== -1 && url[schemeEnd] === ':') {
+        return ['http', 'https', 'mailto', 'tel'].includes(url.slice(0, schemeEnd).toLowerCase());
+    }
+    return url.length > 0;
+}
+
+function renderInline(parent: HTMLElement, text: InlineText) {
+    if (typeof text === 'string') {
+        parent.textContent = text;
+        return;
+    }
+    for (const run of text) {
+        switch (run.type) {
+            case 'text': {
+                let node: Node = document.createTextNode(run.text);
+                for (const mark of [...(run.marks ?? [])].reverse()) {
+                    const el = document.createElement(MARK_TAGS[mark]);
+                    el.appendChild(node);
+                    node = el;
+                }
+                if (run.link && isSafeLink(run.link)) {
+                    const a = document.createElement('a');
+                    a.href = run.link;
+                    a.rel = 'noopener noreferrer';
+                    a.appendChild(node);
+                    node = a;
+                }
+                parent.appendChild(node);
+                break;
+            }
+            case 'footnote_ref': {
+                const sup = document.createElement('sup');
+                const a = document.createElement('a');
+                a.href = `#${run.id}`;
+                a.textContent = run.id;
+                sup.appendChild(a);
+                parent.appendChild(sup);
+                break;
+            }
+            case 'xref': {
+                const a = document.createElement('a');
+                a.href = `#${run.target}`;
+                a.textContent = run.text;
+                parent.appendChild(a);
+                break;
+            }
+        }
+    }
+}
+
 function renderTable(table: TableBlock): HTMLElement {
     const tableEl = document.createElement('table');
     // Use both classes for compatibility
     tableEl.className = 'tdf-table';
     if (table.id) tableEl.id = table.id;
 
-    // Header
+    // Header: group rows, then the column headers
     const thead = document.createElement('thead');
-    const headerRow = document.createElement('tr');
-    for (const col of table.columns) {
-        const th = document.createElement('th');
-        th.textContent = col.header;
-        headerRow.appendChild(th);
+    for (const slots of headerLayout(table)) {
+        const headerRow = document.createElement('tr');
+        for (const slot of slots) {
+            const th = document.createElement('th');
+            th.textContent = slot.text;
+            if (slot.colspan > 1) th.colSpan = slot.colspan;
+            if (slot.rowspan > 1) th.rowSpan = slot.rowspan;
+            headerRow.appendChild(th);
+        }
+        thead.appendChild(headerRow);
     }
-    thead.appendChild(headerRow);
     tableEl.appendChild(thead);
 
-    // Body
+    // Body, skipping cells covered by another cell's span
+    const covered = coveredCells(table);
     const tbody = document.createElement('tbody');
-    for (const row of table.rows) {
+    table.rows.forEach((row, r) => {
         const tr = document.createElement('tr');
-        for (const col of table.columns) {
+        const format = row._row;
+        if (format?.role && format.role !== 'data') tr.className = format.role;
+        table.columns.forEach((col, c) => {
+            if (covered.has(`${r},${c}`)) return;
             const td = document.createElement('td');
+            const span = format?.spans?.[col.id];
+            if (span?.colspan && span.colspan > 1) td.colSpan = span.colspan;
+            if (span?.rowspan && span.rowspan > 1) td.rowSpan = span.rowspan;
+            if (c === 0 && format?.level) td.classList.add(`level-${format.level}`);
             const cell = row[col.id];
             if (cell) {
-                if ('display' in cell) {
+                if ('redacted' in cell) {
+                    td.classList.add('redacted-cell');
+                    td.textContent = 'Redacted';
+                } else if ('display' in cell) {
                     td.textContent = (cell as any).display;
                 } else if ('value' in cell) {
                     td.textContent = (cell as any).value;
                 }
             }
             tr.appendChild(td);
-        }
+        });
         tbody.appendChild(tr);
-    }
+    });
     tableEl.appendChild(tbody);
 
     // Footer
@@ -208,7 +410,22 @@ function getDefaultStyles(): string {
         .tdf-table { border-collapse: collapse; width: 100%; margin: 1em 0; }
         .tdf-table th, .tdf-table td { padding: 8px; border: 1px solid #ddd; }
         .tdf-table th { background-color: #f5f5f5; font-weight: bold; }
+        .tdf-table th[colspan] { text-align: center; }
+        .tdf-table tr.subtotal td, .tdf-table tr.total td { font-weight: bold; }
+        .tdf-table tr.total td { border-top: 2px solid #333; }
+        ${[1, 2, 3, 4, 5, 6, 7].map((l) => `.tdf-table td.level-${l} { padding-left: ${8 + l * 16}px; }`).join('\n        ')}
         .footnote { font-size: 0.9em; color: #666; margin-top: 0.5em; }
+        blockquote { margin: 1em 0; padding-left: 1em; border-left: 3px solid #ccc; color: #444; }
+        pre { background: #f5f5f5; padding: 8px; overflow-x: auto; }
+        li.task { list-style: none; }
+        .form-field { margin: 0.8em 0; }
+        .form-field .field-label { display: block; font-weight: bold; }
+        .form-field .field-required { color: #c62828; margin-left: 0.2em; }
+        .form-field .field-input { display: block; min-height: 1.6em; border-bottom: 1px solid #888; }
+        .form-field .field-input.multiline { min-height: 4.8em; border: 1px solid #888; }
+        .form-field .field-input.signature { min-height: 3em; }
+        .form-field .field-hint, .form-field .field-help { display: block; font-size: 0.85em; color: #666; }
+        .page-break { break-after: page; }
     `;
 }
 
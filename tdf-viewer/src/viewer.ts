@@ -71,7 +71,8 @@ verifyBtn.addEventListener('click', async () => {
             const { verifyDocument } = await import('./verification');
             const result = await verifyDocument(fileInput.files[0]);
             
-            if (result.integrityValid) {
+            // Errors with valid integrity are formula totals that do not add up
+            if (result.integrityValid && result.errors.length === 0) {
                 let message = `✓ Document structure valid. Root hash: ${result.rootHash}`;
                 if (result.signatureCount > 0) {
                     message += ` (${result.signatureCount} signature${result.signatureCount > 1 ? 's' : ''})`;
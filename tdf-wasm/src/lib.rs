@@ -139,8 +139,28 @@ pub fn verify_document(data: &[u8]) -> Result<VerificationResult, JsValue> {
         bytes
     };
 
+    // Plain (unencrypted) content, for re-computing formulas
+    let content: Option<tdf_core::content::DocumentContent> = serde_cbor::from_slice(&content_bytes).ok();
+
+    // Redactable content is hashed as its skeleton of block commitments
+    let content_leaf = if manifest.redaction.is_some() && manifest.encryption.is_none() {
+        let mut salts_bytes = Vec::new();
+        let mut salts_file = zip.by_name(tdf_core::redaction::REDACTION_FILE)
+            .map_err(|_| JsValue::from_str("Missing redaction.cbor"))?;
+        std::io::Read::read_to_end(&mut salts_file, &mut salts_bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read redaction salts: {}", e)))?;
+        let salts: tdf_core::redaction::RedactionSalts = serde_cbor::from_slice(&salts_bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse redaction salts: {}", e)))?;
+        tdf_core::redaction::content_value(&content_bytes)
+            .and_then(|content| tdf_core::redaction::content_commitment(&content, &salts))
+            .map(|commitment| commitment.leaf)
+            .unwrap_or(content_bytes)
+    } else {
+        content_bytes
+    };
+
     components.insert("manifest".to_string(), manifest_bytes_for_hash);
-    components.insert("content".to_string(), content_bytes);
+    components.insert("content".to_string(), content_leaf);
     components.insert("styles".to_string(), styles_bytes);
 
     // Read optional components
@@ -202,15 +222,31 @@ pub fn verify_document(data: &[u8]) -> Result<VerificationResult, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Verification error: {}", e)))?;
     let root_hash = hex::encode(merkle_tree.root_hash());
 
+    let mut errors = Vec::new();
+    if !integrity_valid {
+        errors.push("Integrity check failed - document may have been tampered with".to_string());
+    }
+    // Computed totals are re-checked against their formulas
+    if let Some(content) = &content {
+        for check in tdf_core::formula::verify_formulas(content) {
+            match &check.outcome {
+                tdf_core::formula::FormulaOutcome::Mismatch { computed } => errors.push(format!(
+                    "Formula {} shows {} but computes {}",
+                    check.key(), check.display, computed
+                )),
+                tdf_core::formula::FormulaOutcome::Error { message } => {
+                    errors.push(format!("Formula {} cannot be evaluated: {}", check.key(), message))
+                }
+                _ => {}
+            }
+        }
+    }
+
     Ok(VerificationResult {
         integrity_valid,
         root_hash,
         signature_count: signature_block.signatures.len(),
-        errors: if integrity_valid {
-            vec![]
-        } else {
-            vec!["Integrity check failed - document may have been tampered with".to_string()]
-        },
+        errors,
     })
 }
 
@@ -280,3 +316,93 @@ pub fn compute_merkle_root(components: js_sys::Map) -> Result<String, JsValue> {
     Ok(hex::encode(root))
 }
 
+
+/// Render the body of a document as HTML, exactly as `tdf export --format html` does
+///
+/// A self-verifying HTML export calls this on its embedded archive after
+/// `verify_document`, so the content it shows comes from the verified bytes.
+#[wasm_bindgen]
+pub fn render_html(data: &[u8]) -> Result<String, JsValue> {
+    use std::io::Cursor;
+    use zip::ZipArchive;
+
+    let mut zip = ZipArchive::new(Cursor::new(data))
+        .map_err(|e| JsValue::from_str(&format!("Failed to open ZIP archive: {}", e)))?;
+    let mut read = |name: &str| -> Result<Vec<u8>, JsValue> {
+        let mut file = zip.by_name(name)
+            .map_err(|_| JsValue::from_str(&format!("Missing {}", name)))?;
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut file, &mut bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read {}: {}", name, e)))?;
+        Ok(bytes)
+    };
+
+    let manifest: tdf_core::document::Manifest = serde_cbor::from_slice(&read("manifest.cbor")?)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse manifest: {}", e)))?;
+    if manifest.encryption.is_some() {
+        return Err(JsValue::from_str("Document is encrypted"));
+    }
+    let content: tdf_core::content::DocumentContent = serde_cbor::from_slice(&read("content.cbor")?)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse content: {}", e)))?;
+    let styles = String::from_utf8_lossy(&read("styles.css")?).to_string();
+
+    let names: Vec<String> = zip
+        .file_names()
+        .filter(|name| name.starts_with("assets/") && !name.ends_with('/'))
+        .map(str::to_string)
+        .collect();
+    let mut assets = std::collections::HashMap::new();
+    for name in names {
+        let mut file = zip.by_name(&name)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read asset {}: {}", name, e)))?;
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut file, &mut bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read asset {}: {}", name, e)))?;
+        assets.insert(name, bytes);
+    }
+
+    let document = tdf_core::document::Document { manifest, content, styles, layout: None, data: None };
+    Ok(tdf_core::html::render_body(&document, &assets))
+}
+
+/// Render a `diagram` content block as SVG, with the layout the exporters use
+///
+/// Takes the block as a plain object (`{ type: "diagram", id, diagram_type,
+/// nodes, edges, ... }`), so the viewer can draw diagrams the same way the
+/// PDF and HTML exports do.
+#[wasm_bindgen]
+pub fn render_diagram_svg(block: JsValue) -> Result<String, JsValue> {
+    let block: tdf_core::content::ContentBlock = serde_wasm_bindgen::from_value(block)
+        .map_err(|e| JsValue::from_str(&format!("Invalid diagram block: {}", e)))?;
+    match block {
+        tdf_core::content::ContentBlock::Diagram { id, diagram_type, title, nodes, edges, layout } => Ok(
+            tdf_core::diagram::diagram_svg(&id, &diagram_type, title.as_deref(), &nodes, &edges, layout.as_ref()),
+        ),
+        other => Err(JsValue::from_str(&format!("Expected a diagram block, got '{}'", other.type_name()))),
+    }
+}
+
+/// Render the `chart` block with the given id as SVG, from its table's data
+///
+/// Takes the whole document content, since a chart draws the rows of a table
+/// elsewhere in the document. Fails with the reason when the chart does not
+/// exist or its source table cannot be read.
+#[wasm_bindgen]
+pub fn render_chart_svg(content: JsValue, id: &str) -> Result<String, JsValue> {
+    let content: tdf_core::content::DocumentContent = serde_wasm_bindgen::from_value(content)
+        .map_err(|e| JsValue::from_str(&format!("Invalid content: {}", e)))?;
+    let block = content
+        .sections
+        .iter()
+        .flat_map(|section| section.content.iter())
+        .find(|block| block.id() == Some(id))
+        .ok_or_else(|| JsValue::from_str(&format!("No block with id '{}'", id)))?;
+    match block {
+        tdf_core::content::ContentBlock::Chart { chart_type, title, table, category, series, axes, format, .. } => {
+            let data = tdf_core::chart::resolve(&content, table, category, series).map_err(|e| JsValue::from_str(&e))?;
+            let geometry = tdf_core::chart::layout(*chart_type, title.as_deref(), &data, axes.as_ref(), format.as_ref());
+            Ok(tdf_core::chart::render_svg(&geometry, title.as_deref()))
+        }
+        other => Err(JsValue::from_str(&format!("Expected a chart block, got '{}'", other.type_name()))),
+    }
+}